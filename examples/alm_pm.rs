@@ -46,12 +46,12 @@ fn main() {
     let n1 = 2;
     let n2 = 0;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
-    let set_c = Ball2::new(None, 0.5);
-    let bounds = Ball2::new(None, 10.0);
-    let set_y = Ball2::new(None, 1e12);
+    let set_c = Ball2::new(None, 0.5).unwrap();
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let set_y = Ball2::new(None, 1e12).unwrap();
 
     let factory = AlmFactory::new(
         f,
@@ -62,7 +62,8 @@ fn main() {
         NO_JACOBIAN_MAPPING,
         Some(set_c),
         n2,
-    );
+    )
+    .unwrap();
 
     let alm_problem = AlmProblem::new(
         bounds,
@@ -78,18 +79,28 @@ fn main() {
         NO_MAPPING,
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-5)
+        .unwrap()
         .with_max_outer_iterations(20)
+        .unwrap()
         .with_epsilon_tolerance(1e-6)
+        .unwrap()
         .with_initial_inner_tolerance(1e-2)
+        .unwrap()
         .with_inner_tolerance_update_factor(0.5)
+        .unwrap()
         .with_initial_penalty(100.0)
+        .unwrap()
         .with_penalty_update_factor(1.05)
+        .unwrap()
         .with_sufficient_decrease_coefficient(0.2)
-        .with_initial_lagrange_multipliers(&vec![5.0; n1]);
+        .unwrap()
+        .with_initial_lagrange_multipliers(&vec![5.0; n1])
+        .unwrap();
 
     let mut u = vec![0.0; nx];
     let solver_result = alm_optimizer.solve(&mut u);
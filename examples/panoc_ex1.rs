@@ -46,12 +46,14 @@ fn main() {
     };
 
     // define the constraints
-    let bounds = Ball2::new(None, radius);
+    let bounds = Ball2::new(None, radius).unwrap();
 
     /* PROBLEM STATEMENT */
     let problem = Problem::new(&bounds, df, f);
-    let mut panoc_cache = PANOCCache::new(problem_size, tolerance, lbfgs_memory_size);
-    let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache).with_max_iter(max_iters);
+    let mut panoc_cache = PANOCCache::new(problem_size, tolerance, lbfgs_memory_size).unwrap();
+    let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
+        .with_max_iter(max_iters)
+        .unwrap();
 
     // Invoke the solver
     let status = panoc.solve(&mut u);
@@ -27,7 +27,7 @@ fn main() {
     let mut radius = 1.0;
 
     // the cache is created only ONCE
-    let mut panoc_cache = PANOCCache::new(n_dim_u, tolerance, lbfgs_memory);
+    let mut panoc_cache = PANOCCache::new(n_dim_u, tolerance, lbfgs_memory).unwrap();
 
     let mut idx = 0;
     while idx < 100 {
@@ -47,13 +47,15 @@ fn main() {
         };
 
         // define the bounds at every iteration
-        let bounds = constraints::Ball2::new(None, radius);
+        let bounds = constraints::Ball2::new(None, radius).unwrap();
 
         // the problem definition is updated at every iteration
         let problem = Problem::new(&bounds, df, f);
 
         // updated instance of the solver
-        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache).with_max_iter(max_iters);
+        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_max_iter(max_iters)
+            .unwrap();
 
         let status = panoc.solve(&mut u).unwrap();
 
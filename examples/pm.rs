@@ -66,7 +66,7 @@ fn main() {
     let n1 = 0;
     let n2 = 1;
     let lbfgs_mem = 5;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
     let bounds = NoConstraints::new();
@@ -80,7 +80,8 @@ fn main() {
         Some(jf2t),
         NO_SET,
         n2,
-    );
+    )
+    .unwrap();
 
     let alm_problem = AlmProblem::new(
         bounds,
@@ -96,15 +97,22 @@ fn main() {
         Some(f2),
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-6)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
+        .unwrap()
         .with_max_outer_iterations(20)
+        .unwrap()
         .with_max_inner_iterations(1000)
+        .unwrap()
         .with_initial_penalty(5000.0)
-        .with_penalty_update_factor(2.2);
+        .unwrap()
+        .with_penalty_update_factor(2.2)
+        .unwrap();
 
     let mut u = vec![0.1; nx];
     let solver_result = alm_optimizer.solve(&mut u);
@@ -0,0 +1,142 @@
+/*
+ * C interface of Optimization Engine (OpEn)
+ *
+ * Declarations of the functions of the module `ffi` of the Rust crate
+ * `optimization_engine` (which requires the feature `ffi`). See the
+ * documentation of that module for details.
+ */
+#ifndef OPEN_H
+#define OPEN_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Callbacks: return 0 on success; any other value aborts the solver */
+typedef int (*OpenCostFunction)(const double *u, const double *xi,
+                                double *cost, void *user_data);
+typedef int (*OpenGradientFunction)(const double *u, const double *xi,
+                                    double *grad, void *user_data);
+typedef int (*OpenMappingFunction)(const double *u, double *f1u,
+                                   void *user_data);
+
+/* Exit status of the solver */
+typedef enum {
+    OPEN_CONVERGED = 0,
+    OPEN_NOT_CONVERGED_ITERATIONS,
+    OPEN_NOT_CONVERGED_OUT_OF_TIME,
+    OPEN_NOT_CONVERGED_COST,
+    OPEN_NOT_CONVERGED_NOT_FINITE_COMPUTATION,
+    OPEN_INVALID_INPUT,
+    OPEN_DIVERGED
+} OpenExitStatus;
+
+/* Numbers of evaluations of the oracles */
+typedef struct {
+    unsigned long cost;
+    unsigned long gradient;
+    unsigned long mapping_f1;
+    unsigned long mapping_f2;
+    unsigned long projections;
+} OpenOracleCalls;
+
+/* Status of the solver */
+typedef struct {
+    OpenExitStatus exit_status;
+    unsigned long num_outer_iterations;
+    unsigned long num_inner_iterations;
+    double last_problem_norm_fpr;
+    unsigned long long solve_time_ns;
+    double penalty;
+    double delta_y_norm_over_c;
+    double cost;
+    double f2_norm;
+    OpenOracleCalls oracle_calls;
+} OpenSolverStatus;
+
+/* Status of PANOC and the other inner solvers (gradient_norm is NaN if it
+ * was not computed) */
+typedef struct {
+    OpenExitStatus exit_status;
+    unsigned long num_iterations;
+    double norm_fpr;
+    double cost;
+    double gradient_norm;
+    unsigned long long solve_time_ns;
+    OpenOracleCalls oracle_calls;
+} OpenInnerSolverStatus;
+
+/* Configuration of the solver (zero durations and penalties mean "none") */
+typedef struct {
+    unsigned long max_outer_iterations;
+    unsigned long max_inner_iterations;
+    unsigned long long max_duration_ns;
+    double delta_tolerance;
+    double epsilon_tolerance;
+    double penalty_update_factor;
+    double inner_tolerance_update_factor;
+    double initial_inner_tolerance;
+    double sufficient_decrease_coefficient;
+    double initial_penalty;
+    bool nan_screening;
+    bool record_history;
+    bool penalty_method;
+    bool filter;
+    bool real_time_iterations;
+} OpenAlmConfig;
+
+/* Configuration of PANOC (zero values mean "none") */
+typedef struct {
+    double tolerance;
+    double akkt_tolerance;
+    unsigned long max_iter;
+    unsigned long long max_duration_ns;
+    bool record_history;
+    bool nan_screening;
+    double trust_region_radius;
+    unsigned long sr1_memory;
+} OpenPanocConfig;
+
+/* Solver (opaque) */
+typedef struct OpenSolver OpenSolver;
+
+OpenSolver *open_solver_new(unsigned long nu, unsigned long n1,
+                            OpenCostFunction cost,
+                            OpenGradientFunction gradient,
+                            OpenMappingFunction mapping_f1,
+                            const double *u_min, const double *u_max,
+                            const double *c_min, const double *c_max,
+                            void *user_data);
+
+int open_solver_set_tolerances(OpenSolver *solver, double epsilon_tolerance,
+                               double delta_tolerance);
+
+int open_solver_set_limits(OpenSolver *solver,
+                           unsigned long max_outer_iterations,
+                           unsigned long max_inner_iterations,
+                           unsigned long long max_duration_us);
+
+OpenAlmConfig open_alm_config_default(void);
+
+OpenPanocConfig open_panoc_config_default(void);
+
+int open_solver_get_config(const OpenSolver *solver, OpenAlmConfig *config);
+
+int open_solver_set_config(OpenSolver *solver, const OpenAlmConfig *config);
+
+OpenSolverStatus open_solver_solve(OpenSolver *solver, double *u,
+                                   const double *y0, const double *c0);
+
+OpenSolverStatus open_solver_status(const OpenSolver *solver);
+
+int open_solver_lagrange_multipliers(const OpenSolver *solver, double *y);
+
+void open_solver_free(OpenSolver *solver);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OPEN_H */
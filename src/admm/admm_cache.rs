@@ -0,0 +1,62 @@
+/// Cache for `AdmmOptimizer` (to be allocated once)
+///
+/// The cache contains the scaled dual variable and the workspaces of ADMM;
+/// it can be reused by different instances of `AdmmOptimizer` for problems
+/// of the same dimensions.
+///
+#[derive(Debug)]
+pub struct AdmmCache {
+    /// dimension of $u$
+    pub(crate) nu: usize,
+    /// dimension of $z$
+    pub(crate) nz: usize,
+    /// scaled dual variable, $w = y / \rho$
+    pub(crate) w: Vec<f64>,
+    /// $Au$
+    pub(crate) au: Vec<f64>,
+    /// $Bz$
+    pub(crate) bz: Vec<f64>,
+    /// $Bz$ at the previous iteration
+    pub(crate) bz_previous: Vec<f64>,
+    /// argument $v$ of the $u$- and $z$-updates
+    pub(crate) v: Vec<f64>,
+    /// iteration count
+    pub(crate) iteration: usize,
+    /// norm of the primal residual, $\Vert Au + Bz - c\Vert$
+    pub(crate) primal_residual: f64,
+    /// norm of the dual residual, $\rho\Vert B(z^{k+1} - z^k)\Vert$
+    pub(crate) dual_residual: f64,
+}
+
+impl AdmmCache {
+    /// Constructs a new instance of `AdmmCache`
+    ///
+    /// # Arguments
+    ///
+    /// - `nu`: dimension of $u$
+    /// - `nz`: dimension of $z$
+    /// - `nc`: number of constraints, $Au + Bz = c$
+    ///
+    pub fn new(nu: usize, nz: usize, nc: usize) -> Self {
+        AdmmCache {
+            nu,
+            nz,
+            w: vec![0.0; nc],
+            au: vec![0.0; nc],
+            bz: vec![0.0; nc],
+            bz_previous: vec![0.0; nc],
+            v: vec![0.0; nc],
+            iteration: 0,
+            primal_residual: f64::INFINITY,
+            dual_residual: f64::INFINITY,
+        }
+    }
+
+    /// Resets the cache (the scaled dual variable becomes zero)
+    pub fn reset(&mut self) {
+        self.w.iter_mut().for_each(|wi| *wi = 0.0);
+        self.iteration = 0;
+        self.primal_residual = f64::INFINITY;
+        self.dual_residual = f64::INFINITY;
+    }
+}
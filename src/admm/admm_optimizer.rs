@@ -0,0 +1,316 @@
+use crate::{
+    admm::{AdmmCache, AdmmOptimizerStatus, AdmmProblem},
+    core::ExitStatus,
+    matrix_operations, FunctionCallResult, SolverError,
+};
+
+const DEFAULT_MAX_ITER: usize = 1000;
+const DEFAULT_PENALTY: f64 = 1.0;
+const DEFAULT_ABSOLUTE_TOLERANCE: f64 = 1e-5;
+const DEFAULT_RELATIVE_TOLERANCE: f64 = 1e-4;
+
+/// Residual balancing: the penalty parameter is multiplied (divided) by
+/// `tau` when the primal (dual) residual is more than `mu` times the dual
+/// (primal) residual
+#[derive(Debug, Clone, Copy)]
+struct PenaltyAdaptation {
+    mu: f64,
+    tau: f64,
+}
+
+/// Scaled ADMM for problems of the form
+/// $\min f(u) + g(z)$ subject to $Au + Bz = c$
+///
+/// Starting from $z^0$ and the scaled dual variable $w^0$ (which is zero
+/// unless the [`AdmmCache`] is reused without being reset), the algorithm
+/// performs the iterations
+///
+/// $$\begin{aligned}
+/// u^{k+1} &= \mathrm{argmin}_u f(u) + \tfrac{\rho}{2}\Vert Au + Bz^k - c + w^k\Vert^2,
+/// \\\\
+/// z^{k+1} &= \mathrm{argmin}_z g(z) + \tfrac{\rho}{2}\Vert Au^{k+1} + Bz - c + w^k\Vert^2,
+/// \\\\
+/// w^{k+1} &= w^k + Au^{k+1} + Bz^{k+1} - c,
+/// \end{aligned}$$
+///
+/// and terminates when the primal residual, $r = Au + Bz - c$, and the dual
+/// residual, $s = \rho A^\top B(z^{k+1} - z^k)$, satisfy
+///
+/// $$\begin{aligned}
+/// \Vert r\Vert &\leq \sqrt{n_c}\epsilon_{\rm abs} + \epsilon_{\rm rel}\max\\{\Vert Au\Vert, \Vert Bz\Vert, \Vert c\Vert\\},
+/// \\\\
+/// \Vert s\Vert &\leq \sqrt{n_c}\epsilon_{\rm abs} + \epsilon_{\rm rel}\rho\Vert w\Vert
+/// \end{aligned}$$
+///
+/// where, since $A^\top$ is not available, $\Vert s\Vert$ is replaced by
+/// $\rho\Vert B(z^{k+1} - z^k)\Vert$ (which is equal to it in consensus
+/// problems, where $A = I$).
+///
+/// [`AdmmCache`]: struct.AdmmCache.html
+///
+pub struct AdmmOptimizer<'life, 'a, UStep, ZStep, OperatorA, OperatorB>
+where
+    UStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    ZStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    OperatorA: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    OperatorB: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+{
+    problem: AdmmProblem<'a, UStep, ZStep, OperatorA, OperatorB>,
+    cache: &'life mut AdmmCache,
+    penalty: f64,
+    max_iter: usize,
+    max_duration: Option<std::time::Duration>,
+    absolute_tolerance: f64,
+    relative_tolerance: f64,
+    penalty_adaptation: Option<PenaltyAdaptation>,
+}
+
+impl<'life, 'a, UStep, ZStep, OperatorA, OperatorB>
+    AdmmOptimizer<'life, 'a, UStep, ZStep, OperatorA, OperatorB>
+where
+    UStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    ZStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    OperatorA: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    OperatorB: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+{
+    /// Constructs a new instance of `AdmmOptimizer`
+    ///
+    /// The penalty parameter is $\rho = 1$, the maximum number of iterations
+    /// is 1000 and the absolute and relative tolerances are $10^{-5}$ and
+    /// $10^{-4}$; these can be changed with the `with_*` methods.
+    ///
+    /// # Arguments
+    ///
+    /// - `problem`: problem definition
+    /// - `cache`: cache of dimensions compatible with the problem
+    ///
+    pub fn new(
+        problem: AdmmProblem<'a, UStep, ZStep, OperatorA, OperatorB>,
+        cache: &'life mut AdmmCache,
+    ) -> Self {
+        AdmmOptimizer {
+            problem,
+            cache,
+            penalty: DEFAULT_PENALTY,
+            max_iter: DEFAULT_MAX_ITER,
+            max_duration: None,
+            absolute_tolerance: DEFAULT_ABSOLUTE_TOLERANCE,
+            relative_tolerance: DEFAULT_RELATIVE_TOLERANCE,
+            penalty_adaptation: None,
+        }
+    }
+
+    /// Sets the (initial) penalty parameter, $\rho$
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `penalty` is not positive
+    /// and finite
+    ///
+    pub fn with_penalty(mut self, penalty: f64) -> Result<Self, SolverError> {
+        crate::ensure(
+            penalty > 0.0 && penalty.is_finite(),
+            "penalty",
+            "must be positive and finite",
+        )?;
+        self.penalty = penalty;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `max_iter` is zero
+    ///
+    pub fn with_max_iter(mut self, max_iter: usize) -> Result<Self, SolverError> {
+        crate::ensure(max_iter > 0, "max_iter", "must be positive")?;
+        self.max_iter = max_iter;
+        Ok(self)
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: std::time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Sets the absolute tolerance, $\epsilon_{\rm abs}$
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the tolerance is negative
+    /// or not finite
+    ///
+    pub fn with_absolute_tolerance(mut self, tolerance: f64) -> Result<Self, SolverError> {
+        crate::ensure(
+            tolerance >= 0.0 && tolerance.is_finite(),
+            "absolute_tolerance",
+            "must be nonnegative and finite",
+        )?;
+        self.absolute_tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the relative tolerance, $\epsilon_{\rm rel}$
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the tolerance is negative
+    /// or not finite
+    ///
+    pub fn with_relative_tolerance(mut self, tolerance: f64) -> Result<Self, SolverError> {
+        crate::ensure(
+            tolerance >= 0.0 && tolerance.is_finite(),
+            "relative_tolerance",
+            "must be nonnegative and finite",
+        )?;
+        self.relative_tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Enables the adaptation of the penalty parameter by residual balancing:
+    /// $\rho$ is multiplied by `tau` when $\Vert r\Vert > \mu\Vert s\Vert$ and
+    /// divided by `tau` when $\Vert s\Vert > \mu\Vert r\Vert$ (typical values are
+    /// $\mu = 10$ and $\tau = 2$)
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `mu` or `tau` are not
+    /// greater than 1 and finite
+    ///
+    pub fn with_penalty_adaptation(mut self, mu: f64, tau: f64) -> Result<Self, SolverError> {
+        crate::ensure(mu > 1.0 && mu.is_finite(), "mu", "must be greater than 1")?;
+        crate::ensure(
+            tau > 1.0 && tau.is_finite(),
+            "tau",
+            "must be greater than 1",
+        )?;
+        self.penalty_adaptation = Some(PenaltyAdaptation { mu, tau });
+        Ok(self)
+    }
+
+    fn check_dimension(name: &'static str, expected: usize, actual: usize) -> FunctionCallResult {
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(SolverError::DimensionMismatch {
+                name,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    /// Solves the problem
+    ///
+    /// # Arguments
+    ///
+    /// - `u`: on entry, initial guess of $u$ (which is passed to the first
+    ///   $u$-update); on exit, the solution
+    /// - `z`: on entry, initial guess of $z$; on exit, the solution
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u`, `z` or `c` are not
+    /// compatible with the cache, `SolverError::NotFiniteComputation` if
+    /// the residuals are not finite, or the errors of the oracles
+    ///
+    pub fn solve(
+        &mut self,
+        u: &mut [f64],
+        z: &mut [f64],
+    ) -> Result<AdmmOptimizerStatus, SolverError> {
+        let tic = instant::Instant::now();
+        let cache = &mut *self.cache;
+        let problem = &self.problem;
+        let nc = cache.w.len();
+        Self::check_dimension("u", cache.nu, u.len())?;
+        Self::check_dimension("z", cache.nz, z.len())?;
+        if let Some(c) = problem.c {
+            Self::check_dimension("c", nc, c.len())?;
+        }
+        let c_at = |i: usize| problem.c.map_or(0.0, |c| c[i]);
+        let norm_c = problem.c.map_or(0.0, matrix_operations::norm2);
+        let sqrt_nc = (nc as f64).sqrt();
+        let mut penalty = self.penalty;
+        let mut exit_status = ExitStatus::NotConvergedIterations;
+
+        (problem.operator_b)(z, &mut cache.bz)?;
+        cache.iteration = 0;
+        while cache.iteration < self.max_iter {
+            cache.iteration += 1;
+
+            // u-update: v = c - Bz - w
+            for (i, vi) in cache.v.iter_mut().enumerate() {
+                *vi = c_at(i) - cache.bz[i] - cache.w[i];
+            }
+            (problem.u_step)(&cache.v, penalty, u)?;
+            (problem.operator_a)(u, &mut cache.au)?;
+
+            // z-update: v = c - Au - w
+            for (i, vi) in cache.v.iter_mut().enumerate() {
+                *vi = c_at(i) - cache.au[i] - cache.w[i];
+            }
+            cache.bz_previous.copy_from_slice(&cache.bz);
+            (problem.z_step)(&cache.v, penalty, z)?;
+            (problem.operator_b)(z, &mut cache.bz)?;
+
+            // dual update, w += Au + Bz - c, and residuals
+            let (mut primal_sq, mut dual_sq) = (0.0, 0.0);
+            for i in 0..nc {
+                let r = cache.au[i] + cache.bz[i] - c_at(i);
+                let ds = cache.bz[i] - cache.bz_previous[i];
+                cache.w[i] += r;
+                primal_sq += r * r;
+                dual_sq += ds * ds;
+            }
+            cache.primal_residual = primal_sq.sqrt();
+            cache.dual_residual = penalty * dual_sq.sqrt();
+            if !cache.primal_residual.is_finite() || !cache.dual_residual.is_finite() {
+                return Err(SolverError::NotFiniteComputation);
+            }
+
+            let primal_tolerance = sqrt_nc * self.absolute_tolerance
+                + self.relative_tolerance
+                    * matrix_operations::norm2(&cache.au)
+                        .max(matrix_operations::norm2(&cache.bz))
+                        .max(norm_c);
+            let dual_tolerance = sqrt_nc * self.absolute_tolerance
+                + self.relative_tolerance * penalty * matrix_operations::norm2(&cache.w);
+            if cache.primal_residual <= primal_tolerance && cache.dual_residual <= dual_tolerance {
+                exit_status = ExitStatus::Converged;
+                break;
+            }
+
+            if let Some(PenaltyAdaptation { mu, tau }) = self.penalty_adaptation {
+                // the scaled dual variable, w = y / rho, is rescaled so that
+                // the multipliers, y, do not change
+                let factor = if cache.primal_residual > mu * cache.dual_residual {
+                    tau
+                } else if cache.dual_residual > mu * cache.primal_residual {
+                    1.0 / tau
+                } else {
+                    1.0
+                };
+                if factor != 1.0 {
+                    penalty *= factor;
+                    cache.w.iter_mut().for_each(|wi| *wi /= factor);
+                }
+            }
+
+            if let Some(max_duration) = self.max_duration {
+                if tic.elapsed() > max_duration {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+        }
+
+        Ok(AdmmOptimizerStatus::new(exit_status)
+            .with_iterations(cache.iteration)
+            .with_solve_time(tic.elapsed())
+            .with_residuals(cache.primal_residual, cache.dual_residual)
+            .with_penalty_and_scaled_dual(penalty, &cache.w))
+    }
+}
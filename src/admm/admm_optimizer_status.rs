@@ -0,0 +1,122 @@
+use crate::core::ExitStatus;
+use std::fmt;
+
+/// Solution statistics for `AdmmOptimizer`
+///
+/// This structure has no public fields and no public setter methods.
+/// The idea is that only Optimization Engine can create
+/// `AdmmOptimizerStatus` instances.
+///
+/// The `Display` implementation prints a summary of the solution statistics,
+/// one per line.
+///
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdmmOptimizerStatus {
+    /// Exit status
+    exit_status: ExitStatus,
+    /// Number of iterations
+    num_iterations: usize,
+    /// Total solve time
+    solve_time: std::time::Duration,
+    /// Norm of the primal residual, $\Vert Au + Bz - c\Vert$
+    primal_residual: f64,
+    /// Norm of the dual residual, $\rho\Vert B(z^{k+1} - z^k)\Vert$
+    dual_residual: f64,
+    /// Last value of the penalty parameter
+    penalty: f64,
+    /// Lagrange multipliers of the constraints $Au + Bz = c$
+    lagrange_multipliers: Vec<f64>,
+}
+
+impl AdmmOptimizerStatus {
+    /// Constructor for instances of `AdmmOptimizerStatus`
+    ///
+    /// This method is only accessibly within this crate.
+    pub(crate) fn new(exit_status: ExitStatus) -> Self {
+        AdmmOptimizerStatus {
+            exit_status,
+            num_iterations: 0,
+            solve_time: std::time::Duration::from_nanos(0),
+            primal_residual: 0.0,
+            dual_residual: 0.0,
+            penalty: 0.0,
+            lagrange_multipliers: vec![],
+        }
+    }
+
+    pub(crate) fn with_iterations(mut self, num_iterations: usize) -> Self {
+        self.num_iterations = num_iterations;
+        self
+    }
+
+    pub(crate) fn with_solve_time(mut self, duration: std::time::Duration) -> Self {
+        self.solve_time = duration;
+        self
+    }
+
+    pub(crate) fn with_residuals(mut self, primal_residual: f64, dual_residual: f64) -> Self {
+        self.primal_residual = primal_residual;
+        self.dual_residual = dual_residual;
+        self
+    }
+
+    /// Setter method for the penalty parameter and the Lagrange multipliers,
+    /// which are computed from the scaled dual variable, $y = \rho w$
+    pub(crate) fn with_penalty_and_scaled_dual(mut self, penalty: f64, w: &[f64]) -> Self {
+        self.penalty = penalty;
+        self.lagrange_multipliers = w.iter().map(|wi| penalty * wi).collect();
+        self
+    }
+
+    /// Whether the algorithm has converged
+    pub fn has_converged(&self) -> bool {
+        self.exit_status == ExitStatus::Converged
+    }
+
+    /// Exit status of the algorithm
+    pub fn exit_status(&self) -> ExitStatus {
+        self.exit_status
+    }
+
+    /// Number of iterations
+    pub fn iterations(&self) -> usize {
+        self.num_iterations
+    }
+
+    /// Total solve time
+    pub fn solve_time(&self) -> std::time::Duration {
+        self.solve_time
+    }
+
+    /// Norm of the primal residual, $\Vert Au + Bz - c\Vert$
+    pub fn primal_residual(&self) -> f64 {
+        self.primal_residual
+    }
+
+    /// Norm of the dual residual, $\rho\Vert B(z^{k+1} - z^k)\Vert$
+    pub fn dual_residual(&self) -> f64 {
+        self.dual_residual
+    }
+
+    /// Last value of the penalty parameter
+    pub fn penalty(&self) -> f64 {
+        self.penalty
+    }
+
+    /// Lagrange multipliers of the constraints $Au + Bz = c$
+    pub fn lagrange_multipliers(&self) -> &[f64] {
+        &self.lagrange_multipliers
+    }
+}
+
+impl fmt::Display for AdmmOptimizerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "exit status     : {}", self.exit_status)?;
+        writeln!(f, "iterations      : {}", self.num_iterations)?;
+        writeln!(f, "solve time      : {:.3?}", self.solve_time)?;
+        writeln!(f, "primal residual : {:.6e}", self.primal_residual)?;
+        writeln!(f, "dual residual   : {:.6e}", self.dual_residual)?;
+        write!(f, "penalty         : {:.6e}", self.penalty)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::FunctionCallResult;
+
+/// Definition of an optimization problem to be solved with `AdmmOptimizer`
+///
+/// The problem has the form
+///
+/// $$\begin{aligned}
+/// \mathrm{Minimize}\ f(u) + g(z)
+/// \\\\
+/// Au + Bz = c
+/// \end{aligned}$$
+///
+/// where $u\in\mathbb{R}^{n_u}$ and $z\in\mathbb{R}^{n_z}$ are the decision
+/// variables, $f$ and $g$ are closed, proper, convex functions (which may
+/// be nonsmooth or take infinite values, e.g., indicators of sets) and
+/// $A\in\mathbb{R}^{n_c\times n_u}$, $B\in\mathbb{R}^{n_c\times n_z}$ and
+/// $c\in\mathbb{R}^{n_c}$.
+///
+/// The functions $f$ and $g$ are provided through the solutions of the
+/// subproblems of ADMM:
+///
+/// - `u_step`, given $v\in\mathbb{R}^{n_c}$ and the penalty parameter $\rho$,
+///   computes $u = \mathrm{argmin}_u f(u) + \tfrac{\rho}{2}\Vert Au - v\Vert^2$
+///   (on entry, `u` is the previous iterate, which can be used to warm start
+///   iterative methods),
+/// - `z_step` computes $z = \mathrm{argmin}_z g(z) + \tfrac{\rho}{2}\Vert Bz - v\Vert^2$,
+///
+/// and the (linear) operators $A$ and $B$ are provided as functions which
+/// compute $Au$ and $Bz$. If $A = I$, the $u$-update is the proximal
+/// operator of $f/\rho$ at $v$.
+///
+pub struct AdmmProblem<'a, UStep, ZStep, OperatorA, OperatorB>
+where
+    UStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    ZStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    OperatorA: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    OperatorB: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+{
+    /// $u$-update
+    pub(crate) u_step: UStep,
+    /// $z$-update
+    pub(crate) z_step: ZStep,
+    /// operator $u \mapsto Au$
+    pub(crate) operator_a: OperatorA,
+    /// operator $z \mapsto Bz$
+    pub(crate) operator_b: OperatorB,
+    /// vector $c$ (if `None`, $c = 0$)
+    pub(crate) c: Option<&'a [f64]>,
+}
+
+impl<'a, UStep, ZStep, OperatorA, OperatorB> AdmmProblem<'a, UStep, ZStep, OperatorA, OperatorB>
+where
+    UStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    ZStep: Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult,
+    OperatorA: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    OperatorB: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+{
+    /// Constructs a new instance of `AdmmProblem`
+    ///
+    /// # Arguments
+    ///
+    /// - `u_step`: $u$-update, $(v, \rho, u) \mapsto u$
+    /// - `z_step`: $z$-update, $(v, \rho, z) \mapsto z$
+    /// - `operator_a`: operator $A$ (e.g., `admm::identity`)
+    /// - `operator_b`: operator $B$ (e.g., `admm::negative_identity`)
+    /// - `c`: vector $c$ (`None` stands for $c = 0$)
+    ///
+    pub fn new(
+        u_step: UStep,
+        z_step: ZStep,
+        operator_a: OperatorA,
+        operator_b: OperatorB,
+        c: Option<&'a [f64]>,
+    ) -> Self {
+        AdmmProblem {
+            u_step,
+            z_step,
+            operator_a,
+            operator_b,
+            c,
+        }
+    }
+}
@@ -0,0 +1,104 @@
+#![deny(missing_docs)]
+//! Alternating Direction Method of Multipliers (ADMM)
+//!
+//! A module that contains structures and implementations that allow to
+//! formulate and solve problems of the form
+//!
+//! $$\begin{aligned}
+//! \mathrm{Minimize}\ f(u) + g(z)
+//! \\\\
+//! Au + Bz = c
+//! \end{aligned}$$
+//!
+//! (see [`AdmmProblem`]) with the scaled form of ADMM, which is implemented
+//! by [`AdmmOptimizer`]. ADMM is well suited to problems which split into
+//! two parts whose proximal-type subproblems are cheap, or can be solved in
+//! closed form, such as consensus problems, the lasso, or problems with
+//! constraints $z \in Z$ on sets on which it is easy to project (see
+//! [`consensus_projection`]).
+//!
+//! The user needs to create an [`AdmmCache`] object, which can then be
+//! passed to different instances of `AdmmOptimizer`. Upon completion,
+//! `AdmmOptimizer` returns an [`AdmmOptimizerStatus`].
+//!
+//! # Example
+//!
+//! Projection of $a$ on the Euclidean ball, $\min \tfrac{1}{2}\Vert u - a \Vert^2$
+//! subject to $\Vert u \Vert \leq 1$, in consensus form (with $u - z = 0$):
+//!
+//! ```
+//! use optimization_engine::{admm::*, constraints::Ball2, FunctionCallResult};
+//!
+//! let a = [2.0, -1.0];
+//! // u = argmin 0.5 |u - a|^2 + 0.5 rho |u - v|^2
+//! let u_step = |v: &[f64], rho: f64, u: &mut [f64]| -> FunctionCallResult {
+//!     u.iter_mut()
+//!         .zip(a.iter().zip(v))
+//!         .for_each(|(ui, (ai, vi))| *ui = (ai + rho * vi) / (1.0 + rho));
+//!     Ok(())
+//! };
+//! let ball = Ball2::new(None, 1.0)?;
+//! let problem = AdmmProblem::new(
+//!     u_step,
+//!     consensus_projection(&ball),
+//!     identity,
+//!     negative_identity,
+//!     None,
+//! );
+//! let mut cache = AdmmCache::new(2, 2, 2);
+//! let (mut u, mut z) = ([0.0; 2], [0.0; 2]);
+//! let status = AdmmOptimizer::new(problem, &mut cache).solve(&mut u, &mut z)?;
+//! assert!(status.has_converged());
+//! assert!((z[0] - 2.0 / 5.0_f64.sqrt()).abs() < 1e-4);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`AdmmProblem`]: struct.AdmmProblem.html
+//! [`AdmmOptimizer`]: struct.AdmmOptimizer.html
+//! [`AdmmCache`]: struct.AdmmCache.html
+//! [`AdmmOptimizerStatus`]: struct.AdmmOptimizerStatus.html
+//! [`consensus_projection`]: fn.consensus_projection.html
+//!
+mod admm_cache;
+mod admm_optimizer;
+mod admm_optimizer_status;
+mod admm_problem;
+
+pub use admm_cache::AdmmCache;
+pub use admm_optimizer::AdmmOptimizer;
+pub use admm_optimizer_status::AdmmOptimizerStatus;
+pub use admm_problem::AdmmProblem;
+
+use crate::{constraints::Constraint, FunctionCallResult};
+
+/// Identity operator, $Iu$ (e.g., $A$ in consensus problems, $u - z = 0$)
+pub fn identity(x: &[f64], result: &mut [f64]) -> FunctionCallResult {
+    result.copy_from_slice(x);
+    Ok(())
+}
+
+/// Negative identity operator, $-Iz$ (e.g., $B$ in consensus problems, $u - z = 0$)
+pub fn negative_identity(x: &[f64], result: &mut [f64]) -> FunctionCallResult {
+    result.iter_mut().zip(x).for_each(|(r, xi)| *r = -xi);
+    Ok(())
+}
+
+/// $z$-update of consensus problems, $u - z = 0$ (with $B = -I$), where
+/// $g$ is the indicator function of a closed set $Z$
+///
+/// The $z$-update is $z = \Pi_Z(-v)$.
+pub fn consensus_projection<C: Constraint>(
+    set: &C,
+) -> impl Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult + '_ {
+    move |v: &[f64], _penalty: f64, z: &mut [f64]| {
+        negative_identity(v, z)?;
+        set.project(z);
+        Ok(())
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,167 @@
+use super::*;
+use crate::{
+    constraints::Ball2, core::ExitStatus, matrix_operations, FunctionCallResult, SolverError,
+};
+
+/// u-update of $f(u) = \tfrac{1}{2}\Vert u - a\Vert^2$ with $A = I$
+fn least_squares_step(a: &[f64]) -> impl Fn(&[f64], f64, &mut [f64]) -> FunctionCallResult + '_ {
+    move |v: &[f64], rho: f64, u: &mut [f64]| {
+        u.iter_mut()
+            .zip(a.iter().zip(v))
+            .for_each(|(ui, (ai, vi))| *ui = (ai + rho * vi) / (1.0 + rho));
+        Ok(())
+    }
+}
+
+#[test]
+fn t_admm_lasso() {
+    // minimize 0.5 sum_i d_i (u_i - a_i)^2 + lambda |z|_1, s.t. u - z = 0,
+    // whose solution is u_i = softthreshold(a_i, lambda / d_i)
+    let (d, a, lambda) = ([1.0, 4.0, 0.5], [2.0, -0.1, -3.0], 0.5);
+    let u_step = |v: &[f64], rho: f64, u: &mut [f64]| -> FunctionCallResult {
+        for i in 0..3 {
+            u[i] = (d[i] * a[i] + rho * v[i]) / (d[i] + rho);
+        }
+        Ok(())
+    };
+    // z = prox_{lambda |.|_1 / rho}(-v)
+    let z_step = |v: &[f64], rho: f64, z: &mut [f64]| -> FunctionCallResult {
+        z.iter_mut().zip(v).for_each(|(zi, vi)| {
+            *zi = -vi.signum() * (vi.abs() - lambda / rho).max(0.0);
+        });
+        Ok(())
+    };
+    let problem = AdmmProblem::new(u_step, z_step, identity, negative_identity, None);
+    let mut cache = AdmmCache::new(3, 3, 3);
+    let (mut u, mut z) = ([0.0; 3], [0.0; 3]);
+    let status = AdmmOptimizer::new(problem, &mut cache)
+        .with_absolute_tolerance(1e-9)
+        .unwrap()
+        .with_relative_tolerance(1e-9)
+        .unwrap()
+        .solve(&mut u, &mut z)
+        .unwrap();
+    assert!(status.has_converged());
+    let expected = [1.5, 0.0, -2.0];
+    for i in 0..3 {
+        assert!((z[i] - expected[i]).abs() < 1e-7, "z[{}] = {}", i, z[i]);
+        assert!((u[i] - expected[i]).abs() < 1e-7, "u[{}] = {}", i, u[i]);
+    }
+    assert!(status.primal_residual() < 1e-8);
+}
+
+#[test]
+fn t_admm_consensus_projection() {
+    let a = [3.0, 4.0, 0.0];
+    let ball = Ball2::new(None, 1.0).unwrap();
+    let problem = AdmmProblem::new(
+        least_squares_step(&a),
+        consensus_projection(&ball),
+        identity,
+        negative_identity,
+        None,
+    );
+    let mut cache = AdmmCache::new(3, 3, 3);
+    let (mut u, mut z) = ([0.0; 3], [0.0; 3]);
+    let status = AdmmOptimizer::new(problem, &mut cache)
+        .with_penalty(2.0)
+        .unwrap()
+        .solve(&mut u, &mut z)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!((z[0] - 0.6).abs() < 1e-4 && (z[1] - 0.8).abs() < 1e-4);
+    assert!(matrix_operations::norm2(&z) <= 1.0 + 1e-12);
+    assert_eq!(2.0, status.penalty());
+}
+
+#[test]
+fn t_admm_general_operators() {
+    // minimize 0.5 |u - a|^2 + 0.5 |z - b|^2 s.t. u + 2z = c; the
+    // solution is u = a - y, z = b - 2y with y = (a + 2b - c) / 5
+    let (a, b, c) = ([1.0, -2.0], [0.5, 3.0], [4.0, 1.0]);
+    let z_step = |v: &[f64], rho: f64, z: &mut [f64]| -> FunctionCallResult {
+        z.iter_mut()
+            .zip(b.iter().zip(v))
+            .for_each(|(zi, (bi, vi))| *zi = (bi + 2.0 * rho * vi) / (1.0 + 4.0 * rho));
+        Ok(())
+    };
+    let operator_b = |z: &[f64], bz: &mut [f64]| -> FunctionCallResult {
+        bz.iter_mut().zip(z).for_each(|(bzi, zi)| *bzi = 2.0 * zi);
+        Ok(())
+    };
+    // with rho = 100, ADMM does not converge within the default number of
+    // iterations unless the penalty parameter is adapted
+    for (adaptive, initial_penalty) in [(false, 1.0), (true, 100.0)] {
+        let problem = AdmmProblem::new(
+            least_squares_step(&a),
+            z_step,
+            identity,
+            operator_b,
+            Some(&c),
+        );
+        let mut cache = AdmmCache::new(2, 2, 2);
+        let mut optimizer = AdmmOptimizer::new(problem, &mut cache)
+            .with_penalty(initial_penalty)
+            .unwrap()
+            .with_absolute_tolerance(1e-10)
+            .unwrap()
+            .with_relative_tolerance(0.0)
+            .unwrap();
+        if adaptive {
+            optimizer = optimizer.with_penalty_adaptation(10.0, 2.0).unwrap();
+        }
+        let (mut u, mut z) = ([0.0; 2], [0.0; 2]);
+        let status = optimizer.solve(&mut u, &mut z).unwrap();
+        assert!(status.has_converged());
+        for i in 0..2 {
+            let y = (a[i] + 2.0 * b[i] - c[i]) / 5.0;
+            assert!((u[i] - (a[i] - y)).abs() < 1e-8);
+            assert!((z[i] - (b[i] - 2.0 * y)).abs() < 1e-8);
+            assert!((status.lagrange_multipliers()[i] - y).abs() < 1e-8);
+        }
+        if adaptive {
+            assert!(status.penalty() < 100.0);
+        }
+    }
+}
+
+#[test]
+fn t_admm_max_iter_and_errors() {
+    let a = [3.0, 4.0];
+    let ball = Ball2::new(None, 1.0).unwrap();
+    let problem = AdmmProblem::new(
+        least_squares_step(&a),
+        consensus_projection(&ball),
+        identity,
+        negative_identity,
+        None,
+    );
+    let mut cache = AdmmCache::new(2, 2, 2);
+    let mut optimizer = AdmmOptimizer::new(problem, &mut cache)
+        .with_max_iter(2)
+        .unwrap();
+    let (mut u, mut z) = ([0.0; 2], [0.0; 2]);
+    let status = optimizer.solve(&mut u, &mut z).unwrap();
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+    assert_eq!(2, status.iterations());
+    assert!(matches!(
+        optimizer.solve(&mut [0.0; 3], &mut z),
+        Err(SolverError::DimensionMismatch {
+            name: "u",
+            expected: 2,
+            actual: 3,
+        })
+    ));
+    assert!(optimizer.with_penalty(0.0).is_err());
+
+    let problem = AdmmProblem::new(
+        least_squares_step(&a),
+        consensus_projection(&ball),
+        identity,
+        negative_identity,
+        None,
+    );
+    let mut cache = AdmmCache::new(2, 2, 2);
+    let optimizer = AdmmOptimizer::new(problem, &mut cache);
+    assert!(optimizer.with_penalty_adaptation(1.0, 2.0).is_err());
+}
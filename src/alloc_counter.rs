@@ -0,0 +1,92 @@
+//! Counter of heap allocations (for tests of the allocation-free solve path)
+//!
+//! With the feature `alloc_counter`, OpEn installs a global allocator which
+//! forwards to the system allocator and counts the heap allocations (and
+//! reallocations) of every thread. This is a debugging aid: it is meant for
+//! tests which check that the solvers do not allocate once their caches and
+//! optimizers have been constructed, because allocations cause jitter in
+//! real-time control. It cannot be combined with the features `jem` and
+//! `rp`, which install other global allocators.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{alloc_counter, constraints::*, panoc::*, *};
+//!
+//! let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     grad.copy_from_slice(u);
+//!     Ok(())
+//! };
+//! let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = 0.5 * matrix_operations::norm2_squared(u);
+//!     Ok(())
+//! };
+//! let bounds = Ball2::new(None, 1.0)?;
+//! let mut cache = PANOCCache::new(2, 1e-8, 5)?;
+//! let mut optimizer = PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache);
+//! let mut u = [1.0, 2.0];
+//! let (status, allocations) = alloc_counter::count_allocations(|| optimizer.solve(&mut u));
+//! assert!(status?.has_converged());
+//! assert_eq!(0, allocations);
+//! # Ok::<(), SolverError>(())
+//! ```
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+#[cfg(any(feature = "jem", feature = "rp"))]
+compile_error!("the feature `alloc_counter` cannot be combined with `jem` or `rp`");
+
+thread_local! {
+    /// Number of allocations of the current thread
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// System allocator which counts the allocations of every thread
+struct CountingAllocator;
+
+impl CountingAllocator {
+    fn count() {
+        // the counter is not available while its thread is being torn down
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::count();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::count();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::count();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static COUNTING_ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of heap allocations (and reallocations) of the current thread
+/// since it started
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Runs `f` and returns its result together with the number of heap
+/// allocations (and reallocations) it made in the current thread
+pub fn count_allocations<R, F: FnOnce() -> R>(f: F) -> (R, usize) {
+    let before = allocations();
+    let result = f();
+    (result, allocations() - before)
+}
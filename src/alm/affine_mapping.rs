@@ -0,0 +1,235 @@
+//! Affine mappings
+//!
+//! An [`AffineMapping`], $F_1(u) = Au + b$, provides the mapping $F_1$ and
+//! the product $JF_1(u)^\top d = A^\top d$ of `AlmFactory` and `AlmProblem`,
+//! so that, for affine constraints, only $A$ (a matrix or a
+//! `LinearOperator`) and $b$ need to be given.
+//!
+//! What is exploited is the following:
+//!
+//! - the Jacobian-transpose product is derived from $A$,
+//! - the value of $Au + b$ at the most recent point is stored, so repeated
+//!   evaluations at the same point, e.g., by $\psi$ and $\nabla\psi$ at an
+//!   iterate of the inner solver and by the update of the Lagrange
+//!   multipliers at the solution of the inner problem (which is usually the
+//!   last point at which $\nabla\psi$ was computed), need a single product.
+//!
+//! `AlmOptimizer` itself is not aware that $F_1$ is affine: it still calls
+//! the closures of $F_1$ (which are counted in the oracle calls of
+//! `AlmOptimizerStatus`), and it is these calls which are answered from the
+//! stored value. In particular, an evaluation at a point other than the
+//! previous one costs a product $Au$ even if $F_1$ was evaluated there
+//! before, and the products $A^\top d$ of $\nabla\psi$ are not shared.
+//!
+//! [`AffineMapping`]: struct.AffineMapping.html
+
+use crate::{
+    linear_operator::{DenseMatrix, LinearOperator},
+    FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Most recent evaluation of an affine mapping
+#[derive(Debug)]
+struct LastEvaluation<T> {
+    /// point of the most recent evaluation
+    u: Vec<T>,
+    /// $Au + b$ at `u`
+    value: Vec<T>,
+    /// whether `u` and `value` hold an evaluation
+    valid: bool,
+    /// number of products $Au$ which have been computed
+    matvecs: usize,
+}
+
+/// Affine mapping $F_1(u) = Au + b$, where $A\in\mathbb{R}^{n_1\times n_u}$
+///
+/// The linear part, $A$, is a `LinearOperator`: a `DenseMatrix` (see `new`)
+/// or a matrix-free operator (see `with_operator`). The type parameter `T`
+/// is the scalar type (`f64` by default, which is the scalar type of the
+/// ALM).
+///
+/// When $F_1$ is declared affine, its Jacobian is $A$, so the product
+/// $JF_1(u)^\top d = A^\top d$ is derived from the operator (see
+/// `jacobian_transpose_product`) and need not be provided. The most recent
+/// value of $Au + b$ is stored, so that successive evaluations at the same
+/// point, e.g., in $\psi$ and $\nabla\psi$ (see `AlmFactory`) and in the
+/// update of the Lagrange multipliers at the solution of the inner problem,
+/// need a single product $Au$.
+///
+/// # Example
+///
+/// ```rust
+/// use optimization_engine::{alm::*, constraints::Ball2, FunctionCallResult};
+///
+/// // F1(u) = (u0 + u1, u1 - 1)
+/// let f1 = AffineMapping::new(vec![1.0, 1.0, 0.0, 1.0], vec![0.0, -1.0], 2).unwrap();
+/// let f = |u: &[f64], cost: &mut f64| -> FunctionCallResult { Ok(()) };
+/// let df = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
+/// let factory = AlmFactory::new(
+///     f,
+///     df,
+///     Some(|u: &[f64], f1u: &mut [f64]| f1.mapping(u, f1u)),
+///     Some(|u: &[f64], d: &[f64], res: &mut [f64]| f1.jacobian_transpose_product(u, d, res)),
+///     NO_MAPPING,
+///     NO_JACOBIAN_MAPPING,
+///     Some(Ball2::new(None, 1.0).unwrap()),
+///     0,
+/// )
+/// .unwrap();
+/// ```
+///
+/// The same closure, `|u: &[f64], f1u: &mut [f64]| f1.mapping(u, f1u)`, is
+/// then given to `AlmProblem::new` as $F_1$.
+#[derive(Debug)]
+pub struct AffineMapping<Op = DenseMatrix, T = f64>
+where
+    T: OpEnFloat,
+    Op: LinearOperator<T>,
+{
+    /// linear operator $A$
+    operator: Op,
+    /// vector $b$
+    b: Vec<T>,
+    /// most recent evaluation
+    last_evaluation: Mutex<LastEvaluation<T>>,
+}
+
+impl<T: OpEnFloat> AffineMapping<DenseMatrix<T>, T> {
+    /// Constructs an affine mapping $u \mapsto Au + b$ with a dense matrix
+    ///
+    /// ## Arguments
+    ///
+    /// - `a`: the elements of $A$ in row-major order
+    /// - `b`: the vector $b$, whose length is $n_1$
+    /// - `nu`: number of decision variables, $n_u$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `a` does not have
+    /// $n_1 n_u$ elements
+    pub fn new(a: Vec<T>, b: Vec<T>, nu: usize) -> Result<Self, SolverError> {
+        let n1 = b.len();
+        if a.len() != n1 * nu {
+            return Err(SolverError::DimensionMismatch {
+                name: "a",
+                expected: n1 * nu,
+                actual: a.len(),
+            });
+        }
+        AffineMapping::with_operator(DenseMatrix::new(n1, nu, a)?, b)
+    }
+}
+
+impl<Op, T> AffineMapping<Op, T>
+where
+    T: OpEnFloat,
+    Op: LinearOperator<T>,
+{
+    /// Constructs an affine mapping $u \mapsto Au + b$, where $A$ is a
+    /// (possibly matrix-free) linear operator
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the number of rows of
+    /// `operator` is not the length of `b`
+    pub fn with_operator(operator: Op, b: Vec<T>) -> Result<Self, SolverError> {
+        Self::check_dimension("b", operator.rows(), b.len())?;
+        let (n1, nu) = (operator.rows(), operator.cols());
+        Ok(AffineMapping {
+            operator,
+            b,
+            last_evaluation: Mutex::new(LastEvaluation {
+                u: vec![T::zero(); nu],
+                value: vec![T::zero(); n1],
+                valid: false,
+                matvecs: 0,
+            }),
+        })
+    }
+
+    /// The linear operator $A$
+    pub fn operator(&self) -> &Op {
+        &self.operator
+    }
+
+    /// Range dimension, $n_1$
+    pub fn n1(&self) -> usize {
+        self.b.len()
+    }
+
+    /// Number of decision variables, $n_u$
+    pub fn dimension(&self) -> usize {
+        self.operator.cols()
+    }
+
+    /// Number of products $Au$ which have been computed, that is, of
+    /// evaluations of `mapping` at a point other than the previous one
+    pub fn matvecs(&self) -> usize {
+        self.last_evaluation().matvecs
+    }
+
+    fn last_evaluation(&self) -> MutexGuard<'_, LastEvaluation<T>> {
+        self.last_evaluation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn check_dimension(name: &'static str, expected: usize, actual: usize) -> FunctionCallResult {
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(SolverError::DimensionMismatch {
+                name,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    /// Computes $F_1(u) = Au + b$
+    ///
+    /// If `u` is the point of the previous evaluation, the stored value is
+    /// copied into `f1u` instead
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` does not have $n_u$
+    /// elements or `f1u` does not have $n_1$ elements
+    pub fn mapping(&self, u: &[T], f1u: &mut [T]) -> FunctionCallResult {
+        Self::check_dimension("u", self.dimension(), u.len())?;
+        Self::check_dimension("f1u", self.n1(), f1u.len())?;
+        let mut last = self.last_evaluation();
+        if !last.valid || last.u != u {
+            // invalidated first, in case the operator fails
+            last.valid = false;
+            self.operator.apply(u, &mut last.value)?;
+            last.value
+                .iter_mut()
+                .zip(self.b.iter())
+                .for_each(|(value_i, &b_i)| *value_i += b_i);
+            last.u.copy_from_slice(u);
+            last.valid = true;
+            last.matvecs += 1;
+        }
+        f1u.copy_from_slice(&last.value);
+        Ok(())
+    }
+
+    /// Computes $JF_1(u)^\top d = A^\top d$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `d` does not have $n_1$
+    /// elements or `result` does not have $n_u$ elements
+    pub fn jacobian_transpose_product(
+        &self,
+        _u: &[T],
+        d: &[T],
+        result: &mut [T],
+    ) -> FunctionCallResult {
+        Self::check_dimension("d", self.n1(), d.len())?;
+        Self::check_dimension("result", self.dimension(), result.len())?;
+        self.operator.apply_adjoint(d, result)
+    }
+}
@@ -1,6 +1,14 @@
-use crate::panoc::PANOCCache;
+use crate::{
+    alm::{alm_filter::AlmFilter, AlmCheckpoint},
+    core::{heap_size, OracleCalls, PhaseTimes, SolverStatistics, SolverTrace},
+    ensure,
+    panoc::PANOCCache,
+    pgbb::PGBBCache,
+    OpEnFloat, SolverError,
+};
+use std::num::NonZeroUsize;
 
-const DEFAULT_INITIAL_PENALTY: f64 = 10.0;
+pub(crate) const DEFAULT_INITIAL_PENALTY: f64 = 10.0;
 
 /// Cache for `AlmOptimizer` (to be allocated once)
 ///
@@ -11,26 +19,34 @@ const DEFAULT_INITIAL_PENALTY: f64 = 10.0;
 /// On the other hand, the problem data are provided in an instance
 /// of `AlmProblem`
 ///
+/// The type parameter `T` is the scalar type (`f64` by default)
+///
+/// `AlmCache` is `Send` and `Sync`, so caches can be allocated on one thread
+/// and used for solves on other threads.
+///
 #[derive(Debug)]
-pub struct AlmCache {
+pub struct AlmCache<T = f64> {
     /// PANOC cache for inner problems
-    pub(crate) panoc_cache: PANOCCache,
+    pub(crate) panoc_cache: PANOCCache<T>,
+    /// Cache of the projected gradient method with Barzilai-Borwein steps,
+    /// if it solves the inner problems instead of PANOC
+    pub(crate) pgbb_cache: Option<PGBBCache<T>>,
     /// Lagrange multipliers (next)
-    pub(crate) y_plus: Option<Vec<f64>>,
+    pub(crate) y_plus: Option<Vec<T>>,
     /// Vector $\xi^\nu = (c^\nu, y^\nu)$
-    pub(crate) xi: Option<Vec<f64>>,
+    pub(crate) xi: Option<Vec<T>>,
     /// Infeasibility related to ALM-type constraints
-    pub(crate) delta_y_norm: f64,
+    pub(crate) delta_y_norm: T,
     /// Delta y at iteration `nu+1`
-    pub(crate) delta_y_norm_plus: f64,
+    pub(crate) delta_y_norm_plus: T,
     /// Value $\Vert F_2(u^\nu) \Vert$
-    pub(crate) f2_norm: f64,
+    pub(crate) f2_norm: T,
     /// Value $\Vert F_2(u^{\nu+1}) \Vert$
-    pub(crate) f2_norm_plus: f64,
+    pub(crate) f2_norm_plus: T,
     /// Auxiliary variable `w`
-    pub(crate) w_alm_aux: Option<Vec<f64>>,
+    pub(crate) w_alm_aux: Option<Vec<T>>,
     /// Infeasibility related to PM-type constraints, `w_pm = F2(u)`
-    pub(crate) w_pm: Option<Vec<f64>>,
+    pub(crate) w_pm: Option<Vec<T>>,
     /// (Outer) iteration count
     pub(crate) iteration: usize,
     /// Counter for inner iterations
@@ -43,9 +59,22 @@ pub struct AlmCache {
     /// no bounds on the maximum time). The maximum time is specified,
     /// if at all, in `AlmOptimizer`
     pub(crate) available_time: Option<std::time::Duration>,
+    /// Evaluations of the user-defined functions and projections over all
+    /// outer and inner iterations
+    pub(crate) oracle_calls: OracleCalls,
+    /// Time spent in the phases of the inner solver over all inner problems
+    /// (if measured)
+    pub(crate) phase_times: PhaseTimes,
+    /// Record of the outer iterations (if the history is recorded)
+    pub(crate) trace: Option<SolverTrace>,
+    /// Filter of the outer iterations (if the filter is used)
+    pub(crate) filter: Option<AlmFilter>,
+    /// Statistics of the solves of `AlmOptimizer` (the statistics of the
+    /// inner problems are kept in the PANOC cache)
+    pub(crate) statistics: SolverStatistics,
 }
 
-impl AlmCache {
+impl<T: OpEnFloat> AlmCache<T> {
     /// Construct a new instance of `AlmCache`
     ///
     /// # Arguments
@@ -54,48 +83,207 @@ impl AlmCache {
     ///    the inner problem
     /// - `n1`, `n2`: range dimensions of mappings `F1` and `F2` respectively
     ///
+    /// If `n1 = 0` (e.g., in penalty-only problems), the memory for the
+    /// Lagrange multipliers and $F_1(u)$ is not allocated and `xi` contains
+    /// only the penalty parameter
+    ///
     /// # Panics
     ///
     /// Does not panic
     ///
-    pub fn new(panoc_cache: PANOCCache, n1: usize, n2: usize) -> Self {
+    pub fn new(mut panoc_cache: PANOCCache<T>, n1: usize, n2: usize) -> Self {
+        // the inner problems are solved up to an AKKT tolerance, whose memory
+        // is allocated here (the tolerance is set at the start of every solve)
+        panoc_cache.activate_akkt_tolerance(panoc_cache.tolerance);
         AlmCache {
             panoc_cache,
-            y_plus: if n1 > 0 { Some(vec![0.0; n1]) } else { None },
+            pgbb_cache: None,
+            y_plus: if n1 > 0 {
+                Some(vec![T::zero(); n1])
+            } else {
+                None
+            },
             // Allocate memory for xi = (c, y) if either n1 or n2 is nonzero,
             // otherwise, xi is None
             xi: if n1 + n2 > 0 {
-                let mut xi_init = vec![DEFAULT_INITIAL_PENALTY; 1];
-                xi_init.append(&mut vec![0.0; n1]);
+                let mut xi_init = vec![T::from_f64(DEFAULT_INITIAL_PENALTY); 1];
+                xi_init.append(&mut vec![T::zero(); n1]);
                 Some(xi_init)
             } else {
                 None
             },
             // w_alm_aux should be allocated only if n1 > 0
-            w_alm_aux: if n1 > 0 { Some(vec![0.0; n1]) } else { None },
+            w_alm_aux: if n1 > 0 {
+                Some(vec![T::zero(); n1])
+            } else {
+                None
+            },
             // w_pm is needed only if n2 > 0
-            w_pm: if n2 > 0 { Some(vec![0.0; n2]) } else { None },
+            w_pm: if n2 > 0 {
+                Some(vec![T::zero(); n2])
+            } else {
+                None
+            },
             iteration: 0,
-            delta_y_norm: 0.0,
-            delta_y_norm_plus: std::f64::INFINITY,
-            f2_norm: 0.0,
-            f2_norm_plus: std::f64::INFINITY,
+            delta_y_norm: T::zero(),
+            // without ALM-type constraints there are no multipliers to update
+            delta_y_norm_plus: if n1 > 0 { T::infinity() } else { T::zero() },
+            f2_norm: T::zero(),
+            f2_norm_plus: T::infinity(),
             inner_iteration_count: 0,
             last_inner_problem_norm_fpr: -1.0,
             available_time: None,
+            oracle_calls: OracleCalls::default(),
+            phase_times: PhaseTimes::default(),
+            trace: None,
+            filter: None,
+            statistics: SolverStatistics::default(),
         }
     }
 
+    /// Solves the inner problems with the projected gradient method with
+    /// Barzilai-Borwein steps (see `PGBBOptimizer`) instead of PANOC
+    ///
+    /// This allocates `3*n + 4` floats, where `n` is the problem size of the
+    /// `PANOCCache` of this cache; the PANOC cache still holds the tolerances
+    /// of the inner problems, but its L-BFGS buffer is not used, so it can be
+    /// constructed with a memory of 1.
+    ///
+    pub fn with_pgbb_inner_solver(mut self) -> Self {
+        let n = NonZeroUsize::new(self.panoc_cache.gradient_u.len())
+            .expect("PANOCCache has a positive problem size");
+        self.pgbb_cache = Some(
+            PGBBCache::new(n, self.panoc_cache.tolerance).expect("the PANOC tolerance is positive"),
+        );
+        self
+    }
+
+    /// Statistics of the solves which used `AlmOptimizer` with this cache (they are not reset
+    /// when a new problem is solved)
+    pub fn statistics(&self) -> &SolverStatistics {
+        &self.statistics
+    }
+
+    /// Resets the statistics of the solves (see `statistics`)
+    pub fn reset_statistics(&mut self) {
+        self.statistics.reset();
+    }
+
+    /// Number of bytes held by the cache, that is, the size of the structure
+    /// itself and of all its internal buffers, including those of the cache
+    /// of the inner solver (see `PANOCCache::memory_footprint`)
+    ///
+    /// The buffers are allocated upon construction, except for the record
+    /// of the outer iterations (see `AlmOptimizer::with_history`) and the
+    /// filter (see `AlmOptimizer::with_filter`), which grow with the outer
+    /// iterations and are included with their current size.
+    pub fn memory_footprint(&self) -> usize {
+        let vector_size = |vector: &Option<Vec<T>>| vector.as_ref().map_or(0, heap_size);
+        std::mem::size_of::<Self>()
+            + self.panoc_cache.heap_size()
+            + self.pgbb_cache.as_ref().map_or(0, PGBBCache::heap_size)
+            + vector_size(&self.y_plus)
+            + vector_size(&self.xi)
+            + vector_size(&self.w_alm_aux)
+            + vector_size(&self.w_pm)
+            + self.trace.as_ref().map_or(0, SolverTrace::heap_size)
+            + self.filter.as_ref().map_or(0, AlmFilter::heap_size)
+    }
+
+    /// Takes a checkpoint of the state of the outer iterations at the iterate
+    /// `u` (see `AlmCheckpoint`), typically after a solve which stopped at `u`
+    pub fn checkpoint(&self, u: &[T]) -> AlmCheckpoint {
+        AlmCheckpoint::new(
+            u,
+            self.xi.as_deref(),
+            self.iteration,
+            self.panoc_cache.akkt_tolerance,
+            self.delta_y_norm,
+            self.f2_norm,
+        )
+    }
+
+    /// Restores a checkpoint (see `AlmCheckpoint`); the next solve should
+    /// start from `checkpoint.iterate()`
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the dimensions of the
+    /// iterate or the Lagrange multipliers of the checkpoint are not those of
+    /// the cache and `SolverError::InvalidParameter` if the penalty parameter
+    /// or the inner tolerance of the checkpoint is not positive
+    pub fn restore(&mut self, checkpoint: &AlmCheckpoint) -> Result<(), SolverError> {
+        let mismatch = |name, expected, actual| SolverError::DimensionMismatch {
+            name,
+            expected,
+            actual,
+        };
+        let n = self.panoc_cache.gradient_u.len();
+        if checkpoint.iterate().len() != n {
+            return Err(mismatch("iterate", n, checkpoint.iterate().len()));
+        }
+        let y = checkpoint.lagrange_multipliers();
+        let n1 = self.y_plus.as_ref().map_or(0, Vec::len);
+        if y.len() != n1 {
+            return Err(mismatch("Lagrange multipliers", n1, y.len()));
+        }
+        ensure(
+            checkpoint
+                .inner_tolerance()
+                .is_none_or(|tolerance| tolerance > 0.0),
+            "inner tolerance",
+            "must be positive",
+        )?;
+        ensure(
+            self.xi.is_none() || checkpoint.penalty() > 0.0,
+            "penalty",
+            "must be positive",
+        )?;
+
+        if let Some(inner_tolerance) = checkpoint.inner_tolerance() {
+            self.panoc_cache
+                .activate_akkt_tolerance(T::from_f64(inner_tolerance));
+        }
+        if let Some(xi) = &mut self.xi {
+            xi[0] = T::from_f64(checkpoint.penalty());
+            xi[1..]
+                .iter_mut()
+                .zip(y)
+                .for_each(|(xi_i, &y_i)| *xi_i = T::from_f64(y_i));
+        }
+        if let Some(y_plus) = &mut self.y_plus {
+            y_plus
+                .iter_mut()
+                .zip(y)
+                .for_each(|(y_plus_i, &y_i)| *y_plus_i = T::from_f64(y_i));
+        }
+        self.iteration = checkpoint.outer_iterations();
+        self.delta_y_norm = T::from_f64(checkpoint.delta_y_norm());
+        self.delta_y_norm_plus = self.delta_y_norm;
+        self.f2_norm = T::from_f64(checkpoint.f2_norm());
+        self.f2_norm_plus = self.f2_norm;
+        Ok(())
+    }
+
     /// Resets the cache to its virgin state, and resets the stored instance
     /// of `PANOCCache`
     ///
     pub fn reset(&mut self) {
-        self.panoc_cache.reset();
+        self.reset_counters();
         self.iteration = 0;
-        self.f2_norm = 0.0;
-        self.f2_norm_plus = 0.0;
-        self.delta_y_norm = 0.0;
-        self.delta_y_norm_plus = 0.0;
+        self.f2_norm = T::zero();
+        self.f2_norm_plus = T::zero();
+        self.delta_y_norm = T::zero();
+        self.delta_y_norm_plus = T::zero();
+    }
+
+    /// Resets the stored instance of `PANOCCache` and the counters of inner
+    /// iterations and oracle calls, but keeps the state of the outer
+    /// iterations (used by the real-time iteration mode)
+    pub(crate) fn reset_counters(&mut self) {
+        self.panoc_cache.reset();
         self.inner_iteration_count = 0;
+        self.oracle_calls.reset();
+        self.phase_times = PhaseTimes::default();
     }
 }
@@ -0,0 +1,150 @@
+use crate::{
+    core::checkpoint::{Decoder, Encoder},
+    OpEnFloat, SolverError,
+};
+
+/// Header of the binary encoding of `AlmCheckpoint`
+const HEADER: &[u8; 8] = b"OpEnALM1";
+
+/// Checkpoint of the state of the ALM/PM algorithm, which can be saved and
+/// restored
+///
+/// A checkpoint holds the iterate, the vector of Lagrange multipliers $y$,
+/// the penalty parameter $c$, the number of outer iterations, the
+/// tolerance of the inner problems and the infeasibilities of the last outer
+/// iteration. It is taken with `AlmCache::checkpoint` (e.g., after a solve
+/// which ran out of time or iterations) and restored with
+/// `AlmCache::restore`, possibly in another process.
+///
+/// With the real-time iteration mode (see
+/// `AlmOptimizer::with_real_time_iterations`), the next solve resumes the
+/// outer iterations exactly where they stopped; otherwise, the Lagrange
+/// multipliers and the penalty parameter of the checkpoint are the warm
+/// start of the next solve. In both cases, the next solve should start from
+/// `checkpoint.iterate()`. The state of the inner solver is not part of the
+/// checkpoint, because it is reset at every outer iteration (see
+/// `PanocCheckpoint` to checkpoint PANOC itself).
+///
+/// Checkpoints can be converted to bytes (with `to_bytes` and `from_bytes`)
+/// and, with the feature `serde`, serialized with serde.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlmCheckpoint {
+    /// iterate
+    iterate: Vec<f64>,
+    /// penalty parameter (zero if there are no ALM/PM-type constraints)
+    penalty: f64,
+    /// Lagrange multipliers
+    lagrange_multipliers: Vec<f64>,
+    /// number of outer iterations
+    outer_iterations: usize,
+    /// tolerance of the inner problems (if it is decreasing)
+    inner_tolerance: Option<f64>,
+    /// norm of the last update of the Lagrange multipliers
+    delta_y_norm: f64,
+    /// norm of F2 at the last outer iteration
+    f2_norm: f64,
+}
+
+impl AlmCheckpoint {
+    /// Constructs a checkpoint from the state of the cache (the values are
+    /// stored as `f64`)
+    pub(crate) fn new<T: OpEnFloat>(
+        iterate: &[T],
+        xi: Option<&[T]>,
+        outer_iterations: usize,
+        inner_tolerance: Option<T>,
+        delta_y_norm: T,
+        f2_norm: T,
+    ) -> Self {
+        let to_f64 = |x: &[T]| x.iter().map(|x_i| x_i.as_f64()).collect();
+        let (penalty, lagrange_multipliers) = match xi {
+            Some(xi) => (xi[0].as_f64(), to_f64(&xi[1..])),
+            None => (0.0, Vec::new()),
+        };
+        AlmCheckpoint {
+            iterate: to_f64(iterate),
+            penalty,
+            lagrange_multipliers,
+            outer_iterations,
+            inner_tolerance: inner_tolerance.map(T::as_f64),
+            delta_y_norm: delta_y_norm.as_f64(),
+            f2_norm: f2_norm.as_f64(),
+        }
+    }
+
+    /// Iterate of the checkpoint, from which the solver should resume
+    pub fn iterate(&self) -> &[f64] {
+        &self.iterate
+    }
+
+    /// Penalty parameter of the checkpoint (zero if there are no ALM/PM-type
+    /// constraints)
+    pub fn penalty(&self) -> f64 {
+        self.penalty
+    }
+
+    /// Lagrange multipliers of the checkpoint
+    pub fn lagrange_multipliers(&self) -> &[f64] {
+        &self.lagrange_multipliers
+    }
+
+    /// Number of outer iterations before the checkpoint
+    pub fn outer_iterations(&self) -> usize {
+        self.outer_iterations
+    }
+
+    pub(crate) fn inner_tolerance(&self) -> Option<f64> {
+        self.inner_tolerance
+    }
+
+    pub(crate) fn delta_y_norm(&self) -> f64 {
+        self.delta_y_norm
+    }
+
+    pub(crate) fn f2_norm(&self) -> f64 {
+        self.f2_norm
+    }
+
+    /// Encodes the checkpoint as bytes (see `from_bytes`)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new(HEADER);
+        encoder.vector(&self.iterate);
+        encoder.f64(self.penalty);
+        encoder.vector(&self.lagrange_multipliers);
+        encoder.usize(self.outer_iterations);
+        encoder.bool(self.inner_tolerance.is_some());
+        encoder.f64(self.inner_tolerance.unwrap_or(0.0));
+        encoder.f64(self.delta_y_norm);
+        encoder.f64(self.f2_norm);
+        encoder.into_bytes()
+    }
+
+    /// Decodes a checkpoint which was encoded with `to_bytes`
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `bytes` is not a valid
+    /// checkpoint of the ALM/PM algorithm
+    pub fn from_bytes(bytes: &[u8]) -> Result<AlmCheckpoint, SolverError> {
+        let mut decoder = Decoder::new(bytes, HEADER)?;
+        let iterate = decoder.vector()?;
+        let penalty = decoder.f64()?;
+        let lagrange_multipliers = decoder.vector()?;
+        let outer_iterations = decoder.usize()?;
+        let has_inner_tolerance = decoder.bool()?;
+        let inner_tolerance = Some(decoder.f64()?).filter(|_| has_inner_tolerance);
+        let delta_y_norm = decoder.f64()?;
+        let f2_norm = decoder.f64()?;
+        decoder.finish()?;
+        Ok(AlmCheckpoint {
+            iterate,
+            penalty,
+            lagrange_multipliers,
+            outer_iterations,
+            inner_tolerance,
+            delta_y_norm,
+            f2_norm,
+        })
+    }
+}
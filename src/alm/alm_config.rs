@@ -0,0 +1,166 @@
+use crate::alm::alm_optimizer::{
+    DEFAULT_DELTA_TOLERANCE, DEFAULT_EPSILON_TOLERANCE, DEFAULT_EPSILON_UPDATE_FACTOR,
+    DEFAULT_INFEAS_SUFFICIENT_DECREASE_FACTOR, DEFAULT_INITIAL_TOLERANCE,
+    DEFAULT_MAX_INNER_ITERATIONS, DEFAULT_MAX_OUTER_ITERATIONS, DEFAULT_PENALTY_UPDATE_FACTOR,
+};
+use std::fmt;
+
+/// Configuration of [`AlmOptimizer`]
+///
+/// This structure mirrors the `with_*` setter methods of `AlmOptimizer` and
+/// can be applied to an optimizer using [`AlmOptimizer::with_config`]. The
+/// default values of the fields are the default values of `AlmOptimizer`.
+/// Optional fields which are `None` are not applied, that is, the values
+/// stored in the [`AlmCache`] are kept.
+///
+/// If OpEn is compiled with the feature `serde`, `AlmConfig` can be
+/// deserialized, e.g., from a JSON or a TOML file, so that tolerances and
+/// iteration limits can be tuned without recompiling. Missing fields take
+/// their default values, so a file such as
+///
+/// ```json
+/// { "max_outer_iterations": 20, "epsilon_tolerance": 1e-5 }
+/// ```
+///
+/// is a valid configuration. Configurations can also be compared (they
+/// implement `PartialEq`) and displayed, e.g., to log the configuration of a
+/// solve.
+///
+/// # Example
+///
+/// ```ignore
+/// let json = std::fs::read_to_string("alm.json")?;
+/// let config: AlmConfig = serde_json::from_str(&json)?;
+/// let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+///     .with_config(&config)?;
+/// ```
+///
+/// [`AlmOptimizer`]: struct.AlmOptimizer.html
+/// [`AlmOptimizer::with_config`]: struct.AlmOptimizer.html#method.with_config
+/// [`AlmCache`]: struct.AlmCache.html
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AlmConfig {
+    /// Maximum number of outer iterations (default: `50`)
+    pub max_outer_iterations: usize,
+    /// Maximum number of inner iterations (default: `5000`)
+    pub max_inner_iterations: usize,
+    /// Maximum duration (default: `None`, that is, no limit)
+    pub max_duration: Option<std::time::Duration>,
+    /// Tolerance $\delta$ (infeasibility; default: `1e-4`)
+    pub delta_tolerance: f64,
+    /// Tolerance $\epsilon$ (tolerance of the inner problems; default: `1e-6`)
+    pub epsilon_tolerance: f64,
+    /// Penalty update factor (default: `5.0`)
+    pub penalty_update_factor: f64,
+    /// Update factor of the inner tolerance (default: `0.1`)
+    pub inner_tolerance_update_factor: f64,
+    /// Initial inner tolerance, $\epsilon_0$ (default: `0.1`)
+    pub initial_inner_tolerance: f64,
+    /// Sufficient decrease coefficient (default: `0.1`)
+    pub sufficient_decrease_coefficient: f64,
+    /// Initial vector of Lagrange multipliers, $y^0$, of length `n1`
+    /// (default: `None`, that is, the multipliers of the cache are kept)
+    pub initial_lagrange_multipliers: Option<Vec<f64>>,
+    /// Initial penalty parameter, $c_0$ (default: `None`, that is, the
+    /// penalty parameter of the cache is kept)
+    pub initial_penalty: Option<f64>,
+    /// Whether to screen the outputs of user-defined functions for NaN/infinite
+    /// values (see `AlmOptimizer::with_nan_screening`)
+    pub nan_screening: bool,
+    /// Whether to record the history of the outer iterations (see
+    /// `AlmOptimizer::with_history`)
+    pub record_history: bool,
+    /// Whether to use the quadratic penalty method, that is, to freeze the
+    /// Lagrange multipliers at zero (see `AlmOptimizer::with_penalty_method`)
+    pub penalty_method: bool,
+    /// Whether to govern the outer iterations with a filter (see
+    /// `AlmOptimizer::with_filter`)
+    pub filter: bool,
+    /// Whether to use the real-time iteration mode, with a budget of
+    /// `max_outer_iterations` outer and `max_inner_iterations` inner
+    /// iterations per call (see `AlmOptimizer::with_real_time_iterations`)
+    pub real_time_iterations: bool,
+}
+
+impl Default for AlmConfig {
+    fn default() -> Self {
+        AlmConfig {
+            max_outer_iterations: DEFAULT_MAX_OUTER_ITERATIONS,
+            max_inner_iterations: DEFAULT_MAX_INNER_ITERATIONS,
+            max_duration: None,
+            delta_tolerance: DEFAULT_DELTA_TOLERANCE,
+            epsilon_tolerance: DEFAULT_EPSILON_TOLERANCE,
+            penalty_update_factor: DEFAULT_PENALTY_UPDATE_FACTOR,
+            inner_tolerance_update_factor: DEFAULT_EPSILON_UPDATE_FACTOR,
+            initial_inner_tolerance: DEFAULT_INITIAL_TOLERANCE,
+            sufficient_decrease_coefficient: DEFAULT_INFEAS_SUFFICIENT_DECREASE_FACTOR,
+            initial_lagrange_multipliers: None,
+            initial_penalty: None,
+            nan_screening: false,
+            record_history: false,
+            penalty_method: false,
+            filter: false,
+            real_time_iterations: false,
+        }
+    }
+}
+
+impl fmt::Display for AlmConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "max outer iterations     : {}",
+            self.max_outer_iterations
+        )?;
+        writeln!(
+            f,
+            "max inner iterations     : {}",
+            self.max_inner_iterations
+        )?;
+        match self.max_duration {
+            Some(max_duration) => writeln!(f, "max duration             : {:?}", max_duration)?,
+            None => writeln!(f, "max duration             : none")?,
+        }
+        writeln!(f, "delta tolerance          : {:e}", self.delta_tolerance)?;
+        writeln!(f, "epsilon tolerance        : {:e}", self.epsilon_tolerance)?;
+        writeln!(
+            f,
+            "penalty update factor    : {}",
+            self.penalty_update_factor
+        )?;
+        writeln!(
+            f,
+            "inner tolerance factor   : {}",
+            self.inner_tolerance_update_factor
+        )?;
+        writeln!(
+            f,
+            "initial inner tolerance  : {:e}",
+            self.initial_inner_tolerance
+        )?;
+        writeln!(
+            f,
+            "sufficient decrease      : {}",
+            self.sufficient_decrease_coefficient
+        )?;
+        match &self.initial_lagrange_multipliers {
+            Some(y0) => writeln!(f, "initial multipliers      : {:?}", y0)?,
+            None => writeln!(f, "initial multipliers      : (cache)")?,
+        }
+        match self.initial_penalty {
+            Some(c0) => writeln!(f, "initial penalty          : {}", c0)?,
+            None => writeln!(f, "initial penalty          : (cache)")?,
+        }
+        writeln!(f, "NaN screening            : {}", self.nan_screening)?;
+        writeln!(f, "record history           : {}", self.record_history)?;
+        writeln!(f, "penalty method           : {}", self.penalty_method)?;
+        writeln!(f, "filter                   : {}", self.filter)?;
+        write!(
+            f,
+            "real-time iterations     : {}",
+            self.real_time_iterations
+        )
+    }
+}
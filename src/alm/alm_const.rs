@@ -0,0 +1,599 @@
+//! Heap-free ALM/PM with compile-time dimensions
+//!
+//! [`AlmCacheConst`] stores all the buffers of the ALM/PM algorithm in
+//! arrays: the problem size `N`, the memory `MEM` of the L-BFGS buffer of the
+//! inner solver and the range dimensions `N1` and `N2` of the mappings $F_1$
+//! and $F_2$ are known at compile time. [`AlmOptimizerConst`] solves an
+//! [`AlmProblem`] using such a cache, solving the inner problems with
+//! `PANOCOptimizerConst`, so it does not allocate memory on the heap.
+//!
+//! This optimizer supports the termination criteria and the updates of the
+//! penalty parameter and the inner tolerances of `AlmOptimizer`, but not NaN
+//! screening, histories, progress reports, filters, the penalty-method mode or
+//! real-time iterations. Since `AlmOptimizerStatus` stores the Lagrange
+//! multipliers in a vector, the status returned by `AlmOptimizerConst::solve`
+//! does not contain them; they are available from
+//! `AlmCacheConst::lagrange_multipliers`.
+//!
+//! [`AlmCacheConst`]: struct.AlmCacheConst.html
+//! [`AlmOptimizerConst`]: struct.AlmOptimizerConst.html
+//! [`AlmProblem`]: struct.AlmProblem.html
+use crate::{
+    alm::{alm_cache::DEFAULT_INITIAL_PENALTY, alm_optimizer::*, AlmOptimizerStatus, AlmProblem},
+    constraints,
+    core::{
+        panoc::{PANOCCacheConst, PANOCOptimizerConst},
+        Clock, ExitStatus, Optimizer, OracleCalls, Problem, SolverStatus, StdClock,
+    },
+    ensure, matrix_operations, FunctionCallResult, SolverError,
+};
+
+const SMALL_EPSILON: f64 = f64::EPSILON;
+
+/// Vector $\xi = (c, y)$, which is stored contiguously
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct XiConst<const N1: usize> {
+    c: f64,
+    y: [f64; N1],
+}
+
+impl<const N1: usize> XiConst<N1> {
+    fn as_slice(&self) -> &[f64] {
+        // SAFETY: `XiConst` is `repr(C)` and consists of `1 + N1` values of
+        // type `f64`, so it has the layout of `[f64; 1 + N1]`
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const f64, 1 + N1) }
+    }
+}
+
+/// Cache of the heap-free ALM/PM optimizer, whose buffers are arrays
+///
+/// The problem size is `N`, the memory of the L-BFGS buffer of the inner
+/// solver is `MEM` and the range dimensions of $F_1$ and $F_2$ are `N1` and
+/// `N2` (see `AlmCache`)
+#[derive(Debug, Clone)]
+pub struct AlmCacheConst<const N: usize, const MEM: usize, const N1: usize, const N2: usize> {
+    panoc_cache: PANOCCacheConst<N, MEM>,
+    xi: XiConst<N1>,
+    y_plus: [f64; N1],
+    w_alm_aux: [f64; N1],
+    w_pm: [f64; N2],
+    delta_y_norm: f64,
+    delta_y_norm_plus: f64,
+    f2_norm: f64,
+    f2_norm_plus: f64,
+    iteration: usize,
+    inner_iteration_count: usize,
+    last_inner_problem_norm_fpr: f64,
+    available_time: Option<std::time::Duration>,
+    oracle_calls: OracleCalls,
+}
+
+impl<const N: usize, const MEM: usize, const N1: usize, const N2: usize>
+    AlmCacheConst<N, MEM, N1, N2>
+{
+    /// Constructs a new cache, whose inner solver has the given tolerance
+    /// (see `PANOCCacheConst::new`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not
+    /// positive, or if `N` or `MEM` is zero
+    pub fn new(tolerance: f64) -> Result<Self, SolverError> {
+        Ok(AlmCacheConst {
+            panoc_cache: PANOCCacheConst::new(tolerance)?,
+            xi: XiConst {
+                c: DEFAULT_INITIAL_PENALTY,
+                y: [0.0; N1],
+            },
+            y_plus: [0.0; N1],
+            w_alm_aux: [0.0; N1],
+            w_pm: [0.0; N2],
+            delta_y_norm: 0.0,
+            delta_y_norm_plus: f64::INFINITY,
+            f2_norm: 0.0,
+            f2_norm_plus: f64::INFINITY,
+            iteration: 0,
+            inner_iteration_count: 0,
+            last_inner_problem_norm_fpr: -1.0,
+            available_time: None,
+            oracle_calls: OracleCalls::default(),
+        })
+    }
+
+    /// Lagrange multipliers computed by the last call of
+    /// `AlmOptimizerConst::solve`
+    pub fn lagrange_multipliers(&self) -> &[f64; N1] {
+        &self.y_plus
+    }
+
+    /// Resets the cache to its initial state (see `AlmCache::reset`)
+    pub fn reset(&mut self) {
+        self.panoc_cache.reset();
+        self.inner_iteration_count = 0;
+        self.oracle_calls.reset();
+        self.iteration = 0;
+        self.f2_norm = 0.0;
+        self.f2_norm_plus = 0.0;
+        self.delta_y_norm = 0.0;
+        self.delta_y_norm_plus = 0.0;
+    }
+
+    /// Vector $\xi = (c, y)$, which is empty if there are no ALM/PM-type
+    /// constraints
+    fn xi(&self) -> &[f64] {
+        if N1 + N2 > 0 {
+            self.xi.as_slice()
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Heap-free ALM/PM optimizer, which uses an [`AlmCacheConst`]
+///
+/// See `AlmOptimizer` for details on the algorithm.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{alm::*, constraints::*, core::ExitStatus, FunctionCallResult, SolverError};
+///
+/// // minimize (u1 - 2)^2 + (u2 - 2)^2 subject to u1 + u2 = 1 (F2(u) = u1 + u2 - 1),
+/// // with psi(u; xi) = f(u) + (c/2) F2(u)^2
+/// let psi = |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+///     let f2 = u[0] + u[1] - 1.0;
+///     *cost = (u[0] - 2.0).powi(2) + (u[1] - 2.0).powi(2) + 0.5 * xi[0] * f2 * f2;
+///     Ok(())
+/// };
+/// let d_psi = |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+///     let f2 = u[0] + u[1] - 1.0;
+///     grad[0] = 2.0 * (u[0] - 2.0) + xi[0] * f2;
+///     grad[1] = 2.0 * (u[1] - 2.0) + xi[0] * f2;
+///     Ok(())
+/// };
+/// let f2 = |u: &[f64], res: &mut [f64]| -> FunctionCallResult {
+///     res[0] = u[0] + u[1] - 1.0;
+///     Ok(())
+/// };
+/// let bounds = NoConstraints::new();
+/// let problem = AlmProblem::new(
+///     bounds, NO_SET, NO_SET, psi, d_psi, NO_MAPPING, Some(f2), 0, 1,
+/// )?;
+/// let mut cache = AlmCacheConst::<2, 3, 0, 1>::new(1e-6)?;
+/// let mut u = [0.0; 2];
+/// let status = AlmOptimizerConst::new(&mut cache, problem)?
+///     .with_delta_tolerance(1e-6)?
+///     .solve(&mut u)?;
+/// assert_eq!(status.exit_status(), ExitStatus::Converged);
+/// assert!((u[0] - 0.5).abs() < 1e-4);
+/// # Ok::<(), SolverError>(())
+/// ```
+///
+/// [`AlmCacheConst`]: struct.AlmCacheConst.html
+pub struct AlmOptimizerConst<
+    'life,
+    MappingAlm,
+    MappingPm,
+    ParametricGradientType,
+    ParametricCostType,
+    ConstraintsType,
+    AlmSetC,
+    LagrangeSetY,
+    const N: usize,
+    const MEM: usize,
+    const N1: usize,
+    const N2: usize,
+> where
+    MappingAlm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    MappingPm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[f64], &[f64], &mut f64) -> FunctionCallResult,
+    ConstraintsType: constraints::Constraint,
+    AlmSetC: constraints::Constraint,
+    LagrangeSetY: constraints::Constraint,
+{
+    alm_cache: &'life mut AlmCacheConst<N, MEM, N1, N2>,
+    alm_problem: AlmProblem<
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+    >,
+    max_outer_iterations: usize,
+    max_inner_iterations: usize,
+    max_duration: Option<std::time::Duration>,
+    epsilon_tolerance: f64,
+    delta_tolerance: f64,
+    penalty_update_factor: f64,
+    epsilon_update_factor: f64,
+    sufficient_decrease_coeff: f64,
+    epsilon_inner_initial: f64,
+    clock: &'life dyn Clock,
+}
+
+impl<
+        'life,
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+        const N: usize,
+        const MEM: usize,
+        const N1: usize,
+        const N2: usize,
+    >
+    AlmOptimizerConst<
+        'life,
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+        N,
+        MEM,
+        N1,
+        N2,
+    >
+where
+    MappingAlm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    MappingPm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[f64], &[f64], &mut f64) -> FunctionCallResult,
+    ConstraintsType: constraints::Constraint,
+    AlmSetC: constraints::Constraint,
+    LagrangeSetY: constraints::Constraint,
+{
+    /// Constructs a new heap-free ALM/PM optimizer (see `AlmOptimizer::new`)
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the range dimensions of the
+    /// mappings of `alm_problem` are not `N1` and `N2`
+    pub fn new(
+        alm_cache: &'life mut AlmCacheConst<N, MEM, N1, N2>,
+        alm_problem: AlmProblem<
+            MappingAlm,
+            MappingPm,
+            ParametricGradientType,
+            ParametricCostType,
+            ConstraintsType,
+            AlmSetC,
+            LagrangeSetY,
+        >,
+    ) -> Result<Self, SolverError> {
+        if alm_problem.n1 != N1 {
+            return Err(SolverError::DimensionMismatch {
+                name: "n1",
+                expected: N1,
+                actual: alm_problem.n1,
+            });
+        }
+        if alm_problem.n2 != N2 {
+            return Err(SolverError::DimensionMismatch {
+                name: "n2",
+                expected: N2,
+                actual: alm_problem.n2,
+            });
+        }
+        Ok(AlmOptimizerConst {
+            alm_cache,
+            alm_problem,
+            max_outer_iterations: DEFAULT_MAX_OUTER_ITERATIONS,
+            max_inner_iterations: DEFAULT_MAX_INNER_ITERATIONS,
+            max_duration: None,
+            epsilon_tolerance: DEFAULT_EPSILON_TOLERANCE,
+            delta_tolerance: DEFAULT_DELTA_TOLERANCE,
+            penalty_update_factor: DEFAULT_PENALTY_UPDATE_FACTOR,
+            epsilon_update_factor: DEFAULT_EPSILON_UPDATE_FACTOR,
+            sufficient_decrease_coeff: DEFAULT_INFEAS_SUFFICIENT_DECREASE_FACTOR,
+            epsilon_inner_initial: DEFAULT_INITIAL_TOLERANCE,
+            clock: &StdClock,
+        })
+    }
+
+    /// Sets the maximum number of outer iterations
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `max_outer_iterations` is zero
+    pub fn with_max_outer_iterations(
+        mut self,
+        max_outer_iterations: usize,
+    ) -> Result<Self, SolverError> {
+        ensure(
+            max_outer_iterations > 0,
+            "max_outer_iterations",
+            "must be positive",
+        )?;
+        self.max_outer_iterations = max_outer_iterations;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations of the inner problems
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `max_inner_iterations` is zero
+    pub fn with_max_inner_iterations(
+        mut self,
+        max_inner_iterations: usize,
+    ) -> Result<Self, SolverError> {
+        ensure(
+            max_inner_iterations > 0,
+            "max_inner_iterations",
+            "must be positive",
+        )?;
+        self.max_inner_iterations = max_inner_iterations;
+        Ok(self)
+    }
+
+    /// Sets the maximum duration
+    pub fn with_max_duration(mut self, max_duration: std::time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Sets the clock which measures the solution time, of the inner problems
+    /// too (see `Clock`); the default is `StdClock`
+    pub fn with_clock(mut self, clock: &'life dyn Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the delta tolerance
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the tolerance is not positive
+    pub fn with_delta_tolerance(mut self, delta_tolerance: f64) -> Result<Self, SolverError> {
+        ensure(delta_tolerance > 0.0, "delta_tolerance", "must be positive")?;
+        self.delta_tolerance = delta_tolerance;
+        Ok(self)
+    }
+
+    /// Sets the epsilon tolerance
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the tolerance is not positive
+    pub fn with_epsilon_tolerance(mut self, epsilon_tolerance: f64) -> Result<Self, SolverError> {
+        ensure(
+            epsilon_tolerance > 0.0,
+            "epsilon_tolerance",
+            "must be positive",
+        )?;
+        self.epsilon_tolerance = epsilon_tolerance;
+        Ok(self)
+    }
+
+    /// Sets the initial penalty parameter
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `c0` is not larger than
+    /// `f64::EPSILON`
+    pub fn with_initial_penalty(self, c0: f64) -> Result<Self, SolverError> {
+        ensure(c0 > SMALL_EPSILON, "c0", "must be larger than f64::EPSILON")?;
+        self.alm_cache.xi.c = c0;
+        Ok(self)
+    }
+
+    /// y_plus ← y + c [F1(u) - Proj_C(F1(u) + y/c)]
+    fn update_lagrange_multipliers(&mut self, u: &[f64]) -> FunctionCallResult {
+        let problem = &mut self.alm_problem;
+        let cache = &mut *self.alm_cache;
+        if let (Some(f1), Some(alm_set_c)) = (&mut problem.mapping_f1, &problem.alm_set_c) {
+            cache.oracle_calls.mapping_f1 += 1;
+            f1(u, &mut cache.w_alm_aux)?;
+            let (c, y) = (cache.xi.c, &cache.xi.y);
+            cache
+                .y_plus
+                .iter_mut()
+                .zip(y.iter())
+                .zip(cache.w_alm_aux.iter())
+                .for_each(|((y_plus_i, y_i), w_alm_aux_i)| *y_plus_i = w_alm_aux_i + y_i / c);
+            cache.oracle_calls.projections += 1;
+            alm_set_c.project(&mut cache.y_plus);
+            cache
+                .y_plus
+                .iter_mut()
+                .zip(y.iter())
+                .zip(cache.w_alm_aux.iter())
+                .for_each(|((y_plus_i, y_i), w_alm_aux_i)| {
+                    *y_plus_i = y_i + c * (w_alm_aux_i - *y_plus_i)
+                });
+            cache.delta_y_norm_plus =
+                matrix_operations::norm2_squared_diff(&cache.y_plus, y).sqrt();
+        }
+        Ok(())
+    }
+
+    /// w_pm ← F2(u) and its norm
+    fn compute_pm_infeasibility(&mut self, u: &[f64]) -> FunctionCallResult {
+        let cache = &mut *self.alm_cache;
+        if let Some(f2) = &mut self.alm_problem.mapping_f2 {
+            cache.oracle_calls.mapping_f2 += 1;
+            f2(u, &mut cache.w_pm)?;
+            cache.f2_norm_plus = matrix_operations::norm2(&cache.w_pm);
+        }
+        Ok(())
+    }
+
+    fn solve_inner_problem(&mut self, u: &mut [f64]) -> Result<SolverStatus, SolverError> {
+        let alm_problem = &mut self.alm_problem;
+        let cache = &mut *self.alm_cache;
+        let xi = if N1 + N2 > 0 {
+            cache.xi.as_slice()
+        } else {
+            &[]
+        };
+        let parametric_cost = &mut alm_problem.parametric_cost;
+        let psi = |u: &[f64], psi_val: &mut f64| -> FunctionCallResult {
+            parametric_cost(u, xi, psi_val)
+        };
+        let parametric_gradient = &mut alm_problem.parametric_gradient;
+        let psi_grad = |u: &[f64], psi_grad: &mut [f64]| -> FunctionCallResult {
+            parametric_gradient(u, xi, psi_grad)
+        };
+        let inner_problem = Problem::new(&alm_problem.constraints, psi_grad, psi);
+        let max_duration = cache
+            .available_time
+            .unwrap_or_else(|| std::time::Duration::from_secs(u64::MAX));
+        PANOCOptimizerConst::new(inner_problem, &mut cache.panoc_cache)
+            .with_max_duration(max_duration)
+            .with_clock(self.clock)
+            .with_max_iter(self.max_inner_iterations)?
+            .solve(u)
+    }
+
+    fn is_exit_criterion_satisfied(&self) -> bool {
+        let cache = &*self.alm_cache;
+        let criterion_1 = N1 == 0
+            || (cache.iteration > 0
+                && cache.delta_y_norm_plus <= cache.xi.c * self.delta_tolerance + SMALL_EPSILON);
+        let criterion_2 = N2 == 0 || cache.f2_norm_plus <= self.delta_tolerance + SMALL_EPSILON;
+        let criterion_3 = cache
+            .panoc_cache
+            .akkt_tolerance
+            .is_some_and(|akkt_tolerance| akkt_tolerance <= self.epsilon_tolerance + SMALL_EPSILON);
+        criterion_1 && criterion_2 && criterion_3
+    }
+
+    fn is_penalty_stall_criterion(&self) -> bool {
+        let cache = &*self.alm_cache;
+        if cache.iteration == 0 {
+            return true;
+        }
+        let criterion_alm = N1 == 0
+            || cache.delta_y_norm_plus
+                <= self.sufficient_decrease_coeff * cache.delta_y_norm + SMALL_EPSILON;
+        let criterion_pm = N2 == 0
+            || cache.f2_norm_plus <= self.sufficient_decrease_coeff * cache.f2_norm + SMALL_EPSILON;
+        N1 + N2 > 0 && criterion_alm && criterion_pm
+    }
+
+    /// Step of the ALM/PM algorithm (see `AlmOptimizer::step`); returns whether
+    /// the outer iterations should continue and the exit status of the inner
+    /// problem
+    fn step(&mut self, u: &mut [f64]) -> Result<(bool, ExitStatus), SolverError> {
+        if let Some(y_set) = &self.alm_problem.alm_set_y {
+            self.alm_cache.oracle_calls.projections += 1;
+            y_set.project(&mut self.alm_cache.xi.y);
+        }
+        let status = self.solve_inner_problem(u)?;
+        let cache = &mut *self.alm_cache;
+        cache.last_inner_problem_norm_fpr = status.norm_fpr();
+        cache.inner_iteration_count += status.iterations();
+        cache.oracle_calls += status.oracle_calls();
+        let inner_exit_status = status.exit_status();
+
+        self.update_lagrange_multipliers(u)?;
+        self.compute_pm_infeasibility(u)?;
+        if self.is_exit_criterion_satisfied() {
+            return Ok((false, inner_exit_status));
+        }
+        if !self.is_penalty_stall_criterion() {
+            self.alm_cache.xi.c *= self.penalty_update_factor;
+        }
+        let cache = &mut *self.alm_cache;
+        let akkt_tolerance = cache
+            .panoc_cache
+            .akkt_tolerance
+            .unwrap_or(self.epsilon_inner_initial);
+        cache.panoc_cache.activate_akkt_tolerance(f64::max(
+            akkt_tolerance * self.epsilon_update_factor,
+            self.epsilon_tolerance,
+        ));
+        cache.iteration += 1;
+        cache.delta_y_norm = cache.delta_y_norm_plus;
+        cache.f2_norm = cache.f2_norm_plus;
+        cache.xi.y = cache.y_plus;
+        cache.panoc_cache.reset();
+        Ok((true, inner_exit_status))
+    }
+
+    /// Cost $f(u) = \psi(u; (0, y))$ at `u`
+    fn compute_cost_at_solution(&mut self, u: &[f64]) -> Result<f64, SolverError> {
+        let cache = &mut *self.alm_cache;
+        let c = cache.xi.c;
+        cache.xi.c = 0.0;
+        let mut cost_value = 0.0;
+        cache.oracle_calls.cost += 1;
+        let result = (self.alm_problem.parametric_cost)(u, cache.xi(), &mut cost_value);
+        cache.xi.c = c;
+        result.map(|_| cost_value)
+    }
+
+    /// Solves the specified ALM problem (see `AlmOptimizer::solve`)
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the length of `u` is not
+    /// `N`, or the errors of the inner solver
+    pub fn solve(&mut self, u: &mut [f64]) -> Result<AlmOptimizerStatus, SolverError> {
+        let tic = self.clock.now();
+        if u.len() != N {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: N,
+                actual: u.len(),
+            });
+        }
+        self.alm_cache.reset();
+        self.alm_cache
+            .panoc_cache
+            .activate_akkt_tolerance(self.epsilon_inner_initial);
+        self.alm_cache.available_time = self.max_duration;
+
+        let mut num_outer_iterations = 0;
+        let mut exit_status = ExitStatus::Converged;
+        let (mut continue_iterating, mut inner_exit_status) = (false, ExitStatus::Converged);
+        for _outer_iters in 1..=self.max_outer_iterations {
+            if let Some(max_duration) = self.max_duration {
+                let available_time_left = max_duration.checked_sub(self.clock.elapsed(tic));
+                self.alm_cache.available_time = available_time_left;
+                if available_time_left.is_none() {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+            num_outer_iterations += 1;
+            (continue_iterating, inner_exit_status) = self.step(u)?;
+            if inner_exit_status == ExitStatus::NotConvergedOutOfTime {
+                exit_status = ExitStatus::NotConvergedOutOfTime;
+                break;
+            }
+            if !continue_iterating {
+                break;
+            }
+        }
+        if exit_status != ExitStatus::NotConvergedOutOfTime {
+            exit_status = inner_exit_status;
+        }
+        if num_outer_iterations == self.max_outer_iterations && continue_iterating {
+            exit_status = ExitStatus::NotConvergedIterations;
+        }
+
+        let cost = self.compute_cost_at_solution(u)?;
+        let cache = &*self.alm_cache;
+        Ok(AlmOptimizerStatus::new(exit_status)
+            .with_solve_time(self.clock.elapsed(tic))
+            .with_inner_iterations(cache.inner_iteration_count)
+            .with_outer_iterations(num_outer_iterations)
+            .with_last_problem_norm_fpr(cache.last_inner_problem_norm_fpr)
+            .with_delta_y_norm(cache.delta_y_norm_plus)
+            .with_f2_norm(cache.f2_norm_plus)
+            .with_penalty(if N1 + N2 > 0 { cache.xi.c } else { 0.0 })
+            .with_cost(cost)
+            .with_oracle_calls(cache.oracle_calls))
+    }
+}
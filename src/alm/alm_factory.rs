@@ -5,7 +5,11 @@
 /*        prepares psi and d_psi, which can be used to define an AlmOptimizer   */
 /* ---------------------------------------------------------------------------- */
 
-use crate::{constraints::Constraint, matrix_operations, FunctionCallResult};
+use super::KktResidual;
+use crate::{
+    constraints::Constraint, core::OracleCalls, ensure, matrix_operations, FunctionCallResult,
+    SolverError,
+};
 
 /// Prepares function $\psi$ and its gradient given the problem data: $f$, $\nabla{}f$,
 /// and optionally $F_1$, $JF_1$, $C$ and $F_2$
@@ -130,12 +134,19 @@ where
     /// - `set_c` (optional) set $C$ or `NO_SET`
     /// - `n2` image dimension of $F_2$ (can be 0)
     ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `mapping_f2` is provided while
+    /// `n2 = 0` (or is missing while `n2 > 0`), or if a mapping is provided
+    /// without its Jacobian (or vice versa), or if $F_1$ is provided without a
+    /// set $C$ (or vice versa)
+    ///
     /// # Example
     ///
     /// ```rust
     /// use optimization_engine::{constraints::Ball2, alm::*, FunctionCallResult};
     ///
-    /// let set_c = Ball2::new(None, 1.0);
+    /// let set_c = Ball2::new(None, 1.0).unwrap();
     /// let n2 = 0;
     ///
     /// let f = |u: &[f64], cost: &mut f64| -> FunctionCallResult { Ok(()) };
@@ -152,7 +163,8 @@ where
     ///     NO_JACOBIAN_MAPPING,
     ///     Some(set_c),
     ///     n2,
-    /// );
+    /// )
+    /// .unwrap();
     /// ```
     ///
     pub fn new(
@@ -164,24 +176,28 @@ where
         jacobian_mapping_f2_trans: Option<JacobianMappingF2Trans>,
         set_c: Option<SetC>,
         n2: usize,
-    ) -> Self {
-        assert!(
-            !(mapping_f2.is_none() ^ (n2 == 0)),
-            "if n2 > 0 then and only then should you provide an F2"
-        );
-        assert!(
-            !(jacobian_mapping_f2_trans.is_none() ^ mapping_f2.is_none()),
-            "you must have JF2 together with F2"
-        );
-        assert!(
-            !(mapping_f1.is_none() ^ jacobian_mapping_f1_trans.is_none()),
-            "if n1 > 0 then and only then should you provide an F1"
-        );
-        assert!(
-            !(mapping_f1.is_none() ^ set_c.is_none()),
-            "F1 must be accompanied by a set C"
-        );
-        AlmFactory {
+    ) -> Result<Self, SolverError> {
+        ensure(
+            mapping_f2.is_none() == (n2 == 0),
+            "mapping_f2",
+            "F2 must be provided if and only if n2 > 0",
+        )?;
+        ensure(
+            jacobian_mapping_f2_trans.is_none() == mapping_f2.is_none(),
+            "jacobian_mapping_f2_trans",
+            "JF2 must be provided together with F2",
+        )?;
+        ensure(
+            mapping_f1.is_none() == jacobian_mapping_f1_trans.is_none(),
+            "jacobian_mapping_f1_trans",
+            "JF1 must be provided together with F1",
+        )?;
+        ensure(
+            mapping_f1.is_none() == set_c.is_none(),
+            "set_c",
+            "F1 must be accompanied by a set C",
+        )?;
+        Ok(AlmFactory {
             f,
             df,
             mapping_f1,
@@ -190,7 +206,7 @@ where
             jacobian_mapping_f2_trans,
             set_c,
             n2,
-        }
+        })
     }
 
     /// Computes function $\psi$ given by
@@ -328,6 +344,73 @@ where
         }
         Ok(())
     }
+
+    /// Computes the residuals of the KKT conditions at a pair $(u, y)$
+    ///
+    /// This can be used to verify the quality of a solution (e.g., of
+    /// `AlmOptimizer` with the Lagrange multipliers in `AlmOptimizerStatus`)
+    /// against criteria of the user; see [`KktResidual`] for the definitions
+    /// of the residuals. The constraints $F_2(u) = 0$ have no Lagrange
+    /// multipliers, so they are only accounted for in the infeasibility
+    /// $\Vert F_2(u) \Vert$.
+    ///
+    /// [`KktResidual`]: struct.KktResidual.html
+    ///
+    /// # Arguments
+    ///
+    /// - `u`: vector $u$
+    /// - `y`: Lagrange multipliers, $y\in\mathbb{R}^{n_1}$ (empty if there
+    ///   is no $F_1$)
+    /// - `set_u`: set $U$ of constraints on $u$
+    ///
+    /// # Returns
+    ///
+    /// The KKT residuals, or an appropriate `SolverError` if the computation
+    /// of $\nabla f$, $F_1$, $JF_1^\top$ or $F_2$ fails
+    ///
+    pub fn kkt_residual<U>(
+        &self,
+        u: &[f64],
+        y: &[f64],
+        set_u: &U,
+    ) -> Result<KktResidual, SolverError>
+    where
+        U: Constraint + ?Sized,
+    {
+        let mut grad = vec![0.0; u.len()];
+        (self.df)(u, &mut grad)?; // grad := d_f0(u)
+
+        // grad += JF1(u)'*y and f1_u = F1(u) (if F1 and C are given)
+        let mut f1_u = Vec::new();
+        if let (Some(_), Some(mapping_f1)) = (&self.set_c, &self.mapping_f1) {
+            f1_u.resize(y.len(), 0.0);
+            mapping_f1(u, &mut f1_u)?;
+            if let Some(jf1t) = &self.jacobian_mapping_f1_trans {
+                let mut jac_prod = vec![0.0; u.len()];
+                jf1t(u, y, &mut jac_prod)?;
+                grad.iter_mut()
+                    .zip(jac_prod.iter())
+                    .for_each(|(gradi, jac_prodi)| *gradi += jac_prodi);
+            }
+        }
+
+        let mut f2_u = Vec::new();
+        if let Some(f2) = &self.mapping_f2 {
+            f2_u.resize(self.n2, 0.0);
+            f2(u, &mut f2_u)?;
+        }
+
+        Ok(KktResidual::compute(
+            u,
+            &mut grad,
+            set_u,
+            &mut f1_u,
+            y,
+            self.set_c.as_ref(),
+            &f2_u,
+            &mut OracleCalls::default(),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -336,7 +419,7 @@ mod tests {
 
     #[test]
     fn t_mocking_alm_factory_psi() {
-        let set_c = Ball2::new(None, 1.0);
+        let set_c = Ball2::new(None, 1.0).unwrap();
         let factory = AlmFactory::new(
             mocks::f0,
             mocks::d_f0,
@@ -346,7 +429,8 @@ mod tests {
             NO_JACOBIAN_MAPPING,
             Some(set_c),
             0,
-        );
+        )
+        .unwrap();
         let u = [3.0, 5.0, 7.0];
         let xi = [2.0, 10.0, 20.0];
         let mut cost = 0.0;
@@ -363,7 +447,7 @@ mod tests {
 
     #[test]
     fn t_mocking_alm_factory_grad_psi() {
-        let set_c = Ball2::new(None, 1.0);
+        let set_c = Ball2::new(None, 1.0).unwrap();
         let factory = AlmFactory::new(
             mocks::f0,
             mocks::d_f0,
@@ -373,7 +457,8 @@ mod tests {
             NO_JACOBIAN_MAPPING,
             Some(set_c),
             0,
-        );
+        )
+        .unwrap();
         let u = [3.0, -5.0, 7.0];
         let xi = [2.5, 11.0, 20.0];
         let mut grad_psi = [0.0; 3];
@@ -408,7 +493,7 @@ mod tests {
 
     #[test]
     fn t_mocking_alm_factory_psi_with_f2() {
-        let set_c = Ball2::new(None, 1.0);
+        let set_c = Ball2::new(None, 1.0).unwrap();
         let f2 = mapping_f2;
         let jac_f2_tr =
             |_u: &[f64], _d: &[f64], _res: &mut [f64]| -> Result<(), crate::SolverError> {
@@ -423,7 +508,8 @@ mod tests {
             Some(jac_f2_tr),
             Some(set_c),
             4,
-        );
+        )
+        .unwrap();
         let u = [3.0, 5.0, 7.0];
         let xi = [2.0, 10.0, 20.0];
         let mut cost = 0.0;
@@ -440,7 +526,7 @@ mod tests {
 
     #[test]
     fn t_mocking_alm_factory_grad_psi_with_f2() {
-        let set_c = Ball2::new(None, 1.0);
+        let set_c = Ball2::new(None, 1.0).unwrap();
         let factory = AlmFactory::new(
             mocks::f0,
             mocks::d_f0,
@@ -450,7 +536,8 @@ mod tests {
             Some(jac_mapping_f2_tr),
             Some(set_c),
             4,
-        );
+        )
+        .unwrap();
         let u = [3.0, 5.0, 7.0];
         let xi = [2.0, 10.0, 20.0];
         let mut grad_psi = [0.0; 3];
@@ -480,11 +567,82 @@ mod tests {
             NO_JACOBIAN_MAPPING,
             NO_SET,
             0,
-        );
+        )
+        .unwrap();
         let u = [3.0, 5.0, 7.0];
         let xi = [];
         let mut grad_psi = [0.0; 3];
         assert!(factory.d_psi(&u, &xi, &mut grad_psi).is_ok());
         println!("grad = {:#?}", &grad_psi);
     }
+
+    #[test]
+    fn t_mocking_alm_factory_kkt_residual() {
+        // minimize 0.5*||u - (2, 2)||^2 subject to u0 + u1 <= 2 and u1 - u0 = 0,
+        // whose KKT point is u = (1, 1), y = 1
+        let factory = AlmFactory::new(
+            |u: &[f64], cost: &mut f64| -> FunctionCallResult {
+                *cost = 0.5 * ((u[0] - 2.0).powi(2) + (u[1] - 2.0).powi(2));
+                Ok(())
+            },
+            |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                grad[0] = u[0] - 2.0;
+                grad[1] = u[1] - 2.0;
+                Ok(())
+            },
+            Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+                f1[0] = u[0] + u[1];
+                Ok(())
+            }),
+            Some(
+                |_u: &[f64], d: &[f64], res: &mut [f64]| -> FunctionCallResult {
+                    res[0] = d[0];
+                    res[1] = d[0];
+                    Ok(())
+                },
+            ),
+            Some(|u: &[f64], f2: &mut [f64]| -> FunctionCallResult {
+                f2[0] = u[1] - u[0];
+                Ok(())
+            }),
+            Some(
+                |_u: &[f64], d: &[f64], res: &mut [f64]| -> FunctionCallResult {
+                    res[0] = -d[0];
+                    res[1] = d[0];
+                    Ok(())
+                },
+            ),
+            Some(Rectangle::new(None, Some(&[2.0])).unwrap()),
+            1,
+        )
+        .unwrap();
+        let bounds = Ball2::new(None, 10.0).unwrap();
+
+        let kkt = factory.kkt_residual(&[1.0, 1.0], &[1.0], &bounds).unwrap();
+        assert_eq!(KktResidual::default(), kkt);
+
+        let kkt = factory.kkt_residual(&[1.0, 1.0], &[-1.0], &bounds).unwrap();
+        unit_test_utils::assert_nearly_equal(
+            8.0_f64.sqrt(),
+            kkt.stationarity(),
+            1e-12,
+            1e-12,
+            "st",
+        );
+        unit_test_utils::assert_nearly_equal(1.0, kkt.complementarity(), 1e-12, 1e-12, "compl");
+        assert_eq!(kkt.stationarity(), kkt.max());
+
+        let kkt = factory.kkt_residual(&[2.0, 3.0], &[0.0], &bounds).unwrap();
+        unit_test_utils::assert_nearly_equal(1.0, kkt.stationarity(), 1e-12, 1e-12, "st");
+        assert_eq!(0.0, kkt.complementarity());
+        unit_test_utils::assert_nearly_equal(3.0, kkt.f1_infeasibility(), 1e-12, 1e-12, "f1");
+        unit_test_utils::assert_nearly_equal(1.0, kkt.f2_infeasibility(), 1e-12, 1e-12, "f2");
+
+        // u on the boundary of U = {u: ||u|| <= 1}: only the normal component
+        // of the gradient remains
+        let bounds = Ball2::new(None, 1.0).unwrap();
+        let u = [0.5_f64.sqrt(), 0.5_f64.sqrt()];
+        let kkt = factory.kkt_residual(&u, &[0.0], &bounds).unwrap();
+        assert!(kkt.stationarity() < 1e-12);
+    }
 }
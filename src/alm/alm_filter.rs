@@ -0,0 +1,54 @@
+use crate::core::heap_size;
+
+/// Margin of the infeasibility in the acceptance test of the filter
+const FILTER_INFEASIBILITY_MARGIN: f64 = 1e-5;
+
+/// Margin of the cost in the acceptance test of the filter
+const FILTER_COST_MARGIN: f64 = 1e-5;
+
+/// Filter of the outer iterations of ALM
+///
+/// A filter is a set of pairs $(\theta_j, f_j)$ of infeasibilities and costs
+/// of previous outer iterates, none of which dominates another. A new
+/// iterate, $(\theta, f)$, is acceptable if, for every pair of the filter,
+///
+/// $$
+/// \theta \leq (1 - \gamma_\theta)\theta_j \text{ or } f \leq f_j - \gamma_f \theta_j,
+/// $$
+///
+/// that is, if it sufficiently improves either the infeasibility or the cost.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AlmFilter {
+    /// pairs (infeasibility, cost)
+    entries: Vec<(f64, f64)>,
+}
+
+impl AlmFilter {
+    /// Number of bytes held on the heap by the entries
+    pub(crate) fn heap_size(&self) -> usize {
+        heap_size(&self.entries)
+    }
+
+    /// Whether the pair `(infeasibility, cost)` is acceptable to the filter
+    pub(crate) fn is_acceptable(&self, infeasibility: f64, cost: f64) -> bool {
+        infeasibility.is_finite()
+            && cost.is_finite()
+            && self.entries.iter().all(|&(theta_j, f_j)| {
+                infeasibility <= (1.0 - FILTER_INFEASIBILITY_MARGIN) * theta_j
+                    || cost <= f_j - FILTER_COST_MARGIN * theta_j
+            })
+    }
+
+    /// Adds the pair `(infeasibility, cost)` to the filter and removes the
+    /// pairs which it dominates
+    pub(crate) fn add(&mut self, infeasibility: f64, cost: f64) {
+        self.entries
+            .retain(|&(theta_j, f_j)| theta_j < infeasibility || f_j < cost);
+        self.entries.push((infeasibility, cost));
+    }
+
+    /// Removes all pairs of the filter
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -1,18 +1,24 @@
 use crate::{
     alm::*,
     constraints,
-    core::{panoc::PANOCOptimizer, ExitStatus, Optimizer, Problem, SolverStatus},
-    matrix_operations, FunctionCallResult, SolverError,
+    core::{
+        maybe_owned::MaybeOwnedMut,
+        panoc::{PANOCCache, PANOCOptimizer},
+        pgbb::PGBBOptimizer,
+        screen_output, ActiveSet, Clock, ExitStatus, Optimizer, Problem, Progress, ProgressEvent,
+        SolverStatus, SolverTrace, StdClock, Verbosity,
+    },
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
 };
 
-const DEFAULT_MAX_OUTER_ITERATIONS: usize = 50;
-const DEFAULT_MAX_INNER_ITERATIONS: usize = 5000;
-const DEFAULT_EPSILON_TOLERANCE: f64 = 1e-6;
-const DEFAULT_DELTA_TOLERANCE: f64 = 1e-4;
-const DEFAULT_PENALTY_UPDATE_FACTOR: f64 = 5.0;
-const DEFAULT_EPSILON_UPDATE_FACTOR: f64 = 0.1;
-const DEFAULT_INFEAS_SUFFICIENT_DECREASE_FACTOR: f64 = 0.1;
-const DEFAULT_INITIAL_TOLERANCE: f64 = 0.1;
+pub(crate) const DEFAULT_MAX_OUTER_ITERATIONS: usize = 50;
+pub(crate) const DEFAULT_MAX_INNER_ITERATIONS: usize = 5000;
+pub(crate) const DEFAULT_EPSILON_TOLERANCE: f64 = 1e-6;
+pub(crate) const DEFAULT_DELTA_TOLERANCE: f64 = 1e-4;
+pub(crate) const DEFAULT_PENALTY_UPDATE_FACTOR: f64 = 5.0;
+pub(crate) const DEFAULT_EPSILON_UPDATE_FACTOR: f64 = 0.1;
+pub(crate) const DEFAULT_INFEAS_SUFFICIENT_DECREASE_FACTOR: f64 = 0.1;
+pub(crate) const DEFAULT_INITIAL_TOLERANCE: f64 = 0.1;
 const SMALL_EPSILON: f64 = std::f64::EPSILON;
 
 /// Internal/private structure used by method AlmOptimizer.step
@@ -35,6 +41,49 @@ impl InnerProblemStatus {
     }
 }
 
+/// Settings and workspace of the polishing of the solution (see
+/// `AlmOptimizer::with_polishing`)
+struct Polishing<T> {
+    /// tolerance of the polishing iterations
+    tolerance: T,
+    /// maximum number of polishing iterations
+    max_iterations: usize,
+    /// active set: whether each coordinate is fixed
+    active: Vec<bool>,
+    /// values of the fixed coordinates
+    fixed: Vec<T>,
+    /// gradient (and gradient step) at the solution
+    gradient: Vec<T>,
+}
+
+/// Constraints `U` with the coordinates of the active set fixed at their
+/// values
+///
+/// The projection is exact if `U` is separable (e.g., a box)
+struct ActiveSetConstraints<'a, C, T> {
+    constraints: &'a C,
+    active: &'a [bool],
+    fixed: &'a [T],
+}
+
+impl<'a, C, T> constraints::Constraint<T> for ActiveSetConstraints<'a, C, T>
+where
+    T: OpEnFloat,
+    C: constraints::Constraint<T>,
+{
+    fn project(&self, x: &mut [T]) {
+        self.constraints.project(x);
+        x.iter_mut()
+            .zip(self.active.iter().zip(self.fixed))
+            .filter(|(_, (&active, _))| active)
+            .for_each(|(x_i, (_, &fixed_i))| *x_i = fixed_i);
+    }
+
+    fn is_convex(&self) -> bool {
+        self.constraints.is_convex()
+    }
+}
+
 /// Implements the ALM/PM method
 ///
 /// `AlmOptimizer` solves the problem
@@ -115,6 +164,8 @@ impl InnerProblemStatus {
 /// of $C$ and $\delta_{U}$, $\delta_{C^{\ast}}$ are the indicator functions of $U$ and $C^{\ast}$
 /// respectively.
 ///
+/// The type parameter `T` is the scalar type (`f64` by default)
+///
 pub struct AlmOptimizer<
     'life,
     MappingAlm,
@@ -124,17 +175,19 @@ pub struct AlmOptimizer<
     ConstraintsType,
     AlmSetC,
     LagrangeSetY,
+    T = f64,
 > where
-    MappingAlm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    MappingPm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricGradientType: Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricCostType: Fn(&[f64], &[f64], &mut f64) -> FunctionCallResult,
-    ConstraintsType: constraints::Constraint,
-    AlmSetC: constraints::Constraint,
-    LagrangeSetY: constraints::Constraint,
+    T: OpEnFloat,
+    MappingAlm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    MappingPm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[T], &[T], &mut T) -> FunctionCallResult,
+    ConstraintsType: constraints::Constraint<T>,
+    AlmSetC: constraints::Constraint<T>,
+    LagrangeSetY: constraints::Constraint<T>,
 {
-    /// ALM cache (borrowed)
-    alm_cache: &'life mut AlmCache,
+    /// ALM cache (borrowed or owned)
+    alm_cache: MaybeOwnedMut<'life, AlmCache<T>>,
     /// ALM problem definition (oracle)
     alm_problem: AlmProblem<
         MappingAlm,
@@ -144,6 +197,7 @@ pub struct AlmOptimizer<
         ConstraintsType,
         AlmSetC,
         LagrangeSetY,
+        T,
     >,
     /// Maximum number of outer iterations
     max_outer_iterations: usize,
@@ -152,19 +206,48 @@ pub struct AlmOptimizer<
     /// Maximum duration
     max_duration: Option<std::time::Duration>,
     /// epsilon for inner AKKT condition
-    epsilon_tolerance: f64,
+    epsilon_tolerance: T,
     /// delta for outer AKKT condition
-    delta_tolerance: f64,
+    delta_tolerance: T,
     /// At every outer iteration, c is multiplied by this scalar
-    penalty_update_factor: f64,
+    penalty_update_factor: T,
     /// The epsilon-tolerance is multiplied by this factor until
     /// it reaches its target value
-    epsilon_update_factor: f64,
+    epsilon_update_factor: T,
     /// If current_infeasibility <= sufficient_decrease_coeff * previous_infeasibility,
     /// then the penalty parameter is kept constant
-    sufficient_decrease_coeff: f64,
+    sufficient_decrease_coeff: T,
     // Initial tolerance (for the inner problem)
-    epsilon_inner_initial: f64,
+    epsilon_inner_initial: T,
+    /// Whether the outputs of $F_1$ and $F_2$ are screened for NaN/infinite values
+    nan_screening: bool,
+    /// Whether to record the history of the outer iterations
+    record_history: bool,
+    /// Receiver of progress events (if any)
+    progress: Option<Progress>,
+    /// Whether the Lagrange multipliers are frozen at zero (quadratic
+    /// penalty method)
+    penalty_method: bool,
+    /// Whether the outer iterates and the penalty updates are governed by
+    /// a filter
+    use_filter: bool,
+    /// Whether the state of the outer iterations is carried across calls
+    /// of `solve` (real-time iteration mode)
+    real_time: bool,
+    /// Clock which measures the solution time (of the inner problems too)
+    clock: &'life dyn Clock,
+    /// Polishing of the solution (if any)
+    polishing: Option<Polishing<T>>,
+    /// Whether to compute the KKT residuals at the solution
+    compute_kkt_residual: bool,
+    /// Whether to compute the infeasibilities of the blocks of the
+    /// constraints F1(u) in C at the solution
+    compute_block_infeasibility: bool,
+    /// Tolerance of the identification of the active sets (if they are
+    /// identified)
+    active_set_tolerance: Option<T>,
+    /// Whether to measure the time spent in the phases of the inner solver
+    phase_timing: bool,
 }
 
 impl<
@@ -176,6 +259,7 @@ impl<
         ConstraintsType,
         AlmSetC,
         LagrangeSetY,
+        T,
     >
     AlmOptimizer<
         'life,
@@ -186,15 +270,17 @@ impl<
         ConstraintsType,
         AlmSetC,
         LagrangeSetY,
+        T,
     >
 where
-    MappingAlm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    MappingPm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricGradientType: Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricCostType: Fn(&[f64], &[f64], &mut f64) -> FunctionCallResult,
-    ConstraintsType: constraints::Constraint,
-    AlmSetC: constraints::Constraint,
-    LagrangeSetY: constraints::Constraint,
+    T: OpEnFloat,
+    MappingAlm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    MappingPm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[T], &[T], &mut T) -> FunctionCallResult,
+    ConstraintsType: constraints::Constraint<T>,
+    AlmSetC: constraints::Constraint<T>,
+    LagrangeSetY: constraints::Constraint<T>,
 {
     /* ---------------------------------------------------------------------------- */
     /*          CONSTRUCTOR                                                         */
@@ -221,17 +307,17 @@ where
     /// let n1 = 5;
     /// let n2 = 0;
     /// let lbfgs_mem = 3;
-    /// let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    /// let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     /// let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
     ///
     /// let psi =  |_u: &[f64], _param: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
     /// let d_psi =|_u: &[f64], _param: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
     /// let f1 = |_u: &[f64], _result: &mut [f64]| -> FunctionCallResult { Ok(()) };
-    /// let set_c = constraints::Ball2::new(None, 1.50);
+    /// let set_c = constraints::Ball2::new(None, 1.50).unwrap();
     ///
     /// // Construct an instance of AlmProblem without any PM-type data
-    /// let bounds = constraints::Ball2::new(None, 10.0);
-    /// let set_y = constraints::Ball2::new(None, 1.0);
+    /// let bounds = constraints::Ball2::new(None, 10.0).unwrap();
+    /// let set_y = constraints::Ball2::new(None, 1.0).unwrap();
     /// let alm_problem = AlmProblem::new(
     ///     bounds,
     ///     Some(set_c),
@@ -242,15 +328,15 @@ where
     ///     NO_MAPPING,
     ///     n1,
     ///     n2,
-    /// );
+    /// ).unwrap();
     ///
     /// let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
-    ///     .with_delta_tolerance(1e-4)
-    ///     .with_max_outer_iterations(10);
+    ///     .with_delta_tolerance(1e-4).unwrap()
+    ///     .with_max_outer_iterations(10).unwrap();
     ///```     
     ///
     pub fn new(
-        alm_cache: &'life mut AlmCache,
+        alm_cache: &'life mut AlmCache<T>,
         alm_problem: AlmProblem<
             MappingAlm,
             MappingPm,
@@ -259,6 +345,90 @@ where
             ConstraintsType,
             AlmSetC,
             LagrangeSetY,
+            T,
+        >,
+    ) -> Self {
+        AlmOptimizer::with_cache_storage(MaybeOwnedMut::Borrowed(alm_cache), alm_problem)
+    }
+
+    /// Create new instance of `AlmOptimizer` which takes ownership of its cache
+    ///
+    /// Unlike `AlmOptimizer::new`, the optimizer does not borrow its cache, so,
+    /// if the closures in `alm_problem` do not borrow any data, the lifetime
+    /// parameter `'life` can be `'static` and the optimizer can be stored in a
+    /// long-lived struct. The optimizer is `Send` if the closures and the sets
+    /// of `alm_problem` are, so it can be moved to a worker thread together
+    /// with its cache; it is not `Sync`.
+    ///
+    /// # Arguments
+    ///
+    /// - `alm_cache`: an instance of [`AlmCache`](./struct.AlmCache.html), which is
+    ///   owned by `AlmOptimizer`
+    /// - `alm_problem`: the problem specification (see `AlmOptimizer::new`)
+    ///
+    pub fn new_owned(
+        alm_cache: AlmCache<T>,
+        alm_problem: AlmProblem<
+            MappingAlm,
+            MappingPm,
+            ParametricGradientType,
+            ParametricCostType,
+            ConstraintsType,
+            AlmSetC,
+            LagrangeSetY,
+            T,
+        >,
+    ) -> Self {
+        AlmOptimizer::with_cache_storage(MaybeOwnedMut::Owned(alm_cache), alm_problem)
+    }
+
+    /// Create new instance of `AlmOptimizer` which allocates its own cache
+    ///
+    /// The dimensions `n1` and `n2` of the cache are those of `alm_problem`
+    ///
+    /// # Arguments
+    ///
+    /// - `alm_problem`: the problem specification (see `AlmOptimizer::new`)
+    /// - `problem_size`: dimension of the decision variables
+    /// - `tolerance`: tolerance of the inner solver (see `PANOCCache::new`)
+    /// - `lbfgs_memory_size`: memory of the L-BFGS buffer of the inner solver
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified `tolerance` is
+    /// not positive, or if `problem_size` or `lbfgs_memory_size` is zero
+    ///
+    pub fn from_dimensions(
+        alm_problem: AlmProblem<
+            MappingAlm,
+            MappingPm,
+            ParametricGradientType,
+            ParametricCostType,
+            ConstraintsType,
+            AlmSetC,
+            LagrangeSetY,
+            T,
+        >,
+        problem_size: usize,
+        tolerance: T,
+        lbfgs_memory_size: usize,
+    ) -> Result<Self, SolverError> {
+        let panoc_cache = PANOCCache::new(problem_size, tolerance, lbfgs_memory_size)?;
+        let alm_cache = AlmCache::new(panoc_cache, alm_problem.n1, alm_problem.n2);
+        Ok(AlmOptimizer::new_owned(alm_cache, alm_problem))
+    }
+
+    fn with_cache_storage(
+        mut alm_cache: MaybeOwnedMut<'life, AlmCache<T>>,
+        alm_problem: AlmProblem<
+            MappingAlm,
+            MappingPm,
+            ParametricGradientType,
+            ParametricCostType,
+            ConstraintsType,
+            AlmSetC,
+            LagrangeSetY,
+            T,
         >,
     ) -> Self {
         // set the initial value of the inner tolerance; this step is
@@ -266,19 +436,31 @@ where
         // in #solve (see below)
         alm_cache
             .panoc_cache
-            .set_akkt_tolerance(DEFAULT_INITIAL_TOLERANCE);
+            .activate_akkt_tolerance(T::from_f64(DEFAULT_INITIAL_TOLERANCE));
         AlmOptimizer {
             alm_cache,
             alm_problem,
             max_outer_iterations: DEFAULT_MAX_OUTER_ITERATIONS,
             max_inner_iterations: DEFAULT_MAX_INNER_ITERATIONS,
             max_duration: None,
-            epsilon_tolerance: DEFAULT_EPSILON_TOLERANCE,
-            delta_tolerance: DEFAULT_DELTA_TOLERANCE,
-            penalty_update_factor: DEFAULT_PENALTY_UPDATE_FACTOR,
-            epsilon_update_factor: DEFAULT_EPSILON_UPDATE_FACTOR,
-            sufficient_decrease_coeff: DEFAULT_INFEAS_SUFFICIENT_DECREASE_FACTOR,
-            epsilon_inner_initial: DEFAULT_INITIAL_TOLERANCE,
+            epsilon_tolerance: T::from_f64(DEFAULT_EPSILON_TOLERANCE),
+            delta_tolerance: T::from_f64(DEFAULT_DELTA_TOLERANCE),
+            penalty_update_factor: T::from_f64(DEFAULT_PENALTY_UPDATE_FACTOR),
+            epsilon_update_factor: T::from_f64(DEFAULT_EPSILON_UPDATE_FACTOR),
+            sufficient_decrease_coeff: T::from_f64(DEFAULT_INFEAS_SUFFICIENT_DECREASE_FACTOR),
+            epsilon_inner_initial: T::from_f64(DEFAULT_INITIAL_TOLERANCE),
+            nan_screening: false,
+            record_history: false,
+            progress: None,
+            penalty_method: false,
+            use_filter: false,
+            real_time: false,
+            clock: &StdClock,
+            polishing: None,
+            compute_kkt_residual: false,
+            compute_block_infeasibility: false,
+            active_set_tolerance: None,
+            phase_timing: false,
         }
     }
 
@@ -296,18 +478,23 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified number of outer iterations is zero
+    /// Returns `SolverError::InvalidParameter` if the specified number of outer
+    /// iterations is zero
     ///
     ///
-    pub fn with_max_outer_iterations(mut self, max_outer_iterations: usize) -> Self {
-        assert!(
+    pub fn with_max_outer_iterations(
+        mut self,
+        max_outer_iterations: usize,
+    ) -> Result<Self, SolverError> {
+        ensure(
             max_outer_iterations > 0,
-            "max_outer_iterations must be positive"
-        );
+            "max_outer_iterations",
+            "must be positive",
+        )?;
         self.max_outer_iterations = max_outer_iterations;
-        self
+        Ok(self)
     }
 
     /// Setter method for the maximum number of iterations for the inner problems
@@ -321,18 +508,23 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified number of inner iterations is zero
+    /// Returns `SolverError::InvalidParameter` if the specified number of inner
+    /// iterations is zero
     ///
     ///
-    pub fn with_max_inner_iterations(mut self, max_inner_iterations: usize) -> Self {
-        assert!(
+    pub fn with_max_inner_iterations(
+        mut self,
+        max_inner_iterations: usize,
+    ) -> Result<Self, SolverError> {
+        ensure(
             max_inner_iterations > 0,
-            "max_inner_iterations must be positive"
-        );
+            "max_inner_iterations",
+            "must be positive",
+        )?;
         self.max_inner_iterations = max_inner_iterations;
-        self
+        Ok(self)
     }
 
     /// Setter methods for the maximum duration
@@ -353,6 +545,23 @@ where
         self
     }
 
+    /// Sets the clock which measures the solution time (see `Clock`)
+    ///
+    /// The clock is used by the inner solvers too; the default is `StdClock`
+    ///
+    /// # Arguments
+    ///
+    /// - `clock`: time source
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_clock(mut self, clock: &'life dyn Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Set the delta tolerance
     ///
     /// # Arguments
@@ -363,14 +572,19 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified tolerance is not positive
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is not
+    /// positive
     ///
-    pub fn with_delta_tolerance(mut self, delta_tolerance: f64) -> Self {
-        assert!(delta_tolerance > 0.0, "delta_tolerance must be positive");
+    pub fn with_delta_tolerance(mut self, delta_tolerance: T) -> Result<Self, SolverError> {
+        ensure(
+            delta_tolerance > T::zero(),
+            "delta_tolerance",
+            "must be positive",
+        )?;
         self.delta_tolerance = delta_tolerance;
-        self
+        Ok(self)
     }
 
     /// Set the epsilon tolerance
@@ -383,17 +597,19 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified tolerance is not positive
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is not
+    /// positive
     ///
-    pub fn with_epsilon_tolerance(mut self, epsilon_tolerance: f64) -> Self {
-        assert!(
-            epsilon_tolerance > 0.0,
-            "epsilon_tolerance must be positive"
-        );
+    pub fn with_epsilon_tolerance(mut self, epsilon_tolerance: T) -> Result<Self, SolverError> {
+        ensure(
+            epsilon_tolerance > T::zero(),
+            "epsilon_tolerance",
+            "must be positive",
+        )?;
         self.epsilon_tolerance = epsilon_tolerance;
-        self
+        Ok(self)
     }
 
     /// Setter method for the penalty update factor.
@@ -410,18 +626,23 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the update factor is not larger than `1.0 + f64::EPSILON`
+    /// Returns `SolverError::InvalidParameter` if the update factor is not larger
+    /// than `1.0 + f64::EPSILON`
     ///
     ///
-    pub fn with_penalty_update_factor(mut self, penalty_update_factor: f64) -> Self {
-        assert!(
-            penalty_update_factor > 1.0 + SMALL_EPSILON,
-            "`penalty_update_factor` must be larger than 1.0 + f64::EPSILON"
-        );
+    pub fn with_penalty_update_factor(
+        mut self,
+        penalty_update_factor: T,
+    ) -> Result<Self, SolverError> {
+        ensure(
+            penalty_update_factor.as_f64() > 1.0 + SMALL_EPSILON,
+            "penalty_update_factor",
+            "must be larger than 1.0 + f64::EPSILON",
+        )?;
         self.penalty_update_factor = penalty_update_factor;
-        self
+        Ok(self)
     }
 
     /// Setter method for the update factor for the epsilon tolerance
@@ -439,22 +660,23 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified tolerance update factor is not in the
-    /// interval from `f64::EPSILON` to `1.0 - f64::EPSILON`.
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance update
+    /// factor is not in the interval from `f64::EPSILON` to `1.0 - f64::EPSILON`.
     ///
     pub fn with_inner_tolerance_update_factor(
         mut self,
-        inner_tolerance_update_factor: f64,
-    ) -> Self {
-        assert!(
-            inner_tolerance_update_factor > SMALL_EPSILON
-                && inner_tolerance_update_factor < 1.0 - SMALL_EPSILON,
-            "the tolerance update factor needs to be in (f64::EPSILON, 1)"
-        );
+        inner_tolerance_update_factor: T,
+    ) -> Result<Self, SolverError> {
+        let factor = inner_tolerance_update_factor.as_f64();
+        ensure(
+            factor > SMALL_EPSILON && factor < 1.0 - SMALL_EPSILON,
+            "inner_tolerance_update_factor",
+            "must be in (f64::EPSILON, 1)",
+        )?;
         self.epsilon_update_factor = inner_tolerance_update_factor;
-        self
+        Ok(self)
     }
 
     /// Setter method for the sufficient decrease coefficient
@@ -473,24 +695,29 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified initial inner tolerance is less than the
-    /// target tolerance. If you need to decrease the target tolerance, please use
-    /// `with_inner_tolerance` to do so before invoking `with_initial_inner_tolerance`.
+    /// Returns `SolverError::InvalidParameter` if the specified initial inner
+    /// tolerance is less than the target tolerance. If you need to decrease the
+    /// target tolerance, please use `with_epsilon_tolerance` to do so before
+    /// invoking `with_initial_inner_tolerance`.
     ///
     ///
-    pub fn with_initial_inner_tolerance(mut self, initial_inner_tolerance: f64) -> Self {
-        assert!(
+    pub fn with_initial_inner_tolerance(
+        mut self,
+        initial_inner_tolerance: T,
+    ) -> Result<Self, SolverError> {
+        ensure(
             initial_inner_tolerance >= self.epsilon_tolerance,
-            "the initial tolerance should be no less than the target tolerance"
-        );
+            "initial_inner_tolerance",
+            "must be no less than the target tolerance",
+        )?;
         self.epsilon_inner_initial = initial_inner_tolerance;
         // for safety, we update the value of the tolerance in panoc_cache
         self.alm_cache
             .panoc_cache
-            .set_akkt_tolerance(initial_inner_tolerance);
-        self
+            .activate_akkt_tolerance(initial_inner_tolerance);
+        Ok(self)
     }
 
     /// Setter method for the sufficient decrease coefficient
@@ -507,52 +734,57 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified sufficient decrease coefficient is not
-    /// in the range `(f64::EPSILON, 1.0 - f64::EPSILON)`
+    /// Returns `SolverError::InvalidParameter` if the specified sufficient decrease
+    /// coefficient is not in the range `(f64::EPSILON, 1.0 - f64::EPSILON)`
     ///
     pub fn with_sufficient_decrease_coefficient(
         mut self,
-        sufficient_decrease_coefficient: f64,
-    ) -> Self {
-        assert!(
-            sufficient_decrease_coefficient < 1.0 - SMALL_EPSILON
-                && sufficient_decrease_coefficient > SMALL_EPSILON,
-            "sufficient_decrease_coefficient must be in (f64::EPSILON, 1.0 - f64::EPSILON)"
-        );
+        sufficient_decrease_coefficient: T,
+    ) -> Result<Self, SolverError> {
+        let coefficient = sufficient_decrease_coefficient.as_f64();
+        ensure(
+            coefficient < 1.0 - SMALL_EPSILON && coefficient > SMALL_EPSILON,
+            "sufficient_decrease_coefficient",
+            "must be in (f64::EPSILON, 1.0 - f64::EPSILON)",
+        )?;
         self.sufficient_decrease_coeff = sufficient_decrease_coefficient;
-        self
+        Ok(self)
     }
 
     /// Setter method for the initial vector of Lagrange multipliers, $y^0$
     ///
     /// # Arguments
     ///
-    /// - `y_init`: initial vector of Lagrange multipliers (type: `&[f64]`) of
+    /// - `y_init`: initial vector of Lagrange multipliers (type: `&[T]`) of
     ///             length equal to `n1`
     ///
     /// # Returns
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method will panic if the length of `y_init` is not equal to `n1`
+    /// Returns `SolverError::DimensionMismatch` if the length of `y_init` is not
+    /// equal to `n1`
     ///
-    pub fn with_initial_lagrange_multipliers(mut self, y_init: &[f64]) -> Self {
-        let cache = &mut self.alm_cache;
-        assert!(
-            y_init.len() == self.alm_problem.n1,
-            "y_init has wrong length (not equal to n1)"
-        );
+    pub fn with_initial_lagrange_multipliers(mut self, y_init: &[T]) -> Result<Self, SolverError> {
+        let cache = &mut *self.alm_cache;
         // Function `copy_from_slice` would panic if given two arrays (slices)
         // of different lengths; however we catch this earlier in order to provide
         // a meaningful error message
+        if y_init.len() != self.alm_problem.n1 {
+            return Err(SolverError::DimensionMismatch {
+                name: "y_init",
+                expected: self.alm_problem.n1,
+                actual: y_init.len(),
+            });
+        }
         if let Some(xi_in_cache) = &mut cache.xi {
             xi_in_cache[1..].copy_from_slice(y_init);
         }
-        self
+        Ok(self)
     }
 
     /// Setter method for the initial penalty parameter
@@ -565,28 +797,398 @@ where
     ///
     /// Returns the current mutable and updated instance of the provided object
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the specified initial penalty parameter is not
-    /// larger than `f64::EPSILON`
+    /// Returns `SolverError::InvalidParameter` if the specified initial penalty
+    /// parameter is not larger than `f64::EPSILON`
     ///
-    pub fn with_initial_penalty(self, c0: f64) -> Self {
-        assert!(
-            c0 > SMALL_EPSILON,
-            "the initial penalty must be larger than f64::EPSILON"
-        );
+    pub fn with_initial_penalty(mut self, c0: T) -> Result<Self, SolverError> {
+        ensure(
+            c0.as_f64() > SMALL_EPSILON,
+            "c0",
+            "must be larger than f64::EPSILON",
+        )?;
         if let Some(xi_in_cache) = &mut self.alm_cache.xi {
             xi_in_cache[0] = c0;
         }
+        Ok(self)
+    }
+
+    /// Activates the screening of the outputs of user-defined functions
+    ///
+    /// If activated, the outputs of the mappings $F_1$ and $F_2$, as well as
+    /// the cost function and its gradient (in the inner problems, see
+    /// `PANOCOptimizer::with_nan_screening`), are checked for NaN and infinite
+    /// values after every evaluation; if such a value is found, the solver
+    /// returns `SolverError::NotFiniteOutput`
+    ///
+    /// # Arguments
+    ///
+    /// - `nan_screening`: whether to screen the outputs of user-defined functions
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_nan_screening(mut self, nan_screening: bool) -> Self {
+        self.nan_screening = nan_screening;
+        self.alm_cache.panoc_cache.nan_screening = nan_screening;
+        self
+    }
+
+    /// Whether to record the history of the outer iterations
+    ///
+    /// If activated, the norm of the fixed-point residual and the cost of the
+    /// inner problem, the penalty parameter and the infeasibilities at every
+    /// outer iteration are available via `AlmOptimizerStatus::trace`. This is
+    /// disabled by default since it requires memory allocation at every
+    /// outer iteration.
+    ///
+    /// # Arguments
+    ///
+    /// - `record_history`: whether to record the history
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_history(mut self, record_history: bool) -> Self {
+        self.record_history = record_history;
+        self
+    }
+
+    /// Whether to use the quadratic penalty method
+    ///
+    /// If activated, the Lagrange multipliers are frozen at zero (the initial
+    /// Lagrange multipliers are ignored) and only the penalty parameter is
+    /// updated, that is, the inner problems minimize
+    /// $f(u) + \tfrac{c}{2}\mathrm{dist}^2_C(F_1(u)) + \tfrac{c}{2}\Vert F_2(u)\Vert^2$.
+    /// This is useful when the multiplier update is meaningless, e.g., for some
+    /// nonsmooth mappings $F_1$. The termination criteria are unchanged: since
+    /// $y = 0$, the criterion $\Vert y^+ - y \Vert \leq c\delta$ becomes
+    /// $\mathrm{dist}_C(F_1(u)) \leq \delta$. The Lagrange multipliers in
+    /// `AlmOptimizerStatus` are the estimates $c(F_1(u) - \Pi_C(F_1(u)))$.
+    ///
+    /// # Arguments
+    ///
+    /// - `penalty_method`: whether to use the quadratic penalty method
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_penalty_method(mut self, penalty_method: bool) -> Self {
+        self.penalty_method = penalty_method;
+        self
+    }
+
+    /// Whether to govern the outer iterations with a filter
+    ///
+    /// If activated, the pair $(\theta, f)$ of infeasibility,
+    /// $\theta = (\Vert y^+ - y \Vert^2/c^2 + \Vert F_2(u) \Vert^2)^{1/2}$,
+    /// and cost, $f(u)$, of every outer iterate is tested against a filter of
+    /// the pairs of previous iterates: the iterate is acceptable if it
+    /// sufficiently decreases either the infeasibility or the cost with
+    /// respect to every pair of the filter. If it is, the pair is added to
+    /// the filter, the Lagrange multipliers are updated and the penalty
+    /// parameter is kept; otherwise, the Lagrange multipliers are not updated
+    /// and the penalty parameter is increased. This replaces the sufficient
+    /// decrease criterion of the penalty updates (see
+    /// `with_sufficient_decrease_coefficient`), which may cycle on some
+    /// problems, and requires an additional evaluation of the cost function
+    /// per outer iteration.
+    ///
+    /// # Arguments
+    ///
+    /// - `use_filter`: whether to use a filter
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_filter(mut self, use_filter: bool) -> Self {
+        self.use_filter = use_filter;
+        self
+    }
+
+    /// Reports the progress of the solver, e.g., to a GUI
+    ///
+    /// A `ProgressEvent::OuterIteration` is emitted every `interval` outer
+    /// iterations (see [`Progress`](../core/struct.Progress.html)) and a
+    /// `ProgressEvent::Finished` is emitted when the solver returns a status.
+    ///
+    /// # Arguments
+    ///
+    /// - `progress`: receiver of the progress events
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Prints a compact table of the outer iterations (outer and inner
+    /// iterations, cost, norm of the fixed-point residual, penalty parameter
+    /// and infeasibilities) every `interval` outer iterations to stderr
+    ///
+    /// This is a shorthand for `with_progress(Progress::verbose(verbosity,
+    /// interval, std::io::stderr()))`; use `Progress::verbose` to print to
+    /// another `Write`. It replaces the receiver of `with_progress` (if any).
+    ///
+    pub fn with_verbosity(self, verbosity: Verbosity, interval: usize) -> Self {
+        self.with_progress(Progress::verbose(verbosity, interval, std::io::stderr()))
+    }
+
+    /// Activates the real-time iteration mode, in which every call of `solve`
+    /// performs at most `outer_iterations` outer iterations, each with at most
+    /// `inner_iterations` inner iterations, and resumes the outer iterations
+    /// of the previous call
+    ///
+    /// This is the real-time iteration scheme of embedded MPC: every call
+    /// returns within a fixed budget of iterations (so its worst-case time
+    /// is deterministic), it does not loop until convergence (not converging
+    /// within the budget is normal, and is reported as
+    /// `NotConvergedIterations`), and the iterations of consecutive calls,
+    /// e.g., at consecutive sampling times, add up. The Lagrange multipliers,
+    /// the penalty parameter, the inner tolerance and the infeasibilities of
+    /// the last outer iteration are carried across calls, while the initial
+    /// guess `u` is the warm start of the decision variables. A call returns
+    /// early, however, if an (epsilon, delta)-AKKT point is found.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `outer_iterations` or
+    /// `inner_iterations` is zero
+    ///
+    pub fn with_real_time_iterations(
+        self,
+        outer_iterations: usize,
+        inner_iterations: usize,
+    ) -> Result<Self, SolverError> {
+        let mut optimizer = self
+            .with_max_outer_iterations(outer_iterations)?
+            .with_max_inner_iterations(inner_iterations)?;
+        optimizer.real_time = true;
+        Ok(optimizer)
+    }
+
+    /// Polishes the solution with at most `max_iterations` PANOC iterations
+    /// on the reduced problem with tolerance `tolerance`
+    ///
+    /// When the solver converges, the active set of the constraints $U$ at
+    /// the solution (the coordinates which are moved by the projection of a
+    /// gradient step) is identified and fixed, and the last inner problem
+    /// is solved over the remaining coordinates with the tolerance
+    /// `tolerance` (on the fixed-point residual and the AKKT condition),
+    /// starting from the solution. The tolerance can be much tighter than
+    /// the epsilon tolerance, so that an accurate solution is obtained
+    /// without solving all inner problems to a tight tolerance. The Lagrange
+    /// multipliers and the infeasibilities are recomputed at the polished
+    /// solution, while the exit status is that of the ALM/PM iterations.
+    ///
+    /// The active set is fixed exactly if $U$ is separable (e.g., a box);
+    /// otherwise, the active coordinates are reset to their values after
+    /// every projection on $U$.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not
+    /// positive or `max_iterations` is zero
+    ///
+    pub fn with_polishing(
+        mut self,
+        tolerance: T,
+        max_iterations: usize,
+    ) -> Result<Self, SolverError> {
+        ensure(
+            tolerance > T::zero(),
+            "polishing tolerance",
+            "must be positive",
+        )?;
+        ensure(
+            max_iterations > 0,
+            "polishing iterations",
+            "must be positive",
+        )?;
+        let n = self.alm_cache.panoc_cache.gradient_u.len();
+        self.polishing = Some(Polishing {
+            tolerance,
+            max_iterations,
+            active: vec![false; n],
+            fixed: vec![T::zero(); n],
+            gradient: vec![T::zero(); n],
+        });
+        Ok(self)
+    }
+
+    /// Whether to compute the residuals of the KKT conditions at the solution
+    ///
+    /// If activated, the residuals (see [`KktResidual`]) at the solution $u$
+    /// and the Lagrange multipliers of the last inner problem,
+    /// $y^+ = c[t - \Pi_C(t)]$ with $t = F_1(u) + c^{-1}y$, are available via
+    /// `AlmOptimizerStatus::kkt_residual`; the multipliers of the constraints
+    /// $F_2(u) = 0$ are taken to be $cF_2(u)$. This requires an additional
+    /// evaluation of the gradient of $\psi$ and of the mappings $F_1$ and
+    /// $F_2$, as well as memory allocation, at the end of every solve, and it
+    /// is disabled by default.
+    ///
+    /// [`KktResidual`]: struct.KktResidual.html
+    ///
+    /// # Arguments
+    ///
+    /// - `compute_kkt_residual`: whether to compute the KKT residuals
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_kkt_residual(mut self, compute_kkt_residual: bool) -> Self {
+        self.compute_kkt_residual = compute_kkt_residual;
+        self
+    }
+
+    /// Whether to compute the infeasibility of every block of the constraints
+    /// $F_1(u) \in C$ at the solution
+    ///
+    /// If $C$ (or, otherwise, the set $Y$ of the Lagrange multipliers) is a
+    /// Cartesian product (see `CartesianProduct`), the norm of every block of
+    /// $F_1(u) - \Pi_C(F_1(u))$ at the solution $u$, together with the name
+    /// of the block (see `CartesianProduct::add_named_constraint`), is
+    /// available via `AlmOptimizerStatus::block_infeasibility` (see
+    /// [`BlockInfeasibility`]), so that the groups of constraints which
+    /// prevent convergence can be identified. This requires an additional
+    /// evaluation of $F_1$, a projection on $C$ and memory allocation at the
+    /// end of every solve, and it is disabled by default.
+    ///
+    /// [`BlockInfeasibility`]: struct.BlockInfeasibility.html
+    ///
+    /// # Arguments
+    ///
+    /// - `compute_block_infeasibility`: whether to compute the infeasibilities
+    ///   of the blocks
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_block_infeasibility(mut self, compute_block_infeasibility: bool) -> Self {
+        self.compute_block_infeasibility = compute_block_infeasibility;
         self
     }
 
+    /// Identifies the active sets of the constraints at the solution within
+    /// `tolerance`
+    ///
+    /// The coordinates of the solution $u$ at which the constraints $U$ are
+    /// active and the rows of $F_1(u)$ at which the constraints $C$ are
+    /// active or violated (see [`ActiveSet`]) are available via
+    /// `AlmOptimizerStatus::active_set` and `AlmOptimizerStatus::active_rows`
+    /// respectively. This requires an additional evaluation of $F_1$,
+    /// $2(n + n_1)$ projections, as well as memory allocation, at the end of
+    /// every solve.
+    ///
+    /// [`ActiveSet`]: ../core/struct.ActiveSet.html
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not positive
+    ///
+    pub fn with_active_set_tolerance(mut self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(
+            tolerance > T::zero(),
+            "active set tolerance",
+            "must be positive",
+        )?;
+        self.active_set_tolerance = Some(tolerance);
+        Ok(self)
+    }
+
+    /// Whether to measure the time spent in the phases of the inner solver
+    ///
+    /// If enabled, the time spent by PANOC in evaluations of $\psi$ and its
+    /// gradient, in projections on $U$, in the computation of directions
+    /// and in the line search (see `PANOCOptimizer::with_phase_timing`) is
+    /// accumulated over all inner problems (including the polishing stage)
+    /// and is available via `AlmOptimizerStatus::phase_times`. The time of
+    /// the outer iterations (e.g., the updates of the Lagrange multipliers)
+    /// is not included. This is disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// - `phase_timing`: whether to measure the time of the phases
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    pub fn with_phase_timing(mut self, phase_timing: bool) -> Self {
+        self.phase_timing = phase_timing;
+        self
+    }
+
+    /// Applies a configuration to this optimizer
+    ///
+    /// The configuration is applied using the corresponding setter methods
+    /// (the epsilon tolerance is set before the initial inner tolerance),
+    /// while optional fields of `config` which are `None` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// - `config`: configuration (see [`AlmConfig`](struct.AlmConfig.html))
+    ///
+    /// # Returns
+    ///
+    /// Returns the current mutable and updated instance of the provided object
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration contains invalid values (see
+    /// the corresponding setter methods)
+    ///
+    pub fn with_config(self, config: &AlmConfig) -> Result<Self, SolverError> {
+        let mut optimizer = self
+            .with_max_outer_iterations(config.max_outer_iterations)?
+            .with_max_inner_iterations(config.max_inner_iterations)?
+            .with_delta_tolerance(T::from_f64(config.delta_tolerance))?
+            .with_epsilon_tolerance(T::from_f64(config.epsilon_tolerance))?
+            .with_penalty_update_factor(T::from_f64(config.penalty_update_factor))?
+            .with_inner_tolerance_update_factor(T::from_f64(config.inner_tolerance_update_factor))?
+            .with_initial_inner_tolerance(T::from_f64(config.initial_inner_tolerance))?
+            .with_sufficient_decrease_coefficient(T::from_f64(
+                config.sufficient_decrease_coefficient,
+            ))?;
+        if let Some(max_duration) = config.max_duration {
+            optimizer = optimizer.with_max_duration(max_duration);
+        }
+        if let Some(y_init) = &config.initial_lagrange_multipliers {
+            let y_init: Vec<T> = y_init.iter().map(|&y_i| T::from_f64(y_i)).collect();
+            optimizer = optimizer.with_initial_lagrange_multipliers(&y_init)?;
+        }
+        if let Some(c0) = config.initial_penalty {
+            optimizer = optimizer.with_initial_penalty(T::from_f64(c0))?;
+        }
+        if config.real_time_iterations {
+            optimizer = optimizer.with_real_time_iterations(
+                config.max_outer_iterations,
+                config.max_inner_iterations,
+            )?;
+        }
+        Ok(optimizer
+            .with_nan_screening(config.nan_screening)
+            .with_history(config.record_history)
+            .with_penalty_method(config.penalty_method)
+            .with_filter(config.filter))
+    }
+
     /* ---------------------------------------------------------------------------- */
     /*          PRIVATE METHODS                                                     */
     /* ---------------------------------------------------------------------------- */
 
     fn compute_alm_infeasibility(&mut self) -> FunctionCallResult {
-        let alm_cache = &mut self.alm_cache; // ALM cache
+        let alm_cache = &mut *self.alm_cache; // ALM cache
         if let (Some(y_plus), Some(xi)) = (&alm_cache.y_plus, &alm_cache.xi) {
             // compute ||y_plus - y||
             let norm_diff_squared = matrix_operations::norm2_squared_diff(y_plus, &xi[1..]);
@@ -596,14 +1198,18 @@ where
     }
 
     /// Computes PM infeasibility, that is, ||F2(u)||
-    fn compute_pm_infeasibility(&mut self, u: &[f64]) -> FunctionCallResult {
-        let problem = &self.alm_problem; // ALM problem
-        let cache = &mut self.alm_cache; // ALM cache
+    fn compute_pm_infeasibility(&mut self, u: &[T]) -> FunctionCallResult {
+        let problem = &mut self.alm_problem; // ALM problem
+        let cache = &mut *self.alm_cache; // ALM cache
 
         // If there is an F2 mapping: cache.w_pm <-- F2
         // Then compute the norm of w_pm and store it in cache.f2_norm_plus
-        if let (Some(f2), Some(w_pm_vec)) = (&problem.mapping_f2, &mut cache.w_pm.as_mut()) {
+        if let (Some(f2), Some(w_pm_vec)) = (&mut problem.mapping_f2, &mut cache.w_pm.as_mut()) {
+            cache.oracle_calls.mapping_f2 += 1;
             f2(u, w_pm_vec)?;
+            if self.nan_screening {
+                screen_output("ALM", "F2", cache.iteration, w_pm_vec)?;
+            }
             cache.f2_norm_plus = matrix_operations::norm2(w_pm_vec);
         }
         Ok(())
@@ -613,9 +1219,9 @@ where
     ///
     /// `y_plus <-- y + c*[F1(u_plus) - Proj_C(F1(u_plus) + y/c)]`
     ///
-    fn update_lagrange_multipliers(&mut self, u: &[f64]) -> FunctionCallResult {
-        let problem = &self.alm_problem; // ALM problem
-        let cache = &mut self.alm_cache; // ALM cache
+    fn update_lagrange_multipliers(&mut self, u: &[T]) -> FunctionCallResult {
+        let problem = &mut self.alm_problem; // ALM problem
+        let cache = &mut *self.alm_cache; // ALM cache
 
         // y_plus <-- y + c*[F1(u_plus) - Proj_C(F1(u_plus) + y/c)]
         // This is implemented as follows:
@@ -631,14 +1237,18 @@ where
         }
 
         if let (Some(f1), Some(w_alm_aux), Some(y_plus), Some(xi), Some(alm_set_c)) = (
-            &problem.mapping_f1,
+            &mut problem.mapping_f1,
             &mut cache.w_alm_aux,
             &mut cache.y_plus,
             &mut cache.xi,
             &problem.alm_set_c,
         ) {
             // Step #1: w_alm_aux := F1(u)
+            cache.oracle_calls.mapping_f1 += 1;
             (f1)(u, w_alm_aux)?;
+            if self.nan_screening {
+                screen_output("ALM", "F1", cache.iteration, w_alm_aux)?;
+            }
 
             // Step #2: y_plus := w_alm_aux + y/c
             let y = &xi[1..];
@@ -647,9 +1257,10 @@ where
                 .iter_mut()
                 .zip(y.iter())
                 .zip(w_alm_aux.iter())
-                .for_each(|((y_plus_i, y_i), w_alm_aux_i)| *y_plus_i = w_alm_aux_i + y_i / c);
+                .for_each(|((y_plus_i, &y_i), &w_alm_aux_i)| *y_plus_i = w_alm_aux_i + y_i / c);
 
             // Step #3: y_plus := Proj_C(y_plus)
+            cache.oracle_calls.projections += 1;
             alm_set_c.project(y_plus);
 
             // Step #4
@@ -657,7 +1268,7 @@ where
                 .iter_mut()
                 .zip(y.iter())
                 .zip(w_alm_aux.iter())
-                .for_each(|((y_plus_i, y_i), w_alm_aux_i)| {
+                .for_each(|((y_plus_i, &y_i), &w_alm_aux_i)| {
                     // y_plus := y  + c * (w_alm_aux   - y_plus)
                     *y_plus_i = y_i + c * (w_alm_aux_i - *y_plus_i)
                 });
@@ -666,16 +1277,32 @@ where
         Ok(())
     }
 
+    /// Updates the Lagrange multipliers and computes the infeasibilities at
+    /// the solution, `u`, of the inner problem
+    ///
+    /// In penalty-only problems (`n1 = 0`), there are no Lagrange multipliers
+    /// (`y_plus` and `y` are not allocated), so only `||F2(u)||` is computed
+    fn update_outer_iterate(&mut self, u: &[T]) -> FunctionCallResult {
+        if self.alm_problem.n1 > 0 {
+            // y_plus <-- y + c*[F1(u_plus) - Proj_C(F1(u_plus) + y/c)]
+            self.update_lagrange_multipliers(u)?;
+            self.compute_alm_infeasibility()?; // ALM: ||y_plus - y||
+        }
+        self.compute_pm_infeasibility(u) // penalty method: F2(u_plus) and its norm
+    }
+
     /// Project y on set Y
     fn project_on_set_y(&mut self) {
         let problem = &self.alm_problem;
         if let Some(y_set) = &problem.alm_set_y {
             // NOTE: as_mut() converts from &mut Option<T> to Option<&mut T>
-            // * cache.y is                Option<Vec<f64>>
-            // * cache.y.as_mut is         Option<&mut Vec<f64>>
-            // *  which can be treated as  Option<&mut [f64]>
-            // * y_vec is                  &mut [f64]
-            if let Some(xi_vec) = self.alm_cache.xi.as_mut() {
+            // * cache.y is                Option<Vec<T>>
+            // * cache.y.as_mut is         Option<&mut Vec<T>>
+            // *  which can be treated as  Option<&mut [T]>
+            // * y_vec is                  &mut [T]
+            let cache = &mut *self.alm_cache;
+            if let Some(xi_vec) = cache.xi.as_mut() {
+                cache.oracle_calls.projections += 1;
                 y_set.project(&mut xi_vec[1..]);
             }
         }
@@ -696,32 +1323,40 @@ where
     /// error in solving the inner problem.
     ///
     ///
-    fn solve_inner_problem(&mut self, u: &mut [f64]) -> Result<SolverStatus, SolverError> {
-        let alm_problem = &self.alm_problem; // Problem
-        let alm_cache = &mut self.alm_cache; // ALM cache
+    fn solve_inner_problem(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let alm_problem = &mut self.alm_problem; // Problem
+        let alm_cache = &mut *self.alm_cache; // ALM cache
 
-        // `xi` is either the cached `xi` if one exists, or an reference to an
-        // empty vector, otherwise. We do that becaues the user has the option
-        // to not use any ALM/PM constraints; in that case, `alm_cache.xi` is
-        // `None`
-        let xi_empty = Vec::new();
-        let xi = if let Some(xi_cached) = &alm_cache.xi {
-            xi_cached
-        } else {
-            &xi_empty
-        };
+        // `xi` is either the cached `xi` if one exists, or an empty slice,
+        // otherwise. We do that becaues the user has the option to not use any
+        // ALM/PM constraints; in that case, `alm_cache.xi` is `None`
+        let xi: &[T] = alm_cache.xi.as_deref().unwrap_or(&[]);
         // Construct psi and psi_grad (as functions of `u` alone); it is
         // psi(u) = psi(u; xi) and psi_grad(u) = phi_grad(u; xi)
         // psi: R^nu --> R
-        let psi = |u: &[f64], psi_val: &mut f64| -> FunctionCallResult {
-            (alm_problem.parametric_cost)(u, xi, psi_val)
-        };
+        let parametric_cost = &mut alm_problem.parametric_cost;
+        let psi =
+            |u: &[T], psi_val: &mut T| -> FunctionCallResult { parametric_cost(u, xi, psi_val) };
         // psi_grad: R^nu --> R^nu
-        let psi_grad = |u: &[f64], psi_grad: &mut [f64]| -> FunctionCallResult {
-            (alm_problem.parametric_gradient)(u, xi, psi_grad)
+        let parametric_gradient = &mut alm_problem.parametric_gradient;
+        let psi_grad = |u: &[T], psi_grad: &mut [T]| -> FunctionCallResult {
+            parametric_gradient(u, xi, psi_grad)
         };
         // define the inner problem
-        let inner_problem = Problem::new(&self.alm_problem.constraints, psi_grad, psi);
+        let inner_problem = Problem::new(&alm_problem.constraints, psi_grad, psi);
+        let max_duration = alm_cache
+            .available_time
+            .unwrap_or_else(|| std::time::Duration::from_secs(std::u64::MAX));
+        if let Some(pgbb_cache) = &mut alm_cache.pgbb_cache {
+            // the inner problems are solved (with PGBB) up to the AKKT tolerance
+            let panoc_cache = &alm_cache.panoc_cache;
+            pgbb_cache.tolerance = panoc_cache.akkt_tolerance.unwrap_or(panoc_cache.tolerance);
+            return PGBBOptimizer::new(inner_problem, pgbb_cache)
+                .with_max_duration(max_duration)
+                .with_max_iter(self.max_inner_iterations)
+                .with_clock(self.clock)
+                .solve(u);
+        }
         // The AKKT-tolerance decreases until it reaches the target tolerance
         // We don't need to update the tolerance here; this is done in
         // `update_inner_akkt_tolerance` which updates the AKKT-tolerance (epsilon)
@@ -729,20 +1364,19 @@ where
         let mut inner_solver = PANOCOptimizer::new(inner_problem, &mut alm_cache.panoc_cache)
             // Set the maximum duration of the inner solver to the available time, which is
             // stored in AlmCache, or set it to the maximum possible duration
-            .with_max_duration(
-                alm_cache
-                    .available_time
-                    .unwrap_or_else(|| std::time::Duration::from_secs(std::u64::MAX)),
-            )
+            .with_max_duration(max_duration)
+            .with_clock(self.clock)
+            .with_phase_timing(self.phase_timing)
             // Set the maximum number of inner iterations
-            .with_max_iter(self.max_inner_iterations);
+            .with_max_iter(self.max_inner_iterations)?;
         // this method returns the result of .solve:
         inner_solver.solve(u)
     }
 
     fn is_exit_criterion_satisfied(&self) -> bool {
-        let cache = &self.alm_cache;
+        let cache = &*self.alm_cache;
         let problem = &self.alm_problem;
+        let small_epsilon = T::from_f64(SMALL_EPSILON);
         // Criterion 1: ||Delta y|| <= c * delta
         //              If n1 = 0 (if there are not ALM-type constraints)
         //              then this criterion is automatically satisfied
@@ -750,7 +1384,7 @@ where
             || if let Some(xi) = &cache.xi {
                 let c = xi[0];
                 cache.iteration > 0
-                    && cache.delta_y_norm_plus <= c * self.delta_tolerance + SMALL_EPSILON
+                    && cache.delta_y_norm_plus <= c * self.delta_tolerance + small_epsilon
             } else {
                 true
             };
@@ -758,19 +1392,19 @@ where
         //              If n2 = 0, there are no PM-type constraints, so this
         //              criterion is automatically satisfied
         let criterion_2 =
-            problem.n2 == 0 || cache.f2_norm_plus <= self.delta_tolerance + SMALL_EPSILON;
+            problem.n2 == 0 || cache.f2_norm_plus <= self.delta_tolerance + small_epsilon;
         // Criterion 3: epsilon_nu <= epsilon
         //              This function will panic is there is no akkt_tolerance
         //              This should never happen because we set the AKKT tolerance
         //              in the constructor and can never become `None` again
         let criterion_3 =
-            cache.panoc_cache.akkt_tolerance.unwrap() <= self.epsilon_tolerance + SMALL_EPSILON;
+            cache.panoc_cache.akkt_tolerance.unwrap() <= self.epsilon_tolerance + small_epsilon;
         criterion_1 && criterion_2 && criterion_3
     }
 
     /// Whether the penalty parameter should not be updated
     fn is_penalty_stall_criterion(&self) -> bool {
-        let cache = &self.alm_cache;
+        let cache = &*self.alm_cache;
         let problem = &self.alm_problem;
         // Check whether the penalty parameter should not be updated
         // This is if iteration = 0, or there has been a sufficient
@@ -780,10 +1414,11 @@ where
         }
         let is_alm = problem.n1 > 0;
         let is_pm = problem.n2 > 0;
+        let small_epsilon = T::from_f64(SMALL_EPSILON);
         let criterion_alm = cache.delta_y_norm_plus
-            <= self.sufficient_decrease_coeff * cache.delta_y_norm + SMALL_EPSILON;
+            <= self.sufficient_decrease_coeff * cache.delta_y_norm + small_epsilon;
         let criterion_pm =
-            cache.f2_norm_plus <= self.sufficient_decrease_coeff * cache.f2_norm + SMALL_EPSILON;
+            cache.f2_norm_plus <= self.sufficient_decrease_coeff * cache.f2_norm + small_epsilon;
         if is_alm && !is_pm {
             return criterion_alm;
         } else if !is_alm && is_pm {
@@ -795,29 +1430,71 @@ where
         false
     }
 
+    /// Tests the current outer iterate against the filter (if any) and, if
+    /// it is acceptable, adds it to the filter
+    ///
+    /// Returns `None` if there is no filter, otherwise, whether the iterate
+    /// is acceptable
+    fn filter_outer_iterate(&mut self, u: &mut [T]) -> Result<Option<bool>, SolverError> {
+        if self.alm_cache.filter.is_none() {
+            return Ok(None);
+        }
+        let cost = self.compute_cost_at_solution(u)?.as_f64();
+        let cache = &mut *self.alm_cache;
+        let c = cache.xi.as_ref().map_or(T::one(), |xi| xi[0]);
+        let infeasibility = (cache.delta_y_norm_plus / c)
+            .hypot(cache.f2_norm_plus)
+            .as_f64();
+        let filter = cache.filter.as_mut().expect("there is a filter");
+        let accepted = filter.is_acceptable(infeasibility, cost);
+        if accepted {
+            filter.add(infeasibility, cost);
+        } else if let (Some(xi), Some(y_plus)) = (&cache.xi, &mut cache.y_plus) {
+            // the Lagrange multipliers are not updated
+            y_plus.copy_from_slice(&xi[1..]);
+        }
+        debug_event!(
+            iteration = cache.iteration,
+            infeasibility = infeasibility,
+            cost = cost,
+            "ALM filter: outer iterate {}",
+            if accepted { "accepted" } else { "rejected" }
+        );
+        Ok(Some(accepted))
+    }
+
     fn update_penalty_parameter(&mut self) {
-        let cache = &mut self.alm_cache;
+        let cache = &mut *self.alm_cache;
         if let Some(xi) = &mut cache.xi {
             xi[0] *= self.penalty_update_factor;
+            debug_event!(
+                iteration = cache.iteration,
+                penalty = xi[0].as_f64(),
+                "ALM penalty parameter updated"
+            );
         }
     }
 
     fn update_inner_akkt_tolerance(&mut self) {
-        let cache = &mut self.alm_cache;
+        let cache = &mut *self.alm_cache;
         // epsilon_{nu+1} := max(epsilon, beta*epsilon_nu)
-        cache.panoc_cache.set_akkt_tolerance(f64::max(
+        // the new tolerance is positive because epsilon > 0
+        cache.panoc_cache.activate_akkt_tolerance(T::max(
             cache.panoc_cache.akkt_tolerance.unwrap() * self.epsilon_update_factor,
             self.epsilon_tolerance,
         ));
     }
 
     fn final_cache_update(&mut self) {
-        let cache = &mut self.alm_cache;
+        let cache = &mut *self.alm_cache;
         cache.iteration += 1;
         cache.delta_y_norm = cache.delta_y_norm_plus;
         cache.f2_norm = cache.f2_norm_plus;
         if let (Some(xi), Some(y_plus)) = (&mut cache.xi, &cache.y_plus) {
-            xi[1..].copy_from_slice(y_plus);
+            // in the penalty method, the Lagrange multipliers stay at zero
+            if !self.penalty_method {
+                xi[1..].copy_from_slice(y_plus);
+            }
         }
         cache.panoc_cache.reset();
     }
@@ -837,41 +1514,85 @@ where
     /// - Shrinks the inner tolerance and
     /// - Updates the ALM cache
     ///
-    fn step(&mut self, u: &mut [f64]) -> Result<InnerProblemStatus, SolverError> {
+    fn step(&mut self, u: &mut [T]) -> Result<InnerProblemStatus, SolverError> {
         // store the exit status of the inner problem in this problem
         // (we'll need to return it within `InnerProblemStatus`)
         let mut inner_exit_status: ExitStatus = ExitStatus::Converged;
 
-        // Project y on Y
-        self.project_on_set_y();
+        // Project y on Y (there is no y in penalty-only problems)
+        if self.alm_problem.n1 > 0 {
+            self.project_on_set_y();
+        }
 
         // If the inner problem fails miserably, the failure should be propagated
         // upstream (using `?`). If the inner problem has not converged, that is fine,
         // we should keep solving.
-        self.solve_inner_problem(u).map(|status: SolverStatus| {
+        let inner_cost = self.solve_inner_problem(u).map(|status: SolverStatus| {
             let inner_iters = status.iterations();
             self.alm_cache.last_inner_problem_norm_fpr = status.norm_fpr();
             self.alm_cache.inner_iteration_count += inner_iters;
             inner_exit_status = status.exit_status();
+            self.alm_cache.oracle_calls += status.oracle_calls();
+            if let Some(phase_times) = status.phase_times() {
+                self.alm_cache.phase_times += phase_times;
+            }
+            status.cost_value()
         })?;
 
         // TODO: Check whether the inner problem has converged; set a limit on
         // FPR above which the outer loop cannot reduce the error? (not sure how)
 
-        // Update Lagrange multipliers:
-        // y_plus <-- y + c*[F1(u_plus) - Proj_C(F1(u_plus) + y/c)]
-        self.update_lagrange_multipliers(u)?;
-
-        // Compute infeasibilities
-        self.compute_pm_infeasibility(u)?; // penalty method: F2(u_plus) and its norm
-        self.compute_alm_infeasibility()?; // ALM: ||y_plus - y||
+        // Update Lagrange multipliers and compute infeasibilities
+        self.update_outer_iterate(u)?;
+
+        // Log and record the outer iteration (if the history is recorded)
+        let cache = &mut *self.alm_cache;
+        let penalty = cache.xi.as_ref().map_or(0.0, |xi| xi[0].as_f64());
+        let alm_infeasibility = cache.delta_y_norm_plus.as_f64();
+        let pm_infeasibility = cache.f2_norm_plus.as_f64();
+        debug_event!(
+            iteration = cache.iteration,
+            inner_iterations = cache.inner_iteration_count,
+            fpr = cache.last_inner_problem_norm_fpr,
+            cost = inner_cost,
+            penalty = penalty,
+            alm_infeasibility = alm_infeasibility,
+            pm_infeasibility = pm_infeasibility,
+            "ALM outer iteration: inner problem {}",
+            inner_exit_status
+        );
+        if let Some(progress) = &mut self.progress {
+            progress.iteration(cache.iteration, || ProgressEvent::OuterIteration {
+                iteration: cache.iteration,
+                inner_iterations: cache.inner_iteration_count,
+                fpr: cache.last_inner_problem_norm_fpr,
+                cost: inner_cost,
+                penalty,
+                alm_infeasibility,
+                pm_infeasibility,
+            });
+        }
+        if let Some(trace) = &mut cache.trace {
+            trace.push_outer_iteration(
+                cache.last_inner_problem_norm_fpr,
+                inner_cost,
+                penalty,
+                alm_infeasibility,
+                pm_infeasibility,
+            );
+        }
 
         // Check exit criterion
         if self.is_exit_criterion_satisfied() {
             // Do not continue the outer iteration
             // An (epsilon, delta)-AKKT point has been found
             return Ok(InnerProblemStatus::new(false, inner_exit_status));
-        } else if !self.is_penalty_stall_criterion() {
+        }
+        let update_penalty = match self.filter_outer_iterate(u)? {
+            Some(accepted) => !accepted,
+            None => !self.is_penalty_stall_criterion(),
+        };
+        if update_penalty {
             self.update_penalty_parameter();
         }
 
@@ -885,18 +1606,204 @@ where
         Ok(InnerProblemStatus::new(true, inner_exit_status)) // `true` means do continue the outer iterations
     }
 
-    fn compute_cost_at_solution(&mut self, u: &mut [f64]) -> Result<f64, SolverError> {
+    /// Polishes the solution `u` (see `with_polishing`) within `max_duration`
+    /// and returns the number of polishing iterations
+    fn polish(
+        &mut self,
+        u: &mut [T],
+        max_duration: Option<std::time::Duration>,
+    ) -> Result<usize, SolverError> {
+        let alm_problem = &mut self.alm_problem; // Problem
+        let alm_cache = &mut *self.alm_cache; // ALM cache
+        let polishing = match &mut self.polishing {
+            Some(polishing) => polishing,
+            None => return Ok(0),
+        };
+        let xi: &[T] = alm_cache.xi.as_deref().unwrap_or(&[]);
+
+        // identify the active set: the coordinates which are moved by the
+        // projection of the gradient step u - gamma * grad psi(u; xi)
+        alm_cache.oracle_calls.gradient += 1;
+        (alm_problem.parametric_gradient)(u, xi, &mut polishing.gradient)?;
+        let gamma = if alm_cache.panoc_cache.gamma > T::zero() {
+            alm_cache.panoc_cache.gamma
+        } else {
+            T::one()
+        };
+        polishing
+            .gradient
+            .iter_mut()
+            .zip(u.iter())
+            .for_each(|(g_i, &u_i)| *g_i = u_i - gamma * *g_i);
+        polishing.fixed.copy_from_slice(&polishing.gradient);
+        alm_cache.oracle_calls.projections += 1;
+        alm_problem.constraints.project(&mut polishing.fixed);
+        polishing
+            .active
+            .iter_mut()
+            .zip(polishing.fixed.iter().zip(&polishing.gradient))
+            .for_each(|(active, (p_i, g_i))| *active = p_i != g_i);
+
+        // solve the last inner problem over the free coordinates
+        let constraints = ActiveSetConstraints {
+            constraints: &alm_problem.constraints,
+            active: &polishing.active,
+            fixed: &polishing.fixed,
+        };
+        let parametric_cost = &mut alm_problem.parametric_cost;
+        let psi =
+            |u: &[T], psi_val: &mut T| -> FunctionCallResult { parametric_cost(u, xi, psi_val) };
+        let parametric_gradient = &mut alm_problem.parametric_gradient;
+        let psi_grad = |u: &[T], psi_grad: &mut [T]| -> FunctionCallResult {
+            parametric_gradient(u, xi, psi_grad)
+        };
+        let problem = Problem::new(&constraints, psi_grad, psi);
+        let panoc_cache = &mut alm_cache.panoc_cache;
+        let (tolerance, akkt_tolerance) = (panoc_cache.tolerance, panoc_cache.akkt_tolerance);
+        panoc_cache.tolerance = polishing.tolerance;
+        panoc_cache.akkt_tolerance = akkt_tolerance.map(|_| polishing.tolerance);
+        let status = PANOCOptimizer::new(problem, &mut *panoc_cache)
+            .with_max_duration(
+                max_duration.unwrap_or_else(|| std::time::Duration::from_secs(u64::MAX)),
+            )
+            .with_clock(self.clock)
+            .with_phase_timing(self.phase_timing)
+            .with_max_iter(polishing.max_iterations)
+            .and_then(|mut solver| solver.solve(u));
+        panoc_cache.tolerance = tolerance;
+        panoc_cache.akkt_tolerance = akkt_tolerance;
+        let status = status?;
+        alm_cache.last_inner_problem_norm_fpr = status.norm_fpr();
+        alm_cache.oracle_calls += status.oracle_calls();
+        if let Some(phase_times) = status.phase_times() {
+            alm_cache.phase_times += phase_times;
+        }
+
+        // Lagrange multipliers and infeasibilities at the polished solution
+        self.update_outer_iterate(u)?;
+        Ok(status.iterations())
+    }
+
+    /// Computes the KKT residuals at `u` and the Lagrange multipliers of the
+    /// last inner problem (see `with_kkt_residual`)
+    fn compute_kkt_residual(&mut self, u: &[T]) -> Result<KktResidual, SolverError> {
+        let alm_problem = &mut self.alm_problem; // Problem
+        let alm_cache = &mut *self.alm_cache; // ALM Cache
+        let xi: &[T] = alm_cache.xi.as_deref().unwrap_or(&[]);
+
+        // grad psi(u; xi) = grad f(u) + JF1(u)'*y_plus + c*JF2(u)'*F2(u) is the
+        // gradient of the Lagrangian
+        let mut lagrangian_gradient = vec![T::zero(); u.len()];
+        alm_cache.oracle_calls.gradient += 1;
+        (alm_problem.parametric_gradient)(u, xi, &mut lagrangian_gradient)?;
+
+        // y_plus = c*[t - Proj_C(t)], where t = F1(u) + y/c
+        let mut f1_u = vec![T::zero(); alm_problem.n1];
+        let mut y_plus = vec![T::zero(); alm_problem.n1];
+        if let (Some(f1), Some(alm_set_c)) = (&mut alm_problem.mapping_f1, &alm_problem.alm_set_c) {
+            alm_cache.oracle_calls.mapping_f1 += 1;
+            f1(u, &mut f1_u)?;
+            let c = xi[0];
+            y_plus
+                .iter_mut()
+                .zip(f1_u.iter())
+                .zip(xi[1..].iter())
+                .for_each(|((y_plus_i, &f1_u_i), &y_i)| *y_plus_i = f1_u_i + y_i / c);
+            let mut s = y_plus.clone();
+            alm_set_c.project(&mut s);
+            alm_cache.oracle_calls.projections += 1;
+            y_plus
+                .iter_mut()
+                .zip(s.iter())
+                .for_each(|(y_plus_i, &s_i)| *y_plus_i = c * (*y_plus_i - s_i));
+        }
+
+        let mut f2_u = vec![T::zero(); alm_problem.n2];
+        if let Some(f2) = &mut alm_problem.mapping_f2 {
+            alm_cache.oracle_calls.mapping_f2 += 1;
+            f2(u, &mut f2_u)?;
+        }
+
+        Ok(KktResidual::compute(
+            u,
+            &mut lagrangian_gradient,
+            &alm_problem.constraints,
+            &mut f1_u,
+            &y_plus,
+            alm_problem.alm_set_c.as_ref(),
+            &f2_u,
+            &mut alm_cache.oracle_calls,
+        ))
+    }
+
+    /// Computes the infeasibilities of the blocks of $F_1(u) \in C$ at `u`
+    /// (see `with_block_infeasibility`)
+    fn compute_block_infeasibility(
+        &mut self,
+        u: &[T],
+    ) -> Result<Option<Vec<BlockInfeasibility>>, SolverError> {
+        let alm_problem = &mut self.alm_problem; // Problem
+        let alm_cache = &mut *self.alm_cache; // ALM Cache
+        let (f1, alm_set_c) = match (&mut alm_problem.mapping_f1, &alm_problem.alm_set_c) {
+            (Some(f1), Some(alm_set_c)) => (f1, alm_set_c),
+            _ => return Ok(None),
+        };
+        let mut f1_u = vec![T::zero(); alm_problem.n1];
+        alm_cache.oracle_calls.mapping_f1 += 1;
+        f1(u, &mut f1_u)?;
+        let oracle_calls = &mut alm_cache.oracle_calls;
+        Ok(match &alm_problem.alm_set_y {
+            Some(alm_set_y) if alm_set_c.blocks().is_none() => {
+                BlockInfeasibility::compute(&f1_u, alm_set_c, alm_set_y, oracle_calls)
+            }
+            _ => BlockInfeasibility::compute(&f1_u, alm_set_c, alm_set_c, oracle_calls),
+        })
+    }
+
+    /// Identifies the active sets of $U$ at `u` and of $C$ at $F_1(u)$ (see
+    /// `with_active_set_tolerance`)
+    fn identify_active_sets(
+        &mut self,
+        u: &[T],
+        tolerance: T,
+    ) -> Result<(ActiveSet, Option<ActiveSet>), SolverError> {
+        let alm_problem = &mut self.alm_problem; // Problem
+        let alm_cache = &mut *self.alm_cache; // ALM Cache
+        let active_set = ActiveSet::identify_counted(
+            &alm_problem.constraints,
+            u,
+            tolerance,
+            &mut alm_cache.oracle_calls,
+        );
+        let active_rows = match (&mut alm_problem.mapping_f1, &alm_problem.alm_set_c) {
+            (Some(f1), Some(alm_set_c)) => {
+                let mut f1_u = vec![T::zero(); alm_problem.n1];
+                alm_cache.oracle_calls.mapping_f1 += 1;
+                f1(u, &mut f1_u)?;
+                Some(ActiveSet::identify_counted(
+                    alm_set_c,
+                    &f1_u,
+                    tolerance,
+                    &mut alm_cache.oracle_calls,
+                ))
+            }
+            _ => None,
+        };
+        Ok((active_set, active_rows))
+    }
+
+    fn compute_cost_at_solution(&mut self, u: &mut [T]) -> Result<T, SolverError> {
         /* WORK IN PROGRESS */
-        let alm_problem = &self.alm_problem; // Problem
-        let alm_cache = &mut self.alm_cache; // ALM Cache
-        let mut empty_vec = std::vec::Vec::new(); // Empty vector
-        let xi: &mut std::vec::Vec<f64> = alm_cache.xi.as_mut().unwrap_or(&mut empty_vec);
-        let mut __c: f64 = 0.0;
+        let alm_problem = &mut self.alm_problem; // Problem
+        let alm_cache = &mut *self.alm_cache; // ALM Cache
+        let xi: &mut [T] = alm_cache.xi.as_deref_mut().unwrap_or(&mut []);
+        let mut __c = T::zero();
         if !xi.is_empty() {
             __c = xi[0];
-            xi[0] = 0.0;
+            xi[0] = T::zero();
         }
-        let mut cost_value: f64 = 0.0;
+        let mut cost_value = T::zero();
+        alm_cache.oracle_calls.cost += 1;
         (alm_problem.parametric_cost)(u, xi, &mut cost_value)?;
         if !xi.is_empty() {
             xi[0] = __c;
@@ -908,25 +1815,80 @@ where
     /*          MAIN API                                                            */
     /* ---------------------------------------------------------------------------- */
 
+    /// Number of decision variables (the problem size of the inner solver)
+    pub(crate) fn problem_size(&self) -> usize {
+        self.alm_cache.panoc_cache.gradient_u.len()
+    }
+
     /// Solve the specified ALM problem
     ///
-    ///
-    pub fn solve(&mut self, u: &mut [f64]) -> Result<AlmOptimizerStatus, SolverError> {
+    /// The result is recorded in the statistics of the cache (see
+    /// `AlmCache::statistics`)
+    ///
+    pub fn solve(&mut self, u: &mut [T]) -> Result<AlmOptimizerStatus, SolverError> {
+        let tic = self.clock.now();
+        let result = self.run(u, tic);
+        let statistics = &mut self.alm_cache.statistics;
+        match &result {
+            Ok(status) => statistics.record(
+                status.exit_status(),
+                status.num_inner_iterations(),
+                status.solve_time(),
+                status.delta_y_norm_over_c().max(status.f2_norm()),
+            ),
+            Err(_) => statistics.record_error(self.clock.elapsed(tic)),
+        }
+        result
+    }
+
+    /// Runs the ALM/PM algorithm, which started at time `tic` (see `solve`)
+    fn run(
+        &mut self,
+        u: &mut [T],
+        tic: std::time::Duration,
+    ) -> Result<AlmOptimizerStatus, SolverError> {
         let mut num_outer_iterations = 0;
-        // let tic = std::time::Instant::now();
-        let tic = instant::Instant::now();
         let mut exit_status = ExitStatus::Converged;
-        self.alm_cache.reset(); // first, reset the cache
+        // in the real-time iteration mode, the outer iterations of the
+        // previous call are resumed (if any)
+        let resume = self.real_time && self.alm_cache.iteration > 0;
+        if resume {
+            self.alm_cache.reset_counters();
+        } else {
+            self.alm_cache.reset(); // first, reset the cache
+            self.alm_cache
+                .panoc_cache
+                .activate_akkt_tolerance(self.epsilon_inner_initial);
+        }
         self.alm_cache.available_time = self.max_duration;
+        self.alm_cache.trace = if self.record_history {
+            let trace = SolverTrace::new(Vec::new(), Vec::new());
+            Some(
+                trace
+                    .with_penalty(Vec::new())
+                    .with_infeasibility(Vec::new(), Vec::new()),
+            )
+        } else {
+            None
+        };
 
-        self.alm_cache
-            .panoc_cache
-            .set_akkt_tolerance(self.epsilon_inner_initial);
+        self.alm_cache.filter = if self.use_filter {
+            let mut filter = self.alm_cache.filter.take().unwrap_or_default();
+            filter.clear();
+            Some(filter)
+        } else {
+            None
+        };
+        if self.penalty_method {
+            if let Some(xi) = &mut self.alm_cache.xi {
+                xi[1..].iter_mut().for_each(|y_i| *y_i = T::zero());
+            }
+        }
 
         let mut inner = InnerProblemStatus::new(false, ExitStatus::Converged);
         for _outer_iters in 1..=self.max_outer_iterations {
             if let Some(max_duration) = self.max_duration {
-                let available_time_left = max_duration.checked_sub(tic.elapsed());
+                let available_time_left = max_duration.checked_sub(self.clock.elapsed(tic));
                 self.alm_cache.available_time = available_time_left;
                 if available_time_left.is_none() {
                     // no time left for outer iterations!
@@ -934,6 +1896,7 @@ where
                     break;
                 }
             }
+            enter_debug_span!("alm_outer_iteration", iteration = self.alm_cache.iteration);
             num_outer_iterations += 1;
             inner = self.step(u)?;
             if inner.inner_problem_exit_status == ExitStatus::NotConvergedOutOfTime {
@@ -961,23 +1924,79 @@ where
             exit_status = ExitStatus::NotConvergedIterations;
         }
 
+        // polish the solution (if activated)
+        let mut polishing_iterations = 0;
+        if exit_status == ExitStatus::Converged && self.polishing.is_some() {
+            let available_time = match self.max_duration {
+                Some(max_duration) => max_duration.checked_sub(self.clock.elapsed(tic)),
+                None => None,
+            };
+            if self.max_duration.is_none() || available_time.is_some() {
+                polishing_iterations = self.polish(u, available_time)?;
+            }
+        }
+
         // obtain the penalty parameter
         let c = if let Some(xi) = &self.alm_cache.xi {
-            xi[0]
+            xi[0].as_f64()
         } else {
             0.0
         };
 
-        let cost = self.compute_cost_at_solution(u)?;
+        let cost = self.compute_cost_at_solution(u)?.as_f64();
+        let kkt_residual = if self.compute_kkt_residual {
+            Some(self.compute_kkt_residual(u)?)
+        } else {
+            None
+        };
+        let block_infeasibility = if self.compute_block_infeasibility {
+            self.compute_block_infeasibility(u)?
+        } else {
+            None
+        };
+        let (active_set, active_rows) = match self.active_set_tolerance {
+            Some(tolerance) => {
+                let (active_set, active_rows) = self.identify_active_sets(u, tolerance)?;
+                (Some(active_set), active_rows)
+            }
+            None => (None, None),
+        };
         let status = AlmOptimizerStatus::new(exit_status)
-            .with_solve_time(tic.elapsed())
+            .with_solve_time(self.clock.elapsed(tic))
             .with_inner_iterations(self.alm_cache.inner_iteration_count)
             .with_outer_iterations(num_outer_iterations)
+            .with_polishing_iterations(polishing_iterations)
             .with_last_problem_norm_fpr(self.alm_cache.last_inner_problem_norm_fpr)
-            .with_delta_y_norm(self.alm_cache.delta_y_norm_plus)
-            .with_f2_norm(self.alm_cache.f2_norm_plus)
+            .with_delta_y_norm(self.alm_cache.delta_y_norm_plus.as_f64())
+            .with_f2_norm(self.alm_cache.f2_norm_plus.as_f64())
             .with_penalty(c)
-            .with_cost(cost);
+            .with_cost(cost)
+            .with_kkt_residual(kkt_residual)
+            .with_block_infeasibility(block_infeasibility)
+            .with_active_sets(active_set, active_rows)
+            .with_oracle_calls(self.alm_cache.oracle_calls)
+            .with_phase_times(if self.phase_timing {
+                Some(self.alm_cache.phase_times)
+            } else {
+                None
+            })
+            .with_trace(self.alm_cache.trace.take());
+        debug_event!(
+            outer_iterations = num_outer_iterations,
+            inner_iterations = self.alm_cache.inner_iteration_count,
+            penalty = c,
+            cost = cost,
+            solve_time = status.solve_time().as_secs_f64(),
+            "ALM finished: {}",
+            exit_status
+        );
+        if let Some(progress) = &mut self.progress {
+            progress.emit(ProgressEvent::Finished {
+                exit_status,
+                iterations: num_outer_iterations,
+                solve_time: status.solve_time(),
+            });
+        }
         if self.alm_problem.n1 > 0 {
             let status = status.with_lagrange_multipliers(
                 self.alm_cache
@@ -992,6 +2011,45 @@ where
     }
 }
 
+impl<
+        'life,
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+        T,
+    > Optimizer<T>
+    for AlmOptimizer<
+        'life,
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+        T,
+    >
+where
+    T: OpEnFloat,
+    MappingAlm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    MappingPm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[T], &[T], &mut T) -> FunctionCallResult,
+    ConstraintsType: constraints::Constraint<T>,
+    AlmSetC: constraints::Constraint<T>,
+    LagrangeSetY: constraints::Constraint<T>,
+{
+    /// Solves the ALM problem (see `AlmOptimizer::solve`) and summarises the
+    /// resulting `AlmOptimizerStatus` as a `SolverStatus`
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        AlmOptimizer::solve(self, u).map(|status| SolverStatus::from(&status))
+    }
+}
+
 /* ---------------------------------------------------------------------------- */
 /*          TESTS                                                               */
 /* ---------------------------------------------------------------------------- */
@@ -1021,7 +2079,7 @@ mod tests {
         // Main problem data
         let psi = void_parameteric_cost;
         let d_psi = void_parameteric_gradient;
-        let bounds = Ball2::new(None, 10.0);
+        let bounds = Ball2::new(None, 10.0).unwrap();
         // ALM-type data
         let f1: Option<MappingType> = if n1 == 0 {
             NO_MAPPING
@@ -1029,12 +2087,12 @@ mod tests {
             Some(void_mapping)
         };
         let set_c = if n1 > 0 {
-            Some(Ball2::new(None, 1.50))
+            Some(Ball2::new(None, 1.50).unwrap())
         } else {
             None::<Ball2>
         };
         let set_y: Option<Ball2> = if n1 > 0 {
-            Some(Ball2::new(None, 2.0))
+            Some(Ball2::new(None, 2.0).unwrap())
         } else {
             None::<Ball2>
         };
@@ -1045,13 +2103,13 @@ mod tests {
             Some(void_mapping)
         };
         // problem
-        AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, f2, n1, n2)
+        AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, f2, n1, n2).unwrap()
     }
 
     #[test]
     fn t_setter_methods() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-8, 10, 5, 0, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
 
@@ -1063,7 +2121,7 @@ mod tests {
         }
 
         // Test: with_initial_penalty
-        let alm_optimizer = alm_optimizer.with_initial_penalty(7.0);
+        let alm_optimizer = alm_optimizer.with_initial_penalty(7.0).unwrap();
         assert!(!alm_optimizer.alm_cache.xi.is_none());
         if let Some(xi) = &alm_optimizer.alm_cache.xi {
             unit_test_utils::assert_nearly_equal(
@@ -1077,7 +2135,9 @@ mod tests {
 
         // Test: with_initial_lagrange_multipliers
         let y_init = vec![2.0, 3.0, 4.0, 5.0, 6.0];
-        let alm_optimizer = alm_optimizer.with_initial_lagrange_multipliers(&y_init);
+        let alm_optimizer = alm_optimizer
+            .with_initial_lagrange_multipliers(&y_init)
+            .unwrap();
         if let Some(xi) = &alm_optimizer.alm_cache.xi {
             unit_test_utils::assert_nearly_equal_array(
                 &y_init,
@@ -1089,7 +2149,7 @@ mod tests {
         }
 
         // Test: with_initial_inner_tolerance
-        let alm_optimizer = alm_optimizer.with_initial_inner_tolerance(5e-3);
+        let alm_optimizer = alm_optimizer.with_initial_inner_tolerance(5e-3).unwrap();
         unit_test_utils::assert_nearly_equal(
             5e-3,
             alm_optimizer.epsilon_inner_initial,
@@ -1113,7 +2173,7 @@ mod tests {
     #[test]
     fn t_project_on_set_y() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-8, 10, 4, 0, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
 
@@ -1127,7 +2187,9 @@ mod tests {
         //
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
             .with_initial_penalty(25.0)
-            .with_initial_lagrange_multipliers(&[2., 3., 4., 10.]);
+            .unwrap()
+            .with_initial_lagrange_multipliers(&[2., 3., 4., 10.])
+            .unwrap();
 
         alm_optimizer.project_on_set_y();
         if let Some(xi_after_proj) = &alm_optimizer.alm_cache.xi {
@@ -1162,7 +2224,7 @@ mod tests {
         // Tests whether compute_pm_infeasibility() works properly: it need to compute
         // F2(u_plus) and ||F2(u_plus)||. It stores F2(u_plus) in alm_cache.w_pm
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-6, 5, 0, 2, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let psi = void_parameteric_cost;
         let d_psi = void_parameteric_gradient;
@@ -1171,11 +2233,12 @@ mod tests {
             res[1] = matrix_operations::norm2_squared(u);
             Ok(())
         });
-        let bounds = Ball2::new(None, 10.0);
+        let bounds = Ball2::new(None, 10.0).unwrap();
         let alm_problem =
-            AlmProblem::new(bounds, NO_SET, NO_SET, psi, d_psi, NO_MAPPING, f2, n1, n2);
-        let mut alm_optimizer =
-            AlmOptimizer::new(&mut alm_cache, alm_problem).with_initial_penalty(10.0);
+            AlmProblem::new(bounds, NO_SET, NO_SET, psi, d_psi, NO_MAPPING, f2, n1, n2).unwrap();
+        let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+            .with_initial_penalty(10.0)
+            .unwrap();
 
         let u_plus = vec![1.0, 5.0, -2.0, 9.0, -6.0];
         assert!(alm_optimizer.compute_pm_infeasibility(&u_plus).is_ok());
@@ -1203,19 +2266,22 @@ mod tests {
     #[test]
     fn t_compute_alm_infeasibility() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-6, 5, 4, 0, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let psi = void_parameteric_cost;
         let d_psi = void_parameteric_gradient;
         let f1 = Some(void_mapping);
-        let set_c = Some(Ball2::new(None, 1.0));
-        let bounds = Ball2::new(None, 10.0);
-        let set_y = Some(Ball2::new(None, 2.0));
-        let alm_problem = AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, NO_MAPPING, n1, n2);
+        let set_c = Some(Ball2::new(None, 1.0).unwrap());
+        let bounds = Ball2::new(None, 10.0).unwrap();
+        let set_y = Some(Ball2::new(None, 2.0).unwrap());
+        let alm_problem =
+            AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, NO_MAPPING, n1, n2).unwrap();
         // Set y0 = [2, 3, 4, 10]
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
             .with_initial_penalty(10.0)
-            .with_initial_lagrange_multipliers(&[2., 3., 4., 10.]);
+            .unwrap()
+            .with_initial_lagrange_multipliers(&[2., 3., 4., 10.])
+            .unwrap();
         {
             let cache = &mut alm_optimizer.alm_cache;
             // Set y1 = [10, 20, 11, 100]
@@ -1236,7 +2302,7 @@ mod tests {
     #[test]
     fn t_update_lagrange_multipliers() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-6, 5, 2, 0, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let psi = void_parameteric_cost;
         let d_psi = void_parameteric_gradient;
@@ -1245,15 +2311,18 @@ mod tests {
             res[1] = matrix_operations::norm2_squared(u);
             Ok(())
         });
-        let set_c = Some(Ball2::new(None, 1.5));
-        let bounds = Ball2::new(None, 10.0);
-        let set_y = Some(Ball2::new(None, 2.0));
-        let alm_problem = AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, NO_MAPPING, n1, n2);
+        let set_c = Some(Ball2::new(None, 1.5).unwrap());
+        let bounds = Ball2::new(None, 10.0).unwrap();
+        let set_y = Some(Ball2::new(None, 2.0).unwrap());
+        let alm_problem =
+            AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, NO_MAPPING, n1, n2).unwrap();
 
         // Set y0 = [2, 3, 4, 10]
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
             .with_initial_penalty(10.0)
-            .with_initial_lagrange_multipliers(&[2., 3.]);
+            .unwrap()
+            .with_initial_lagrange_multipliers(&[2., 3.])
+            .unwrap();
         let u = [3.0, 5.0, 7.0, 9.0, 11.];
         assert!(alm_optimizer.update_lagrange_multipliers(&u).is_ok());
 
@@ -1274,13 +2343,16 @@ mod tests {
     #[test]
     fn t_update_inner_akkt_tolerance() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-8, 10, 0, 0, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
             .with_epsilon_tolerance(2e-5)
+            .unwrap()
             .with_initial_inner_tolerance(1e-1)
-            .with_inner_tolerance_update_factor(0.2);
+            .unwrap()
+            .with_inner_tolerance_update_factor(0.2)
+            .unwrap();
 
         alm_optimizer.update_inner_akkt_tolerance();
 
@@ -1323,12 +2395,14 @@ mod tests {
     #[test]
     fn t_update_penalty_parameter() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-6, 5, 0, 2, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
             .with_initial_penalty(5.0)
-            .with_penalty_update_factor(15.0);
+            .unwrap()
+            .with_penalty_update_factor(15.0)
+            .unwrap();
         if let Some(xi) = &alm_optimizer.alm_cache.xi {
             unit_test_utils::assert_nearly_equal(xi[0], 5.0, 1e-16, 1e-12, "wrong initial penalty");
         }
@@ -1357,7 +2431,7 @@ mod tests {
     #[test]
     fn t_final_cache_update() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-6, 5, 2, 2, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem);
@@ -1402,12 +2476,13 @@ mod tests {
     #[test]
     fn t_is_exit_criterion_satisfied() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-6, 5, 2, 2, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         alm_cache.iteration = 2;
         let alm_problem = make_dummy_alm_problem(n1, n2);
-        let alm_optimizer =
-            AlmOptimizer::new(&mut alm_cache, alm_problem).with_delta_tolerance(1e-3);
+        let alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+            .with_delta_tolerance(1e-3)
+            .unwrap();
 
         // should not exit yet...
         assert!(
@@ -1415,9 +2490,11 @@ mod tests {
             "exists right away"
         );
 
-        let alm_optimizer = alm_optimizer
+        let mut alm_optimizer = alm_optimizer
             .with_initial_inner_tolerance(1e-3)
-            .with_epsilon_tolerance(1e-3);
+            .unwrap()
+            .with_epsilon_tolerance(1e-3)
+            .unwrap();
         assert!(!alm_optimizer.is_exit_criterion_satisfied());
 
         alm_optimizer.alm_cache.delta_y_norm_plus = 1e-3;
@@ -1430,11 +2507,12 @@ mod tests {
     #[test]
     fn t_is_penalty_stall_criterion() {
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-8, 10, 1, 1, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
-        let alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
-            .with_sufficient_decrease_coefficient(0.1);
+        let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+            .with_sufficient_decrease_coefficient(0.1)
+            .unwrap();
 
         // should stall because iteration = 0
         assert!(alm_optimizer.is_penalty_stall_criterion());
@@ -1455,11 +2533,12 @@ mod tests {
     fn t_is_penalty_stall_criterion_alm() {
         // --- ONLY ALM (n1 > 0, n2 = 0)
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-8, 10, 1, 0, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
-        let alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
-            .with_sufficient_decrease_coefficient(0.1);
+        let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+            .with_sufficient_decrease_coefficient(0.1)
+            .unwrap();
 
         // should stall because iteration = 0
         assert!(alm_optimizer.is_penalty_stall_criterion());
@@ -1480,11 +2559,12 @@ mod tests {
     fn t_is_penalty_stall_criterion_pm() {
         // -- ONLY PM (n1 = 0, n2 > 0)
         let (tolerance, nx, n1, n2, lbfgs_mem) = (1e-8, 10, 0, 1, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let alm_problem = make_dummy_alm_problem(n1, n2);
-        let alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
-            .with_sufficient_decrease_coefficient(0.1);
+        let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+            .with_sufficient_decrease_coefficient(0.1)
+            .unwrap();
 
         // should stall because iteration = 0
         assert!(alm_optimizer.is_penalty_stall_criterion());
@@ -1521,24 +2601,29 @@ mod tests {
         //       are actually using a different inner termination criterion with
         //       tolerance specified by AlmOptimizer.with_initial_inner_tolerance
         let (tolerance, nx, n1, n2, lbfgs_mem) = (0.1, 5, 2, 0, 3);
-        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+        let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
         let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
         let psi = psi_cost_dummy;
         let d_psi = psi_gradient_dummy;
         let f1 = Some(void_mapping);
-        let set_c = Some(Ball2::new(None, 1.5));
+        let set_c = Some(Ball2::new(None, 1.5).unwrap());
         let xmin = vec![-5.0; nx];
         let xmax = vec![0.0; nx];
-        let bounds = Rectangle::new(Some(&xmin), Some(&xmax));
-        let set_y = Some(Ball2::new(None, 2.0));
-        let alm_problem = AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, NO_MAPPING, n1, n2);
+        let bounds = Rectangle::new(Some(&xmin), Some(&xmax)).unwrap();
+        let set_y = Some(Ball2::new(None, 2.0).unwrap());
+        let alm_problem =
+            AlmProblem::new(bounds, set_c, set_y, psi, d_psi, f1, NO_MAPPING, n1, n2).unwrap();
 
         // Set y0 = [5.0, 6.0] and initial penalty = 1.0 (so, xi = [1.0, 5.0, 6.0])
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
             .with_initial_lagrange_multipliers(&[5.0, 6.0])
+            .unwrap()
             .with_initial_penalty(1.0)
+            .unwrap()
             .with_epsilon_tolerance(1e-12)
-            .with_initial_inner_tolerance(1e-12);
+            .unwrap()
+            .with_initial_inner_tolerance(1e-12)
+            .unwrap();
         let mut u = vec![0.0; nx];
         let result = alm_optimizer.solve_inner_problem(&mut u);
         println!("result = {:#?}", &result);
@@ -1,4 +1,9 @@
-use crate::core::ExitStatus;
+use super::{BlockInfeasibility, KktResidual};
+use crate::{
+    core::{ActiveSet, ExitStatus, OracleCalls, PhaseTimes, SolverStatus, SolverTrace},
+    OpEnFloat,
+};
+use std::fmt;
 
 /// Solution statistics for `AlmOptimizer`
 ///
@@ -6,7 +11,11 @@ use crate::core::ExitStatus;
 /// The idea is that only Optimization Engine can create optimizer
 /// `AlmOptimizerStatus` instances.
 ///
+/// The `Display` implementation prints a summary of the solution statistics
+/// (including the penalty parameter and the infeasibilities), one per line.
+///
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlmOptimizerStatus {
     /// Exit status
     exit_status: ExitStatus,
@@ -17,6 +26,10 @@ pub struct AlmOptimizerStatus {
     /// This is the sum of the numbers of iterations of
     /// inner solvers
     num_inner_iterations: usize,
+    /// Number of iterations of the polishing of the solution (not included
+    /// in the number of inner iterations)
+    #[cfg_attr(feature = "serde", serde(default))]
+    num_polishing_iterations: usize,
     /// Norm of the fixed-point residual of the the problem
     last_problem_norm_fpr: f64,
     ///
@@ -32,6 +45,29 @@ pub struct AlmOptimizerStatus {
     f2_norm: f64,
     /// Value of cost function at optimal solution (optimal cost)
     cost: f64,
+    /// Residuals of the KKT conditions at the solution (if computed)
+    #[cfg_attr(feature = "serde", serde(default))]
+    kkt_residual: Option<KktResidual>,
+    /// Infeasibilities of the blocks of the constraints F1(u) in C (if
+    /// computed)
+    #[cfg_attr(feature = "serde", serde(default))]
+    block_infeasibility: Option<Vec<BlockInfeasibility>>,
+    /// Active set of the constraints U at the solution (if identified)
+    #[cfg_attr(feature = "serde", serde(default))]
+    active_set: Option<ActiveSet>,
+    /// Active set of the constraints C at F1(u) (if identified)
+    #[cfg_attr(feature = "serde", serde(default))]
+    active_rows: Option<ActiveSet>,
+    /// Number of evaluations of the cost, its gradient, the mappings F1 and F2
+    /// and of projections (accumulated over all inner problems)
+    #[cfg_attr(feature = "serde", serde(default))]
+    oracle_calls: OracleCalls,
+    /// Time spent in the phases of the inner solver (if measured)
+    #[cfg_attr(feature = "serde", serde(default))]
+    phase_times: Option<PhaseTimes>,
+    /// Record of every outer iteration (if recorded)
+    #[cfg_attr(feature = "serde", serde(default))]
+    trace: Option<SolverTrace>,
 }
 
 impl AlmOptimizerStatus {
@@ -60,6 +96,7 @@ impl AlmOptimizerStatus {
             exit_status,
             num_outer_iterations: 0,
             num_inner_iterations: 0,
+            num_polishing_iterations: 0,
             last_problem_norm_fpr: -1.0,
             lagrange_multipliers: None,
             solve_time: std::time::Duration::from_nanos(0),
@@ -67,6 +104,13 @@ impl AlmOptimizerStatus {
             delta_y_norm: 0.0,
             f2_norm: 0.0,
             cost: 0.0,
+            kkt_residual: None,
+            block_infeasibility: None,
+            active_set: None,
+            active_rows: None,
+            oracle_calls: OracleCalls::default(),
+            phase_times: None,
+            trace: None,
         }
     }
 
@@ -117,6 +161,12 @@ impl AlmOptimizerStatus {
         self
     }
 
+    /// Setter method for the number of polishing iterations
+    pub(crate) fn with_polishing_iterations(mut self, polishing_iters: usize) -> Self {
+        self.num_polishing_iterations = polishing_iters;
+        self
+    }
+
     /// Setter method for the vector of Lagrange multipliers at the solution
     ///
     /// # Arguments
@@ -129,11 +179,16 @@ impl AlmOptimizerStatus {
     /// Does not panic; it is the responsibility of the caller to provide a vector of
     /// Lagrange multipliers of correct length
     ///
-    pub(crate) fn with_lagrange_multipliers(mut self, lagrange_multipliers: &[f64]) -> Self {
-        self.lagrange_multipliers = Some(vec![]);
-        if let Some(y) = &mut self.lagrange_multipliers {
-            y.extend_from_slice(lagrange_multipliers);
-        }
+    pub(crate) fn with_lagrange_multipliers<T: OpEnFloat>(
+        mut self,
+        lagrange_multipliers: &[T],
+    ) -> Self {
+        self.lagrange_multipliers = Some(
+            lagrange_multipliers
+                .iter()
+                .map(|y_i| y_i.as_f64())
+                .collect(),
+        );
         self
     }
 
@@ -187,6 +242,44 @@ impl AlmOptimizerStatus {
         self
     }
 
+    pub(crate) fn with_kkt_residual(mut self, kkt_residual: Option<KktResidual>) -> Self {
+        self.kkt_residual = kkt_residual;
+        self
+    }
+
+    pub(crate) fn with_block_infeasibility(
+        mut self,
+        block_infeasibility: Option<Vec<BlockInfeasibility>>,
+    ) -> Self {
+        self.block_infeasibility = block_infeasibility;
+        self
+    }
+
+    pub(crate) fn with_active_sets(
+        mut self,
+        active_set: Option<ActiveSet>,
+        active_rows: Option<ActiveSet>,
+    ) -> Self {
+        self.active_set = active_set;
+        self.active_rows = active_rows;
+        self
+    }
+
+    pub(crate) fn with_oracle_calls(mut self, oracle_calls: OracleCalls) -> Self {
+        self.oracle_calls = oracle_calls;
+        self
+    }
+
+    pub(crate) fn with_phase_times(mut self, phase_times: Option<PhaseTimes>) -> Self {
+        self.phase_times = phase_times;
+        self
+    }
+
+    pub(crate) fn with_trace(mut self, trace: Option<SolverTrace>) -> Self {
+        self.trace = trace;
+        self
+    }
+
     // -------------------------------------------------
     // Update Methods
     // -------------------------------------------------
@@ -239,6 +332,12 @@ impl AlmOptimizerStatus {
         self.num_inner_iterations
     }
 
+    /// Number of iterations of the polishing of the solution (zero if the
+    /// polishing is not activated, see `AlmOptimizer::with_polishing`)
+    pub fn num_polishing_iterations(&self) -> usize {
+        self.num_polishing_iterations
+    }
+
     /// Vector of Lagrange multipliers at the solution
     ///
     /// The method returns a reference to an `Option<Vec<f64>>` which contains
@@ -297,4 +396,98 @@ impl AlmOptimizerStatus {
     pub fn cost(&self) -> f64 {
         self.cost
     }
+
+    /// Residuals of the KKT conditions at the solution, or `None` if they
+    /// have not been computed (see `AlmOptimizer::with_kkt_residual`)
+    pub fn kkt_residual(&self) -> Option<KktResidual> {
+        self.kkt_residual
+    }
+
+    /// Infeasibilities of the blocks of the constraints $F_1(u) \in C$ at
+    /// the solution, or `None` if they have not been computed (see
+    /// `AlmOptimizer::with_block_infeasibility`) or if neither $C$ nor $Y$
+    /// is a Cartesian product
+    pub fn block_infeasibility(&self) -> Option<&[BlockInfeasibility]> {
+        self.block_infeasibility.as_deref()
+    }
+
+    /// Coordinates of the solution at which the constraints $U$ are active,
+    /// or `None` if they have not been identified (see
+    /// `AlmOptimizer::with_active_set_tolerance`)
+    pub fn active_set(&self) -> Option<&ActiveSet> {
+        self.active_set.as_ref()
+    }
+
+    /// Rows of $F_1(u)$ at which the constraints $C$ are active (or
+    /// violated), or `None` if they have not been identified or if there
+    /// is no $F_1$
+    pub fn active_rows(&self) -> Option<&ActiveSet> {
+        self.active_rows.as_ref()
+    }
+
+    /// Number of evaluations of the cost, its gradient, the mappings F1 and F2
+    /// and of projections
+    pub fn oracle_calls(&self) -> OracleCalls {
+        self.oracle_calls
+    }
+
+    /// Time spent in the phases of the inner solver, accumulated over all
+    /// inner problems, or `None` if it has not been measured (see
+    /// `AlmOptimizer::with_phase_timing`)
+    pub fn phase_times(&self) -> Option<PhaseTimes> {
+        self.phase_times
+    }
+
+    /// Record of every outer iteration (the norm of the fixed-point residual
+    /// and the cost of the inner problem, the penalty parameter and the
+    /// infeasibilities), which can be written to a CSV or a JSON file, or
+    /// `None` if the history has not been recorded (see
+    /// `AlmOptimizer::with_history`)
+    pub fn trace(&self) -> Option<&SolverTrace> {
+        self.trace.as_ref()
+    }
+}
+
+impl From<&AlmOptimizerStatus> for SolverStatus {
+    /// Summarises an `AlmOptimizerStatus` as a `SolverStatus`
+    ///
+    /// The number of iterations is the total number of inner iterations and the
+    /// norm of the fixed-point residual is that of the last inner problem; the
+    /// ALM-specific information (Lagrange multipliers, penalty parameter and
+    /// infeasibility) is discarded
+    fn from(status: &AlmOptimizerStatus) -> Self {
+        SolverStatus::new(
+            status.exit_status,
+            status.num_inner_iterations,
+            status.solve_time,
+            status.last_problem_norm_fpr,
+            status.cost,
+        )
+        .with_oracle_calls(status.oracle_calls)
+    }
+}
+
+impl fmt::Display for AlmOptimizerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "exit status         : {}", self.exit_status)?;
+        writeln!(f, "outer iterations    : {}", self.num_outer_iterations)?;
+        writeln!(f, "inner iterations    : {}", self.num_inner_iterations)?;
+        writeln!(f, "solve time          : {:.3?}", self.solve_time)?;
+        writeln!(
+            f,
+            "FPR (last inner)    : {:.6e}",
+            self.last_problem_norm_fpr
+        )?;
+        writeln!(f, "cost                : {:.6e}", self.cost)?;
+        writeln!(f, "penalty             : {:.6e}", self.penalty)?;
+        writeln!(f, "infeasibility (ALM) : {:.6e}", self.delta_y_norm)?;
+        write!(f, "infeasibility (PM)  : {:.6e}", self.f2_norm)?;
+        for block in self.block_infeasibility.iter().flatten() {
+            write!(f, "\n  {}", block)?;
+        }
+        if let Some(kkt_residual) = &self.kkt_residual {
+            write!(f, "\n{}", kkt_residual)?;
+        }
+        Ok(())
+    }
 }
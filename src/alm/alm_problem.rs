@@ -1,4 +1,9 @@
-use crate::{constraints::Constraint, FunctionCallResult};
+use crate::{
+    alm::DynAlmProblem,
+    constraints::{BoxedConstraint, Constraint},
+    ensure, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::marker::PhantomData;
 
 /// Definition of optimization problem to be solved with `AlmOptimizer`. The optimization
 /// problem has the general form
@@ -24,6 +29,11 @@ use crate::{constraints::Constraint, FunctionCallResult};
 ///   are mappings with smooth partial derivatives, and
 /// - $C\subseteq\mathbb{R}^{n_1}$ is a convex closed set on which we can easily compute projections.
 ///
+/// The parametric cost, its gradient and the mappings are `FnMut` closures,
+/// so they may own mutable workspaces (e.g., the state of an integrator).
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+///
 pub struct AlmProblem<
     MappingAlm,
     MappingPm,
@@ -32,16 +42,18 @@ pub struct AlmProblem<
     ConstraintsType,
     AlmSetC,
     LagrangeSetY,
+    T = f64,
 > where
+    T: OpEnFloat,
     // This is function F1: R^xn --> R^n1 (ALM)
-    MappingAlm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    MappingAlm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
     // This is function F2: R^xn --> R^n2 (PM)
-    MappingPm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricGradientType: Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricCostType: Fn(&[f64], &[f64], &mut f64) -> FunctionCallResult,
-    ConstraintsType: Constraint,
-    AlmSetC: Constraint,
-    LagrangeSetY: Constraint,
+    MappingPm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[T], &[T], &mut T) -> FunctionCallResult,
+    ConstraintsType: Constraint<T>,
+    AlmSetC: Constraint<T>,
+    LagrangeSetY: Constraint<T>,
 {
     //
     // NOTE: the reason why we need to define different set types (ConstraintsType,
@@ -67,6 +79,7 @@ pub struct AlmProblem<
     pub(crate) n1: usize,
     /// number of PM-type parameters (range dim of F2)
     pub(crate) n2: usize,
+    marker: PhantomData<T>,
 }
 
 impl<
@@ -77,6 +90,7 @@ impl<
         ConstraintsType,
         AlmSetC,
         LagrangeSetY,
+        T,
     >
     AlmProblem<
         MappingAlm,
@@ -86,15 +100,17 @@ impl<
         ConstraintsType,
         AlmSetC,
         LagrangeSetY,
+        T,
     >
 where
-    MappingAlm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    MappingPm: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricGradientType: Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
-    ParametricCostType: Fn(&[f64], &[f64], &mut f64) -> FunctionCallResult,
-    ConstraintsType: Constraint,
-    AlmSetC: Constraint,
-    LagrangeSetY: Constraint,
+    T: OpEnFloat,
+    MappingAlm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    MappingPm: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[T], &[T], &mut T) -> FunctionCallResult,
+    ConstraintsType: Constraint<T>,
+    AlmSetC: Constraint<T>,
+    LagrangeSetY: Constraint<T>,
 {
     ///Constructs new instance of `AlmProblem`
     ///
@@ -116,6 +132,13 @@ where
     ///
     /// Instance of `AlmProblem`
     ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if exactly one of `mapping_f1`
+    /// and `alm_set_c` is provided, if `alm_set_c` or `alm_set_y` is provided
+    /// while `n1 = 0` (or is missing while `n1 > 0`), or if `mapping_f2` is
+    /// provided while `n2 = 0` (or is missing while `n2 > 0`)
+    ///
     /// # Example
     ///
     ///
@@ -126,10 +149,11 @@ where
     /// let dpsi = |_u: &[f64], _p: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
     /// let n1 = 0;
     /// let n2 = 0;
-    /// let bounds = Ball2::new(None, 10.0);
+    /// let bounds = Ball2::new(None, 10.0).unwrap();
     /// let _alm_problem = AlmProblem::new(
     ///     bounds, NO_SET, NO_SET, psi, dpsi, NO_MAPPING, NO_MAPPING, n1, n2,
-    /// );
+    /// )
+    /// .unwrap();
     /// ```
     ///
     pub fn new(
@@ -142,18 +166,31 @@ where
         mapping_f2: Option<MappingPm>,
         n1: usize,
         n2: usize,
-    ) -> Self {
+    ) -> Result<Self, SolverError> {
         // if one of `mapping_f1` and `alm_set_c` is provided, the other one
         // should be provided as well (it's ok for both to be None)
-        assert!(
-            !(mapping_f1.is_none() ^ alm_set_c.is_none()),
-            "either F1 or C has not been provided"
-        );
-        assert!(!(alm_set_c.is_none() ^ (n1 == 0)), "C is Some iff n1 > 0");
-        assert!(!(alm_set_y.is_none() ^ (n1 == 0)), "Y is Some iff n1 > 0");
-        assert!(!(mapping_f2.is_none() ^ (n2 == 0)), "F2 is Some iff n2 > 0");
+        ensure(
+            mapping_f1.is_none() == alm_set_c.is_none(),
+            "mapping_f1",
+            "F1 must be provided together with C",
+        )?;
+        ensure(
+            alm_set_c.is_none() == (n1 == 0),
+            "alm_set_c",
+            "C is Some iff n1 > 0",
+        )?;
+        ensure(
+            alm_set_y.is_none() == (n1 == 0),
+            "alm_set_y",
+            "Y is Some iff n1 > 0",
+        )?;
+        ensure(
+            mapping_f2.is_none() == (n2 == 0),
+            "mapping_f2",
+            "F2 is Some iff n2 > 0",
+        )?;
 
-        AlmProblem {
+        Ok(AlmProblem {
             constraints,
             alm_set_c,
             alm_set_y,
@@ -163,6 +200,180 @@ where
             mapping_f2,
             n1,
             n2,
-        }
+            marker: PhantomData,
+        })
     }
 }
+
+impl<'a, T> DynAlmProblem<'a, T>
+where
+    T: OpEnFloat,
+{
+    /// Constructs new type-erased instance of `AlmProblem`
+    ///
+    /// The functions and sets are boxed, so all problems constructed with this
+    /// method have the same type, `DynAlmProblem`. The arguments and the
+    /// errors are the same as in `AlmProblem::new`; `NO_SET` and `NO_MAPPING`
+    /// can be used for the optional arguments.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use optimization_engine::{FunctionCallResult, alm::*, constraints::*};
+    ///
+    /// let psi = |_u: &[f64], _p: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
+    /// let dpsi = |_u: &[f64], _p: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
+    /// let f1 = |u: &[f64], f1u: &mut [f64]| -> FunctionCallResult {
+    ///     f1u[0] = u[0];
+    ///     Ok(())
+    /// };
+    /// let problems: Vec<DynAlmProblem> = vec![
+    ///     AlmProblem::new_boxed(
+    ///         Ball2::new(None, 10.0).unwrap(), NO_SET, NO_SET, psi, dpsi, NO_MAPPING, NO_MAPPING, 0, 0,
+    ///     )
+    ///     .unwrap(),
+    ///     AlmProblem::new_boxed(
+    ///         NoConstraints::new(),
+    ///         Some(Ball1::new(None, 1.0).unwrap()),
+    ///         Some(BallInf::new(None, 100.0).unwrap()),
+    ///         psi,
+    ///         dpsi,
+    ///         Some(f1),
+    ///         NO_MAPPING,
+    ///         1,
+    ///         0,
+    ///     )
+    ///     .unwrap(),
+    /// ];
+    /// ```
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_boxed<U, C, Y, Psi, DPsi, F1, F2>(
+        constraints: U,
+        alm_set_c: Option<C>,
+        alm_set_y: Option<Y>,
+        parametric_cost: Psi,
+        parametric_gradient: DPsi,
+        mapping_f1: Option<F1>,
+        mapping_f2: Option<F2>,
+        n1: usize,
+        n2: usize,
+    ) -> Result<Self, SolverError>
+    where
+        U: Constraint<T> + 'a,
+        C: Constraint<T> + 'a,
+        Y: Constraint<T> + 'a,
+        Psi: FnMut(&[T], &[T], &mut T) -> FunctionCallResult + 'a,
+        DPsi: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult + 'a,
+        F1: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'a,
+        F2: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'a,
+    {
+        AlmProblem::new(
+            Box::new(constraints) as BoxedConstraint<'a, T>,
+            alm_set_c.map(|c| Box::new(c) as BoxedConstraint<'a, T>),
+            alm_set_y.map(|y| Box::new(y) as BoxedConstraint<'a, T>),
+            Box::new(parametric_cost) as _,
+            Box::new(parametric_gradient) as _,
+            mapping_f1.map(|f1| Box::new(f1) as _),
+            mapping_f2.map(|f2| Box::new(f2) as _),
+            n1,
+            n2,
+        )
+    }
+}
+
+/// Constructs an `AlmProblem` from named parts
+///
+/// This is an alternative to the nine arguments of `AlmProblem::new`: the
+/// cost, its gradient and the constraints are named, and the ALM-type
+/// constraints ($F_1(u) \in C$, with the set $Y$ of Lagrange multipliers)
+/// and the PM-type constraints ($F_2(u) = 0$) are optional groups, which
+/// are given together with their dimensions or not at all. The dimensions
+/// `n1` and `n2` must be constant expressions (e.g., literals) and must be
+/// positive, which is checked at compile time, so the problem is returned
+/// directly rather than as a `Result`.
+///
+/// The optional groups, if any, follow the constraints in this order:
+///
+/// - `f1: <mapping>, n1: <dimension>, set_c: <set>, set_y: <set>`
+/// - `f2: <mapping>, n2: <dimension>`
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{alm::*, constraints::*, problem, FunctionCallResult};
+///
+/// let psi = |_u: &[f64], _xi: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
+/// let dpsi = |_u: &[f64], _xi: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
+/// let f1 = |u: &[f64], f1u: &mut [f64]| -> FunctionCallResult {
+///     f1u[0] = u[0] + u[1];
+///     Ok(())
+/// };
+/// let alm_problem = problem! {
+///     cost: psi,
+///     gradient: dpsi,
+///     constraints: Ball2::new(None, 10.0)?,
+///     f1: f1,
+///     n1: 1,
+///     set_c: Ball1::new(None, 1.0)?,
+///     set_y: BallInf::new(None, 100.0)?,
+/// };
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+///
+/// A dimension which is zero is a compile-time error:
+///
+/// ```compile_fail
+/// # use optimization_engine::{constraints::*, problem, FunctionCallResult};
+/// # let psi = |_u: &[f64], _xi: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
+/// # let dpsi = |_u: &[f64], _xi: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
+/// # let f2 = |_u: &[f64], _f2u: &mut [f64]| -> FunctionCallResult { Ok(()) };
+/// let alm_problem = problem! {
+///     cost: psi,
+///     gradient: dpsi,
+///     constraints: NoConstraints::new(),
+///     f2: f2,
+///     n2: 0,
+/// };
+/// ```
+#[macro_export]
+macro_rules! problem {
+    (
+        cost: $cost:expr,
+        gradient: $gradient:expr,
+        constraints: $constraints:expr
+        $(, f1: $f1:expr, n1: $n1:expr, set_c: $set_c:expr, set_y: $set_y:expr)?
+        $(, f2: $f2:expr, n2: $n2:expr)?
+        $(,)?
+    ) => {{
+        const __OPEN_N1: usize = 0 $(+ $n1)?;
+        const __OPEN_N2: usize = 0 $(+ $n2)?;
+        $(
+            const _: () = ::core::assert!($n1 > 0, "the dimension `n1` of F1 must be positive");
+        )?
+        $(
+            const _: () = ::core::assert!($n2 > 0, "the dimension `n2` of F2 must be positive");
+        )?
+        match $crate::alm::AlmProblem::new(
+            $constraints,
+            $crate::problem!(@optional $($set_c)?, $crate::alm::NO_SET),
+            $crate::problem!(@optional $($set_y)?, $crate::alm::NO_SET),
+            $cost,
+            $gradient,
+            $crate::problem!(@optional $($f1)?, $crate::alm::NO_MAPPING),
+            $crate::problem!(@optional $($f2)?, $crate::alm::NO_MAPPING),
+            __OPEN_N1,
+            __OPEN_N2,
+        ) {
+            ::core::result::Result::Ok(problem) => problem,
+            // the groups of the macro guarantee that the parts are consistent
+            ::core::result::Result::Err(_) => ::core::unreachable!(),
+        }
+    }};
+    (@optional , $none:expr) => {
+        $none
+    };
+    (@optional $value:expr, $none:expr) => {
+        ::core::option::Option::Some($value)
+    };
+}
@@ -0,0 +1,121 @@
+use crate::{constraints::Constraint, core::OracleCalls, matrix_operations, OpEnFloat};
+use std::fmt;
+
+/// Infeasibility of a block of the constraints $F_1(u) \in C$
+///
+/// When $C$ (or the set $Y$ of the Lagrange multipliers) is a Cartesian
+/// product, $C = C_0 \times C_1 \times \ldots \times C_{m-1}$ (see
+/// `CartesianProduct`), the rows of $F_1(u)$ are split into the blocks of
+/// the product and the infeasibility of the $i$-th block is the norm of the
+/// $i$-th block of $F_1(u) - \Pi_C(F_1(u))$, that is,
+/// $\mathrm{dist}_{C_i}(F_1(u)_i)$ if $C$ is the product. `AlmOptimizer`
+/// reports these if `AlmOptimizer::with_block_infeasibility` is activated,
+/// so that the constraints which prevent convergence can be identified.
+///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockInfeasibility {
+    /// Index of the block
+    index: usize,
+    /// Name of the block (if named, see `CartesianProduct::add_named_constraint`)
+    name: Option<String>,
+    /// First row of the block
+    start: usize,
+    /// End (exclusive) of the rows of the block
+    end: usize,
+    /// Norm of the block of `F1(u) - Proj_C(F1(u))`
+    infeasibility: f64,
+}
+
+impl BlockInfeasibility {
+    /// Computes the infeasibilities of the blocks of $F_1(u) \in C$
+    ///
+    /// # Arguments
+    ///
+    /// - `f1_u`: $F_1(u)$
+    /// - `set_c`: set $C$
+    /// - `partition`: set whose blocks (and their names) are used, that is,
+    ///   $C$ or $Y$
+    /// - `oracle_calls`: counters of oracle calls, whose `projections` are
+    ///   incremented by the projection made here
+    ///
+    /// Returns `None` if `partition` is not a Cartesian product or if its
+    /// dimension differs from that of `f1_u`
+    pub(crate) fn compute<T, C, P>(
+        f1_u: &[T],
+        set_c: &C,
+        partition: &P,
+        oracle_calls: &mut OracleCalls,
+    ) -> Option<Vec<Self>>
+    where
+        T: OpEnFloat,
+        C: Constraint<T> + ?Sized,
+        P: Constraint<T> + ?Sized,
+    {
+        let blocks = partition.blocks()?;
+        if blocks.last() != Some(&f1_u.len()) {
+            return None;
+        }
+        let mut f1_u_proj = f1_u.to_vec();
+        set_c.project(&mut f1_u_proj);
+        oracle_calls.projections += 1;
+        let mut start = 0;
+        let infeasibilities = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, &end)| {
+                let infeasibility = matrix_operations::norm2_squared_diff(
+                    &f1_u[start..end],
+                    &f1_u_proj[start..end],
+                )
+                .sqrt()
+                .as_f64();
+                let block = BlockInfeasibility {
+                    index,
+                    name: partition.block_name(index).map(str::to_owned),
+                    start,
+                    end,
+                    infeasibility,
+                };
+                start = end;
+                block
+            })
+            .collect();
+        Some(infeasibilities)
+    }
+
+    /// Index of the block
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Name of the block, if it is named
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Rows of $F_1(u)$ which belong to the block
+    pub fn rows(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+
+    /// Infeasibility of the block, that is, the norm of the block of
+    /// $F_1(u) - \Pi_C(F_1(u))$
+    pub fn infeasibility(&self) -> f64 {
+        self.infeasibility
+    }
+}
+
+impl fmt::Display for BlockInfeasibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "block {} ({})", self.index, name)?,
+            None => write!(f, "block {}", self.index)?,
+        }
+        write!(
+            f,
+            ", rows {}..{}: {:.6e}",
+            self.start, self.end, self.infeasibility
+        )
+    }
+}
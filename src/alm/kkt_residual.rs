@@ -0,0 +1,154 @@
+use crate::{constraints::Constraint, core::OracleCalls, matrix_operations, OpEnFloat};
+use std::fmt;
+
+/// Residuals of the Karush-Kuhn-Tucker (KKT) conditions at a pair $(u, y)$
+///
+/// For the problem
+///
+/// $$
+/// \mathrm{Minimize}\ f(u)\ \mathrm{subject\ to}\ u \in U,\ F_1(u) \in C,\ F_2(u) = 0,
+/// $$
+///
+/// the residuals at a point $u$ and a vector of Lagrange multipliers $y$
+/// (for the constraints $F_1(u) \in C$) are
+///
+/// - the *stationarity* residual,
+///   $\Vert u - \Pi_U(u - \nabla f(u) - JF_1(u)^\top y) \Vert$, which is
+///   equal to $\Vert \nabla f(u) + JF_1(u)^\top y \Vert$ when $u$ is in
+///   the interior of $U$ (e.g., if $U = \mathbb{R}^n$),
+/// - the *complementarity* residual,
+///   $\Vert \Pi_C(F_1(u)) - \Pi_C(F_1(u) + y) \Vert$, which is zero if and
+///   only if $y$ is in the normal cone of $C$ at $\Pi_C(F_1(u))$,
+/// - the *infeasibilities* $\mathrm{dist}_C(F_1(u))$ and $\Vert F_2(u) \Vert$.
+///
+/// All residuals are zero if and only if $(u, y)$ is a KKT point of the
+/// problem. Residuals can be computed at any pair $(u, y)$ with
+/// `AlmFactory::kkt_residual`, and `AlmOptimizer` computes them at the
+/// solution if `AlmOptimizer::with_kkt_residual` is activated.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KktResidual {
+    /// Norm of the projected gradient of the Lagrangian
+    stationarity: f64,
+    /// Distance of `y` from the normal cone of `C`, measured by projections
+    complementarity: f64,
+    /// Distance of `F1(u)` from `C`
+    f1_infeasibility: f64,
+    /// Norm of `F2(u)`
+    f2_infeasibility: f64,
+}
+
+impl KktResidual {
+    /// Computes the residuals (which are stored as `f64`)
+    ///
+    /// # Arguments
+    ///
+    /// - `u`: point $u$
+    /// - `lagrangian_gradient`: on entry, the gradient of the Lagrangian at
+    ///   `u` (it is overwritten)
+    /// - `set_u`: set $U$
+    /// - `f1_u`: on entry, $F_1(u)$ (it is overwritten)
+    /// - `y`: Lagrange multipliers (of the same length as `f1_u`)
+    /// - `set_c`: set $C$, or `None` if there are no ALM-type constraints
+    /// - `f2_u`: $F_2(u)$ (empty if there are no PM-type constraints)
+    /// - `oracle_calls`: counters of oracle calls, whose `projections` are
+    ///   incremented by the projections made here
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn compute<T, U, C>(
+        u: &[T],
+        lagrangian_gradient: &mut [T],
+        set_u: &U,
+        f1_u: &mut [T],
+        y: &[T],
+        set_c: Option<&C>,
+        f2_u: &[T],
+        oracle_calls: &mut OracleCalls,
+    ) -> Self
+    where
+        T: OpEnFloat,
+        U: Constraint<T> + ?Sized,
+        C: Constraint<T> + ?Sized,
+    {
+        // lagrangian_gradient := Proj_U(u - grad L(u, y))
+        lagrangian_gradient
+            .iter_mut()
+            .zip(u.iter())
+            .for_each(|(g, ui)| *g = *ui - *g);
+        set_u.project(lagrangian_gradient);
+        oracle_calls.projections += 1;
+        let stationarity = matrix_operations::norm2_squared_diff(u, lagrangian_gradient).sqrt();
+
+        let (complementarity, f1_infeasibility) = match set_c {
+            Some(set_c) => {
+                // projection of F1(u) on C
+                let mut f1_u_proj = f1_u.to_vec();
+                set_c.project(&mut f1_u_proj);
+                oracle_calls.projections += 1;
+                let f1_infeasibility =
+                    matrix_operations::norm2_squared_diff(f1_u, &f1_u_proj).sqrt();
+                // f1_u := Proj_C(F1(u) + y)
+                f1_u.iter_mut()
+                    .zip(y.iter())
+                    .for_each(|(fi, yi)| *fi += *yi);
+                set_c.project(f1_u);
+                oracle_calls.projections += 1;
+                let complementarity =
+                    matrix_operations::norm2_squared_diff(f1_u, &f1_u_proj).sqrt();
+                (complementarity, f1_infeasibility)
+            }
+            None => (T::zero(), T::zero()),
+        };
+
+        KktResidual {
+            stationarity: stationarity.as_f64(),
+            complementarity: complementarity.as_f64(),
+            f1_infeasibility: f1_infeasibility.as_f64(),
+            f2_infeasibility: if f2_u.is_empty() {
+                0.0
+            } else {
+                matrix_operations::norm2(f2_u).as_f64()
+            },
+        }
+    }
+
+    /// Stationarity residual, $\Vert u - \Pi_U(u - \nabla f(u) - JF_1(u)^\top y) \Vert$
+    pub fn stationarity(&self) -> f64 {
+        self.stationarity
+    }
+
+    /// Complementarity residual, $\Vert \Pi_C(F_1(u)) - \Pi_C(F_1(u) + y) \Vert$
+    pub fn complementarity(&self) -> f64 {
+        self.complementarity
+    }
+
+    /// Infeasibility of the constraints $F_1(u) \in C$, that is,
+    /// $\mathrm{dist}_C(F_1(u))$
+    pub fn f1_infeasibility(&self) -> f64 {
+        self.f1_infeasibility
+    }
+
+    /// Infeasibility of the constraints $F_2(u) = 0$, that is,
+    /// $\Vert F_2(u) \Vert$
+    pub fn f2_infeasibility(&self) -> f64 {
+        self.f2_infeasibility
+    }
+
+    /// Largest of the residuals
+    pub fn max(&self) -> f64 {
+        self.stationarity
+            .max(self.complementarity)
+            .max(self.f1_infeasibility)
+            .max(self.f2_infeasibility)
+    }
+}
+
+impl fmt::Display for KktResidual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "KKT stationarity    : {:.6e}", self.stationarity)?;
+        writeln!(f, "KKT complementarity : {:.6e}", self.complementarity)?;
+        writeln!(f, "KKT infeas. (F1)    : {:.6e}", self.f1_infeasibility)?;
+        write!(f, "KKT infeas. (F2)    : {:.6e}", self.f2_infeasibility)
+    }
+}
@@ -28,34 +28,50 @@
 //! [`AlmOptimizerStatus`]: struct.AlmOptimizerStatus.html
 //! [`AlmFactory`]: struct.AlmFactory.html
 //!
+mod affine_mapping;
 mod alm_cache;
+mod alm_checkpoint;
+mod alm_config;
+mod alm_const;
 mod alm_factory;
+mod alm_filter;
 mod alm_optimizer;
 mod alm_optimizer_status;
 mod alm_problem;
+mod block_infeasibility;
+mod kkt_residual;
 
+pub use affine_mapping::AffineMapping;
 pub use alm_cache::AlmCache;
+pub use alm_checkpoint::AlmCheckpoint;
+pub use alm_config::AlmConfig;
+pub use alm_const::{AlmCacheConst, AlmOptimizerConst};
 pub use alm_factory::AlmFactory;
 pub use alm_optimizer::AlmOptimizer;
 pub use alm_optimizer_status::AlmOptimizerStatus;
 pub use alm_problem::AlmProblem;
+pub use block_infeasibility::BlockInfeasibility;
+pub use kkt_residual::KktResidual;
 
 /// Type of mappings $F_1(u)$ and $F_2(u)$
 ///
 /// Mappings $F_1$ and $F_2$ are computed by functions with signature
 ///
 /// ```ignore
-/// fn mapping_f(&[f64], &mut [f64]) -> Result<(), crate::SolverError>
+/// fn mapping_f(&[T], &mut [T]) -> Result<(), crate::SolverError>
 /// ```
-pub type MappingType = fn(&[f64], &mut [f64]) -> Result<(), crate::SolverError>;
+///
+/// where `T` is the scalar type (`f64` by default)
+pub type MappingType<T = f64> = fn(&[T], &mut [T]) -> Result<(), crate::SolverError>;
 
 /// Type of the Jacobian of mappings $F_1$ and $F_2$
 ///
 /// These are mappings $(u, d) \mapsto JF_1(u)^\top d$, for given vectors $u\in\mathbb{R}$
 /// and $d\in\mathbb{R}^{n_1}$ (similarly for $F_2$)
-pub type JacobianMappingType = fn(&[f64], &[f64], &mut [f64]) -> Result<(), crate::SolverError>;
+pub type JacobianMappingType<T = f64> = fn(&[T], &[T], &mut [T]) -> Result<(), crate::SolverError>;
 
-/// No mapping $F_1(u)$ or $F_2(u)$ is specified
+/// No mapping $F_1(u)$ or $F_2(u)$ is specified (for other scalar types
+/// than `f64`, use `None::<MappingType<T>>`)
 pub const NO_MAPPING: Option<MappingType> = None::<MappingType>;
 
 /// No Jacobian mapping is specified for $F_1$ and $F_2$
@@ -65,6 +81,48 @@ pub const NO_JACOBIAN_MAPPING: Option<JacobianMappingType> = None::<JacobianMapp
 pub const NO_SET: Option<crate::constraints::NoConstraints> =
     None::<crate::constraints::NoConstraints>;
 
+/// Boxed (type-erased) mapping $F_1(u)$ or $F_2(u)$
+pub type BoxedMapping<'a, T = f64> =
+    Box<dyn FnMut(&[T], &mut [T]) -> crate::FunctionCallResult + 'a>;
+
+/// Boxed (type-erased) parametric cost, $\psi(u, \xi)$
+pub type BoxedParametricCost<'a, T = f64> =
+    Box<dyn FnMut(&[T], &[T], &mut T) -> crate::FunctionCallResult + 'a>;
+
+/// Boxed (type-erased) gradient of the parametric cost, $\nabla_u \psi(u, \xi)$
+pub type BoxedParametricGradient<'a, T = f64> =
+    Box<dyn FnMut(&[T], &[T], &mut [T]) -> crate::FunctionCallResult + 'a>;
+
+/// Type-erased ALM/PM problem
+///
+/// All problems constructed with `AlmProblem::new_boxed` have this type,
+/// regardless of the types of their functions and sets, so that
+/// `AlmOptimizer` needs to be compiled only once for all of them (see
+/// `DynAlmOptimizer`)
+pub type DynAlmProblem<'a, T = f64> = AlmProblem<
+    BoxedMapping<'a, T>,
+    BoxedMapping<'a, T>,
+    BoxedParametricGradient<'a, T>,
+    BoxedParametricCost<'a, T>,
+    crate::constraints::BoxedConstraint<'a, T>,
+    crate::constraints::BoxedConstraint<'a, T>,
+    crate::constraints::BoxedConstraint<'a, T>,
+    T,
+>;
+
+/// ALM/PM optimizer for type-erased problems (see `DynAlmProblem`)
+pub type DynAlmOptimizer<'life, 'a, T = f64> = AlmOptimizer<
+    'life,
+    BoxedMapping<'a, T>,
+    BoxedMapping<'a, T>,
+    BoxedParametricGradient<'a, T>,
+    BoxedParametricCost<'a, T>,
+    crate::constraints::BoxedConstraint<'a, T>,
+    crate::constraints::BoxedConstraint<'a, T>,
+    crate::constraints::BoxedConstraint<'a, T>,
+    T,
+>;
+
 /* ---------------------------------------------------------------------------- */
 /*          TESTS                                                               */
 /* ---------------------------------------------------------------------------- */
@@ -1,6 +1,9 @@
 use crate::{
     alm::*,
-    core::{constraints::*, panoc::*, ExitStatus},
+    core::{
+        constraints::*, panoc::*, ExitStatus, ManualClock, OracleCalls, Progress, ProgressEvent,
+        Verbosity,
+    },
     matrix_operations, mocks, FunctionCallResult, SolverError,
 };
 
@@ -11,7 +14,7 @@ fn t_create_alm_cache() {
     let n1 = 5;
     let n2 = 0;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let alm_cache = AlmCache::new(panoc_cache, n1, n2);
     assert!(alm_cache.iteration == 0, "iter != 0");
     assert!(
@@ -28,6 +31,22 @@ fn t_create_alm_cache() {
     );
 }
 
+#[test]
+fn t_alm_cache_memory_footprint() {
+    let panoc_cache = PANOCCache::new(10, 1e-6, 5).unwrap();
+    let panoc_footprint = panoc_cache.memory_footprint();
+    let alm_cache = AlmCache::new(panoc_cache, 0, 0);
+    let footprint = alm_cache.memory_footprint();
+    assert!(footprint > panoc_footprint);
+
+    // y_plus, xi and w_alm_aux (n1 + 1 floats in xi) and w_pm
+    let alm_cache = AlmCache::new(PANOCCache::new(10, 1e-6, 5).unwrap(), 4, 3);
+    assert_eq!(
+        footprint + (3 * 4 + 1 + 3) * std::mem::size_of::<f64>(),
+        alm_cache.memory_footprint()
+    );
+}
+
 #[test]
 fn t_create_alm_problem() {
     let f = |_u: &[f64], _p: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
@@ -37,17 +56,18 @@ fn t_create_alm_problem() {
         // Construct an instance of AlmProblem without any AL-type data
         let n1 = 0;
         let n2 = 0;
-        let bounds = Ball2::new(None, 10.0);
+        let bounds = Ball2::new(None, 10.0).unwrap();
         let _alm_problem = AlmProblem::new(
             bounds, NO_SET, NO_SET, f, df, NO_MAPPING, NO_MAPPING, n1, n2,
-        );
+        )
+        .unwrap();
     }
 
     {
         // Construct an AlmProblem with AL-type constraints, but no PM-type ones
         let f1 = |_u: &[f64], _f1up: &mut [f64]| -> FunctionCallResult { Ok(()) };
-        let soc = SecondOrderCone::new(1.5);
-        let bounds = Ball2::new(None, 10.0);
+        let soc = SecondOrderCone::new(1.5).unwrap();
+        let bounds = Ball2::new(None, 10.0).unwrap();
         let y_set = NoConstraints::new();
         let _alm_problem = AlmProblem::new(
             bounds,
@@ -59,15 +79,16 @@ fn t_create_alm_problem() {
             NO_MAPPING,
             1,
             0,
-        );
+        )
+        .unwrap();
     }
 
     {
         // Construct an AlmProblem with both AL-type and PM-type constraints
         let f1 = |_u: &[f64], _f1up: &mut [f64]| -> FunctionCallResult { Ok(()) };
         let f2 = |_u: &[f64], _f2up: &mut [f64]| -> FunctionCallResult { Ok(()) };
-        let soc = SecondOrderCone::new(1.5);
-        let bounds = Ball2::new(None, 10.0);
+        let soc = SecondOrderCone::new(1.5).unwrap();
+        let bounds = Ball2::new(None, 10.0).unwrap();
         let y_set = NoConstraints::new();
         let _alm_problem = AlmProblem::new(
             bounds,
@@ -79,11 +100,11 @@ fn t_create_alm_problem() {
             Some(f2),
             1,
             1,
-        );
+        )
+        .unwrap();
     }
 }
 
-#[should_panic]
 #[test]
 fn t_create_alm_problem_fail_alm() {
     let f = |_u: &[f64], _p: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
@@ -91,8 +112,8 @@ fn t_create_alm_problem_fail_alm() {
     let n1 = 1; // n1 = 1, but there is no set C
     let n2 = 0; // no F2 (and n2 = 0)
     let y_set = NoConstraints::new();
-    let bounds = Ball2::new(None, 10.0);
-    let _alm_problem = AlmProblem::new(
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let alm_problem = AlmProblem::new(
         bounds,
         NO_SET,
         Some(y_set),
@@ -103,18 +124,25 @@ fn t_create_alm_problem_fail_alm() {
         n1,
         n2,
     );
+    assert!(matches!(
+        alm_problem,
+        Err(SolverError::InvalidParameter {
+            name: "alm_set_c",
+            ..
+        })
+    ));
 }
 
-#[should_panic]
 #[test]
 fn t_create_alm_problem_fail_pm() {
     let f = |_u: &[f64], _p: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
     let df = |_u: &[f64], _p: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
     let f2 = |_u: &[f64], _f2up: &mut [f64]| -> FunctionCallResult { Ok(()) };
-    let bounds = Ball2::new(None, 10.0);
+    let bounds = Ball2::new(None, 10.0).unwrap();
     let n1 = 1;
     let n2 = 0; // there is an f2, but n2 = 0
-    let _alm_problem = AlmProblem::new(bounds, NO_SET, NO_SET, f, df, NO_MAPPING, Some(f2), n1, n2);
+    let alm_problem = AlmProblem::new(bounds, NO_SET, NO_SET, f, df, NO_MAPPING, Some(f2), n1, n2);
+    assert!(alm_problem.is_err());
 }
 
 #[test]
@@ -124,17 +152,17 @@ fn t_create_alm_optimizer() {
     let n1 = 5;
     let n2 = 0;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
     let f = |_u: &[f64], _p: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
     let df = |_u: &[f64], _p: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
     let f1 = |_u: &[f64], _result: &mut [f64]| -> FunctionCallResult { Ok(()) };
-    let set_c = Ball2::new(None, 1.50);
+    let set_c = Ball2::new(None, 1.50).unwrap();
 
     // Construct an instance of AlmProblem without any PM-type data
-    let bounds = Ball2::new(None, 10.0);
-    let set_y = Ball2::new(None, 1.0);
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let set_y = Ball2::new(None, 1.0).unwrap();
     let alm_problem = AlmProblem::new(
         bounds,
         Some(set_c),
@@ -145,12 +173,16 @@ fn t_create_alm_optimizer() {
         NO_MAPPING,
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-4)
+        .unwrap()
         .with_max_outer_iterations(10)
-        .with_initial_lagrange_multipliers(&vec![5.0; n1]);
+        .unwrap()
+        .with_initial_lagrange_multipliers(&vec![5.0; n1])
+        .unwrap();
 
     let mut u = vec![0.0; nx];
     println!("result = {:?}", alm_optimizer.solve(&mut u));
@@ -163,7 +195,7 @@ fn t_alm_only_penalty_method() {
     let n1 = 0;
     let n2 = 1;
     let lbfgs_mem = 5;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
     let bounds = NoConstraints::new();
@@ -200,7 +232,8 @@ fn t_alm_only_penalty_method() {
         Some(jf2t),
         NO_SET,
         n2,
-    );
+    )
+    .unwrap();
 
     let alm_problem = AlmProblem::new(
         bounds,
@@ -216,15 +249,22 @@ fn t_alm_only_penalty_method() {
         Some(f2),
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-6)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
+        .unwrap()
         .with_max_outer_iterations(20)
+        .unwrap()
         .with_max_inner_iterations(1000)
+        .unwrap()
         .with_initial_penalty(5000.0)
-        .with_penalty_update_factor(2.2);
+        .unwrap()
+        .with_penalty_update_factor(2.2)
+        .unwrap();
 
     let mut u = vec![0.1; nx];
     let solver_result = alm_optimizer.solve(&mut u);
@@ -232,6 +272,12 @@ fn t_alm_only_penalty_method() {
     let r = solver_result.unwrap();
     assert!(r.exit_status() == ExitStatus::Converged);
     assert!(r.f2_norm() < 1e-6);
+
+    // penalty-only problem: no multipliers are updated and F1 is never called
+    assert!(r.lagrange_multipliers().is_none());
+    assert_eq!(0.0, r.delta_y_norm_over_c());
+    assert_eq!(0, r.oracle_calls().mapping_f1());
+    assert!(r.oracle_calls().mapping_f2() > 0);
 }
 
 #[test]
@@ -241,12 +287,12 @@ fn t_alm_numeric_test_1() {
     let n1 = 2;
     let n2 = 0;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
-    let set_c = Ball2::new(None, 1.0);
-    let bounds = Ball2::new(None, 10.0);
-    let set_y = Ball2::new(None, 10000.0);
+    let set_c = Ball2::new(None, 1.0).unwrap();
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let set_y = Ball2::new(None, 10000.0).unwrap();
 
     let factory = AlmFactory::new(
         mocks::f0,
@@ -257,9 +303,10 @@ fn t_alm_numeric_test_1() {
         NO_JACOBIAN_MAPPING,
         Some(set_c),
         n2,
-    );
+    )
+    .unwrap();
 
-    let set_c_b = Ball2::new(None, 1.0);
+    let set_c_b = Ball2::new(None, 1.0).unwrap();
     let alm_problem = AlmProblem::new(
         bounds,
         Some(set_c_b),
@@ -272,18 +319,28 @@ fn t_alm_numeric_test_1() {
         NO_MAPPING,
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-4)
+        .unwrap()
         .with_max_outer_iterations(30)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
+        .unwrap()
         .with_initial_inner_tolerance(1e-2)
+        .unwrap()
         .with_inner_tolerance_update_factor(0.5)
+        .unwrap()
         .with_initial_penalty(1.0)
+        .unwrap()
         .with_penalty_update_factor(1.2)
+        .unwrap()
         .with_sufficient_decrease_coefficient(0.1)
-        .with_initial_lagrange_multipliers(&vec![5.0; n1]);
+        .unwrap()
+        .with_initial_lagrange_multipliers(&vec![5.0; n1])
+        .unwrap();
 
     let mut u = vec![0.0; nx];
     let solver_result = alm_optimizer.solve(&mut u);
@@ -312,6 +369,198 @@ fn t_alm_numeric_test_1() {
     );
 }
 
+#[test]
+fn t_alm_affine_mapping() {
+    let (nx, n1, n2) = (3, 2, 0);
+    // the affine mapping of mocks::mapping_f1_affine
+    let f1 = AffineMapping::new(vec![2.0, 0.0, 1.0, 1.0, 3.0, 0.0], vec![-1.0, 0.0], nx).unwrap();
+    assert_eq!((2, 3), (f1.n1(), f1.dimension()));
+    let u = [0.5, -1.0, 2.0];
+    let (mut f1u, mut f1u_expected) = ([0.0; 2], [0.0; 2]);
+    f1.mapping(&u, &mut f1u).unwrap();
+    mocks::mapping_f1_affine(&u, &mut f1u_expected).unwrap();
+    assert_eq!(f1u_expected, f1u);
+    let (mut jtd, mut jtd_expected) = ([0.0; 3], [0.0; 3]);
+    f1.jacobian_transpose_product(&u, &[1.5, -2.0], &mut jtd)
+        .unwrap();
+    mocks::mapping_f1_affine_jacobian_product(&u, &[1.5, -2.0], &mut jtd_expected).unwrap();
+    assert_eq!(jtd_expected, jtd);
+    // a second evaluation at the same point does not compute Au
+    f1.mapping(&u, &mut f1u).unwrap();
+    assert_eq!(1, f1.matvecs());
+    assert!(matches!(
+        f1.mapping(&[0.0; 2], &mut f1u),
+        Err(SolverError::DimensionMismatch { name: "u", .. })
+    ));
+    // the same mapping with a matrix-free operator (here, a reference)
+    let f1_operator = AffineMapping::with_operator(f1.operator(), vec![-1.0, 0.0]).unwrap();
+    f1_operator.mapping(&u, &mut f1u).unwrap();
+    assert_eq!(f1u_expected, f1u);
+    assert!(AffineMapping::with_operator(f1.operator(), vec![0.0; 3]).is_err());
+
+    let mut solutions = vec![];
+    let mut calls = OracleCalls::default();
+    for affine in [false, true] {
+        let panoc_cache = PANOCCache::new(nx, 1e-8, 3).unwrap();
+        let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
+        let mapping = |u: &[f64], f1u: &mut [f64]| -> FunctionCallResult {
+            if affine {
+                f1.mapping(u, f1u)
+            } else {
+                mocks::mapping_f1_affine(u, f1u)
+            }
+        };
+        let factory = AlmFactory::new(
+            mocks::f0,
+            mocks::d_f0,
+            Some(mapping),
+            Some(|u: &[f64], d: &[f64], res: &mut [f64]| f1.jacobian_transpose_product(u, d, res)),
+            NO_MAPPING,
+            NO_JACOBIAN_MAPPING,
+            Some(Ball2::new(None, 1.0).unwrap()),
+            n2,
+        )
+        .unwrap();
+        let alm_problem = AlmProblem::new(
+            Ball2::new(None, 10.0).unwrap(),
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(Ball2::new(None, 10000.0).unwrap()),
+            |u: &[f64], xi: &[f64], cost: &mut f64| factory.psi(u, xi, cost),
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| factory.d_psi(u, xi, grad),
+            Some(mapping),
+            NO_MAPPING,
+            n1,
+            n2,
+        )
+        .unwrap();
+        let mut u = vec![0.0; nx];
+        let status = AlmOptimizer::new(&mut alm_cache, alm_problem)
+            .with_delta_tolerance(1e-6)
+            .unwrap()
+            .solve(&mut u)
+            .unwrap();
+        assert_eq!(ExitStatus::Converged, status.exit_status());
+        solutions.push(u);
+        calls = status.oracle_calls();
+    }
+    unit_test_utils::assert_nearly_equal_array(&solutions[0], &solutions[1], 1e-10, 1e-10, "u");
+    // F1 is evaluated by psi, d_psi and the update of the multipliers, but
+    // Au is computed at most once per point
+    assert!(f1.matvecs() < calls.cost() + calls.gradient() + calls.mapping_f1());
+
+    // scalar types other than f64
+    let f1 = AffineMapping::new(vec![1.0_f32, 2.0, 0.0, 1.0], vec![0.5, -1.0], 2).unwrap();
+    let (mut f1_u, mut a_t_d) = ([0.0_f32; 2], [0.0_f32; 2]);
+    f1.mapping(&[1.0, 1.0], &mut f1_u).unwrap();
+    f1.jacobian_transpose_product(&[1.0, 1.0], &[1.0, 1.0], &mut a_t_d)
+        .unwrap();
+    assert_eq!([3.5, 0.0], f1_u);
+    assert_eq!([1.0, 3.0], a_t_d);
+}
+
+#[test]
+fn t_alm_f32() {
+    // minimise (u0 - 1)^2 + (u1 - 1)^2 subject to u0 + u1 <= 1, whose solution
+    // is u* = (0.5, 0.5) with the Lagrange multiplier y* = 1
+    let excess = |u: &[f32], xi: &[f32]| (u[0] + u[1] + xi[1] / xi[0] - 1.0).max(0.0);
+    let psi = |u: &[f32], xi: &[f32], cost: &mut f32| -> FunctionCallResult {
+        *cost = (u[0] - 1.0).powi(2) + (u[1] - 1.0).powi(2) + 0.5 * xi[0] * excess(u, xi).powi(2);
+        Ok(())
+    };
+    let d_psi = |u: &[f32], xi: &[f32], grad: &mut [f32]| -> FunctionCallResult {
+        let penalty_gradient = xi[0] * excess(u, xi);
+        grad[0] = 2.0 * (u[0] - 1.0) + penalty_gradient;
+        grad[1] = 2.0 * (u[1] - 1.0) + penalty_gradient;
+        Ok(())
+    };
+    let f1 = |u: &[f32], f1_u: &mut [f32]| -> FunctionCallResult {
+        f1_u[0] = u[0] + u[1];
+        Ok(())
+    };
+    let xmax = [1.0_f32];
+    let alm_problem = AlmProblem::new(
+        NoConstraints::new(),
+        Some(Rectangle::new(None, Some(&xmax)).unwrap()),
+        Some(BallInf::new(None, 1e6_f32).unwrap()),
+        psi,
+        d_psi,
+        Some(f1),
+        None::<MappingType<f32>>,
+        1,
+        0,
+    )
+    .unwrap();
+    let mut alm_cache = AlmCache::new(PANOCCache::new(2, 1e-4_f32, 5).unwrap(), 1, 0);
+    let mut u = [0.0_f32; 2];
+    let status = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_delta_tolerance(1e-4)
+        .unwrap()
+        .with_epsilon_tolerance(1e-4)
+        .unwrap()
+        .with_kkt_residual(true)
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    unit_test_utils::assert_nearly_equal_array(&[0.5_f32, 0.5], &u, 1e-3, 1e-3, "u");
+    let y = status.lagrange_multipliers().as_ref().unwrap();
+    unit_test_utils::assert_nearly_equal(1.0, y[0], 1e-2, 1e-2, "y");
+    assert!(status.kkt_residual().unwrap().max() < 1e-2);
+}
+
+#[test]
+fn t_alm_pgbb_inner_solver() {
+    let (nx, n1, n2) = (3, 2, 0);
+    let mut solutions = vec![];
+    for use_pgbb in [false, true] {
+        let panoc_cache = PANOCCache::new(nx, 1e-8, 1).unwrap();
+        let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
+        if use_pgbb {
+            alm_cache = alm_cache.with_pgbb_inner_solver();
+        }
+        let factory = AlmFactory::new(
+            mocks::f0,
+            mocks::d_f0,
+            Some(mocks::mapping_f1_affine),
+            Some(mocks::mapping_f1_affine_jacobian_product),
+            NO_MAPPING,
+            NO_JACOBIAN_MAPPING,
+            Some(Ball2::new(None, 1.0).unwrap()),
+            n2,
+        )
+        .unwrap();
+        let alm_problem = AlmProblem::new(
+            Ball2::new(None, 10.0).unwrap(),
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(Ball2::new(None, 10000.0).unwrap()),
+            |u: &[f64], xi: &[f64], cost: &mut f64| factory.psi(u, xi, cost),
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| factory.d_psi(u, xi, grad),
+            Some(mocks::mapping_f1_affine),
+            NO_MAPPING,
+            n1,
+            n2,
+        )
+        .unwrap();
+        let mut u = vec![0.0; nx];
+        let status = AlmOptimizer::new(&mut alm_cache, alm_problem)
+            .with_epsilon_tolerance(1e-6)
+            .unwrap()
+            .with_initial_penalty(1.0)
+            .unwrap()
+            .solve(&mut u)
+            .unwrap();
+        assert_eq!(ExitStatus::Converged, status.exit_status());
+        // PGBB, unlike PANOC, evaluates the gradient once per iteration
+        if use_pgbb {
+            assert_eq!(
+                status.oracle_calls().gradient(),
+                status.num_inner_iterations() + status.num_outer_iterations()
+            );
+        }
+        solutions.push(u);
+    }
+    unit_test_utils::assert_nearly_equal_array(&solutions[0], &solutions[1], 1e-3, 1e-4, "u");
+}
+
 fn mapping_f2(u: &[f64], res: &mut [f64]) -> FunctionCallResult {
     res[0] = u[0];
     res[1] = u[1];
@@ -334,12 +583,12 @@ fn t_alm_numeric_test_2() {
     let n1 = 2;
     let n2 = 4;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
-    let set_c = Ball2::new(None, 1.0);
-    let bounds = Ball2::new(None, 10.0);
-    let set_y = Ball2::new(None, 10000.0);
+    let set_c = Ball2::new(None, 1.0).unwrap();
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let set_y = Ball2::new(None, 10000.0).unwrap();
 
     let factory = AlmFactory::new(
         mocks::f0,
@@ -350,9 +599,10 @@ fn t_alm_numeric_test_2() {
         Some(jac_mapping_f2_tr),
         Some(set_c),
         n2,
-    );
+    )
+    .unwrap();
 
-    let set_c_b = Ball2::new(None, 1.0);
+    let set_c_b = Ball2::new(None, 1.0).unwrap();
     let alm_problem = AlmProblem::new(
         bounds,
         Some(set_c_b),
@@ -365,12 +615,16 @@ fn t_alm_numeric_test_2() {
         Some(mapping_f2),
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-4)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
-        .with_initial_inner_tolerance(1e-4);
+        .unwrap()
+        .with_initial_inner_tolerance(1e-4)
+        .unwrap();
 
     let mut u = vec![0.0; nx];
     let solver_result = alm_optimizer.solve(&mut u);
@@ -391,6 +645,173 @@ fn t_alm_numeric_test_2() {
     println!("y = {:#?}", r.lagrange_multipliers());
 }
 
+#[test]
+fn t_alm_problem_macro() {
+    let f = |_u: &[f64], _p: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) };
+    let df = |_u: &[f64], _p: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) };
+
+    let alm_problem = crate::problem! {
+        cost: f,
+        gradient: df,
+        constraints: Ball2::new(None, 10.0).unwrap(),
+    };
+    assert_eq!((0, 0), (alm_problem.n1, alm_problem.n2));
+    assert!(alm_problem.mapping_f1.is_none() && alm_problem.alm_set_c.is_none());
+
+    let alm_problem = crate::problem! {
+        cost: f,
+        gradient: df,
+        constraints: NoConstraints::new(),
+        f2: mapping_f2,
+        n2: 4,
+    };
+    assert_eq!((0, 4), (alm_problem.n1, alm_problem.n2));
+    assert!(alm_problem.mapping_f2.is_some() && alm_problem.alm_set_y.is_none());
+
+    // the problem of `t_alm_numeric_test_2`
+    let (nx, n1, n2) = (3, 2, 4);
+    let factory = AlmFactory::new(
+        mocks::f0,
+        mocks::d_f0,
+        Some(mocks::mapping_f1_affine),
+        Some(mocks::mapping_f1_affine_jacobian_product),
+        Some(mapping_f2),
+        Some(jac_mapping_f2_tr),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        n2,
+    )
+    .unwrap();
+    let alm_problem = crate::problem! {
+        cost: |u: &[f64], xi: &[f64], cost: &mut f64| factory.psi(u, xi, cost),
+        gradient: |u: &[f64], xi: &[f64], grad: &mut [f64]| factory.d_psi(u, xi, grad),
+        constraints: Ball2::new(None, 10.0).unwrap(),
+        f1: mocks::mapping_f1_affine,
+        n1: 2,
+        set_c: Ball2::new(None, 1.0).unwrap(),
+        set_y: Ball2::new(None, 10000.0).unwrap(),
+        f2: mapping_f2,
+        n2: 4,
+    };
+    assert_eq!((n1, n2), (alm_problem.n1, alm_problem.n2));
+    let mut alm_cache = AlmCache::new(PANOCCache::new(nx, 1e-8, 3).unwrap(), n1, n2);
+    let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_delta_tolerance(1e-4)
+        .unwrap()
+        .with_epsilon_tolerance(1e-5)
+        .unwrap()
+        .with_initial_inner_tolerance(1e-4)
+        .unwrap();
+    let mut u = vec![0.0; nx];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+}
+
+#[test]
+fn t_alm_send() {
+    fn assert_send_sync<S: Send + Sync>() {}
+    assert_send_sync::<AlmCache>();
+
+    let (nx, n1, n2) = (3, 2, 0);
+    let alm_problem = || {
+        AlmProblem::new(
+            Ball2::new(None, 10.0).unwrap(),
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(Ball2::new(None, 10000.0).unwrap()),
+            // psi(u; c, y) = f0(u) + c/2 dist^2(F1(u) + y/c, C), with F1(u) = u[..2]
+            |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                mocks::f0(u, cost)?;
+                let (c, y) = (xi[0], &xi[1..]);
+                let t_norm = matrix_operations::norm2(&[u[0] + y[0] / c, u[1] + y[1] / c]);
+                *cost += 0.5 * c * (t_norm - 1.0).max(0.0).powi(2);
+                Ok(())
+            },
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                mocks::d_f0(u, grad)?;
+                let (c, y) = (xi[0], &xi[1..]);
+                let t = [u[0] + y[0] / c, u[1] + y[1] / c];
+                let t_norm = matrix_operations::norm2(&t);
+                let scale = c * (1.0 - 1.0 / t_norm).max(0.0);
+                grad[0] += scale * t[0];
+                grad[1] += scale * t[1];
+                Ok(())
+            },
+            Some(|u: &[f64], f1_u: &mut [f64]| -> FunctionCallResult {
+                f1_u.copy_from_slice(&u[..2]);
+                Ok(())
+            }),
+            NO_MAPPING,
+            n1,
+            n2,
+        )
+        .unwrap()
+    };
+    let mut u_reference = vec![0.0; nx];
+    let status_reference = AlmOptimizer::from_dimensions(alm_problem(), nx, 1e-8, 3)
+        .unwrap()
+        .solve(&mut u_reference)
+        .unwrap();
+    assert_eq!(ExitStatus::Converged, status_reference.exit_status());
+
+    // an optimizer which owns its cache is solved on a worker thread
+    let mut alm_optimizer = AlmOptimizer::from_dimensions(alm_problem(), nx, 1e-8, 3).unwrap();
+    let u = std::thread::spawn(move || {
+        let mut u = vec![0.0; nx];
+        alm_optimizer.solve(&mut u).unwrap();
+        u
+    })
+    .join()
+    .unwrap();
+    assert_eq!(u_reference, u);
+}
+
+#[test]
+fn t_alm_fnmut_oracles() {
+    let (nx, n1, n2) = (3, 2, 0);
+    let factory = AlmFactory::new(
+        mocks::f0,
+        mocks::d_f0,
+        Some(mocks::mapping_f1_affine),
+        Some(mocks::mapping_f1_affine_jacobian_product),
+        NO_MAPPING,
+        NO_JACOBIAN_MAPPING,
+        Some(Ball2::new(None, 1.0).unwrap()),
+        n2,
+    )
+    .unwrap();
+    // the mapping F1 owns a workspace and counts its calls
+    let mut f1_calls = 0;
+    let mut workspace = vec![0.0; n1];
+    let f1 = |u: &[f64], f1_u: &mut [f64]| -> FunctionCallResult {
+        f1_calls += 1;
+        mocks::mapping_f1_affine(u, &mut workspace)?;
+        f1_u.copy_from_slice(&workspace);
+        Ok(())
+    };
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        Some(Ball2::new(None, 10000.0).unwrap()),
+        |u: &[f64], xi: &[f64], cost: &mut f64| factory.psi(u, xi, cost),
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| factory.d_psi(u, xi, grad),
+        Some(f1),
+        NO_MAPPING,
+        n1,
+        n2,
+    )
+    .unwrap();
+    let mut alm_cache = AlmCache::new(PANOCCache::new(nx, 1e-8, 3).unwrap(), n1, n2);
+    let status = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_delta_tolerance(1e-4)
+        .unwrap()
+        .with_epsilon_tolerance(1e-5)
+        .unwrap()
+        .solve(&mut vec![0.0; nx])
+        .unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    assert!(f1_calls > 0);
+    assert_eq!(status.oracle_calls().mapping_f1(), f1_calls);
+}
+
 // Trait alias (type aliases are not stable yet, so the alternative is to use
 // the following trait definition, i.e., to "extend" Fn and implement it)
 // See https://bit.ly/2zJvd6g
@@ -421,19 +842,19 @@ fn t_alm_numeric_test_repeat() {
     let n1 = 2;
     let n2 = 4;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
     for i in 1..3 {
-        let bounds = Ball2::new(None, 10.0);
-        let set_y = Ball2::new(None, 10000.0);
+        let bounds = Ball2::new(None, 10.0).unwrap();
+        let set_y = Ball2::new(None, 10000.0).unwrap();
 
         let parameter = [1.0, 2.0 * i as f64];
 
         let f0 = make_mockito_f0(1.0, &parameter);
         let jf = make_mockito_jacobian(&parameter);
 
-        let set_c_b = Ball2::new(None, 1.0);
+        let set_c_b = Ball2::new(None, 1.0).unwrap();
         let alm_problem = AlmProblem::new(
             bounds,
             Some(set_c_b),
@@ -444,14 +865,18 @@ fn t_alm_numeric_test_repeat() {
             Some(mapping_f2),
             n1,
             n2,
-        );
+        )
+        .unwrap();
 
         // ALM *borrows* the cache: lovely! Otherwise we wouldn't be able to
         // run this in a loop with the cache allocated outside the loop once
         let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
             .with_delta_tolerance(1e-4)
+            .unwrap()
             .with_epsilon_tolerance(1e-5)
-            .with_initial_inner_tolerance(1e-4);
+            .unwrap()
+            .with_initial_inner_tolerance(1e-4)
+            .unwrap();
 
         let mut u = vec![0.0; nx];
         let solver_result = alm_optimizer.solve(&mut u);
@@ -467,12 +892,12 @@ fn t_alm_numeric_test_out_of_outer_iterations() {
     let n1 = 2;
     let n2 = 4;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
-    let set_c = Ball2::new(None, 1.0);
-    let bounds = Ball2::new(None, 10.0);
-    let set_y = Ball2::new(None, 10000.0);
+    let set_c = Ball2::new(None, 1.0).unwrap();
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let set_y = Ball2::new(None, 10000.0).unwrap();
 
     let factory = AlmFactory::new(
         mocks::f0,
@@ -483,9 +908,10 @@ fn t_alm_numeric_test_out_of_outer_iterations() {
         Some(jac_mapping_f2_tr),
         Some(set_c),
         n2,
-    );
+    )
+    .unwrap();
 
-    let set_c_b = Ball2::new(None, 1.0);
+    let set_c_b = Ball2::new(None, 1.0).unwrap();
     let alm_problem = AlmProblem::new(
         bounds,
         Some(set_c_b),
@@ -498,13 +924,18 @@ fn t_alm_numeric_test_out_of_outer_iterations() {
         Some(mapping_f2),
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-4)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
+        .unwrap()
         .with_initial_inner_tolerance(1e-4)
-        .with_max_outer_iterations(3);
+        .unwrap()
+        .with_max_outer_iterations(3)
+        .unwrap();
 
     let mut u = vec![0.0; nx];
     let solver_result = alm_optimizer.solve(&mut u);
@@ -523,12 +954,12 @@ fn t_alm_numeric_test_out_of_inner_iterations() {
     let n1 = 2;
     let n2 = 4;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
-    let set_c = Ball2::new(None, 1.0);
-    let bounds = Ball2::new(None, 10.0);
-    let set_y = Ball2::new(None, 10000.0);
+    let set_c = Ball2::new(None, 1.0).unwrap();
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let set_y = Ball2::new(None, 10000.0).unwrap();
 
     let factory = AlmFactory::new(
         mocks::f0,
@@ -539,9 +970,10 @@ fn t_alm_numeric_test_out_of_inner_iterations() {
         Some(jac_mapping_f2_tr),
         Some(set_c),
         n2,
-    );
+    )
+    .unwrap();
 
-    let set_c_b = Ball2::new(None, 1.0);
+    let set_c_b = Ball2::new(None, 1.0).unwrap();
     let alm_problem = AlmProblem::new(
         bounds,
         Some(set_c_b),
@@ -554,13 +986,18 @@ fn t_alm_numeric_test_out_of_inner_iterations() {
         Some(mapping_f2),
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-4)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
+        .unwrap()
         .with_initial_inner_tolerance(1e-4)
-        .with_max_inner_iterations(10);
+        .unwrap()
+        .with_max_inner_iterations(10)
+        .unwrap();
 
     let mut u = vec![0.0; nx];
     let solver_result = alm_optimizer.solve(&mut u);
@@ -579,12 +1016,12 @@ fn t_alm_numeric_test_out_of_time() {
     let n1 = 2;
     let n2 = 4;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
-    let set_c = Ball2::new(None, 1.0);
-    let bounds = Ball2::new(None, 10.0);
-    let set_y = Ball2::new(None, 10000.0);
+    let set_c = Ball2::new(None, 1.0).unwrap();
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let set_y = Ball2::new(None, 10000.0).unwrap();
 
     let factory = AlmFactory::new(
         mocks::f0,
@@ -595,9 +1032,10 @@ fn t_alm_numeric_test_out_of_time() {
         Some(jac_mapping_f2_tr),
         Some(set_c),
         n2,
-    );
+    )
+    .unwrap();
 
-    let set_c_b = Ball2::new(None, 1.0);
+    let set_c_b = Ball2::new(None, 1.0).unwrap();
     let alm_problem = AlmProblem::new(
         bounds,
         Some(set_c_b),
@@ -610,12 +1048,16 @@ fn t_alm_numeric_test_out_of_time() {
         Some(mapping_f2),
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-4)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
+        .unwrap()
         .with_initial_inner_tolerance(1e-4)
+        .unwrap()
         .with_max_duration(std::time::Duration::from_micros(50));
 
     let mut u = vec![0.0; nx];
@@ -635,10 +1077,10 @@ fn t_alm_numeric_test_no_mappings() {
     let n1 = 0;
     let n2 = 0;
     let lbfgs_mem = 3;
-    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem);
+    let panoc_cache = PANOCCache::new(nx, tolerance, lbfgs_mem).unwrap();
     let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
 
-    let bounds = Ball2::new(None, 10.0);
+    let bounds = Ball2::new(None, 10.0).unwrap();
 
     let factory = AlmFactory::new(
         mocks::f0,
@@ -649,7 +1091,8 @@ fn t_alm_numeric_test_no_mappings() {
         NO_JACOBIAN_MAPPING,
         NO_SET,
         n2,
-    );
+    )
+    .unwrap();
 
     let alm_problem = AlmProblem::new(
         bounds,
@@ -663,12 +1106,16 @@ fn t_alm_numeric_test_no_mappings() {
         NO_MAPPING,
         n1,
         n2,
-    );
+    )
+    .unwrap();
 
     let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
         .with_delta_tolerance(1e-16)
+        .unwrap()
         .with_epsilon_tolerance(1e-5)
-        .with_initial_inner_tolerance(1e-5);
+        .unwrap()
+        .with_initial_inner_tolerance(1e-5)
+        .unwrap();
 
     let mut u = vec![0.0; nx];
     let solver_result = alm_optimizer.solve(&mut u);
@@ -678,3 +1125,1240 @@ fn t_alm_numeric_test_no_mappings() {
     assert_eq!(1, res.num_outer_iterations());
     assert!(res.last_problem_norm_fpr() <= 1e-5);
 }
+
+fn solve_numeric_test_1(
+    config: Option<&AlmConfig>,
+    progress: Option<Progress>,
+) -> (Vec<f64>, AlmOptimizerStatus) {
+    let nx = 3;
+    let n1 = 2;
+    let n2 = 0;
+    let panoc_cache = PANOCCache::new(nx, 1e-8, 3).unwrap();
+    let mut alm_cache = AlmCache::new(panoc_cache, n1, n2);
+
+    let factory = AlmFactory::new(
+        mocks::f0,
+        mocks::d_f0,
+        Some(mocks::mapping_f1_affine),
+        Some(mocks::mapping_f1_affine_jacobian_product),
+        NO_MAPPING,
+        NO_JACOBIAN_MAPPING,
+        Some(Ball2::new(None, 1.0).unwrap()),
+        n2,
+    )
+    .unwrap();
+
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        Some(Ball2::new(None, 10000.0).unwrap()),
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult { factory.psi(u, xi, cost) },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            factory.d_psi(u, xi, grad)
+        },
+        Some(mocks::mapping_f1_affine),
+        NO_MAPPING,
+        n1,
+        n2,
+    )
+    .unwrap();
+
+    let alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem);
+    let mut alm_optimizer = match config {
+        Some(config) => alm_optimizer.with_config(config).unwrap(),
+        None => alm_optimizer
+            .with_delta_tolerance(1e-4)
+            .unwrap()
+            .with_max_outer_iterations(30)
+            .unwrap()
+            .with_epsilon_tolerance(1e-5)
+            .unwrap()
+            .with_initial_inner_tolerance(1e-2)
+            .unwrap()
+            .with_inner_tolerance_update_factor(0.5)
+            .unwrap()
+            .with_initial_penalty(1.0)
+            .unwrap()
+            .with_penalty_update_factor(1.2)
+            .unwrap()
+            .with_sufficient_decrease_coefficient(0.1)
+            .unwrap()
+            .with_initial_lagrange_multipliers(&[5.0; 2])
+            .unwrap(),
+    };
+
+    if let Some(progress) = progress {
+        alm_optimizer = alm_optimizer.with_progress(progress);
+    }
+
+    let mut u = vec![0.0; nx];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    (u, status)
+}
+
+#[test]
+fn t_alm_with_config() {
+    let config = AlmConfig {
+        delta_tolerance: 1e-4,
+        max_outer_iterations: 30,
+        epsilon_tolerance: 1e-5,
+        initial_inner_tolerance: 1e-2,
+        inner_tolerance_update_factor: 0.5,
+        initial_penalty: Some(1.0),
+        penalty_update_factor: 1.2,
+        sufficient_decrease_coefficient: 0.1,
+        initial_lagrange_multipliers: Some(vec![5.0; 2]),
+        ..AlmConfig::default()
+    };
+    let (u_config, status_config) = solve_numeric_test_1(Some(&config), None);
+    let (u_builder, status_builder) = solve_numeric_test_1(None, None);
+    assert_eq!(ExitStatus::Converged, status_config.exit_status());
+    assert_eq!(u_builder, u_config);
+    assert_eq!(
+        status_builder.num_outer_iterations(),
+        status_config.num_outer_iterations()
+    );
+    assert_eq!(
+        status_builder.lagrange_multipliers(),
+        status_config.lagrange_multipliers()
+    );
+}
+
+#[test]
+fn t_alm_config_display() {
+    let config = AlmConfig {
+        initial_penalty: Some(2.0),
+        ..AlmConfig::default()
+    };
+    let text = format!("{}", config);
+    assert!(text.starts_with("max outer iterations     : 50\n"));
+    assert!(text.contains("epsilon tolerance        : 1e-6\n"));
+    assert!(text.contains("initial multipliers      : (cache)\n"));
+    assert!(text.contains("initial penalty          : 2\n"));
+    assert!(text.ends_with("real-time iterations     : false"));
+    assert_eq!(16, text.lines().count());
+    assert_ne!(config, AlmConfig::default());
+}
+
+#[test]
+fn t_alm_trace() {
+    let config = AlmConfig {
+        delta_tolerance: 1e-4,
+        max_outer_iterations: 30,
+        initial_penalty: Some(1.0),
+        penalty_update_factor: 1.2,
+        record_history: true,
+        ..AlmConfig::default()
+    };
+    let (_u, status) = solve_numeric_test_1(Some(&config), None);
+    let trace = status.trace().unwrap();
+    assert_eq!(status.num_outer_iterations(), trace.len());
+    assert_eq!(status.last_problem_norm_fpr(), *trace.fpr().last().unwrap());
+    let penalty = trace.penalty().unwrap();
+    assert_eq!(1.0, penalty[0]);
+    assert!(penalty.windows(2).all(|c| c[0] <= c[1]));
+    assert!(trace.alm_infeasibility().unwrap().last().unwrap() <= &1e-4);
+    assert!(trace.pm_infeasibility().unwrap().iter().all(|&x| x == 0.0));
+    assert!(trace.gamma().is_none());
+
+    // the history is not recorded by default
+    let (_u, status) = solve_numeric_test_1(None, None);
+    assert!(status.trace().is_none());
+}
+
+#[test]
+fn t_alm_penalty_method() {
+    let config = AlmConfig {
+        delta_tolerance: 1e-4,
+        max_outer_iterations: 50,
+        epsilon_tolerance: 1e-5,
+        initial_penalty: Some(1.0),
+        penalty_update_factor: 5.0,
+        initial_lagrange_multipliers: Some(vec![5.0; 2]),
+        penalty_method: true,
+        ..AlmConfig::default()
+    };
+    let (u_penalty, status_penalty) = solve_numeric_test_1(Some(&config), None);
+    let (u_alm, status_alm) = solve_numeric_test_1(None, None);
+    assert_eq!(ExitStatus::Converged, status_penalty.exit_status());
+    assert!(status_penalty.penalty() > status_alm.penalty());
+    unit_test_utils::assert_nearly_equal_array(&u_alm, &u_penalty, 1e-3, 1e-3, "u");
+    // the multiplier estimates, c * (F1(u) - Proj_C(F1(u))), approximate
+    // the Lagrange multipliers
+    unit_test_utils::assert_nearly_equal_array(
+        status_alm.lagrange_multipliers().as_ref().unwrap(),
+        status_penalty.lagrange_multipliers().as_ref().unwrap(),
+        1e-2,
+        1e-2,
+        "y",
+    );
+}
+
+#[test]
+fn t_alm_filter() {
+    let mut filter = super::alm_filter::AlmFilter::default();
+    assert!(filter.is_acceptable(1.0, 10.0));
+    filter.add(1.0, 10.0);
+    filter.add(0.5, 20.0);
+    assert!(filter.is_acceptable(0.1, 30.0));
+    assert!(filter.is_acceptable(2.0, 5.0));
+    assert!(filter.is_acceptable(0.7, 15.0));
+    assert!(!filter.is_acceptable(0.8, 25.0));
+    assert!(!filter.is_acceptable(f64::NAN, 0.0));
+    // (0.5, 5.0) dominates both pairs
+    filter.add(0.5, 5.0);
+    assert!(filter.is_acceptable(0.7, 4.0));
+    assert!(!filter.is_acceptable(0.7, 15.0));
+
+    let config = AlmConfig {
+        delta_tolerance: 1e-4,
+        max_outer_iterations: 30,
+        epsilon_tolerance: 1e-5,
+        initial_penalty: Some(1.0),
+        penalty_update_factor: 1.2,
+        initial_lagrange_multipliers: Some(vec![5.0; 2]),
+        filter: true,
+        ..AlmConfig::default()
+    };
+    let (u_filter, status_filter) = solve_numeric_test_1(Some(&config), None);
+    let (u_alm, _status_alm) = solve_numeric_test_1(None, None);
+    assert_eq!(ExitStatus::Converged, status_filter.exit_status());
+    unit_test_utils::assert_nearly_equal_array(&u_alm, &u_filter, 1e-3, 1e-3, "u");
+    // the filter evaluates the cost at every outer iteration
+    assert!(status_filter.oracle_calls().cost() > status_filter.num_inner_iterations());
+}
+
+#[test]
+fn t_alm_progress() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let (_u, status) = solve_numeric_test_1(None, Some(Progress::from_sender(sender, 2)));
+    let events: Vec<ProgressEvent> = receiver.iter().collect();
+    let outer_iterations = status.num_outer_iterations();
+    assert_eq!(outer_iterations.div_ceil(2) + 1, events.len());
+    for (i, event) in events[..events.len() - 1].iter().enumerate() {
+        assert!(matches!(
+            event,
+            ProgressEvent::OuterIteration { iteration, penalty, .. }
+                if *iteration == 2 * i && *penalty >= 1.0
+        ));
+    }
+    assert_eq!(
+        ProgressEvent::Finished {
+            exit_status: ExitStatus::Converged,
+            iterations: outer_iterations,
+            solve_time: status.solve_time(),
+        },
+        events[events.len() - 1]
+    );
+}
+
+/// Writer into a shared buffer (which the tests can read while a
+/// `Progress::verbose` owns the writer)
+#[derive(Clone, Default)]
+struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedBuffer {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+}
+
+#[test]
+fn t_alm_verbose() {
+    let buffer = SharedBuffer::default();
+    let progress = Progress::verbose(Verbosity::Iterations, 2, buffer.clone());
+    let (_u, status) = solve_numeric_test_1(None, Some(progress));
+    let lines = buffer.lines();
+    let outer_iterations = status.num_outer_iterations();
+    // header, a row every two outer iterations and the final line
+    assert_eq!(outer_iterations.div_ceil(2) + 2, lines.len());
+    assert!(lines[0].contains("outer") && lines[0].contains("infeas (PM)"));
+    assert_eq!(7, lines[1].split_whitespace().count());
+    assert!(lines[1].trim_start().starts_with("0 "));
+    assert!(lines[lines.len() - 1].starts_with("finished after"));
+
+    let buffer = SharedBuffer::default();
+    let progress = Progress::verbose(Verbosity::Summary, 2, buffer.clone());
+    solve_numeric_test_1(None, Some(progress));
+    assert_eq!(1, buffer.lines().len());
+
+    let buffer = SharedBuffer::default();
+    let progress = Progress::verbose(Verbosity::Silent, 2, buffer.clone());
+    solve_numeric_test_1(None, Some(progress));
+    assert!(buffer.lines().is_empty());
+}
+
+#[test]
+fn t_alm_with_config_invalid() {
+    let config = AlmConfig {
+        penalty_update_factor: 0.5,
+        ..AlmConfig::default()
+    };
+    let panoc_cache = PANOCCache::new(3, 1e-8, 3).unwrap();
+    let mut alm_cache = AlmCache::new(panoc_cache, 0, 0);
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 1.0).unwrap(),
+        NO_SET,
+        NO_SET,
+        |_u: &[f64], _p: &[f64], _cost: &mut f64| -> FunctionCallResult { Ok(()) },
+        |_u: &[f64], _p: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) },
+        NO_MAPPING,
+        NO_MAPPING,
+        0,
+        0,
+    )
+    .unwrap();
+    let alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem).with_config(&config);
+    assert!(matches!(
+        alm_optimizer,
+        Err(SolverError::InvalidParameter {
+            name: "penalty_update_factor",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn t_alm_owned_cache() {
+    let make_problem = || {
+        AlmProblem::new(
+            Ball2::new(None, 10.0).unwrap(),
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(Ball2::new(None, 10000.0).unwrap()),
+            |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                mocks::my_cost(u, cost)?;
+                *cost += xi[1] * (u[0] + u[1]);
+                Ok(())
+            },
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                mocks::my_gradient(u, grad)?;
+                grad.iter_mut().for_each(|g| *g += xi[1]);
+                Ok(())
+            },
+            Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+                f1[0] = u[0] + u[1];
+                Ok(())
+            }),
+            NO_MAPPING,
+            1,
+            0,
+        )
+        .unwrap()
+    };
+
+    let panoc_cache = PANOCCache::new(2, 1e-8, 3).unwrap();
+    let mut alm_cache = AlmCache::new(panoc_cache, 1, 0);
+    let mut u_borrowed = vec![0.0; 2];
+    let status_borrowed = AlmOptimizer::new(&mut alm_cache, make_problem())
+        .solve(&mut u_borrowed)
+        .unwrap();
+
+    let mut alm_optimizer = AlmOptimizer::from_dimensions(make_problem(), 2, 1e-8, 3).unwrap();
+    let mut u = vec![0.0; 2];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert_eq!(u_borrowed, u);
+    assert_eq!(
+        status_borrowed.num_outer_iterations(),
+        status.num_outer_iterations()
+    );
+
+    // the owned optimizer does not borrow anything, so it can be reused
+    // (warm-started from the Lagrange multipliers stored in its cache)
+    let mut u = vec![0.0; 2];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    unit_test_utils::assert_nearly_equal_array(&u_borrowed, &u, 1e-4, 1e-4, "u");
+
+    let alm_cache = AlmCache::new(PANOCCache::new(2, 1e-8, 3).unwrap(), 1, 0);
+    let mut u = vec![0.0; 2];
+    let status = AlmOptimizer::new_owned(alm_cache, make_problem())
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(u_borrowed, u);
+    assert_eq!(
+        status_borrowed.num_inner_iterations(),
+        status.num_inner_iterations()
+    );
+
+    assert!(AlmOptimizer::from_dimensions(make_problem(), 2, -1.0, 3).is_err());
+}
+
+#[test]
+fn t_alm_real_time_iterations() {
+    let make_problem = || {
+        AlmProblem::new(
+            Ball2::new(None, 10.0).unwrap(),
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(Ball2::new(None, 10000.0).unwrap()),
+            |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                mocks::my_cost(u, cost)?;
+                *cost += xi[1] * (u[0] + u[1]);
+                Ok(())
+            },
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                mocks::my_gradient(u, grad)?;
+                grad.iter_mut().for_each(|g| *g += xi[1]);
+                Ok(())
+            },
+            Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+                f1[0] = u[0] + u[1];
+                Ok(())
+            }),
+            NO_MAPPING,
+            1,
+            0,
+        )
+        .unwrap()
+    };
+    let mut alm_optimizer = AlmOptimizer::from_dimensions(make_problem(), 2, 1e-6, 3)
+        .unwrap()
+        .with_real_time_iterations(1, 5)
+        .unwrap();
+    let mut u = vec![0.0; 2];
+    let mut statuses = vec![];
+    for _ in 0..200 {
+        let status = alm_optimizer.solve(&mut u).unwrap();
+        assert_eq!(1, status.num_outer_iterations());
+        assert!(status.num_inner_iterations() <= 5);
+        statuses.push(status.exit_status());
+        if status.exit_status() == ExitStatus::Converged {
+            break;
+        }
+    }
+    // the budget is too small for a single call, but the iterations of
+    // consecutive calls add up
+    assert_eq!(ExitStatus::NotConvergedIterations, statuses[0]);
+    assert_eq!(Some(&ExitStatus::Converged), statuses.last());
+
+    let mut u_full = vec![0.0; 2];
+    let status = AlmOptimizer::from_dimensions(make_problem(), 2, 1e-8, 3)
+        .unwrap()
+        .solve(&mut u_full)
+        .unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    unit_test_utils::assert_nearly_equal_array(&u_full, &u, 1e-3, 1e-3, "u");
+
+    assert!(AlmOptimizer::from_dimensions(make_problem(), 2, 1e-8, 3)
+        .unwrap()
+        .with_real_time_iterations(0, 5)
+        .is_err());
+}
+
+#[test]
+fn t_alm_const() {
+    let make_problem = || {
+        AlmProblem::new(
+            Ball2::new(None, 10.0).unwrap(),
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(Ball2::new(None, 10000.0).unwrap()),
+            |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                mocks::my_cost(u, cost)?;
+                *cost += xi[1] * (u[0] + u[1]);
+                Ok(())
+            },
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                mocks::my_gradient(u, grad)?;
+                grad.iter_mut().for_each(|g| *g += xi[1]);
+                Ok(())
+            },
+            Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+                f1[0] = u[0] + u[1];
+                Ok(())
+            }),
+            NO_MAPPING,
+            1,
+            0,
+        )
+        .unwrap()
+    };
+    let mut u = vec![0.0; 2];
+    let status = AlmOptimizer::from_dimensions(make_problem(), 2, 1e-6, 3)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+
+    let mut cache = AlmCacheConst::<2, 3, 1, 0>::new(1e-6).unwrap();
+    let mut u_const = [0.0; 2];
+    let status_const = AlmOptimizerConst::new(&mut cache, make_problem())
+        .unwrap()
+        .solve(&mut u_const)
+        .unwrap();
+    assert_eq!(ExitStatus::Converged, status_const.exit_status());
+    assert_eq!(
+        status.num_outer_iterations(),
+        status_const.num_outer_iterations()
+    );
+    assert_eq!(
+        status.num_inner_iterations(),
+        status_const.num_inner_iterations()
+    );
+    unit_test_utils::assert_nearly_equal_array(&u, &u_const, 1e-10, 1e-12, "u");
+    let y = status.lagrange_multipliers().as_ref().unwrap();
+    assert!(matrix_operations::norm_inf_diff(y, cache.lagrange_multipliers()) < 1e-10);
+
+    // the dimensions of the cache must match those of the problem
+    let mut cache = AlmCacheConst::<2, 3, 2, 0>::new(1e-6).unwrap();
+    assert!(AlmOptimizerConst::new(&mut cache, make_problem()).is_err());
+    let mut cache = AlmCacheConst::<2, 3, 1, 0>::new(1e-6).unwrap();
+    let mut optimizer = AlmOptimizerConst::new(&mut cache, make_problem()).unwrap();
+    assert!(optimizer.solve(&mut [0.0; 3]).is_err());
+}
+
+#[test]
+fn t_alm_clock() {
+    let problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        Some(Ball2::new(None, 10000.0).unwrap()),
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+            mocks::my_cost(u, cost)?;
+            *cost += xi[1] * (u[0] + u[1]);
+            Ok(())
+        },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            mocks::my_gradient(u, grad)?;
+            grad.iter_mut().for_each(|g| *g += xi[1]);
+            Ok(())
+        },
+        Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+            f1[0] = u[0] + u[1];
+            Ok(())
+        }),
+        NO_MAPPING,
+        1,
+        0,
+    )
+    .unwrap();
+    // every reading of the clock (also by the inner solver) takes 1ms
+    let clock = ManualClock::new().with_tick(std::time::Duration::from_millis(1));
+    let mut u = vec![0.0; 2];
+    let status = AlmOptimizer::from_dimensions(problem, 2, 1e-6, 3)
+        .unwrap()
+        .with_max_duration(std::time::Duration::from_millis(10))
+        .with_clock(&clock)
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(ExitStatus::NotConvergedOutOfTime, status.exit_status());
+    assert!(status.solve_time() > std::time::Duration::from_millis(10));
+    assert!(status.solve_time() < std::time::Duration::from_millis(20));
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_events {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    /// Level, message, fields and innermost entered span of an event
+    pub(super) type CapturedEvent = (Level, String, Vec<String>, Option<&'static str>);
+
+    #[derive(Default)]
+    pub(super) struct Captured {
+        /// Names of the spans (the ID of a span is its index plus one)
+        pub(super) spans: Vec<&'static str>,
+        /// Stack of the IDs of the entered spans
+        entered: Vec<u64>,
+        pub(super) events: Vec<CapturedEvent>,
+    }
+
+    struct TestSubscriber(Arc<Mutex<Captured>>);
+
+    struct Fields<'a>(&'a mut String, &'a mut Vec<String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                *self.0 = format!("{:?}", value);
+            } else {
+                self.1.push(field.name().to_string());
+            }
+        }
+    }
+
+    impl Subscriber for TestSubscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut captured = self.0.lock().unwrap();
+            captured.spans.push(span.metadata().name());
+            Id::from_u64(captured.spans.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let (mut message, mut fields) = (String::new(), Vec::new());
+            event.record(&mut Fields(&mut message, &mut fields));
+            let mut captured = self.0.lock().unwrap();
+            let span = captured
+                .entered
+                .last()
+                .map(|id| captured.spans[*id as usize - 1]);
+            let level = *event.metadata().level();
+            captured.events.push((level, message, fields, span));
+        }
+
+        fn enter(&self, span: &Id) {
+            self.0.lock().unwrap().entered.push(span.into_u64());
+        }
+
+        fn exit(&self, span: &Id) {
+            let mut captured = self.0.lock().unwrap();
+            assert_eq!(Some(span.into_u64()), captured.entered.pop());
+        }
+    }
+
+    /// Returns the spans and events of the current thread while `f` runs
+    pub(super) fn capture<F: FnOnce()>(f: F) -> Captured {
+        let captured = Arc::new(Mutex::new(Captured::default()));
+        tracing::subscriber::with_default(TestSubscriber(captured.clone()), f);
+        let mut captured = captured.lock().unwrap();
+        std::mem::take(&mut *captured)
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn t_alm_tracing_events() {
+    use tracing::Level;
+
+    let mut status = None;
+    let captured = tracing_events::capture(|| status = Some(solve_numeric_test_1(None, None).1));
+    let status = status.unwrap();
+    let events = &captured.events;
+    let count = |message: &str| events.iter().filter(|e| e.1.starts_with(message)).count();
+    let spans = |name: &str| captured.spans.iter().filter(|s| **s == name).count();
+
+    assert_eq!(status.num_outer_iterations(), spans("alm_outer_iteration"));
+    assert_eq!(status.num_outer_iterations(), count("ALM outer iteration"));
+    assert_eq!(status.num_outer_iterations(), count("PANOC finished"));
+    assert_eq!(1, count("ALM finished: converged"));
+    assert!(count("PANOC iteration") >= status.num_inner_iterations());
+    assert_eq!(count("PANOC iteration"), spans("panoc_iteration"));
+    assert!(count("ALM penalty parameter updated") < status.num_outer_iterations());
+
+    let (level, _, fields, span) = events
+        .iter()
+        .find(|e| e.1.starts_with("ALM outer iteration"))
+        .unwrap();
+    assert_eq!(Level::DEBUG, *level);
+    assert_eq!(Some("alm_outer_iteration"), *span);
+    assert!(fields.iter().any(|k| k == "penalty"));
+    assert!(fields.iter().any(|k| k == "alm_infeasibility"));
+    let (level, _, fields, span) = events.iter().find(|e| e.1 == "PANOC iteration").unwrap();
+    assert_eq!(Level::TRACE, *level);
+    assert_eq!(Some("panoc_iteration"), *span);
+    assert!(fields.iter().any(|k| k == "fpr"));
+    // the line search of PANOC runs in the span of its iteration
+    assert!(events
+        .iter()
+        .filter(|e| e.1.starts_with("PANOC line search"))
+        .all(|e| e.3 == Some("panoc_iteration")));
+}
+
+fn solve_polishing_test(polishing: Option<(f64, usize)>) -> (Vec<f64>, AlmOptimizerStatus) {
+    // Rosenbrock function on [-2, 0.5] x [-2, 2] with F2(u) = u_0 + u_1 - 0.75
+    // The solution is u* = (0.5, 0.25), where the bound of u_0 is active
+    let panoc_cache = PANOCCache::new(2, 1e-4, 5).unwrap();
+    let mut alm_cache = AlmCache::new(panoc_cache, 0, 1);
+    let alm_problem = AlmProblem::new(
+        Rectangle::new(Some(&[-2.0, -2.0]), Some(&[0.5, 2.0])).unwrap(),
+        NO_SET,
+        NO_SET,
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+            *cost =
+                mocks::rosenbrock_cost(1.0, 10.0, u) + 0.5 * xi[0] * (u[0] + u[1] - 0.75).powi(2);
+            Ok(())
+        },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            mocks::rosenbrock_grad(1.0, 10.0, u, grad);
+            let f2 = u[0] + u[1] - 0.75;
+            grad.iter_mut().for_each(|g| *g += xi[0] * f2);
+            Ok(())
+        },
+        NO_MAPPING,
+        Some(|u: &[f64], f2: &mut [f64]| -> FunctionCallResult {
+            f2[0] = u[0] + u[1] - 0.75;
+            Ok(())
+        }),
+        0,
+        1,
+    )
+    .unwrap();
+    let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_epsilon_tolerance(1e-4)
+        .unwrap()
+        .with_delta_tolerance(1e-4)
+        .unwrap();
+    if let Some((tolerance, max_iterations)) = polishing {
+        alm_optimizer = alm_optimizer
+            .with_polishing(tolerance, max_iterations)
+            .unwrap();
+    }
+    let mut u = vec![0.0, 0.0];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    (u, status)
+}
+
+#[test]
+fn t_alm_polishing() {
+    let (u, status) = solve_polishing_test(None);
+    let (u_polished, status_polished) = solve_polishing_test(Some((1e-12, 100)));
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    assert_eq!(ExitStatus::Converged, status_polished.exit_status());
+    assert_eq!(0, status.num_polishing_iterations());
+    assert!(status_polished.num_polishing_iterations() > 0);
+    assert_eq!(
+        status.num_inner_iterations(),
+        status_polished.num_inner_iterations()
+    );
+    // the polished solution is on the bound and is more accurate
+    assert_eq!(0.5, u_polished[0]);
+    assert!(status_polished.last_problem_norm_fpr() <= 1e-12);
+    assert!(status_polished.last_problem_norm_fpr() < status.last_problem_norm_fpr());
+    let error = |u: &[f64]| matrix_operations::norm_inf_diff(u, &[0.5, 0.25]);
+    assert!(error(&u_polished) < 1e-10);
+    assert!(error(&u_polished) < error(&u));
+
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 1.0).unwrap(),
+        NO_SET,
+        NO_SET,
+        mocks::psi_cost_dummy,
+        mocks::psi_gradient_dummy,
+        NO_MAPPING,
+        NO_MAPPING,
+        0,
+        0,
+    )
+    .unwrap();
+    let mut alm_cache = AlmCache::new(PANOCCache::new(2, 1e-4, 5).unwrap(), 0, 0);
+    let alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem);
+    assert!(alm_optimizer.with_polishing(0.0, 10).is_err());
+}
+
+#[test]
+fn t_alm_statistics() {
+    let mut alm_cache = AlmCache::new(PANOCCache::new(2, 1e-6, 5).unwrap(), 0, 1);
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        NO_SET,
+        NO_SET,
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+            mocks::my_cost(u, cost)?;
+            *cost += 0.5 * xi[0] * (u[0] + u[1] - 1.0).powi(2);
+            Ok(())
+        },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            mocks::my_gradient(u, grad)?;
+            grad.iter_mut()
+                .for_each(|g| *g += xi[0] * (u[0] + u[1] - 1.0));
+            Ok(())
+        },
+        NO_MAPPING,
+        Some(|u: &[f64], f2: &mut [f64]| -> FunctionCallResult {
+            f2[0] = u[0] + u[1] - 1.0;
+            Ok(())
+        }),
+        0,
+        1,
+    )
+    .unwrap();
+    let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_delta_tolerance(1e-4)
+        .unwrap();
+    let status = alm_optimizer.solve(&mut [0.0, 0.0]).unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    alm_optimizer = alm_optimizer.with_max_outer_iterations(1).unwrap();
+    let status_failed = alm_optimizer.solve(&mut [0.0, 0.0]).unwrap();
+    assert_ne!(ExitStatus::Converged, status_failed.exit_status());
+    assert!(alm_optimizer.solve(&mut [0.0]).is_err());
+
+    let statistics = *alm_cache.statistics();
+    assert_eq!(3, statistics.num_solves());
+    assert_eq!(2, statistics.num_failures());
+    assert_eq!(1, statistics.num_errors());
+    assert_eq!(
+        status.f2_norm().max(status_failed.f2_norm()),
+        statistics.max_infeasibility()
+    );
+    // the inner problems are recorded in the statistics of the PANOC cache
+    assert!(alm_cache.panoc_cache.statistics().num_solves() > 3);
+    alm_cache.reset_statistics();
+    assert_eq!(0, alm_cache.statistics().num_solves());
+}
+
+#[test]
+fn t_alm_kkt_residual() {
+    let (nx, n1, n2) = (3, 2, 0);
+    let mut alm_cache = AlmCache::new(PANOCCache::new(nx, 1e-8, 3).unwrap(), n1, n2);
+    let bounds = Ball2::new(None, 10.0).unwrap();
+    let factory = AlmFactory::new(
+        mocks::f0,
+        mocks::d_f0,
+        Some(mocks::mapping_f1_affine),
+        Some(mocks::mapping_f1_affine_jacobian_product),
+        NO_MAPPING,
+        NO_JACOBIAN_MAPPING,
+        Some(Ball2::new(None, 1.0).unwrap()),
+        n2,
+    )
+    .unwrap();
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        Some(Ball2::new(None, 10000.0).unwrap()),
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult { factory.psi(u, xi, cost) },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            factory.d_psi(u, xi, grad)
+        },
+        Some(mocks::mapping_f1_affine),
+        NO_MAPPING,
+        n1,
+        n2,
+    )
+    .unwrap();
+    let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_delta_tolerance(1e-6)
+        .unwrap()
+        .with_epsilon_tolerance(1e-7)
+        .unwrap()
+        .with_max_outer_iterations(50)
+        .unwrap();
+
+    let mut u = vec![0.0; nx];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert!(status.kkt_residual().is_none());
+
+    alm_optimizer = alm_optimizer.with_kkt_residual(true);
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    let kkt = status.kkt_residual().unwrap();
+    // the inner problems are solved up to a tolerance on the fixed-point
+    // residual, which is scaled by the (small) step size of PANOC
+    assert!(kkt.stationarity() < 0.05);
+    assert!(kkt.complementarity() < 1e-5);
+    assert_eq!(0.0, kkt.f1_infeasibility());
+    assert_eq!(0.0, kkt.f2_infeasibility());
+    assert!(format!("{}", status).contains("KKT stationarity"));
+
+    // the residuals at the solution and the Lagrange multipliers of the status
+    let y = status.lagrange_multipliers().as_ref().unwrap();
+    let kkt_expected = factory.kkt_residual(&u, y, &bounds).unwrap();
+    unit_test_utils::assert_nearly_equal(
+        kkt_expected.stationarity(),
+        kkt.stationarity(),
+        1e-6,
+        1e-9,
+        "stationarity",
+    );
+    unit_test_utils::assert_nearly_equal(
+        kkt_expected.f1_infeasibility(),
+        kkt.f1_infeasibility(),
+        1e-6,
+        1e-9,
+        "f1 infeasibility",
+    );
+}
+
+#[test]
+fn t_kkt_residual_projections() {
+    let bounds = Ball2::new(None, 1.0).unwrap();
+    let set_c = Ball2::new(None, 0.5).unwrap();
+    let (u, y) = ([0.5, 0.5], [1.0, 0.0]);
+    let mut oracle_calls = OracleCalls::default();
+    let kkt = KktResidual::compute(
+        &u,
+        &mut [0.1, 0.2],
+        &bounds,
+        &mut [1.0, 0.0],
+        &y,
+        Some(&set_c),
+        &[],
+        &mut oracle_calls,
+    );
+    // one projection on U, and on C those of F1(u) and of F1(u) + y
+    assert_eq!(3, oracle_calls.projections());
+    assert!((kkt.f1_infeasibility() - 0.5).abs() < 1e-12);
+
+    KktResidual::compute(
+        &u,
+        &mut [0.1, 0.2],
+        &bounds,
+        &mut [],
+        &[],
+        None::<&NoConstraints>,
+        &[],
+        &mut oracle_calls,
+    );
+    assert_eq!(4, oracle_calls.projections());
+}
+
+#[test]
+fn t_alm_active_set() {
+    // minimize 0.5*||u - (2, 2)||^2 on the box [-1, 1.5]^2 subject to
+    // F1(u) = (u0 + u1, u0 - u1) in [-10, 2] x [-10, 10], whose solution
+    // is u = (1, 1), where the first row of F1 is active at its upper bound
+    let mut alm_cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 2, 0);
+    let (xmin, xmax) = ([-1.0; 2], [1.5; 2]);
+    let (cmin, cmax) = ([-10.0; 2], [2.0, 10.0]);
+    let factory = AlmFactory::new(
+        |u: &[f64], cost: &mut f64| -> FunctionCallResult {
+            *cost = 0.5 * ((u[0] - 2.0).powi(2) + (u[1] - 2.0).powi(2));
+            Ok(())
+        },
+        |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad[0] = u[0] - 2.0;
+            grad[1] = u[1] - 2.0;
+            Ok(())
+        },
+        Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+            f1[0] = u[0] + u[1];
+            f1[1] = u[0] - u[1];
+            Ok(())
+        }),
+        Some(
+            |_u: &[f64], d: &[f64], res: &mut [f64]| -> FunctionCallResult {
+                res[0] = d[0] + d[1];
+                res[1] = d[0] - d[1];
+                Ok(())
+            },
+        ),
+        NO_MAPPING,
+        NO_JACOBIAN_MAPPING,
+        Some(Rectangle::new(Some(&cmin), Some(&cmax)).unwrap()),
+        0,
+    )
+    .unwrap();
+    let alm_problem = AlmProblem::new(
+        Rectangle::new(Some(&xmin), Some(&xmax)).unwrap(),
+        Some(Rectangle::new(Some(&cmin), Some(&cmax)).unwrap()),
+        Some(Ball2::new(None, 1e6).unwrap()),
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult { factory.psi(u, xi, cost) },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            factory.d_psi(u, xi, grad)
+        },
+        Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+            f1[0] = u[0] + u[1];
+            f1[1] = u[0] - u[1];
+            Ok(())
+        }),
+        NO_MAPPING,
+        2,
+        0,
+    )
+    .unwrap();
+    let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_delta_tolerance(1e-6)
+        .unwrap()
+        .with_active_set_tolerance(1e-4)
+        .unwrap();
+    let mut u = [0.0; 2];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    assert!(status.active_set().unwrap().is_empty());
+    let active_rows = status.active_rows().unwrap();
+    assert!(active_rows.lower().is_empty());
+    assert_eq!(&[0], active_rows.upper());
+    assert!(alm_optimizer.with_active_set_tolerance(-1.0).is_err());
+}
+
+#[test]
+fn t_alm_block_infeasibility() {
+    // minimize 0.5*||u - (2, 2)||^2 subject to F1(u) = u in C, where C is
+    // the product of [-10, 1] (named) and [-10, 10], whose solution is
+    // u = (1, 2), where only the first block is active
+    let mut alm_cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 2, 0);
+    let (cmin, cmax) = ([-10.0], [1.0, 10.0]);
+    let set_c = || {
+        CartesianProduct::new()
+            .add_named_constraint(
+                1,
+                "first",
+                Rectangle::new(Some(&cmin), Some(&cmax[..1])).unwrap(),
+            )
+            .unwrap()
+            .add_constraint(2, Rectangle::new(Some(&cmin), Some(&cmax[1..])).unwrap())
+            .unwrap()
+    };
+    let identity = |u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+        f1.copy_from_slice(u);
+        Ok(())
+    };
+    let factory = AlmFactory::new(
+        |u: &[f64], cost: &mut f64| -> FunctionCallResult {
+            *cost = 0.5 * ((u[0] - 2.0).powi(2) + (u[1] - 2.0).powi(2));
+            Ok(())
+        },
+        |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad[0] = u[0] - 2.0;
+            grad[1] = u[1] - 2.0;
+            Ok(())
+        },
+        Some(identity),
+        Some(
+            |_u: &[f64], d: &[f64], res: &mut [f64]| -> FunctionCallResult {
+                res.copy_from_slice(d);
+                Ok(())
+            },
+        ),
+        NO_MAPPING,
+        NO_JACOBIAN_MAPPING,
+        Some(set_c()),
+        0,
+    )
+    .unwrap();
+    let alm_problem = AlmProblem::new(
+        NoConstraints::new(),
+        Some(set_c()),
+        Some(Ball2::new(None, 1e6).unwrap()),
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult { factory.psi(u, xi, cost) },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            factory.d_psi(u, xi, grad)
+        },
+        Some(identity),
+        NO_MAPPING,
+        2,
+        0,
+    )
+    .unwrap();
+    let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_delta_tolerance(1e-6)
+        .unwrap();
+    let mut u = [0.0; 2];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert!(status.block_infeasibility().is_none());
+
+    alm_optimizer = alm_optimizer
+        .with_block_infeasibility(true)
+        .with_kkt_residual(true);
+    let mut u = [0.0; 2];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::Converged, status.exit_status());
+    let blocks = status.block_infeasibility().unwrap();
+    assert_eq!(2, blocks.len());
+    assert_eq!(
+        (0, Some("first"), 0..1),
+        (blocks[0].index(), blocks[0].name(), blocks[0].rows())
+    );
+    assert_eq!(
+        (1, None, 1..2),
+        (blocks[1].index(), blocks[1].name(), blocks[1].rows())
+    );
+    assert_eq!(0.0, blocks[1].infeasibility());
+    unit_test_utils::assert_nearly_equal(
+        status.kkt_residual().unwrap().f1_infeasibility(),
+        blocks[0].infeasibility().hypot(blocks[1].infeasibility()),
+        1e-12,
+        1e-14,
+        "block infeasibilities",
+    );
+    assert!(format!("{}", status).contains("block 0 (first), rows 0..1"));
+}
+
+#[test]
+fn t_alm_phase_timing() {
+    let problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        Some(Ball2::new(None, 10000.0).unwrap()),
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+            mocks::my_cost(u, cost)?;
+            *cost += xi[1] * (u[0] + u[1]);
+            Ok(())
+        },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            mocks::my_gradient(u, grad)?;
+            grad.iter_mut().for_each(|g| *g += xi[1]);
+            Ok(())
+        },
+        Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+            f1[0] = u[0] + u[1];
+            Ok(())
+        }),
+        NO_MAPPING,
+        1,
+        0,
+    )
+    .unwrap();
+    let clock = ManualClock::new().with_tick(std::time::Duration::from_millis(1));
+    let mut alm_optimizer = AlmOptimizer::from_dimensions(problem, 2, 1e-6, 3)
+        .unwrap()
+        .with_clock(&clock)
+        .with_phase_timing(true);
+    let status = alm_optimizer.solve(&mut [0.0; 2]).unwrap();
+    let phase_times = status.phase_times().unwrap();
+    // the phases of every inner problem are accumulated
+    assert!(
+        phase_times.cost()
+            >= std::time::Duration::from_millis(status.num_outer_iterations() as u64)
+    );
+    assert!(phase_times.total() <= status.solve_time());
+
+    let status = alm_optimizer
+        .with_phase_timing(false)
+        .solve(&mut [0.0; 2])
+        .unwrap();
+    assert!(status.phase_times().is_none());
+}
+
+#[test]
+fn t_alm_checkpoint() {
+    let make_problem = || {
+        AlmProblem::new(
+            Ball2::new(None, 10.0).unwrap(),
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(Ball2::new(None, 10000.0).unwrap()),
+            |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                mocks::my_cost(u, cost)?;
+                *cost += xi[1] * (u[0] + u[1]);
+                Ok(())
+            },
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                mocks::my_gradient(u, grad)?;
+                grad.iter_mut().for_each(|g| *g += xi[1]);
+                Ok(())
+            },
+            Some(|u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+                f1[0] = u[0] + u[1];
+                Ok(())
+            }),
+            NO_MAPPING,
+            1,
+            0,
+        )
+        .unwrap()
+    };
+    let new_cache = || AlmCache::new(PANOCCache::new(2, 1e-6, 3).unwrap(), 1, 0);
+    let solve = |cache: &mut AlmCache, u: &mut [f64], calls: usize| {
+        let mut alm_optimizer = AlmOptimizer::new(cache, make_problem())
+            .with_real_time_iterations(1, 5)
+            .unwrap();
+        for _ in 0..calls {
+            alm_optimizer.solve(u).unwrap();
+        }
+    };
+
+    // three calls in the real-time iteration mode...
+    let (mut cache, mut u) = (new_cache(), vec![0.0; 2]);
+    solve(&mut cache, &mut u, 3);
+    // ...are equivalent to two calls, a checkpoint and a call with a new cache
+    let (mut cache_interrupted, mut u_interrupted) = (new_cache(), vec![0.0; 2]);
+    solve(&mut cache_interrupted, &mut u_interrupted, 2);
+    let checkpoint = cache_interrupted.checkpoint(&u_interrupted);
+    assert_eq!(2, checkpoint.outer_iterations());
+    assert_eq!(1, checkpoint.lagrange_multipliers().len());
+    assert!(checkpoint.penalty() > 0.0);
+    let bytes = checkpoint.to_bytes();
+    assert_eq!(checkpoint, AlmCheckpoint::from_bytes(&bytes).unwrap());
+    assert!(AlmCheckpoint::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    let mut cache_restored = new_cache();
+    cache_restored.restore(&checkpoint).unwrap();
+    let mut u_restored = checkpoint.iterate().to_vec();
+    solve(&mut cache_restored, &mut u_restored, 1);
+    assert_eq!(u, u_restored);
+
+    assert!(AlmCache::new(PANOCCache::new(3, 1e-6, 3).unwrap(), 1, 0)
+        .restore(&checkpoint)
+        .is_err());
+    assert!(AlmCache::new(PANOCCache::new(2, 1e-6, 3).unwrap(), 2, 0)
+        .restore(&checkpoint)
+        .is_err());
+}
+
+// subscribers (such as the one of the tests of the tracing events) may allocate
+#[cfg(all(feature = "alloc_counter", not(feature = "tracing")))]
+#[test]
+fn t_alm_no_allocations() {
+    let cost = |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+        mocks::my_cost(u, cost)?;
+        *cost += xi[1] * (u[0] + u[1]);
+        Ok(())
+    };
+    let gradient = |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+        mocks::my_gradient(u, grad)?;
+        grad.iter_mut().for_each(|g| *g += xi[1]);
+        Ok(())
+    };
+    let f = |u: &[f64], f: &mut [f64]| -> FunctionCallResult {
+        f[0] = u[0] + u[1];
+        Ok(())
+    };
+    let solve = |alm_optimizer: &mut dyn crate::Optimizer| {
+        let mut u = [0.0; 2];
+        let (status, allocations) =
+            crate::alloc_counter::count_allocations(|| alm_optimizer.solve(&mut u));
+        assert!(status.unwrap().has_converged());
+        allocations
+    };
+
+    // ALM: the only allocation is the copy of the Lagrange multipliers in the
+    // status
+    let problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        Some(Ball2::new(None, 10000.0).unwrap()),
+        cost,
+        gradient,
+        Some(f),
+        NO_MAPPING,
+        1,
+        0,
+    )
+    .unwrap();
+    let mut alm_cache = AlmCache::new(PANOCCache::new(2, 1e-6, 3).unwrap(), 1, 0);
+    assert_eq!(1, solve(&mut AlmOptimizer::new(&mut alm_cache, problem)));
+
+    // penalty method: no allocations
+    let problem = AlmProblem::new(
+        Ball2::new(None, 10.0).unwrap(),
+        NO_SET,
+        NO_SET,
+        |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+            mocks::my_cost(u, cost)?;
+            let mut f2 = [0.0];
+            f(u, &mut f2)?;
+            *cost += 0.5 * xi[0] * (f2[0] - 1.0).powi(2);
+            Ok(())
+        },
+        |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            mocks::my_gradient(u, grad)?;
+            let mut f2 = [0.0];
+            f(u, &mut f2)?;
+            grad.iter_mut().for_each(|g| *g += xi[0] * (f2[0] - 1.0));
+            Ok(())
+        },
+        NO_MAPPING,
+        Some(|u: &[f64], f2: &mut [f64]| -> FunctionCallResult {
+            f(u, f2)?;
+            f2[0] -= 1.0;
+            Ok(())
+        }),
+        0,
+        1,
+    )
+    .unwrap();
+    let mut alm_cache = AlmCache::new(PANOCCache::new(2, 1e-6, 3).unwrap(), 0, 1);
+    assert_eq!(0, solve(&mut AlmOptimizer::new(&mut alm_cache, problem)));
+}
@@ -0,0 +1,1319 @@
+//! Forward-mode automatic differentiation
+//!
+//! Gradients are computed exactly (to machine precision) using dual numbers,
+//! $a + b\varepsilon$ with $\varepsilon^2 = 0$: if the cost function is
+//! evaluated at $u + \varepsilon e_i$, the dual part of the result is the
+//! partial derivative $\partial f(u) / \partial u_i$.
+//!
+//! A cost function which is written for a generic scalar type, `T:
+//! OpEnFloat`, can be evaluated both at `f64` and at [`Dual`] numbers, which
+//! implement `OpEnFloat` (and, therefore, `num::Float`). Then,
+//! [`ForwardDiff`] provides the cost and its gradient, so that the gradient
+//! need not be implemented by hand. Likewise, [`ForwardDiffMapping`]
+//! provides the Jacobian products of mappings, such as the mappings $F_1$
+//! and $F_2$ of the augmented Lagrangian method (see `AlmFactory`).
+//!
+//! [`Dual`] is a minimal dual number which needs no further dependencies.
+//! With the feature `num-dual`, cost functions and mappings can instead be
+//! written against the API of [num-dual](https://docs.rs/num-dual) (i.e.,
+//! for `D: num_dual::DualNum<Primitive = f64>`); `ForwardDiff::new_num_dual`
+//! and `ForwardDiffMapping::new_num_dual` evaluate them at
+//! `num_dual::Dual64`. Other types of dual numbers can be used by
+//! implementing [`DualScalar`].
+//!
+//! The gradient of a function of $n$ variables requires $n$ evaluations of
+//! the function, so forward-mode differentiation is suitable for problems
+//! of small to medium size.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     autodiff::ForwardDiff, constraints::Ball2, panoc::*, FunctionCallResult, OpEnFloat,
+//!     Optimizer, Problem,
+//! };
+//!
+//! fn rosenbrock<T: OpEnFloat>(u: &[T], p: &[f64], cost: &mut T) -> FunctionCallResult {
+//!     let (a, b) = (T::from_f64(p[0]), T::from_f64(p[1]));
+//!     *cost = (a - u[0]).powi(2) + b * (u[1] - u[0].powi(2)).powi(2);
+//!     Ok(())
+//! }
+//!
+//! let p = [1.0, 100.0];
+//! let autodiff = ForwardDiff::new(2, |u, c| rosenbrock(u, &p, c));
+//! let bounds = Ball2::new(None, 2.0)?;
+//! let problem = Problem::new(
+//!     &bounds,
+//!     |u: &[f64], grad: &mut [f64]| autodiff.gradient(u, grad),
+//!     |u: &[f64], c: &mut f64| autodiff.cost(u, c),
+//! );
+//! let mut u = [-1.2, 1.0];
+//! let status = PANOCOptimizer::from_dimensions(problem, 2, 1e-10, 10)?
+//!     .with_max_iter(1000)?
+//!     .solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 1.0).abs() < 1e-6 && (u[1] - 1.0).abs() < 1e-6);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`Dual`]: struct.Dual.html
+//! [`ForwardDiff`]: struct.ForwardDiff.html
+//! [`ForwardDiffMapping`]: struct.ForwardDiffMapping.html
+//! [`DualScalar`]: trait.DualScalar.html
+
+use crate::{FunctionCallResult, OpEnFloat, SolverError};
+use num::{traits::ParseFloatError, Float, Num, NumCast, One, ToPrimitive, Zero};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    fmt,
+    iter::Sum,
+    num::FpCategory,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign},
+};
+
+/// Dual number, $a + b\varepsilon$, with $\varepsilon^2 = 0$
+///
+/// The real part, $a$, is the value of a quantity and the dual part, $b$,
+/// is its derivative (along a given direction). Comparisons only involve
+/// the values, so that branches are taken as with `f64`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dual {
+    value: f64,
+    derivative: f64,
+}
+
+impl Dual {
+    /// Constructs a dual number with given value and derivative
+    pub fn new(value: f64, derivative: f64) -> Self {
+        Dual { value, derivative }
+    }
+
+    /// Constructs a constant (whose derivative is zero)
+    pub fn constant(value: f64) -> Self {
+        Dual::new(value, 0.0)
+    }
+
+    /// Constructs the independent variable (whose derivative is one)
+    pub fn variable(value: f64) -> Self {
+        Dual::new(value, 1.0)
+    }
+
+    /// Value (real part)
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Derivative (dual part)
+    pub fn derivative(&self) -> f64 {
+        self.derivative
+    }
+
+    /// Applies a function with value `value` and derivative `slope` at
+    /// `self.value` (chain rule)
+    #[inline]
+    fn chain(self, value: f64, slope: f64) -> Self {
+        Dual::new(value, slope * self.derivative)
+    }
+}
+
+impl From<f64> for Dual {
+    fn from(value: f64) -> Self {
+        Dual::constant(value)
+    }
+}
+
+impl PartialEq for Dual {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialOrd for Dual {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl fmt::Display for Dual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)?;
+        write!(f, " + ")?;
+        fmt::Display::fmt(&self.derivative, f)?;
+        write!(f, "ε")
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    fn neg(self) -> Dual {
+        Dual::new(-self.value, -self.derivative)
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+
+    fn add(self, rhs: Dual) -> Dual {
+        Dual::new(self.value + rhs.value, self.derivative + rhs.derivative)
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+
+    fn sub(self, rhs: Dual) -> Dual {
+        Dual::new(self.value - rhs.value, self.derivative - rhs.derivative)
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+
+    fn mul(self, rhs: Dual) -> Dual {
+        Dual::new(
+            self.value * rhs.value,
+            self.derivative * rhs.value + self.value * rhs.derivative,
+        )
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+
+    fn div(self, rhs: Dual) -> Dual {
+        let value = self.value / rhs.value;
+        Dual::new(
+            value,
+            (self.derivative - value * rhs.derivative) / rhs.value,
+        )
+    }
+}
+
+impl Rem for Dual {
+    type Output = Dual;
+
+    fn rem(self, rhs: Dual) -> Dual {
+        // x % y = x - trunc(x / y) y
+        let quotient = (self.value / rhs.value).trunc();
+        Dual::new(
+            self.value % rhs.value,
+            self.derivative - quotient * rhs.derivative,
+        )
+    }
+}
+
+/// Implements the arithmetic operations between dual numbers and `f64`,
+/// both ways, and the compound assignment operations
+macro_rules! impl_scalar_ops {
+    ($($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident;)+) => {
+        $(
+            impl $trait<f64> for Dual {
+                type Output = Dual;
+
+                fn $method(self, rhs: f64) -> Dual {
+                    self.$method(Dual::constant(rhs))
+                }
+            }
+
+            impl $trait<Dual> for f64 {
+                type Output = Dual;
+
+                fn $method(self, rhs: Dual) -> Dual {
+                    Dual::constant(self).$method(rhs)
+                }
+            }
+
+            impl $assign_trait for Dual {
+                fn $assign_method(&mut self, rhs: Dual) {
+                    *self = (*self).$method(rhs);
+                }
+            }
+
+            impl $assign_trait<f64> for Dual {
+                fn $assign_method(&mut self, rhs: f64) {
+                    *self = (*self).$method(rhs);
+                }
+            }
+        )+
+    };
+}
+
+impl_scalar_ops! {
+    Add, add, AddAssign, add_assign;
+    Sub, sub, SubAssign, sub_assign;
+    Mul, mul, MulAssign, mul_assign;
+    Div, div, DivAssign, div_assign;
+    Rem, rem, RemAssign, rem_assign;
+}
+
+impl Sum for Dual {
+    fn sum<I: Iterator<Item = Dual>>(iter: I) -> Dual {
+        iter.fold(Dual::zero(), Add::add)
+    }
+}
+
+impl Zero for Dual {
+    fn zero() -> Self {
+        Dual::constant(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0.0
+    }
+}
+
+impl One for Dual {
+    fn one() -> Self {
+        Dual::constant(1.0)
+    }
+}
+
+impl Num for Dual {
+    type FromStrRadixErr = ParseFloatError;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        f64::from_str_radix(s, radix).map(Dual::constant)
+    }
+}
+
+impl ToPrimitive for Dual {
+    fn to_i64(&self) -> Option<i64> {
+        self.value.to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.value.to_u64()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.value)
+    }
+}
+
+impl NumCast for Dual {
+    fn from<N: ToPrimitive>(n: N) -> Option<Self> {
+        n.to_f64().map(Dual::constant)
+    }
+}
+
+impl Float for Dual {
+    fn nan() -> Self {
+        Dual::constant(f64::NAN)
+    }
+
+    fn infinity() -> Self {
+        Dual::constant(f64::INFINITY)
+    }
+
+    fn neg_infinity() -> Self {
+        Dual::constant(f64::NEG_INFINITY)
+    }
+
+    fn neg_zero() -> Self {
+        Dual::constant(-0.0)
+    }
+
+    fn min_value() -> Self {
+        Dual::constant(f64::MIN)
+    }
+
+    fn min_positive_value() -> Self {
+        Dual::constant(f64::MIN_POSITIVE)
+    }
+
+    fn epsilon() -> Self {
+        Dual::constant(f64::EPSILON)
+    }
+
+    fn max_value() -> Self {
+        Dual::constant(f64::MAX)
+    }
+
+    fn is_nan(self) -> bool {
+        self.value.is_nan()
+    }
+
+    fn is_infinite(self) -> bool {
+        self.value.is_infinite()
+    }
+
+    fn is_finite(self) -> bool {
+        self.value.is_finite()
+    }
+
+    fn is_normal(self) -> bool {
+        self.value.is_normal()
+    }
+
+    fn classify(self) -> FpCategory {
+        self.value.classify()
+    }
+
+    fn floor(self) -> Self {
+        Dual::constant(self.value.floor())
+    }
+
+    fn ceil(self) -> Self {
+        Dual::constant(self.value.ceil())
+    }
+
+    fn round(self) -> Self {
+        Dual::constant(self.value.round())
+    }
+
+    fn trunc(self) -> Self {
+        Dual::constant(self.value.trunc())
+    }
+
+    fn fract(self) -> Self {
+        self.chain(self.value.fract(), 1.0)
+    }
+
+    fn abs(self) -> Self {
+        if self.value < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    fn signum(self) -> Self {
+        Dual::constant(self.value.signum())
+    }
+
+    fn is_sign_positive(self) -> bool {
+        self.value.is_sign_positive()
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.value.is_sign_negative()
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn recip(self) -> Self {
+        let recip = self.value.recip();
+        self.chain(recip, -recip * recip)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        match n {
+            0 => Dual::one(),
+            _ => self.chain(self.value.powi(n), n as f64 * self.value.powi(n - 1)),
+        }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        let value = self.value.powf(n.value);
+        let mut derivative = n.value * self.value.powf(n.value - 1.0) * self.derivative;
+        if n.derivative != 0.0 {
+            derivative += value * self.value.ln() * n.derivative;
+        }
+        Dual::new(value, derivative)
+    }
+
+    fn sqrt(self) -> Self {
+        let sqrt = self.value.sqrt();
+        self.chain(sqrt, 0.5 / sqrt)
+    }
+
+    fn exp(self) -> Self {
+        let exp = self.value.exp();
+        self.chain(exp, exp)
+    }
+
+    fn exp2(self) -> Self {
+        let exp2 = self.value.exp2();
+        self.chain(exp2, exp2 * std::f64::consts::LN_2)
+    }
+
+    fn ln(self) -> Self {
+        self.chain(self.value.ln(), self.value.recip())
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        self.chain(
+            self.value.log2(),
+            (self.value * std::f64::consts::LN_2).recip(),
+        )
+    }
+
+    fn log10(self) -> Self {
+        self.chain(
+            self.value.log10(),
+            (self.value * std::f64::consts::LN_10).recip(),
+        )
+    }
+
+    fn max(self, other: Self) -> Self {
+        if self.value.is_nan() || other.value > self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if self.value.is_nan() || other.value < self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn abs_sub(self, other: Self) -> Self {
+        (self - other).max(Dual::zero())
+    }
+
+    fn cbrt(self) -> Self {
+        let cbrt = self.value.cbrt();
+        self.chain(cbrt, (3.0 * cbrt * cbrt).recip())
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        let hypot = self.value.hypot(other.value);
+        Dual::new(
+            hypot,
+            (self.value * self.derivative + other.value * other.derivative) / hypot,
+        )
+    }
+
+    fn sin(self) -> Self {
+        self.chain(self.value.sin(), self.value.cos())
+    }
+
+    fn cos(self) -> Self {
+        self.chain(self.value.cos(), -self.value.sin())
+    }
+
+    fn tan(self) -> Self {
+        let tan = self.value.tan();
+        self.chain(tan, 1.0 + tan * tan)
+    }
+
+    fn asin(self) -> Self {
+        self.chain(
+            self.value.asin(),
+            (1.0 - self.value * self.value).sqrt().recip(),
+        )
+    }
+
+    fn acos(self) -> Self {
+        self.chain(
+            self.value.acos(),
+            -(1.0 - self.value * self.value).sqrt().recip(),
+        )
+    }
+
+    fn atan(self) -> Self {
+        self.chain(self.value.atan(), (1.0 + self.value * self.value).recip())
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        let (y, x) = (self, other);
+        Dual::new(
+            y.value.atan2(x.value),
+            (x.value * y.derivative - y.value * x.derivative)
+                / (x.value * x.value + y.value * y.value),
+        )
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        (self.sin(), self.cos())
+    }
+
+    fn exp_m1(self) -> Self {
+        self.chain(self.value.exp_m1(), self.value.exp())
+    }
+
+    fn ln_1p(self) -> Self {
+        self.chain(self.value.ln_1p(), (1.0 + self.value).recip())
+    }
+
+    fn sinh(self) -> Self {
+        self.chain(self.value.sinh(), self.value.cosh())
+    }
+
+    fn cosh(self) -> Self {
+        self.chain(self.value.cosh(), self.value.sinh())
+    }
+
+    fn tanh(self) -> Self {
+        let tanh = self.value.tanh();
+        self.chain(tanh, 1.0 - tanh * tanh)
+    }
+
+    fn asinh(self) -> Self {
+        self.chain(
+            self.value.asinh(),
+            (self.value * self.value + 1.0).sqrt().recip(),
+        )
+    }
+
+    fn acosh(self) -> Self {
+        self.chain(
+            self.value.acosh(),
+            (self.value * self.value - 1.0).sqrt().recip(),
+        )
+    }
+
+    fn atanh(self) -> Self {
+        self.chain(self.value.atanh(), (1.0 - self.value * self.value).recip())
+    }
+
+    fn integer_decode(self) -> (u64, i16, i8) {
+        self.value.integer_decode()
+    }
+}
+
+impl OpEnFloat for Dual {
+    fn from_f64(x: f64) -> Self {
+        Dual::constant(x)
+    }
+
+    fn as_f64(self) -> f64 {
+        self.value
+    }
+}
+
+/// Checks that a vector has the expected dimension
+fn check_dimension(name: &'static str, expected: usize, actual: usize) -> FunctionCallResult {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(SolverError::DimensionMismatch {
+            name,
+            expected,
+            actual,
+        })
+    }
+}
+
+/// Dual numbers at which [`ForwardDiff`] and [`ForwardDiffMapping`] evaluate
+/// cost functions and mappings
+///
+/// This trait is implemented for [`Dual`] and, with the feature `num-dual`,
+/// for `num_dual::Dual64`, so that cost functions and mappings can also be
+/// written against the API of [num-dual](https://docs.rs/num-dual).
+pub trait DualScalar: Copy {
+    /// Constructs a dual number with given value and derivative
+    fn new(value: f64, derivative: f64) -> Self;
+
+    /// Value (real part)
+    fn value(&self) -> f64;
+
+    /// Derivative (dual part)
+    fn derivative(&self) -> f64;
+}
+
+impl DualScalar for Dual {
+    fn new(value: f64, derivative: f64) -> Self {
+        Dual { value, derivative }
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn derivative(&self) -> f64 {
+        self.derivative
+    }
+}
+
+#[cfg(feature = "num-dual")]
+impl DualScalar for num_dual::Dual64 {
+    fn new(value: f64, derivative: f64) -> Self {
+        num_dual::Dual64::new(value, derivative)
+    }
+
+    fn value(&self) -> f64 {
+        self.re
+    }
+
+    fn derivative(&self) -> f64 {
+        self.eps
+    }
+}
+
+/// Sets the workspace to the point `u` (with zero derivatives)
+fn set_point<D: DualScalar>(workspace: &mut [D], u: &[f64]) {
+    workspace
+        .iter_mut()
+        .zip(u)
+        .for_each(|(x, &ui)| *x = D::new(ui, 0.0));
+}
+
+/// Cost function and its gradient, which is computed by forward-mode
+/// automatic differentiation
+///
+/// The cost function, which is evaluated at dual numbers, has the same
+/// signature as the cost functions of `Problem`. It is typically a generic
+/// function, `f<T: OpEnFloat>(u: &[T], p: &[f64], cost: &mut T)`, which is
+/// called as `|u, c| f(u, &p, c)`. The methods `cost` and `gradient` can be
+/// passed to `Problem::new` (see the [module documentation](index.html)).
+///
+/// The type parameter `D` is the type of the dual numbers ([`Dual`] by
+/// default; see [`DualScalar`]). With the feature `num-dual`,
+/// `ForwardDiff::new_num_dual` evaluates the cost function at
+/// `num_dual::Dual64`.
+///
+/// A workspace of `n` dual numbers is allocated once, when a `ForwardDiff`
+/// is constructed.
+pub struct ForwardDiff<F, D = Dual>
+where
+    F: Fn(&[D], &mut D) -> FunctionCallResult,
+    D: DualScalar,
+{
+    /// cost function evaluated at dual numbers
+    cost: F,
+    /// point at which the cost function is evaluated
+    workspace: RefCell<Vec<D>>,
+}
+
+impl<F> ForwardDiff<F>
+where
+    F: Fn(&[Dual], &mut Dual) -> FunctionCallResult,
+{
+    /// Constructs a new instance
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: number of decision variables
+    /// - `cost`: cost function evaluated at dual numbers
+    ///
+    pub fn new(n: usize, cost: F) -> Self {
+        ForwardDiff::with_dual_type(n, cost)
+    }
+}
+
+#[cfg(feature = "num-dual")]
+impl<F> ForwardDiff<F, num_dual::Dual64>
+where
+    F: Fn(&[num_dual::Dual64], &mut num_dual::Dual64) -> FunctionCallResult,
+{
+    /// Constructs a new instance whose cost function is evaluated at the dual
+    /// numbers of num-dual, `num_dual::Dual64`
+    ///
+    /// The cost function is typically written against the API of num-dual,
+    /// e.g., `f<D: DualNum<Primitive = f64> + Copy>(u: &[D], cost: &mut D)`,
+    /// and is then instantiated at `num_dual::Dual64`.
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: number of decision variables
+    /// - `cost`: cost function evaluated at `num_dual::Dual64`
+    ///
+    pub fn new_num_dual(n: usize, cost: F) -> Self {
+        ForwardDiff::with_dual_type(n, cost)
+    }
+}
+
+impl<F, D> ForwardDiff<F, D>
+where
+    F: Fn(&[D], &mut D) -> FunctionCallResult,
+    D: DualScalar,
+{
+    /// Constructs a new instance which evaluates the cost function at dual
+    /// numbers of type `D`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: number of decision variables
+    /// - `cost`: cost function evaluated at dual numbers
+    ///
+    pub fn with_dual_type(n: usize, cost: F) -> Self {
+        ForwardDiff {
+            cost,
+            workspace: RefCell::new(vec![D::new(0.0, 0.0); n]),
+        }
+    }
+
+    /// Number of decision variables
+    pub fn dimension(&self) -> usize {
+        self.workspace.borrow().len()
+    }
+
+    /// Computes the cost at `u`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` does not have `n`
+    /// elements, or the error of the cost function
+    ///
+    pub fn cost(&self, u: &[f64], cost: &mut f64) -> FunctionCallResult {
+        check_dimension("u", self.dimension(), u.len())?;
+        let mut workspace = self.workspace.borrow_mut();
+        set_point(&mut workspace, u);
+        let mut value = D::new(0.0, 0.0);
+        (self.cost)(&workspace, &mut value)?;
+        *cost = value.value();
+        Ok(())
+    }
+
+    /// Computes the gradient of the cost at `u` (with `n` evaluations of the
+    /// cost function)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` or `grad` do not have
+    /// `n` elements, or the error of the cost function
+    ///
+    pub fn gradient(&self, u: &[f64], grad: &mut [f64]) -> FunctionCallResult {
+        check_dimension("u", self.dimension(), u.len())?;
+        check_dimension("grad", self.dimension(), grad.len())?;
+        let mut workspace = self.workspace.borrow_mut();
+        set_point(&mut workspace, u);
+        for (i, grad_i) in grad.iter_mut().enumerate() {
+            workspace[i] = D::new(u[i], 1.0);
+            let mut value = D::new(0.0, 0.0);
+            let result = (self.cost)(&workspace, &mut value);
+            workspace[i] = D::new(u[i], 0.0);
+            result?;
+            *grad_i = value.derivative();
+        }
+        Ok(())
+    }
+}
+
+impl<F, D> fmt::Debug for ForwardDiff<F, D>
+where
+    F: Fn(&[D], &mut D) -> FunctionCallResult,
+    D: DualScalar,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForwardDiff")
+            .field("dimension", &self.dimension())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Mapping, $F:\mathbb{R}^n\to\mathbb{R}^m$, and its Jacobian products,
+/// which are computed by forward-mode automatic differentiation
+///
+/// The mapping, which is evaluated at dual numbers, has the same signature
+/// as the mappings $F_1$ and $F_2$ of `AlmFactory`; the methods `mapping`
+/// and `jacobian_transpose_product` can be passed to `AlmFactory::new`, so
+/// that neither the gradient of the cost nor the Jacobian products need to
+/// be implemented by hand:
+///
+/// ```
+/// use optimization_engine::{
+///     alm::*, autodiff::*, constraints::Ball2, FunctionCallResult, OpEnFloat,
+/// };
+///
+/// fn cost<T: OpEnFloat>(u: &[T], c: &mut T) -> FunctionCallResult {
+///     *c = u[0].powi(2) + u[1].exp();
+///     Ok(())
+/// }
+///
+/// fn f1<T: OpEnFloat>(u: &[T], f1u: &mut [T]) -> FunctionCallResult {
+///     f1u[0] = u[0] * u[1];
+///     f1u[1] = u[0].sin();
+///     Ok(())
+/// }
+///
+/// let (cost, f1) = (ForwardDiff::new(2, cost), ForwardDiffMapping::new(2, 2, f1));
+/// let set_c = Ball2::new(None, 1.0)?;
+/// let factory = AlmFactory::new(
+///     |u: &[f64], c: &mut f64| cost.cost(u, c),
+///     |u: &[f64], grad: &mut [f64]| cost.gradient(u, grad),
+///     Some(|u: &[f64], f1u: &mut [f64]| f1.mapping(u, f1u)),
+///     Some(|u: &[f64], d: &[f64], res: &mut [f64]| f1.jacobian_transpose_product(u, d, res)),
+///     NO_MAPPING,
+///     NO_JACOBIAN_MAPPING,
+///     Some(set_c),
+///     0,
+/// )?;
+/// # let _ = factory;
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+///
+/// As with [`ForwardDiff`], the type parameter `D` is the type of the dual
+/// numbers ([`Dual`] by default) and, with the feature `num-dual`,
+/// `ForwardDiffMapping::new_num_dual` evaluates the mapping at
+/// `num_dual::Dual64`.
+///
+/// Workspaces of `n` and `m` dual numbers are allocated once, when a
+/// `ForwardDiffMapping` is constructed.
+pub struct ForwardDiffMapping<F, D = Dual>
+where
+    F: Fn(&[D], &mut [D]) -> FunctionCallResult,
+    D: DualScalar,
+{
+    /// mapping evaluated at dual numbers
+    mapping: F,
+    /// point at which the mapping is evaluated
+    workspace: RefCell<Vec<D>>,
+    /// value of the mapping
+    result: RefCell<Vec<D>>,
+}
+
+impl<F> ForwardDiffMapping<F>
+where
+    F: Fn(&[Dual], &mut [Dual]) -> FunctionCallResult,
+{
+    /// Constructs a new instance
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: dimension of the domain of the mapping
+    /// - `m`: dimension of the range of the mapping
+    /// - `mapping`: mapping evaluated at dual numbers
+    ///
+    pub fn new(n: usize, m: usize, mapping: F) -> Self {
+        ForwardDiffMapping::with_dual_type(n, m, mapping)
+    }
+}
+
+#[cfg(feature = "num-dual")]
+impl<F> ForwardDiffMapping<F, num_dual::Dual64>
+where
+    F: Fn(&[num_dual::Dual64], &mut [num_dual::Dual64]) -> FunctionCallResult,
+{
+    /// Constructs a new instance whose mapping is evaluated at the dual
+    /// numbers of num-dual, `num_dual::Dual64`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: dimension of the domain of the mapping
+    /// - `m`: dimension of the range of the mapping
+    /// - `mapping`: mapping evaluated at `num_dual::Dual64`
+    ///
+    pub fn new_num_dual(n: usize, m: usize, mapping: F) -> Self {
+        ForwardDiffMapping::with_dual_type(n, m, mapping)
+    }
+}
+
+impl<F, D> ForwardDiffMapping<F, D>
+where
+    F: Fn(&[D], &mut [D]) -> FunctionCallResult,
+    D: DualScalar,
+{
+    /// Constructs a new instance which evaluates the mapping at dual numbers
+    /// of type `D`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: dimension of the domain of the mapping
+    /// - `m`: dimension of the range of the mapping
+    /// - `mapping`: mapping evaluated at dual numbers
+    ///
+    pub fn with_dual_type(n: usize, m: usize, mapping: F) -> Self {
+        ForwardDiffMapping {
+            mapping,
+            workspace: RefCell::new(vec![D::new(0.0, 0.0); n]),
+            result: RefCell::new(vec![D::new(0.0, 0.0); m]),
+        }
+    }
+
+    /// Dimension of the domain of the mapping, $n$
+    pub fn domain_dimension(&self) -> usize {
+        self.workspace.borrow().len()
+    }
+
+    /// Dimension of the range of the mapping, $m$
+    pub fn range_dimension(&self) -> usize {
+        self.result.borrow().len()
+    }
+
+    /// Computes $F(u)$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` does not have `n`
+    /// elements or `fu` does not have `m` elements, or the error of the
+    /// mapping
+    ///
+    pub fn mapping(&self, u: &[f64], fu: &mut [f64]) -> FunctionCallResult {
+        self.jacobian_product(u, None, fu, None)
+    }
+
+    /// Computes $F(u)$ and the Jacobian-vector product $JF(u)v$ (with one
+    /// evaluation of the mapping)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` or `v` do not have `n`
+    /// elements or `fu` or `jv` do not have `m` elements, or the error of the
+    /// mapping
+    ///
+    pub fn jacobian_vector_product(
+        &self,
+        u: &[f64],
+        v: &[f64],
+        fu: &mut [f64],
+        jv: &mut [f64],
+    ) -> FunctionCallResult {
+        self.jacobian_product(u, Some(v), fu, Some(jv))
+    }
+
+    fn jacobian_product(
+        &self,
+        u: &[f64],
+        v: Option<&[f64]>,
+        fu: &mut [f64],
+        jv: Option<&mut [f64]>,
+    ) -> FunctionCallResult {
+        let (n, m) = (self.domain_dimension(), self.range_dimension());
+        check_dimension("u", n, u.len())?;
+        check_dimension("fu", m, fu.len())?;
+        check_dimension("v", n, v.map_or(n, <[f64]>::len))?;
+        check_dimension("jv", m, jv.as_ref().map_or(m, |jv| jv.len()))?;
+        let mut workspace = self.workspace.borrow_mut();
+        let mut result = self.result.borrow_mut();
+        set_point(&mut workspace, u);
+        if let Some(v) = v {
+            workspace
+                .iter_mut()
+                .zip(u.iter().zip(v))
+                .for_each(|(x, (&ui, &vi))| *x = D::new(ui, vi));
+        }
+        (self.mapping)(&workspace, &mut result)?;
+        fu.iter_mut()
+            .zip(result.iter())
+            .for_each(|(fu_j, y)| *fu_j = y.value());
+        if let Some(jv) = jv {
+            jv.iter_mut()
+                .zip(result.iter())
+                .for_each(|(jv_j, y)| *jv_j = y.derivative());
+        }
+        Ok(())
+    }
+
+    /// Computes the product $JF(u)^\intercal d$ (with `n` evaluations of the
+    /// mapping)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` or `res` do not have
+    /// `n` elements or `d` does not have `m` elements, or the error of the
+    /// mapping
+    ///
+    pub fn jacobian_transpose_product(
+        &self,
+        u: &[f64],
+        d: &[f64],
+        res: &mut [f64],
+    ) -> FunctionCallResult {
+        let (n, m) = (self.domain_dimension(), self.range_dimension());
+        check_dimension("u", n, u.len())?;
+        check_dimension("d", m, d.len())?;
+        check_dimension("res", n, res.len())?;
+        let mut workspace = self.workspace.borrow_mut();
+        let mut result = self.result.borrow_mut();
+        set_point(&mut workspace, u);
+        for (i, res_i) in res.iter_mut().enumerate() {
+            // the i-th element of JF(u)'d is the derivative of d'F(u) along e_i
+            workspace[i] = D::new(u[i], 1.0);
+            let evaluation = (self.mapping)(&workspace, &mut result);
+            workspace[i] = D::new(u[i], 0.0);
+            evaluation?;
+            *res_i = result
+                .iter()
+                .zip(d)
+                .map(|(y, dj)| y.derivative() * dj)
+                .sum();
+        }
+        Ok(())
+    }
+}
+
+impl<F, D> fmt::Debug for ForwardDiffMapping<F, D>
+where
+    F: Fn(&[D], &mut [D]) -> FunctionCallResult,
+    D: DualScalar,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForwardDiffMapping")
+            .field("domain_dimension", &self.domain_dimension())
+            .field("range_dimension", &self.range_dimension())
+            .finish_non_exhaustive()
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alm::*, mocks};
+
+    fn rosenbrock<T: OpEnFloat>(u: &[T], p: &[f64], cost: &mut T) -> FunctionCallResult {
+        let (a, b) = (T::from_f64(p[0]), T::from_f64(p[1]));
+        *cost = (a - u[0]).powi(2) + b * (u[1] - u[0].powi(2)).powi(2);
+        Ok(())
+    }
+
+    /// Derivative of `f` at `x` by central finite differences
+    fn finite_difference(f: impl Fn(f64) -> f64, x: f64) -> f64 {
+        (f(x + 1e-6) - f(x - 1e-6)) / 2e-6
+    }
+
+    #[test]
+    fn t_forward_diff_rosenbrock() {
+        let p = [1.5, 10.0];
+        let autodiff = ForwardDiff::new(2, |u, c| rosenbrock(u, &p, c));
+        let u = [0.3, -0.7];
+        let (mut cost, mut grad) = (0.0, [0.0; 2]);
+        let mut grad_expected = [0.0; 2];
+        autodiff.cost(&u, &mut cost).unwrap();
+        autodiff.gradient(&u, &mut grad).unwrap();
+        mocks::rosenbrock_grad(p[0], p[1], &u, &mut grad_expected);
+        assert_eq!(mocks::rosenbrock_cost(p[0], p[1], &u), cost);
+        unit_test_utils::assert_nearly_equal_array(&grad_expected, &grad, 1e-14, 1e-14, "grad");
+    }
+
+    #[test]
+    fn t_dual_elementary_functions() {
+        /// Name, function of a dual number and the same function of a float
+        type ElementaryFunction = (&'static str, fn(Dual) -> Dual, fn(f64) -> f64);
+        let functions: [ElementaryFunction; 14] = [
+            ("recip", |x| x.recip(), |x| x.recip()),
+            ("powi", |x| x.powi(-3), |x| x.powi(-3)),
+            ("powf", |x| x.powf(Dual::constant(2.5)), |x| x.powf(2.5)),
+            (
+                "powf (exponent)",
+                |x| Dual::constant(2.0).powf(x),
+                |x| 2.0_f64.powf(x),
+            ),
+            ("sqrt", |x| x.sqrt(), |x| x.sqrt()),
+            ("exp2", |x| x.exp2(), |x| x.exp2()),
+            ("log10", |x| x.log10(), |x| x.log10()),
+            ("cbrt", |x| x.cbrt(), |x| x.cbrt()),
+            ("hypot", |x| x.hypot(x * x), |x| x.hypot(x * x)),
+            ("tan", |x| x.tan(), |x| x.tan()),
+            ("acos", |x| x.acos(), |x| x.acos()),
+            (
+                "atan2",
+                |x| Dual::constant(-0.3).atan2(x),
+                |x| (-0.3_f64).atan2(x),
+            ),
+            ("asinh", |x| x.asinh(), |x| x.asinh()),
+            ("atanh", |x| (x / 2.0).atanh(), |x| (x / 2.0).atanh()),
+        ];
+        let x = 0.7;
+        for (name, f_dual, f) in functions.iter() {
+            let y = f_dual(Dual::variable(x));
+            assert_eq!(f(x), y.value(), "{}", name);
+            unit_test_utils::assert_nearly_equal(
+                finite_difference(f, x),
+                y.derivative(),
+                1e-7,
+                1e-8,
+                name,
+            );
+        }
+    }
+
+    #[test]
+    fn t_dual_arithmetic() {
+        let (x, y) = (Dual::new(2.0, 1.0), Dual::new(-3.0, 0.5));
+        assert_eq!(1.0 * -3.0 + 2.0 * 0.5, (x * y).derivative());
+        assert_eq!((1.0 * -3.0 - 2.0 * 0.5) / 9.0, (x / y).derivative());
+        assert_eq!(1.0, (x % y).derivative());
+        assert_eq!(3.0, (1.0 - 3.0 * x).abs().derivative());
+        assert_eq!(0.5, x.max(y).min(y).derivative());
+        let mut z = x;
+        z *= 2.0;
+        z += y;
+        assert_eq!((1.0, 2.5), (z.value(), z.derivative()));
+        assert_eq!(2.5, [x, y, x].iter().copied().sum::<Dual>().derivative());
+        assert!(x > y && x == Dual::constant(2.0));
+        assert_eq!("2 + 1ε", format!("{}", x));
+    }
+
+    /// Generic version of `mocks::mapping_f1_affine`
+    fn mapping_f1_affine<T: OpEnFloat>(u: &[T], f1u: &mut [T]) -> FunctionCallResult {
+        let (one, two, three) = (T::one(), T::from_f64(2.0), T::from_f64(3.0));
+        f1u[0] = two * u[0] + u[2] - one;
+        f1u[1] = u[0] + three * u[1];
+        Ok(())
+    }
+
+    /// Generic version of `mocks::f0`
+    fn f0<T: OpEnFloat>(u: &[T], cost: &mut T) -> FunctionCallResult {
+        *cost = u.iter().map(|&ui| T::from_f64(0.5) * ui * ui + ui).sum();
+        Ok(())
+    }
+
+    #[test]
+    fn t_forward_diff_mapping_alm_factory() {
+        let (f, f1) = (
+            ForwardDiff::new(3, f0),
+            ForwardDiffMapping::new(3, 2, mapping_f1_affine),
+        );
+        let set_c = crate::constraints::Ball2::new(None, 1.0).unwrap();
+        let factory = AlmFactory::new(
+            |u: &[f64], c: &mut f64| f.cost(u, c),
+            |u: &[f64], grad: &mut [f64]| f.gradient(u, grad),
+            Some(|u: &[f64], f1u: &mut [f64]| f1.mapping(u, f1u)),
+            Some(|u: &[f64], d: &[f64], res: &mut [f64]| f1.jacobian_transpose_product(u, d, res)),
+            NO_MAPPING,
+            NO_JACOBIAN_MAPPING,
+            Some(set_c),
+            0,
+        )
+        .unwrap();
+        let set_c = crate::constraints::Ball2::new(None, 1.0).unwrap();
+        let factory_expected = AlmFactory::new(
+            mocks::f0,
+            mocks::d_f0,
+            Some(mocks::mapping_f1_affine),
+            Some(mocks::mapping_f1_affine_jacobian_product),
+            NO_MAPPING,
+            NO_JACOBIAN_MAPPING,
+            Some(set_c),
+            0,
+        )
+        .unwrap();
+        let (u, xi) = ([0.5, -1.5, 2.0], [10.0, 0.3, -0.7]);
+        let (mut psi, mut psi_expected) = (0.0, 0.0);
+        let (mut grad, mut grad_expected) = ([0.0; 3], [0.0; 3]);
+        factory.psi(&u, &xi, &mut psi).unwrap();
+        factory_expected.psi(&u, &xi, &mut psi_expected).unwrap();
+        factory.d_psi(&u, &xi, &mut grad).unwrap();
+        factory_expected.d_psi(&u, &xi, &mut grad_expected).unwrap();
+        unit_test_utils::assert_nearly_equal(psi_expected, psi, 1e-14, 1e-14, "psi");
+        unit_test_utils::assert_nearly_equal_array(&grad_expected, &grad, 1e-14, 1e-14, "d_psi");
+    }
+
+    #[test]
+    fn t_forward_diff_mapping_products() {
+        let mapping = |u: &[Dual], fu: &mut [Dual]| -> FunctionCallResult {
+            fu[0] = u[0] * u[1].sin();
+            fu[1] = u[1].exp() - u[0].powi(3);
+            fu[2] = u[0].hypot(u[1]);
+            Ok(())
+        };
+        let jacobian = |u: &[f64]| {
+            let r = u[0].hypot(u[1]);
+            [
+                [u[1].sin(), u[0] * u[1].cos()],
+                [-3.0 * u[0].powi(2), u[1].exp()],
+                [u[0] / r, u[1] / r],
+            ]
+        };
+        let f = ForwardDiffMapping::new(2, 3, mapping);
+        let (u, v, d) = ([0.7, -1.3], [2.0, 0.5], [1.0, -2.0, 0.25]);
+        let (mut fu, mut jv, mut jtd) = ([0.0; 3], [0.0; 3], [0.0; 2]);
+        f.jacobian_vector_product(&u, &v, &mut fu, &mut jv).unwrap();
+        f.jacobian_transpose_product(&u, &d, &mut jtd).unwrap();
+        let j = jacobian(&u);
+        for (jv_i, j_i) in jv.iter().zip(j.iter()) {
+            unit_test_utils::assert_nearly_equal(
+                j_i[0] * v[0] + j_i[1] * v[1],
+                *jv_i,
+                1e-14,
+                1e-14,
+                "Jv",
+            );
+        }
+        for (k, jtd_k) in jtd.iter().enumerate() {
+            let expected: f64 = j.iter().zip(d.iter()).map(|(j_i, d_i)| j_i[k] * d_i).sum();
+            unit_test_utils::assert_nearly_equal(expected, *jtd_k, 1e-14, 1e-14, "J'd");
+        }
+        assert_eq!(fu[1], (-1.3_f64).exp() - 0.7_f64.powi(3));
+        assert!(matches!(
+            f.jacobian_vector_product(&u, &v, &mut fu, &mut jv[..2]),
+            Err(SolverError::DimensionMismatch { name: "jv", .. })
+        ));
+        assert!(f.jacobian_transpose_product(&u, &d[..2], &mut jtd).is_err());
+    }
+
+    #[cfg(feature = "num-dual")]
+    #[test]
+    fn t_forward_diff_num_dual() {
+        use num_dual::DualNum;
+
+        fn cost<D: DualNum<Primitive = f64> + Copy>(u: &[D], c: &mut D) -> FunctionCallResult {
+            *c = (u[0] - 1.5).powi(2) + (u[1] - u[0].powi(2)).powi(2) * 10.0;
+            Ok(())
+        }
+
+        fn mapping<D: DualNum<Primitive = f64> + Copy>(
+            u: &[D],
+            fu: &mut [D],
+        ) -> FunctionCallResult {
+            fu[0] = u[0] * u[1].sin();
+            fu[1] = u[1].exp() - u[0].powi(3);
+            Ok(())
+        }
+
+        let (f, f_mapping) = (
+            ForwardDiff::new_num_dual(2, cost),
+            ForwardDiffMapping::new_num_dual(2, 2, mapping),
+        );
+        let u = [0.3, -0.7];
+        let (mut c, mut grad, mut grad_expected) = (0.0, [0.0; 2], [0.0; 2]);
+        f.cost(&u, &mut c).unwrap();
+        f.gradient(&u, &mut grad).unwrap();
+        mocks::rosenbrock_grad(1.5, 10.0, &u, &mut grad_expected);
+        unit_test_utils::assert_nearly_equal(
+            mocks::rosenbrock_cost(1.5, 10.0, &u),
+            c,
+            1e-14,
+            1e-14,
+            "cost",
+        );
+        unit_test_utils::assert_nearly_equal_array(&grad_expected, &grad, 1e-14, 1e-14, "grad");
+
+        // the same mapping, evaluated at the dual numbers of this module
+        let f_mapping_expected = ForwardDiffMapping::new(2, 2, |u: &[Dual], fu: &mut [Dual]| {
+            fu[0] = u[0] * u[1].sin();
+            fu[1] = u[1].exp() - u[0].powi(3);
+            Ok(())
+        });
+        let (v, d) = ([2.0, 0.5], [1.0, -2.0]);
+        let (mut fu, mut jv, mut jtd) = ([0.0; 2], [0.0; 2], [0.0; 2]);
+        let (mut fu_expected, mut jv_expected, mut jtd_expected) = ([0.0; 2], [0.0; 2], [0.0; 2]);
+        f_mapping
+            .jacobian_vector_product(&u, &v, &mut fu, &mut jv)
+            .unwrap();
+        f_mapping
+            .jacobian_transpose_product(&u, &d, &mut jtd)
+            .unwrap();
+        f_mapping_expected
+            .jacobian_vector_product(&u, &v, &mut fu_expected, &mut jv_expected)
+            .unwrap();
+        f_mapping_expected
+            .jacobian_transpose_product(&u, &d, &mut jtd_expected)
+            .unwrap();
+        unit_test_utils::assert_nearly_equal_array(&fu_expected, &fu, 1e-14, 1e-14, "fu");
+        unit_test_utils::assert_nearly_equal_array(&jv_expected, &jv, 1e-14, 1e-14, "Jv");
+        unit_test_utils::assert_nearly_equal_array(&jtd_expected, &jtd, 1e-14, 1e-14, "J'd");
+    }
+
+    #[test]
+    fn t_forward_diff_errors() {
+        let autodiff = ForwardDiff::new(2, |u: &[Dual], c: &mut Dual| {
+            if u[0] < Dual::zero() {
+                return Err(SolverError::Cost);
+            }
+            *c = u[0] * u[1];
+            Ok(())
+        });
+        let mut grad = [0.0; 3];
+        assert!(matches!(
+            autodiff.gradient(&[1.0, 2.0], &mut grad),
+            Err(SolverError::DimensionMismatch {
+                name: "grad",
+                expected: 2,
+                actual: 3,
+            })
+        ));
+        let mut cost = 0.0;
+        assert!(autodiff.cost(&[1.0], &mut cost).is_err());
+        assert!(autodiff.gradient(&[-1.0, 2.0], &mut grad[..2]).is_err());
+        autodiff.gradient(&[1.0, 2.0], &mut grad[..2]).unwrap();
+        assert_eq!([2.0, 1.0], grad[..2]);
+    }
+}
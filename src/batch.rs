@@ -0,0 +1,378 @@
+//! Batched parametric solves
+//!
+//! Scenario-based MPC and Monte-Carlo studies solve many independent
+//! instances of the same parametric problem, which differ only in their
+//! parameters. A [`BatchSolver`] solves a batch of instances, optionally on
+//! several threads, using caches from a [`CachePool`], so that caches are
+//! allocated once (at most one per thread) and are reused across batches.
+//! The solution of every instance is stored in a [`BatchSolution`], which
+//! also holds its initial guess, so that the instances can be warm started
+//! from the solutions of the previous batch.
+//!
+//! The solver is a closure which, given the parameter of an instance and a
+//! cache, solves the instance from the initial guess `u` (in place); any
+//! optimizer of OpEn can be used.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     batch::*, constraints::Ball2, core::CachePool, panoc::*, FunctionCallResult, Optimizer,
+//!     Problem,
+//! };
+//!
+//! // minimize (u_0 - a)^2 + u_1^2 subject to |u| <= 10, for many values of a
+//! let solver = BatchSolver::new(
+//!     CachePool::for_panoc(2, 1e-8, 5)?,
+//!     |a: &f64, cache: &mut PANOCCache, u: &mut [f64]| {
+//!         let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!             *c = (u[0] - a).powi(2) + u[1].powi(2);
+//!             Ok(())
+//!         };
+//!         let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!             grad[0] = 2.0 * (u[0] - a);
+//!             grad[1] = 2.0 * u[1];
+//!             Ok(())
+//!         };
+//!         let bounds = Ball2::new(None, 10.0)?;
+//!         PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), cache).solve(u)
+//!     },
+//! )
+//! .with_num_threads(4)?;
+//! let parameters: Vec<f64> = (0..100).map(|i| i as f64 / 10.0).collect();
+//! let mut solutions: Vec<_> = parameters.iter().map(|_| BatchSolution::new(&[0.0; 2])).collect();
+//! solver.solve_batch(&parameters, &mut solutions)?;
+//! for (a, solution) in parameters.iter().zip(solutions.iter()) {
+//!     assert!(solution.status().unwrap().is_ok());
+//!     assert!((solution.u()[0] - a).abs() < 1e-6);
+//! }
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`BatchSolver`]: struct.BatchSolver.html
+//! [`BatchSolution`]: struct.BatchSolution.html
+//! [`CachePool`]: ../core/struct.CachePool.html
+
+use crate::{core::CachePool, ensure, FunctionCallResult, SolverError};
+use std::thread;
+
+/// Solution of an instance of a batch
+#[derive(Debug)]
+pub struct BatchSolution<S> {
+    /// initial guess (before the solve) or solution (after the solve)
+    u: Vec<f64>,
+    /// outcome of the last solve
+    status: Option<Result<S, SolverError>>,
+}
+
+impl<S> BatchSolution<S> {
+    /// Constructs a new (unsolved) solution with initial guess
+    /// `initial_guess`
+    pub fn new(initial_guess: &[f64]) -> Self {
+        BatchSolution {
+            u: initial_guess.to_vec(),
+            status: None,
+        }
+    }
+
+    /// Solution of the last solve (or the initial guess, if the instance has
+    /// not been solved yet)
+    pub fn u(&self) -> &[f64] {
+        &self.u
+    }
+
+    /// Mutable access to the initial guess of the next solve (which is, by
+    /// default, the solution of the last solve)
+    pub fn u_mut(&mut self) -> &mut [f64] {
+        &mut self.u
+    }
+
+    /// Status (or error) of the last solve, or `None` if the instance has
+    /// not been solved yet
+    pub fn status(&self) -> Option<&Result<S, SolverError>> {
+        self.status.as_ref()
+    }
+}
+
+/// Solver of batches of instances of a parametric problem
+///
+/// See the [module documentation](index.html).
+pub struct BatchSolver<C, F> {
+    /// caches of the solves
+    pool: CachePool<C>,
+    /// solves an instance, given its parameter, a cache and the initial
+    /// guess
+    solver: F,
+    /// number of threads
+    num_threads: usize,
+}
+
+impl<C, F> BatchSolver<C, F> {
+    /// Constructs a new batch solver
+    ///
+    /// ## Arguments
+    ///
+    /// - `pool`: pool of the caches of the solves (e.g., from
+    ///   `CachePool::for_panoc` or `CachePool::for_alm`)
+    /// - `solver`: closure which solves the instance with parameter `p` (of
+    ///   any type `P`) using the cache `cache`, from the initial guess `u`,
+    ///   which it overwrites with the solution; it has the signature
+    ///   `Fn(&P, &mut C, &mut [f64]) -> Result<S, SolverError>`, where `S`
+    ///   is the status of the solve (e.g., `SolverStatus` or
+    ///   `AlmOptimizerStatus`)
+    ///
+    /// By default, the instances are solved sequentially on the calling
+    /// thread (see `with_num_threads`).
+    pub fn new(pool: CachePool<C>, solver: F) -> Self {
+        BatchSolver {
+            pool,
+            solver,
+            num_threads: 1,
+        }
+    }
+
+    /// Sets the number of threads on which the instances are solved
+    ///
+    /// The instances are split into contiguous chunks of (almost) equal
+    /// size, one for each thread.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `num_threads` is zero
+    pub fn with_num_threads(mut self, num_threads: usize) -> Result<Self, SolverError> {
+        ensure(num_threads > 0, "num_threads", "must be positive")?;
+        self.num_threads = num_threads;
+        Ok(self)
+    }
+
+    /// The pool of caches
+    pub fn pool(&self) -> &CachePool<C> {
+        &self.pool
+    }
+
+    /// Solves the instances with parameters `parameters`, storing the
+    /// solution of the $i$-th instance in `solutions[i]`, whose initial
+    /// guess is used
+    ///
+    /// A failing instance (e.g., whose cost returns an error) does not stop
+    /// the batch: its error is stored in its solution. The results do not
+    /// depend on the number of threads.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the lengths of
+    /// `parameters` and `solutions` differ
+    pub fn solve_batch<P, S>(
+        &self,
+        parameters: &[P],
+        solutions: &mut [BatchSolution<S>],
+    ) -> FunctionCallResult
+    where
+        P: Sync,
+        S: Send,
+        C: Send,
+        F: Fn(&P, &mut C, &mut [f64]) -> Result<S, SolverError> + Sync,
+    {
+        if parameters.len() != solutions.len() {
+            return Err(SolverError::DimensionMismatch {
+                name: "solutions",
+                expected: parameters.len(),
+                actual: solutions.len(),
+            });
+        }
+        let solve_chunk = |parameters: &[P], solutions: &mut [BatchSolution<S>]| {
+            for (p, solution) in parameters.iter().zip(solutions.iter_mut()) {
+                let status = self
+                    .pool
+                    .acquire()
+                    .and_then(|mut cache| (self.solver)(p, &mut cache, &mut solution.u));
+                solution.status = Some(status);
+            }
+        };
+        if self.num_threads == 1 || parameters.len() <= 1 {
+            solve_chunk(parameters, solutions);
+        } else {
+            let chunk_size = parameters.len().div_ceil(self.num_threads);
+            thread::scope(|scope| {
+                let workers: Vec<_> = parameters
+                    .chunks(chunk_size)
+                    .zip(solutions.chunks_mut(chunk_size))
+                    .map(|(parameters, solutions)| {
+                        let solve_chunk = &solve_chunk;
+                        scope.spawn(move || solve_chunk(parameters, solutions))
+                    })
+                    .collect();
+                for worker in workers {
+                    if let Err(panic) = worker.join() {
+                        std::panic::resume_unwind(panic);
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alm::*,
+        constraints::{Ball2, Rectangle},
+        core::{panoc::PANOCCache, SolverStatus},
+        panoc::PANOCOptimizer,
+        Optimizer, Problem,
+    };
+
+    /// Solves min (u_0 - a)^2 + (u_1 - 2a)^2 subject to |u| <= 1
+    fn solve_instance(
+        a: &f64,
+        cache: &mut PANOCCache,
+        u: &mut [f64],
+    ) -> Result<SolverStatus, SolverError> {
+        let a = *a;
+        if a.is_nan() {
+            return Err(SolverError::Cost);
+        }
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = (u[0] - a).powi(2) + (u[1] - 2.0 * a).powi(2);
+            Ok(())
+        };
+        let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad[0] = 2.0 * (u[0] - a);
+            grad[1] = 2.0 * (u[1] - 2.0 * a);
+            Ok(())
+        };
+        let bounds = Ball2::new(None, 1.0)?;
+        PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), cache).solve(u)
+    }
+
+    #[test]
+    fn t_batch_panoc() {
+        let parameters: Vec<f64> = (0..37).map(|i| i as f64 / 12.0 - 1.0).collect();
+        let solve = |num_threads| {
+            let solver =
+                BatchSolver::new(CachePool::for_panoc(2, 1e-10, 5).unwrap(), solve_instance)
+                    .with_num_threads(num_threads)
+                    .unwrap();
+            let mut solutions: Vec<_> = parameters
+                .iter()
+                .map(|_| BatchSolution::new(&[0.0; 2]))
+                .collect();
+            solver.solve_batch(&parameters, &mut solutions).unwrap();
+            // at most one cache per thread is allocated
+            assert!(solver.pool().idle() <= num_threads);
+            solutions
+        };
+        let solutions = solve(1);
+        for (&a, solution) in parameters.iter().zip(solutions.iter()) {
+            let status = solution.status().unwrap().as_ref().unwrap();
+            assert!(status.has_converged());
+            // the solution is the projection of (a, 2a) on the unit ball
+            let scale = 1.0_f64.min(1.0 / (5.0 * a * a).sqrt());
+            assert!((solution.u()[0] - scale * a).abs() < 1e-6);
+            assert!((solution.u()[1] - 2.0 * scale * a).abs() < 1e-6);
+        }
+        // the same results on threads
+        for (a, b) in solve(4).iter().zip(solutions.iter()) {
+            assert_eq!(a.u(), b.u());
+        }
+    }
+
+    #[test]
+    fn t_batch_failures_and_warm_start() {
+        let solver = BatchSolver::new(CachePool::for_panoc(2, 1e-10, 5).unwrap(), solve_instance)
+            .with_num_threads(2)
+            .unwrap();
+        let parameters = [0.1, f64::NAN, 0.2];
+        let mut solutions: Vec<_> = (0..3).map(|_| BatchSolution::new(&[0.0; 2])).collect();
+        assert!(solutions[0].status().is_none());
+        solver.solve_batch(&parameters, &mut solutions).unwrap();
+        assert!(solutions[0].status().unwrap().is_ok());
+        assert!(matches!(
+            solutions[1].status(),
+            Some(Err(SolverError::Cost))
+        ));
+        assert!(solutions[2].status().unwrap().is_ok());
+
+        // warm start from the solutions of the previous batch
+        let iterations = |solution: &BatchSolution<SolverStatus>| {
+            solution.status().unwrap().as_ref().unwrap().iterations()
+        };
+        let cold_iterations = iterations(&solutions[2]);
+        solutions[1].u_mut().copy_from_slice(&[0.0, 0.0]);
+        solver
+            .solve_batch(&[0.1, 0.3, 0.2], &mut solutions)
+            .unwrap();
+        assert!(solutions.iter().all(|s| s.status().unwrap().is_ok()));
+        assert!(iterations(&solutions[2]) < cold_iterations);
+
+        assert!(matches!(
+            solver.solve_batch(&[0.1], &mut solutions),
+            Err(SolverError::DimensionMismatch {
+                name: "solutions",
+                expected: 1,
+                actual: 3
+            })
+        ));
+        assert!(
+            BatchSolver::new(CachePool::for_panoc(2, 1e-10, 5).unwrap(), solve_instance)
+                .with_num_threads(0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn t_batch_alm() {
+        // min (u_0 - a)^2 + (u_1 - a)^2 subject to u_0 + u_1 = 1 (PM-type)
+        let solver = BatchSolver::new(
+            CachePool::for_alm(2, 1e-8, 5, 0, 1).unwrap(),
+            |a: &f64, cache: &mut AlmCache, u: &mut [f64]| {
+                let psi = |u: &[f64], xi: &[f64], c: &mut f64| -> FunctionCallResult {
+                    let f2 = u[0] + u[1] - 1.0;
+                    *c = (u[0] - a).powi(2) + (u[1] - a).powi(2) + 0.5 * xi[0] * f2 * f2;
+                    Ok(())
+                };
+                let d_psi = |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                    let f2 = u[0] + u[1] - 1.0;
+                    grad[0] = 2.0 * (u[0] - a) + xi[0] * f2;
+                    grad[1] = 2.0 * (u[1] - a) + xi[0] * f2;
+                    Ok(())
+                };
+                let f2 = |u: &[f64], f2u: &mut [f64]| -> FunctionCallResult {
+                    f2u[0] = u[0] + u[1] - 1.0;
+                    Ok(())
+                };
+                let bounds = Rectangle::new(Some(&[-5.0; 2]), Some(&[5.0; 2]))?;
+                let problem = AlmProblem::new(
+                    bounds,
+                    NO_SET,
+                    NO_SET,
+                    psi,
+                    d_psi,
+                    NO_MAPPING,
+                    Some(f2),
+                    0,
+                    1,
+                )?;
+                AlmOptimizer::new(cache, problem)
+                    .with_delta_tolerance(1e-6)?
+                    .solve(u)
+            },
+        )
+        .with_num_threads(3)
+        .unwrap();
+        let parameters = [-1.0, 0.0, 1.0, 2.0];
+        let mut solutions: Vec<_> = (0..4).map(|_| BatchSolution::new(&[0.0; 2])).collect();
+        solver.solve_batch(&parameters, &mut solutions).unwrap();
+        for solution in solutions.iter() {
+            let status = solution.status().unwrap().as_ref().unwrap();
+            assert_eq!(crate::core::ExitStatus::Converged, status.exit_status());
+            assert!((solution.u()[0] - 0.5).abs() < 1e-4);
+            assert!((solution.u()[1] - 0.5).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,533 @@
+#![deny(missing_docs)]
+//! Loader for CasADi-generated C functions
+//!
+//! This module (which requires the feature `casadi` and a Unix-like target)
+//! loads functions which have been generated by [CasADi](https://web.casadi.org)
+//! (e.g., using `Function.generate` in Python) and compiled into a shared
+//! library, so that models which are built in CasADi can be solved by OpEn
+//! directly, without generating a solver crate (see also the crate `icasadi`,
+//! which links the generated C files statically).
+//!
+//! A [`CasadiLibrary`] is opened with `dlopen` and every function is loaded
+//! by name as a [`CasadiFunction`], which evaluates the function with the
+//! CasADi calling convention and workspaces which are allocated when the
+//! function is loaded. Inputs and outputs are dense vectors (matrices are
+//! stored column by column); sparse inputs and outputs are handled
+//! internally.
+//!
+//! # Example
+//!
+//! ```python
+//! import casadi.casadi as cs
+//! u, p = cs.SX.sym("u", 5), cs.SX.sym("p", 2)
+//! phi = cs.Function("phi", [u, p], [cs.sum1((p[0] - u)**2)])
+//! grad_phi = cs.Function("grad_phi", [u, p], [cs.jacobian(phi(u, p), u)])
+//! cg = cs.CodeGenerator("model.c")
+//! cg.add(phi); cg.add(grad_phi); cg.generate()
+//! # then: cc -shared -fPIC -O2 model.c -o libmodel.so
+//! ```
+//!
+//! ```no_run
+//! use optimization_engine::{casadi::CasadiLibrary, constraints::Ball2, panoc::*, *};
+//!
+//! let library = CasadiLibrary::open("./libmodel.so")?;
+//! let (phi, grad_phi) = (library.function("phi")?, library.function("grad_phi")?);
+//! let p = [1.0, 0.5];
+//! let bounds = Ball2::new(None, 10.0)?;
+//! let problem = Problem::new(
+//!     &bounds,
+//!     |u: &[f64], grad: &mut [f64]| grad_phi.call(&[u, &p], &mut [grad]),
+//!     |u: &[f64], cost: &mut f64| phi.call_scalar(&[u, &p], cost),
+//! );
+//! let mut u = [0.0; 5];
+//! PANOCOptimizer::from_dimensions(problem, 5, 1e-6, 10)?.solve(&mut u)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`CasadiLibrary`]: struct.CasadiLibrary.html
+//! [`CasadiFunction`]: struct.CasadiFunction.html
+
+use crate::{FunctionCallResult, SolverError};
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    ffi::{CStr, CString},
+    fmt, io,
+    os::{
+        raw::{c_char, c_double, c_int, c_longlong, c_void},
+        unix::ffi::OsStrExt,
+    },
+    path::Path,
+    ptr,
+    sync::Arc,
+};
+
+/// Integer type of CasADi (`casadi_int`, which is `long long int` by default)
+type CasadiInt = c_longlong;
+
+/// Evaluation function, `int f(const casadi_real** arg, casadi_real** res,
+/// casadi_int* iw, casadi_real* w, int mem)`
+type EvalFunction = unsafe extern "C" fn(
+    arg: *mut *const c_double,
+    res: *mut *mut c_double,
+    iw: *mut CasadiInt,
+    w: *mut c_double,
+    mem: c_int,
+) -> c_int;
+
+/// Number of inputs or outputs, `casadi_int f_n_in(void)`
+type CountFunction = unsafe extern "C" fn() -> CasadiInt;
+
+/// Sparsity pattern of an input or output, `const casadi_int* f_sparsity_in(casadi_int i)`
+type SparsityFunction = unsafe extern "C" fn(i: CasadiInt) -> *const CasadiInt;
+
+/// Sizes of the workspaces, `int f_work(casadi_int* sz_arg, casadi_int*
+/// sz_res, casadi_int* sz_iw, casadi_int* sz_w)`
+type WorkFunction = unsafe extern "C" fn(
+    sz_arg: *mut CasadiInt,
+    sz_res: *mut CasadiInt,
+    sz_iw: *mut CasadiInt,
+    sz_w: *mut CasadiInt,
+) -> c_int;
+
+/// Reference counting, `void f_incref(void)` and `void f_decref(void)`
+type ReferenceFunction = unsafe extern "C" fn();
+
+/// `RTLD_NOW`, which has the same value on Linux and macOS
+const RTLD_NOW: c_int = 2;
+
+#[cfg_attr(target_os = "linux", link(name = "dl"))]
+extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    fn dlclose(handle: *mut c_void) -> c_int;
+    fn dlerror() -> *mut c_char;
+}
+
+/// Message of the last error of `dlopen` or `dlsym`
+fn last_error(default: &str) -> String {
+    // SAFETY: `dlerror` returns null or a valid C string
+    let message = unsafe { dlerror() };
+    if message.is_null() {
+        default.to_string()
+    } else {
+        // SAFETY: the string is valid until the next call to `dlerror`
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Handle of a shared library, which is closed when it is dropped
+struct Handle(*mut c_void);
+
+// SAFETY: handles of `dlopen` can be used (and closed) from any thread
+unsafe impl Send for Handle {}
+unsafe impl Sync for Handle {}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        // SAFETY: the handle was returned by `dlopen` and is closed once
+        unsafe {
+            dlclose(self.0);
+        }
+    }
+}
+
+/// Shared library with CasADi-generated functions
+///
+/// The library stays loaded as long as the library or any of its functions
+/// is alive.
+#[derive(Clone)]
+pub struct CasadiLibrary {
+    handle: Arc<Handle>,
+}
+
+impl CasadiLibrary {
+    /// Opens a shared library (e.g., `libmodel.so`)
+    ///
+    /// Mind that loading a library runs its initialisation code, so only
+    /// trusted libraries should be opened.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the library cannot be loaded
+    ///
+    pub fn open(path: impl AsRef<Path>) -> io::Result<CasadiLibrary> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        // SAFETY: `path` is a valid C string
+        let handle = unsafe { dlopen(path.as_ptr(), RTLD_NOW) };
+        if handle.is_null() {
+            return Err(io::Error::other(last_error("cannot open library")));
+        }
+        Ok(CasadiLibrary {
+            handle: Arc::new(Handle(handle)),
+        })
+    }
+
+    /// Address of a symbol, or `None` if the library does not define it
+    fn symbol(&self, name: &str) -> Option<*mut c_void> {
+        let name = CString::new(name).ok()?;
+        // SAFETY: the handle is open and `name` is a valid C string
+        let address = unsafe { dlsym(self.handle.0, name.as_ptr()) };
+        (!address.is_null()).then_some(address)
+    }
+
+    fn required_symbol(&self, name: &str) -> io::Result<*mut c_void> {
+        self.symbol(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("symbol `{}` not found", name),
+            )
+        })
+    }
+
+    /// Loads the function `name`
+    ///
+    /// The library must define the symbols which CasADi generates for the
+    /// function: `name`, `name_n_in`, `name_n_out`, `name_sparsity_in`,
+    /// `name_sparsity_out` and `name_work` (and, optionally, `name_incref`
+    /// and `name_decref`).
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if a symbol is missing, if the function reports an
+    /// error or if the sparsity patterns are invalid
+    ///
+    pub fn function(&self, name: &str) -> io::Result<CasadiFunction> {
+        let symbol = |suffix: &str| self.required_symbol(&format!("{}{}", name, suffix));
+        // SAFETY: the symbols are functions which CasADi generates with these
+        // signatures
+        let (eval, n_in, n_out, sparsity_in, sparsity_out, work) = unsafe {
+            (
+                std::mem::transmute::<*mut c_void, EvalFunction>(symbol("")?),
+                std::mem::transmute::<*mut c_void, CountFunction>(symbol("_n_in")?),
+                std::mem::transmute::<*mut c_void, CountFunction>(symbol("_n_out")?),
+                std::mem::transmute::<*mut c_void, SparsityFunction>(symbol("_sparsity_in")?),
+                std::mem::transmute::<*mut c_void, SparsityFunction>(symbol("_sparsity_out")?),
+                std::mem::transmute::<*mut c_void, WorkFunction>(symbol("_work")?),
+            )
+        };
+        let reference_function = |suffix: &str| {
+            self.symbol(&format!("{}{}", name, suffix))
+                // SAFETY: see above
+                .map(|f| unsafe { std::mem::transmute::<*mut c_void, ReferenceFunction>(f) })
+        };
+        let (incref, decref) = (reference_function("_incref"), reference_function("_decref"));
+
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("function `{}`: {}", name, message),
+            )
+        };
+        // SAFETY: the functions take no pointers (or write to valid integers)
+        let (n_in, n_out) = unsafe { (n_in(), n_out()) };
+        let (mut sz_arg, mut sz_res, mut sz_iw, mut sz_w) = (0, 0, 0, 0);
+        if unsafe { work(&mut sz_arg, &mut sz_res, &mut sz_iw, &mut sz_w) } != 0 {
+            return Err(invalid("cannot determine the sizes of the workspaces"));
+        }
+        let size = |n: CasadiInt| usize::try_from(n).map_err(|_| invalid("negative size"));
+        let (n_in, n_out) = (size(n_in)?, size(n_out)?);
+        let parse = |f: SparsityFunction, i: usize| {
+            // SAFETY: CasADi returns a sparsity pattern for every input and
+            // output (or null)
+            unsafe { Sparsity::from_raw(f(i as CasadiInt)) }
+                .ok_or_else(|| invalid("invalid sparsity pattern"))
+        };
+        let sparsity_in = (0..n_in)
+            .map(|i| parse(sparsity_in, i))
+            .collect::<io::Result<Vec<_>>>()?;
+        let sparsity_out = (0..n_out)
+            .map(|i| parse(sparsity_out, i))
+            .collect::<io::Result<Vec<_>>>()?;
+        let workspace = Workspace {
+            arg: vec![ptr::null(); size(sz_arg)?.max(n_in)],
+            res: vec![ptr::null_mut(); size(sz_res)?.max(n_out)],
+            iw: vec![0; size(sz_iw)?],
+            w: vec![0.0; size(sz_w)?],
+            inputs: sparsity_in.iter().map(|s| vec![0.0; s.nnz()]).collect(),
+            outputs: sparsity_out.iter().map(|s| vec![0.0; s.nnz()]).collect(),
+        };
+        if let Some(incref) = incref {
+            // SAFETY: see above
+            unsafe { incref() };
+        }
+        Ok(CasadiFunction {
+            name: name.to_string(),
+            eval,
+            decref,
+            sparsity_in,
+            sparsity_out,
+            workspace: RefCell::new(workspace),
+            _library: self.clone(),
+        })
+    }
+}
+
+impl fmt::Debug for CasadiLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CasadiLibrary").finish_non_exhaustive()
+    }
+}
+
+/// Sparsity pattern in compressed column storage
+#[derive(Debug, Clone, PartialEq)]
+struct Sparsity {
+    nrow: usize,
+    ncol: usize,
+    /// row indices of the nonzeros, column by column, or `None` if the
+    /// pattern is dense
+    nonzeros: Option<Vec<usize>>,
+}
+
+impl Sparsity {
+    /// Parses a sparsity pattern, `[nrow, ncol, colind[0..=ncol], row[0..nnz]]`,
+    /// or `[nrow, ncol, 1]` for dense patterns
+    ///
+    /// # Safety
+    ///
+    /// `pattern` must be null or point to a valid sparsity pattern
+    unsafe fn from_raw(pattern: *const CasadiInt) -> Option<Sparsity> {
+        if pattern.is_null() {
+            return None;
+        }
+        let index = |i: usize| usize::try_from(*pattern.add(i)).ok();
+        let (nrow, ncol) = (index(0)?, index(1)?);
+        // the first column index is zero, unless the pattern is dense
+        if index(2)? == 1 {
+            return Some(Sparsity {
+                nrow,
+                ncol,
+                nonzeros: None,
+            });
+        }
+        let colind = (0..=ncol)
+            .map(|c| index(2 + c))
+            .collect::<Option<Vec<usize>>>()?;
+        let mut nonzeros = Vec::with_capacity(colind[ncol]);
+        for c in 0..ncol {
+            for k in colind[c]..colind[c + 1] {
+                let row = index(3 + ncol + k).filter(|&row| row < nrow)?;
+                nonzeros.push(row + c * nrow);
+            }
+        }
+        let dense = nonzeros.len() == nrow * ncol;
+        Some(Sparsity {
+            nrow,
+            ncol,
+            nonzeros: (!dense).then_some(nonzeros),
+        })
+    }
+
+    /// Number of elements of the dense matrix
+    fn numel(&self) -> usize {
+        self.nrow * self.ncol
+    }
+
+    /// Number of (structural) nonzeros
+    fn nnz(&self) -> usize {
+        self.nonzeros.as_ref().map_or(self.numel(), Vec::len)
+    }
+}
+
+/// Workspaces of a function
+struct Workspace {
+    arg: Vec<*const c_double>,
+    res: Vec<*mut c_double>,
+    iw: Vec<CasadiInt>,
+    w: Vec<c_double>,
+    /// nonzeros of the inputs
+    inputs: Vec<Vec<f64>>,
+    /// nonzeros of the outputs
+    outputs: Vec<Vec<f64>>,
+}
+
+/// Function which has been loaded from a [`CasadiLibrary`](struct.CasadiLibrary.html)
+///
+/// The function is evaluated with `call` (or `call_scalar`) on dense inputs
+/// and outputs; no memory is allocated after the function has been loaded.
+pub struct CasadiFunction {
+    name: String,
+    eval: EvalFunction,
+    decref: Option<ReferenceFunction>,
+    sparsity_in: Vec<Sparsity>,
+    sparsity_out: Vec<Sparsity>,
+    workspace: RefCell<Workspace>,
+    /// keeps the library loaded
+    _library: CasadiLibrary,
+}
+
+// SAFETY: the raw pointers of the workspace only point to buffers of the
+// workspace (during a call), so the function can be moved to another thread
+unsafe impl Send for CasadiFunction {}
+
+impl CasadiFunction {
+    /// Name of the function
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of inputs
+    pub fn num_inputs(&self) -> usize {
+        self.sparsity_in.len()
+    }
+
+    /// Number of outputs
+    pub fn num_outputs(&self) -> usize {
+        self.sparsity_out.len()
+    }
+
+    /// Dimension (number of rows and columns) of input `i`
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if `i` is not smaller than `num_inputs()`
+    ///
+    pub fn input_dimension(&self, i: usize) -> (usize, usize) {
+        let sparsity = &self.sparsity_in[i];
+        (sparsity.nrow, sparsity.ncol)
+    }
+
+    /// Dimension (number of rows and columns) of output `i`
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if `i` is not smaller than `num_outputs()`
+    ///
+    pub fn output_dimension(&self, i: usize) -> (usize, usize) {
+        let sparsity = &self.sparsity_out[i];
+        (sparsity.nrow, sparsity.ncol)
+    }
+
+    /// Evaluates the function
+    ///
+    /// ## Arguments
+    ///
+    /// - `inputs`: inputs of the function (matrices are stored column by
+    ///   column)
+    /// - `outputs`: on exit, the outputs of the function (matrices are
+    ///   stored column by column)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the number or the sizes
+    /// of the inputs and outputs are wrong, or `SolverError::Cost` if the
+    /// function returns a nonzero status
+    ///
+    pub fn call(&self, inputs: &[&[f64]], outputs: &mut [&mut [f64]]) -> FunctionCallResult {
+        check_dimension("inputs", self.num_inputs(), inputs.len())?;
+        check_dimension("outputs", self.num_outputs(), outputs.len())?;
+        for (input, sparsity) in inputs.iter().zip(&self.sparsity_in) {
+            check_dimension("input", sparsity.numel(), input.len())?;
+        }
+        for (output, sparsity) in outputs.iter().zip(&self.sparsity_out) {
+            check_dimension("output", sparsity.numel(), output.len())?;
+        }
+
+        let mut workspace = self.workspace.borrow_mut();
+        let workspace = &mut *workspace;
+        for ((buffer, input), sparsity) in workspace
+            .inputs
+            .iter_mut()
+            .zip(inputs)
+            .zip(&self.sparsity_in)
+        {
+            match &sparsity.nonzeros {
+                None => buffer.copy_from_slice(input),
+                Some(nonzeros) => buffer
+                    .iter_mut()
+                    .zip(nonzeros)
+                    .for_each(|(x, &k)| *x = input[k]),
+            }
+        }
+        for (arg, buffer) in workspace.arg.iter_mut().zip(&workspace.inputs) {
+            *arg = buffer.as_ptr();
+        }
+        for (res, buffer) in workspace.res.iter_mut().zip(&mut workspace.outputs) {
+            *res = buffer.as_mut_ptr();
+        }
+        // SAFETY: the workspaces have the sizes which the function requires
+        // and the buffers have the sizes of the sparsity patterns
+        let status = unsafe {
+            (self.eval)(
+                workspace.arg.as_mut_ptr(),
+                workspace.res.as_mut_ptr(),
+                workspace.iw.as_mut_ptr(),
+                workspace.w.as_mut_ptr(),
+                0,
+            )
+        };
+        if status != 0 {
+            return Err(SolverError::Cost);
+        }
+
+        for ((output, buffer), sparsity) in outputs
+            .iter_mut()
+            .zip(&workspace.outputs)
+            .zip(&self.sparsity_out)
+        {
+            match &sparsity.nonzeros {
+                None => output.copy_from_slice(buffer),
+                Some(nonzeros) => {
+                    output.fill(0.0);
+                    nonzeros
+                        .iter()
+                        .zip(buffer)
+                        .for_each(|(&k, &x)| output[k] = x);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates a function with a single, scalar output (e.g., a cost
+    /// function)
+    ///
+    /// ## Errors
+    ///
+    /// See `call`
+    ///
+    pub fn call_scalar(&self, inputs: &[&[f64]], output: &mut f64) -> FunctionCallResult {
+        self.call(inputs, &mut [std::slice::from_mut(output)])
+    }
+}
+
+impl Drop for CasadiFunction {
+    fn drop(&mut self) {
+        if let Some(decref) = self.decref {
+            // SAFETY: the library is still loaded
+            unsafe { decref() };
+        }
+    }
+}
+
+impl fmt::Debug for CasadiFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CasadiFunction")
+            .field("name", &self.name)
+            .field("num_inputs", &self.num_inputs())
+            .field("num_outputs", &self.num_outputs())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Checks that the number of elements is the expected one
+fn check_dimension(name: &'static str, expected: usize, actual: usize) -> FunctionCallResult {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(SolverError::DimensionMismatch {
+            name,
+            expected,
+            actual,
+        })
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,110 @@
+/*
+ * Functions in the format of CasADi-generated C code (used by the tests of
+ * the module `casadi`)
+ *
+ * - rosenbrock(u[2], p[2]) = (p0 - u0)^2 + p1 (u1 - u0^2)^2
+ * - rosenbrock_grad(u[2], p[2]) = gradient of rosenbrock (compact dense
+ *   sparsity pattern)
+ * - sparse_mapping(u[2]) = (u0 u1, 0, sin(u0)) (3x1, structural zero at
+ *   row 1); returns 1 if u0 is negative
+ */
+#include <math.h>
+
+#define casadi_real double
+#define casadi_int long long int
+
+static const casadi_int dense_2[6] = {2, 1, 0, 2, 0, 1};
+static const casadi_int scalar[5] = {1, 1, 0, 1, 0};
+static const casadi_int compact_dense_2[3] = {2, 1, 1};
+static const casadi_int sparse_3[6] = {3, 1, 0, 2, 0, 2};
+
+static int references = 0;
+
+int rosenbrock(const casadi_real **arg, casadi_real **res, casadi_int *iw,
+               casadi_real *w, int mem) {
+  const casadi_real *u = arg[0], *p = arg[1];
+  w[0] = u[1] - u[0] * u[0];
+  res[0][0] = (p[0] - u[0]) * (p[0] - u[0]) + p[1] * w[0] * w[0];
+  return 0;
+}
+
+casadi_int rosenbrock_n_in(void) { return 2; }
+casadi_int rosenbrock_n_out(void) { return 1; }
+
+const casadi_int *rosenbrock_sparsity_in(casadi_int i) {
+  return i < 2 ? dense_2 : 0;
+}
+
+const casadi_int *rosenbrock_sparsity_out(casadi_int i) {
+  return i == 0 ? scalar : 0;
+}
+
+int rosenbrock_work(casadi_int *sz_arg, casadi_int *sz_res, casadi_int *sz_iw,
+                    casadi_int *sz_w) {
+  *sz_arg = 2;
+  *sz_res = 1;
+  *sz_iw = 0;
+  *sz_w = 1;
+  return 0;
+}
+
+void rosenbrock_incref(void) { references++; }
+void rosenbrock_decref(void) { references--; }
+
+int rosenbrock_grad(const casadi_real **arg, casadi_real **res, casadi_int *iw,
+                    casadi_real *w, int mem) {
+  const casadi_real *u = arg[0], *p = arg[1];
+  casadi_real r = u[1] - u[0] * u[0];
+  res[0][0] = 2.0 * (u[0] - p[0]) - 4.0 * p[1] * u[0] * r;
+  res[0][1] = 2.0 * p[1] * r;
+  return 0;
+}
+
+casadi_int rosenbrock_grad_n_in(void) { return 2; }
+casadi_int rosenbrock_grad_n_out(void) { return 1; }
+
+const casadi_int *rosenbrock_grad_sparsity_in(casadi_int i) {
+  return i < 2 ? compact_dense_2 : 0;
+}
+
+const casadi_int *rosenbrock_grad_sparsity_out(casadi_int i) {
+  return i == 0 ? compact_dense_2 : 0;
+}
+
+int rosenbrock_grad_work(casadi_int *sz_arg, casadi_int *sz_res,
+                         casadi_int *sz_iw, casadi_int *sz_w) {
+  *sz_arg = 2;
+  *sz_res = 1;
+  *sz_iw = 0;
+  *sz_w = 0;
+  return 0;
+}
+
+int sparse_mapping(const casadi_real **arg, casadi_real **res, casadi_int *iw,
+                   casadi_real *w, int mem) {
+  const casadi_real *u = arg[0];
+  if (u[0] < 0.0) return 1;
+  res[0][0] = u[0] * u[1];
+  res[0][1] = sin(u[0]);
+  return 0;
+}
+
+casadi_int sparse_mapping_n_in(void) { return 1; }
+casadi_int sparse_mapping_n_out(void) { return 1; }
+
+const casadi_int *sparse_mapping_sparsity_in(casadi_int i) {
+  return i == 0 ? dense_2 : 0;
+}
+
+const casadi_int *sparse_mapping_sparsity_out(casadi_int i) {
+  return i == 0 ? sparse_3 : 0;
+}
+
+int sparse_mapping_work(casadi_int *sz_arg, casadi_int *sz_res,
+                        casadi_int *sz_iw, casadi_int *sz_w) {
+  *sz_arg = 1;
+  *sz_res = 1;
+  *sz_iw = 0;
+  *sz_w = 0;
+  return 0;
+}
@@ -0,0 +1,114 @@
+use super::*;
+use crate::{constraints::Ball2, core::panoc::PANOCOptimizer, Optimizer, Problem};
+use std::{path::PathBuf, process::Command, sync::OnceLock};
+
+/// Compiles `test_functions.c` into a shared library (once), or returns
+/// `None` if there is no C compiler
+fn test_library() -> Option<&'static PathBuf> {
+    static LIBRARY: OnceLock<Option<PathBuf>> = OnceLock::new();
+    LIBRARY
+        .get_or_init(|| {
+            let dir = std::env::temp_dir().join(format!("open_casadi_{}", std::process::id()));
+            std::fs::create_dir_all(&dir).ok()?;
+            let source = dir.join("test_functions.c");
+            let library = dir.join("libtest_functions.so");
+            std::fs::write(&source, include_str!("test_functions.c")).ok()?;
+            let status = Command::new("cc")
+                .args(["-shared", "-fPIC", "-o"])
+                .arg(&library)
+                .arg(&source)
+                .arg("-lm")
+                .status()
+                .ok()?;
+            status.success().then_some(library)
+        })
+        .as_ref()
+}
+
+macro_rules! open_test_library {
+    () => {
+        match test_library() {
+            Some(path) => CasadiLibrary::open(path).unwrap(),
+            None => {
+                eprintln!("skipped: no C compiler");
+                return;
+            }
+        }
+    };
+}
+
+#[test]
+fn t_casadi_call() {
+    let library = open_test_library!();
+    let rosenbrock = library.function("rosenbrock").unwrap();
+    assert_eq!("rosenbrock", rosenbrock.name());
+    assert_eq!((2, 1), (rosenbrock.num_inputs(), rosenbrock.num_outputs()));
+    assert_eq!((2, 1), rosenbrock.input_dimension(1));
+    assert_eq!((1, 1), rosenbrock.output_dimension(0));
+    let (u, p) = ([0.5, -1.0], [1.0, 10.0]);
+    let mut cost = 0.0;
+    rosenbrock.call_scalar(&[&u, &p], &mut cost).unwrap();
+    assert_eq!(crate::mocks::rosenbrock_cost(1.0, 10.0, &u), cost);
+
+    let grad_function = library.function("rosenbrock_grad").unwrap();
+    let (mut grad, mut grad_expected) = ([0.0; 2], [0.0; 2]);
+    grad_function.call(&[&u, &p], &mut [&mut grad]).unwrap();
+    crate::mocks::rosenbrock_grad(1.0, 10.0, &u, &mut grad_expected);
+    assert_eq!(grad_expected, grad);
+}
+
+#[test]
+fn t_casadi_sparse_output() {
+    let library = open_test_library!();
+    let mapping = library.function("sparse_mapping").unwrap();
+    let mut y = [f64::NAN; 3];
+    mapping.call(&[&[2.0, 3.0]], &mut [&mut y]).unwrap();
+    assert_eq!([6.0, 0.0, 2.0_f64.sin()], y);
+    assert_eq!(
+        Err(SolverError::Cost),
+        mapping.call(&[&[-2.0, 3.0]], &mut [&mut y])
+    );
+    assert!(matches!(
+        mapping.call(&[&[2.0]], &mut [&mut y]),
+        Err(SolverError::DimensionMismatch {
+            name: "input",
+            expected: 2,
+            actual: 1,
+        })
+    ));
+    assert!(mapping.call(&[], &mut [&mut y]).is_err());
+}
+
+#[test]
+fn t_casadi_missing() {
+    let library = open_test_library!();
+    let error = library.function("no_such_function").unwrap_err();
+    assert_eq!(io::ErrorKind::NotFound, error.kind());
+    assert!(CasadiLibrary::open("/no/such/library.so").is_err());
+}
+
+#[test]
+fn t_casadi_panoc() {
+    let library = open_test_library!();
+    let (cost, grad) = (
+        library.function("rosenbrock").unwrap(),
+        library.function("rosenbrock_grad").unwrap(),
+    );
+    drop(library);
+    let p = [0.5, 20.0];
+    let bounds = Ball2::new(None, 2.0).unwrap();
+    let problem = Problem::new(
+        &bounds,
+        |u: &[f64], g: &mut [f64]| grad.call(&[u, &p], &mut [g]),
+        |u: &[f64], c: &mut f64| cost.call_scalar(&[u, &p], c),
+    );
+    let mut u = [0.0; 2];
+    let status = PANOCOptimizer::from_dimensions(problem, 2, 1e-10, 5)
+        .unwrap()
+        .with_max_iter(1000)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!((u[0] - 0.5).abs() < 1e-6 && (u[1] - 0.25).abs() < 1e-6);
+}
@@ -0,0 +1,826 @@
+#![deny(missing_docs)]
+//! Generation of standalone solver crates
+//!
+//! This module (which requires the feature `codegen`) emits the source code
+//! of a self-contained Rust crate which solves a given parametric ALM/PM
+//! problem. The dimensions, the constraints and the solver settings are fixed
+//! at generation time and are embedded in the generated code as constants,
+//! so the generated crate is small and easy to audit, and can be deployed,
+//! e.g., on embedded targets. The generated crate depends only on OpEn
+//! (without any optional features).
+//!
+//! The generated crate consists of
+//!
+//! | File             | Contents                                                |
+//! |------------------|---------------------------------------------------------|
+//! | `Cargo.toml`     | manifest                                              |
+//! | `src/lib.rs`     | constants, `initialize_solver` and `solve`             |
+//! | `src/problem.rs` | cost function, its gradient and the mappings $F_1$, $F_2$ |
+//!
+//! The functions in `src/problem.rs` are provided by the user (see
+//! `CodeGenerator::with_problem_source`); otherwise, a skeleton is generated
+//! whose functions return an error and which is to be completed by hand.
+//! These functions have the signatures
+//!
+//! ```ignore
+//! pub fn cost(u: &[f64], xi: &[f64], p: &[f64], cost: &mut f64) -> FunctionCallResult
+//! pub fn gradient(u: &[f64], xi: &[f64], p: &[f64], grad: &mut [f64]) -> FunctionCallResult
+//! pub fn mapping_f1(u: &[f64], p: &[f64], f1u: &mut [f64]) -> FunctionCallResult // if n1 > 0
+//! pub fn mapping_f2(u: &[f64], p: &[f64], f2u: &mut [f64]) -> FunctionCallResult // if n2 > 0
+//! ```
+//!
+//! where `xi` is as in [`AlmProblem`](../alm/struct.AlmProblem.html) and `p`
+//! is the parameter.
+//!
+//! With move blocking (see `CodeGenerator::with_move_blocking`), the
+//! decision variables of the generated solver are the control actions of the
+//! blocks, whereas the functions in `src/problem.rs` are defined on the full
+//! sequence of control actions (of dimension `NUM_CONTROLS`); the generated
+//! crate maps between the two with a [`MoveBlocking`](../mpc/struct.MoveBlocking.html).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use optimization_engine::{alm::AlmConfig, codegen::*};
+//!
+//! let generated = CodeGenerator::new("rocket_solver", 2, 1)
+//!     .with_constraints(SetDescription::Ball2 { center: None, radius: 1.0 })
+//!     .with_alm_constraints(1, SetDescription::Zero)
+//!     .with_config(AlmConfig { max_outer_iterations: 20, ..AlmConfig::default() })
+//!     .generate()?;
+//! generated.write_to("rocket_solver").expect("cannot write crate");
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+use crate::{
+    alm::{AlmCache, AlmConfig, AlmOptimizer, AlmProblem, NO_MAPPING, NO_SET},
+    constraints::NoConstraints,
+    core::panoc::PANOCCache,
+    mpc::MoveBlocking,
+    FunctionCallResult, SolverError,
+};
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Default memory of the L-BFGS buffer of the generated solver
+const DEFAULT_LBFGS_MEMORY: usize = 10;
+
+/// Radius of the set $Y$ of Lagrange multipliers in the generated solver
+const LAGRANGE_SET_RADIUS: f64 = 1e12;
+
+/// Description of a set of constraints
+#[derive(Clone, Debug, PartialEq)]
+pub enum SetDescription {
+    /// No constraints (the whole space)
+    NoConstraints,
+    /// The set $\\{0\\}$
+    Zero,
+    /// A rectangle (see `constraints::Rectangle`) with bounds `min` and `max`,
+    /// either of which may be `None`, but not both
+    Rectangle {
+        /// lower bound
+        min: Option<Vec<f64>>,
+        /// upper bound
+        max: Option<Vec<f64>>,
+    },
+    /// A Euclidean ball (see `constraints::Ball2`)
+    Ball2 {
+        /// center (the origin if `None`)
+        center: Option<Vec<f64>>,
+        /// radius
+        radius: f64,
+    },
+    /// A ball of the infinity norm (see `constraints::BallInf`)
+    BallInf {
+        /// center (the origin if `None`)
+        center: Option<Vec<f64>>,
+        /// radius
+        radius: f64,
+    },
+}
+
+impl SetDescription {
+    /// Checks that the vectors of the set have length `dimension` and that
+    /// all values are valid
+    fn validate(&self, dimension: usize) -> FunctionCallResult {
+        let check_vector = |name: &'static str, x: &Option<Vec<f64>>| -> FunctionCallResult {
+            match x {
+                Some(x) if x.len() != dimension => Err(SolverError::DimensionMismatch {
+                    name,
+                    expected: dimension,
+                    actual: x.len(),
+                }),
+                Some(x) if x.iter().any(|xi| xi.is_nan()) => Err(SolverError::InvalidParameter {
+                    name,
+                    reason: "must not contain NaN",
+                }),
+                _ => Ok(()),
+            }
+        };
+        match self {
+            SetDescription::NoConstraints | SetDescription::Zero => Ok(()),
+            SetDescription::Rectangle { min, max } => {
+                crate::ensure(
+                    min.is_some() || max.is_some(),
+                    "min",
+                    "at least one of min and max must be provided",
+                )?;
+                check_vector("min", min)?;
+                check_vector("max", max)?;
+                if let (Some(min), Some(max)) = (min, max) {
+                    crate::ensure(
+                        min.iter().zip(max.iter()).all(|(a, b)| a <= b),
+                        "max",
+                        "must not be smaller than min",
+                    )?;
+                }
+                Ok(())
+            }
+            SetDescription::Ball2 { center, radius }
+            | SetDescription::BallInf { center, radius } => {
+                crate::ensure(
+                    *radius > 0.0 && radius.is_finite(),
+                    "radius",
+                    "must be positive and finite",
+                )?;
+                check_vector("center", center)
+            }
+        }
+    }
+
+    /// Emits the declarations of the static data of the set (whose names
+    /// start with `prefix` and whose length is the constant `size`) and
+    /// returns the expression which constructs the set
+    fn render(&self, prefix: &str, size: &str, code: &mut String) -> String {
+        let mut declare = |suffix: &str, x: &Option<Vec<f64>>| match x {
+            Some(x) => {
+                let _ = writeln!(
+                    code,
+                    "static {}_{}: [f64; {}] = {};",
+                    prefix,
+                    suffix,
+                    size,
+                    vector_literal(x)
+                );
+                format!("Some(&{}_{})", prefix, suffix)
+            }
+            None => "None".to_string(),
+        };
+        match self {
+            SetDescription::NoConstraints => "NoConstraints::new()".to_string(),
+            SetDescription::Zero => "Zero::new()".to_string(),
+            SetDescription::Rectangle { min, max } => {
+                let min = declare("MIN", min);
+                let max = declare("MAX", max);
+                format!("Rectangle::new({}, {})?", min, max)
+            }
+            SetDescription::Ball2 { center, radius } => format!(
+                "Ball2::new({}, {})?",
+                declare("CENTER", center),
+                float_literal(*radius)
+            ),
+            SetDescription::BallInf { center, radius } => format!(
+                "BallInf::new({}, {})?",
+                declare("CENTER", center),
+                float_literal(*radius)
+            ),
+        }
+    }
+}
+
+/// Rust literal of a float
+fn float_literal(x: f64) -> String {
+    if x == f64::INFINITY {
+        "f64::INFINITY".to_string()
+    } else if x == f64::NEG_INFINITY {
+        "f64::NEG_INFINITY".to_string()
+    } else {
+        format!("{:?}", x)
+    }
+}
+
+/// Rust literal of an array of floats
+fn vector_literal(x: &[f64]) -> String {
+    let values: Vec<String> = x.iter().map(|&xi| float_literal(xi)).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Generator of standalone solver crates
+///
+/// The problem is described by its dimensions, the set of constraints $U$,
+/// the ALM-type constraints $F_1(u) \in C$ and the number of PM-type
+/// constraints $F_2(u) = 0$ (see [`AlmProblem`](../alm/struct.AlmProblem.html)).
+/// The settings of the generated solver are those of an
+/// [`AlmConfig`](../alm/struct.AlmConfig.html); its initial Lagrange
+/// multipliers and initial penalty are ignored, since these are arguments of
+/// the generated function `solve`.
+///
+#[derive(Clone, Debug)]
+pub struct CodeGenerator {
+    name: String,
+    num_decision_variables: usize,
+    num_parameters: usize,
+    constraints: SetDescription,
+    n1: usize,
+    set_c: Option<SetDescription>,
+    n2: usize,
+    config: AlmConfig,
+    lbfgs_memory: usize,
+    dependency: String,
+    problem_source: Option<String>,
+    move_blocking: Option<MoveBlocking>,
+}
+
+impl CodeGenerator {
+    /// Constructs a new code generator
+    ///
+    /// # Arguments
+    ///
+    /// - `name`: name of the generated crate
+    /// - `num_decision_variables`: number of decision variables
+    /// - `num_parameters`: number of parameters
+    ///
+    /// By default, there are no constraints and the solver uses the default
+    /// settings of `AlmOptimizer` and an L-BFGS memory of 10.
+    pub fn new(name: &str, num_decision_variables: usize, num_parameters: usize) -> Self {
+        CodeGenerator {
+            name: name.to_string(),
+            num_decision_variables,
+            num_parameters,
+            constraints: SetDescription::NoConstraints,
+            n1: 0,
+            set_c: None,
+            n2: 0,
+            config: AlmConfig::default(),
+            lbfgs_memory: DEFAULT_LBFGS_MEMORY,
+            dependency: format!(
+                "{{ version = \"={}\", default-features = false }}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            problem_source: None,
+            move_blocking: None,
+        }
+    }
+
+    /// Sets the set of constraints, $U$
+    pub fn with_constraints(mut self, constraints: SetDescription) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// Sets the ALM-type constraints, $F_1(u) \in C$, where $F_1$ has
+    /// dimension `n1`; the set $Y$ of Lagrange multipliers is a ball of the
+    /// infinity norm with radius $10^{12}$
+    pub fn with_alm_constraints(mut self, n1: usize, set_c: SetDescription) -> Self {
+        self.n1 = n1;
+        self.set_c = Some(set_c);
+        self
+    }
+
+    /// Sets the number of PM-type constraints, $F_2(u) = 0$
+    pub fn with_penalty_constraints(mut self, n2: usize) -> Self {
+        self.n2 = n2;
+        self
+    }
+
+    /// Sets the settings of the solver
+    pub fn with_config(mut self, config: AlmConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the memory of the L-BFGS buffer of the solver
+    pub fn with_lbfgs_memory(mut self, lbfgs_memory: usize) -> Self {
+        self.lbfgs_memory = lbfgs_memory;
+        self
+    }
+
+    /// Sets the dependency specification of OpEn in the generated `Cargo.toml`
+    ///
+    /// The default is the current version of OpEn without default features;
+    /// a path (e.g., `{ path = "../optimization-engine" }`) can be used for
+    /// vendored sources
+    pub fn with_dependency(mut self, dependency: &str) -> Self {
+        self.dependency = dependency.to_string();
+        self
+    }
+
+    /// Sets the source code of `src/problem.rs` (see the module documentation)
+    pub fn with_problem_source(mut self, problem_source: &str) -> Self {
+        self.problem_source = Some(problem_source.to_string());
+        self
+    }
+
+    /// Sets a move-blocking pattern (see the module documentation)
+    ///
+    /// The number of decision variables must be
+    /// `move_blocking.reduced_dimension()`, and the constraints $U$ are
+    /// imposed on the control actions of the blocks
+    pub fn with_move_blocking(mut self, move_blocking: MoveBlocking) -> Self {
+        self.move_blocking = Some(move_blocking);
+        self
+    }
+
+    /// Generates the crate
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the name of the crate is not
+    /// a valid identifier, if the number of decision variables is zero, if
+    /// `n1` is zero while ALM-type constraints are given, if a set contains
+    /// invalid values or if the settings are invalid (see
+    /// `AlmOptimizer::with_config`), and `SolverError::DimensionMismatch` if
+    /// the dimensions of the sets are wrong or if the number of decision
+    /// variables does not match the move-blocking pattern
+    pub fn generate(&self) -> Result<GeneratedCrate, SolverError> {
+        self.validate()?;
+        let mut files = vec![
+            (PathBuf::from("Cargo.toml"), self.cargo_toml()),
+            (PathBuf::from("src").join("lib.rs"), self.lib_rs()),
+        ];
+        let problem_source = match &self.problem_source {
+            Some(problem_source) => problem_source.clone(),
+            None => self.problem_rs_skeleton(),
+        };
+        files.push((PathBuf::from("src").join("problem.rs"), problem_source));
+        Ok(GeneratedCrate { files })
+    }
+
+    fn validate(&self) -> FunctionCallResult {
+        crate::ensure(
+            self.name
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic())
+                && self
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            "name",
+            "must start with a letter and contain only letters, digits and underscores",
+        )?;
+        crate::ensure(
+            self.num_decision_variables > 0,
+            "num_decision_variables",
+            "must be positive",
+        )?;
+        crate::ensure(
+            self.set_c.is_some() == (self.n1 > 0),
+            "n1",
+            "must be positive iff ALM-type constraints are given",
+        )?;
+        if let Some(move_blocking) = &self.move_blocking {
+            if move_blocking.reduced_dimension() != self.num_decision_variables {
+                return Err(SolverError::DimensionMismatch {
+                    name: "num_decision_variables",
+                    expected: move_blocking.reduced_dimension(),
+                    actual: self.num_decision_variables,
+                });
+            }
+        }
+        self.constraints.validate(self.num_decision_variables)?;
+        if let Some(set_c) = &self.set_c {
+            set_c.validate(self.n1)?;
+        }
+        crate::ensure(self.lbfgs_memory > 0, "lbfgs_memory", "must be positive")?;
+        // the settings are validated by applying them to a dummy optimizer
+        let config = AlmConfig {
+            initial_lagrange_multipliers: None,
+            initial_penalty: None,
+            ..self.config.clone()
+        };
+        let mut alm_cache = AlmCache::new(
+            PANOCCache::new(1, config.epsilon_tolerance, self.lbfgs_memory)?,
+            0,
+            0,
+        );
+        let alm_problem = AlmProblem::new(
+            NoConstraints::new(),
+            NO_SET,
+            NO_SET,
+            |_u: &[f64], _xi: &[f64], _c: &mut f64| -> FunctionCallResult { Ok(()) },
+            |_u: &[f64], _xi: &[f64], _g: &mut [f64]| -> FunctionCallResult { Ok(()) },
+            NO_MAPPING,
+            NO_MAPPING,
+            0,
+            0,
+        )?;
+        AlmOptimizer::new(&mut alm_cache, alm_problem).with_config(&config)?;
+        Ok(())
+    }
+
+    fn cargo_toml(&self) -> String {
+        format!(
+            "[package]\n\
+             name = \"{name}\"\n\
+             version = \"0.1.0\"\n\
+             edition = \"2018\"\n\
+             publish = false\n\
+             \n\
+             [dependencies]\n\
+             optimization_engine = {dependency}\n",
+            name = self.name,
+            dependency = self.dependency
+        )
+    }
+
+    fn lib_rs(&self) -> String {
+        let config = &self.config;
+        let mut code = String::new();
+        let _ = writeln!(
+            code,
+            "//! Solver `{}`\n\
+             //!\n\
+             //! This crate was generated by `optimization_engine::codegen` (OpEn {});\n\
+             //! the dimensions, constraints and settings are fixed.\n\
+             //!\n\
+             #![forbid(unsafe_code)]\n\
+             \n\
+             pub mod problem;\n\
+             \n\
+             pub use optimization_engine::{{alm::{{AlmCache, AlmOptimizerStatus}}, SolverError}};\n\
+             use optimization_engine::{{alm::*, constraints::*, core::panoc::PANOCCache, FunctionCallResult}};\n",
+            self.name,
+            env!("CARGO_PKG_VERSION")
+        );
+
+        // dimensions
+        let _ = writeln!(code, "/// Number of decision variables");
+        let _ = writeln!(
+            code,
+            "pub const NUM_DECISION_VARIABLES: usize = {};",
+            self.num_decision_variables
+        );
+        let _ = writeln!(code, "/// Number of parameters");
+        let _ = writeln!(
+            code,
+            "pub const NUM_PARAMETERS: usize = {};",
+            self.num_parameters
+        );
+        let _ = writeln!(
+            code,
+            "/// Dimension of the mapping F1 (ALM-type constraints)"
+        );
+        let _ = writeln!(code, "pub const N1: usize = {};", self.n1);
+        let _ = writeln!(
+            code,
+            "/// Dimension of the mapping F2 (PM-type constraints)"
+        );
+        let _ = writeln!(code, "pub const N2: usize = {};", self.n2);
+        let cache_memory = PANOCCache::new(
+            self.num_decision_variables,
+            config.epsilon_tolerance,
+            self.lbfgs_memory,
+        )
+        .map_or(0, |panoc_cache| {
+            AlmCache::new(panoc_cache, self.n1, self.n2).memory_footprint()
+        });
+        let _ = writeln!(
+            code,
+            "/// Number of bytes held by the cache of the solver on the platform of the\n\
+             /// code generator (see `AlmCache::memory_footprint`)\n\
+             pub const CACHE_MEMORY_BYTES: usize = {};\n",
+            cache_memory
+        );
+        if let Some(move_blocking) = &self.move_blocking {
+            let blocks: Vec<String> = move_blocking
+                .blocks()
+                .iter()
+                .map(|block| block.to_string())
+                .collect();
+            let _ = writeln!(
+                code,
+                "pub use optimization_engine::mpc::MoveBlocking;\n\
+                 /// Dimension of the control action of a stage\n\
+                 pub const CONTROL_DIMENSION: usize = {};\n\
+                 /// Numbers of stages of the blocks of the move-blocking pattern\n\
+                 pub const BLOCKS: [usize; {}] = [{}];\n\
+                 /// Number of control actions of all stages (the dimension of `u` in `problem`)\n\
+                 pub const NUM_CONTROLS: usize = {};\n\
+                 \n\
+                 /// Move-blocking pattern, which maps the decision variables to the\n\
+                 /// control actions of all stages\n\
+                 pub fn move_blocking() -> Result<MoveBlocking, SolverError> {{\n    \
+                     MoveBlocking::new(CONTROL_DIMENSION, &BLOCKS)\n\
+                 }}\n",
+                move_blocking.control_dimension(),
+                blocks.len(),
+                blocks.join(", "),
+                move_blocking.full_dimension()
+            );
+        }
+
+        // settings
+        let settings = [
+            ("LBFGS_MEMORY", "usize", self.lbfgs_memory.to_string()),
+            (
+                "MAX_OUTER_ITERATIONS",
+                "usize",
+                config.max_outer_iterations.to_string(),
+            ),
+            (
+                "MAX_INNER_ITERATIONS",
+                "usize",
+                config.max_inner_iterations.to_string(),
+            ),
+            (
+                "DELTA_TOLERANCE",
+                "f64",
+                float_literal(config.delta_tolerance),
+            ),
+            (
+                "EPSILON_TOLERANCE",
+                "f64",
+                float_literal(config.epsilon_tolerance),
+            ),
+            (
+                "PENALTY_UPDATE_FACTOR",
+                "f64",
+                float_literal(config.penalty_update_factor),
+            ),
+            (
+                "INNER_TOLERANCE_UPDATE_FACTOR",
+                "f64",
+                float_literal(config.inner_tolerance_update_factor),
+            ),
+            (
+                "INITIAL_INNER_TOLERANCE",
+                "f64",
+                float_literal(config.initial_inner_tolerance),
+            ),
+            (
+                "SUFFICIENT_DECREASE_COEFFICIENT",
+                "f64",
+                float_literal(config.sufficient_decrease_coefficient),
+            ),
+            ("NAN_SCREENING", "bool", config.nan_screening.to_string()),
+            ("PENALTY_METHOD", "bool", config.penalty_method.to_string()),
+            ("FILTER", "bool", config.filter.to_string()),
+        ];
+        for (name, type_name, value) in settings.iter() {
+            let _ = writeln!(code, "const {}: {} = {};", name, type_name, value);
+        }
+        if let Some(max_duration) = config.max_duration {
+            let _ = writeln!(
+                code,
+                "const MAX_DURATION_MICROS: u64 = {};",
+                max_duration.as_micros()
+            );
+        }
+        code.push('\n');
+
+        // sets
+        let constraints = self
+            .constraints
+            .render("U", "NUM_DECISION_VARIABLES", &mut code);
+        let set_c = self
+            .set_c
+            .as_ref()
+            .map(|set_c| set_c.render("C", "N1", &mut code));
+        if set_c.is_some() {
+            let _ = writeln!(
+                code,
+                "const LAGRANGE_SET_RADIUS: f64 = {};",
+                float_literal(LAGRANGE_SET_RADIUS)
+            );
+        }
+
+        // initialize_solver
+        let _ = writeln!(
+            code,
+            "\n/// Allocates the cache of the solver\n\
+             pub fn initialize_solver() -> Result<AlmCache, SolverError> {{\n    \
+                 let panoc_cache = PANOCCache::new(NUM_DECISION_VARIABLES, EPSILON_TOLERANCE, LBFGS_MEMORY)?;\n    \
+                 Ok(AlmCache::new(panoc_cache, N1, N2))\n\
+             }}\n"
+        );
+
+        // solve
+        let _ = writeln!(
+            code,
+            "/// Solves the problem for the parameter `p`\n\
+             ///\n\
+             /// The initial guess `u` is overwritten with the solution; `y0` and `c0` are\n\
+             /// the initial Lagrange multipliers and the initial penalty parameter (if\n\
+             /// `None`, the values stored in the cache are used).\n\
+             pub fn solve(\n    \
+                 p: &[f64],\n    \
+                 cache: &mut AlmCache,\n    \
+                 u: &mut [f64],\n    \
+                 y0: Option<&[f64]>,\n    \
+                 c0: Option<f64>,\n\
+             ) -> Result<AlmOptimizerStatus, SolverError> {{\n    \
+                 if p.len() != NUM_PARAMETERS {{\n        \
+                     return Err(SolverError::DimensionMismatch {{\n            \
+                         name: \"p\",\n            \
+                         expected: NUM_PARAMETERS,\n            \
+                         actual: p.len(),\n        \
+                     }});\n    \
+                 }}\n    \
+                 let cost = |u: &[f64], xi: &[f64], c: &mut f64| -> FunctionCallResult {{\n        \
+                     problem::cost(u, xi, p, c)\n    \
+                 }};\n    \
+                 let gradient = |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {{\n        \
+                     problem::gradient(u, xi, p, grad)\n    \
+                 }};"
+        );
+        let (set_c, set_y, mapping_f1) = match set_c {
+            Some(set_c) => {
+                let _ = writeln!(
+                    code,
+                    "    let mapping_f1 = |u: &[f64], f1u: &mut [f64]| -> FunctionCallResult {{\n        \
+                         problem::mapping_f1(u, p, f1u)\n    \
+                     }};"
+                );
+                (
+                    format!("Some({})", set_c),
+                    "Some(BallInf::new(None, LAGRANGE_SET_RADIUS)?)".to_string(),
+                    "Some(mapping_f1)",
+                )
+            }
+            None => ("NO_SET".to_string(), "NO_SET".to_string(), "NO_MAPPING"),
+        };
+        let mapping_f2 = if self.n2 > 0 {
+            let _ = writeln!(
+                code,
+                "    let mapping_f2 = |u: &[f64], f2u: &mut [f64]| -> FunctionCallResult {{\n        \
+                     problem::mapping_f2(u, p, f2u)\n    \
+                 }};"
+            );
+            "Some(mapping_f2)"
+        } else {
+            "NO_MAPPING"
+        };
+        if self.move_blocking.is_some() {
+            let _ = writeln!(
+                code,
+                "    let move_blocking = move_blocking()?;\n    \
+                 let cost = move_blocking.cost(cost);\n    \
+                 let gradient = move_blocking.gradient(gradient);"
+            );
+            if self.set_c.is_some() {
+                let _ = writeln!(
+                    code,
+                    "    let mapping_f1 = move_blocking.mapping(mapping_f1);"
+                );
+            }
+            if self.n2 > 0 {
+                let _ = writeln!(
+                    code,
+                    "    let mapping_f2 = move_blocking.mapping(mapping_f2);"
+                );
+            }
+        }
+        let _ = writeln!(
+            code,
+            "    let alm_problem = AlmProblem::new(\n        \
+                 {constraints},\n        \
+                 {set_c},\n        \
+                 {set_y},\n        \
+                 cost,\n        \
+                 gradient,\n        \
+                 {mapping_f1},\n        \
+                 {mapping_f2},\n        \
+                 N1,\n        \
+                 N2,\n    \
+             )?;\n    \
+             let mut optimizer = AlmOptimizer::new(cache, alm_problem)\n        \
+                 .with_max_outer_iterations(MAX_OUTER_ITERATIONS)?\n        \
+                 .with_max_inner_iterations(MAX_INNER_ITERATIONS)?\n        \
+                 .with_delta_tolerance(DELTA_TOLERANCE)?\n        \
+                 .with_epsilon_tolerance(EPSILON_TOLERANCE)?\n        \
+                 .with_penalty_update_factor(PENALTY_UPDATE_FACTOR)?\n        \
+                 .with_inner_tolerance_update_factor(INNER_TOLERANCE_UPDATE_FACTOR)?\n        \
+                 .with_initial_inner_tolerance(INITIAL_INNER_TOLERANCE)?\n        \
+                 .with_sufficient_decrease_coefficient(SUFFICIENT_DECREASE_COEFFICIENT)?\n        \
+                 .with_nan_screening(NAN_SCREENING)\n        \
+                 .with_penalty_method(PENALTY_METHOD)\n        \
+                 .with_filter(FILTER);",
+            constraints = constraints,
+            set_c = set_c,
+            set_y = set_y,
+            mapping_f1 = mapping_f1,
+            mapping_f2 = mapping_f2
+        );
+        if config.max_duration.is_some() {
+            let _ = writeln!(
+                code,
+                "    optimizer = optimizer.with_max_duration(std::time::Duration::from_micros(MAX_DURATION_MICROS));"
+            );
+        }
+        let _ = writeln!(
+            code,
+            "    if let Some(y0) = y0 {{\n        \
+                 optimizer = optimizer.with_initial_lagrange_multipliers(y0)?;\n    \
+             }}\n    \
+             if let Some(c0) = c0 {{\n        \
+                 optimizer = optimizer.with_initial_penalty(c0)?;\n    \
+             }}\n    \
+             optimizer.solve(u)\n\
+             }}"
+        );
+        code
+    }
+
+    fn problem_rs_skeleton(&self) -> String {
+        let mut code = String::from(
+            "//! Cost function, gradient and mappings of the problem\n\
+             //!\n\
+             //! Complete the following functions (which return an error until\n\
+             //! they are implemented).\n\
+             //!\n\
+             use optimization_engine::{FunctionCallResult, SolverError};\n",
+        );
+        if self.move_blocking.is_some() {
+            code.insert_str(
+                code.find("//!\n")
+                    .expect("the skeleton has a module documentation"),
+                "//!\n\
+                 //! `u` is the sequence of control actions of all stages (of dimension\n\
+                 //! `crate::NUM_CONTROLS`), which is computed from the decision variables\n\
+                 //! by move blocking.\n",
+            );
+        }
+        let mut function = |signature: &str, name: &str| {
+            let _ = write!(
+                code,
+                "\n/// {name}\n\
+                 pub fn {signature} -> FunctionCallResult {{\n    \
+                     Err(SolverError::user(\"{name}: not implemented\"))\n\
+                 }}\n",
+                name = name,
+                signature = signature
+            );
+        };
+        function(
+            "cost(_u: &[f64], _xi: &[f64], _p: &[f64], _cost: &mut f64)",
+            "cost function",
+        );
+        function(
+            "gradient(_u: &[f64], _xi: &[f64], _p: &[f64], _grad: &mut [f64])",
+            "gradient of the cost function",
+        );
+        if self.n1 > 0 {
+            function(
+                "mapping_f1(_u: &[f64], _p: &[f64], _f1u: &mut [f64])",
+                "mapping F1",
+            );
+        }
+        if self.n2 > 0 {
+            function(
+                "mapping_f2(_u: &[f64], _p: &[f64], _f2u: &mut [f64])",
+                "mapping F2",
+            );
+        }
+        code
+    }
+}
+
+/// Source code of a generated crate
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedCrate {
+    files: Vec<(PathBuf, String)>,
+}
+
+impl GeneratedCrate {
+    /// Files of the crate (paths relative to the root of the crate and their
+    /// contents)
+    pub fn files(&self) -> &[(PathBuf, String)] {
+        &self.files
+    }
+
+    /// Contents of the file with the given path (relative to the root of the
+    /// crate), if any
+    pub fn file(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|(file_path, _)| file_path == path.as_ref())
+            .map(|(_, contents)| contents.as_str())
+    }
+
+    /// Writes the crate into the directory `directory` (which is created if
+    /// it does not exist)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory or a file cannot be created
+    pub fn write_to(&self, directory: impl AsRef<Path>) -> io::Result<()> {
+        for (path, contents) in &self.files {
+            let path = directory.as_ref().join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests;
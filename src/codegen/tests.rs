@@ -0,0 +1,178 @@
+use super::*;
+
+#[test]
+fn t_codegen_files() {
+    let generated = CodeGenerator::new("my_solver", 3, 2)
+        .with_constraints(SetDescription::Rectangle {
+            min: Some(vec![-1.0, f64::NEG_INFINITY, 0.0]),
+            max: None,
+        })
+        .with_alm_constraints(
+            2,
+            SetDescription::Ball2 {
+                center: Some(vec![0.5, 1e-3]),
+                radius: 2.0,
+            },
+        )
+        .with_penalty_constraints(1)
+        .with_lbfgs_memory(7)
+        .with_config(AlmConfig {
+            max_outer_iterations: 12,
+            max_duration: Some(std::time::Duration::from_millis(5)),
+            ..AlmConfig::default()
+        })
+        .generate()
+        .unwrap();
+    assert_eq!(3, generated.files().len());
+
+    let cargo_toml = generated.file("Cargo.toml").unwrap();
+    assert!(cargo_toml.contains("name = \"my_solver\""));
+    assert!(cargo_toml.contains("default-features = false"));
+
+    let lib_rs = generated.file("src/lib.rs").unwrap();
+    assert!(lib_rs.contains("pub const NUM_DECISION_VARIABLES: usize = 3;"));
+    assert!(lib_rs.contains("pub const NUM_PARAMETERS: usize = 2;"));
+    assert!(lib_rs.contains("pub const N1: usize = 2;"));
+    assert!(lib_rs.contains("pub const N2: usize = 1;"));
+    let cache = AlmCache::new(PANOCCache::new(3, 1e-4, 7).unwrap(), 2, 1);
+    assert!(lib_rs.contains(&format!(
+        "pub const CACHE_MEMORY_BYTES: usize = {};",
+        cache.memory_footprint()
+    )));
+    assert!(lib_rs.contains("const LBFGS_MEMORY: usize = 7;"));
+    assert!(lib_rs.contains("const MAX_OUTER_ITERATIONS: usize = 12;"));
+    assert!(lib_rs.contains("const MAX_DURATION_MICROS: u64 = 5000;"));
+    assert!(lib_rs
+        .contains("static U_MIN: [f64; NUM_DECISION_VARIABLES] = [-1.0, f64::NEG_INFINITY, 0.0];"));
+    assert!(lib_rs.contains("Rectangle::new(Some(&U_MIN), None)?"));
+    assert!(lib_rs.contains("static C_CENTER: [f64; N1] = [0.5, 0.001];"));
+    assert!(lib_rs.contains("Some(Ball2::new(Some(&C_CENTER), 2.0)?)"));
+    assert!(lib_rs.contains("Some(mapping_f2)"));
+
+    let problem_rs = generated.file("src/problem.rs").unwrap();
+    assert!(problem_rs.contains("pub fn cost("));
+    assert!(problem_rs.contains("pub fn mapping_f1("));
+    assert!(problem_rs.contains("pub fn mapping_f2("));
+}
+
+#[test]
+fn t_codegen_problem_source() {
+    let source = "// user-defined problem\n";
+    let generated = CodeGenerator::new("solver", 2, 0)
+        .with_problem_source(source)
+        .with_dependency("{ path = \"../optimization-engine\" }")
+        .generate()
+        .unwrap();
+    assert_eq!(Some(source), generated.file("src/problem.rs"));
+    assert!(generated
+        .file("Cargo.toml")
+        .unwrap()
+        .contains("optimization_engine = { path = \"../optimization-engine\" }"));
+    let lib_rs = generated.file("src/lib.rs").unwrap();
+    assert!(lib_rs.contains("NoConstraints::new()"));
+    assert!(lib_rs.contains("NO_SET"));
+    assert!(!lib_rs.contains("mapping_f1"));
+    assert!(!lib_rs.contains("MAX_DURATION_MICROS"));
+}
+
+#[test]
+fn t_codegen_move_blocking() {
+    let move_blocking = crate::mpc::MoveBlocking::new(2, &[1, 2, 5]).unwrap();
+    let generated = CodeGenerator::new("mpc_solver", 6, 3)
+        .with_move_blocking(move_blocking.clone())
+        .with_alm_constraints(1, SetDescription::Zero)
+        .generate()
+        .unwrap();
+    let lib_rs = generated.file("src/lib.rs").unwrap();
+    assert!(lib_rs.contains("pub const CONTROL_DIMENSION: usize = 2;"));
+    assert!(lib_rs.contains("pub const BLOCKS: [usize; 3] = [1, 2, 5];"));
+    assert!(lib_rs.contains("pub const NUM_CONTROLS: usize = 16;"));
+    assert!(lib_rs.contains("let gradient = move_blocking.gradient(gradient);"));
+    assert!(lib_rs.contains("let mapping_f1 = move_blocking.mapping(mapping_f1);"));
+    assert!(!lib_rs.contains("move_blocking.mapping(mapping_f2)"));
+    assert!(generated
+        .file("src/problem.rs")
+        .unwrap()
+        .contains("`crate::NUM_CONTROLS`"));
+
+    let result = CodeGenerator::new("mpc_solver", 16, 3)
+        .with_move_blocking(move_blocking)
+        .generate();
+    assert!(matches!(
+        result,
+        Err(SolverError::DimensionMismatch {
+            name: "num_decision_variables",
+            expected: 6,
+            actual: 16
+        })
+    ));
+}
+
+#[test]
+fn t_codegen_invalid_descriptions() {
+    let invalid_parameter = |generator: CodeGenerator, expected: &str| match generator.generate() {
+        Err(SolverError::InvalidParameter { name, .. }) => assert_eq!(expected, name),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    };
+    invalid_parameter(CodeGenerator::new("1solver", 2, 0), "name");
+    invalid_parameter(CodeGenerator::new("my-solver", 2, 0), "name");
+    invalid_parameter(CodeGenerator::new("solver", 0, 0), "num_decision_variables");
+    invalid_parameter(
+        CodeGenerator::new("solver", 2, 0).with_alm_constraints(0, SetDescription::Zero),
+        "n1",
+    );
+    invalid_parameter(
+        CodeGenerator::new("solver", 2, 0).with_constraints(SetDescription::BallInf {
+            center: None,
+            radius: -1.0,
+        }),
+        "radius",
+    );
+    invalid_parameter(
+        CodeGenerator::new("solver", 2, 0).with_constraints(SetDescription::Rectangle {
+            min: Some(vec![1.0, 1.0]),
+            max: Some(vec![0.0, 2.0]),
+        }),
+        "max",
+    );
+    invalid_parameter(
+        CodeGenerator::new("solver", 2, 0).with_config(AlmConfig {
+            max_inner_iterations: 0,
+            ..AlmConfig::default()
+        }),
+        "max_inner_iterations",
+    );
+    invalid_parameter(
+        CodeGenerator::new("solver", 2, 0).with_lbfgs_memory(0),
+        "lbfgs_memory",
+    );
+
+    let result = CodeGenerator::new("solver", 2, 0)
+        .with_constraints(SetDescription::Ball2 {
+            center: Some(vec![0.0; 3]),
+            radius: 1.0,
+        })
+        .generate();
+    assert!(matches!(
+        result,
+        Err(SolverError::DimensionMismatch {
+            name: "center",
+            expected: 2,
+            actual: 3
+        })
+    ));
+}
+
+#[test]
+fn t_codegen_write_to() {
+    let directory = std::env::temp_dir().join(format!("open_codegen_{}", std::process::id()));
+    let generated = CodeGenerator::new("solver", 2, 0).generate().unwrap();
+    generated.write_to(&directory).unwrap();
+    for (path, contents) in generated.files() {
+        assert_eq!(
+            *contents,
+            std::fs::read_to_string(directory.join(path)).unwrap()
+        );
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}
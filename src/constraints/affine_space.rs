@@ -1,4 +1,5 @@
 use super::Constraint;
+use crate::SolverError;
 
 extern crate modcholesky;
 extern crate ndarray;
@@ -13,6 +14,8 @@ type OpenVec<T> = ArrayBase<OwnedRepr<T>, Dim<[usize; 1]>>;
 /// An affine space here is defined as the set of solutions of a linear equation, $Ax = b$,
 /// that is, $E=\\{x\in\mathbb{R}^n: Ax = b\\}$, which is an affine space. It is assumed that
 /// the matrix $AA^\intercal$ is full-rank.
+///
+/// This constraint is only available in double precision (`f64`).
 pub struct AffineSpace {
     a_mat: OpenMat<f64>,
     b_vec: OpenVec<f64>,
@@ -32,16 +35,20 @@ impl AffineSpace {
     /// - `b`: vector $b$
     ///
     /// ## Returns
-    /// New Affine Space structure
+    /// New Affine Space structure, or `SolverError::InvalidParameter` if `b` is
+    /// empty or the number of elements of `a` is not a multiple of the length
+    /// of `b`
     ///
-    pub fn new(a: Vec<f64>, b: Vec<f64>) -> Self {
+    pub fn new(a: Vec<f64>, b: Vec<f64>) -> Result<Self, SolverError> {
         // Infer dimensions of A and b
         let n_rows = b.len();
         let n_elements_a = a.len();
-        assert!(
-            n_elements_a % n_rows == 0,
-            "A and b have incompatible dimensions"
-        );
+        if n_rows == 0 || n_elements_a == 0 || n_elements_a % n_rows != 0 {
+            return Err(SolverError::InvalidParameter {
+                name: "a",
+                reason: "A and b have incompatible dimensions",
+            });
+        }
         let n_cols = n_elements_a / n_rows;
         // Cast A and b as ndarray structures
         let a_mat = Array2::from_shape_vec((n_rows, n_cols), a).unwrap();
@@ -56,14 +63,55 @@ impl AffineSpace {
         let p = res.p;
 
         // Construct and return new AffineSpace structure
-        AffineSpace {
+        Ok(AffineSpace {
             a_mat,
             b_vec,
             l,
             p,
             n_rows,
             n_cols,
+        })
+    }
+
+    /// Construct a new affine space given the matrix $A$ and the vector $b$ as
+    /// nalgebra types (requires the feature `nalgebra`)
+    ///
+    /// ## Returns
+    /// New Affine Space structure, or `SolverError::InvalidParameter` if `b` is
+    /// empty or the number of rows of `a` is not the length of `b`
+    ///
+    #[cfg(feature = "nalgebra")]
+    pub fn from_nalgebra(
+        a: &nalgebra::DMatrix<f64>,
+        b: &nalgebra::DVector<f64>,
+    ) -> Result<Self, SolverError> {
+        if a.nrows() != b.len() {
+            return Err(SolverError::InvalidParameter {
+                name: "a",
+                reason: "A and b have incompatible dimensions",
+            });
+        }
+        // nalgebra stores matrices column-wise, so the columns of the
+        // transpose are the rows of A
+        AffineSpace::new(a.transpose().as_slice().to_vec(), b.as_slice().to_vec())
+    }
+
+    /// Construct a new affine space given the matrix $A$ and the vector $b$ as
+    /// ndarray arrays (requires the feature `ndarray`), in any memory layout
+    ///
+    /// ## Returns
+    /// New Affine Space structure, or `SolverError::InvalidParameter` if `b` is
+    /// empty or the number of rows of `a` is not the length of `b`
+    ///
+    #[cfg(feature = "ndarray")]
+    pub fn from_ndarray(a: &Array2<f64>, b: &Array1<f64>) -> Result<Self, SolverError> {
+        if a.nrows() != b.len() {
+            return Err(SolverError::InvalidParameter {
+                name: "a",
+                reason: "A and b have incompatible dimensions",
+            });
         }
+        AffineSpace::new(a.iter().copied().collect(), b.to_vec())
     }
 }
 
@@ -93,7 +141,7 @@ impl Constraint for AffineSpace {
     ///
     /// let a = vec![0.5, 0.1, 0.2, -0.3, -0.6, 0.3, 0., 0.5, 1.0, 0.1, -1.0, -0.4,];
     /// let b = vec![1., 2., -0.5];
-    /// let affine_set = AffineSpace::new(a, b);
+    /// let affine_set = AffineSpace::new(a, b).unwrap();
     /// let mut x = [1., -2., -0.3, 0.5];
     /// affine_set.project(&mut x);
     /// ```
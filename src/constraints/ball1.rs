@@ -1,46 +1,49 @@
 use super::Constraint;
 use super::Simplex;
+use crate::{OpEnFloat, SolverError};
 
 #[derive(Copy, Clone)]
 /// A norm-1 ball, that is, a set given by $B_1^r = \\{x \in \mathbb{R}^n {}:{} \Vert{}x{}\Vert_1 \leq r\\}$
 /// or a ball-1 centered at a point $x_c$, that is, $B_1^{x_c, r} = \\{x \in \mathbb{R}^n {}:{} \Vert{}x-x_c{}\Vert_1 \leq r\\}$
-pub struct Ball1<'a> {
-    center: Option<&'a [f64]>,
-    radius: f64,
-    simplex: Simplex,
+pub struct Ball1<'a, T = f64> {
+    center: Option<&'a [T]>,
+    radius: T,
+    simplex: Simplex<T>,
 }
 
-impl<'a> Ball1<'a> {
+impl<'a, T: OpEnFloat> Ball1<'a, T> {
     /// Construct a new ball-1 with given center and radius.
     /// If no `center` is given, then it is assumed to be in the origin
-    pub fn new(center: Option<&'a [f64]>, radius: f64) -> Self {
-        assert!(radius > 0.0);
-        let simplex = Simplex::new(radius);
-        Ball1 {
+    ///
+    /// Returns `SolverError::InvalidParameter` if the radius is not positive
+    pub fn new(center: Option<&'a [T]>, radius: T) -> Result<Self, SolverError> {
+        crate::ensure(radius > T::zero(), "radius", "must be positive")?;
+        let simplex = Simplex::new(radius)?;
+        Ok(Ball1 {
             center,
             radius,
             simplex,
-        }
+        })
     }
 
-    fn project_on_ball1_centered_at_origin(&self, x: &mut [f64]) {
+    fn project_on_ball1_centered_at_origin(&self, x: &mut [T]) {
         if crate::matrix_operations::norm1(x) > self.radius {
             // u = |x| (copied)
-            let mut u = vec![0.0; x.len()];
+            let mut u = vec![T::zero(); x.len()];
             u.iter_mut()
                 .zip(x.iter())
-                .for_each(|(ui, &xi)| *ui = f64::abs(xi));
+                .for_each(|(ui, &xi)| *ui = xi.abs());
             // u = P_simplex(u)
             self.simplex.project(&mut u);
             x.iter_mut()
                 .zip(u.iter())
-                .for_each(|(xi, &ui)| *xi = f64::signum(*xi) * ui);
+                .for_each(|(xi, &ui)| *xi = xi.signum() * ui);
         }
     }
 }
 
-impl<'a> Constraint for Ball1<'a> {
-    fn project(&self, x: &mut [f64]) {
+impl<'a, T: OpEnFloat> Constraint<T> for Ball1<'a, T> {
+    fn project(&self, x: &mut [T]) {
         if let Some(center) = &self.center {
             x.iter_mut()
                 .zip(center.iter())
@@ -1,30 +1,32 @@
 use super::Constraint;
+use crate::{OpEnFloat, SolverError};
 
 #[derive(Copy, Clone)]
 /// A Euclidean ball, that is, a set given by $B_2^r = \\{x \in \mathbb{R}^n {}:{} \Vert{}x{}\Vert \leq r\\}$
 /// or a Euclidean ball centered at a point $x_c$, that is, $B_2^{x_c, r} = \\{x \in \mathbb{R}^n {}:{} \Vert{}x-x_c{}\Vert \leq r\\}$
-pub struct Ball2<'a> {
-    center: Option<&'a [f64]>,
-    radius: f64,
+pub struct Ball2<'a, T = f64> {
+    center: Option<&'a [T]>,
+    radius: T,
 }
 
-impl<'a> Ball2<'a> {
+impl<'a, T: OpEnFloat> Ball2<'a, T> {
     /// Construct a new Euclidean ball with given center and radius
     /// If no `center` is given, then it is assumed to be in the origin
-    pub fn new(center: Option<&'a [f64]>, radius: f64) -> Self {
-        assert!(radius > 0.0);
-
-        Ball2 { center, radius }
+    ///
+    /// Returns `SolverError::InvalidParameter` if the radius is not positive
+    pub fn new(center: Option<&'a [T]>, radius: T) -> Result<Self, SolverError> {
+        crate::ensure(radius > T::zero(), "radius", "must be positive")?;
+        Ok(Ball2 { center, radius })
     }
 }
 
-impl<'a> Constraint for Ball2<'a> {
-    fn project(&self, x: &mut [f64]) {
+impl<'a, T: OpEnFloat> Constraint<T> for Ball2<'a, T> {
+    fn project(&self, x: &mut [T]) {
         if let Some(center) = &self.center {
-            let mut norm_difference = 0.0;
+            let mut norm_difference = T::zero();
             x.iter().zip(center.iter()).for_each(|(a, b)| {
                 let diff_ = *a - *b;
-                norm_difference += diff_ * diff_
+                norm_difference += diff_ * diff_;
             });
 
             norm_difference = norm_difference.sqrt();
@@ -1,4 +1,5 @@
 use super::Constraint;
+use crate::{OpEnFloat, SolverError};
 
 /// Cartesian product of constraints
 ///
@@ -21,13 +22,18 @@ use super::Constraint;
 /// The constraint $x \in C$ is interpreted as $x_i \in C_i$
 /// for all $i=0,\ldots, n-1$.
 ///
+/// The sets can be named (see `add_named_constraint`); the names are
+/// reported, e.g., in the infeasibilities of the blocks of the constraints
+/// of `AlmOptimizer` (see `AlmOptimizer::with_block_infeasibility`).
+///
 #[derive(Default)]
-pub struct CartesianProduct<'a> {
+pub struct CartesianProduct<'a, T = f64> {
     idx: Vec<usize>,
-    constraints: Vec<Box<dyn Constraint + 'a>>,
+    constraints: Vec<Box<dyn Constraint<T> + 'a>>,
+    names: Vec<Option<String>>,
 }
 
-impl<'a> CartesianProduct<'a> {
+impl<'a, T: OpEnFloat> CartesianProduct<'a, T> {
     /// Construct new instance of Cartesian product of constraints
     ///
     /// # Note
@@ -40,6 +46,7 @@ impl<'a> CartesianProduct<'a> {
         CartesianProduct {
             idx: Vec::new(),
             constraints: Vec::new(),
+            names: Vec::new(),
         }
     }
 
@@ -58,6 +65,7 @@ impl<'a> CartesianProduct<'a> {
         CartesianProduct {
             idx: Vec::with_capacity(num_sets),
             constraints: Vec::new(),
+            names: Vec::with_capacity(num_sets),
         }
     }
 
@@ -95,39 +103,75 @@ impl<'a> CartesianProduct<'a> {
     ///  */
     /// let idx1 = 3;
     /// let idx2 = 5;
-    /// let ball1 = Ball2::new(None, 1.0);
-    /// let ball2 = Ball2::new(None, 0.5);
+    /// let ball1 = Ball2::new(None, 1.0).unwrap();
+    /// let ball2 = Ball2::new(None, 0.5).unwrap();
     /// let mut cart_prod = CartesianProduct::new()
-    ///     .add_constraint(idx1, ball1)
-    ///     .add_constraint(idx2, ball2);
+    ///     .add_constraint(idx1, ball1).unwrap()
+    ///     .add_constraint(idx2, ball2).unwrap();
     /// ```
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if `ni` is less than or equal to the previous
-    /// dimension of the cartesian product. For example, the following
-    /// code will fail:
+    /// The method returns `SolverError::InvalidParameter` if `ni` is less than
+    /// or equal to the previous dimension of the cartesian product. For example,
+    /// the following code will fail:
     ///
     /// ```compile_fail
     /// let mut cart_prod = CartesianProduct::new()
-    ///     .add_constraint(7, &rectangle);     // OK, since 7  > 0
-    ///     .add_constraint(10, &ball1);        // OK, since 10 > 7
-    ///     .add_constraint(2, &ball3);         // 2 <= 10, so it will fail
+    ///     .add_constraint(7, &rectangle)?;     // OK, since 7  > 0
+    ///     .add_constraint(10, &ball1)?;        // OK, since 10 > 7
+    ///     .add_constraint(2, &ball3)?;         // 2 <= 10, so it will fail
     /// ```
+    ///
+    /// # Panics
+    ///
     /// The method will panic if any of the associated projections panics.
     ///
-    pub fn add_constraint(mut self, ni: usize, constraint: impl Constraint + 'a) -> Self {
-        assert!(
-            self.dimension() < ni,
-            "provided index is smaller than or equal to previous index, or zero"
-        );
+    pub fn add_constraint(
+        mut self,
+        ni: usize,
+        constraint: impl Constraint<T> + 'a,
+    ) -> Result<Self, SolverError> {
+        if ni <= self.dimension() {
+            return Err(SolverError::InvalidParameter {
+                name: "ni",
+                reason: "provided index is smaller than or equal to previous index, or zero",
+            });
+        }
         self.idx.push(ni);
         self.constraints.push(Box::new(constraint));
-        self
+        self.names.push(None);
+        Ok(self)
+    }
+
+    /// Add constraint `x(i) in C(i)` with a name (see `add_constraint`)
+    ///
+    /// # Arguments
+    ///
+    /// - `ni`: total length of vector `(x(0), ..., x(i))`
+    /// - `name`: name of the set, e.g., `"input bounds"`
+    /// - `constraint`: constraint to be added implementation of trait `Constraint`
+    ///
+    /// # Errors
+    ///
+    /// The method returns `SolverError::InvalidParameter` if `ni` is less than
+    /// or equal to the previous dimension of the cartesian product
+    ///
+    pub fn add_named_constraint(
+        self,
+        ni: usize,
+        name: &str,
+        constraint: impl Constraint<T> + 'a,
+    ) -> Result<Self, SolverError> {
+        let mut cart_prod = self.add_constraint(ni, constraint)?;
+        if let Some(last) = cart_prod.names.last_mut() {
+            *last = Some(name.to_owned());
+        }
+        Ok(cart_prod)
     }
 }
 
-impl<'a> Constraint for CartesianProduct<'a> {
+impl<'a, T: OpEnFloat> Constraint<T> for CartesianProduct<'a, T> {
     /// Project onto Cartesian product of constraints
     ///
     /// The given vector `x` is updated with the projection on the set
@@ -136,7 +180,7 @@ impl<'a> Constraint for CartesianProduct<'a> {
     ///
     /// The method will panic if the dimension of `x` is not equal to the
     /// dimension of the Cartesian product (see `dimension()`)
-    fn project(&self, x: &mut [f64]) {
+    fn project(&self, x: &mut [T]) {
         assert!(x.len() == self.dimension(), "x has wrong size");
         let mut j = 0;
         self.idx
@@ -154,4 +198,12 @@ impl<'a> Constraint for CartesianProduct<'a> {
             flag
         })
     }
+
+    fn blocks(&self) -> Option<&[usize]> {
+        Some(&self.idx)
+    }
+
+    fn block_name(&self, i: usize) -> Option<&str> {
+        self.names.get(i)?.as_deref()
+    }
 }
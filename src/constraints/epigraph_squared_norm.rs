@@ -5,6 +5,8 @@ use super::Constraint;
 #[derive(Copy, Clone, Default)]
 /// The epigraph of the squared Eucliden norm is a set of the form
 /// $X = \\{x = (z, t) \in \mathbb{R}^{n}\times \mathbb{R} {}:{} \\|z\\|^2 \leq t \\}.$
+///
+/// This constraint is only available in double precision (`f64`).
 pub struct EpigraphSquaredNorm {}
 
 impl EpigraphSquaredNorm {
@@ -1,17 +1,18 @@
 use super::Constraint;
+use crate::{OpEnFloat, SolverError};
 
 ///
 /// A finite set, $X = \\{x_1, x_2, \ldots, x_n\\}\subseteq\mathbb{R}^n$, given vectors
 /// $x_i\in\mathbb{R}^n$
 ///
 #[derive(Clone, Copy)]
-pub struct FiniteSet<'a> {
+pub struct FiniteSet<'a, T = f64> {
     /// The data is stored in a Vec-of-Vec datatype, that is, a vector
     /// of vectors
-    data: &'a [&'a [f64]],
+    data: &'a [&'a [T]],
 }
 
-impl<'a> FiniteSet<'a> {
+impl<'a, T: OpEnFloat> FiniteSet<'a, T> {
     /// Construct a finite set, $X = \\{x_1, x_2, \ldots, x_n\\}$, given vectors
     /// $x_i\in\mathbb{R}^n$
     ///
@@ -32,27 +33,37 @@ impl<'a> FiniteSet<'a> {
     ///    &[1.0, 0.0],
     ///    &[0.0, 0.0],
     /// ];
-    /// let finite_set = FiniteSet::new(data);
+    /// let finite_set = FiniteSet::new(data).unwrap();
     /// ```
     ///
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This method will panic if (i) the given vector of data is empty
-    /// and (ii) if the given vectors have unequal dimensions.
+    /// This method returns an error if (i) the given vector of data is empty
+    /// (`SolverError::InvalidParameter`) or (ii) the given vectors have unequal
+    /// dimensions (`SolverError::DimensionMismatch`).
     ///
-    pub fn new(data: &'a [&'a [f64]]) -> Self {
+    pub fn new(data: &'a [&'a [T]]) -> Result<Self, SolverError> {
         // Do a sanity check...
-        assert!(!data.is_empty(), "empty data not allowed");
+        if data.is_empty() {
+            return Err(SolverError::InvalidParameter {
+                name: "data",
+                reason: "empty data not allowed",
+            });
+        }
         let n = data[0].len();
-        for v in data.iter() {
-            assert!(n == v.len(), "inconsistent dimensions");
+        if let Some(v) = data.iter().find(|v| v.len() != n) {
+            return Err(SolverError::DimensionMismatch {
+                name: "data",
+                expected: n,
+                actual: v.len(),
+            });
         }
-        FiniteSet { data }
+        Ok(FiniteSet { data })
     }
 }
 
-impl<'a> Constraint for FiniteSet<'a> {
+impl<'a, T: OpEnFloat> Constraint<T> for FiniteSet<'a, T> {
     ///
     /// Projection on the current finite set
     ///
@@ -75,7 +86,7 @@ impl<'a> Constraint for FiniteSet<'a> {
     ///    &[0.0, 0.0],
     ///    &[1.0, 1.0],
     /// ];
-    /// let finite_set = FiniteSet::new(data);
+    /// let finite_set = FiniteSet::new(data).unwrap();
     /// let mut x = [0.7, 0.6];
     /// finite_set.project(&mut x); // compute projection
     /// ```
@@ -84,9 +95,9 @@ impl<'a> Constraint for FiniteSet<'a> {
     ///
     /// Does not panic
     ///
-    fn project(&self, x: &mut [f64]) {
+    fn project(&self, x: &mut [T]) {
         let mut idx: usize = 0;
-        let mut best_distance: f64 = num::Float::infinity();
+        let mut best_distance = T::infinity();
         for (i, v) in self.data.iter().enumerate() {
             let dist = crate::matrix_operations::norm2_squared_diff(v, x);
             if dist < best_distance {
@@ -0,0 +1,91 @@
+use super::Constraint;
+use super::Simplex;
+use crate::{matrix_operations, prox::block_soft_threshold, OpEnFloat, SolverError};
+
+#[derive(Clone)]
+/// A group-norm ball, that is, a set of the form
+/// $B_{G}^r = \\{x \in \mathbb{R}^n {}:{} \sum_j \Vert{}x_{G_j}{}\Vert_2 \leq r\\}$,
+/// where the groups $G_j$ are consecutive blocks of $x$
+///
+/// This is the constraint counterpart of the group lasso (see
+/// `prox::GroupL1Norm`): the groups of the projection are either zero or
+/// scaled towards zero, so it promotes solutions in which whole groups
+/// (e.g., all inputs of an actuator) vanish.
+pub struct GroupNormBall<T = f64> {
+    radius: T,
+    group_sizes: Vec<usize>,
+    dimension: usize,
+    simplex: Simplex<T>,
+}
+
+impl<T: OpEnFloat> GroupNormBall<T> {
+    /// Construct a new group-norm ball with given radius, where the $j$-th
+    /// group consists of the next `group_sizes[j]` elements of $x$
+    ///
+    /// Returns `SolverError::InvalidParameter` if the radius is not positive,
+    /// or if `group_sizes` is empty or contains zeros
+    pub fn new(radius: T, group_sizes: &[usize]) -> Result<Self, SolverError> {
+        crate::ensure(radius > T::zero(), "radius", "must be positive")?;
+        crate::ensure(
+            !group_sizes.is_empty() && group_sizes.iter().all(|&size| size > 0),
+            "group_sizes",
+            "must be nonempty and positive",
+        )?;
+        let simplex = Simplex::new(radius)?;
+        Ok(GroupNormBall {
+            radius,
+            group_sizes: group_sizes.to_vec(),
+            dimension: group_sizes.iter().sum(),
+            simplex,
+        })
+    }
+
+    /// Dimension of $x$, that is, the sum of the sizes of the groups
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+impl<T: OpEnFloat> Constraint<T> for GroupNormBall<T> {
+    /// Projection on the group-norm ball
+    ///
+    /// The norms of the groups are projected on the $\ell_1$ ball of radius
+    /// $r$ (which, since they are nonnegative, amounts to a projection on
+    /// the simplex); if this shrinks them by $\theta$, the projection is the
+    /// block soft thresholding of each group with threshold $\theta$
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the dimension of `x` is not the sum of the group sizes
+    fn project(&self, x: &mut [T]) {
+        assert!(x.len() == self.dimension, "x has wrong dimension");
+        let mut norms = Vec::with_capacity(self.group_sizes.len());
+        let mut start = 0;
+        for &size in self.group_sizes.iter() {
+            norms.push(matrix_operations::norm2(&x[start..start + size]));
+            start += size;
+        }
+        if norms.iter().fold(T::zero(), |sum, &norm| sum + norm) <= self.radius {
+            return;
+        }
+        let mut shrunk_norms = norms.clone();
+        self.simplex.project(&mut shrunk_norms);
+        // theta = norm_j - shrunk_norm_j for all groups which are not
+        // zeroed, and norm_j <= theta for the others
+        let theta = norms
+            .iter()
+            .zip(shrunk_norms.iter())
+            .fold(T::zero(), |theta, (&norm, &shrunk_norm)| {
+                theta.max(norm - shrunk_norm)
+            });
+        let mut start = 0;
+        for &size in self.group_sizes.iter() {
+            block_soft_threshold(&mut x[start..start + size], theta);
+            start += size;
+        }
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
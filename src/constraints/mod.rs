@@ -15,6 +15,7 @@ mod ballinf;
 mod cartesian_product;
 mod epigraph_squared_norm;
 mod finite;
+mod group_norm_ball;
 mod halfspace;
 mod hyperplane;
 mod no_constraints;
@@ -31,6 +32,7 @@ pub use ballinf::BallInf;
 pub use cartesian_product::CartesianProduct;
 pub use epigraph_squared_norm::EpigraphSquaredNorm;
 pub use finite::FiniteSet;
+pub use group_norm_ball::GroupNormBall;
 pub use halfspace::Halfspace;
 pub use hyperplane::Hyperplane;
 pub use no_constraints::NoConstraints;
@@ -44,7 +46,10 @@ pub use zero::Zero;
 ///
 /// This trait defines an abstract function that allows to compute projections
 /// on sets; this is implemented by a series of structures (see below for details)
-pub trait Constraint {
+///
+/// The type parameter `T` is the scalar type (`f64` by default, see
+/// [`OpEnFloat`](../numeric/trait.OpEnFloat.html))
+pub trait Constraint<T = f64> {
     /// Projection onto the set, that is,
     ///
     /// $$
@@ -55,10 +60,51 @@ pub trait Constraint {
     ///
     /// - `x`: The given vector $x$ is updated with the projection on the set
     ///
-    fn project(&self, x: &mut [f64]);
+    fn project(&self, x: &mut [T]);
 
     /// Returns true if and only if the set is convex
     fn is_convex(&self) -> bool;
+
+    /// Ends of the blocks of the set, if it is a Cartesian product (see
+    /// `CartesianProduct`), otherwise `None`
+    ///
+    /// The $i$-th block consists of the coordinates from the end of the
+    /// previous block (or zero) up to, but excluding, the $i$-th end.
+    fn blocks(&self) -> Option<&[usize]> {
+        None
+    }
+
+    /// Name of the $i$-th block of the set (see `blocks`), if it has one
+    fn block_name(&self, _i: usize) -> Option<&str> {
+        None
+    }
+}
+
+/// Boxed (type-erased) constraint
+///
+/// Boxed constraints are themselves constraints, so problems with different
+/// types of constraints can have the same type (see, e.g., `DynProblem`)
+pub type BoxedConstraint<'a, T = f64> = Box<dyn Constraint<T> + 'a>;
+
+impl<T, C> Constraint<T> for Box<C>
+where
+    C: Constraint<T> + ?Sized,
+{
+    fn project(&self, x: &mut [T]) {
+        (**self).project(x)
+    }
+
+    fn is_convex(&self) -> bool {
+        (**self).is_convex()
+    }
+
+    fn blocks(&self) -> Option<&[usize]> {
+        (**self).blocks()
+    }
+
+    fn block_name(&self, i: usize) -> Option<&str> {
+        (**self).block_name(i)
+    }
 }
 
 /* ---------------------------------------------------------------------------- */
@@ -1,4 +1,5 @@
 use super::Constraint;
+use crate::OpEnFloat;
 
 /// The whole space, no constraints
 #[derive(Default, Clone, Copy)]
@@ -12,8 +13,8 @@ impl NoConstraints {
     }
 }
 
-impl Constraint for NoConstraints {
-    fn project(&self, _x: &mut [f64]) {}
+impl<T: OpEnFloat> Constraint<T> for NoConstraints {
+    fn project(&self, _x: &mut [T]) {}
 
     fn is_convex(&self) -> bool {
         true
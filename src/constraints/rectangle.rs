@@ -1,4 +1,5 @@
 use super::Constraint;
+use crate::{OpEnFloat, SolverError};
 
 #[derive(Clone, Copy)]
 ///
@@ -7,12 +8,12 @@ use super::Constraint;
 /// A set of the form $\\{x \in \mathbb{R}^n {}:{} x_{\min} {}\leq{} x {}\leq{} x_{\max}\\}$,
 /// where $\leq$ is meant in the element-wise sense and either of $x_{\min}$ and $x_{\max}$ can
 /// be equal to infinity.
-pub struct Rectangle<'a> {
-    xmin: Option<&'a [f64]>,
-    xmax: Option<&'a [f64]>,
+pub struct Rectangle<'a, T = f64> {
+    xmin: Option<&'a [T]>,
+    xmax: Option<&'a [T]>,
 }
 
-impl<'a> Rectangle<'a> {
+impl<'a, T: OpEnFloat> Rectangle<'a, T> {
     /// Construct a new rectangle with given $x_{\min}$ and $x_{\max}$
     ///
     /// # Arguments
@@ -26,26 +27,38 @@ impl<'a> Rectangle<'a> {
     /// a reference. You may set one of `xmin` and `xmax` to `None` (but not
     /// both).
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if:
+    /// The method returns an error if:
     ///
-    /// - Both `xmin` and `xmax` are `None` (use `NoConstraints` instead)
+    /// - Both `xmin` and `xmax` are `None` (use `NoConstraints` instead);
+    ///   this is a `SolverError::InvalidParameter`
     /// - Both `xmin` and `xmax` have been provided, but they have incompatible
-    ///   dimensions
+    ///   dimensions; this is a `SolverError::DimensionMismatch`
     ///
-    pub fn new(xmin: Option<&'a [f64]>, xmax: Option<&'a [f64]>) -> Self {
-        assert!(xmin.is_some() || xmax.is_some()); // xmin or xmax must be Some
-        assert!(
-            xmin.is_none() || xmax.is_none() || xmin.unwrap().len() == xmax.unwrap().len(),
-            "incompatible dimensions of xmin and xmax"
-        );
-        Rectangle { xmin, xmax }
+    pub fn new(xmin: Option<&'a [T]>, xmax: Option<&'a [T]>) -> Result<Self, SolverError> {
+        match (xmin, xmax) {
+            (None, None) => {
+                return Err(SolverError::InvalidParameter {
+                    name: "xmin",
+                    reason: "at least one of xmin and xmax must be provided",
+                })
+            }
+            (Some(xmin), Some(xmax)) if xmin.len() != xmax.len() => {
+                return Err(SolverError::DimensionMismatch {
+                    name: "xmax",
+                    expected: xmin.len(),
+                    actual: xmax.len(),
+                })
+            }
+            _ => {}
+        }
+        Ok(Rectangle { xmin, xmax })
     }
 }
 
-impl<'a> Constraint for Rectangle<'a> {
-    fn project(&self, x: &mut [f64]) {
+impl<'a, T: OpEnFloat> Constraint<T> for Rectangle<'a, T> {
+    fn project(&self, x: &mut [T]) {
         if let Some(xmin) = &self.xmin {
             x.iter_mut().zip(xmin.iter()).for_each(|(x_, xmin_)| {
                 if *x_ < *xmin_ {
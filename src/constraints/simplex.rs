@@ -1,43 +1,46 @@
 use super::Constraint;
+use crate::{OpEnFloat, SolverError};
 
 #[derive(Copy, Clone)]
 /// A simplex with level $\alpha$ is a set of the form
 /// $\Delta_\alpha^n = \\{x \in \mathbb{R}^n {}:{} x \geq 0, \sum_i x_i = \alpha\\}$,
 /// where $\alpha$ is a positive constant.
-pub struct Simplex {
+pub struct Simplex<T = f64> {
     /// Simplex level
-    alpha: f64,
+    alpha: T,
 }
 
-impl Simplex {
+impl<T: OpEnFloat> Simplex<T> {
     /// Construct a new simplex with given (positive) $\alpha$. The user does not need
     /// to specify the dimension of the simplex.
-    pub fn new(alpha: f64) -> Self {
-        assert!(alpha > 0.0, "alpha is nonpositive");
-        Simplex { alpha }
+    ///
+    /// Returns `SolverError::InvalidParameter` if `alpha` is not positive
+    pub fn new(alpha: T) -> Result<Self, SolverError> {
+        crate::ensure(alpha > T::zero(), "alpha", "must be positive")?;
+        Ok(Simplex { alpha })
     }
 }
 
-impl Constraint for Simplex {
+impl<T: OpEnFloat> Constraint<T> for Simplex<T> {
     /// Project onto $\Delta_\alpha^n$ using Condat's fast projection algorithm.
     ///
     /// See: Laurent Condat. Fast Projection onto the Simplex and the $\ell_1$ Ball.
     /// <em>Mathematical Programming, Series A,</em> Springer, 2016, 158 (1), pp.575-585.
     /// ⟨<a href="https://dx.doi.org/10.1007/s10107-015-0946-6">10.1007/s10107-015-0946-6</a>⟩.
-    fn project(&self, x: &mut [f64]) {
-        let a = &self.alpha;
+    fn project(&self, x: &mut [T]) {
+        let a = self.alpha;
 
         // ---- step 1
-        let mut v = Vec::<f64>::with_capacity(x.len()); // vector containing x[0]
+        let mut v = Vec::<T>::with_capacity(x.len()); // vector containing x[0]
         v.push(x[0]);
         let mut v_size_old: i64 = -1; // 64 bit signed int
-        let mut v_tilde: Vec<f64> = Vec::new(); // empty vector of f64
-        let mut rho: f64 = x[0] - a; // 64 bit float
+        let mut v_tilde: Vec<T> = Vec::new(); // empty vector
+        let mut rho: T = x[0] - a;
 
         // ---- step 2
         x.iter().skip(1).for_each(|x_n| {
             if *x_n > rho {
-                rho += (*x_n - rho) / ((v.len() + 1) as f64);
+                rho += (*x_n - rho) / T::from_f64((v.len() + 1) as f64);
                 if rho > *x_n - a {
                     v.push(*x_n);
                 } else {
@@ -53,7 +56,7 @@ impl Constraint for Simplex {
             v_tilde.iter().for_each(|v_t_n| {
                 if *v_t_n > rho {
                     v.push(*v_t_n);
-                    rho += (*v_t_n - rho) / (v.len() as f64);
+                    rho += (*v_t_n - rho) / T::from_f64(v.len() as f64);
                 }
             });
         }
@@ -67,7 +70,7 @@ impl Constraint for Simplex {
                 if *v_n <= rho {
                     hit_list.push(n);
                     current_len_v -= 1;
-                    rho += (rho - *v_n) / (current_len_v as f64);
+                    rho += (rho - *v_n) / T::from_f64(current_len_v as f64);
                 }
             });
             hit_list.iter().rev().for_each(|target| {
@@ -79,8 +82,8 @@ impl Constraint for Simplex {
         }
 
         // ---- step 6
-        let zero: f64 = 0.0;
-        x.iter_mut().for_each(|x_n| *x_n = zero.max(*x_n - rho));
+        x.iter_mut()
+            .for_each(|x_n| *x_n = T::zero().max(*x_n - rho));
     }
 
     fn is_convex(&self) -> bool {
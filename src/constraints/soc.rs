@@ -1,5 +1,5 @@
 use super::Constraint;
-use crate::matrix_operations;
+use crate::{matrix_operations, OpEnFloat, SolverError};
 
 #[derive(Clone, Copy)]
 ///
@@ -17,11 +17,11 @@ use crate::matrix_operations;
 /// 1996 doctoral dissertation: Projection Algorithms and Monotone Operators
 /// (p. 40, Theorem 3.3.6).
 ///
-pub struct SecondOrderCone {
-    alpha: f64,
+pub struct SecondOrderCone<T = f64> {
+    alpha: T,
 }
 
-impl SecondOrderCone {
+impl<T: OpEnFloat> SecondOrderCone<T> {
     /// Construct a new instance of SecondOrderCone with parameter `alpha`
     ///
     /// A second-order cone with parameter alpha is the set
@@ -36,16 +36,17 @@ impl SecondOrderCone {
     ///
     /// - `alpha`: parameter $\alpha$
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The method panics if the given parameter `alpha` is nonpositive.
-    pub fn new(alpha: f64) -> SecondOrderCone {
-        assert!(alpha > 0.0); // alpha must be positive
-        SecondOrderCone { alpha }
+    /// Returns `SolverError::InvalidParameter` if the given parameter `alpha`
+    /// is nonpositive.
+    pub fn new(alpha: T) -> Result<SecondOrderCone<T>, SolverError> {
+        crate::ensure(alpha > T::zero(), "alpha", "must be positive")?;
+        Ok(SecondOrderCone { alpha })
     }
 }
 
-impl Constraint for SecondOrderCone {
+impl<T: OpEnFloat> Constraint<T> for SecondOrderCone<T> {
     /// Project on the second-order cone (updates the given vector/slice)
     ///
     /// # Arguments
@@ -57,7 +58,7 @@ impl Constraint for SecondOrderCone {
     ///
     /// The methods panics is the length of `x` is less than 2.
     ///
-    fn project(&self, x: &mut [f64]) {
+    fn project(&self, x: &mut [T]) {
         // x = (z, r)
         let n = x.len();
         assert!(n >= 2, "x must be of dimension at least 2");
@@ -65,9 +66,9 @@ impl Constraint for SecondOrderCone {
         let r = x[n - 1];
         let norm_z = matrix_operations::norm2(z);
         if self.alpha * norm_z <= -r {
-            x.iter_mut().for_each(|v| *v = 0.0);
+            x.iter_mut().for_each(|v| *v = T::zero());
         } else if norm_z > self.alpha * r {
-            let beta = (self.alpha * norm_z + r) / (self.alpha.powi(2) + 1.0);
+            let beta = (self.alpha * norm_z + r) / (self.alpha.powi(2) + T::one());
             x[..n - 1]
                 .iter_mut()
                 .for_each(|v| *v *= self.alpha * beta / norm_z);
@@ -1,4 +1,4 @@
-use crate::matrix_operations;
+use crate::{matrix_operations, SolverError};
 
 use super::*;
 use rand;
@@ -74,25 +74,25 @@ fn t_halfspace_project_outside() {
 }
 
 #[test]
-#[should_panic]
 fn t_finite_set_inconsistent_dimensions() {
     let x1 = vec![1.0; 2];
     let x2 = vec![0.0; 3];
     let data: &[&[f64]] = &[&x1, &x2];
-    let _f = FiniteSet::new(data);
+    let f = FiniteSet::new(data);
+    assert!(matches!(f, Err(SolverError::DimensionMismatch { .. })));
 }
 
 #[test]
-#[should_panic]
 fn t_finite_set_empty_data() {
-    let mut _data: &[&[f64]] = &[];
-    let _f = FiniteSet::new(_data);
+    let data: &[&[f64]] = &[];
+    let f = FiniteSet::new(data);
+    assert!(matches!(f, Err(SolverError::InvalidParameter { .. })));
 }
 
 #[test]
 fn t_finite_set() {
     let data: &[&[f64]] = &[&[0.0, 0.0], &[1.0, 1.0], &[0.0, 1.0], &[1.0, 0.0]];
-    let finite_set = FiniteSet::new(data);
+    let finite_set = FiniteSet::new(data).unwrap();
     let mut x = [0.7, 0.6];
     finite_set.project(&mut x);
     unit_test_utils::assert_nearly_equal_array(
@@ -135,7 +135,7 @@ fn t_finite_set() {
 fn t_rectangle_bounded() {
     let xmin = vec![2.0; 5];
     let xmax = vec![4.5; 5];
-    let rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..]));
+    let rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..])).unwrap();
     let mut x = [1.0, 2.0, 3.0, 4.0, 5.0];
 
     rectangle.project(&mut x);
@@ -153,7 +153,7 @@ fn t_rectangle_bounded() {
 fn t_rectangle_infinite_bounds() {
     let xmin = [-1.0, 2.0, std::f64::NEG_INFINITY];
     let xmax = [1.0, std::f64::INFINITY, 5.0];
-    let rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..]));
+    let rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..])).unwrap();
     let mut x = [-2.0, 3.0, 1.0];
 
     rectangle.project(&mut x);
@@ -168,18 +168,25 @@ fn t_rectangle_infinite_bounds() {
 }
 
 #[test]
-#[should_panic]
 fn t_rectangle_incompatible_dims() {
     let xmin = vec![1.0; 5];
     let xmax = vec![2.0; 4];
-    let _rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..]));
+    let rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..]));
+    assert_eq!(
+        Err(SolverError::DimensionMismatch {
+            name: "xmax",
+            expected: 5,
+            actual: 4
+        }),
+        rectangle.map(|_| ())
+    );
 }
 
 #[test]
 fn t_rectangle_bounded_negative_entries() {
     let xmin = [-5.0, -4.0, -3.0, -2.0, -1.0, 0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
     let xmax = [-1.0, -2.0, -1.0, 2.0, 1.0, 0.0, 4.0, 6.0, 9.0, 100.0, 500.0];
-    let rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..]));
+    let rectangle = Rectangle::new(Some(&xmin[..]), Some(&xmax[..])).unwrap();
     let mut x = [-6.0, -3.0, 0.0, 3.0, -5.0, 1.0, 2.0, 3.0, -1.0, 0.0, 0.0];
 
     rectangle.project(&mut x);
@@ -196,7 +203,7 @@ fn t_rectangle_bounded_negative_entries() {
 #[test]
 fn t_rectangle_only_xmin() {
     let xmin = vec![2.0; 5];
-    let rectangle = Rectangle::new(Some(&xmin[..]), None);
+    let rectangle = Rectangle::new(Some(&xmin[..]), None).unwrap();
     let mut x = [1.0, 2.0, 3.0, 4.0, 5.0];
 
     rectangle.project(&mut x);
@@ -213,7 +220,7 @@ fn t_rectangle_only_xmin() {
 #[test]
 fn t_rectangle_only_xmax() {
     let xmax = vec![-3.0; 5];
-    let rectangle = Rectangle::new(None, Some(&xmax[..]));
+    let rectangle = Rectangle::new(None, Some(&xmax[..])).unwrap();
     let mut x = [-10.0, -20.0, 0.0, 5.0, 3.0];
 
     rectangle.project(&mut x);
@@ -231,7 +238,7 @@ fn t_rectangle_only_xmax() {
 fn t_ball2_at_origin() {
     let radius = 1.0;
     let mut x = [1.0, 1.0];
-    let ball = Ball2::new(None, radius);
+    let ball = Ball2::new(None, radius).unwrap();
 
     ball.project(&mut x);
 
@@ -251,7 +258,7 @@ fn t_ball2_at_origin() {
 fn t_ball2_at_origin_different_radius_outside() {
     let radius = 0.8;
     let mut x = [1.0, 1.0];
-    let ball = Ball2::new(None, radius);
+    let ball = Ball2::new(None, radius).unwrap();
     ball.project(&mut x);
     let norm_proj_x = crate::matrix_operations::norm2(&x);
     unit_test_utils::assert_nearly_equal(radius, norm_proj_x, 1e-10, 1e-12, "wrong norm");
@@ -261,7 +268,7 @@ fn t_ball2_at_origin_different_radius_outside() {
 fn t_ball2_at_origin_different_radius_inside() {
     let radius = 0.8;
     let mut x = [-0.2, 0.15];
-    let ball = Ball2::new(None, radius);
+    let ball = Ball2::new(None, radius).unwrap();
     ball.project(&mut x);
     unit_test_utils::assert_nearly_equal_array(&x, &[-0.2, 0.15], 1e-10, 1e-12, "wrong");
 }
@@ -270,8 +277,8 @@ fn t_ball2_at_origin_different_radius_inside() {
 fn t_ball2_at_center_different_radius_outside() {
     let radius = 1.2;
     let mut x = [1.0, 1.0];
-    let center = [-0.8, -1.1];
-    let ball = Ball2::new(Some(&center), radius);
+    let center: [f64; 2] = [-0.8, -1.1];
+    let ball = Ball2::new(Some(&center), radius).unwrap();
     ball.project(&mut x);
     let norm_x_minus_c = crate::matrix_operations::norm2_squared_diff(&x, &center).sqrt();
     unit_test_utils::assert_nearly_equal(radius, norm_x_minus_c, 1e-10, 1e-12, "wrong norm");
@@ -282,7 +289,7 @@ fn t_ball2_at_center_different_radius_inside() {
     let radius = 1.2;
     let mut x = [-0.9, -0.85];
     let center = [-0.8, -1.1];
-    let ball = Ball2::new(Some(&center), radius);
+    let ball = Ball2::new(Some(&center), radius).unwrap();
     ball.project(&mut x);
     unit_test_utils::assert_nearly_equal_array(&[-0.9, -0.85], &x, 1e-10, 1e-12, "wrong result");
 }
@@ -292,7 +299,7 @@ fn t_ball2_elsewhere() {
     let radius = 1.0;
     let center = [1.0, 1.0];
     let mut x = [2.0, 2.0];
-    let ball = Ball2::new(Some(&center[..]), radius);
+    let ball = Ball2::new(Some(&center[..]), radius).unwrap();
 
     ball.project(&mut x);
 
@@ -317,23 +324,29 @@ fn t_no_constraints() {
 }
 
 #[test]
-#[should_panic]
 fn t_cartesian_product_constraints_incoherent_indices() {
-    let ball1 = Ball2::new(None, 1.0);
-    let ball2 = Ball2::new(None, 0.5);
-    let _cart_prod = CartesianProduct::new()
+    let ball1 = Ball2::new(None, 1.0).unwrap();
+    let ball2 = Ball2::new(None, 0.5).unwrap();
+    let cart_prod = CartesianProduct::new()
         .add_constraint(3, ball1)
+        .unwrap()
         .add_constraint(2, ball2);
+    assert!(matches!(
+        cart_prod,
+        Err(SolverError::InvalidParameter { name: "ni", .. })
+    ));
 }
 
 #[test]
 #[should_panic]
 fn t_cartesian_product_constraints_wrong_vector_dim() {
-    let ball1 = Ball2::new(None, 1.0);
-    let ball2 = Ball2::new(None, 0.5);
+    let ball1 = Ball2::new(None, 1.0).unwrap();
+    let ball2 = Ball2::new(None, 0.5).unwrap();
     let cart_prod = CartesianProduct::new()
         .add_constraint(3, ball1)
-        .add_constraint(10, ball2);
+        .unwrap()
+        .add_constraint(10, ball2)
+        .unwrap();
     let mut x = [0.0; 30];
     cart_prod.project(&mut x);
 }
@@ -344,11 +357,13 @@ fn t_cartesian_product_constraints() {
     let radius2 = 0.5;
     let idx1 = 3;
     let idx2 = 5;
-    let ball1 = Ball2::new(None, radius1);
-    let ball2 = Ball2::new(None, radius2);
+    let ball1 = Ball2::new(None, radius1).unwrap();
+    let ball2 = Ball2::new(None, radius2).unwrap();
     let cart_prod = CartesianProduct::new()
         .add_constraint(idx1, ball1)
-        .add_constraint(idx2, ball2);
+        .unwrap()
+        .add_constraint(idx2, ball2)
+        .unwrap();
     let mut x = [3.0, 4.0, 5.0, 2.0, 1.0];
     cart_prod.project(&mut x);
     let r1 = crate::matrix_operations::norm2(&x[0..idx1]);
@@ -362,22 +377,25 @@ fn t_cartesian_product_ball_and_rectangle() {
     /* Rectangle 1 */
     let xmin1 = vec![-1.0; 3];
     let xmax1 = vec![1.0; 3];
-    let rectangle1 = Rectangle::new(Some(&xmin1), Some(&xmax1));
+    let rectangle1 = Rectangle::new(Some(&xmin1), Some(&xmax1)).unwrap();
 
     /* Ball */
     let radius = 1.0;
-    let ball = Ball2::new(None, radius);
+    let ball = Ball2::new(None, radius).unwrap();
 
     /* Rectangle 2 */
     let xmin2 = vec![-0.5; 2];
     let xmax2 = vec![0.5; 2];
-    let rectangle2 = Rectangle::new(Some(&xmin2), Some(&xmax2));
+    let rectangle2 = Rectangle::new(Some(&xmin2), Some(&xmax2)).unwrap();
 
     /* Cartesian product */
     let cart_prod = CartesianProduct::new()
         .add_constraint(3, rectangle1)
+        .unwrap()
         .add_constraint(7, ball)
-        .add_constraint(9, rectangle2);
+        .unwrap()
+        .add_constraint(9, rectangle2)
+        .unwrap();
 
     /* Projection */
     let mut x = [-10.0, 0.5, 10.0, 0.01, -0.01, 0.1, 10.0, -1.0, 1.0];
@@ -405,7 +423,7 @@ fn t_cartesian_product_ball_and_rectangle() {
 
 #[test]
 fn t_second_order_cone_case_i() {
-    let soc = SecondOrderCone::new(1.0);
+    let soc = SecondOrderCone::new(1.0).unwrap();
     let mut x = vec![1.0, 1.0, 1.42];
     let x_copy = x.clone();
     soc.project(&mut x);
@@ -415,7 +433,7 @@ fn t_second_order_cone_case_i() {
 #[test]
 fn t_second_order_cone_case_ii() {
     let alpha = 0.5;
-    let soc = SecondOrderCone::new(alpha);
+    let soc = SecondOrderCone::new(alpha).unwrap();
     let mut x = vec![1.0, 1.0, -0.71];
     soc.project(&mut x);
     let expected = vec![0.0; 3];
@@ -430,8 +448,8 @@ fn t_second_order_cone_case_ii() {
 
 #[test]
 fn t_second_order_cone_case_iii() {
-    let alpha = 1.5;
-    let soc = SecondOrderCone::new(alpha);
+    let alpha: f64 = 1.5;
+    let soc = SecondOrderCone::new(alpha).unwrap();
     let mut x = vec![1.0, 1.0, 0.1];
     soc.project(&mut x);
     // make sure the new `x` is in the cone
@@ -442,24 +460,22 @@ fn t_second_order_cone_case_iii() {
 }
 
 #[test]
-#[should_panic]
 fn t_second_order_cone_illegal_alpha_i() {
     let alpha = 0.0;
-    let _soc = SecondOrderCone::new(alpha);
+    assert!(SecondOrderCone::new(alpha).is_err());
 }
 
 #[test]
-#[should_panic]
 fn t_second_order_cone_illegal_alpha_ii() {
     let alpha = -1.0;
-    let _soc = SecondOrderCone::new(alpha);
+    assert!(SecondOrderCone::new(alpha).is_err());
 }
 
 #[test]
 #[should_panic]
 fn t_second_order_cone_short_vector() {
     let alpha = 1.0;
-    let soc = SecondOrderCone::new(alpha);
+    let soc = SecondOrderCone::new(alpha).unwrap();
     let mut _x = vec![1.0];
     soc.project(&mut _x);
 }
@@ -467,15 +483,19 @@ fn t_second_order_cone_short_vector() {
 #[test]
 fn t_cartesian_product_dimension() {
     let data: &[&[f64]] = &[&[0.0, 0.0], &[1.0, 1.0]];
-    let finite_set = FiniteSet::new(data);
+    let finite_set = FiniteSet::new(data).unwrap();
     let finite_set_2 = finite_set;
-    let ball = Ball2::new(None, 1.0);
+    let ball = Ball2::new(None, 1.0).unwrap();
     let no_constraints = NoConstraints::new();
     let cartesian = CartesianProduct::new_with_capacity(4)
         .add_constraint(2, finite_set)
+        .unwrap()
         .add_constraint(4, finite_set_2)
+        .unwrap()
         .add_constraint(7, no_constraints)
-        .add_constraint(10, ball);
+        .unwrap()
+        .add_constraint(10, ball)
+        .unwrap();
     assert!(10 == cartesian.dimension());
 
     // let's do a projection to make sure this works
@@ -508,13 +528,17 @@ fn t_cartesian_product_dimension() {
 fn t_cartesian_ball_no_constraint() {
     let xc = [1., 0., 0.];
     let radius = 1.0;
-    let ball2 = Ball2::new(Some(&xc), radius);
+    let ball2 = Ball2::new(Some(&xc), radius).unwrap();
     let no_constraints = NoConstraints::new();
     let cartesian = CartesianProduct::new_with_capacity(4)
         .add_constraint(2, no_constraints)
+        .unwrap()
         .add_constraint(5, ball2)
+        .unwrap()
         .add_constraint(8, no_constraints)
-        .add_constraint(9, no_constraints);
+        .unwrap()
+        .add_constraint(9, no_constraints)
+        .unwrap();
     assert_eq!(9, cartesian.dimension());
     let mut x = [100., -200., 0.5, 1.5, 3.5, 1000., 5., -500., 2_000_000.];
     cartesian.project(&mut x);
@@ -538,7 +562,7 @@ fn t_cartesian_ball_no_constraint() {
 
 #[test]
 fn t_ball_inf_origin() {
-    let ball_inf = BallInf::new(None, 1.0);
+    let ball_inf = BallInf::new(None, 1.0).unwrap();
     let mut x = [0.0, -0.5, 0.5, 1.5, 3.5, 0.8, 1.1, -5.0, -10.0];
     let x_correct = [0.0, -0.5, 0.5, 1.0, 1.0, 0.8, 1.0, -1.0, -1.0];
     ball_inf.project(&mut x);
@@ -555,7 +579,7 @@ fn t_ball_inf_origin() {
 #[test]
 fn t_ball_inf_center() {
     let xc = [5.0, -6.0];
-    let ball_inf = BallInf::new(Some(&xc), 1.5);
+    let ball_inf = BallInf::new(Some(&xc), 1.5).unwrap();
     let mut x = [11.0, -0.5];
     ball_inf.project(&mut x);
     unit_test_utils::assert_nearly_equal_array(&[6.5, -4.5], &x, 1e-10, 1e-12, "upper right");
@@ -587,35 +611,35 @@ fn t_ball_inf_center() {
 
 #[test]
 fn t_is_convex_ball_inf() {
-    let ball_inf = BallInf::new(None, 1.5);
+    let ball_inf = BallInf::new(None, 1.5).unwrap();
     assert!(ball_inf.is_convex());
 }
 
 #[test]
 fn t_is_convex_ball2() {
-    let ball_2 = Ball2::new(None, 1.0);
+    let ball_2 = Ball2::new(None, 1.0).unwrap();
     assert!(ball_2.is_convex());
 }
 
 #[test]
 fn t_is_convex_finite_set() {
-    let finite = FiniteSet::new(&[&[1.0, 2.0, 3.0]]);
+    let finite = FiniteSet::new(&[&[1.0, 2.0, 3.0]]).unwrap();
     assert!(finite.is_convex());
 
-    let finite_noncvx = FiniteSet::new(&[&[1.0, 2.0], &[3.0, 4.0]]);
+    let finite_noncvx = FiniteSet::new(&[&[1.0, 2.0], &[3.0, 4.0]]).unwrap();
     assert!(!finite_noncvx.is_convex());
 }
 
 #[test]
 fn t_is_convex_soc() {
-    let soc = SecondOrderCone::new(2.0);
+    let soc = SecondOrderCone::new(2.0).unwrap();
     assert!(soc.is_convex());
 }
 
 #[test]
 fn t_is_convex_zero() {
     let zero = Zero::new();
-    assert!(zero.is_convex());
+    assert!(Constraint::<f64>::is_convex(&zero));
 }
 
 #[test]
@@ -628,17 +652,20 @@ fn t_is_convex_halfspace() {
 
 #[test]
 fn t_is_convex_cartesian_product() {
-    let ball_2 = Ball2::new(None, 1.0);
-    let ball_inf = BallInf::new(None, 1.5);
-    let finite = FiniteSet::new(&[&[1.0, 2.0, 3.0]]);
+    let ball_2 = Ball2::new(None, 1.0).unwrap();
+    let ball_inf = BallInf::new(None, 1.5).unwrap();
+    let finite = FiniteSet::new(&[&[1.0, 2.0, 3.0]]).unwrap();
     let cartesian_product = CartesianProduct::new()
         .add_constraint(4, ball_2)
+        .unwrap()
         .add_constraint(6, ball_inf)
-        .add_constraint(9, finite);
+        .unwrap()
+        .add_constraint(9, finite)
+        .unwrap();
     assert!(cartesian_product.is_convex());
 
-    let finite_noncvx = FiniteSet::new(&[&[1.0, 2.0], &[3.0, 4.0]]);
-    let cartesian_product = cartesian_product.add_constraint(10, finite_noncvx);
+    let finite_noncvx = FiniteSet::new(&[&[1.0, 2.0], &[3.0, 4.0]]).unwrap();
+    let cartesian_product = cartesian_product.add_constraint(10, finite_noncvx).unwrap();
     assert!(!cartesian_product.is_convex());
 }
 
@@ -654,7 +681,7 @@ fn t_hyperplane_is_convex() {
 fn t_simplex_projection() {
     let mut x = [1.0, 2.0, 3.0];
     let alpha = 3.0;
-    let my_simplex = Simplex::new(alpha);
+    let my_simplex = Simplex::new(alpha).unwrap();
     my_simplex.project(&mut x);
     unit_test_utils::assert_nearly_equal(
         crate::matrix_operations::sum(&x),
@@ -676,7 +703,7 @@ fn t_simplex_projection_random_spam() {
             .for_each(|xi| *xi = scale * (2. * rand::random::<f64>() - 1.));
         let alpha_scale = 20.;
         let alpha = alpha_scale * rand::random::<f64>();
-        let simplex = Simplex::new(alpha);
+        let simplex = Simplex::new(alpha).unwrap();
         simplex.project(&mut x);
         println!("x = {:?}", x);
         assert!(x.iter().all(|&xi| xi >= -1e-12));
@@ -700,7 +727,7 @@ fn t_simplex_projection_random_optimality() {
                 .for_each(|xi| *xi = scale * (2. * rand::random::<f64>() - 1.));
             let alpha_scale = 100.;
             let alpha = alpha_scale * rand::random::<f64>();
-            let simplex = Simplex::new(alpha);
+            let simplex = Simplex::new(alpha).unwrap();
             let y = z.clone();
             simplex.project(&mut z);
             for j in 0..n {
@@ -718,15 +745,13 @@ fn t_simplex_projection_random_optimality() {
 }
 
 #[test]
-#[should_panic]
 fn t_simplex_alpha_zero() {
-    let _ = Simplex::new(0.);
+    assert!(Simplex::new(0.).is_err());
 }
 
 #[test]
-#[should_panic]
 fn t_simplex_alpha_negative() {
-    let _ = Simplex::new(-1.);
+    assert!(Simplex::new(-1.).is_err());
 }
 
 #[test]
@@ -742,7 +767,7 @@ fn t_ball1_random_optimality_conditions() {
                 .for_each(|xi| *xi = scale * (2. * rand::random::<f64>() - 1.));
             x.copy_from_slice(&x_star);
             let radius = 5. * rand::random::<f64>();
-            let ball1 = Ball1::new(None, radius);
+            let ball1 = Ball1::new(None, radius).unwrap();
             ball1.project(&mut x_star);
             // make sure |x|_1 <= radius
             assert!(
@@ -791,7 +816,7 @@ fn t_ball1_random_optimality_conditions_centered() {
             xc.iter_mut()
                 .for_each(|xi| *xi = scale_xc * (2. * rand::random::<f64>() - 1.));
             let radius = 5. * rand::random::<f64>();
-            let ball1 = Ball1::new(Some(&xc), radius);
+            let ball1 = Ball1::new(Some(&xc), radius).unwrap();
             ball1.project(&mut x);
             // x = x - xc
             x.iter_mut()
@@ -810,7 +835,7 @@ fn t_sphere2_no_center() {
     let radius = 0.9;
     let mut x_out = [1.0, 1.0];
     let mut x_in = [-0.3, -0.2];
-    let unit_sphere = Sphere2::new(None, radius);
+    let unit_sphere = Sphere2::new(None, radius).unwrap();
     unit_sphere.project(&mut x_out);
     unit_sphere.project(&mut x_in);
     let norm_out = crate::matrix_operations::norm2(&x_out);
@@ -823,7 +848,7 @@ fn t_sphere2_no_center() {
 fn t_sphere2_no_center_projection_of_zero() {
     let radius = 0.9;
     let mut x = [0.0, 0.0];
-    let unit_sphere = Sphere2::new(None, radius);
+    let unit_sphere = Sphere2::new(None, radius).unwrap();
     unit_sphere.project(&mut x);
     let norm_result = crate::matrix_operations::norm2(&x);
     unit_test_utils::assert_nearly_equal(radius, norm_result, 1e-10, 1e-12, "norm_out is not 1.0");
@@ -834,7 +859,7 @@ fn t_sphere2_center() {
     let radius = 1.3;
     let center = [-3.0, 5.0];
     let mut x = [1.0, 1.0];
-    let unit_sphere = Sphere2::new(Some(&center), radius);
+    let unit_sphere = Sphere2::new(Some(&center), radius).unwrap();
 
     unit_sphere.project(&mut x);
     let mut x_minus_c = [0.0; 2];
@@ -854,7 +879,7 @@ fn t_sphere2_center_projection_of_center() {
     let radius = 1.3;
     let center = [-3.0, 5.0];
     let mut x = [-3.0, 5.0];
-    let unit_sphere = Sphere2::new(Some(&center), radius);
+    let unit_sphere = Sphere2::new(Some(&center), radius).unwrap();
 
     unit_sphere.project(&mut x);
     let mut x_minus_c = [0.0; 2];
@@ -870,9 +895,8 @@ fn t_sphere2_center_projection_of_center() {
 }
 
 #[test]
-#[should_panic]
 fn t_ball1_alpha_negative() {
-    let _ = Ball1::new(None, -1.);
+    assert!(Ball1::new(None, -1.).is_err());
 }
 
 #[test]
@@ -928,7 +952,7 @@ fn t_affine_space() {
         0.5, 0.1, 0.2, -0.3, -0.6, 0.3, 0., 0.5, 1.0, 0.1, -1.0, -0.4,
     ];
     let b = vec![1., 2., -0.5];
-    let affine_set = AffineSpace::new(a, b);
+    let affine_set = AffineSpace::new(a, b).unwrap();
     let mut x = [1., -2., -0.3, 0.5];
     affine_set.project(&mut x);
     let x_correct = [
@@ -952,7 +976,7 @@ fn t_affine_space_larger() {
         1.0f64, 1., 1., 0., 0., 0., 1., 1., 1., 0., 0., 0., 1., 1., 1., -1., 4., -1., 0., 2.,
     ];
     let b = vec![1., -2., 3., 4.];
-    let affine_set = AffineSpace::new(a, b);
+    let affine_set = AffineSpace::new(a, b).unwrap();
     let mut x = [10., 11., -9., 4., 5.];
     affine_set.project(&mut x);
     let x_correct = [
@@ -975,7 +999,7 @@ fn t_affine_space_larger() {
 fn t_affine_space_single_row() {
     let a = vec![1., 1., 1., 1.];
     let b = vec![1.];
-    let affine_set = AffineSpace::new(a, b);
+    let affine_set = AffineSpace::new(a, b).unwrap();
     let mut x = [5., 6., 10., 25.];
     affine_set.project(&mut x);
     let s = x.iter().sum();
@@ -983,9 +1007,94 @@ fn t_affine_space_single_row() {
 }
 
 #[test]
-#[should_panic]
 fn t_affine_space_wrong_dimensions() {
     let a = vec![0.5, 0.1, 0.2, -0.3, -0.6, 0.3, 0., 0.5, 1.0, 0.1, -1.0];
     let b = vec![1., 2., -0.5];
-    let _ = AffineSpace::new(a, b);
+    assert!(AffineSpace::new(a, b).is_err());
+}
+
+#[test]
+fn t_single_precision_projections() {
+    let mut x = [3.0_f32, 4.0];
+    Ball2::new(None, 1.0_f32).unwrap().project(&mut x);
+    unit_test_utils::assert_nearly_equal_array(&[0.6_f32, 0.8], &x, 1e-6, 1e-7, "ball2");
+
+    let xmin = [-1.0_f32, 0.0];
+    let xmax = [1.0_f32, 0.5];
+    let mut x = [2.0_f32, -1.0];
+    Rectangle::new(Some(&xmin), Some(&xmax))
+        .unwrap()
+        .project(&mut x);
+    unit_test_utils::assert_nearly_equal_array(&[1.0_f32, 0.0], &x, 1e-6, 1e-7, "rectangle");
+
+    let mut x = [0.5_f32, 1.5, 2.0];
+    Simplex::new(1.0_f32).unwrap().project(&mut x);
+    unit_test_utils::assert_nearly_equal_array(&[0.0_f32, 0.25, 0.75], &x, 1e-6, 1e-7, "simplex");
+
+    let cart_prod = CartesianProduct::new()
+        .add_constraint(2, Ball2::new(None, 1.0_f32).unwrap())
+        .unwrap()
+        .add_constraint(3, Zero::new())
+        .unwrap();
+    let mut x = [0.0_f32, 2.0, 5.0];
+    cart_prod.project(&mut x);
+    unit_test_utils::assert_nearly_equal_array(&[0.0_f32, 1.0, 0.0], &x, 1e-6, 1e-7, "cart");
+}
+
+#[test]
+fn t_group_norm_ball() {
+    let ball = GroupNormBall::new(2.0, &[2, 2, 2]).unwrap();
+    assert_eq!(6, ball.dimension());
+    // norms of the groups: 5, 1 and 0, which are projected on (2, 0, 0)
+    let mut x = [3.0, 4.0, 0.0, -1.0, 0.0, 0.0];
+    ball.project(&mut x);
+    unit_test_utils::assert_nearly_equal_array(
+        &[1.2, 1.6, 0.0, 0.0, 0.0, 0.0],
+        &x,
+        1e-12,
+        1e-12,
+        "group norm ball",
+    );
+    // points in the ball are not moved
+    let mut x = [0.6, -0.8, 0.5, 0.0, 0.0, 0.3];
+    let x_copy = x;
+    ball.project(&mut x);
+    assert_eq!(x_copy, x);
+
+    assert!(GroupNormBall::new(0.0, &[2]).is_err());
+    assert!(GroupNormBall::new(1.0, &[2, 0]).is_err());
+    assert!(GroupNormBall::<f64>::new(1.0, &[]).is_err());
+}
+
+#[test]
+fn t_group_norm_ball_random_optimality_conditions() {
+    let group_sizes = [3, 1, 2, 4];
+    let ball = GroupNormBall::new(1.5, &group_sizes).unwrap();
+    let group_norm = |z: &[f64]| {
+        let mut start = 0;
+        group_sizes.iter().fold(0.0, |sum, &size| {
+            start += size;
+            sum + matrix_operations::norm2(&z[start - size..start])
+        })
+    };
+    for _ in 0..200 {
+        let x: Vec<f64> = (0..10).map(|_| 4. * rand::random::<f64>() - 2.).collect();
+        let mut x_star = x.clone();
+        ball.project(&mut x_star);
+        assert!(group_norm(&x_star) <= 1.5 * (1. + 1e-10));
+        // <x - x_star, z - x_star> <= 0 for all z in the ball
+        for _ in 0..50 {
+            let mut z: Vec<f64> = (0..10).map(|_| 4. * rand::random::<f64>() - 2.).collect();
+            ball.project(&mut z);
+            let inner = x
+                .iter()
+                .zip(x_star.iter())
+                .zip(z.iter())
+                .fold(0.0, |sum, ((xi, si), zi)| sum + (xi - si) * (zi - si));
+            assert!(
+                inner <= 1e-10,
+                "optimality conditions failed for group norm ball"
+            );
+        }
+    }
 }
@@ -0,0 +1,171 @@
+//! Active sets of constraints at a solution
+//!
+//!
+use crate::{constraints::Constraint, core::OracleCalls, OpEnFloat};
+
+/// Indices of the coordinates of a point at which a set of constraints is
+/// active (within a tolerance)
+///
+/// A coordinate $i$ of $x \in C$ is *active at its lower bound* if the
+/// constraints prevent it from decreasing by the tolerance $\epsilon$, that
+/// is, if $\Pi_C(x - \epsilon e_i) \neq x - \epsilon e_i$ in the $i$-th
+/// coordinate, and *active at its upper bound* if the same holds for
+/// $x + \epsilon e_i$. For a box, these are the coordinates within
+/// $\epsilon$ of their lower and upper bounds respectively; for an
+/// equality constraint (a box with equal bounds), a coordinate is active at
+/// both bounds.
+///
+/// Optimizers report the active sets in their status if asked to (see
+/// `PANOCOptimizer::with_active_set_tolerance` and
+/// `AlmOptimizer::with_active_set_tolerance`), e.g., to detect saturated
+/// actuators or to warm-start the structure of subsequent computations.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{constraints::Rectangle, core::ActiveSet};
+///
+/// let xmin = [-1.0, -1.0, -1.0];
+/// let xmax = [1.0, 1.0, 1.0];
+/// let bounds = Rectangle::new(Some(&xmin), Some(&xmax))?;
+/// let active_set = ActiveSet::identify(&bounds, &[-1.0, 0.2, 0.999_999], 1e-4);
+/// assert_eq!(&[0], active_set.lower());
+/// assert_eq!(&[2], active_set.upper());
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveSet {
+    /// coordinates which are active at their lower bounds (increasing)
+    lower: Vec<usize>,
+    /// coordinates which are active at their upper bounds (increasing)
+    upper: Vec<usize>,
+}
+
+impl ActiveSet {
+    /// Identifies the active set of `set` at the point `x` (which is assumed
+    /// to be in `set`) within `tolerance`
+    ///
+    /// This involves $2n$ projections on the set, where $n$ is the
+    /// dimension of `x`, and the allocation of a vector of dimension $n$.
+    pub fn identify<T, C>(set: &C, x: &[T], tolerance: T) -> Self
+    where
+        T: OpEnFloat,
+        C: Constraint<T> + ?Sized,
+    {
+        Self::identify_counted(set, x, tolerance, &mut OracleCalls::default())
+    }
+
+    /// Same as `identify`, but the projections are counted in `oracle_calls`
+    pub(crate) fn identify_counted<T, C>(
+        set: &C,
+        x: &[T],
+        tolerance: T,
+        oracle_calls: &mut OracleCalls,
+    ) -> Self
+    where
+        T: OpEnFloat,
+        C: Constraint<T> + ?Sized,
+    {
+        let mut active_set = ActiveSet::default();
+        let mut probe = x.to_vec();
+        for (i, &x_i) in x.iter().enumerate() {
+            if Self::is_blocked(set, &mut probe, i, x, x_i - tolerance, oracle_calls) {
+                active_set.lower.push(i);
+            }
+            if Self::is_blocked(set, &mut probe, i, x, x_i + tolerance, oracle_calls) {
+                active_set.upper.push(i);
+            }
+        }
+        active_set
+    }
+
+    /// Whether the projection of `x` with its `i`-th coordinate replaced by
+    /// `value` changes the `i`-th coordinate (`probe` is overwritten)
+    fn is_blocked<T, C>(
+        set: &C,
+        probe: &mut [T],
+        i: usize,
+        x: &[T],
+        value: T,
+        oracle_calls: &mut OracleCalls,
+    ) -> bool
+    where
+        T: OpEnFloat,
+        C: Constraint<T> + ?Sized,
+    {
+        probe.copy_from_slice(x);
+        probe[i] = value;
+        set.project(probe);
+        oracle_calls.projections += 1;
+        probe[i] != value
+    }
+
+    /// Coordinates which are active at their lower bounds, in increasing order
+    pub fn lower(&self) -> &[usize] {
+        &self.lower
+    }
+
+    /// Coordinates which are active at their upper bounds, in increasing order
+    pub fn upper(&self) -> &[usize] {
+        &self.upper
+    }
+
+    /// Coordinates which are active at either bound, in increasing order
+    pub fn indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .lower
+            .iter()
+            .chain(self.upper.iter())
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Whether no coordinate is active
+    pub fn is_empty(&self) -> bool {
+        self.lower.is_empty() && self.upper.is_empty()
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::{Ball2, NoConstraints, Rectangle};
+
+    #[test]
+    fn t_active_set_box() {
+        let xmin = [-1.0, 0.0, 2.0, 3.0];
+        let xmax = [1.0, 0.0, 4.0, 5.0];
+        let bounds = Rectangle::new(Some(&xmin), Some(&xmax)).unwrap();
+        let mut oracle_calls = OracleCalls::default();
+        let active_set = ActiveSet::identify_counted(
+            &bounds,
+            &[0.99, 0.0, 2.0 + 1e-7, 4.0],
+            1e-6,
+            &mut oracle_calls,
+        );
+        assert_eq!(8, oracle_calls.projections());
+        assert_eq!(&[1, 2], active_set.lower());
+        assert_eq!(&[1], active_set.upper());
+        assert_eq!(vec![1, 2], active_set.indices());
+        assert!(!active_set.is_empty());
+    }
+
+    #[test]
+    fn t_active_set_other_sets() {
+        assert!(ActiveSet::identify(&NoConstraints::new(), &[1.0, 2.0], 1e-3).is_empty());
+        let ball = Ball2::new(None, 1.0).unwrap();
+        assert!(ActiveSet::identify(&ball, &[0.5, 0.0], 1e-3).is_empty());
+        // on the boundary of a ball, the set blocks the outward direction and,
+        // since it is curved, the tangential directions
+        let active_set = ActiveSet::identify(&ball, &[1.0, 0.0], 1e-3);
+        assert_eq!(&[1], active_set.lower());
+        assert_eq!(&[0, 1], active_set.upper());
+    }
+}
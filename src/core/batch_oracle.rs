@@ -0,0 +1,310 @@
+//! Batched cost-and-gradient oracles
+//!
+//! Costs which are evaluated on accelerators (e.g., neural networks on a
+//! GPU) are much cheaper per point when they are evaluated at many points at
+//! once, since the cost of a kernel launch is amortized. A [`BatchOracle`]
+//! wraps a closure which computes the cost and its gradient at a batch of
+//! points, and provides the cost and the gradient which are passed to a
+//! `Problem`; the values at the points of the last batch are remembered.
+//!
+//! The solvers announce the points where they will (probably) evaluate the
+//! oracles via the trait [`BatchPrefetch`]: with
+//! `PANOCOptimizer::with_batch_line_search`, PANOC evaluates the
+//! first candidates of its line search (with $\tau = 1, 1/2, 1/4, \ldots$)
+//! in a single batch, so that a line search usually costs a single batch
+//! instead of several sequential evaluations.
+//!
+//! [`BatchOracle`]: struct.BatchOracle.html
+//! [`BatchPrefetch`]: trait.BatchPrefetch.html
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{constraints::Ball2, core::batch_oracle::BatchOracle, panoc::*, *};
+//!
+//! // f(u) = u_0^2 + 2 u_1^2 at a batch of points, stored one after the other
+//! let oracle = BatchOracle::new(|points: &[f64], costs: &mut [f64], grads: &mut [f64]| {
+//!     for ((u, cost), grad) in points.chunks(2).zip(costs.iter_mut()).zip(grads.chunks_mut(2)) {
+//!         *cost = u[0] * u[0] + 2.0 * u[1] * u[1];
+//!         grad[0] = 2.0 * u[0];
+//!         grad[1] = 4.0 * u[1];
+//!     }
+//!     Ok(())
+//! });
+//! let bounds = Ball2::new(None, 1.0)?;
+//! let problem = Problem::new(&bounds, oracle.gradient(), oracle.cost());
+//! let mut cache = PANOCCache::new(2, 1e-6, 5)?;
+//! let mut u = [0.5, -0.3];
+//! let status = PANOCOptimizer::new(problem, &mut cache)
+//!     .with_batch_line_search(&oracle, 3)?
+//!     .solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!(oracle.num_points() >= oracle.num_batches());
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+use crate::{FunctionCallResult, OpEnFloat};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex, MutexGuard, PoisonError,
+};
+
+/// Oracle which can evaluate the cost and its gradient at a batch of points
+/// ahead of the requests of a solver
+///
+/// Batch oracles are `Sync`, so that the optimizers which borrow them can be
+/// sent to other threads.
+pub trait BatchPrefetch<T = f64>: Sync {
+    /// Evaluates the cost and its gradient at `points`, which are stored one
+    /// after the other and have dimension `dimension`, and remembers the
+    /// values for the subsequent requests of the solver
+    fn prefetch(&self, points: &[T], dimension: usize) -> FunctionCallResult;
+}
+
+/// Memory of the last batch of evaluations
+struct BatchMemory<T> {
+    /// points of the last batch, one after the other
+    points: Vec<T>,
+    /// dimension of the points
+    dimension: usize,
+    /// costs at the points
+    costs: Vec<T>,
+    /// gradients at the points, one after the other
+    gradients: Vec<T>,
+    /// whether the above values are valid
+    valid: bool,
+}
+
+impl<T: OpEnFloat> BatchMemory<T> {
+    /// Index, in the last batch, of `u` (if any)
+    fn find(&self, u: &[T]) -> Option<usize> {
+        if !self.valid || self.dimension != u.len() {
+            return None;
+        }
+        self.points
+            .chunks_exact(self.dimension)
+            .position(|point| point == u)
+    }
+}
+
+/// Cost function and gradient computed at batches of points by a single
+/// closure
+///
+/// The closure has the form `|points, costs, grads| -> FunctionCallResult`,
+/// where `points` contains $k$ points of dimension $n$, one after the other,
+/// and it computes the costs (of length $k$) and the gradients (of length
+/// $kn$, one after the other) at these points.
+pub struct BatchOracle<F, T = f64>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut [T], &mut [T]) -> FunctionCallResult,
+{
+    costs_and_gradients: F,
+    memory: Mutex<BatchMemory<T>>,
+    num_batches: AtomicUsize,
+    num_points: AtomicUsize,
+}
+
+impl<F, T> BatchOracle<F, T>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut [T], &mut [T]) -> FunctionCallResult,
+{
+    /// Constructs a new batch oracle from a closure which computes the costs
+    /// and the gradients at a batch of points
+    ///
+    /// ## Memory allocation
+    ///
+    /// The memory needed to store the last batch of evaluations is allocated
+    /// upon the first invocation (and grows with the largest batch)
+    pub fn new(costs_and_gradients: F) -> Self {
+        BatchOracle {
+            costs_and_gradients,
+            memory: Mutex::new(BatchMemory {
+                points: Vec::new(),
+                dimension: 0,
+                costs: Vec::new(),
+                gradients: Vec::new(),
+                valid: false,
+            }),
+            num_batches: AtomicUsize::new(0),
+            num_points: AtomicUsize::new(0),
+        }
+    }
+
+    fn memory(&self) -> MutexGuard<'_, BatchMemory<T>> {
+        self.memory.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Evaluates the closure at `points` (of dimension `dimension`)
+    fn evaluate(&self, points: &[T], dimension: usize) -> FunctionCallResult {
+        let num_points = points.len() / dimension.max(1);
+        let mut memory = self.memory();
+        let memory = &mut *memory;
+        memory.valid = false;
+        memory.costs.resize(num_points, T::zero());
+        memory.gradients.resize(points.len(), T::zero());
+        self.num_batches.fetch_add(1, Ordering::Relaxed);
+        self.num_points.fetch_add(num_points, Ordering::Relaxed);
+        (self.costs_and_gradients)(points, &mut memory.costs, &mut memory.gradients)?;
+        memory.points.clear();
+        memory.points.extend_from_slice(points);
+        memory.dimension = dimension;
+        memory.valid = true;
+        Ok(())
+    }
+
+    /// Index of `u` in the last batch, which is evaluated at `u` alone if it
+    /// does not contain `u`
+    fn lookup(&self, u: &[T]) -> Result<usize, crate::SolverError> {
+        if let Some(index) = self.memory().find(u) {
+            return Ok(index);
+        }
+        self.evaluate(u, u.len())?;
+        Ok(0)
+    }
+
+    /// Cost function, which can be passed to [`Problem`](../problem/struct.Problem.html)
+    pub fn cost(&self) -> impl Fn(&[T], &mut T) -> FunctionCallResult + '_ {
+        move |u: &[T], cost: &mut T| -> FunctionCallResult {
+            let index = self.lookup(u)?;
+            *cost = self.memory().costs[index];
+            Ok(())
+        }
+    }
+
+    /// Gradient of the cost function, which can be passed to
+    /// [`Problem`](../problem/struct.Problem.html)
+    pub fn gradient(&self) -> impl Fn(&[T], &mut [T]) -> FunctionCallResult + '_ {
+        move |u: &[T], grad: &mut [T]| -> FunctionCallResult {
+            let index = self.lookup(u)?;
+            let n = u.len();
+            grad.copy_from_slice(&self.memory().gradients[index * n..(index + 1) * n]);
+            Ok(())
+        }
+    }
+
+    /// Number of times the given closure has been invoked (that is, the
+    /// number of batches)
+    pub fn num_batches(&self) -> usize {
+        self.num_batches.load(Ordering::Relaxed)
+    }
+
+    /// Total number of points at which the given closure has been invoked
+    pub fn num_points(&self) -> usize {
+        self.num_points.load(Ordering::Relaxed)
+    }
+}
+
+impl<F, T> BatchPrefetch<T> for BatchOracle<F, T>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut [T], &mut [T]) -> FunctionCallResult + Sync,
+{
+    fn prefetch(&self, points: &[T], dimension: usize) -> FunctionCallResult {
+        self.evaluate(points, dimension)
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::fused_oracle::FusedOracle;
+    use crate::core::panoc::{PANOCCache, PANOCOptimizer};
+    use crate::core::{Optimizer, Problem};
+    use crate::{constraints, mocks, SolverError};
+
+    /// Batched version of `mocks::my_cost` and `mocks::my_gradient`
+    fn batched_mock(points: &[f64], costs: &mut [f64], grads: &mut [f64]) -> FunctionCallResult {
+        for ((u, cost), grad) in points
+            .chunks(2)
+            .zip(costs.iter_mut())
+            .zip(grads.chunks_mut(2))
+        {
+            mocks::my_cost(u, cost)?;
+            mocks::my_gradient(u, grad)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn t_batch_oracle_prefetch() {
+        let oracle = BatchOracle::new(batched_mock);
+        let cost = oracle.cost();
+        let gradient = oracle.gradient();
+        oracle
+            .prefetch(&[1.0, 2.0, -1.0, 0.5, 3.0, 3.0], 2)
+            .unwrap();
+        assert_eq!((1, 3), (oracle.num_batches(), oracle.num_points()));
+        let (mut c, mut g) = (0.0, [0.0; 2]);
+        let (mut c_expected, mut g_expected) = (0.0, [0.0; 2]);
+        for u in [[1.0, 2.0], [-1.0, 0.5], [3.0, 3.0]].iter() {
+            cost(u, &mut c).unwrap();
+            gradient(u, &mut g).unwrap();
+            mocks::my_cost(u, &mut c_expected).unwrap();
+            mocks::my_gradient(u, &mut g_expected).unwrap();
+            assert_eq!((c_expected, g_expected), (c, g));
+        }
+        assert_eq!(1, oracle.num_batches());
+
+        // a point which is not in the last batch is evaluated alone
+        gradient(&[0.0, 1.0], &mut g).unwrap();
+        cost(&[0.0, 1.0], &mut c).unwrap();
+        assert_eq!((2, 4), (oracle.num_batches(), oracle.num_points()));
+        mocks::my_gradient(&[0.0, 1.0], &mut g_expected).unwrap();
+        assert_eq!(g_expected, g);
+    }
+
+    #[test]
+    fn t_batch_oracle_error_is_not_cached() {
+        let oracle = BatchOracle::new(|_points: &[f64], _costs: &mut [f64], _grads: &mut [f64]| {
+            Err(SolverError::Cost)
+        });
+        let mut c = 0.0;
+        assert!(oracle.prefetch(&[1.0, 2.0], 1).is_err());
+        assert!((oracle.cost())(&[1.0], &mut c).is_err());
+        assert_eq!(2, oracle.num_batches());
+    }
+
+    #[test]
+    fn t_batch_oracle_panoc() {
+        let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+        let mut panoc_cache = PANOCCache::new(2, 1e-8, 5).unwrap();
+
+        // solve the problem with a fused oracle...
+        let fused = FusedOracle::new(|u: &[f64], cost: &mut f64, grad: &mut [f64]| {
+            mocks::my_cost(u, cost)?;
+            mocks::my_gradient(u, grad)
+        });
+        let problem = Problem::new(&bounds, fused.gradient(), fused.cost());
+        let mut u_expected = [1.0, -2.0];
+        let status_expected = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .solve(&mut u_expected)
+            .unwrap();
+
+        // ...and with batched line searches
+        let oracle = BatchOracle::new(batched_mock);
+        let problem = Problem::new(&bounds, oracle.gradient(), oracle.cost());
+        let mut u = [1.0, -2.0];
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_batch_line_search(&oracle, 4)
+            .unwrap()
+            .solve(&mut u)
+            .unwrap();
+
+        assert!(status.has_converged());
+        assert_eq!(status_expected.iterations(), status.iterations());
+        assert_eq!(status_expected.oracle_calls(), status.oracle_calls());
+        assert_eq!(u_expected, u);
+        // the candidates of a line search are evaluated in a single batch
+        assert!(oracle.num_batches() < fused.num_evaluations());
+
+        let problem = Problem::new(&bounds, oracle.gradient(), oracle.cost());
+        assert!(PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_batch_line_search(&oracle, 0)
+            .is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! Thread-safe pool of caches for concurrent solves
+//!
+//!
+use crate::{alm::AlmCache, core::panoc::PANOCCache, FunctionCallResult, SolverError};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+type CacheFactory<C> = Box<dyn Fn() -> Result<C, SolverError> + Send + Sync>;
+
+/// Thread-safe pool of caches (e.g., `PANOCCache` or `AlmCache`)
+///
+/// A `CachePool` hands out caches to threads which solve problems
+/// concurrently (e.g., the workers of a server which serves many clients)
+/// and recycles them: a cache is taken from the pool with `acquire` and is
+/// returned to the pool when the `PooledCache` is dropped. New caches are
+/// only allocated when all caches are in use, so that the caches need not
+/// be allocated for every request, while every cache is used by one thread
+/// at a time.
+///
+/// Caches are returned to the pool as they are; this is fine, since the
+/// solvers reset their caches before solving a problem.
+///
+/// # Example
+///
+/// ```rust
+/// use optimization_engine::{constraints::Ball2, core::*, panoc::*, FunctionCallResult};
+/// use std::{sync::Arc, thread};
+///
+/// let pool = Arc::new(CachePool::for_panoc(2, 1e-6, 5)?);
+/// let workers: Vec<_> = (1..=4)
+///     .map(|i| {
+///         let pool = Arc::clone(&pool);
+///         thread::spawn(move || {
+///             let a = i as f64;
+///             let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+///                 *c = (u[0] - a).powi(2) + u[1].powi(2);
+///                 Ok(())
+///             };
+///             let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+///                 grad[0] = 2.0 * (u[0] - a);
+///                 grad[1] = 2.0 * u[1];
+///                 Ok(())
+///             };
+///             let bounds = Ball2::new(None, 10.0)?;
+///             let mut cache = pool.acquire()?;
+///             let mut u = [0.0; 2];
+///             PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache)
+///                 .solve(&mut u)?;
+///             Ok::<f64, SolverError>(u[0])
+///         })
+///     })
+///     .collect();
+/// for (i, worker) in (1..=4).zip(workers) {
+///     assert!((worker.join().unwrap()? - i as f64).abs() < 1e-4);
+/// }
+/// assert!(pool.idle() <= 4);
+/// # Ok::<(), SolverError>(())
+/// ```
+pub struct CachePool<C> {
+    /// constructs new caches
+    factory: CacheFactory<C>,
+    /// caches which are not in use
+    idle: Mutex<Vec<C>>,
+    /// maximum number of caches which are kept in the pool
+    max_idle: usize,
+}
+
+impl<C> CachePool<C> {
+    /// Constructs a new (empty) pool
+    ///
+    /// ## Arguments
+    ///
+    /// - `factory`: function which constructs a new cache when all caches
+    ///   are in use
+    ///
+    pub fn new<F>(factory: F) -> Self
+    where
+        F: Fn() -> Result<C, SolverError> + Send + Sync + 'static,
+    {
+        CachePool {
+            factory: Box::new(factory),
+            idle: Mutex::new(Vec::new()),
+            max_idle: usize::MAX,
+        }
+    }
+
+    /// Sets the maximum number of caches which are kept in the pool; caches
+    /// which are returned to a full pool are dropped (by default, all caches
+    /// are kept)
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Locks the list of idle caches (the caches are still usable if another
+    /// thread panicked while holding the lock)
+    fn lock(&self) -> MutexGuard<'_, Vec<C>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Allocates caches until the pool holds (at least) `count` idle caches,
+    /// e.g., when a server starts, so that no caches need to be allocated
+    /// while serving requests
+    ///
+    /// ## Errors
+    ///
+    /// Returns the error of the factory, if a cache cannot be constructed
+    ///
+    pub fn preallocate(&self, count: usize) -> FunctionCallResult {
+        let missing = count.saturating_sub(self.idle());
+        let caches = (0..missing)
+            .map(|_| (self.factory)())
+            .collect::<Result<Vec<C>, SolverError>>()?;
+        self.lock().extend(caches);
+        Ok(())
+    }
+
+    /// Takes a cache from the pool, or constructs a new one if there are no
+    /// idle caches; the cache is returned to the pool when the `PooledCache`
+    /// is dropped
+    ///
+    /// ## Errors
+    ///
+    /// Returns the error of the factory, if a new cache cannot be constructed
+    ///
+    pub fn acquire(&self) -> Result<PooledCache<'_, C>, SolverError> {
+        let cache = self.lock().pop();
+        let cache = match cache {
+            Some(cache) => cache,
+            None => (self.factory)()?,
+        };
+        Ok(PooledCache {
+            pool: self,
+            cache: Some(cache),
+        })
+    }
+
+    /// Number of idle caches in the pool
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn recycle(&self, cache: C) {
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            idle.push(cache);
+        }
+    }
+}
+
+impl CachePool<PANOCCache> {
+    /// Constructs a pool of caches of PANOC (see `PANOCCache::new`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the arguments are invalid
+    /// (see `PANOCCache::new`)
+    ///
+    pub fn for_panoc(
+        problem_size: usize,
+        tolerance: f64,
+        lbfgs_memory: usize,
+    ) -> Result<Self, SolverError> {
+        PANOCCache::new(problem_size, tolerance, lbfgs_memory)?;
+        Ok(CachePool::new(move || {
+            PANOCCache::new(problem_size, tolerance, lbfgs_memory)
+        }))
+    }
+}
+
+impl CachePool<AlmCache> {
+    /// Constructs a pool of caches of ALM (see `AlmCache::new`), whose
+    /// inner problems have `problem_size` decision variables, with `n1`
+    /// ALM-type and `n2` PM-type constraints
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the arguments are invalid
+    /// (see `PANOCCache::new`)
+    ///
+    pub fn for_alm(
+        problem_size: usize,
+        tolerance: f64,
+        lbfgs_memory: usize,
+        n1: usize,
+        n2: usize,
+    ) -> Result<Self, SolverError> {
+        PANOCCache::new(problem_size, tolerance, lbfgs_memory)?;
+        Ok(CachePool::new(move || {
+            let panoc_cache = PANOCCache::new(problem_size, tolerance, lbfgs_memory)?;
+            Ok(AlmCache::new(panoc_cache, n1, n2))
+        }))
+    }
+}
+
+impl<C> fmt::Debug for CachePool<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachePool")
+            .field("idle", &self.idle())
+            .field("max_idle", &self.max_idle)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Cache which has been taken from a [`CachePool`](struct.CachePool.html)
+///
+/// `PooledCache` dereferences to the cache and returns it to the pool when
+/// it is dropped.
+#[derive(Debug)]
+pub struct PooledCache<'a, C> {
+    pool: &'a CachePool<C>,
+    cache: Option<C>,
+}
+
+impl<C> Deref for PooledCache<'_, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.cache.as_ref().expect("the cache has been returned")
+    }
+}
+
+impl<C> DerefMut for PooledCache<'_, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.cache.as_mut().expect("the cache has been returned")
+    }
+}
+
+impl<C> Drop for PooledCache<'_, C> {
+    fn drop(&mut self) {
+        if let Some(cache) = self.cache.take() {
+            self.pool.recycle(cache);
+        }
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::Ball2, core::panoc::PANOCOptimizer, core::*, mocks};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn t_cache_pool_recycles_caches() {
+        let allocations = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&allocations);
+        let pool = CachePool::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            PANOCCache::new(2, 1e-8, 5)
+        });
+        pool.preallocate(2).unwrap();
+        assert_eq!(2, pool.idle());
+        {
+            let _a = pool.acquire().unwrap();
+            let _b = pool.acquire().unwrap();
+            let _c = pool.acquire().unwrap();
+            assert_eq!(0, pool.idle());
+        }
+        assert_eq!(3, pool.idle());
+        assert_eq!(3, allocations.load(Ordering::SeqCst));
+        let _d = pool.acquire().unwrap();
+        pool.preallocate(2).unwrap();
+        assert_eq!(2, pool.idle());
+        assert_eq!(3, allocations.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn t_cache_pool_max_idle() {
+        let pool = CachePool::for_alm(3, 1e-6, 3, 2, 0)
+            .unwrap()
+            .with_max_idle(1);
+        {
+            let a = pool.acquire().unwrap();
+            let _b = pool.acquire().unwrap();
+            assert_eq!(Some(2), a.y_plus.as_ref().map(Vec::len));
+        }
+        assert_eq!(1, pool.idle());
+    }
+
+    #[test]
+    fn t_cache_pool_invalid() {
+        assert!(CachePool::for_panoc(0, 1e-6, 5).is_err());
+        let pool: CachePool<PANOCCache> = CachePool::new(|| PANOCCache::new(2, -1.0, 5));
+        assert!(matches!(
+            pool.acquire(),
+            Err(SolverError::InvalidParameter {
+                name: "tolerance",
+                ..
+            })
+        ));
+        assert_eq!(0, pool.idle());
+    }
+
+    #[test]
+    fn t_cache_pool_concurrent_solves() {
+        let pool = CachePool::for_panoc(2, 1e-8, 5).unwrap();
+        let bounds = Ball2::new(None, 0.2).unwrap();
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..5 {
+                        let mut cache = pool.acquire().unwrap();
+                        let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+                        let mut u = [0.0; 2];
+                        let status = PANOCOptimizer::new(problem, &mut cache)
+                            .solve(&mut u)
+                            .unwrap();
+                        assert!(status.has_converged());
+                        assert!((-0.14896 - u[0]).abs() < 1e-4);
+                    }
+                });
+            }
+        });
+        assert!(pool.idle() >= 1 && pool.idle() <= 8);
+    }
+}
@@ -0,0 +1,114 @@
+//! Binary encoding of solver checkpoints
+//!
+//! Checkpoints (see `PanocCheckpoint` and `AlmCheckpoint`) are encoded as a
+//! header of eight bytes, which identifies the kind and the version of the
+//! checkpoint, followed by their fields in little-endian order; vectors are
+//! encoded as their length followed by their entries.
+use crate::SolverError;
+use std::convert::TryFrom;
+
+/// Error of the decoding of a checkpoint
+const INVALID_CHECKPOINT: SolverError = SolverError::InvalidParameter {
+    name: "checkpoint",
+    reason: "is not a valid checkpoint (truncated, corrupted or of another kind)",
+};
+
+/// Encoder of the fields of a checkpoint
+pub(crate) struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    /// Constructs an encoder which starts with `header`
+    pub(crate) fn new(header: &[u8; 8]) -> Self {
+        Encoder {
+            bytes: header.to_vec(),
+        }
+    }
+
+    pub(crate) fn usize(&mut self, value: usize) {
+        self.bytes.extend_from_slice(&(value as u64).to_le_bytes());
+    }
+
+    pub(crate) fn f64(&mut self, value: f64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub(crate) fn vector(&mut self, vector: &[f64]) {
+        self.usize(vector.len());
+        vector.iter().for_each(|&x| self.f64(x));
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Decoder of the fields of a checkpoint
+pub(crate) struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Constructs a decoder of `bytes`, which must start with `header`
+    pub(crate) fn new(bytes: &'a [u8], header: &[u8; 8]) -> Result<Self, SolverError> {
+        let mut decoder = Decoder { bytes };
+        if decoder.take(header.len())? != header {
+            return Err(INVALID_CHECKPOINT);
+        }
+        Ok(decoder)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SolverError> {
+        if self.bytes.len() < len {
+            return Err(INVALID_CHECKPOINT);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u64(&mut self) -> Result<u64, SolverError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    pub(crate) fn usize(&mut self) -> Result<usize, SolverError> {
+        usize::try_from(self.u64()?).map_err(|_| INVALID_CHECKPOINT)
+    }
+
+    pub(crate) fn f64(&mut self) -> Result<f64, SolverError> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, SolverError> {
+        match self.take(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(INVALID_CHECKPOINT),
+        }
+    }
+
+    pub(crate) fn vector(&mut self) -> Result<Vec<f64>, SolverError> {
+        let len = self.usize()?;
+        // the length is checked before the allocation
+        if self.bytes.len() / 8 < len {
+            return Err(INVALID_CHECKPOINT);
+        }
+        (0..len).map(|_| self.f64()).collect()
+    }
+
+    /// Checks that all bytes have been decoded
+    pub(crate) fn finish(self) -> Result<(), SolverError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(INVALID_CHECKPOINT)
+        }
+    }
+}
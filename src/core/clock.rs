@@ -0,0 +1,146 @@
+//! Time sources of the time limits of the optimizers
+//!
+//!
+use std::{
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
+    time::Duration,
+};
+
+/// Monotonic time source
+///
+/// The optimizers which support time limits (e.g., `FBSOptimizer`,
+/// `PANOCOptimizer` and `AlmOptimizer`) measure the solve time and check the
+/// maximum duration using a clock, which can be set with their `with_clock`
+/// methods; the default is [`StdClock`]. Embedded and WASM targets can
+/// provide their own time source (e.g., a hardware timer) and tests can use
+/// a [`ManualClock`].
+///
+/// Clocks are `Sync`, so that the optimizers which borrow them can be sent
+/// to other threads.
+///
+/// [`StdClock`]: struct.StdClock.html
+/// [`ManualClock`]: struct.ManualClock.html
+pub trait Clock: Sync {
+    /// Time elapsed since an arbitrary, fixed origin; consecutive calls must
+    /// return nondecreasing values
+    fn now(&self) -> Duration;
+
+    /// Time elapsed since `start`, which was returned by `now`
+    fn elapsed(&self, start: Duration) -> Duration {
+        self.now().saturating_sub(start)
+    }
+}
+
+/// The clock of the standard library (or, with the feature `wasm`, of
+/// `performance.now()`)
+///
+/// The origin is the first call of `now`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdClock;
+
+impl Clock for StdClock {
+    fn now(&self) -> Duration {
+        static ORIGIN: OnceLock<instant::Instant> = OnceLock::new();
+        ORIGIN.get_or_init(instant::Instant::now).elapsed()
+    }
+}
+
+/// Clock which is advanced manually
+///
+/// The time starts at zero and is advanced by `advance` and, optionally,
+/// automatically by a fixed tick at every call of `now`, so that time limits
+/// can be tested deterministically
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::core::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new().with_tick(Duration::from_millis(1));
+/// assert_eq!(Duration::from_millis(0), clock.now());
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(Duration::from_millis(1001), clock.now());
+/// ```
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    time: Mutex<Duration>,
+    tick: Duration,
+}
+
+impl Clone for ManualClock {
+    fn clone(&self) -> Self {
+        ManualClock {
+            time: Mutex::new(*self.time()),
+            tick: self.tick,
+        }
+    }
+}
+
+impl ManualClock {
+    /// Constructs a new clock, whose time is zero and which does not advance
+    /// automatically
+    pub fn new() -> Self {
+        ManualClock::default()
+    }
+
+    /// Advances the clock by `tick` at every call of `now` (after the time
+    /// is read)
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// Advances the clock by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.time() += duration;
+    }
+
+    fn time(&self) -> MutexGuard<'_, Duration> {
+        self.time.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        let mut time = self.time();
+        let now = *time;
+        *time += self.tick;
+        now
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_std_clock_is_monotonic() {
+        let clock = StdClock;
+        let start = clock.now();
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(clock.elapsed(start) >= Duration::from_millis(2));
+    }
+
+    #[test]
+    fn t_manual_clock() {
+        let clock = ManualClock::new();
+        assert_eq!(Duration::ZERO, clock.now());
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(Duration::from_millis(5), clock.now());
+        assert_eq!(
+            Duration::from_millis(3),
+            clock.elapsed(Duration::from_millis(2))
+        );
+        // the elapsed time is never negative
+        assert_eq!(Duration::ZERO, clock.elapsed(Duration::from_secs(1)));
+        // a clone starts at the time of the original and advances separately
+        let clone = clock.clone();
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(Duration::from_millis(5), clone.now());
+        assert_eq!(Duration::from_millis(6), clock.now());
+    }
+}
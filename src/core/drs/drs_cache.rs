@@ -0,0 +1,56 @@
+//! DRS Cache
+//!
+use crate::{core::OracleCalls, ensure, OpEnFloat, SolverError};
+use std::num::NonZeroUsize;
+
+/// Cache for the Douglas-Rachford splitting (DRS) algorithm
+///
+/// This struct allocates memory needed for the DRS algorithm
+pub struct DRSCache<T = f64> {
+    /// governing sequence, $s$
+    pub(crate) work_s: Vec<T>,
+    /// reflection, $2x - s$
+    pub(crate) work_reflection: Vec<T>,
+    /// output of the proximal operator of $g$, $y$
+    pub(crate) work_y: Vec<T>,
+    pub(crate) gamma: T,
+    pub(crate) relaxation: T,
+    pub(crate) tolerance: T,
+    pub(crate) norm_fpr: T,
+    pub(crate) oracle_calls: OracleCalls,
+}
+
+impl<T: OpEnFloat> DRSCache<T> {
+    /// Construct a new instance of `DRSCache`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n` number of decision variables
+    /// - `gamma` parameter gamma of the algorithm
+    /// - `tolerance` tolerance used for termination
+    ///
+    /// ## Memory allocation
+    ///
+    /// This method allocates memory for `3*n + 4` floats; if an optimizer
+    /// needs to be called more than once, construct a `DRSCache` only once.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `gamma` or `tolerance` is
+    /// not positive
+    ///
+    pub fn new(n: NonZeroUsize, gamma: T, tolerance: T) -> Result<DRSCache<T>, SolverError> {
+        ensure(gamma > T::zero(), "gamma", "must be positive")?;
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        Ok(DRSCache {
+            work_s: vec![T::zero(); n.get()],
+            work_reflection: vec![T::zero(); n.get()],
+            work_y: vec![T::zero(); n.get()],
+            gamma,
+            relaxation: T::one(),
+            tolerance,
+            norm_fpr: T::infinity(),
+            oracle_calls: OracleCalls::default(),
+        })
+    }
+}
@@ -0,0 +1,90 @@
+//! DRS Engine
+//!
+use crate::{
+    core::{
+        drs::{DRSCache, DRSProblem},
+        AlgorithmEngine,
+    },
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+
+/// The DRS Engine defines the steps of the DRS algorithm and the termination criterion
+///
+pub struct DRSEngine<'a, ProxF, ProxG, T = f64>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+{
+    pub(crate) problem: DRSProblem<ProxF, ProxG, T>,
+    pub(crate) cache: &'a mut DRSCache<T>,
+}
+
+impl<'a, ProxF, ProxG, T> DRSEngine<'a, ProxF, ProxG, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+{
+    /// Constructor for instances of `DRSEngine`
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem` problem definition (proximal operators of $f$ and $g$)
+    /// - mutable reference to a `cache` a cache (which is created once); the cache is reuseable
+    ///
+    pub fn new(
+        problem: DRSProblem<ProxF, ProxG, T>,
+        cache: &'a mut DRSCache<T>,
+    ) -> DRSEngine<'a, ProxF, ProxG, T> {
+        DRSEngine { problem, cache }
+    }
+}
+
+impl<'a, ProxF, ProxG, T> AlgorithmEngine<T> for DRSEngine<'a, ProxF, ProxG, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'a,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'a,
+{
+    /// Take a DRS step and check whether the algorithm should terminate
+    ///
+    /// ## Arguments
+    ///
+    /// - `u_current` on exit, $x = \mathrm{prox}_{\gamma f}(s)$
+    ///
+    /// ## Returns
+    ///
+    /// - A boolean flag which is`true` if and only if the algorithm should not
+    ///   terminate
+    ///
+    fn step(&mut self, u_current: &mut [T]) -> Result<bool, SolverError> {
+        let cache = &mut *self.cache;
+        let gamma = cache.gamma;
+        (self.problem.prox_f)(&cache.work_s, gamma, u_current)?;
+        cache
+            .work_reflection
+            .iter_mut()
+            .zip(u_current.iter().zip(cache.work_s.iter()))
+            .for_each(|(r, (x, s))| *r = *x + *x - *s);
+        (self.problem.prox_g)(&cache.work_reflection, gamma, &mut cache.work_y)?;
+        cache.oracle_calls.projections += 2;
+
+        let relaxation = cache.relaxation;
+        cache
+            .work_s
+            .iter_mut()
+            .zip(cache.work_y.iter().zip(u_current.iter()))
+            .for_each(|(s, (y, x))| *s += relaxation * (*y - *x));
+        cache.norm_fpr = matrix_operations::norm_inf_diff(&cache.work_y, u_current);
+
+        Ok(cache.norm_fpr > cache.tolerance)
+    }
+
+    /// Initializes the governing sequence with `u_current`
+    fn init(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        self.cache.work_s.copy_from_slice(u_current);
+        self.cache.oracle_calls.reset();
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+//! DRS Algorithm
+//!
+use crate::{
+    core::{
+        drs::{drs_engine::DRSEngine, DRSCache, DRSProblem},
+        AlgorithmEngine, ExitStatus, Optimizer, SolverStatus,
+    },
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+const MAX_ITER: usize = 100_usize;
+
+/// Optimiser using Douglas-Rachford splitting iterations
+///
+/// The solution, $x = \mathrm{prox}_{\gamma f}(s)$, is written into the
+/// argument of `solve`, which, on entry, is the initial guess of the
+/// governing sequence, $s$.
+///
+/// Since DRS only uses the proximal operators of $f$ and $g$, the cost
+/// value of the returned `SolverStatus` is NaN and each evaluation of a
+/// proximal operator is counted as a projection in its `OracleCalls`.
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct DRSOptimizer<'a, ProxF, ProxG, T = f64>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+{
+    drs_engine: DRSEngine<'a, ProxF, ProxG, T>,
+    max_iter: usize,
+    max_duration: Option<time::Duration>,
+}
+
+impl<'a, ProxF, ProxG, T> DRSOptimizer<'a, ProxF, ProxG, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+{
+    /// Constructs a new instance of `DRSOptimizer`
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem`: problem definition
+    /// - `cache`: instance of `DRSCache`
+    pub fn new(problem: DRSProblem<ProxF, ProxG, T>, cache: &'a mut DRSCache<T>) -> Self {
+        DRSOptimizer {
+            drs_engine: DRSEngine::new(problem, cache),
+            max_iter: MAX_ITER,
+            max_duration: None,
+        }
+    }
+
+    /// Sets the tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        self.drs_engine.cache.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the relaxation parameter, $\lambda$ (the default is 1; values
+    /// in $(1, 2)$ correspond to over-relaxation, which often speeds up
+    /// the convergence)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the relaxation parameter
+    /// is not in $(0, 2)$
+    pub fn with_relaxation(self, relaxation: T) -> Result<Self, SolverError> {
+        ensure(
+            relaxation > T::zero() && relaxation < T::from_f64(2.0),
+            "relaxation",
+            "must be in (0, 2)",
+        )?;
+        self.drs_engine.cache.relaxation = relaxation;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+}
+
+impl<'life, ProxF, ProxG, T> Optimizer<T> for DRSOptimizer<'life, ProxF, ProxG, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'life,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = instant::Instant::now();
+
+        let problem_size = self.drs_engine.cache.work_s.len();
+        if u.len() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: problem_size,
+                actual: u.len(),
+            });
+        }
+
+        self.drs_engine.init(u)?;
+
+        let mut num_iter: usize = 0;
+        let mut exit_status = ExitStatus::Converged;
+        loop {
+            num_iter += 1;
+            if !self.drs_engine.step(u)? {
+                break;
+            }
+            if num_iter >= self.max_iter {
+                exit_status = ExitStatus::NotConvergedIterations;
+                break;
+            }
+            if let Some(max_duration) = self.max_duration {
+                if now.elapsed() > max_duration {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+        }
+
+        if !matrix_operations::is_finite(u) || !self.drs_engine.cache.norm_fpr.is_finite() {
+            return Err(SolverError::NotFiniteComputation);
+        }
+
+        Ok(SolverStatus::new(
+            exit_status,
+            num_iter,
+            now.elapsed(),
+            self.drs_engine.cache.norm_fpr.as_f64(),
+            f64::NAN,
+        )
+        .with_oracle_calls(self.drs_engine.cache.oracle_calls))
+    }
+}
@@ -0,0 +1,52 @@
+//! DRS Problem
+//!
+use crate::{FunctionCallResult, OpEnFloat};
+use std::marker::PhantomData;
+
+/// Definition of an optimization problem to be solved with `DRSOptimizer`
+///
+/// The problem is to minimize $f(u) + g(u)$, where $f$ and $g$ are given
+/// through their proximal operators, `prox_f` and `prox_g`, which, given
+/// `v` and $\gamma > 0$, compute
+///
+/// $$\mathrm{prox}_{\gamma f}(v) = \mathrm{argmin}_x f(x) + \tfrac{1}{2\gamma}\Vert x - v\Vert^2$$
+///
+/// and write it into their last argument. If $f$ is the indicator
+/// function of a set, its proximal operator is the projection on the set
+/// (see `drs::projection`).
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct DRSProblem<ProxF, ProxG, T = f64>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+{
+    /// proximal operator of $f$
+    pub(crate) prox_f: ProxF,
+    /// proximal operator of $g$
+    pub(crate) prox_g: ProxG,
+    marker: PhantomData<T>,
+}
+
+impl<ProxF, ProxG, T> DRSProblem<ProxF, ProxG, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxG: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+{
+    /// Construct a new instance of a DRS problem
+    ///
+    /// ## Arguments
+    ///
+    /// - `prox_f`: proximal operator of $f$, $(v, \gamma, x) \mapsto x = \mathrm{prox}_{\gamma f}(v)$
+    /// - `prox_g`: proximal operator of $g$
+    ///
+    pub fn new(prox_f: ProxF, prox_g: ProxG) -> Self {
+        DRSProblem {
+            prox_f,
+            prox_g,
+            marker: PhantomData,
+        }
+    }
+}
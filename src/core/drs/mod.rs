@@ -0,0 +1,92 @@
+//! Douglas-Rachford splitting algorithm
+//!
+//! ## About
+//!
+//! This module offers an implementation of the Douglas-Rachford splitting
+//! (DRS) algorithm for problems of the form
+//!
+//! $$\mathrm{Minimize}\ f(u) + g(u),$$
+//!
+//! where $f$ and $g$ are closed, proper, convex functions which are given
+//! through their proximal operators (see [`DRSProblem`]). Starting from
+//! $s^0$, DRS performs the iterations
+//!
+//! $$\begin{aligned}
+//! x^{k} &= \mathrm{prox}_{\gamma f}(s^k),
+//! \\\\
+//! y^{k} &= \mathrm{prox}_{\gamma g}(2x^k - s^k),
+//! \\\\
+//! s^{k+1} &= s^k + \lambda (y^k - x^k),
+//! \end{aligned}$$
+//!
+//! where $\gamma > 0$ and $\lambda \in (0, 2)$ is the relaxation parameter
+//! ($\lambda > 1$ is over-relaxation), until $\Vert y^k - x^k\Vert_\infty$
+//! drops below the tolerance.
+//!
+//! When $f$ and $g$ are the indicators of two closed convex sets, DRS finds
+//! a point in their intersection; the projection of a point on an
+//! intersection of sets can be computed similarly (see the example below).
+//! The function [`projection`] turns a set into the proximal operator of its
+//! indicator.
+//!
+//! ## Example: projection on the intersection of two sets
+//!
+//! ```
+//! use optimization_engine::{constraints::*, drs::*, *};
+//! use std::num::NonZeroUsize;
+//!
+//! // project a on the intersection of a ball and a halfspace:
+//! // f(u) = 0.5|u - a|^2 + indicator of the ball, g = indicator of the halfspace
+//! let a = [2.0, 2.0];
+//! let ball = Ball2::new(None, 2.0)?;
+//! let halfspace = Halfspace::new(&[1.0, 0.0], 0.5);
+//! let prox_f = |v: &[f64], gamma: f64, x: &mut [f64]| -> FunctionCallResult {
+//!     x.iter_mut()
+//!         .zip(v.iter().zip(a.iter()))
+//!         .for_each(|(xi, (vi, ai))| *xi = (vi + gamma * ai) / (1.0 + gamma));
+//!     ball.project(x);
+//!     Ok(())
+//! };
+//! let problem = DRSProblem::new(prox_f, projection(&halfspace));
+//! let mut cache = DRSCache::new(NonZeroUsize::new(2).unwrap(), 1.0, 1e-8)?;
+//! let mut u = [0.0; 2];
+//! let status = DRSOptimizer::new(problem, &mut cache)
+//!     .with_max_iter(1000)
+//!     .solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 0.5).abs() < 1e-6 && (u[1] - 3.75_f64.sqrt()).abs() < 1e-6);
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! [`DRSProblem`]: struct.DRSProblem.html
+//! [`projection`]: fn.projection.html
+
+mod drs_cache;
+mod drs_engine;
+mod drs_optimizer;
+mod drs_problem;
+
+pub use drs_cache::DRSCache;
+pub use drs_optimizer::DRSOptimizer;
+pub use drs_problem::DRSProblem;
+
+use crate::{constraints::Constraint, FunctionCallResult, OpEnFloat};
+
+/// Proximal operator of the indicator function of a set, that is, the
+/// projection on the set (which does not depend on $\gamma$)
+pub fn projection<T: OpEnFloat, C: Constraint<T>>(
+    set: &C,
+) -> impl Fn(&[T], T, &mut [T]) -> FunctionCallResult + '_ {
+    move |v: &[T], _gamma: T, result: &mut [T]| {
+        result.copy_from_slice(v);
+        set.project(result);
+        Ok(())
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+/*          TESTS                                                                                */
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,102 @@
+use super::*;
+use crate::{constraints::*, core::ExitStatus, matrix_operations, Optimizer, SolverError};
+use std::num::NonZeroUsize;
+
+#[test]
+fn t_drs_feasibility() {
+    // find a point in the intersection of a ball and a halfspace
+    let ball = Ball2::new(Some(&[1.0, 1.0]), 1.0).unwrap();
+    let normal = [1.0, 1.0];
+    let halfspace = Halfspace::new(&normal, 1.5);
+    let problem = DRSProblem::new(projection(&ball), projection(&halfspace));
+    let mut cache = DRSCache::new(NonZeroUsize::new(2).unwrap(), 1.0, 1e-10).unwrap();
+    let mut u = [5.0, -3.0];
+    let status = DRSOptimizer::new(problem, &mut cache)
+        .with_max_iter(10_000)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.norm_fpr() < 1e-10);
+    assert!(status.cost_value().is_nan());
+    assert_eq!(2 * status.iterations(), status.oracle_calls().projections());
+    assert!(matrix_operations::norm2(&[u[0] - 1.0, u[1] - 1.0]) <= 1.0 + 1e-12);
+    assert!(u[0] + u[1] <= 1.5 + 1e-9);
+}
+
+#[test]
+fn t_drs_lasso_over_relaxation() {
+    // minimize 0.5 |u - a|^2 + |u|_1 subject to u in [-1, 1]^3, whose
+    // solution is clip(softthreshold(a, 1), -1, 1)
+    let a = [3.0, -0.5, -1.5];
+    let prox_f = |v: &[f64], gamma: f64, x: &mut [f64]| -> FunctionCallResult {
+        x.iter_mut()
+            .zip(v.iter().zip(a.iter()))
+            .for_each(|(xi, (vi, ai))| {
+                let w = (vi + gamma * ai) / (1.0 + gamma);
+                let threshold = gamma / (1.0 + gamma);
+                *xi = w.signum() * (w.abs() - threshold).max(0.0);
+            });
+        Ok(())
+    };
+    let bounds = Rectangle::new(Some(&[-1.0; 3]), Some(&[1.0; 3])).unwrap();
+    let mut iterations = vec![];
+    for relaxation in [1.0, 1.8] {
+        let problem = DRSProblem::new(prox_f, projection(&bounds));
+        let mut cache = DRSCache::new(NonZeroUsize::new(3).unwrap(), 0.5, 1e-10).unwrap();
+        let mut u = [0.0; 3];
+        let status = DRSOptimizer::new(problem, &mut cache)
+            .with_relaxation(relaxation)
+            .unwrap()
+            .with_max_iter(1000)
+            .solve(&mut u)
+            .unwrap();
+        assert!(status.has_converged());
+        unit_test_utils::assert_nearly_equal_array(&[1.0, 0.0, -0.5], &u, 1e-8, 1e-8, "u");
+        iterations.push(status.iterations());
+    }
+    assert!(iterations[1] < iterations[0]);
+}
+
+#[test]
+fn t_drs_f32() {
+    let ball = Ball2::new(None, 1.0_f32).unwrap();
+    let prox_f = |v: &[f32], gamma: f32, x: &mut [f32]| -> FunctionCallResult {
+        // f(u) = 0.5 |u - (2, 0)|^2
+        x[0] = (v[0] + 2.0 * gamma) / (1.0 + gamma);
+        x[1] = v[1] / (1.0 + gamma);
+        Ok(())
+    };
+    let problem = DRSProblem::new(prox_f, projection(&ball));
+    let mut cache = DRSCache::new(NonZeroUsize::new(2).unwrap(), 1.0_f32, 1e-5).unwrap();
+    let mut u = [0.0_f32; 2];
+    let status = DRSOptimizer::new(problem, &mut cache)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!((u[0] - 1.0).abs() < 1e-4 && u[1].abs() < 1e-4);
+}
+
+#[test]
+fn t_drs_max_iter_and_errors() {
+    let ball = Ball2::new(None, 1.0).unwrap();
+    let far_ball = Ball2::new(Some(&[3.0, 0.0]), 1.0).unwrap();
+    // the sets do not intersect, so DRS does not converge
+    let problem = DRSProblem::new(projection(&ball), projection(&far_ball));
+    let mut cache = DRSCache::new(NonZeroUsize::new(2).unwrap(), 1.0, 1e-8).unwrap();
+    let mut optimizer = DRSOptimizer::new(problem, &mut cache).with_max_iter(20);
+    let mut u = [0.0; 2];
+    let status = optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+    assert_eq!(20, status.iterations());
+    assert!(matches!(
+        optimizer.solve(&mut [0.0; 3]),
+        Err(SolverError::DimensionMismatch {
+            name: "u",
+            expected: 2,
+            actual: 3,
+        })
+    ));
+    let optimizer = optimizer.with_relaxation(2.0);
+    assert!(optimizer.is_err());
+    assert!(DRSCache::new(NonZeroUsize::new(2).unwrap(), 0.0, 1e-8).is_err());
+}
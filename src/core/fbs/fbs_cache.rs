@@ -1,19 +1,28 @@
 //! FBS Cache
 //!
+use crate::{
+    core::{OracleCalls, SolverStatistics, StepTolerance},
+    ensure, OpEnFloat, SolverError,
+};
 use std::num::NonZeroUsize;
 
 /// Cache for the forward-backward splitting (FBS), or projected gradient, algorithm
 ///
 /// This struct allocates memory needed for the FBS algorithm
-pub struct FBSCache {
-    pub(crate) work_gradient_u: Vec<f64>,
-    pub(crate) work_u_previous: Vec<f64>,
-    pub(crate) gamma: f64,
-    pub(crate) tolerance: f64,
-    pub(crate) norm_fpr: f64,
+pub struct FBSCache<T = f64> {
+    pub(crate) work_gradient_u: Vec<T>,
+    pub(crate) work_u_previous: Vec<T>,
+    pub(crate) gamma: T,
+    pub(crate) tolerance: T,
+    pub(crate) norm_fpr: T,
+    /// Tolerances of the termination criterion on the change of the iterate
+    /// (if activated)
+    pub(crate) step_tolerance: Option<StepTolerance<T>>,
+    pub(crate) oracle_calls: OracleCalls,
+    pub(crate) statistics: SolverStatistics,
 }
 
-impl FBSCache {
+impl<T: OpEnFloat> FBSCache<T> {
     /// Construct a new instance of `FBSCache`
     ///
     /// ## Arguments
@@ -32,18 +41,39 @@ impl FBSCache {
     ///
     /// This method will allocate memory for `2*n + 3` floats
     ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `gamma` or `tolerance` is
+    /// not positive
+    ///
     /// ## Panics
     ///
     /// This method will panic if there is no available memory for the required allocation
     /// (capacity overflow)
     ///
-    pub fn new(n: NonZeroUsize, gamma: f64, tolerance: f64) -> FBSCache {
-        FBSCache {
-            work_gradient_u: vec![0.0; n.get()],
-            work_u_previous: vec![0.0; n.get()],
+    pub fn new(n: NonZeroUsize, gamma: T, tolerance: T) -> Result<FBSCache<T>, SolverError> {
+        ensure(gamma > T::zero(), "gamma", "must be positive")?;
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        Ok(FBSCache {
+            work_gradient_u: vec![T::zero(); n.get()],
+            work_u_previous: vec![T::zero(); n.get()],
             gamma,
             tolerance,
-            norm_fpr: std::f64::INFINITY,
-        }
+            norm_fpr: T::infinity(),
+            step_tolerance: None,
+            oracle_calls: OracleCalls::default(),
+            statistics: SolverStatistics::default(),
+        })
+    }
+
+    /// Statistics of the solves which used this cache (they are not reset
+    /// when a new problem is solved)
+    pub fn statistics(&self) -> &SolverStatistics {
+        &self.statistics
+    }
+
+    /// Resets the statistics of the solves (see `statistics`)
+    pub fn reset_statistics(&mut self) {
+        self.statistics.reset();
     }
 }
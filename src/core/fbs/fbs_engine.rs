@@ -2,28 +2,30 @@
 //!
 use crate::{
     constraints,
-    core::{fbs::FBSCache, AlgorithmEngine, Problem},
-    matrix_operations, FunctionCallResult, SolverError,
+    core::{fbs::FBSCache, maybe_owned::MaybeOwnedMut, AlgorithmEngine, Problem},
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
 };
 
 /// The FBE Engine defines the steps of the FBE algorithm and the termination criterion
 ///
-pub struct FBSEngine<'a, GradientType, ConstraintType, CostType>
+pub struct FBSEngine<'a, GradientType, ConstraintType, CostType, T = f64>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
-    pub(crate) problem: Problem<'a, GradientType, ConstraintType, CostType>,
-    pub(crate) cache: &'a mut FBSCache,
+    pub(crate) problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+    pub(crate) cache: MaybeOwnedMut<'a, FBSCache<T>>,
 }
 
-impl<'a, GradientType, ConstraintType, CostType>
-    FBSEngine<'a, GradientType, ConstraintType, CostType>
+impl<'a, GradientType, ConstraintType, CostType, T>
+    FBSEngine<'a, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
     /// Constructor for instances of `FBSEngine`
     ///
@@ -36,37 +38,51 @@ where
     ///
     /// An new instance of `FBSEngine`
     pub fn new(
-        problem: Problem<'a, GradientType, ConstraintType, CostType>,
-        cache: &'a mut FBSCache,
-    ) -> FBSEngine<'a, GradientType, ConstraintType, CostType> {
-        FBSEngine { problem, cache }
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: &'a mut FBSCache<T>,
+    ) -> FBSEngine<'a, GradientType, ConstraintType, CostType, T> {
+        FBSEngine {
+            problem,
+            cache: MaybeOwnedMut::Borrowed(cache),
+        }
     }
 
-    fn gradient_step(&mut self, u_current: &mut [f64]) {
-        assert_eq!(
-            Ok(()),
-            (self.problem.gradf)(u_current, &mut self.cache.work_gradient_u),
-            "The computation of the gradient of the cost failed miserably"
-        );
+    /// Constructor for instances of `FBSEngine` which own their cache
+    pub(crate) fn new_owned(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: FBSCache<T>,
+    ) -> FBSEngine<'a, GradientType, ConstraintType, CostType, T> {
+        FBSEngine {
+            problem,
+            cache: MaybeOwnedMut::Owned(cache),
+        }
+    }
+
+    fn gradient_step(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        self.cache.oracle_calls.gradient += 1;
+        (self.problem.gradf)(u_current, &mut self.cache.work_gradient_u)?;
 
         // take a gradient step: u_currect -= gamma * gradient
         u_current
             .iter_mut()
             .zip(self.cache.work_gradient_u.iter())
             .for_each(|(u, w)| *u -= self.cache.gamma * *w);
+        Ok(())
     }
 
-    fn projection_step(&mut self, u_current: &mut [f64]) {
+    fn projection_step(&mut self, u_current: &mut [T]) {
         self.problem.constraints.project(u_current);
+        self.cache.oracle_calls.projections += 1;
     }
 }
 
-impl<'a, GradientType, ConstraintType, CostType> AlgorithmEngine
-    for FBSEngine<'a, GradientType, ConstraintType, CostType>
+impl<'a, GradientType, ConstraintType, CostType, T> AlgorithmEngine<T>
+    for FBSEngine<'a, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult + 'a,
-    ConstraintType: constraints::Constraint + 'a,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'a,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'a,
+    ConstraintType: constraints::Constraint<T> + 'a,
 {
     /// Take a forward-backward step and check whether the algorithm should terminate
     ///
@@ -83,17 +99,22 @@ where
     ///
     /// The method may panick if the computation of the gradient of the cost function
     /// or the cost function panics.
-    fn step(&mut self, u_current: &mut [f64]) -> Result<bool, SolverError> {
+    fn step(&mut self, u_current: &mut [T]) -> Result<bool, SolverError> {
         self.cache.work_u_previous.copy_from_slice(u_current); // cache the previous step
-        self.gradient_step(u_current); // compute the gradient
+        self.gradient_step(u_current)?; // compute the gradient
         self.projection_step(u_current); // project
         self.cache.norm_fpr =
             matrix_operations::norm_inf_diff(u_current, &self.cache.work_u_previous);
 
-        Ok(self.cache.norm_fpr > self.cache.tolerance)
+        let step_converged = self.cache.step_tolerance.is_some_and(|step_tolerance| {
+            step_tolerance.is_satisfied(u_current, &self.cache.work_u_previous)
+        });
+
+        Ok(self.cache.norm_fpr > self.cache.tolerance && !step_converged)
     }
 
-    fn init(&mut self, _u_current: &mut [f64]) -> FunctionCallResult {
+    fn init(&mut self, _u_current: &mut [T]) -> FunctionCallResult {
+        self.cache.oracle_calls.reset();
         Ok(())
     }
 }
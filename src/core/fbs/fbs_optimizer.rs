@@ -3,15 +3,27 @@
 use crate::{
     constraints,
     core::{
-        fbs::fbs_engine::FBSEngine, fbs::FBSCache, AlgorithmEngine, ExitStatus, Optimizer, Problem,
-        SolverStatus,
+        fbs::fbs_engine::FBSEngine,
+        fbs::FBSCache,
+        problem::{BoxedCost, BoxedGradient},
+        screen_output, AlgorithmEngine, Clock, ExitStatus, Optimizer, Problem, SolverStatus,
+        StdClock, StepTolerance,
     },
-    matrix_operations, FunctionCallResult, SolverError,
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
 };
 use std::time;
 
 const MAX_ITER: usize = 100_usize;
 
+/// FBS optimizer for type-erased problems (see `DynProblem`)
+pub type DynFBSOptimizer<'a, T = f64> = FBSOptimizer<
+    'a,
+    BoxedGradient<'a, T>,
+    constraints::BoxedConstraint<'a, T>,
+    BoxedCost<'a, T>,
+    T,
+>;
+
 /// Optimiser using forward-backward splitting iterations (projected gradient)
 ///
 /// Note that an `FBSOptimizer` holds a reference to an instance of `FBSEngine`
@@ -21,24 +33,29 @@ const MAX_ITER: usize = 100_usize;
 /// The `FBSEngine` is supposed to be updated whenever you need to solve
 /// a different optimization problem.
 ///
-///
-pub struct FBSOptimizer<'a, GradientType, ConstraintType, CostType>
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct FBSOptimizer<'a, GradientType, ConstraintType, CostType, T = f64>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
-    fbs_engine: FBSEngine<'a, GradientType, ConstraintType, CostType>,
+    fbs_engine: FBSEngine<'a, GradientType, ConstraintType, CostType, T>,
     max_iter: usize,
     max_duration: Option<time::Duration>,
+    record_history: bool,
+    nan_screening: bool,
+    clock: &'a dyn Clock,
 }
 
-impl<'a, GradientType, ConstraintType, CostType>
-    FBSOptimizer<'a, GradientType, ConstraintType, CostType>
+impl<'a, GradientType, ConstraintType, CostType, T>
+    FBSOptimizer<'a, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
     /// Constructs a new instance of `FBSOptimizer`
     ///
@@ -47,36 +64,86 @@ where
     /// - `problem`: problem definition
     /// - `cache`: instance of `FBSCache`
     pub fn new(
-        problem: Problem<'a, GradientType, ConstraintType, CostType>,
-        cache: &'a mut FBSCache,
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: &'a mut FBSCache<T>,
     ) -> Self {
         FBSOptimizer {
             fbs_engine: FBSEngine::new(problem, cache),
             max_iter: MAX_ITER,
             max_duration: None,
+            record_history: false,
+            nan_screening: false,
+            clock: &StdClock,
+        }
+    }
+
+    /// Constructs a new instance of `FBSOptimizer` which takes ownership of
+    /// its cache
+    ///
+    /// The optimizer is `Send` if the closures and the constraints of
+    /// `problem` are, so it can be moved to a worker thread.
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem`: problem definition
+    /// - `cache`: instance of `FBSCache`, which is owned by the optimizer
+    pub fn new_owned(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: FBSCache<T>,
+    ) -> Self {
+        FBSOptimizer {
+            fbs_engine: FBSEngine::new_owned(problem, cache),
+            max_iter: MAX_ITER,
+            max_duration: None,
+            record_history: false,
+            nan_screening: false,
+            clock: &StdClock,
         }
     }
 
     /// Sets the tolerance
     ///
-    /// ## Panics
+    /// ## Errors
     ///
-    /// The method panics if the specified tolerance is not positive
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
     pub fn with_tolerance(
-        self,
-        tolerance: f64,
-    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType> {
-        assert!(tolerance > 0.0);
+        mut self,
+        tolerance: T,
+    ) -> Result<FBSOptimizer<'a, GradientType, ConstraintType, CostType, T>, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
 
         self.fbs_engine.cache.tolerance = tolerance;
-        self
+        Ok(self)
+    }
+
+    /// Activates the termination criterion on the change of the iterate,
+    /// `||u - u_previous|| <= relative * ||u|| + absolute`
+    ///
+    /// The algorithm then also exits (with `ExitStatus::Converged`) if the
+    /// change of the iterate (in the Euclidean norm) is within these
+    /// tolerances, even if the norm of the fixed-point residual is above the
+    /// tolerance. The tolerances are stored in the `FBSCache`, so they persist
+    /// and also apply to later optimizers which use the same cache.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if either tolerance is
+    /// negative or both are zero
+    pub fn with_step_tolerance(
+        mut self,
+        relative: T,
+        absolute: T,
+    ) -> Result<FBSOptimizer<'a, GradientType, ConstraintType, CostType, T>, SolverError> {
+        self.fbs_engine.cache.step_tolerance = Some(StepTolerance::new(relative, absolute)?);
+        Ok(self)
     }
 
     /// Sets the maximum number of iterations
     pub fn with_max_iter(
         mut self,
         max_iter: usize,
-    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType> {
+    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType, T> {
         self.max_iter = max_iter;
         self
     }
@@ -85,59 +152,190 @@ where
     pub fn with_max_duration(
         mut self,
         max_duration: time::Duration,
-    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType> {
+    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType, T> {
         self.max_duration = Some(max_duration);
         self
     }
+
+    /// Sets the clock which measures the solution time (see `Clock`); the
+    /// default is `StdClock`
+    pub fn with_clock(
+        mut self,
+        clock: &'a dyn Clock,
+    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType, T> {
+        self.clock = clock;
+        self
+    }
+
+    /// Whether to record the cost and the norm of the fixed-point residual at
+    /// every iteration
+    ///
+    /// If enabled, the recorded values are available via `SolverStatus::cost_history`
+    /// and `SolverStatus::fpr_history`. Note that FBS does not compute the cost at
+    /// every iteration, so this requires an additional cost evaluation per iteration.
+    pub fn with_history(
+        mut self,
+        record_history: bool,
+    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType, T> {
+        self.record_history = record_history;
+        self
+    }
+
+    /// Activates the screening of the outputs of the cost and its gradient
+    ///
+    /// If activated, the gradient is checked for NaN and infinite values after
+    /// every iteration (and the cost after every evaluation); if such a value is
+    /// found, the solver returns `SolverError::NotFiniteOutput`. This is disabled
+    /// by default.
+    pub fn with_nan_screening(
+        mut self,
+        nan_screening: bool,
+    ) -> FBSOptimizer<'a, GradientType, ConstraintType, CostType, T> {
+        self.nan_screening = nan_screening;
+        self
+    }
+
+    /// Screens the gradient of the cost (last computed in a step) for
+    /// NaN/infinite values, if screening is activated
+    fn screen_gradient(&self, iteration: usize) -> FunctionCallResult {
+        if self.nan_screening {
+            screen_output(
+                "FBS",
+                "gradient",
+                iteration,
+                &self.fbs_engine.cache.work_gradient_u,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Computes the cost at `u` and screens it for NaN/infinite values, if
+    /// screening is activated
+    fn cost(&mut self, u: &[T], iteration: usize) -> Result<T, SolverError> {
+        let mut cost_value = T::zero();
+        self.fbs_engine.cache.oracle_calls.cost += 1;
+        (self.fbs_engine.problem.cost)(u, &mut cost_value)?;
+        if self.nan_screening {
+            screen_output("FBS", "cost", iteration, std::slice::from_ref(&cost_value))?;
+        }
+        Ok(cost_value)
+    }
+
+    /// Screens the gradient (if screening is activated) and appends the cost
+    /// at `u` and the current norm of the fixed-point residual to the history
+    /// (if it is being recorded)
+    fn record(
+        &mut self,
+        u: &[T],
+        iteration: usize,
+        history: &mut Option<(Vec<f64>, Vec<f64>)>,
+    ) -> FunctionCallResult {
+        self.screen_gradient(iteration)?;
+        if let Some((cost_history, fpr_history)) = history {
+            let cost_value = self.cost(u, iteration)?;
+            cost_history.push(cost_value.as_f64());
+            fpr_history.push(self.fbs_engine.cache.norm_fpr.as_f64());
+        }
+        Ok(())
+    }
 }
 
-impl<'life, GradientType, ConstraintType, CostType> Optimizer
-    for FBSOptimizer<'life, GradientType, ConstraintType, CostType>
+impl<'life, GradientType, ConstraintType, CostType, T>
+    FBSOptimizer<'life, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'life,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult + 'life,
-    ConstraintType: constraints::Constraint + 'life,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'life,
+    ConstraintType: constraints::Constraint<T> + 'life,
 {
-    fn solve(&mut self, u: &mut [f64]) -> Result<SolverStatus, SolverError> {
-        let now = instant::Instant::now();
+    /// Runs the algorithm, which started at time `now` (see `solve`)
+    fn run(&mut self, u: &mut [T], now: time::Duration) -> Result<SolverStatus, SolverError> {
+        let problem_size = self.fbs_engine.cache.work_gradient_u.len();
+        if u.len() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: problem_size,
+                actual: u.len(),
+            });
+        }
 
         // Initialize - propagate error upstream, if any
         self.fbs_engine.init(u)?;
 
+        let mut history = if self.record_history {
+            Some((Vec::new(), Vec::new()))
+        } else {
+            None
+        };
+
         let mut num_iter: usize = 0;
+        let mut continue_runtime = true;
         let mut step_flag = self.fbs_engine.step(u)?;
+        self.record(u, num_iter, &mut history)?;
 
-        if let Some(dur) = self.max_duration {
-            while step_flag && num_iter < self.max_iter && dur <= now.elapsed() {
-                num_iter += 1;
-                step_flag = self.fbs_engine.step(u)?
-            }
-        } else {
-            while step_flag && num_iter < self.max_iter {
-                num_iter += 1;
-                step_flag = self.fbs_engine.step(u)?
-            }
+        while step_flag && num_iter < self.max_iter && continue_runtime {
+            num_iter += 1;
+            step_flag = self.fbs_engine.step(u)?;
+            self.record(u, num_iter, &mut history)?;
+            continue_runtime = self
+                .max_duration
+                .is_none_or(|dur| self.clock.elapsed(now) <= dur);
         }
 
         // cost at the solution [propagate error upstream]
-        let mut cost_value: f64 = 0.0;
-        (self.fbs_engine.problem.cost)(u, &mut cost_value)?;
+        let cost_value = self.cost(u, num_iter)?;
 
-        if !matrix_operations::is_finite(u) || !cost_value.is_finite() {
+        if !matrix_operations::is_finite(u) {
             return Err(SolverError::NotFiniteComputation);
         }
+        if !cost_value.is_finite() {
+            return Err(SolverError::NotFiniteCost {
+                location: "FBS",
+                iteration: num_iter,
+            });
+        }
 
         // export solution status
-        Ok(SolverStatus::new(
-            if num_iter < self.max_iter {
-                ExitStatus::Converged
-            } else {
+        let status = SolverStatus::new(
+            if num_iter >= self.max_iter {
                 ExitStatus::NotConvergedIterations
+            } else if step_flag {
+                ExitStatus::NotConvergedOutOfTime
+            } else {
+                ExitStatus::Converged
             },
             num_iter,
-            now.elapsed(),
-            self.fbs_engine.cache.norm_fpr,
-            cost_value,
-        ))
+            self.clock.elapsed(now),
+            self.fbs_engine.cache.norm_fpr.as_f64(),
+            cost_value.as_f64(),
+        )
+        .with_gradient_norm(
+            matrix_operations::norm2(&self.fbs_engine.cache.work_gradient_u).as_f64(),
+        )
+        .with_oracle_calls(self.fbs_engine.cache.oracle_calls);
+        Ok(match history {
+            Some((cost_history, fpr_history)) => status.with_history(cost_history, fpr_history),
+            None => status,
+        })
+    }
+}
+
+impl<'life, GradientType, ConstraintType, CostType, T> Optimizer<T>
+    for FBSOptimizer<'life, GradientType, ConstraintType, CostType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'life,
+    ConstraintType: constraints::Constraint<T> + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = self.clock.now();
+        let result = self.run(u, now);
+        let solve_time = self.clock.elapsed(now);
+        self.fbs_engine
+            .cache
+            .statistics
+            .record_result(&result, solve_time);
+        result
     }
 }
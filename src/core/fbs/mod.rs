@@ -25,12 +25,12 @@
 //! }
 //!
 //! let radius = 0.2;
-//! let box_constraints = Ball2::new(None, radius);
+//! let box_constraints = Ball2::new(None, radius).unwrap();
 //! let problem = Problem::new(&box_constraints, my_gradient, my_cost);
 //! let gamma = 0.3;
 //! let tolerance = 1e-6;
 //!
-//! let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), gamma, tolerance);
+//! let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), gamma, tolerance).unwrap();
 //! let mut u = [0.0; 2];
 //! let mut optimizer = FBSOptimizer::new(problem, &mut fbs_cache);
 //!
@@ -44,7 +44,7 @@ mod fbs_engine;
 mod fbs_optimizer;
 
 pub use fbs_cache::FBSCache;
-pub use fbs_optimizer::FBSOptimizer;
+pub use fbs_optimizer::{DynFBSOptimizer, FBSOptimizer};
 
 /* --------------------------------------------------------------------------------------------- */
 /*          TESTS                                                                                */
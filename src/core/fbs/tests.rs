@@ -21,7 +21,7 @@ fn t_solve_fbs_hard() {
     let gamma = 0.0005;
     let tolerance = 1e-6;
 
-    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(3).unwrap(), gamma, tolerance);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(3).unwrap(), gamma, tolerance).unwrap();
     let mut u = [-12., -160., 55.];
     let mut optimizer = FBSOptimizer::new(problem, &mut fbs_cache).with_max_iter(100_000);
     let status = optimizer.solve(&mut u).unwrap();
@@ -44,7 +44,7 @@ fn t_solve_fbs_hard_failure_nan() {
     let gamma = 0.005;
     let tolerance = 1e-6;
 
-    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(3).unwrap(), gamma, tolerance);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(3).unwrap(), gamma, tolerance).unwrap();
     let mut u = [-12., -160., 55.];
     let mut optimizer = FBSOptimizer::new(problem, &mut fbs_cache).with_max_iter(10000);
     let status = optimizer.solve(&mut u);
@@ -58,7 +58,7 @@ fn t_fbs_step_no_constraints() {
     let gamma = 0.1;
     let tolerance = 1e-6;
 
-    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance).unwrap();
     {
         let mut fbs_engine = FBSEngine::new(problem, &mut fbs_cache);
         let mut u = [1.0, 3.0];
@@ -77,12 +77,12 @@ fn t_fbs_step_no_constraints() {
 
 #[test]
 fn t_fbs_step_ball_constraints() {
-    let no_constraints = constraints::Ball2::new(None, 0.1);
+    let no_constraints = constraints::Ball2::new(None, 0.1).unwrap();
     let problem = Problem::new(&no_constraints, mocks::my_gradient, mocks::my_cost);
     let gamma = 0.1;
     let tolerance = 1e-6;
 
-    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance).unwrap();
     let mut fbs_engine = FBSEngine::new(problem, &mut fbs_cache);
 
     let mut u = [1.0, 3.0];
@@ -100,12 +100,12 @@ fn t_fbs_step_ball_constraints() {
 #[test]
 fn t_solve_fbs() {
     let radius = 0.2;
-    let box_constraints = constraints::Ball2::new(None, radius);
+    let box_constraints = constraints::Ball2::new(None, radius).unwrap();
     let problem = Problem::new(&box_constraints, mocks::my_gradient, mocks::my_cost);
     let gamma = 0.1;
     let tolerance = 1e-6;
 
-    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance).unwrap();
     let mut u = [0.0; N_DIM];
     let mut optimizer = FBSOptimizer::new(problem, &mut fbs_cache);
 
@@ -124,13 +124,13 @@ fn t_solve_fbs_many_times() {
     let tolerance = 1e-6;
 
     // The cache is constructed ONCE. This step allocates memory.
-    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), gamma, tolerance).unwrap();
 
     let mut u = [0.0; 2];
 
     for _i in 1..10 {
         // Every time NMPC is executed, the constraints may change
-        let box_constraints = constraints::Ball2::new(None, 0.2);
+        let box_constraints = constraints::Ball2::new(None, 0.2).unwrap();
 
         // The problem is surely update at every execution of NMPC
         let problem = Problem::new(&box_constraints, mocks::my_gradient, mocks::my_cost);
@@ -147,3 +147,103 @@ fn t_solve_fbs_many_times() {
         assert!(status.norm_fpr() < tolerance);
     }
 }
+
+#[test]
+fn t_solve_fbs_single_precision() {
+    let df = |u: &[f32], grad: &mut [f32]| -> FunctionCallResult {
+        grad[0] = u[0] + u[1] + 1.0;
+        grad[1] = u[0] + 2. * u[1] - 1.0;
+        Ok(())
+    };
+    let f = |u: &[f32], c: &mut f32| -> FunctionCallResult {
+        *c = 0.5 * u[0] * u[0] + u[1] * u[1] + u[0] * u[1] + u[0] - u[1] + 3.0;
+        Ok(())
+    };
+    let bounds = constraints::Ball2::new(None, 0.2_f32).unwrap();
+    let problem = Problem::new(&bounds, df, f);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), 0.1_f32, 1e-5).unwrap();
+    let mut u = [0.0_f32; 2];
+    let mut optimizer = FBSOptimizer::new(problem, &mut fbs_cache).with_max_iter(1000);
+
+    let status = optimizer.solve(&mut u).unwrap();
+
+    assert!(status.has_converged());
+    let u_f64 = [u[0] as f64, u[1] as f64];
+    unit_test_utils::assert_nearly_equal_array(
+        &mocks::SOLUTION_A,
+        &u_f64,
+        1e-4,
+        1e-5,
+        "solution (f32)",
+    );
+}
+
+#[test]
+fn t_solve_fbs_step_tolerance() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), 0.1, 1e-12).unwrap();
+    let mut u = [0.0; N_DIM];
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let reference = FBSOptimizer::new(problem, &mut fbs_cache)
+        .with_max_iter(1000)
+        .solve(&mut u)
+        .unwrap();
+
+    let mut u = [0.0; N_DIM];
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let status = FBSOptimizer::new(problem, &mut fbs_cache)
+        .with_max_iter(1000)
+        .with_step_tolerance(1e-4, 0.0)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.iterations() < reference.iterations());
+    assert!(status.norm_fpr() > 1e-12);
+    unit_test_utils::assert_nearly_equal_array(&mocks::SOLUTION_A, &u, 1e-2, 1e-3, "u");
+
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    assert!(FBSOptimizer::new(problem, &mut fbs_cache)
+        .with_step_tolerance(0.0, 0.0)
+        .is_err());
+}
+
+#[test]
+fn t_solve_fbs_history() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), 0.1, 1e-8).unwrap();
+    let mut u = [0.0; N_DIM];
+    let status = FBSOptimizer::new(problem, &mut fbs_cache)
+        .with_history(true)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.gradient_norm().is_some());
+    let cost_history = status.cost_history().unwrap();
+    assert_eq!(status.iterations() + 1, cost_history.len());
+    assert_eq!(status.cost_value(), *cost_history.last().unwrap());
+    assert_eq!(
+        status.norm_fpr(),
+        *status.fpr_history().unwrap().last().unwrap()
+    );
+}
+
+#[test]
+fn t_solve_fbs_clock() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(N_DIM).unwrap(), 0.1, 1e-12).unwrap();
+    let mut u = [0.0; N_DIM];
+    // every reading of the clock takes 1ms
+    let clock = ManualClock::new().with_tick(std::time::Duration::from_millis(1));
+    let status = FBSOptimizer::new(problem, &mut fbs_cache)
+        .with_max_iter(1000)
+        .with_max_duration(std::time::Duration::from_millis(10))
+        .with_clock(&clock)
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(ExitStatus::NotConvergedOutOfTime, status.exit_status());
+    assert_eq!(11, status.iterations());
+    assert_eq!(std::time::Duration::from_millis(12), status.solve_time());
+}
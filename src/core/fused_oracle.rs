@@ -0,0 +1,329 @@
+//! Fused cost-and-gradient oracles
+//!
+//! In many applications (e.g., when the cost function is computed by means
+//! of a simulation) most of the work required to evaluate the cost function
+//! is shared with the computation of its gradient. Instead of providing a
+//! cost function and its gradient separately, you may provide a single closure
+//! which computes both at once and wrap it in a [`FusedOracle`].
+//!
+//! A `FusedOracle` remembers the last point where the closure was invoked,
+//! so when a solver asks for the cost and the gradient at the same point
+//! (as PANOC does at every line-search trial), the closure is called only once.
+//!
+//! Similarly, problems with parametric cost functions (e.g., those that appear in
+//! [`AlmProblem`]) can use a [`ParametricFusedOracle`].
+//!
+//! [`FusedOracle`]: struct.FusedOracle.html
+//! [`ParametricFusedOracle`]: struct.ParametricFusedOracle.html
+//! [`AlmProblem`]: ../../alm/struct.AlmProblem.html
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{constraints::Ball2, core::fused_oracle::FusedOracle, panoc::*, *};
+//!
+//! let oracle = FusedOracle::new(|u: &[f64], cost: &mut f64, grad: &mut [f64]| {
+//!     // expensive computations shared by the cost and its gradient...
+//!     *cost = u[0] * u[0] + 2.0 * u[1] * u[1];
+//!     grad[0] = 2.0 * u[0];
+//!     grad[1] = 4.0 * u[1];
+//!     Ok(())
+//! });
+//! let bounds = Ball2::new(None, 1.0).unwrap();
+//! let problem = Problem::new(&bounds, oracle.gradient(), oracle.cost());
+//! let mut cache = PANOCCache::new(2, 1e-6, 5).unwrap();
+//! let mut optimizer = PANOCOptimizer::new(problem, &mut cache);
+//! let mut u = [0.5, -0.3];
+//! let status = optimizer.solve(&mut u).unwrap();
+//! assert!(status.has_converged());
+//! ```
+//!
+use crate::{FunctionCallResult, OpEnFloat};
+use std::cell::{Cell, RefCell};
+
+/// Memory of the last evaluation of a fused oracle
+struct FusedMemory<T> {
+    /// point where the oracle was last evaluated
+    u: Vec<T>,
+    /// parameter at which the oracle was last evaluated
+    p: Vec<T>,
+    /// cost at (`u`, `p`)
+    cost: T,
+    /// gradient at (`u`, `p`)
+    gradient: Vec<T>,
+    /// whether the above values are valid
+    valid: bool,
+}
+
+impl<T: OpEnFloat> FusedMemory<T> {
+    fn new() -> Self {
+        FusedMemory {
+            u: Vec::new(),
+            p: Vec::new(),
+            cost: T::zero(),
+            gradient: Vec::new(),
+            valid: false,
+        }
+    }
+
+    fn is_cached(&self, u: &[T], p: &[T]) -> bool {
+        self.valid && self.u == u && self.p == p
+    }
+
+    /// Evaluates `fused` at (`u`, `p`) unless it has already been evaluated there
+    fn evaluate<F>(&mut self, u: &[T], p: &[T], fused: F) -> FunctionCallResult
+    where
+        F: FnOnce(&mut T, &mut [T]) -> FunctionCallResult,
+    {
+        if self.is_cached(u, p) {
+            return Ok(());
+        }
+        self.valid = false;
+        self.gradient.resize(u.len(), T::zero());
+        fused(&mut self.cost, &mut self.gradient)?;
+        self.u.clear();
+        self.u.extend_from_slice(u);
+        self.p.clear();
+        self.p.extend_from_slice(p);
+        self.valid = true;
+        Ok(())
+    }
+}
+
+/// Cost function and gradient computed by a single closure
+///
+/// The closure has the form `|u, cost, grad| -> FunctionCallResult` and
+/// computes both the cost and its gradient at `u`.
+pub struct FusedOracle<F, T = f64>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut T, &mut [T]) -> FunctionCallResult,
+{
+    cost_and_gradient: F,
+    memory: RefCell<FusedMemory<T>>,
+    num_evaluations: Cell<usize>,
+}
+
+impl<F, T> FusedOracle<F, T>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut T, &mut [T]) -> FunctionCallResult,
+{
+    /// Constructs a new fused oracle from a closure which computes the cost
+    /// and its gradient
+    ///
+    /// ## Memory allocation
+    ///
+    /// The memory needed to store the last evaluation of the oracle is
+    /// allocated upon the first invocation
+    pub fn new(cost_and_gradient: F) -> Self {
+        FusedOracle {
+            cost_and_gradient,
+            memory: RefCell::new(FusedMemory::new()),
+            num_evaluations: Cell::new(0),
+        }
+    }
+
+    fn evaluate(&self, u: &[T]) -> FunctionCallResult {
+        let mut memory = self.memory.borrow_mut();
+        let f = &self.cost_and_gradient;
+        let num_evaluations = &self.num_evaluations;
+        memory.evaluate(u, &[], |cost, grad| {
+            num_evaluations.set(num_evaluations.get() + 1);
+            f(u, cost, grad)
+        })
+    }
+
+    /// Cost function, which can be passed to [`Problem`](../problem/struct.Problem.html)
+    pub fn cost(&self) -> impl Fn(&[T], &mut T) -> FunctionCallResult + '_ {
+        move |u: &[T], cost: &mut T| -> FunctionCallResult {
+            self.evaluate(u)?;
+            *cost = self.memory.borrow().cost;
+            Ok(())
+        }
+    }
+
+    /// Gradient of the cost function, which can be passed to
+    /// [`Problem`](../problem/struct.Problem.html)
+    pub fn gradient(&self) -> impl Fn(&[T], &mut [T]) -> FunctionCallResult + '_ {
+        move |u: &[T], grad: &mut [T]| -> FunctionCallResult {
+            self.evaluate(u)?;
+            grad.copy_from_slice(&self.memory.borrow().gradient);
+            Ok(())
+        }
+    }
+
+    /// Number of times the given closure has been invoked
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations.get()
+    }
+}
+
+/// Parametric cost function and gradient computed by a single closure
+///
+/// The closure has the form `|u, p, cost, grad| -> FunctionCallResult` and
+/// computes both the cost and its gradient with respect to `u` at `(u, p)`.
+/// This is meant to be used with [`AlmProblem`](../../alm/struct.AlmProblem.html).
+pub struct ParametricFusedOracle<F, T = f64>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &[T], &mut T, &mut [T]) -> FunctionCallResult,
+{
+    cost_and_gradient: F,
+    memory: RefCell<FusedMemory<T>>,
+    num_evaluations: Cell<usize>,
+}
+
+impl<F, T> ParametricFusedOracle<F, T>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &[T], &mut T, &mut [T]) -> FunctionCallResult,
+{
+    /// Constructs a new fused oracle from a closure which computes the parametric
+    /// cost and its gradient
+    ///
+    /// ## Memory allocation
+    ///
+    /// The memory needed to store the last evaluation of the oracle is
+    /// allocated upon the first invocation
+    pub fn new(cost_and_gradient: F) -> Self {
+        ParametricFusedOracle {
+            cost_and_gradient,
+            memory: RefCell::new(FusedMemory::new()),
+            num_evaluations: Cell::new(0),
+        }
+    }
+
+    fn evaluate(&self, u: &[T], p: &[T]) -> FunctionCallResult {
+        let mut memory = self.memory.borrow_mut();
+        let f = &self.cost_and_gradient;
+        let num_evaluations = &self.num_evaluations;
+        memory.evaluate(u, p, |cost, grad| {
+            num_evaluations.set(num_evaluations.get() + 1);
+            f(u, p, cost, grad)
+        })
+    }
+
+    /// Parametric cost function, which can be passed to
+    /// [`AlmProblem`](../../alm/struct.AlmProblem.html)
+    pub fn cost(&self) -> impl Fn(&[T], &[T], &mut T) -> FunctionCallResult + '_ {
+        move |u: &[T], p: &[T], cost: &mut T| -> FunctionCallResult {
+            self.evaluate(u, p)?;
+            *cost = self.memory.borrow().cost;
+            Ok(())
+        }
+    }
+
+    /// Gradient of the parametric cost function (with respect to `u`), which
+    /// can be passed to [`AlmProblem`](../../alm/struct.AlmProblem.html)
+    pub fn gradient(&self) -> impl Fn(&[T], &[T], &mut [T]) -> FunctionCallResult + '_ {
+        move |u: &[T], p: &[T], grad: &mut [T]| -> FunctionCallResult {
+            self.evaluate(u, p)?;
+            grad.copy_from_slice(&self.memory.borrow().gradient);
+            Ok(())
+        }
+    }
+
+    /// Number of times the given closure has been invoked
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations.get()
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::panoc::{PANOCCache, PANOCOptimizer};
+    use crate::core::{Optimizer, Problem};
+    use crate::{constraints, mocks, SolverError};
+
+    #[test]
+    fn t_fused_oracle_evaluates_once_per_point() {
+        let oracle = FusedOracle::new(|u: &[f64], cost: &mut f64, grad: &mut [f64]| {
+            mocks::my_cost(u, cost)?;
+            mocks::my_gradient(u, grad)
+        });
+        let cost = oracle.cost();
+        let gradient = oracle.gradient();
+        let mut c = 0.0;
+        let mut g = [0.0; 2];
+        cost(&[1.0, 2.0], &mut c).unwrap();
+        gradient(&[1.0, 2.0], &mut g).unwrap();
+        assert_eq!(1, oracle.num_evaluations());
+
+        let mut c_expected = 0.0;
+        let mut g_expected = [0.0; 2];
+        mocks::my_cost(&[1.0, 2.0], &mut c_expected).unwrap();
+        mocks::my_gradient(&[1.0, 2.0], &mut g_expected).unwrap();
+        assert_eq!(c_expected, c);
+        assert_eq!(g_expected, g);
+
+        gradient(&[1.0, 3.0], &mut g).unwrap();
+        assert_eq!(2, oracle.num_evaluations());
+    }
+
+    #[test]
+    fn t_fused_oracle_error_is_not_cached() {
+        let oracle = FusedOracle::new(|_u: &[f64], _cost: &mut f64, _grad: &mut [f64]| {
+            Err(SolverError::Cost)
+        });
+        let cost = oracle.cost();
+        let mut c = 0.0;
+        assert!(cost(&[1.0], &mut c).is_err());
+        assert!(cost(&[1.0], &mut c).is_err());
+        assert_eq!(2, oracle.num_evaluations());
+    }
+
+    #[test]
+    fn t_fused_oracle_panoc() {
+        let oracle = FusedOracle::new(|u: &[f64], cost: &mut f64, grad: &mut [f64]| {
+            mocks::my_cost(u, cost)?;
+            mocks::my_gradient(u, grad)
+        });
+        let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+        let mut panoc_cache = PANOCCache::new(2, 1e-6, 5).unwrap();
+
+        // solve the problem with a separate cost and gradient...
+        let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache);
+        let mut u_expected = [0.0; 2];
+        let status_expected = panoc.solve(&mut u_expected).unwrap();
+
+        // ...and with a fused oracle
+        let problem = Problem::new(&bounds, oracle.gradient(), oracle.cost());
+        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache);
+        let mut u = [0.0; 2];
+        let status = panoc.solve(&mut u).unwrap();
+
+        assert!(status.has_converged());
+        assert_eq!(status_expected.iterations(), status.iterations());
+        assert_eq!(u_expected, u);
+    }
+
+    #[test]
+    fn t_parametric_fused_oracle() {
+        let oracle =
+            ParametricFusedOracle::new(|u: &[f64], p: &[f64], cost: &mut f64, grad: &mut [f64]| {
+                *cost = p[0] * u[0] * u[0];
+                grad[0] = 2.0 * p[0] * u[0];
+                Ok(())
+            });
+        let cost = oracle.cost();
+        let gradient = oracle.gradient();
+        let mut c = 0.0;
+        let mut g = [0.0];
+        cost(&[3.0], &[2.0], &mut c).unwrap();
+        gradient(&[3.0], &[2.0], &mut g).unwrap();
+        assert_eq!(1, oracle.num_evaluations());
+        assert_eq!(18.0, c);
+        assert_eq!([12.0], g);
+
+        // a different parameter triggers a new evaluation
+        gradient(&[3.0], &[1.0], &mut g).unwrap();
+        assert_eq!(2, oracle.num_evaluations());
+        assert_eq!([6.0], g);
+    }
+}
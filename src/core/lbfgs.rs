@@ -0,0 +1,588 @@
+//! L-BFGS buffer
+//!
+//! Limited-memory BFGS approximation of the inverse Hessian of a function,
+//! which is used by PANOC to compute quasi-Newtonian directions. On top of
+//! the classical L-BFGS update, the buffer is only updated if the
+//! [C-BFGS conditions] of Li and Fukushima, together with basic curvature
+//! checks, are satisfied, which leads to good convergence properties for
+//! nonconvex problems.
+//!
+//! This implementation is adapted from the [`lbfgs`] crate, so that it is
+//! generic over the scalar type (see [`OpEnFloat`]).
+//!
+//! [C-BFGS conditions]: https://pdfs.semanticscholar.org/5b90/45b7d27a53b1e3c3b3f0dc6aab908cc3e0b2.pdf
+//! [`lbfgs`]: https://crates.io/crates/lbfgs
+//! [`OpEnFloat`]: ../../numeric/trait.OpEnFloat.html
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::core::lbfgs::{Lbfgs, UpdateStatus};
+//!
+//! let mut lbfgs = Lbfgs::new(3, 5).unwrap()
+//!     .with_sy_epsilon(1e-8).unwrap()
+//!     .with_cbfgs_alpha(1.0).unwrap()
+//!     .with_cbfgs_epsilon(1e-4).unwrap();
+//!
+//! // The first update is always accepted (no s or y vectors yet)
+//! assert_eq!(
+//!     UpdateStatus::UpdateOk,
+//!     lbfgs.update_hessian(&[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0])
+//! );
+//! assert_eq!(
+//!     UpdateStatus::UpdateOk,
+//!     lbfgs.update_hessian(&[-0.5, 0.6, -1.2], &[0.1, 0.2, -0.3])
+//! );
+//!
+//! // Apply the Hessian approximation on a vector
+//! let mut g = [-3.1, 1.5, 2.1];
+//! lbfgs.apply_hessian(&mut g);
+//! ```
+//!
+
+use crate::{core::heap_size, ensure, matrix_operations, OpEnFloat, SolverError};
+
+/// The default `sy_epsilon`
+pub const DEFAULT_SY_EPSILON: f64 = 1e-10;
+
+/// Initial Hessian estimate, $H_0 = \gamma I$, of the two-loop recursion
+///
+/// The scalings other than `Constant` are computed from the most recent
+/// pair `(s, y)` of the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum InitialScaling<T = f64> {
+    /// $\gamma = \langle s, y\rangle / \Vert y\Vert^2$, that is, the second
+    /// (short) Barzilai-Borwein step (default)
+    #[default]
+    SyOverYy,
+    /// $\gamma = \Vert s\Vert^2 / \langle s, y\rangle$, that is, the first
+    /// (long) Barzilai-Borwein step
+    SsOverSy,
+    /// $\gamma = \Vert s\Vert / \Vert y\Vert$, the geometric mean of the two
+    /// Barzilai-Borwein steps
+    GeometricMean,
+    /// Constant $\gamma$, which does not depend on the buffer
+    Constant(T),
+}
+
+/// Memory of an L-BFGS buffer (see `Lbfgs::export_memory`), which is part
+/// of a checkpoint of PANOC
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct LbfgsMemory {
+    /// scaling of the initial Hessian estimate
+    pub(crate) gamma: f64,
+    /// vectors `s` in use (the most recent first)
+    pub(crate) s: Vec<Vec<f64>>,
+    /// vectors `y` in use (the most recent first)
+    pub(crate) y: Vec<Vec<f64>>,
+    /// values `rho` in use
+    pub(crate) rho: Vec<f64>,
+    /// state and g of the last update (if any)
+    pub(crate) old_state_and_g: Option<(Vec<f64>, Vec<f64>)>,
+}
+
+/// L-BFGS buffer
+///
+/// `Lbfgs` maintains a buffer of pairs `(s, y)` and values `rho` (inverses
+/// of the inner products of `s` and `y`)
+#[derive(Debug, Clone)]
+pub struct Lbfgs<T = f64> {
+    /// The number of vectors in s and y that are currently in use
+    active_size: usize,
+    /// Used to warm-start the Hessian estimation with H_0 = gamma * I
+    gamma: T,
+    /// How `gamma` is chosen
+    initial_scaling: InitialScaling<T>,
+    /// s holds the vectors of state difference s_k = x_{k+1} - x_k, s_0 holds the most recent s
+    s: Vec<Vec<T>>,
+    /// y holds the vectors of the function g (usually cost function gradient) difference:
+    /// y_k = g_{k+1} - g_k, y_0 holds the most recent y
+    y: Vec<Vec<T>>,
+    /// Intermediary storage for the forward L-BFGS pass
+    alpha: Vec<T>,
+    /// Inverses of the inner products of the s and y vectors
+    rho: Vec<T>,
+    /// The alpha parameter of the C-BFGS criterion
+    cbfgs_alpha: T,
+    /// The epsilon parameter of the C-BFGS criterion
+    cbfgs_epsilon: T,
+    /// Limit on the inner product s'*y for acceptance in the buffer
+    sy_epsilon: T,
+    /// Holds the state of the last `update_hessian`, used to calculate the `s_k` vectors
+    old_state: Vec<T>,
+    /// Holds the g of the last `update_hessian`, used to calculate the `y_k` vectors
+    old_g: Vec<T>,
+    /// Check to see if the `old_*` variables have valid data
+    first_old: bool,
+}
+
+/// Outcome of an update of the L-BFGS buffer
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UpdateStatus {
+    /// The g and state was accepted to update the Hessian estimate
+    UpdateOk,
+    /// The g and state was rejected by the C-BFGS criteria
+    Rejection,
+}
+
+impl<T: OpEnFloat> Lbfgs<T> {
+    /// Number of bytes held on the heap by the buffers
+    pub(crate) fn heap_size(&self) -> usize {
+        let buffers = |vectors: &Vec<Vec<T>>| -> usize {
+            heap_size(vectors) + vectors.iter().map(heap_size).sum::<usize>()
+        };
+        buffers(&self.s)
+            + buffers(&self.y)
+            + heap_size(&self.alpha)
+            + heap_size(&self.rho)
+            + heap_size(&self.old_state)
+            + heap_size(&self.old_g)
+    }
+
+    /// Create a new L-BFGS instance with a specific problem and L-BFGS buffer size
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `problem_size` or `buffer_size`
+    /// is zero
+    pub fn new(problem_size: usize, buffer_size: usize) -> Result<Lbfgs<T>, SolverError> {
+        ensure(problem_size > 0, "problem_size", "must be positive")?;
+        ensure(buffer_size > 0, "buffer_size", "must be positive")?;
+
+        Ok(Lbfgs {
+            active_size: 0,
+            gamma: T::one(),
+            initial_scaling: InitialScaling::default(),
+            // +1 for the temporary checking area
+            s: vec![vec![T::zero(); problem_size]; buffer_size + 1],
+            y: vec![vec![T::zero(); problem_size]; buffer_size + 1],
+            alpha: vec![T::zero(); buffer_size],
+            rho: vec![T::zero(); buffer_size + 1],
+            cbfgs_alpha: T::zero(),
+            cbfgs_epsilon: T::zero(),
+            sy_epsilon: T::from_f64(DEFAULT_SY_EPSILON),
+            old_state: vec![T::zero(); problem_size],
+            old_g: vec![T::zero(); problem_size],
+            first_old: true,
+        })
+    }
+
+    /// Size of the L-BFGS buffer (memory)
+    pub(crate) fn buffer_size(&self) -> usize {
+        self.alpha.len()
+    }
+
+    /// Exports the pairs `(s, y)` in use and the data of the last update
+    pub(crate) fn export_memory(&self) -> LbfgsMemory {
+        let to_f64 = |x: &Vec<T>| -> Vec<f64> { x.iter().map(|x_i| x_i.as_f64()).collect() };
+        let k = self.active_size;
+        LbfgsMemory {
+            gamma: self.gamma.as_f64(),
+            s: self.s[..k].iter().map(to_f64).collect(),
+            y: self.y[..k].iter().map(to_f64).collect(),
+            rho: self.rho[..k].iter().map(|rho| rho.as_f64()).collect(),
+            old_state_and_g: if self.first_old {
+                None
+            } else {
+                Some((to_f64(&self.old_state), to_f64(&self.old_g)))
+            },
+        }
+    }
+
+    /// Imports a memory which was exported by `export_memory` from a buffer
+    /// of the same problem size; if the memory has more pairs `(s, y)` than
+    /// this buffer can hold, the most recent ones are imported
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the vectors of `memory` do not have the problem
+    /// size of this buffer
+    pub(crate) fn import_memory(&mut self, memory: &LbfgsMemory) {
+        let from_f64 = |x: &mut [T], x_f64: &[f64]| {
+            assert_eq!(x.len(), x_f64.len());
+            x.iter_mut()
+                .zip(x_f64)
+                .for_each(|(x_i, &x_f64_i)| *x_i = T::from_f64(x_f64_i));
+        };
+        let k = memory.s.len().min(self.buffer_size());
+        for i in 0..k {
+            from_f64(&mut self.s[i], &memory.s[i]);
+            from_f64(&mut self.y[i], &memory.y[i]);
+            self.rho[i] = T::from_f64(memory.rho[i]);
+        }
+        self.active_size = k;
+        self.gamma = T::from_f64(memory.gamma);
+        self.first_old = match &memory.old_state_and_g {
+            Some((old_state, old_g)) => {
+                from_f64(&mut self.old_state, old_state);
+                from_f64(&mut self.old_g, old_g);
+                false
+            }
+            None => true,
+        };
+    }
+
+    /// Update the default C-BFGS alpha
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `alpha` is negative
+    pub fn with_cbfgs_alpha(mut self, alpha: T) -> Result<Self, SolverError> {
+        ensure(alpha >= T::zero(), "alpha", "must be nonnegative")?;
+
+        self.cbfgs_alpha = alpha;
+        Ok(self)
+    }
+
+    /// Update the default C-BFGS epsilon
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `epsilon` is negative
+    pub fn with_cbfgs_epsilon(mut self, epsilon: T) -> Result<Self, SolverError> {
+        ensure(epsilon >= T::zero(), "epsilon", "must be nonnegative")?;
+
+        self.cbfgs_epsilon = epsilon;
+        Ok(self)
+    }
+
+    /// Update the default sy_epsilon
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `sy_epsilon` is negative
+    pub fn with_sy_epsilon(mut self, sy_epsilon: T) -> Result<Self, SolverError> {
+        ensure(sy_epsilon >= T::zero(), "sy_epsilon", "must be nonnegative")?;
+
+        self.sy_epsilon = sy_epsilon;
+        Ok(self)
+    }
+
+    /// Sets the initial Hessian estimate of the two-loop recursion (the
+    /// default is `InitialScaling::SyOverYy`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the scaling is
+    /// `InitialScaling::Constant` with a value which is not positive and finite
+    pub fn with_initial_scaling(
+        mut self,
+        initial_scaling: InitialScaling<T>,
+    ) -> Result<Self, SolverError> {
+        if let InitialScaling::Constant(gamma) = initial_scaling {
+            ensure(
+                gamma > T::zero() && gamma.is_finite(),
+                "initial_scaling",
+                "must be positive and finite",
+            )?;
+            self.gamma = gamma;
+        }
+        self.initial_scaling = initial_scaling;
+        Ok(self)
+    }
+
+    /// "Empties" the buffer
+    ///
+    /// This is a cheap operation as it amount to setting certain internal flags
+    pub fn reset(&mut self) {
+        self.active_size = 0;
+        self.first_old = true;
+    }
+
+    /// Apply the current Hessian estimate to an input vector
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the length of `g` is not equal to the problem size
+    pub fn apply_hessian(&mut self, g: &mut [T]) {
+        assert!(g.len() == self.old_g.len());
+
+        if self.active_size == 0 {
+            // No Hessian available, the g is the best we can do for now
+            return;
+        }
+
+        let active_s = &self.s[0..self.active_size];
+        let active_y = &self.y[0..self.active_size];
+        let rho = &self.rho[0..self.active_size];
+        let alpha = &mut self.alpha;
+
+        let q = g;
+
+        // Perform the forward L-BFGS algorithm
+        for (s_k, (y_k, (&rho_k, alpha_k))) in active_s
+            .iter()
+            .zip(active_y.iter().zip(rho.iter().zip(alpha.iter_mut())))
+        {
+            let a = rho_k * matrix_operations::inner_product(s_k, q);
+            *alpha_k = a;
+            q.iter_mut()
+                .zip(y_k.iter())
+                .for_each(|(q_i, &y_i)| *q_i -= a * y_i);
+        }
+
+        // Apply the initial Hessian estimate and form r = H_0 * q, where H_0 = gamma * I
+        let gamma = self.gamma;
+        q.iter_mut().for_each(|q_i| *q_i *= gamma);
+        let r = q;
+
+        // Perform the backward L-BFGS algorithm
+        for (s_k, (y_k, (&rho_k, &alpha_k))) in active_s
+            .iter()
+            .zip(active_y.iter().zip(rho.iter().zip(alpha.iter())))
+            .rev()
+        {
+            let beta = rho_k * matrix_operations::inner_product(y_k, r);
+            r.iter_mut()
+                .zip(s_k.iter())
+                .for_each(|(r_i, &s_i)| *r_i += (alpha_k - beta) * s_i);
+        }
+
+        // The g with the Hessian applied is available in the input g
+        // r = H_k * grad f
+    }
+
+    /// Check the validity of the newly added s and y vectors. Based on the condition in:
+    /// D.-H. Li and M. Fukushima, "On the global convergence of the BFGS method for nonconvex
+    /// unconstrained optimization problems," vol. 11, no. 4, pp. 1054–1064, jan 2001.
+    fn new_s_and_y_valid(&mut self, g: &[T]) -> bool {
+        let s = self.s.last().unwrap();
+        let y = self.y.last().unwrap();
+        let rho = self.rho.last_mut().unwrap();
+        let ys = matrix_operations::inner_product(s, y);
+        let norm_s_squared = matrix_operations::norm2_squared(s);
+
+        *rho = T::one() / ys;
+
+        if norm_s_squared <= T::min_positive_value()
+            || (self.sy_epsilon > T::zero() && ys <= self.sy_epsilon)
+        {
+            // In classic L-BFGS, the buffer should be updated only if
+            // y'*s is strictly positive and |s| is nonzero
+            false
+        } else if self.cbfgs_epsilon > T::zero() && self.cbfgs_alpha > T::zero() {
+            // Check the CBFGS condition of Li and Fukushima
+            // Condition: (y^T * s) / ||s||^2 > epsilon * ||grad(x)||^alpha
+            let lhs_cbfgs = ys / norm_s_squared;
+            let rhs_cbfgs = self.cbfgs_epsilon * matrix_operations::norm2(g).powf(self.cbfgs_alpha);
+
+            lhs_cbfgs > rhs_cbfgs
+        } else {
+            // The standard L-BFGS conditions are satisfied and C-BFGS is
+            // not active (either cbfgs_epsilon <= 0.0 or cbfgs_alpha <= 0.0)
+            true
+        }
+    }
+
+    /// Saves vectors to update the Hessian estimate
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the lengths of `g` and `state` are not equal
+    /// to the problem size
+    pub fn update_hessian(&mut self, g: &[T], state: &[T]) -> UpdateStatus {
+        assert!(g.len() == self.old_state.len());
+        assert!(state.len() == self.old_state.len());
+
+        // First iteration, only save
+        if self.first_old {
+            self.first_old = false;
+
+            self.old_state.copy_from_slice(state);
+            self.old_g.copy_from_slice(g);
+
+            return UpdateStatus::UpdateOk;
+        }
+
+        // Form the new s_k and y_k in the temporary area
+        difference_and_save(self.s.last_mut().unwrap(), state, &self.old_state);
+        difference_and_save(self.y.last_mut().unwrap(), g, &self.old_g);
+
+        // Check that the s and y are valid to use
+        if !self.new_s_and_y_valid(g) {
+            return UpdateStatus::Rejection;
+        }
+
+        self.old_state.copy_from_slice(state);
+        self.old_g.copy_from_slice(g);
+
+        // Move the new s_0,  y_0 and rho_0 to the front
+        self.s.rotate_right(1);
+        self.y.rotate_right(1);
+        self.rho.rotate_right(1);
+
+        // Update the Hessian estimate
+        let (ss, yy) = (
+            matrix_operations::norm2_squared(&self.s[0]),
+            matrix_operations::norm2_squared(&self.y[0]),
+        );
+        self.gamma = match self.initial_scaling {
+            InitialScaling::SyOverYy => (T::one() / self.rho[0]) / yy,
+            InitialScaling::SsOverSy => ss * self.rho[0],
+            InitialScaling::GeometricMean => (ss / yy).sqrt(),
+            InitialScaling::Constant(gamma) => gamma,
+        };
+
+        // Update the indexes and number of active, -1 comes from the temporary area used in
+        // the end of s and y to check if they are valid
+        self.active_size = (self.s.len() - 1).min(self.active_size + 1);
+
+        UpdateStatus::UpdateOk
+    }
+}
+
+/// Computes `out = a - b`
+#[inline]
+fn difference_and_save<T: OpEnFloat>(out: &mut [T], a: &[T], b: &[T]) {
+    out.iter_mut()
+        .zip(a.iter().zip(b.iter()))
+        .for_each(|(out_i, (&a_i, &b_i))| *out_i = a_i - b_i);
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_lbfgs_invalid_zero_n() {
+        assert!(Lbfgs::<f64>::new(0, 1).is_err());
+    }
+
+    #[test]
+    fn t_lbfgs_invalid_zero_mem() {
+        assert!(Lbfgs::<f64>::new(1, 0).is_err());
+    }
+
+    #[test]
+    fn t_lbfgs_invalid_cbfgs_alpha() {
+        assert!(Lbfgs::new(5, 5).unwrap().with_cbfgs_alpha(-1.0).is_err());
+    }
+
+    #[test]
+    fn t_lbfgs_buffer_storage() {
+        let mut e = Lbfgs::new(2, 3).unwrap();
+        e.update_hessian(&[1.0, 1.0], &[1.5, 1.5]);
+        assert_eq!(e.active_size, 0);
+
+        assert_eq!(
+            UpdateStatus::UpdateOk,
+            e.update_hessian(&[2.0, 2.0], &[2.5, 2.5])
+        );
+        assert_eq!(e.active_size, 1);
+        assert_eq!(&e.s[0], &[1.0, 1.0]);
+        assert_eq!(&e.y[0], &[1.0, 1.0]);
+
+        assert_eq!(
+            UpdateStatus::UpdateOk,
+            e.update_hessian(&[-3.0, -3.0], &[-3.5, -3.5])
+        );
+        assert_eq!(e.active_size, 2);
+        assert_eq!(&e.s[0], &[-6.0, -6.0]);
+        assert_eq!(&e.s[1], &[1.0, 1.0]);
+        assert_eq!(&e.y[0], &[-5.0, -5.0]);
+        assert_eq!(&e.y[1], &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn t_lbfgs_cbfgs_rejection_and_direction() {
+        let mut e = Lbfgs::new(3, 5)
+            .unwrap()
+            .with_sy_epsilon(1e-8)
+            .unwrap()
+            .with_cbfgs_alpha(1.0)
+            .unwrap()
+            .with_cbfgs_epsilon(1e-4)
+            .unwrap();
+
+        assert_eq!(
+            UpdateStatus::UpdateOk,
+            e.update_hessian(&[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0])
+        );
+        // Rejected because of the C-BFGS condition
+        assert_eq!(
+            UpdateStatus::Rejection,
+            e.update_hessian(&[-0.838, 0.260, 0.479], &[-0.5, 0.6, -1.2])
+        );
+        // Rejected because y'*s == 0 (curvature condition)
+        assert_eq!(
+            UpdateStatus::Rejection,
+            e.update_hessian(
+                &[-0.5, 0.6, -1.2],
+                &[
+                    0.419_058_177_461_747,
+                    0.869_843_029_576_958,
+                    0.260_313_940_846_084
+                ]
+            )
+        );
+        assert_eq!(
+            UpdateStatus::UpdateOk,
+            e.update_hessian(&[-0.5, 0.6, -1.2], &[0.1, 0.2, -0.3])
+        );
+
+        let mut g = [-3.1, 1.5, 2.1];
+        e.apply_hessian(&mut g);
+        unit_test_utils::assert_nearly_equal_array(
+            &[
+                -1.100_601_247_872_944,
+                -0.086_568_349_404_424,
+                0.948_633_011_911_515,
+            ],
+            &g,
+            1e-10,
+            1e-12,
+            "direction",
+        );
+    }
+
+    #[test]
+    fn t_lbfgs_initial_scaling() {
+        // one pair with s'y = 2, |s|^2 = 1 and |y|^2 = 8
+        let scalings = [
+            (InitialScaling::SyOverYy, 0.25),
+            (InitialScaling::SsOverSy, 0.5),
+            (InitialScaling::GeometricMean, 0.125_f64.sqrt()),
+            (InitialScaling::Constant(3.0), 3.0),
+        ];
+        for (scaling, gamma) in scalings {
+            let mut e = Lbfgs::new(2, 3)
+                .unwrap()
+                .with_initial_scaling(scaling)
+                .unwrap();
+            e.update_hessian(&[0.0, 0.0], &[0.0, 0.0]);
+            e.update_hessian(&[2.0, 2.0], &[1.0, 0.0]);
+            assert!((e.gamma - gamma).abs() < 1e-14);
+            // the secant condition, H y = s, holds for every scaling
+            let mut y = [2.0, 2.0];
+            e.apply_hessian(&mut y);
+            unit_test_utils::assert_nearly_equal_array(&[1.0, 0.0], &y, 1e-14, 1e-14, "Hy");
+        }
+        assert!(Lbfgs::new(2, 3)
+            .unwrap()
+            .with_initial_scaling(InitialScaling::Constant(0.0))
+            .is_err());
+    }
+
+    #[test]
+    fn t_lbfgs_single_precision() {
+        let mut e = Lbfgs::<f32>::new(3, 5).unwrap();
+        e.update_hessian(&[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0]);
+        assert_eq!(
+            UpdateStatus::UpdateOk,
+            e.update_hessian(&[-0.5, 0.6, -1.2], &[0.1, 0.2, -0.3])
+        );
+        let mut g = [-3.1_f32, 1.5, 2.1];
+        e.apply_hessian(&mut g);
+        unit_test_utils::assert_nearly_equal_array(
+            &[-1.100_601_2_f32, -0.086_568_35, 0.948_633],
+            &g,
+            1e-4,
+            1e-5,
+            "direction (f32)",
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! LM Cache
+//!
+use crate::{core::OracleCalls, ensure, OpEnFloat, SolverError};
+use std::num::NonZeroUsize;
+
+/// Default initial damping parameter
+const DEFAULT_INITIAL_DAMPING: f64 = 1e-3;
+
+/// Cache for the projected Levenberg-Marquardt method
+///
+/// This struct allocates memory needed for the algorithm
+#[derive(Debug)]
+pub struct LMCache<T = f64> {
+    pub(crate) gradient_u: Vec<T>,
+    pub(crate) u_trial: Vec<T>,
+    pub(crate) step: Vec<T>,
+    pub(crate) cg_residual: Vec<T>,
+    pub(crate) cg_direction: Vec<T>,
+    pub(crate) hessian_product: Vec<T>,
+    pub(crate) active: Vec<bool>,
+    pub(crate) cost_value: T,
+    pub(crate) initial_damping: T,
+    pub(crate) damping: T,
+    pub(crate) damping_factor: T,
+    pub(crate) tolerance: T,
+    pub(crate) norm_fpr: T,
+    pub(crate) oracle_calls: OracleCalls,
+}
+
+impl<T: OpEnFloat> LMCache<T> {
+    /// Construct a new instance of `LMCache`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n` number of decision variables
+    /// - `tolerance` tolerance used for termination
+    ///
+    /// ## Memory allocation
+    ///
+    /// This method allocates memory for `6*n + 7` floats and `n` booleans; if an optimizer
+    /// needs to be called more than once, construct an `LMCache` only once.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not positive
+    ///
+    pub fn new(n: NonZeroUsize, tolerance: T) -> Result<LMCache<T>, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        let zeros = || vec![T::zero(); n.get()];
+        Ok(LMCache {
+            gradient_u: zeros(),
+            u_trial: zeros(),
+            step: zeros(),
+            cg_residual: zeros(),
+            cg_direction: zeros(),
+            hessian_product: zeros(),
+            active: vec![false; n.get()],
+            cost_value: T::zero(),
+            initial_damping: T::from_f64(DEFAULT_INITIAL_DAMPING),
+            damping: T::from_f64(DEFAULT_INITIAL_DAMPING),
+            damping_factor: T::from_f64(2.0),
+            tolerance,
+            norm_fpr: T::infinity(),
+            oracle_calls: OracleCalls::default(),
+        })
+    }
+
+    /// Damping parameter at the end of the last solve
+    pub fn damping(&self) -> T {
+        self.damping
+    }
+}
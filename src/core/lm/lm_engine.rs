@@ -0,0 +1,266 @@
+//! LM Engine
+//!
+use crate::{
+    constraints,
+    core::{lm::LMCache, AlgorithmEngine},
+    least_squares::LeastSquares,
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+
+/// Minimum ratio of the actual to the predicted reduction of the cost for a
+/// step to be accepted
+const MIN_REDUCTION_RATIO: f64 = 1e-4;
+/// Relative rounding error of the cost, in units of the machine epsilon
+const ROUNDING_ERROR_FACTOR: f64 = 100.0;
+/// Minimum factor for the decrease of the damping parameter
+const MIN_DAMPING_DECREASE: f64 = 1.0 / 3.0;
+
+/// The LM Engine defines the steps of the algorithm and the termination criterion
+///
+pub struct LMEngine<
+    'a,
+    Residual,
+    JacobianProduct,
+    JacobianTransposeProduct,
+    ConstraintType,
+    T = f64,
+> where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    pub(crate) least_squares:
+        &'a LeastSquares<Residual, JacobianProduct, JacobianTransposeProduct, T>,
+    pub(crate) constraints: &'a ConstraintType,
+    pub(crate) cache: &'a mut LMCache<T>,
+}
+
+impl<'a, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T>
+    LMEngine<'a, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructor for instances of `LMEngine`
+    ///
+    /// ## Arguments
+    ///
+    /// - `least_squares` residual and Jacobian products
+    /// - `constraints` constraints
+    /// - mutable reference to a `cache` a cache (which is created once); the cache is reuseable
+    ///
+    pub fn new(
+        least_squares: &'a LeastSquares<Residual, JacobianProduct, JacobianTransposeProduct, T>,
+        constraints: &'a ConstraintType,
+        cache: &'a mut LMCache<T>,
+    ) -> Self {
+        LMEngine {
+            least_squares,
+            constraints,
+            cache,
+        }
+    }
+
+    /// Computes the gradient at `u` and the projected gradient residual
+    fn gradient(&mut self, u: &[T]) -> FunctionCallResult {
+        let cache = &mut *self.cache;
+        cache.oracle_calls.gradient += 1;
+        self.least_squares.gradient(u, &mut cache.gradient_u)?;
+        cache
+            .u_trial
+            .iter_mut()
+            .zip(u.iter().zip(cache.gradient_u.iter()))
+            .for_each(|(w, (&u_i, &g_i))| *w = u_i - g_i);
+        self.constraints.project(&mut cache.u_trial);
+        cache.oracle_calls.projections += 1;
+        cache.norm_fpr = matrix_operations::norm_inf_diff(u, &cache.u_trial);
+        Ok(())
+    }
+
+    /// Computes the damped Gauss-Newton step, $d$, and writes it into `step`
+    ///
+    /// The active set, $A$, consists of the coordinates which are changed by
+    /// the projection of the gradient step, $\bar{u} = \Pi_U(u - \nabla f(u))$.
+    /// On the active set, $d_A = (\bar{u}_A - u_A)/(1 + \mu)$, and on the free
+    /// variables, $F$, $d_F$ is the (approximate) solution of
+    /// $(J^\top J + \mu I)_{FF} d_F = -\nabla_F f(u) - (J^\top J)_{FA} d_A$,
+    /// which is computed with conjugate gradient iterations
+    fn damped_gauss_newton_step(&mut self, u: &[T]) -> FunctionCallResult {
+        let cache = &mut *self.cache;
+        let damping = cache.damping;
+        cache
+            .u_trial
+            .iter_mut()
+            .zip(u.iter().zip(cache.gradient_u.iter()))
+            .for_each(|(w, (&u_i, &g_i))| *w = u_i - g_i);
+        cache.cg_direction.copy_from_slice(&cache.u_trial);
+        self.constraints.project(&mut cache.u_trial);
+        cache.oracle_calls.projections += 1;
+        let mut num_free = 0;
+        for (i, d_i) in cache.step.iter_mut().enumerate() {
+            cache.active[i] = cache.u_trial[i] != cache.cg_direction[i];
+            *d_i = if cache.active[i] {
+                (cache.u_trial[i] - u[i]) / (T::one() + damping)
+            } else {
+                num_free += 1;
+                T::zero()
+            };
+        }
+        if num_free < u.len() {
+            self.least_squares
+                .hessian_product(u, &cache.step, &mut cache.hessian_product)?;
+        } else {
+            cache
+                .hessian_product
+                .iter_mut()
+                .for_each(|h| *h = T::zero());
+        }
+        for (i, r) in cache.cg_residual.iter_mut().enumerate() {
+            *r = if cache.active[i] {
+                T::zero()
+            } else {
+                -cache.gradient_u[i] - cache.hessian_product[i]
+            };
+        }
+        cache.cg_direction.copy_from_slice(&cache.cg_residual);
+        let mut rho = matrix_operations::norm2_squared(&cache.cg_residual);
+        let cg_tolerance = T::from_f64(0.1).min(rho.sqrt().sqrt()) * rho.sqrt();
+        for _ in 0..num_free {
+            if rho.sqrt() <= cg_tolerance {
+                break;
+            }
+            self.least_squares.hessian_product(
+                u,
+                &cache.cg_direction,
+                &mut cache.hessian_product,
+            )?;
+            for (i, h) in cache.hessian_product.iter_mut().enumerate() {
+                *h = if cache.active[i] {
+                    T::zero()
+                } else {
+                    *h + damping * cache.cg_direction[i]
+                };
+            }
+            let curvature =
+                matrix_operations::inner_product(&cache.cg_direction, &cache.hessian_product);
+            if curvature <= T::zero() {
+                break;
+            }
+            let alpha = rho / curvature;
+            cache
+                .step
+                .iter_mut()
+                .zip(cache.cg_direction.iter())
+                .for_each(|(d, &p)| *d += alpha * p);
+            cache
+                .cg_residual
+                .iter_mut()
+                .zip(cache.hessian_product.iter())
+                .for_each(|(r, &h)| *r -= alpha * h);
+            let rho_next = matrix_operations::norm2_squared(&cache.cg_residual);
+            let beta = rho_next / rho;
+            rho = rho_next;
+            cache
+                .cg_direction
+                .iter_mut()
+                .zip(cache.cg_residual.iter())
+                .for_each(|(p, &r)| *p = r + beta * *p);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T> AlgorithmEngine<T>
+    for LMEngine<'a, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult + 'a,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult + 'a,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult + 'a,
+    ConstraintType: constraints::Constraint<T> + 'a,
+{
+    /// Take a (projected, damped Gauss-Newton) step and check whether the
+    /// algorithm should terminate
+    ///
+    /// ## Returns
+    ///
+    /// - A boolean flag which is`true` if and only if the algorithm should not
+    ///   terminate
+    ///
+    fn step(&mut self, u_current: &mut [T]) -> Result<bool, SolverError> {
+        self.damped_gauss_newton_step(u_current)?;
+
+        // trial point, u_trial = P(u + d), and s = u_trial - u (in `step`)
+        let cache = &mut *self.cache;
+        cache
+            .u_trial
+            .iter_mut()
+            .zip(u_current.iter().zip(cache.step.iter()))
+            .for_each(|(w, (&u, &d))| *w = u + d);
+        self.constraints.project(&mut cache.u_trial);
+        cache.oracle_calls.projections += 1;
+        cache
+            .step
+            .iter_mut()
+            .zip(cache.u_trial.iter().zip(u_current.iter()))
+            .for_each(|(s, (&u_trial, &u))| *s = u_trial - u);
+
+        // predicted reduction, -<g, s> - 0.5 <s, J'J s>
+        self.least_squares
+            .hessian_product(u_current, &cache.step, &mut cache.hessian_product)?;
+        let predicted_reduction = -matrix_operations::inner_product(&cache.gradient_u, &cache.step)
+            - T::from_f64(0.5)
+                * matrix_operations::inner_product(&cache.step, &cache.hessian_product);
+
+        let mut cost_trial = T::zero();
+        cache.oracle_calls.cost += 1;
+        self.least_squares.cost(&cache.u_trial, &mut cost_trial)?;
+        let ratio = (cache.cost_value - cost_trial) / predicted_reduction;
+        // near the solution, the reductions of the cost are below its
+        // rounding errors, so the ratio is meaningless
+        let rounding_error =
+            T::from_f64(ROUNDING_ERROR_FACTOR) * T::epsilon() * cache.cost_value.abs();
+        let negligible_reduction = predicted_reduction <= rounding_error
+            && cost_trial <= cache.cost_value + rounding_error;
+
+        if predicted_reduction > T::zero()
+            && (ratio > T::from_f64(MIN_REDUCTION_RATIO) || negligible_reduction)
+        {
+            // successful step: accept the trial point and decrease the damping
+            u_current.copy_from_slice(&cache.u_trial);
+            cache.cost_value = cost_trial;
+            let two = T::from_f64(2.0);
+            let factor = T::one() - (two * ratio - T::one()).powi(3);
+            cache.damping *= factor.max(T::from_f64(MIN_DAMPING_DECREASE));
+            cache.damping_factor = two;
+            self.gradient(u_current)?;
+        } else {
+            cache.damping *= cache.damping_factor;
+            cache.damping_factor *= T::from_f64(2.0);
+            if !cache.damping.is_finite() {
+                return Err(SolverError::NotFiniteComputation);
+            }
+        }
+
+        Ok(self.cache.norm_fpr > self.cache.tolerance)
+    }
+
+    /// Computes the cost, its gradient and the projected gradient residual at
+    /// the (projected) initial point
+    fn init(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        let cache = &mut *self.cache;
+        cache.oracle_calls.reset();
+        cache.damping = cache.initial_damping;
+        cache.damping_factor = T::from_f64(2.0);
+        self.constraints.project(u_current);
+        cache.oracle_calls.projections += 1;
+        cache.oracle_calls.cost += 1;
+        self.least_squares.cost(u_current, &mut cache.cost_value)?;
+        self.gradient(u_current)
+    }
+}
@@ -0,0 +1,178 @@
+//! LM Algorithm
+//!
+use crate::{
+    constraints,
+    core::{
+        lm::{lm_engine::LMEngine, LMCache},
+        AlgorithmEngine, ExitStatus, Optimizer, SolverStatus,
+    },
+    ensure,
+    least_squares::LeastSquares,
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+const MAX_ITER: usize = 500_usize;
+
+/// Optimiser using projected Levenberg-Marquardt iterations
+///
+/// Each iteration evaluates the cost once and, if the step is accepted, its
+/// gradient once; the products with $J^\top J$ of the conjugate gradient
+/// iterations are not counted in the `OracleCalls` of the returned
+/// `SolverStatus`.
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct LMOptimizer<
+    'a,
+    Residual,
+    JacobianProduct,
+    JacobianTransposeProduct,
+    ConstraintType,
+    T = f64,
+> where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    lm_engine: LMEngine<'a, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T>,
+    max_iter: usize,
+    max_duration: Option<time::Duration>,
+}
+
+impl<'a, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T>
+    LMOptimizer<'a, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructs a new instance of `LMOptimizer`
+    ///
+    /// ## Arguments
+    ///
+    /// - `least_squares`: residual and Jacobian products
+    /// - `constraints`: constraints
+    /// - `cache`: instance of `LMCache`
+    pub fn new(
+        least_squares: &'a LeastSquares<Residual, JacobianProduct, JacobianTransposeProduct, T>,
+        constraints: &'a ConstraintType,
+        cache: &'a mut LMCache<T>,
+    ) -> Self {
+        LMOptimizer {
+            lm_engine: LMEngine::new(least_squares, constraints, cache),
+            max_iter: MAX_ITER,
+            max_duration: None,
+        }
+    }
+
+    /// Sets the tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        self.lm_engine.cache.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the initial damping parameter (the default is `1e-3`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `initial_damping` is not
+    /// positive and finite
+    pub fn with_initial_damping(self, initial_damping: T) -> Result<Self, SolverError> {
+        ensure(
+            initial_damping > T::zero() && initial_damping.is_finite(),
+            "initial_damping",
+            "must be positive and finite",
+        )?;
+        self.lm_engine.cache.initial_damping = initial_damping;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+}
+
+impl<'life, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T> Optimizer<T>
+    for LMOptimizer<'life, Residual, JacobianProduct, JacobianTransposeProduct, ConstraintType, T>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult + 'life,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult + 'life,
+    ConstraintType: constraints::Constraint<T> + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = instant::Instant::now();
+
+        let problem_size = self.lm_engine.cache.gradient_u.len();
+        for (name, actual) in [
+            ("u", u.len()),
+            ("least_squares", self.lm_engine.least_squares.dimension()),
+        ] {
+            if actual != problem_size {
+                return Err(SolverError::DimensionMismatch {
+                    name,
+                    expected: problem_size,
+                    actual,
+                });
+            }
+        }
+
+        self.lm_engine.init(u)?;
+
+        let mut num_iter: usize = 0;
+        let mut exit_status = ExitStatus::Converged;
+        while self.lm_engine.cache.norm_fpr > self.lm_engine.cache.tolerance {
+            if num_iter >= self.max_iter {
+                exit_status = ExitStatus::NotConvergedIterations;
+                break;
+            }
+            if let Some(max_duration) = self.max_duration {
+                if now.elapsed() > max_duration {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+            num_iter += 1;
+            if !self.lm_engine.step(u)? {
+                break;
+            }
+        }
+
+        let cache = &*self.lm_engine.cache;
+        if !matrix_operations::is_finite(u)
+            || !cache.norm_fpr.is_finite()
+            || !cache.cost_value.is_finite()
+        {
+            return Err(SolverError::NotFiniteComputation);
+        }
+
+        Ok(SolverStatus::new(
+            exit_status,
+            num_iter,
+            now.elapsed(),
+            cache.norm_fpr.as_f64(),
+            cache.cost_value.as_f64(),
+        )
+        .with_gradient_norm(matrix_operations::norm2(&cache.gradient_u).as_f64())
+        .with_oracle_calls(cache.oracle_calls))
+    }
+}
@@ -0,0 +1,86 @@
+//! Projected Levenberg-Marquardt method
+//!
+//! ## About
+//!
+//! This module offers a Levenberg-Marquardt (LM) method for bound-constrained
+//! nonlinear least-squares problems,
+//!
+//! $$\mathrm{Minimize}\ \tfrac{1}{2}\Vert r(u)\Vert^2 \text{ subject to } u \in U,$$
+//!
+//! where the residual, $r$, and the products of its Jacobian, $J$, with
+//! vectors are given by a [`LeastSquares`]. At every iteration, the damped
+//! Gauss-Newton system
+//!
+//! $$(J(u^k)^\top J(u^k) + \mu_k I) d^k = -J(u^k)^\top r(u^k)$$
+//!
+//! is solved with conjugate gradient iterations on the free variables (the
+//! coordinates which are not changed by the projection of the gradient step,
+//! $\Pi_U(u^k - \nabla f(u^k))$; on the other coordinates, $d^k$ is a damped
+//! projected gradient step) and the step is projected on the constraints,
+//! $\hat{u} = \Pi_U(u^k + d^k)$. The trial point is accepted
+//! if the ratio of the actual to the predicted (by the Gauss-Newton model)
+//! reduction of the cost is sufficiently positive; the damping parameter,
+//! $\mu_k$, is decreased after successful steps and increased otherwise.
+//! The iterations terminate when the projected gradient residual,
+//!
+//! $$\Vert u^k - \Pi_U(u^k - \nabla f(u^k))\Vert_\infty,$$
+//!
+//! drops below the tolerance.
+//!
+//! The projection of the step is exact for boxes (`Rectangle`), which are
+//! the usual constraints of calibration and fitting problems; for other
+//! convex sets the method still converges, but possibly slower. For such
+//! problems, `LMOptimizer` can be used instead of `PANOCOptimizer`.
+//!
+//! ## Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     constraints::Rectangle, least_squares::LeastSquares, lm::*, FunctionCallResult, Optimizer,
+//! };
+//! use std::num::NonZeroUsize;
+//!
+//! // fit y = u[0] + u[1] t^2 to the points (0, 1), (1, 3), (2, 9) with u[0] <= 0.5
+//! let (t, y) = ([0.0, 1.0, 2.0], [1.0, 3.0, 9.0]);
+//! let residual = |u: &[f64], r: &mut [f64]| -> FunctionCallResult {
+//!     for i in 0..3 {
+//!         r[i] = u[0] + u[1] * t[i] * t[i] - y[i];
+//!     }
+//!     Ok(())
+//! };
+//! let jacobian_product = |_u: &[f64], v: &[f64], jv: &mut [f64]| -> FunctionCallResult {
+//!     for i in 0..3 {
+//!         jv[i] = v[0] + v[1] * t[i] * t[i];
+//!     }
+//!     Ok(())
+//! };
+//! let jacobian_transpose_product = |_u: &[f64], w: &[f64], jtw: &mut [f64]| -> FunctionCallResult {
+//!     jtw[0] = w.iter().sum();
+//!     jtw[1] = (0..3).map(|i| t[i] * t[i] * w[i]).sum();
+//!     Ok(())
+//! };
+//! let least_squares = LeastSquares::new(2, 3, residual, jacobian_product, jacobian_transpose_product);
+//! let bounds = Rectangle::new(None, Some(&[0.5, 10.0]))?;
+//! let mut cache = LMCache::new(NonZeroUsize::new(2).unwrap(), 1e-10)?;
+//! let mut u = [0.0; 2];
+//! let status = LMOptimizer::new(&least_squares, &bounds, &mut cache).solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 0.5).abs() < 1e-10 && (u[1] - 36.5 / 17.0).abs() < 1e-8);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`LeastSquares`]: ../least_squares/struct.LeastSquares.html
+
+mod lm_cache;
+mod lm_engine;
+mod lm_optimizer;
+
+pub use lm_cache::LMCache;
+pub use lm_optimizer::LMOptimizer;
+
+/* --------------------------------------------------------------------------------------------- */
+/*          TESTS                                                                                */
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests;
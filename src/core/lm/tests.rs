@@ -0,0 +1,116 @@
+use super::*;
+use crate::{
+    constraints::{NoConstraints, Rectangle},
+    core::{panoc::PANOCOptimizer, ExitStatus},
+    least_squares::LeastSquares,
+    mocks, FunctionCallResult, Optimizer, Problem, SolverError,
+};
+use std::num::NonZeroUsize;
+
+/// Residual of the Rosenbrock function with a = 1, b = 100
+fn residual(u: &[f64], r: &mut [f64]) -> FunctionCallResult {
+    r[0] = 1.0 - u[0];
+    r[1] = 10.0 * (u[1] - u[0] * u[0]);
+    Ok(())
+}
+
+fn jacobian_product(u: &[f64], v: &[f64], jv: &mut [f64]) -> FunctionCallResult {
+    jv[0] = -v[0];
+    jv[1] = 10.0 * (v[1] - 2.0 * u[0] * v[0]);
+    Ok(())
+}
+
+fn jacobian_transpose_product(u: &[f64], w: &[f64], jtw: &mut [f64]) -> FunctionCallResult {
+    jtw[0] = -w[0] - 20.0 * u[0] * w[1];
+    jtw[1] = 10.0 * w[1];
+    Ok(())
+}
+
+#[test]
+fn t_lm_rosenbrock() {
+    let least_squares =
+        LeastSquares::new(2, 2, residual, jacobian_product, jacobian_transpose_product);
+    let no_constraints = NoConstraints::new();
+    let mut cache = LMCache::new(NonZeroUsize::new(2).unwrap(), 1e-12).unwrap();
+    let mut u = [-1.2, 1.0];
+    let status = LMOptimizer::new(&least_squares, &no_constraints, &mut cache)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.iterations() < 50);
+    assert!(status.cost_value() < 1e-20);
+    unit_test_utils::assert_nearly_equal_array(&[1.0, 1.0], &u, 1e-10, 1e-10, "u");
+    // twice the cost, with the Rosenbrock function of the mocks
+    assert_eq!(mocks::rosenbrock_cost(1.0, 100.0, &[-1.2, 1.0]), {
+        let mut c = 0.0;
+        least_squares.cost(&[-1.2, 1.0], &mut c).unwrap();
+        2.0 * c
+    });
+}
+
+#[test]
+fn t_lm_bounds() {
+    let least_squares =
+        LeastSquares::new(2, 2, residual, jacobian_product, jacobian_transpose_product);
+    let bounds = Rectangle::new(Some(&[-2.0, -2.0]), Some(&[0.5, 2.0])).unwrap();
+    let mut cache = LMCache::new(NonZeroUsize::new(2).unwrap(), 1e-10).unwrap();
+    let mut u = [-1.5, 1.5];
+    let status = LMOptimizer::new(&least_squares, &bounds, &mut cache)
+        .with_initial_damping(1.0)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.norm_fpr() <= 1e-10);
+    assert_eq!(status.iterations(), status.oracle_calls().cost() - 1);
+
+    // same solution as PANOC
+    let problem = Problem::new(
+        &bounds,
+        |u: &[f64], g: &mut [f64]| least_squares.gradient(u, g),
+        |u: &[f64], c: &mut f64| least_squares.cost(u, c),
+    );
+    let mut u_panoc = [-1.5, 1.5];
+    PANOCOptimizer::from_dimensions(problem, 2, 1e-12, 5)
+        .unwrap()
+        .with_max_iter(10_000)
+        .unwrap()
+        .solve(&mut u_panoc)
+        .unwrap();
+    unit_test_utils::assert_nearly_equal_array(&u_panoc, &u, 1e-6, 1e-7, "u");
+    assert_eq!(0.5, u[0]);
+}
+
+#[test]
+fn t_lm_max_iter_and_errors() {
+    let least_squares =
+        LeastSquares::new(2, 2, residual, jacobian_product, jacobian_transpose_product);
+    let no_constraints = NoConstraints::new();
+    let mut cache = LMCache::new(NonZeroUsize::new(2).unwrap(), 1e-12).unwrap();
+    let mut optimizer =
+        LMOptimizer::new(&least_squares, &no_constraints, &mut cache).with_max_iter(2);
+    let mut u = [-1.2, 1.0];
+    let status = optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+    assert_eq!(2, status.iterations());
+    assert!(matches!(
+        optimizer.solve(&mut [0.0; 3]),
+        Err(SolverError::DimensionMismatch {
+            name: "u",
+            expected: 2,
+            actual: 3,
+        })
+    ));
+    assert!(optimizer.with_initial_damping(0.0).is_err());
+    assert!(LMCache::<f64>::new(NonZeroUsize::new(2).unwrap(), -1.0).is_err());
+
+    let mut cache = LMCache::new(NonZeroUsize::new(3).unwrap(), 1e-12).unwrap();
+    assert!(matches!(
+        LMOptimizer::new(&least_squares, &no_constraints, &mut cache).solve(&mut [0.0; 3]),
+        Err(SolverError::DimensionMismatch {
+            name: "least_squares",
+            expected: 3,
+            actual: 2,
+        })
+    ));
+}
@@ -0,0 +1,88 @@
+//! Storage which is either borrowed or owned
+//!
+//! Optimizers and problems either borrow their caches and constraints (which
+//! is the way to reuse them across many optimizers) or take ownership of them
+//! (so that the optimizer can be stored, e.g., in a long-lived struct).
+//!
+use crate::constraints::Constraint;
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Either a shared reference to `X` or an owned `X`
+pub(crate) enum MaybeOwned<'a, X> {
+    /// borrowed instance
+    Borrowed(&'a X),
+    /// owned instance
+    Owned(X),
+}
+
+impl<'a, X> Deref for MaybeOwned<'a, X> {
+    type Target = X;
+
+    fn deref(&self) -> &X {
+        match self {
+            MaybeOwned::Borrowed(x) => x,
+            MaybeOwned::Owned(x) => x,
+        }
+    }
+}
+
+impl<'a, T, C: Constraint<T>> Constraint<T> for MaybeOwned<'a, C> {
+    fn project(&self, x: &mut [T]) {
+        (**self).project(x)
+    }
+
+    fn is_convex(&self) -> bool {
+        (**self).is_convex()
+    }
+
+    fn blocks(&self) -> Option<&[usize]> {
+        (**self).blocks()
+    }
+
+    fn block_name(&self, i: usize) -> Option<&str> {
+        (**self).block_name(i)
+    }
+}
+
+impl<'a, X: fmt::Debug> fmt::Debug for MaybeOwned<'a, X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// Either a mutable reference to `X` or an owned `X`
+pub(crate) enum MaybeOwnedMut<'a, X> {
+    /// mutably borrowed instance
+    Borrowed(&'a mut X),
+    /// owned instance
+    Owned(X),
+}
+
+impl<'a, X> Deref for MaybeOwnedMut<'a, X> {
+    type Target = X;
+
+    fn deref(&self) -> &X {
+        match self {
+            MaybeOwnedMut::Borrowed(x) => x,
+            MaybeOwnedMut::Owned(x) => x,
+        }
+    }
+}
+
+impl<'a, X> DerefMut for MaybeOwnedMut<'a, X> {
+    fn deref_mut(&mut self) -> &mut X {
+        match self {
+            MaybeOwnedMut::Borrowed(x) => x,
+            MaybeOwnedMut::Owned(x) => x,
+        }
+    }
+}
+
+impl<'a, X: fmt::Debug> fmt::Debug for MaybeOwnedMut<'a, X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
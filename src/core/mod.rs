@@ -3,19 +3,48 @@
 //!
 //!
 
+pub mod active_set;
+pub mod batch_oracle;
+pub mod cache_pool;
+pub(crate) mod checkpoint;
+pub mod clock;
+pub mod drs;
 pub mod fbs;
+pub mod fused_oracle;
+pub mod lbfgs;
+pub mod lm;
+pub(crate) mod maybe_owned;
+pub mod nelder_mead;
+pub mod newton_cg;
+pub mod oracle_calls;
 pub mod panoc;
+pub mod pdhg;
+pub mod pgbb;
+pub mod phase_times;
 pub mod problem;
+pub mod progress;
+pub mod solver_statistics;
 pub mod solver_status;
+pub mod solver_trace;
 
 pub use crate::{constraints, FunctionCallResult, SolverError};
-pub use problem::Problem;
-pub use solver_status::SolverStatus;
+use crate::{ensure, matrix_operations, OpEnFloat};
+pub use active_set::ActiveSet;
+pub use cache_pool::{CachePool, PooledCache};
+pub use clock::{Clock, ManualClock, StdClock};
+pub use oracle_calls::OracleCalls;
+pub use phase_times::PhaseTimes;
+pub use problem::{DynProblem, Problem};
+pub use progress::{Progress, ProgressEvent, Verbosity};
+pub use solver_statistics::SolverStatistics;
+pub use solver_status::{SolverStatus, SolverWarning};
+pub use solver_trace::SolverTrace;
 
 /// Exit status of an algorithm (not algorithm specific)
 ///
 ///
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExitStatus {
     /// The algorithm has converged
     ///
@@ -26,15 +55,74 @@ pub enum ExitStatus {
     NotConvergedIterations,
     /// Failed to converge because the maximum execution time was reached
     NotConvergedOutOfTime,
+    /// Stopped because the problem is ill-conditioned (e.g., the step size
+    /// of PANOC collapsed, see `PANOCOptimizer::with_ill_conditioning_detection`)
+    Diverged,
+}
+
+impl std::fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitStatus::Converged => write!(f, "converged"),
+            ExitStatus::NotConvergedIterations => {
+                write!(f, "not converged (maximum number of iterations)")
+            }
+            ExitStatus::NotConvergedOutOfTime => write!(f, "not converged (out of time)"),
+            ExitStatus::Diverged => write!(f, "diverged (ill-conditioned problem)"),
+        }
+    }
 }
 
 /// A general optimizer
-pub trait Optimizer {
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+///
+/// All optimizers of OpEn (`PANOCOptimizer`, `FBSOptimizer`, `PGBBOptimizer`,
+/// `DRSOptimizer`, `PDHGOptimizer`, `LMOptimizer`, `NelderMeadOptimizer`, `NewtonCGOptimizer` and `AlmOptimizer`) implement this trait and return a
+/// `SolverStatus`; PANOC, FBS and PGBB solve the same `Problem`, so they can
+/// be swapped behind a generic parameter or a trait object. `AlmOptimizer` returns a summary of its `AlmOptimizerStatus`
+/// (the latter is returned by the inherent method `AlmOptimizer::solve`).
+///
+/// # Example
+///
+/// ```rust
+/// use optimization_engine::{constraints::Ball2, core::{fbs::*, panoc::*, SolverStatus}, *};
+/// use std::num::NonZeroUsize;
+///
+/// fn run(optimizer: &mut dyn Optimizer, u: &mut [f64]) -> Result<SolverStatus, SolverError> {
+///     optimizer.solve(u)
+/// }
+///
+/// let df = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+///     grad[0] = u[0] - 1.0;
+///     grad[1] = u[1] + 2.0;
+///     Ok(())
+/// };
+/// let f = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+///     *c = 0.5 * ((u[0] - 1.0).powi(2) + (u[1] + 2.0).powi(2));
+///     Ok(())
+/// };
+/// let bounds = Ball2::new(None, 1.0)?;
+///
+/// let mut panoc_cache = PANOCCache::new(2, 1e-8, 5)?;
+/// let mut panoc = PANOCOptimizer::new(Problem::new(&bounds, df, f), &mut panoc_cache);
+///
+/// let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), 0.5, 1e-8)?;
+/// let mut fbs = FBSOptimizer::new(Problem::new(&bounds, df, f), &mut fbs_cache);
+///
+/// let optimizers: [&mut dyn Optimizer; 2] = [&mut panoc, &mut fbs];
+/// for optimizer in optimizers {
+///     let mut u = [0.0; 2];
+///     assert!(run(optimizer, &mut u)?.has_converged());
+/// }
+/// # Ok::<(), SolverError>(())
+/// ```
+pub trait Optimizer<T = f64> {
     /// solves a given problem and updates the initial estimate `u` with the solution
     ///
     /// Returns the solver status
     ///
-    fn solve(&mut self, u: &mut [f64]) -> Result<SolverStatus, SolverError>;
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError>;
 }
 
 /// Engine supporting an algorithm
@@ -46,10 +134,72 @@ pub trait Optimizer {
 /// It defines what the algorithm does at every step (see `step`) and whether
 /// the specified termination criterion is satisfied
 ///
-pub trait AlgorithmEngine {
+pub trait AlgorithmEngine<T = f64> {
     /// Take a step of the algorithm and return `Ok(true)` only if the iterations should continue
-    fn step(&mut self, u: &mut [f64]) -> Result<bool, SolverError>;
+    fn step(&mut self, u: &mut [T]) -> Result<bool, SolverError>;
 
     /// Initializes the algorithm
-    fn init(&mut self, u: &mut [f64]) -> FunctionCallResult;
+    fn init(&mut self, u: &mut [T]) -> FunctionCallResult;
+}
+
+/// Number of bytes which `vector` holds on the heap
+#[allow(clippy::ptr_arg)]
+pub(crate) fn heap_size<T>(vector: &Vec<T>) -> usize {
+    vector.capacity() * std::mem::size_of::<T>()
+}
+
+/// Screens the output of a user-defined function for NaN or infinite values
+///
+/// Returns `SolverError::NotFiniteOutput`, which indicates the first non-finite
+/// element of `values`, if any, otherwise `Ok(())`
+pub(crate) fn screen_output<T: OpEnFloat>(
+    location: &'static str,
+    function: &'static str,
+    iteration: usize,
+    values: &[T],
+) -> FunctionCallResult {
+    match matrix_operations::first_non_finite(values) {
+        Some(index) => Err(SolverError::NotFiniteOutput {
+            location,
+            function,
+            index,
+            iteration,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Tolerances of the termination criterion on the change of the iterate,
+/// `||u - u_previous|| <= relative * ||u|| + absolute`, which is checked
+/// in addition to the criterion on the fixed-point residual (see, e.g.,
+/// `PANOCOptimizer::with_step_tolerance`)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StepTolerance<T> {
+    pub(crate) relative: T,
+    pub(crate) absolute: T,
+}
+
+impl<T: OpEnFloat> StepTolerance<T> {
+    /// Constructs the tolerances
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if either tolerance is
+    /// negative or both are zero
+    pub(crate) fn new(relative: T, absolute: T) -> Result<StepTolerance<T>, SolverError> {
+        ensure(relative >= T::zero(), "relative", "must be nonnegative")?;
+        ensure(absolute >= T::zero(), "absolute", "must be nonnegative")?;
+        ensure(
+            relative > T::zero() || absolute > T::zero(),
+            "absolute",
+            "must be positive if the relative tolerance is zero",
+        )?;
+        Ok(StepTolerance { relative, absolute })
+    }
+
+    /// Whether the change from `u_previous` to `u` is within the tolerances
+    pub(crate) fn is_satisfied(&self, u: &[T], u_previous: &[T]) -> bool {
+        let change = matrix_operations::norm2_squared_diff(u, u_previous).sqrt();
+        change <= self.relative * matrix_operations::norm2(u) + self.absolute
+    }
 }
@@ -0,0 +1,65 @@
+//! Nelder-Mead method
+//!
+//! ## About
+//!
+//! This module offers the Nelder-Mead (downhill simplex) method for problems
+//! of the form
+//!
+//! $$\mathrm{Minimize}\ f(u) \text{ subject to } u \in U,$$
+//!
+//! where only the cost function, $f$, is available (no gradient). Starting
+//! from a simplex of $n + 1$ vertices around the initial guess, every
+//! iteration replaces the worst vertex by its reflection through the
+//! centroid of the other vertices (possibly expanded or contracted), or
+//! shrinks the simplex towards the best vertex. New vertices are projected
+//! on $U$, which is exact for boxes (`Rectangle`), the usual constraints of
+//! tuning problems. The iterations terminate when both the diameter of the
+//! simplex (in the infinity norm) and the spread of the costs at its
+//! vertices drop below the tolerance. Points where the cost is not finite
+//! are never accepted, so the cost may return NaN or infinity, e.g., when a
+//! simulated closed loop is unstable.
+//!
+//! The method is a fallback for low-dimensional problems (e.g., up to ten
+//! variables), such as tuning a few controller gains; it converges slowly
+//! and may stall on problems of higher dimension, or on nonsmooth problems
+//! whose minimizers lie on the boundary of $U$, for which a gradient
+//! (e.g., from [`finite_difference`] or [`autodiff`]) and PANOC are
+//! preferable.
+//!
+//! ## Example
+//!
+//! ```
+//! use optimization_engine::{constraints::Rectangle, nelder_mead::*, *};
+//! use std::num::NonZeroUsize;
+//!
+//! // tune the gains (kp, ki, kd) of a controller
+//! let cost = |k: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = (k[0] - 2.0).powi(2) + 10.0 * (k[1] - 0.5).powi(2) + (k[2] - k[0] / 4.0).powi(2);
+//!     Ok(())
+//! };
+//! let bounds = Rectangle::new(Some(&[0.0; 3]), Some(&[1.5, 5.0, 5.0]))?;
+//! let mut cache = NelderMeadCache::new(NonZeroUsize::new(3).unwrap(), 1e-8)?;
+//! let mut k = [1.0, 1.0, 1.0];
+//! let status = NelderMeadOptimizer::new(&bounds, cost, &mut cache).solve(&mut k)?;
+//! assert!(status.has_converged());
+//! assert!((k[0] - 1.5).abs() < 1e-6 && (k[1] - 0.5).abs() < 1e-6);
+//! assert!((k[2] - 0.375).abs() < 1e-6);
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! [`finite_difference`]: ../finite_difference/index.html
+//! [`autodiff`]: ../autodiff/index.html
+
+mod nelder_mead_cache;
+mod nelder_mead_engine;
+mod nelder_mead_optimizer;
+
+pub use nelder_mead_cache::NelderMeadCache;
+pub use nelder_mead_optimizer::NelderMeadOptimizer;
+
+/* --------------------------------------------------------------------------------------------- */
+/*          TESTS                                                                                */
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,75 @@
+//! Nelder-Mead Cache
+//!
+use crate::{core::OracleCalls, ensure, OpEnFloat, SolverError};
+use std::num::NonZeroUsize;
+
+/// Default initial step, relative to $\max\\{1, |u_i|\\}$
+const DEFAULT_INITIAL_STEP: f64 = 0.1;
+
+/// Cache for the Nelder-Mead method
+///
+/// This struct allocates memory needed for the algorithm
+#[derive(Debug)]
+pub struct NelderMeadCache<T = f64> {
+    /// vertices of the simplex, sorted by cost
+    pub(crate) simplex: Vec<Vec<T>>,
+    /// costs at the vertices of the simplex
+    pub(crate) values: Vec<T>,
+    pub(crate) centroid: Vec<T>,
+    pub(crate) reflected: Vec<T>,
+    pub(crate) trial: Vec<T>,
+    pub(crate) initial_step: T,
+    pub(crate) tolerance: T,
+    /// infinity-norm distance of the vertices from the best vertex
+    pub(crate) diameter: T,
+    /// difference between the worst and the best cost
+    pub(crate) cost_spread: T,
+    pub(crate) oracle_calls: OracleCalls,
+}
+
+impl<T: OpEnFloat> NelderMeadCache<T> {
+    /// Construct a new instance of `NelderMeadCache`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n` number of decision variables
+    /// - `tolerance` tolerance used for termination
+    ///
+    /// ## Memory allocation
+    ///
+    /// This method allocates memory for `(n + 1)*(n + 1) + 3*n + 4` floats; if an
+    /// optimizer needs to be called more than once, construct a `NelderMeadCache` only once.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not positive
+    ///
+    pub fn new(n: NonZeroUsize, tolerance: T) -> Result<NelderMeadCache<T>, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        let zeros = || vec![T::zero(); n.get()];
+        Ok(NelderMeadCache {
+            simplex: (0..=n.get()).map(|_| zeros()).collect(),
+            values: vec![T::infinity(); n.get() + 1],
+            centroid: zeros(),
+            reflected: zeros(),
+            trial: zeros(),
+            initial_step: T::from_f64(DEFAULT_INITIAL_STEP),
+            tolerance,
+            diameter: T::infinity(),
+            cost_spread: T::infinity(),
+            oracle_calls: OracleCalls::default(),
+        })
+    }
+
+    /// Vertices of the simplex at the end of the last solve, sorted by cost
+    /// (best first)
+    pub fn simplex(&self) -> &[Vec<T>] {
+        &self.simplex
+    }
+
+    /// Difference between the largest and the smallest cost at the vertices
+    /// of the simplex at the end of the last solve
+    pub fn cost_spread(&self) -> T {
+        self.cost_spread
+    }
+}
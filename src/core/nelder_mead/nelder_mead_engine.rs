@@ -0,0 +1,262 @@
+//! Nelder-Mead Engine
+//!
+use crate::{
+    constraints,
+    core::{nelder_mead::NelderMeadCache, AlgorithmEngine, OracleCalls},
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+
+/// The Nelder-Mead Engine defines the steps of the algorithm
+///
+pub struct NelderMeadEngine<'a, CostType, ConstraintType, T = f64>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    pub(crate) cost: CostType,
+    pub(crate) constraints: &'a ConstraintType,
+    pub(crate) cache: &'a mut NelderMeadCache<T>,
+    /// coefficients of reflection, expansion, contraction and shrinkage
+    reflection: T,
+    expansion: T,
+    contraction: T,
+    shrinkage: T,
+}
+
+impl<'a, CostType, ConstraintType, T> NelderMeadEngine<'a, CostType, ConstraintType, T>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructor for instances of `NelderMeadEngine`
+    ///
+    /// The coefficients depend on the dimension, $n$, of the problem as
+    /// proposed by Gao and Han (2012), that is, $1$, $1 + 2/n$,
+    /// $3/4 - 1/(2n)$ and $1 - 1/n$ for the reflection, the expansion, the
+    /// contraction and the shrinkage respectively (with $n \geq 2$), which
+    /// are the standard coefficients for $n = 2$.
+    ///
+    /// ## Arguments
+    ///
+    /// - `cost` cost function
+    /// - `constraints` constraints
+    /// - mutable reference to a `cache` a cache (which is created once); the cache is reuseable
+    ///
+    pub fn new(
+        cost: CostType,
+        constraints: &'a ConstraintType,
+        cache: &'a mut NelderMeadCache<T>,
+    ) -> NelderMeadEngine<'a, CostType, ConstraintType, T> {
+        let n = T::from_f64(cache.centroid.len().max(2) as f64);
+        let (one, two) = (T::one(), T::from_f64(2.0));
+        NelderMeadEngine {
+            cost,
+            constraints,
+            cache,
+            reflection: one,
+            expansion: one + two / n,
+            contraction: T::from_f64(0.75) - one / (two * n),
+            shrinkage: one - one / n,
+        }
+    }
+
+    /// Computes `result = a + coefficient * (b - a)` and projects it on the
+    /// constraints
+    fn combine(&mut self, which: Point, coefficient: T) {
+        let cache = &mut *self.cache;
+        let n = cache.centroid.len();
+        let (a, b, result): (&[T], &[T], &mut [T]) = match which {
+            Point::Reflected => (&cache.centroid, &cache.simplex[n], &mut cache.reflected),
+            Point::TrialFromReflected => (&cache.centroid, &cache.reflected, &mut cache.trial),
+            Point::TrialFromWorst => (&cache.centroid, &cache.simplex[n], &mut cache.trial),
+        };
+        result
+            .iter_mut()
+            .zip(a.iter().zip(b.iter()))
+            .for_each(|(r, (&ai, &bi))| *r = ai + coefficient * (bi - ai));
+        cache.oracle_calls.projections += 1;
+        self.constraints.project(result);
+    }
+
+    /// Replaces the worst vertex by `point` (either the reflected or the
+    /// trial point)
+    fn replace_worst(&mut self, use_reflected: bool, value: T) {
+        let cache = &mut *self.cache;
+        let n = cache.centroid.len();
+        let point = if use_reflected {
+            &cache.reflected
+        } else {
+            &cache.trial
+        };
+        cache.simplex[n].copy_from_slice(point);
+        cache.values[n] = value;
+    }
+
+    /// Shrinks the simplex towards the best vertex
+    fn shrink(&mut self) -> FunctionCallResult {
+        let (cache, shrinkage) = (&mut *self.cache, self.shrinkage);
+        let (best, others) = cache.simplex.split_at_mut(1);
+        for (vertex, value) in others.iter_mut().zip(cache.values[1..].iter_mut()) {
+            vertex
+                .iter_mut()
+                .zip(best[0].iter())
+                .for_each(|(v, &b)| *v = b + shrinkage * (*v - b));
+            cache.oracle_calls.projections += 1;
+            self.constraints.project(vertex);
+            *value = evaluate(&mut self.cost, &mut cache.oracle_calls, vertex)?;
+        }
+        Ok(())
+    }
+
+    /// Sorts the vertices by cost, copies the best vertex into `u` and
+    /// computes the diameter of the simplex and the spread of the costs
+    fn sort_simplex(&mut self, u: &mut [T]) {
+        let cache = &mut *self.cache;
+        for i in 1..cache.values.len() {
+            let mut j = i;
+            while j > 0 && cache.values[j] < cache.values[j - 1] {
+                cache.values.swap(j, j - 1);
+                cache.simplex.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+        u.copy_from_slice(&cache.simplex[0]);
+        cache.diameter = cache.simplex[1..]
+            .iter()
+            .map(|vertex| matrix_operations::norm_inf_diff(vertex, u))
+            .fold(T::zero(), T::max);
+        cache.cost_spread = cache.values[cache.values.len() - 1] - cache.values[0];
+    }
+}
+
+/// Evaluates the cost at `u`; non-finite values are replaced by infinity,
+/// so that such points are never accepted
+fn evaluate<T, CostType>(
+    cost: &mut CostType,
+    oracle_calls: &mut OracleCalls,
+    u: &[T],
+) -> Result<T, SolverError>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+{
+    let mut value = T::zero();
+    oracle_calls.cost += 1;
+    cost(u, &mut value)?;
+    Ok(if value.is_finite() {
+        value
+    } else {
+        T::infinity()
+    })
+}
+
+/// Points computed by `NelderMeadEngine::combine`
+#[derive(Clone, Copy)]
+enum Point {
+    /// reflection of the worst vertex through the centroid
+    Reflected,
+    /// expansion or outside contraction
+    TrialFromReflected,
+    /// inside contraction
+    TrialFromWorst,
+}
+
+impl<'a, CostType, ConstraintType, T> AlgorithmEngine<T>
+    for NelderMeadEngine<'a, CostType, ConstraintType, T>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Takes a step of the Nelder-Mead method (a reflection, possibly
+    /// followed by an expansion or a contraction, or a shrinkage)
+    ///
+    /// Returns `Ok(true)`, that is, the termination criterion is checked by
+    /// `NelderMeadOptimizer`
+    fn step(&mut self, u: &mut [T]) -> Result<bool, SolverError> {
+        let n = self.cache.centroid.len();
+        let n_as_float = T::from_f64(n as f64);
+        {
+            let cache = &mut *self.cache;
+            cache.centroid.fill(T::zero());
+            for vertex in &cache.simplex[..n] {
+                cache
+                    .centroid
+                    .iter_mut()
+                    .zip(vertex.iter())
+                    .for_each(|(c, &v)| *c += v / n_as_float);
+            }
+        }
+        let (best, second_worst, worst) = (
+            self.cache.values[0],
+            self.cache.values[n - 1],
+            self.cache.values[n],
+        );
+
+        self.combine(Point::Reflected, -self.reflection);
+        let cache = &mut *self.cache;
+        let reflected = evaluate(&mut self.cost, &mut cache.oracle_calls, &cache.reflected)?;
+        if reflected < best {
+            self.combine(Point::TrialFromReflected, self.expansion);
+            let cache = &mut *self.cache;
+            let expanded = evaluate(&mut self.cost, &mut cache.oracle_calls, &cache.trial)?;
+            if expanded < reflected {
+                self.replace_worst(false, expanded);
+            } else {
+                self.replace_worst(true, reflected);
+            }
+        } else if reflected < second_worst {
+            self.replace_worst(true, reflected);
+        } else {
+            let outside = reflected < worst;
+            let point = if outside {
+                Point::TrialFromReflected
+            } else {
+                Point::TrialFromWorst
+            };
+            self.combine(point, self.contraction);
+            let cache = &mut *self.cache;
+            let contracted = evaluate(&mut self.cost, &mut cache.oracle_calls, &cache.trial)?;
+            if (outside && contracted <= reflected) || (!outside && contracted < worst) {
+                self.replace_worst(false, contracted);
+            } else {
+                self.shrink()?;
+            }
+        }
+        self.sort_simplex(u);
+        Ok(true)
+    }
+
+    /// Constructs the initial simplex, with vertices $\Pi_U(u)$ and
+    /// $\Pi_U(u \pm h_i e_i)$ (the latter if the projection of $u + h_i e_i$
+    /// coincides with $\Pi_U(u)$), where $h_i = h\max\\{1, |u_i|\\}$ and $h$ is
+    /// the initial step
+    fn init(&mut self, u: &mut [T]) -> FunctionCallResult {
+        let cache = &mut *self.cache;
+        cache.oracle_calls.reset();
+        cache.oracle_calls.projections += 1;
+        self.constraints.project(u);
+        let initial_step = cache.initial_step;
+        let (first, others) = cache.simplex.split_at_mut(1);
+        first[0].copy_from_slice(u);
+        for (i, vertex) in others.iter_mut().enumerate() {
+            let h = initial_step * T::one().max(u[i].abs());
+            for direction in [h, -h] {
+                vertex.copy_from_slice(u);
+                vertex[i] += direction;
+                cache.oracle_calls.projections += 1;
+                self.constraints.project(vertex);
+                if matrix_operations::norm_inf_diff(vertex, u) > T::zero() {
+                    break;
+                }
+            }
+        }
+        for (vertex, value) in cache.simplex.iter().zip(cache.values.iter_mut()) {
+            *value = evaluate(&mut self.cost, &mut cache.oracle_calls, vertex)?;
+        }
+        self.sort_simplex(u);
+        Ok(())
+    }
+}
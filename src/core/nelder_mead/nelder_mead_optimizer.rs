@@ -0,0 +1,159 @@
+//! Nelder-Mead Algorithm
+//!
+use crate::{
+    constraints,
+    core::{
+        nelder_mead::{nelder_mead_engine::NelderMeadEngine, NelderMeadCache},
+        AlgorithmEngine, ExitStatus, Optimizer, SolverStatus,
+    },
+    ensure, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+const MAX_ITER: usize = 1000_usize;
+
+/// Optimiser using the Nelder-Mead (downhill simplex) method
+///
+/// Each iteration evaluates the cost once or twice, or $n + 1$ times if the
+/// simplex is shrunk. The returned `SolverStatus` reports the diameter of the
+/// simplex as the norm of the fixed-point residual.
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct NelderMeadOptimizer<'a, CostType, ConstraintType, T = f64>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    nelder_mead_engine: NelderMeadEngine<'a, CostType, ConstraintType, T>,
+    max_iter: usize,
+    max_duration: Option<time::Duration>,
+}
+
+impl<'a, CostType, ConstraintType, T> NelderMeadOptimizer<'a, CostType, ConstraintType, T>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructs a new instance of `NelderMeadOptimizer`
+    ///
+    /// ## Arguments
+    ///
+    /// - `constraints`: constraints
+    /// - `cost`: cost function
+    /// - `cache`: instance of `NelderMeadCache`
+    pub fn new(
+        constraints: &'a ConstraintType,
+        cost: CostType,
+        cache: &'a mut NelderMeadCache<T>,
+    ) -> Self {
+        NelderMeadOptimizer {
+            nelder_mead_engine: NelderMeadEngine::new(cost, constraints, cache),
+            max_iter: MAX_ITER,
+            max_duration: None,
+        }
+    }
+
+    /// Sets the tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        self.nelder_mead_engine.cache.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the initial step, $h$, that is, the size of the initial simplex
+    /// relative to $\max\\{1, |u_i|\\}$ (the default is `0.1`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `initial_step` is not
+    /// positive and finite
+    pub fn with_initial_step(self, initial_step: T) -> Result<Self, SolverError> {
+        ensure(
+            initial_step > T::zero() && initial_step.is_finite(),
+            "initial_step",
+            "must be positive and finite",
+        )?;
+        self.nelder_mead_engine.cache.initial_step = initial_step;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Whether the simplex is small enough, that is, its diameter and the
+    /// spread of the costs at its vertices are below the tolerance
+    fn has_converged(&self) -> bool {
+        let cache = &*self.nelder_mead_engine.cache;
+        cache.diameter <= cache.tolerance && cache.cost_spread <= cache.tolerance
+    }
+}
+
+impl<'life, CostType, ConstraintType, T> Optimizer<T>
+    for NelderMeadOptimizer<'life, CostType, ConstraintType, T>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'life,
+    ConstraintType: constraints::Constraint<T> + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = instant::Instant::now();
+
+        let problem_size = self.nelder_mead_engine.cache.centroid.len();
+        if u.len() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: problem_size,
+                actual: u.len(),
+            });
+        }
+
+        self.nelder_mead_engine.init(u)?;
+
+        let mut num_iter: usize = 0;
+        let mut exit_status = ExitStatus::Converged;
+        while !self.has_converged() {
+            if num_iter >= self.max_iter {
+                exit_status = ExitStatus::NotConvergedIterations;
+                break;
+            }
+            if let Some(max_duration) = self.max_duration {
+                if now.elapsed() > max_duration {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+            num_iter += 1;
+            self.nelder_mead_engine.step(u)?;
+        }
+
+        let cache = &*self.nelder_mead_engine.cache;
+        if !cache.values[0].is_finite() {
+            return Err(SolverError::NotFiniteComputation);
+        }
+
+        Ok(SolverStatus::new(
+            exit_status,
+            num_iter,
+            now.elapsed(),
+            cache.diameter.as_f64(),
+            cache.values[0].as_f64(),
+        )
+        .with_oracle_calls(cache.oracle_calls))
+    }
+}
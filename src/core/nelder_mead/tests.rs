@@ -0,0 +1,93 @@
+use super::*;
+use crate::{
+    constraints::{NoConstraints, Rectangle},
+    core::ExitStatus,
+    mocks, FunctionCallResult, Optimizer, SolverError,
+};
+use std::num::NonZeroUsize;
+
+fn rosenbrock(u: &[f64], cost: &mut f64) -> FunctionCallResult {
+    *cost = mocks::rosenbrock_cost(1.0, 100.0, u);
+    Ok(())
+}
+
+#[test]
+fn t_nelder_mead_rosenbrock() {
+    let no_constraints = NoConstraints::new();
+    let mut cache = NelderMeadCache::new(NonZeroUsize::new(2).unwrap(), 1e-10).unwrap();
+    let mut u = [-1.2, 1.0];
+    let status = NelderMeadOptimizer::new(&no_constraints, rosenbrock, &mut cache)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.norm_fpr() <= 1e-10);
+    assert!(status.cost_value() < 1e-16);
+    unit_test_utils::assert_nearly_equal_array(&[1.0, 1.0], &u, 1e-8, 1e-8, "u");
+    assert_eq!(u.to_vec(), cache.simplex()[0]);
+    assert!(cache.cost_spread() <= 1e-10);
+    assert!(status.oracle_calls().cost() > status.iterations());
+    assert_eq!(0, status.oracle_calls().gradient());
+}
+
+#[test]
+fn t_nelder_mead_bounds_and_non_finite_costs() {
+    // the cost is infinite outside a disc and the minimizer lies on a bound
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = if u[0] * u[0] + u[1] * u[1] > 9.0 {
+            f64::NAN
+        } else {
+            (u[0] - 2.0).powi(2) + (u[1] + 1.0).powi(2) + u[2].powi(4)
+        };
+        Ok(())
+    };
+    let bounds = Rectangle::new(Some(&[-5.0, -0.5, -1.0]), Some(&[5.0, 5.0, 1.0])).unwrap();
+    let mut cache = NelderMeadCache::new(NonZeroUsize::new(3).unwrap(), 1e-9).unwrap();
+    let mut u = [0.0, 2.0, 0.5];
+    let status = NelderMeadOptimizer::new(&bounds, cost, &mut cache)
+        .with_initial_step(0.5)
+        .unwrap()
+        .with_max_iter(5000)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!((u[0] - 2.0).abs() < 1e-6 && (u[1] + 0.5).abs() < 1e-9);
+    assert!(u[2].abs() < 1e-2);
+    assert!(status.oracle_calls().projections() > status.iterations());
+}
+
+#[test]
+fn t_nelder_mead_max_iter_and_errors() {
+    let no_constraints = NoConstraints::new();
+    let mut cache = NelderMeadCache::new(NonZeroUsize::new(2).unwrap(), 1e-12).unwrap();
+    let mut u = [-1.2, 1.0];
+    let status = NelderMeadOptimizer::new(&no_constraints, rosenbrock, &mut cache)
+        .with_max_iter(10)
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+    assert_eq!(10, status.iterations());
+    assert!(status.cost_value() < mocks::rosenbrock_cost(1.0, 100.0, &[-1.2, 1.0]));
+
+    assert!(matches!(
+        NelderMeadOptimizer::new(&no_constraints, rosenbrock, &mut cache).solve(&mut [0.0; 3]),
+        Err(SolverError::DimensionMismatch {
+            name: "u",
+            expected: 2,
+            actual: 3,
+        })
+    ));
+    let nan = |_u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = f64::NAN;
+        Ok(())
+    };
+    assert_eq!(
+        Err(SolverError::NotFiniteComputation),
+        NelderMeadOptimizer::new(&no_constraints, nan, &mut cache).solve(&mut u)
+    );
+    assert!(
+        NelderMeadOptimizer::new(&no_constraints, rosenbrock, &mut cache)
+            .with_initial_step(0.0)
+            .is_err()
+    );
+    assert!(NelderMeadCache::new(NonZeroUsize::new(2).unwrap(), 0.0).is_err());
+}
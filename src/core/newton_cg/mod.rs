@@ -0,0 +1,78 @@
+//! Projected truncated Newton (Newton-CG) method
+//!
+//! ## About
+//!
+//! This module offers a projected truncated Newton method for problems of the
+//! form
+//!
+//! $$\mathrm{Minimize}\ f(u) \text{ subject to } u \in U,$$
+//!
+//! that is, for the same `Problem` as PANOC, where, in addition, products of
+//! the Hessian of $f$ with vectors, $\nabla^2 f(u) v$, are available. At every
+//! iteration, the variables are split into the active ones, $A$, where the
+//! projected gradient step $\Pi_U(u - \nabla f(u))$ differs from
+//! $u - \nabla f(u)$, and the free ones, $F$. The direction is the projected
+//! gradient step on the active variables, $d_A = \Pi_U(u - \nabla f(u))_A - u_A$,
+//! and an approximate solution of the Newton system
+//!
+//! $$\nabla^2_{FF} f(u)\, d_F = -\nabla_F f(u) - \nabla^2_{FA} f(u)\, d_A$$
+//!
+//! on the free variables, which is computed with (Steihaug) conjugate
+//! gradient iterations; these stop at a relative residual of
+//! $\min\\{1/2, \Vert b\Vert^{1/2}\\}$ (so that the convergence is
+//! superlinear) or when a direction of negative curvature is met. The next
+//! iterate, $\Pi_U(u + \alpha d)$, is selected with a backtracking line search
+//! along the projection arc. The iterations terminate when the norm of the
+//! projected gradient, $\Vert \Pi_U(u - \nabla f(u)) - u\Vert_\infty$, drops
+//! below the tolerance.
+//!
+//! The active set is exact for boxes (see `Rectangle`), for which this is the
+//! projected Newton method of Bertsekas; for other sets of constraints, the
+//! free variables are those which are not changed by the projection. For
+//! smooth, well-conditioned problems, Newton-CG typically needs an order of
+//! magnitude fewer iterations than PANOC, at the cost of a few Hessian-vector
+//! products per iteration.
+//!
+//! ## Example
+//!
+//! ```
+//! use optimization_engine::{constraints::Rectangle, newton_cg::*, *};
+//! use std::num::NonZeroUsize;
+//!
+//! let df = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     grad[0] = 2.0 * u[0] + u[1] - 2.0;
+//!     grad[1] = u[0] + 4.0 * u[1];
+//!     Ok(())
+//! };
+//! let f = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = u[0].powi(2) + u[0] * u[1] + 2.0 * u[1].powi(2) - 2.0 * u[0];
+//!     Ok(())
+//! };
+//! let hessian_product = |_u: &[f64], v: &[f64], hv: &mut [f64]| -> FunctionCallResult {
+//!     hv[0] = 2.0 * v[0] + v[1];
+//!     hv[1] = v[0] + 4.0 * v[1];
+//!     Ok(())
+//! };
+//! let bounds = Rectangle::new(Some(&[-1.0, 0.0]), Some(&[0.5, 1.0]))?;
+//! let problem = Problem::new(&bounds, df, f);
+//! let mut cache = NewtonCGCache::new(NonZeroUsize::new(2).unwrap(), 1e-10)?;
+//! let mut u = [0.0; 2];
+//! let status = NewtonCGOptimizer::new(problem, hessian_product, &mut cache).solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 0.5).abs() < 1e-10 && u[1].abs() < 1e-10);
+//! # Ok::<(), SolverError>(())
+//! ```
+
+mod newton_cg_cache;
+mod newton_cg_engine;
+mod newton_cg_optimizer;
+
+pub use newton_cg_cache::NewtonCGCache;
+pub use newton_cg_optimizer::NewtonCGOptimizer;
+
+/* --------------------------------------------------------------------------------------------- */
+/*          TESTS                                                                                */
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+//! Newton-CG Cache
+//!
+use crate::{core::OracleCalls, ensure, OpEnFloat, SolverError};
+use std::num::NonZeroUsize;
+
+/// Cache for the projected truncated Newton (Newton-CG) method
+///
+/// This struct allocates memory needed for the algorithm
+#[derive(Debug)]
+pub struct NewtonCGCache<T = f64> {
+    pub(crate) gradient_u: Vec<T>,
+    /// projection of `u - gradient_u`
+    pub(crate) u_half_step: Vec<T>,
+    pub(crate) u_previous: Vec<T>,
+    pub(crate) direction: Vec<T>,
+    /// whether each variable is in the active set
+    pub(crate) active: Vec<bool>,
+    pub(crate) cg_residual: Vec<T>,
+    pub(crate) cg_direction: Vec<T>,
+    pub(crate) hessian_product: Vec<T>,
+    pub(crate) cost_value: T,
+    pub(crate) tolerance: T,
+    pub(crate) norm_fpr: T,
+    /// number of products of the Hessian with vectors in the last solve
+    pub(crate) hessian_products: usize,
+    pub(crate) oracle_calls: OracleCalls,
+}
+
+impl<T: OpEnFloat> NewtonCGCache<T> {
+    /// Construct a new instance of `NewtonCGCache`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n` number of decision variables
+    /// - `tolerance` tolerance used for termination
+    ///
+    /// ## Memory allocation
+    ///
+    /// This method allocates memory for `7*n + 3` floats and `n` booleans; if
+    /// an optimizer needs to be called more than once, construct a
+    /// `NewtonCGCache` only once.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not positive
+    ///
+    pub fn new(n: NonZeroUsize, tolerance: T) -> Result<NewtonCGCache<T>, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        Ok(NewtonCGCache {
+            gradient_u: vec![T::zero(); n.get()],
+            u_half_step: vec![T::zero(); n.get()],
+            u_previous: vec![T::zero(); n.get()],
+            direction: vec![T::zero(); n.get()],
+            active: vec![false; n.get()],
+            cg_residual: vec![T::zero(); n.get()],
+            cg_direction: vec![T::zero(); n.get()],
+            hessian_product: vec![T::zero(); n.get()],
+            cost_value: T::zero(),
+            tolerance,
+            norm_fpr: T::infinity(),
+            hessian_products: 0,
+            oracle_calls: OracleCalls::default(),
+        })
+    }
+
+    /// Number of products of the Hessian with vectors in the last solve
+    pub fn hessian_products(&self) -> usize {
+        self.hessian_products
+    }
+}
@@ -0,0 +1,266 @@
+//! Newton-CG Engine
+//!
+use crate::{
+    constraints,
+    core::{newton_cg::NewtonCGCache, AlgorithmEngine, Problem},
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+
+/// Coefficient of the sufficient decrease condition
+const SUFFICIENT_DECREASE: f64 = 1e-4;
+/// Maximum number of halvings of the step along the projection arc per iteration
+const MAX_BACKTRACKING: usize = 60;
+/// Upper bound on the relative residual of the conjugate gradient iterations
+const MAX_FORCING: f64 = 0.5;
+
+/// The Newton-CG Engine defines the steps of the algorithm and the termination criterion
+///
+pub struct NewtonCGEngine<'a, GradientType, ConstraintType, CostType, HessianType, T = f64>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    HessianType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    pub(crate) problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+    /// product of the Hessian of the cost at `u` with `v`, `hessian(u, v, hv)`
+    hessian: HessianType,
+    pub(crate) cache: &'a mut NewtonCGCache<T>,
+}
+
+impl<'a, GradientType, ConstraintType, CostType, HessianType, T>
+    NewtonCGEngine<'a, GradientType, ConstraintType, CostType, HessianType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    HessianType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructor for instances of `NewtonCGEngine`
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem` problem definition (cost function, gradient of the cost, constraints)
+    /// - `hessian` product of the Hessian of the cost with vectors
+    /// - mutable reference to a `cache` a cache (which is created once); the cache is reuseable
+    ///
+    pub fn new(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        hessian: HessianType,
+        cache: &'a mut NewtonCGCache<T>,
+    ) -> NewtonCGEngine<'a, GradientType, ConstraintType, CostType, HessianType, T> {
+        NewtonCGEngine {
+            problem,
+            hessian,
+            cache,
+        }
+    }
+
+    fn cost(&mut self, u: &[T]) -> Result<T, SolverError> {
+        let mut cost_value = T::zero();
+        self.cache.oracle_calls.cost += 1;
+        (self.problem.cost)(u, &mut cost_value)?;
+        Ok(cost_value)
+    }
+
+    fn gradient(&mut self, u: &[T]) -> FunctionCallResult {
+        self.cache.oracle_calls.gradient += 1;
+        (self.problem.gradf)(u, &mut self.cache.gradient_u)
+    }
+
+    /// Computes `u_half_step = Proj(u - gradient_u)`, the active set and the
+    /// norm of the projected gradient, $\Vert \Pi_U(u - \nabla f(u)) - u \Vert_\infty$
+    fn update_projected_gradient(&mut self, u: &[T]) {
+        let cache = &mut *self.cache;
+        cache
+            .u_half_step
+            .iter_mut()
+            .zip(u.iter().zip(cache.gradient_u.iter()))
+            .for_each(|(u_half, (&ui, &df))| *u_half = ui - df);
+        self.problem.constraints.project(&mut cache.u_half_step);
+        cache.oracle_calls.projections += 1;
+        let mut norm_fpr = T::zero();
+        for (i, &ui) in u.iter().enumerate() {
+            cache.active[i] = cache.u_half_step[i] != ui - cache.gradient_u[i];
+            norm_fpr = norm_fpr.max((ui - cache.u_half_step[i]).abs());
+        }
+        cache.norm_fpr = norm_fpr;
+    }
+
+    /// Computes `hessian_product` = Hessian at `u` times `v`
+    fn hessian_product(&mut self, u: &[T], v_is_direction: bool) -> FunctionCallResult {
+        let cache = &mut *self.cache;
+        cache.hessian_products += 1;
+        let v = if v_is_direction {
+            &cache.direction
+        } else {
+            &cache.cg_direction
+        };
+        (self.hessian)(u, v, &mut cache.hessian_product)
+    }
+
+    /// Computes the Newton direction: `direction` is the projected gradient
+    /// step on the active set and the result of (Steihaug) conjugate
+    /// gradient iterations on the free variables
+    fn newton_direction(&mut self, u: &[T]) -> FunctionCallResult {
+        let n = u.len();
+        let cache = &mut *self.cache;
+        let mut any_active = false;
+        for (i, (d, &ui)) in cache.direction.iter_mut().zip(u.iter()).enumerate() {
+            *d = if cache.active[i] {
+                any_active = true;
+                cache.u_half_step[i] - ui
+            } else {
+                T::zero()
+            };
+        }
+        if any_active {
+            self.hessian_product(u, true)?;
+        }
+        // cg_residual = b = -g_F - H_FA d_A
+        let cache = &mut *self.cache;
+        let mut rho = T::zero();
+        for i in 0..n {
+            cache.cg_residual[i] = if cache.active[i] {
+                T::zero()
+            } else if any_active {
+                -cache.gradient_u[i] - cache.hessian_product[i]
+            } else {
+                -cache.gradient_u[i]
+            };
+            cache.cg_direction[i] = cache.cg_residual[i];
+            rho += cache.cg_residual[i] * cache.cg_residual[i];
+        }
+        let norm_b = rho.sqrt();
+        let target = norm_b * T::from_f64(MAX_FORCING).min(norm_b.sqrt());
+        for cg_iteration in 0..n {
+            if rho.sqrt() <= target {
+                break;
+            }
+            self.hessian_product(u, false)?;
+            let cache = &mut *self.cache;
+            cache
+                .active
+                .iter()
+                .zip(cache.hessian_product.iter_mut())
+                .filter(|(&active, _)| active)
+                .for_each(|(_, hp)| *hp = T::zero());
+            let curvature =
+                matrix_operations::inner_product(&cache.cg_direction, &cache.hessian_product);
+            if curvature <= T::zero() || !curvature.is_finite() {
+                // negative curvature: at the first iteration, fall back to
+                // the steepest descent direction, b, on the free variables
+                if cg_iteration == 0 {
+                    for i in 0..n {
+                        if !cache.active[i] {
+                            cache.direction[i] = cache.cg_residual[i];
+                        }
+                    }
+                }
+                break;
+            }
+            let alpha = rho / curvature;
+            let mut rho_next = T::zero();
+            for i in 0..n {
+                cache.direction[i] += alpha * cache.cg_direction[i];
+                cache.cg_residual[i] -= alpha * cache.hessian_product[i];
+                rho_next += cache.cg_residual[i] * cache.cg_residual[i];
+            }
+            let beta = rho_next / rho;
+            rho = rho_next;
+            cache
+                .cg_direction
+                .iter_mut()
+                .zip(cache.cg_residual.iter())
+                .for_each(|(p, &r)| *p = r + beta * *p);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, GradientType, ConstraintType, CostType, HessianType, T> AlgorithmEngine<T>
+    for NewtonCGEngine<'a, GradientType, ConstraintType, CostType, HessianType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'a,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'a,
+    HessianType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T> + 'a,
+{
+    /// Take a step along the projected Newton direction and check whether
+    /// the algorithm should terminate
+    ///
+    /// ## Returns
+    ///
+    /// - A boolean flag which is`true` if and only if the algorithm should not
+    ///   terminate
+    ///
+    fn step(&mut self, u_current: &mut [T]) -> Result<bool, SolverError> {
+        self.newton_direction(u_current)?;
+        // fall back to the projected gradient direction if the Newton
+        // direction is not a descent direction
+        if matrix_operations::inner_product(&self.cache.gradient_u, &self.cache.direction)
+            >= T::zero()
+        {
+            let cache = &mut *self.cache;
+            cache
+                .direction
+                .iter_mut()
+                .zip(cache.u_half_step.iter().zip(u_current.iter()))
+                .for_each(|(d, (&u_half, &ui))| *d = u_half - ui);
+        }
+        self.cache.u_previous.copy_from_slice(u_current);
+        let cost_previous = self.cache.cost_value;
+        let sigma = T::from_f64(SUFFICIENT_DECREASE);
+
+        // backtracking along the projection arc, u(alpha) = Proj(u + alpha*d)
+        let mut alpha = T::one();
+        let mut backtracking = 0;
+        loop {
+            let cache = &mut *self.cache;
+            u_current
+                .iter_mut()
+                .zip(cache.u_previous.iter().zip(cache.direction.iter()))
+                .for_each(|(ui, (&u_prev, &d))| *ui = u_prev + alpha * d);
+            self.problem.constraints.project(u_current);
+            cache.oracle_calls.projections += 1;
+            let decrease = cache
+                .gradient_u
+                .iter()
+                .zip(u_current.iter().zip(cache.u_previous.iter()))
+                .fold(T::zero(), |sum, (&df, (&ui, &u_prev))| {
+                    sum + df * (ui - u_prev)
+                });
+            let cost_value = self.cost(u_current)?;
+            if cost_value <= cost_previous + sigma * decrease || backtracking == MAX_BACKTRACKING {
+                self.cache.cost_value = cost_value;
+                break;
+            }
+            backtracking += 1;
+            alpha /= T::from_f64(2.0);
+        }
+        if !self.cache.cost_value.is_finite() {
+            return Err(SolverError::NotFiniteComputation);
+        }
+
+        self.gradient(u_current)?;
+        self.update_projected_gradient(u_current);
+
+        Ok(self.cache.norm_fpr > self.cache.tolerance)
+    }
+
+    /// Computes the cost, its gradient and the norm of the projected
+    /// gradient at the initial point
+    fn init(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        self.cache.oracle_calls.reset();
+        self.cache.hessian_products = 0;
+        self.problem.constraints.project(u_current);
+        self.cache.oracle_calls.projections += 1;
+        self.cache.cost_value = self.cost(u_current)?;
+        self.gradient(u_current)?;
+        self.update_projected_gradient(u_current);
+        Ok(())
+    }
+}
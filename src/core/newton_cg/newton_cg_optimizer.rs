@@ -0,0 +1,146 @@
+//! Newton-CG Algorithm
+//!
+use crate::{
+    constraints,
+    core::{
+        newton_cg::{newton_cg_engine::NewtonCGEngine, NewtonCGCache},
+        AlgorithmEngine, ExitStatus, Optimizer, Problem, SolverStatus,
+    },
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+const MAX_ITER: usize = 1000_usize;
+
+/// Optimiser using projected truncated Newton (Newton-CG) iterations
+///
+/// Each iteration evaluates the gradient once, the cost at least once, and
+/// the product of the Hessian with a vector at most $n + 1$ times (see
+/// `NewtonCGCache::hessian_products`).
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct NewtonCGOptimizer<'a, GradientType, ConstraintType, CostType, HessianType, T = f64>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    HessianType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    newton_cg_engine: NewtonCGEngine<'a, GradientType, ConstraintType, CostType, HessianType, T>,
+    max_iter: usize,
+    max_duration: Option<time::Duration>,
+}
+
+impl<'a, GradientType, ConstraintType, CostType, HessianType, T>
+    NewtonCGOptimizer<'a, GradientType, ConstraintType, CostType, HessianType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    HessianType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructs a new instance of `NewtonCGOptimizer`
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem`: problem definition
+    /// - `hessian_product`: product of the Hessian of the cost with a vector,
+    ///   `hessian_product(u, v, hv)` computes $hv = \nabla^2 f(u) v$
+    /// - `cache`: instance of `NewtonCGCache`
+    pub fn new(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        hessian_product: HessianType,
+        cache: &'a mut NewtonCGCache<T>,
+    ) -> Self {
+        NewtonCGOptimizer {
+            newton_cg_engine: NewtonCGEngine::new(problem, hessian_product, cache),
+            max_iter: MAX_ITER,
+            max_duration: None,
+        }
+    }
+
+    /// Sets the tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        self.newton_cg_engine.cache.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+}
+
+impl<'life, GradientType, ConstraintType, CostType, HessianType, T> Optimizer<T>
+    for NewtonCGOptimizer<'life, GradientType, ConstraintType, CostType, HessianType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'life,
+    HessianType: FnMut(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T> + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = instant::Instant::now();
+
+        let problem_size = self.newton_cg_engine.cache.gradient_u.len();
+        if u.len() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: problem_size,
+                actual: u.len(),
+            });
+        }
+
+        self.newton_cg_engine.init(u)?;
+
+        let mut num_iter: usize = 0;
+        let mut exit_status = ExitStatus::Converged;
+        while self.newton_cg_engine.cache.norm_fpr > self.newton_cg_engine.cache.tolerance {
+            if num_iter >= self.max_iter {
+                exit_status = ExitStatus::NotConvergedIterations;
+                break;
+            }
+            if let Some(max_duration) = self.max_duration {
+                if now.elapsed() > max_duration {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+            num_iter += 1;
+            if !self.newton_cg_engine.step(u)? {
+                break;
+            }
+        }
+
+        let cache = &*self.newton_cg_engine.cache;
+        if !matrix_operations::is_finite(u) || !cache.norm_fpr.is_finite() {
+            return Err(SolverError::NotFiniteComputation);
+        }
+
+        Ok(SolverStatus::new(
+            exit_status,
+            num_iter,
+            now.elapsed(),
+            cache.norm_fpr.as_f64(),
+            cache.cost_value.as_f64(),
+        )
+        .with_gradient_norm(matrix_operations::norm2(&cache.gradient_u).as_f64())
+        .with_oracle_calls(cache.oracle_calls))
+    }
+}
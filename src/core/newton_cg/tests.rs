@@ -0,0 +1,130 @@
+use super::*;
+use crate::{
+    constraints, core::panoc::PANOCOptimizer, mocks, FunctionCallResult, Optimizer, Problem,
+    SolverError,
+};
+use std::num::NonZeroUsize;
+
+fn rosenbrock_hessian_product(b: f64, u: &[f64], v: &[f64], hv: &mut [f64]) {
+    let h00 = 2.0 - 4.0 * b * u[1] + 12.0 * b * u[0].powi(2);
+    let h01 = -4.0 * b * u[0];
+    hv[0] = h00 * v[0] + h01 * v[1];
+    hv[1] = h01 * v[0] + 2.0 * b * v[1];
+}
+
+#[test]
+fn t_newton_cg_rosenbrock() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    let hessian = |u: &[f64], v: &[f64], hv: &mut [f64]| -> FunctionCallResult {
+        rosenbrock_hessian_product(b, u, v, hv);
+        Ok(())
+    };
+    let bounds = constraints::Rectangle::new(Some(&[-2.0, -2.0]), Some(&[0.5, 2.0])).unwrap();
+    let mut cache = NewtonCGCache::new(NonZeroUsize::new(2).unwrap(), 1e-10).unwrap();
+    let mut u = [-1.5, 1.5];
+    let status = NewtonCGOptimizer::new(Problem::new(&bounds, grad, cost), hessian, &mut cache)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.norm_fpr() <= 1e-10);
+    assert_eq!(status.oracle_calls().gradient(), status.iterations() + 1);
+    assert!(cache.hessian_products() <= 3 * status.iterations());
+
+    // same solution as PANOC, with fewer iterations
+    let mut u_panoc = [-1.5, 1.5];
+    let status_panoc =
+        PANOCOptimizer::from_dimensions(Problem::new(&bounds, grad, cost), 2, 1e-12, 5)
+            .unwrap()
+            .with_max_iter(10_000)
+            .unwrap()
+            .solve(&mut u_panoc)
+            .unwrap();
+    unit_test_utils::assert_nearly_equal_array(&u_panoc, &u, 1e-6, 1e-7, "u");
+    assert_eq!(0.5, u[0]);
+    assert!(status.iterations() < status_panoc.iterations());
+}
+
+#[test]
+fn t_newton_cg_chained_rosenbrock() {
+    // chained Rosenbrock function, sum of (1 - u[i])^2 + 10 (u[i+1] - u[i]^2)^2,
+    // with the bounds u[i] <= 0.8 active at the solution
+    let n = 20;
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = u
+            .windows(2)
+            .map(|w| mocks::rosenbrock_cost(1.0, 10.0, w))
+            .sum();
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        g.iter_mut().for_each(|g| *g = 0.0);
+        let mut gw = [0.0; 2];
+        for i in 0..u.len() - 1 {
+            mocks::rosenbrock_grad(1.0, 10.0, &u[i..i + 2], &mut gw);
+            g[i] += gw[0];
+            g[i + 1] += gw[1];
+        }
+        Ok(())
+    };
+    let hessian = |u: &[f64], v: &[f64], hv: &mut [f64]| -> FunctionCallResult {
+        hv.iter_mut().for_each(|h| *h = 0.0);
+        let mut hw = [0.0; 2];
+        for i in 0..u.len() - 1 {
+            rosenbrock_hessian_product(10.0, &u[i..i + 2], &v[i..i + 2], &mut hw);
+            hv[i] += hw[0];
+            hv[i + 1] += hw[1];
+        }
+        Ok(())
+    };
+    let lower = vec![-2.0; n];
+    let upper = vec![0.8; n];
+    let bounds = constraints::Rectangle::new(Some(&lower), Some(&upper)).unwrap();
+    let mut cache = NewtonCGCache::new(NonZeroUsize::new(n).unwrap(), 1e-8).unwrap();
+    let mut u = vec![-1.0; n];
+    let status = NewtonCGOptimizer::new(Problem::new(&bounds, grad, cost), hessian, &mut cache)
+        .solve(&mut u)
+        .unwrap();
+    let mut u_panoc = vec![-1.0; n];
+    let status_panoc =
+        PANOCOptimizer::from_dimensions(Problem::new(&bounds, grad, cost), n, 1e-8, 10)
+            .unwrap()
+            .with_max_iter(10_000)
+            .unwrap()
+            .solve(&mut u_panoc)
+            .unwrap();
+    assert!(status.has_converged() && status_panoc.has_converged());
+    unit_test_utils::assert_nearly_equal_array(&u_panoc, &u, 1e-6, 1e-7, "u");
+    assert_eq!(0.8, u[0]);
+    // far fewer iterations than PANOC
+    assert!(5 * status.iterations() <= status_panoc.iterations());
+}
+
+#[test]
+fn t_newton_cg_errors() {
+    let bounds = constraints::NoConstraints::new();
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let hessian =
+        |_u: &[f64], _v: &[f64], _hv: &mut [f64]| -> FunctionCallResult { Err(SolverError::Cost) };
+    let mut cache = NewtonCGCache::new(NonZeroUsize::new(2).unwrap(), 1e-6).unwrap();
+    let mut optimizer = NewtonCGOptimizer::new(problem, hessian, &mut cache).with_max_iter(10);
+    let mut u = [1.0, 1.0];
+    assert_eq!(Err(SolverError::Cost), optimizer.solve(&mut u));
+    assert!(matches!(
+        optimizer.solve(&mut [0.0; 3]),
+        Err(SolverError::DimensionMismatch {
+            name: "u",
+            expected: 2,
+            actual: 3,
+        })
+    ));
+    assert!(optimizer.with_tolerance(0.0).is_err());
+    assert!(NewtonCGCache::<f64>::new(NonZeroUsize::new(2).unwrap(), -1.0).is_err());
+}
@@ -0,0 +1,75 @@
+//! Counters of evaluations of user-defined functions (oracles)
+//!
+//!
+use std::ops::AddAssign;
+
+/// Number of evaluations of the user-defined functions and of projections
+///
+/// The counters are maintained by the caches of the optimizers (which reset
+/// them when a new problem is solved) and are reported in `SolverStatus` and
+/// `AlmOptimizerStatus`. When the oracles are expensive (e.g., they involve
+/// a simulation), these counts, rather than the number of iterations, are a
+/// fair basis for comparing solvers and their settings.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OracleCalls {
+    /// evaluations of the cost function
+    pub(crate) cost: usize,
+    /// evaluations of the gradient of the cost function
+    pub(crate) gradient: usize,
+    /// evaluations of the mapping $F_1$ (ALM only)
+    pub(crate) mapping_f1: usize,
+    /// evaluations of the mapping $F_2$ (ALM only)
+    pub(crate) mapping_f2: usize,
+    /// projections on sets
+    pub(crate) projections: usize,
+}
+
+impl OracleCalls {
+    /// Constructs a new instance with all counters equal to zero
+    pub fn new() -> Self {
+        OracleCalls::default()
+    }
+
+    /// number of evaluations of the cost function
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+
+    /// number of evaluations of the gradient of the cost function
+    pub fn gradient(&self) -> usize {
+        self.gradient
+    }
+
+    /// number of evaluations of the mapping $F_1$ (ALM/PM only)
+    pub fn mapping_f1(&self) -> usize {
+        self.mapping_f1
+    }
+
+    /// number of evaluations of the mapping $F_2$ (ALM/PM only)
+    pub fn mapping_f2(&self) -> usize {
+        self.mapping_f2
+    }
+
+    /// number of projections (on the set of constraints and, in ALM/PM, on the
+    /// sets $C$ and $Y$)
+    pub fn projections(&self) -> usize {
+        self.projections
+    }
+
+    /// Sets all counters to zero
+    pub(crate) fn reset(&mut self) {
+        *self = OracleCalls::default();
+    }
+}
+
+impl AddAssign for OracleCalls {
+    fn add_assign(&mut self, other: OracleCalls) {
+        self.cost += other.cost;
+        self.gradient += other.gradient;
+        self.mapping_f1 += other.mapping_f1;
+        self.mapping_f2 += other.mapping_f2;
+        self.projections += other.projections;
+    }
+}
@@ -0,0 +1,123 @@
+//! Gauss-Newton directions for PANOC
+//!
+use crate::{least_squares::GaussNewtonHessian, FunctionCallResult, OpEnFloat};
+
+/// Gauss-Newton directions, which replace the L-BFGS directions of PANOC
+/// (see `PANOCOptimizer::with_gauss_newton`)
+///
+/// The direction is an approximate solution of $J_R(u) d = R(u)$, where
+/// $R(u) = u - \Pi_U(u - \gamma\nabla f(u))$ is the fixed-point residual
+/// (`gamma_fpr`) and $J_R(u) = I - P(I - \gamma H)$, with $H = J^\top J$ the
+/// Gauss-Newton approximation of the Hessian and $P$ the diagonal matrix with
+/// zeros on the active set. That is, $d_i = R_i$ on the active set, $A$, and
+/// $H_{FF} d_F = R_F/\gamma - H_{FA} d_A$ on the free variables, $F$, which is
+/// solved with conjugate gradient iterations.
+pub(crate) struct GaussNewtonDirection<'a, T> {
+    hessian: &'a dyn GaussNewtonHessian<T>,
+    active: Vec<bool>,
+    cg_residual: Vec<T>,
+    cg_direction: Vec<T>,
+    hessian_product: Vec<T>,
+}
+
+impl<'a, T: OpEnFloat> GaussNewtonDirection<'a, T> {
+    pub(crate) fn new(hessian: &'a dyn GaussNewtonHessian<T>) -> Self {
+        let n = hessian.dimension();
+        GaussNewtonDirection {
+            hessian,
+            active: vec![false; n],
+            cg_residual: vec![T::zero(); n],
+            cg_direction: vec![T::zero(); n],
+            hessian_product: vec![T::zero(); n],
+        }
+    }
+
+    /// Computes the Gauss-Newton direction at `u` and writes it into
+    /// `direction`
+    ///
+    /// ## Arguments
+    ///
+    /// - `u`: current iterate
+    /// - `gamma`: step size of PANOC
+    /// - `gradient_step`: $u - \gamma\nabla f(u)$
+    /// - `u_half_step`: projection of `gradient_step` on the constraints
+    /// - `direction`: on exit, the direction
+    pub(crate) fn compute(
+        &mut self,
+        u: &[T],
+        gamma: T,
+        gradient_step: &[T],
+        u_half_step: &[T],
+        direction: &mut [T],
+    ) -> FunctionCallResult {
+        let n = u.len();
+        let mut num_free = 0;
+        // d = (R_A, 0)
+        for i in 0..n {
+            self.active[i] = u_half_step[i] != gradient_step[i];
+            direction[i] = if self.active[i] {
+                u[i] - u_half_step[i]
+            } else {
+                num_free += 1;
+                T::zero()
+            };
+        }
+        if num_free < n {
+            self.hessian
+                .hessian_product(u, direction, &mut self.hessian_product)?;
+        } else {
+            self.hessian_product.iter_mut().for_each(|h| *h = T::zero());
+        }
+        // cg_residual = b = R_F/gamma - H_FA d_A
+        let mut rho = T::zero();
+        for i in 0..n {
+            self.cg_residual[i] = if self.active[i] {
+                T::zero()
+            } else {
+                (u[i] - u_half_step[i]) / gamma - self.hessian_product[i]
+            };
+            self.cg_direction[i] = self.cg_residual[i];
+            rho += self.cg_residual[i] * self.cg_residual[i];
+        }
+        let cg_tolerance = T::from_f64(0.1).min(rho.sqrt().sqrt()) * rho.sqrt();
+        for k in 0..num_free {
+            if rho.sqrt() <= cg_tolerance {
+                break;
+            }
+            self.hessian
+                .hessian_product(u, &self.cg_direction, &mut self.hessian_product)?;
+            let mut curvature = T::zero();
+            for i in 0..n {
+                if self.active[i] {
+                    self.hessian_product[i] = T::zero();
+                }
+                curvature += self.cg_direction[i] * self.hessian_product[i];
+            }
+            if curvature <= T::zero() {
+                if k == 0 {
+                    // gradient direction on the free variables (the step of
+                    // PANOC is then a projected gradient step)
+                    for i in 0..n {
+                        if !self.active[i] {
+                            direction[i] = u[i] - u_half_step[i];
+                        }
+                    }
+                }
+                break;
+            }
+            let alpha = rho / curvature;
+            let mut rho_next = T::zero();
+            for (i, d_i) in direction.iter_mut().enumerate() {
+                *d_i += alpha * self.cg_direction[i];
+                self.cg_residual[i] -= alpha * self.hessian_product[i];
+                rho_next += self.cg_residual[i] * self.cg_residual[i];
+            }
+            let beta = rho_next / rho;
+            rho = rho_next;
+            for i in 0..n {
+                self.cg_direction[i] = self.cg_residual[i] + beta * self.cg_direction[i];
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+//! Limited-memory SR1 directions for PANOC
+//!
+use crate::{
+    core::{heap_size, lbfgs::UpdateStatus},
+    ensure, matrix_operations, OpEnFloat, SolverError,
+};
+
+/// Pairs with $|\langle p, y\rangle| < r\Vert p\Vert\Vert y\Vert$, where
+/// $p = s - Hy$, are skipped, which keeps the SR1 update bounded
+const SR1_SKIP_THRESHOLD: f64 = 1e-8;
+
+/// The trust region shrinks if the line search selects a smaller `tau`
+/// (that is, if it backtracks more than four times)
+const SHRINK_TAU: f64 = 0.0625;
+
+/// Limited-memory SR1 estimate of the inverse Jacobian of the fixed-point
+/// residual, which replaces the L-BFGS estimate of PANOC (see
+/// `PANOCOptimizer::with_sr1`)
+///
+/// The estimate is
+///
+/// $$
+/// H = \gamma_0 I + \sum_i \frac{p_i p_i^\top}{\langle p_i, y_i\rangle},
+/// \quad p_i = s_i - H_{i-1} y_i,
+/// $$
+///
+/// where the (s, y) pairs are stored from the oldest to the most recent one
+/// and $H_{i-1}$ is the estimate built from the pairs before the $i$-th. Unlike
+/// L-BFGS, $H$ need not be positive definite, so negative curvature is
+/// captured, but the directions can be arbitrarily long, so their (relative)
+/// distance from the forward-backward step is limited to a trust-region
+/// radius (see `limit_step` and `adapt_radius`).
+/// The vectors $p_i$ are recomputed (at a cost of $O(m^2 n)$) whenever a pair
+/// is added, because they all depend on the oldest pair, which is discarded
+/// once the buffer is full.
+#[derive(Debug)]
+pub(crate) struct Lsr1<T> {
+    /// Number of stored (s, y) pairs
+    active_size: usize,
+    /// Scaling of the initial estimate, $H_0 = \gamma_0 I$
+    gamma: T,
+    /// s_0 holds the most recent state difference (the last element is a
+    /// temporary area)
+    s: Vec<Vec<T>>,
+    /// y_0 holds the most recent residual difference
+    y: Vec<Vec<T>>,
+    /// Vectors $p_i = s_i - H_{i-1} y_i$
+    p: Vec<Vec<T>>,
+    /// Inner products $\langle p_i, y_i\rangle$ (zero if the pair is skipped)
+    py: Vec<T>,
+    /// Coefficients $\langle p_i, v\rangle / \langle p_i, y_i\rangle$ used
+    /// in `apply_hessian`
+    coefficients: Vec<T>,
+    old_state: Vec<T>,
+    old_g: Vec<T>,
+    first_old: bool,
+    /// Trust-region radius (bound on the distance of the directions from the
+    /// fixed-point residual, relative to its norm)
+    radius: T,
+    /// Relative distance of the last direction from the fixed-point
+    /// residual, after `limit_step`
+    step_norm: T,
+    /// Whether the last direction was on the boundary of the trust region
+    clipped: bool,
+}
+
+impl<T: OpEnFloat> Lsr1<T> {
+    /// Number of bytes held on the heap by the buffers
+    pub(crate) fn heap_size(&self) -> usize {
+        let buffers = |vectors: &Vec<Vec<T>>| -> usize {
+            heap_size(vectors) + vectors.iter().map(heap_size).sum::<usize>()
+        };
+        buffers(&self.s)
+            + buffers(&self.y)
+            + buffers(&self.p)
+            + heap_size(&self.py)
+            + heap_size(&self.coefficients)
+            + heap_size(&self.old_state)
+            + heap_size(&self.old_g)
+    }
+
+    /// Constructs a new L-SR1 buffer
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `problem_size` or
+    /// `buffer_size` is zero
+    pub(crate) fn new(problem_size: usize, buffer_size: usize) -> Result<Self, SolverError> {
+        ensure(problem_size > 0, "problem_size", "must be positive")?;
+        ensure(buffer_size > 0, "buffer_size", "must be positive")?;
+        Ok(Lsr1 {
+            active_size: 0,
+            gamma: T::one(),
+            s: vec![vec![T::zero(); problem_size]; buffer_size + 1],
+            y: vec![vec![T::zero(); problem_size]; buffer_size + 1],
+            p: vec![vec![T::zero(); problem_size]; buffer_size + 1],
+            py: vec![T::zero(); buffer_size + 1],
+            coefficients: vec![T::zero(); buffer_size],
+            old_state: vec![T::zero(); problem_size],
+            old_g: vec![T::zero(); problem_size],
+            first_old: true,
+            radius: T::infinity(),
+            step_norm: T::zero(),
+            clipped: false,
+        })
+    }
+
+    /// Empties the buffer and removes the trust-region bound
+    pub(crate) fn reset(&mut self) {
+        self.active_size = 0;
+        self.gamma = T::one();
+        self.first_old = true;
+        self.radius = T::infinity();
+    }
+
+    /// Moves `direction` towards `fpr` (the forward-backward step), so that
+    /// their distance, relative to the norm of `fpr`, does not exceed the
+    /// trust-region radius
+    pub(crate) fn limit_step(&mut self, direction: &mut [T], fpr: &[T]) {
+        let norm = matrix_operations::norm2_squared_diff(direction, fpr).sqrt()
+            / matrix_operations::norm2(fpr);
+        self.clipped = norm > self.radius;
+        if self.clipped {
+            let scaling = self.radius / norm;
+            direction
+                .iter_mut()
+                .zip(fpr.iter())
+                .for_each(|(d_i, &r_i)| *d_i = r_i + scaling * (*d_i - r_i));
+        }
+        self.step_norm = norm.min(self.radius);
+    }
+
+    /// Updates the trust-region radius given the step size `tau` selected by
+    /// the line search: the radius is halved (with respect to the relative
+    /// distance of the last direction from the residual) if the line search
+    /// backtracked more than four times, and multiplied by four if the
+    /// direction was on the boundary and `tau` is at least one half
+    pub(crate) fn adapt_radius(&mut self, tau: T) {
+        if tau < T::from_f64(SHRINK_TAU) {
+            if self.step_norm > T::zero() {
+                self.radius = self.step_norm / T::from_f64(2.0);
+            }
+        } else if self.clipped && tau >= T::from_f64(0.5) {
+            self.radius = T::from_f64(4.0) * self.radius;
+        }
+    }
+
+    /// Computes `p = s - H y`, where `H` is built from `gamma` and the
+    /// vectors `older_p`, and returns `<p, y>`, or zero if the pair has to
+    /// be skipped
+    fn compute_p(gamma: T, older_p: &[Vec<T>], older_py: &[T], s: &[T], y: &[T], p: &mut [T]) -> T {
+        p.iter_mut()
+            .zip(s.iter().zip(y.iter()))
+            .for_each(|(p_i, (&s_i, &y_i))| *p_i = s_i - gamma * y_i);
+        for (p_j, &py_j) in older_p.iter().zip(older_py.iter()) {
+            if py_j != T::zero() {
+                let c = matrix_operations::inner_product(p_j, y) / py_j;
+                p.iter_mut()
+                    .zip(p_j.iter())
+                    .for_each(|(p_i, &p_ji)| *p_i -= c * p_ji);
+            }
+        }
+        let py = matrix_operations::inner_product(p, y);
+        let threshold = T::from_f64(SR1_SKIP_THRESHOLD)
+            * matrix_operations::norm2(p)
+            * matrix_operations::norm2(y);
+        if py.abs() > threshold && py.is_finite() {
+            py
+        } else {
+            T::zero()
+        }
+    }
+
+    /// Recomputes the vectors `p` of all stored pairs, from the oldest to the
+    /// most recent one
+    fn rebuild(&mut self) {
+        for i in (0..self.active_size).rev() {
+            let (newer, older) = self.p.split_at_mut(i + 1);
+            let (newer_py, older_py) = self.py.split_at_mut(i + 1);
+            newer_py[i] = Lsr1::compute_p(
+                self.gamma,
+                &older[..self.active_size - i - 1],
+                &older_py[..self.active_size - i - 1],
+                &self.s[i],
+                &self.y[i],
+                &mut newer[i],
+            );
+        }
+    }
+
+    /// Applies the current estimate to `g` (in place)
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the length of `g` is not equal to the problem size
+    pub(crate) fn apply_hessian(&mut self, g: &mut [T]) {
+        assert!(g.len() == self.old_g.len());
+        let active = self.active_size;
+        for ((c, p_i), &py_i) in self.coefficients[..active]
+            .iter_mut()
+            .zip(self.p.iter())
+            .zip(self.py.iter())
+        {
+            *c = if py_i != T::zero() {
+                matrix_operations::inner_product(p_i, g) / py_i
+            } else {
+                T::zero()
+            };
+        }
+        let gamma = self.gamma;
+        g.iter_mut().for_each(|g_i| *g_i *= gamma);
+        for (&c, p_i) in self.coefficients[..active].iter().zip(self.p.iter()) {
+            g.iter_mut()
+                .zip(p_i.iter())
+                .for_each(|(g_i, &p_ij)| *g_i += c * p_ij);
+        }
+    }
+
+    /// Saves the differences of `state` and `g` from their previous values
+    ///
+    /// The pair is rejected if `s` is zero or if the SR1 update is not
+    /// well defined, that is, if $\langle s - Hy, y\rangle$ is too small
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the lengths of `g` and `state` are not equal
+    /// to the problem size
+    pub(crate) fn update_hessian(&mut self, g: &[T], state: &[T]) -> UpdateStatus {
+        assert!(g.len() == self.old_state.len());
+        assert!(state.len() == self.old_state.len());
+
+        if self.first_old {
+            self.first_old = false;
+            self.old_state.copy_from_slice(state);
+            self.old_g.copy_from_slice(g);
+            return UpdateStatus::UpdateOk;
+        }
+
+        let last = self.s.len() - 1;
+        self.s[last]
+            .iter_mut()
+            .zip(state.iter().zip(self.old_state.iter()))
+            .for_each(|(s_i, (&x, &x_old))| *s_i = x - x_old);
+        self.y[last]
+            .iter_mut()
+            .zip(g.iter().zip(self.old_g.iter()))
+            .for_each(|(y_i, (&g_i, &g_old))| *y_i = g_i - g_old);
+
+        let (current_p, temporary_p) = self.p.split_at_mut(last);
+        let py = Lsr1::compute_p(
+            self.gamma,
+            &current_p[..self.active_size],
+            &self.py[..self.active_size],
+            &self.s[last],
+            &self.y[last],
+            &mut temporary_p[0],
+        );
+        if matrix_operations::norm2_squared(&self.s[last]) <= T::min_positive_value()
+            || py == T::zero()
+        {
+            return UpdateStatus::Rejection;
+        }
+
+        self.old_state.copy_from_slice(state);
+        self.old_g.copy_from_slice(g);
+
+        self.s.rotate_right(1);
+        self.y.rotate_right(1);
+        self.p.rotate_right(1);
+        self.py.rotate_right(1);
+        self.active_size = last.min(self.active_size + 1);
+
+        // Barzilai-Borwein scaling of the initial estimate (if the most
+        // recent pair has positive curvature)
+        let sy = matrix_operations::inner_product(&self.s[0], &self.y[0]);
+        if sy > T::zero() {
+            self.gamma = sy / matrix_operations::norm2_squared(&self.y[0]);
+        }
+        self.rebuild();
+
+        UpdateStatus::UpdateOk
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_lsr1_indefinite_secant() {
+        // residual of an indefinite quadratic, g(x) = A x, A = diag(2, -1)
+        let g_of = |x: &[f64]| [2.0 * x[0], -x[1]];
+        let mut sr1 = Lsr1::new(2, 3).unwrap();
+        let points = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+        for x in points.iter() {
+            assert_eq!(UpdateStatus::UpdateOk, sr1.update_hessian(&g_of(x), x));
+        }
+        // SR1 recovers the inverse of A after two linearly independent steps
+        let mut v = [2.0, -1.0];
+        sr1.apply_hessian(&mut v);
+        unit_test_utils::assert_nearly_equal_array(&[1.0, 1.0], &v, 1e-12, 1e-12, "H*v");
+        // a repeated point is rejected
+        assert_eq!(
+            UpdateStatus::Rejection,
+            sr1.update_hessian(&g_of(&points[2]), &points[2])
+        );
+        assert!(Lsr1::<f64>::new(2, 0).is_err());
+    }
+}
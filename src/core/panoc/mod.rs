@@ -2,12 +2,28 @@
 
 #![deny(missing_docs)]
 
+mod gauss_newton;
+mod lsr1;
 mod panoc_cache;
+mod panoc_checkpoint;
+mod panoc_config;
+mod panoc_const;
 mod panoc_engine;
+mod panoc_mixed;
 mod panoc_optimizer;
+#[cfg(feature = "session")]
+mod panoc_session;
+mod semismooth_newton;
 
 pub use panoc_cache::PANOCCache;
-pub use panoc_optimizer::PANOCOptimizer;
+pub use panoc_checkpoint::PanocCheckpoint;
+pub use panoc_config::PanocConfig;
+pub use panoc_const::{PANOCCacheConst, PANOCOptimizerConst};
+pub use panoc_mixed::PANOCOptimizerMixed;
+pub use panoc_optimizer::{DynPANOCOptimizer, PANOCOptimizer};
+#[cfg(feature = "session")]
+pub use panoc_session::PanocSession;
+pub use semismooth_newton::SemismoothNewtonCache;
 
 #[cfg(test)]
 mod tests;
@@ -1,6 +1,52 @@
-const DEFAULT_SY_EPSILON: f64 = 1e-10;
-const DEFAULT_CBFGS_EPSILON: f64 = 1e-8;
-const DEFAULT_CBFGS_ALPHA: f64 = 1.0;
+use crate::{
+    core::{
+        heap_size,
+        lbfgs::{InitialScaling, Lbfgs},
+        panoc::{lsr1::Lsr1, PanocCheckpoint},
+        OracleCalls, PhaseTimes, SolverStatistics, StepTolerance,
+    },
+    ensure, OpEnFloat, SolverError,
+};
+use std::collections::VecDeque;
+
+pub(crate) const DEFAULT_SY_EPSILON: f64 = 1e-10;
+pub(crate) const DEFAULT_CBFGS_EPSILON: f64 = 1e-8;
+pub(crate) const DEFAULT_CBFGS_ALPHA: f64 = 1.0;
+
+/// State of the trust-region globalization of PANOC (see
+/// `PANOCOptimizer::with_trust_region`)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TrustRegion<T> {
+    pub(crate) initial_radius: T,
+    pub(crate) radius: T,
+}
+
+/// Schedule of the candidates of the line search of PANOC (see
+/// `PANOCOptimizer::with_initial_tau`)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LineSearchSchedule<T> {
+    /// tau of the first candidate
+    pub(crate) initial_tau: T,
+    /// factor by which tau is multiplied after a rejected candidate
+    pub(crate) shrink_factor: T,
+    /// maximum number of rejected candidates before the forward-backward
+    /// step is taken
+    pub(crate) max_backtracks: usize,
+    /// smallest admissible tau (below which the forward-backward step is
+    /// taken)
+    pub(crate) min_tau: T,
+}
+
+impl<T: OpEnFloat> Default for LineSearchSchedule<T> {
+    fn default() -> Self {
+        LineSearchSchedule {
+            initial_tau: T::one(),
+            shrink_factor: T::from_f64(0.5),
+            max_backtracks: crate::core::panoc::panoc_engine::MAX_LINESEARCH_ITERATIONS as usize,
+            min_tau: T::zero(),
+        }
+    }
+}
 
 /// Cache for PANOC
 ///
@@ -11,33 +57,79 @@ const DEFAULT_CBFGS_ALPHA: f64 = 1.0;
 ///
 /// Subsequently, a `PANOCEngine` is used to construct an instance of `PANOCAlgorithm`
 ///
+/// `PANOCCache` is `Send` and `Sync`, so caches can be allocated on one thread
+/// and used for solves on other threads.
+///
 #[derive(Debug)]
-pub struct PANOCCache {
-    pub(crate) lbfgs: lbfgs::Lbfgs,
-    pub(crate) gradient_u: Vec<f64>,
+pub struct PANOCCache<T = f64> {
+    pub(crate) lbfgs: Lbfgs<T>,
+    pub(crate) gradient_u: Vec<T>,
     /// Stores the gradient of the cost at the previous iteration. This is
     /// an optional field because it is used (and needs to be allocated)
     /// only if we need to check the AKKT-specific termination conditions
-    pub(crate) gradient_u_previous: Option<Vec<f64>>,
-    pub(crate) u_half_step: Vec<f64>,
-    pub(crate) gradient_step: Vec<f64>,
-    pub(crate) direction_lbfgs: Vec<f64>,
-    pub(crate) u_plus: Vec<f64>,
-    pub(crate) rhs_ls: f64,
-    pub(crate) lhs_ls: f64,
-    pub(crate) gamma_fpr: Vec<f64>,
-    pub(crate) gamma: f64,
-    pub(crate) tolerance: f64,
-    pub(crate) norm_gamma_fpr: f64,
-    pub(crate) tau: f64,
-    pub(crate) lipschitz_constant: f64,
-    pub(crate) sigma: f64,
-    pub(crate) cost_value: f64,
+    pub(crate) gradient_u_previous: Option<Vec<T>>,
+    pub(crate) u_half_step: Vec<T>,
+    pub(crate) gradient_step: Vec<T>,
+    pub(crate) direction_lbfgs: Vec<T>,
+    pub(crate) u_plus: Vec<T>,
+    pub(crate) rhs_ls: T,
+    pub(crate) lhs_ls: T,
+    pub(crate) gamma_fpr: Vec<T>,
+    pub(crate) gamma: T,
+    pub(crate) tolerance: T,
+    pub(crate) norm_gamma_fpr: T,
+    pub(crate) tau: T,
+    pub(crate) lipschitz_constant: T,
+    /// Lipschitz constant of the gradient of the cost, if it is known (see
+    /// `PANOCOptimizer::with_lipschitz_constant`), in which case it is not
+    /// estimated at the initial point
+    pub(crate) known_lipschitz_constant: Option<T>,
+    pub(crate) sigma: T,
+    pub(crate) cost_value: T,
+    /// Cost at the forward-backward step (`u_half_step`), which is computed
+    /// in the update of the Lipschitz constant
+    pub(crate) cost_half_step: T,
     pub(crate) iteration: usize,
-    pub(crate) akkt_tolerance: Option<f64>,
+    pub(crate) akkt_tolerance: Option<T>,
+    /// Tolerances of the termination criterion on the change of the iterate
+    /// (if activated)
+    pub(crate) step_tolerance: Option<StepTolerance<T>>,
+    /// Stores the iterate of the previous iteration; it is allocated only if
+    /// the termination criterion on the change of the iterate is activated
+    pub(crate) u_previous: Option<Vec<T>>,
+    /// Whether the outputs of the cost and its gradient are screened for
+    /// NaN/infinite values after every evaluation
+    pub(crate) nan_screening: bool,
+    /// Whether the line search interpolates the FBE instead of shrinking tau
+    /// by a constant factor
+    pub(crate) interpolating_line_search: bool,
+    /// Schedule of the candidates of the line search
+    pub(crate) line_search: LineSearchSchedule<T>,
+    /// Numbers of evaluations of the cost, its gradient and of projections
+    pub(crate) oracle_calls: OracleCalls,
+    /// Trust-region radius, if the trust-region globalization replaces the
+    /// line search
+    pub(crate) trust_region: Option<TrustRegion<T>>,
+    /// L-SR1 buffer, if SR1 directions replace the L-BFGS directions
+    pub(crate) sr1: Option<Lsr1<T>>,
+    /// Tangent vectors (allocated only on manifolds, see
+    /// `PANOCOptimizer::with_manifold`)
+    pub(crate) tangent: Vec<T>,
+    /// Statistics of the solves (see `SolverStatistics`)
+    pub(crate) statistics: SolverStatistics,
+    /// Time spent in the phases of the current solve (measured only if
+    /// phase timing is activated)
+    pub(crate) phase_times: PhaseTimes,
+    /// Checkpoint from which the next solve resumes (see `restore`)
+    pub(crate) pending_checkpoint: Option<PanocCheckpoint>,
+    /// Norms of the fixed-point residual of the most recent iterations
+    /// (oldest first)
+    fpr_history: VecDeque<T>,
+    /// Maximum length of `fpr_history` (zero if it is not kept)
+    fpr_history_capacity: usize,
 }
 
-impl PANOCCache {
+impl<T: OpEnFloat> PANOCCache<T> {
     /// Construct a new instance of `PANOCCache`
     ///
     /// ## Arguments
@@ -46,46 +138,68 @@ impl PANOCCache {
     /// - `tolerance` specified tolerance
     /// - `lbfgs_memory_size` memory of the LBFGS buffer
     ///
-    /// ## Panics
+    /// ## Errors
     ///
-    /// The method will panic if
+    /// Returns `SolverError::InvalidParameter` if the specified `tolerance` is
+    /// not positive, or if `problem_size` or `lbfgs_memory_size` is zero
     ///
-    /// - the specified `tolerance` is not positive
-    /// - memory allocation fails (memory capacity overflow)
+    /// ## Panics
+    ///
+    /// The method will panic if memory allocation fails (memory capacity overflow)
     ///
     /// ## Memory allocation
     ///
     /// This constructor allocated memory using `vec!`.
     ///
-    /// It allocates a total of `8*problem_size + 2*lbfgs_memory_size*problem_size + 2*lbfgs_memory_size + 11` floats (`f64`)
+    /// It allocates a total of `8*problem_size + 2*lbfgs_memory_size*problem_size + 2*lbfgs_memory_size + 11` floats (of type `T`)
     ///
-    pub fn new(problem_size: usize, tolerance: f64, lbfgs_memory_size: usize) -> PANOCCache {
-        assert!(tolerance > 0., "tolerance must be positive");
+    pub fn new(
+        problem_size: usize,
+        tolerance: T,
+        lbfgs_memory_size: usize,
+    ) -> Result<PANOCCache<T>, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
 
-        PANOCCache {
-            gradient_u: vec![0.0; problem_size],
+        Ok(PANOCCache {
+            gradient_u: vec![T::zero(); problem_size],
             gradient_u_previous: None,
-            u_half_step: vec![0.0; problem_size],
-            gamma_fpr: vec![0.0; problem_size],
-            direction_lbfgs: vec![0.0; problem_size],
-            gradient_step: vec![0.0; problem_size],
-            u_plus: vec![0.0; problem_size],
-            gamma: 0.0,
+            u_half_step: vec![T::zero(); problem_size],
+            gamma_fpr: vec![T::zero(); problem_size],
+            direction_lbfgs: vec![T::zero(); problem_size],
+            gradient_step: vec![T::zero(); problem_size],
+            u_plus: vec![T::zero(); problem_size],
+            gamma: T::zero(),
             tolerance,
-            norm_gamma_fpr: std::f64::INFINITY,
-            lbfgs: lbfgs::Lbfgs::new(problem_size, lbfgs_memory_size)
-                .with_cbfgs_alpha(DEFAULT_CBFGS_ALPHA)
-                .with_cbfgs_epsilon(DEFAULT_CBFGS_EPSILON)
-                .with_sy_epsilon(DEFAULT_SY_EPSILON),
-            lhs_ls: 0.0,
-            rhs_ls: 0.0,
-            tau: 1.0,
-            lipschitz_constant: 0.0,
-            sigma: 0.0,
-            cost_value: 0.0,
+            norm_gamma_fpr: T::infinity(),
+            lbfgs: Lbfgs::new(problem_size, lbfgs_memory_size)?
+                .with_cbfgs_alpha(T::from_f64(DEFAULT_CBFGS_ALPHA))?
+                .with_cbfgs_epsilon(T::from_f64(DEFAULT_CBFGS_EPSILON))?
+                .with_sy_epsilon(T::from_f64(DEFAULT_SY_EPSILON))?,
+            lhs_ls: T::zero(),
+            rhs_ls: T::zero(),
+            tau: T::one(),
+            lipschitz_constant: T::zero(),
+            known_lipschitz_constant: None,
+            sigma: T::zero(),
+            cost_value: T::zero(),
+            cost_half_step: T::zero(),
             iteration: 0,
             akkt_tolerance: None,
-        }
+            step_tolerance: None,
+            u_previous: None,
+            nan_screening: false,
+            interpolating_line_search: false,
+            line_search: LineSearchSchedule::default(),
+            oracle_calls: OracleCalls::default(),
+            trust_region: None,
+            sr1: None,
+            tangent: Vec::new(),
+            statistics: SolverStatistics::default(),
+            phase_times: PhaseTimes::default(),
+            pending_checkpoint: None,
+            fpr_history: VecDeque::new(),
+            fpr_history_capacity: 0,
+        })
     }
 
     /// Sets the AKKT-specific tolerance and activates the corresponding
@@ -95,14 +209,62 @@ impl PANOCCache {
     ///
     /// - `akkt_tolerance`: Tolerance for the AKKT-specific termination condition
     ///
-    /// ## Panics
+    /// ## Errors
     ///
-    /// The method panics if `akkt_tolerance` is nonpositive
+    /// Returns `SolverError::InvalidParameter` if `akkt_tolerance` is nonpositive
     ///
-    pub fn set_akkt_tolerance(&mut self, akkt_tolerance: f64) {
-        assert!(akkt_tolerance > 0.0, "akkt_tolerance must be positive");
+    pub fn set_akkt_tolerance(&mut self, akkt_tolerance: T) -> Result<(), SolverError> {
+        ensure(
+            akkt_tolerance > T::zero(),
+            "akkt_tolerance",
+            "must be positive",
+        )?;
+        self.activate_akkt_tolerance(akkt_tolerance);
+        Ok(())
+    }
+
+    /// Same as `set_akkt_tolerance`, for callers which have already checked
+    /// that `akkt_tolerance` is positive
+    pub(crate) fn activate_akkt_tolerance(&mut self, akkt_tolerance: T) {
         self.akkt_tolerance = Some(akkt_tolerance);
-        self.gradient_u_previous = Some(vec![0.0; self.gradient_step.len()]);
+        // the previous gradient is only allocated once (ALM activates the AKKT
+        // tolerance at every outer iteration)
+        match &mut self.gradient_u_previous {
+            Some(df_previous) => df_previous.iter_mut().for_each(|df_i| *df_i = T::zero()),
+            None => self.gradient_u_previous = Some(vec![T::zero(); self.gradient_step.len()]),
+        }
+    }
+
+    /// Sets the tolerances of the termination criterion on the change of the
+    /// iterate and activates it (see `PANOCOptimizer::with_step_tolerance`)
+    ///
+    /// The criterion remains active for all subsequent solves which use this
+    /// cache.
+    ///
+    /// ## Arguments
+    ///
+    /// - `relative`: tolerance relative to the norm of the iterate
+    /// - `absolute`: absolute tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if either tolerance is
+    /// negative or both are zero
+    ///
+    pub fn set_step_tolerance(&mut self, relative: T, absolute: T) -> Result<(), SolverError> {
+        self.step_tolerance = Some(StepTolerance::new(relative, absolute)?);
+        if self.u_previous.is_none() {
+            self.u_previous = Some(vec![T::zero(); self.gradient_step.len()]);
+        }
+        Ok(())
+    }
+
+    /// Copies the current iterate, `u`, to `u_previous` (if the termination
+    /// criterion on the change of the iterate is activated)
+    pub(crate) fn cache_previous_iterate(&mut self, u: &[T]) {
+        if let Some(u_previous) = &mut self.u_previous {
+            u_previous.copy_from_slice(u);
+        }
     }
 
     /// Copies the value of the current cost gradient to `gradient_u_previous`,
@@ -117,8 +279,8 @@ impl PANOCCache {
     }
 
     /// Computes the AKKT residual which is defined as `||gamma*(fpr + df - df_previous)||`
-    fn akkt_residual(&self) -> f64 {
-        let mut r = 0.0;
+    fn akkt_residual(&self) -> T {
+        let mut r = T::zero();
         if let Some(df_previous) = &self.gradient_u_previous {
             // Notation: gamma_fpr_i is the i-th element of gamma_fpr = gamma * fpr,
             // df_i is the i-th element of the gradient of the cost function at the
@@ -129,7 +291,7 @@ impl PANOCCache {
                 .iter()
                 .zip(self.gradient_u.iter())
                 .zip(df_previous.iter())
-                .fold(0.0, |mut sum, ((&gamma_fpr_i, &df_i), &dfp_i)| {
+                .fold(T::zero(), |mut sum, ((&gamma_fpr_i, &df_i), &dfp_i)| {
                     sum += (gamma_fpr_i + self.gamma * (df_i - dfp_i)).powi(2);
                     sum
                 })
@@ -153,6 +315,18 @@ impl PANOCCache {
         exit_condition
     }
 
+    /// Checks whether the termination condition on the change of the iterate,
+    /// `||u - u_previous|| <= relative * ||u|| + absolute`, is activated and
+    /// satisfied (it is never satisfied at the first iteration)
+    pub(crate) fn step_exit_condition(&self, u: &[T]) -> bool {
+        match (&self.step_tolerance, &self.u_previous) {
+            (Some(step_tolerance), Some(u_previous)) if self.iteration >= 1 => {
+                step_tolerance.is_satisfied(u, u_previous)
+            }
+            _ => false,
+        }
+    }
+
     /// Returns `true` iff all termination conditions are satisfied
     ///
     /// It checks whether:
@@ -167,22 +341,164 @@ impl PANOCCache {
     ///
     /// In particular,
     ///
-    /// - Resets/empties the LBFGS buffer
+    /// - Resets/empties the LBFGS buffer (and the L-SR1 buffer, if any)
     /// - Sets tau = 1.0
     /// - Sets the iteration count to 0
+    /// - Resets the trust-region radius (if any) to its initial value
     /// - Sets the internal variables `lhs_ls`, `rhs_ls`,
     ///   `lipschitz_constant`, `sigma`, `cost_value`
     ///   and `gamma` to 0.0
     pub fn reset(&mut self) {
         self.lbfgs.reset();
-        self.lhs_ls = 0.0;
-        self.rhs_ls = 0.0;
-        self.tau = 1.0;
-        self.lipschitz_constant = 0.0;
-        self.sigma = 0.0;
-        self.cost_value = 0.0;
+        if let Some(sr1) = &mut self.sr1 {
+            sr1.reset();
+        }
+        self.lhs_ls = T::zero();
+        self.rhs_ls = T::zero();
+        self.tau = T::one();
+        self.lipschitz_constant = T::zero();
+        self.sigma = T::zero();
+        self.cost_value = T::zero();
         self.iteration = 0;
-        self.gamma = 0.0;
+        self.gamma = T::zero();
+        self.oracle_calls.reset();
+        self.phase_times = PhaseTimes::default();
+        self.fpr_history.clear();
+        if let Some(trust_region) = &mut self.trust_region {
+            trust_region.radius = trust_region.initial_radius;
+        }
+    }
+
+    /// Takes a checkpoint of the state of the solver at the iterate `u` (see
+    /// `PanocCheckpoint`), typically after a solve which stopped at `u`
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the length of `u` is not the problem size
+    pub fn checkpoint(&self, u: &[T]) -> PanocCheckpoint {
+        assert_eq!(u.len(), self.gradient_u.len(), "u has wrong dimension");
+        PanocCheckpoint {
+            iterate: u.iter().map(|u_i| u_i.as_f64()).collect(),
+            gamma: self.gamma.as_f64(),
+            sigma: self.sigma.as_f64(),
+            lipschitz_constant: self.lipschitz_constant.as_f64(),
+            lbfgs: self.lbfgs.export_memory(),
+        }
+    }
+
+    /// Restores a checkpoint, so that the next solve with this cache resumes
+    /// with the step size and the L-BFGS memory of the checkpoint (the next
+    /// solve should start from `checkpoint.iterate()`)
+    ///
+    /// If the checkpoint holds more pairs `(s, y)` than the L-BFGS memory of
+    /// this cache, the most recent ones are restored.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the problem size of the
+    /// checkpoint is not that of the cache and `SolverError::InvalidParameter`
+    /// if the checkpoint is inconsistent
+    pub fn restore(&mut self, checkpoint: &PanocCheckpoint) -> Result<(), SolverError> {
+        checkpoint.check(self.gradient_u.len())?;
+        self.pending_checkpoint = Some(checkpoint.clone());
+        Ok(())
+    }
+
+    /// Applies the restored checkpoint (if any) to the cache after a reset,
+    /// so that the solver resumes from it; returns whether a checkpoint has
+    /// been applied
+    pub(crate) fn resume(&mut self) -> bool {
+        match self.pending_checkpoint.take() {
+            Some(checkpoint) => {
+                self.lbfgs.import_memory(&checkpoint.lbfgs);
+                self.gamma = T::from_f64(checkpoint.gamma);
+                self.sigma = T::from_f64(checkpoint.sigma);
+                self.lipschitz_constant = T::from_f64(checkpoint.lipschitz_constant);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Statistics of the solves which used this cache (they are not reset
+    /// when a new problem is solved)
+    pub fn statistics(&self) -> &SolverStatistics {
+        &self.statistics
+    }
+
+    /// Resets the statistics of the solves (see `statistics`)
+    pub fn reset_statistics(&mut self) {
+        self.statistics.reset();
+    }
+
+    /// Number of bytes held by the cache, that is, the size of the structure
+    /// itself and of all its internal buffers (including the L-BFGS or
+    /// L-SR1 buffers and, if kept, the history of the fixed-point residual)
+    ///
+    /// The buffers are allocated upon construction (and by `with_*` methods),
+    /// so this is the memory which a solver with this cache needs, e.g., on
+    /// an embedded device; it grows linearly with the problem size and the
+    /// L-BFGS memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use optimization_engine::panoc::PANOCCache;
+    ///
+    /// let small = PANOCCache::<f64>::new(10, 1e-6, 5)?;
+    /// let large = PANOCCache::<f64>::new(100, 1e-6, 5)?;
+    /// println!("{} bytes", large.memory_footprint());
+    /// assert!(large.memory_footprint() > small.memory_footprint());
+    /// # Ok::<(), optimization_engine::SolverError>(())
+    /// ```
+    pub fn memory_footprint(&self) -> usize {
+        std::mem::size_of::<Self>() + self.heap_size()
+    }
+
+    /// Number of bytes held on the heap by the buffers
+    pub(crate) fn heap_size(&self) -> usize {
+        self.lbfgs.heap_size()
+            + heap_size(&self.gradient_u)
+            + self.gradient_u_previous.as_ref().map_or(0, heap_size)
+            + self.u_previous.as_ref().map_or(0, heap_size)
+            + heap_size(&self.u_half_step)
+            + heap_size(&self.gradient_step)
+            + heap_size(&self.direction_lbfgs)
+            + heap_size(&self.u_plus)
+            + heap_size(&self.gamma_fpr)
+            + self.sr1.as_ref().map_or(0, Lsr1::heap_size)
+            + heap_size(&self.tangent)
+            + self.fpr_history.capacity().max(self.fpr_history_capacity) * std::mem::size_of::<T>()
+    }
+
+    /// Norms of the fixed-point residual of the (at most `capacity`) most
+    /// recent iterations of the last solve, oldest first (see
+    /// `with_fpr_history`); this is empty if they are not kept
+    ///
+    /// Unlike the full history of `PANOCOptimizer::with_history`, these
+    /// norms are kept in a buffer of fixed size, which is allocated once,
+    /// and they can be used to check whether the last iterations converged
+    /// smoothly or oscillated.
+    pub fn fpr_history(&self) -> impl ExactSizeIterator<Item = T> + '_ {
+        self.fpr_history.iter().copied()
+    }
+
+    /// Appends the current norm of the fixed-point residual to the recent
+    /// history (if it is kept), discarding the oldest one if it is full
+    pub(crate) fn record_fpr(&mut self) {
+        if self.fpr_history_capacity == 0 {
+            return;
+        }
+        if self.fpr_history.len() == self.fpr_history_capacity {
+            self.fpr_history.pop_front();
+        }
+        self.fpr_history.push_back(self.norm_gamma_fpr);
+    }
+
+    /// Current trust-region radius, or `None` if the step sizes of PANOC are
+    /// selected by line search (see `PANOCOptimizer::with_trust_region`)
+    pub fn trust_region_radius(&self) -> Option<T> {
+        self.trust_region.map(|trust_region| trust_region.radius)
     }
 
     /// Sets the CBFGS parameters `alpha` and `epsilon`
@@ -197,17 +513,57 @@ impl PANOCCache {
     /// - epsilon
     /// - sy_epsilon
     ///
-    /// ## Panics
+    /// ## Errors
     ///
-    /// The method panics if alpha or epsilon are nonpositive and if sy_epsilon
+    /// Returns `SolverError::InvalidParameter` if alpha, epsilon or sy_epsilon
     /// is negative.
     ///
-    pub fn with_cbfgs_parameters(mut self, alpha: f64, epsilon: f64, sy_epsilon: f64) -> Self {
+    pub fn with_cbfgs_parameters(
+        mut self,
+        alpha: T,
+        epsilon: T,
+        sy_epsilon: T,
+    ) -> Result<Self, SolverError> {
         self.lbfgs = self
             .lbfgs
-            .with_cbfgs_alpha(alpha)
-            .with_cbfgs_epsilon(epsilon)
-            .with_sy_epsilon(sy_epsilon);
-        self
+            .with_cbfgs_alpha(alpha)?
+            .with_cbfgs_epsilon(epsilon)?
+            .with_sy_epsilon(sy_epsilon)?;
+        Ok(self)
+    }
+
+    /// Sets the initial Hessian estimate of the L-BFGS two-loop recursion,
+    /// $H_0 = \gamma I$ (by default, $\gamma = \langle s, y\rangle/\Vert
+    /// y\Vert^2$; see `InitialScaling`)
+    ///
+    /// For stiff problems, where the curvature differs by orders of
+    /// magnitude along different directions, a different scaling may give
+    /// better L-BFGS directions.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `initial_scaling` is a
+    /// constant which is not positive and finite
+    pub fn with_lbfgs_initial_scaling(
+        mut self,
+        initial_scaling: InitialScaling<T>,
+    ) -> Result<Self, SolverError> {
+        self.lbfgs = self.lbfgs.with_initial_scaling(initial_scaling)?;
+        Ok(self)
+    }
+
+    /// Keeps the norms of the fixed-point residual of the `capacity` most
+    /// recent iterations of every solve (see `fpr_history`)
+    ///
+    /// This allocates `capacity` floats.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `capacity` is zero
+    pub fn with_fpr_history(mut self, capacity: usize) -> Result<Self, SolverError> {
+        ensure(capacity > 0, "capacity", "must be positive")?;
+        self.fpr_history = VecDeque::with_capacity(capacity);
+        self.fpr_history_capacity = capacity;
+        Ok(self)
     }
 }
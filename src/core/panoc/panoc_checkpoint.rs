@@ -0,0 +1,178 @@
+use crate::{
+    core::{
+        checkpoint::{Decoder, Encoder},
+        lbfgs::LbfgsMemory,
+    },
+    ensure, SolverError,
+};
+
+/// Header of the binary encoding of `PanocCheckpoint`
+const HEADER: &[u8; 8] = b"OpEnPAN1";
+
+/// Checkpoint of the state of PANOC, which can be saved and restored
+///
+/// A checkpoint holds the iterate, the step size $\gamma$ (together with
+/// the Lipschitz estimate and the parameter $\sigma$ of the line search)
+/// and the memory of the L-BFGS buffer. It is taken with
+/// `PANOCCache::checkpoint` (e.g., after a solve which ran out of time or
+/// iterations) and restored with `PANOCCache::restore`: the next solve with
+/// the restored cache, from the iterate of the checkpoint, resumes with the
+/// step size and the L-BFGS memory of the checkpoint instead of estimating
+/// them anew. This way, long runs survive process restarts and can be
+/// migrated between processes.
+///
+/// Checkpoints can be converted to bytes (with `to_bytes` and `from_bytes`)
+/// and, with the feature `serde`, serialized with serde. The state of the
+/// L-SR1 and the trust-region variants of PANOC is not part of the
+/// checkpoint.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{constraints::*, panoc::*, *};
+///
+/// let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+///     grad.iter_mut().zip(u).for_each(|(g, u_i)| *g = 4.0 * u_i.powi(3) - 1.0);
+///     Ok(())
+/// };
+/// let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+///     *c = u.iter().map(|u_i| u_i.powi(4) - u_i).sum();
+///     Ok(())
+/// };
+/// let bounds = NoConstraints::new();
+/// let mut u = [1.0; 3];
+/// let mut cache = PANOCCache::new(3, 1e-10, 5)?;
+/// PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache)
+///     .with_max_iter(2)?
+///     .solve(&mut u)?;
+/// let bytes = cache.checkpoint(&u).to_bytes();
+///
+/// // later (e.g., in another process)
+/// let checkpoint = PanocCheckpoint::from_bytes(&bytes)?;
+/// let mut cache = PANOCCache::new(3, 1e-10, 5)?;
+/// cache.restore(&checkpoint)?;
+/// let mut u = checkpoint.iterate().to_vec();
+/// let status = PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache)
+///     .solve(&mut u)?;
+/// assert!(status.has_converged());
+/// # Ok::<(), SolverError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PanocCheckpoint {
+    /// iterate
+    pub(crate) iterate: Vec<f64>,
+    /// step size
+    pub(crate) gamma: f64,
+    /// parameter of the line search
+    pub(crate) sigma: f64,
+    /// estimate of the Lipschitz constant of the gradient
+    pub(crate) lipschitz_constant: f64,
+    /// memory of the L-BFGS buffer
+    pub(crate) lbfgs: LbfgsMemory,
+}
+
+impl PanocCheckpoint {
+    /// Iterate of the checkpoint, from which the solver should resume
+    pub fn iterate(&self) -> &[f64] {
+        &self.iterate
+    }
+
+    /// Step size of the checkpoint
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    /// Number of pairs `(s, y)` in the L-BFGS memory of the checkpoint
+    pub fn lbfgs_pairs(&self) -> usize {
+        self.lbfgs.s.len()
+    }
+
+    /// Encodes the checkpoint as bytes (see `from_bytes`)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new(HEADER);
+        encoder.vector(&self.iterate);
+        encoder.f64(self.gamma);
+        encoder.f64(self.sigma);
+        encoder.f64(self.lipschitz_constant);
+        let lbfgs = &self.lbfgs;
+        encoder.f64(lbfgs.gamma);
+        encoder.usize(lbfgs.s.len());
+        for ((s, y), &rho) in lbfgs.s.iter().zip(&lbfgs.y).zip(&lbfgs.rho) {
+            encoder.vector(s);
+            encoder.vector(y);
+            encoder.f64(rho);
+        }
+        encoder.bool(lbfgs.old_state_and_g.is_some());
+        if let Some((old_state, old_g)) = &lbfgs.old_state_and_g {
+            encoder.vector(old_state);
+            encoder.vector(old_g);
+        }
+        encoder.into_bytes()
+    }
+
+    /// Decodes a checkpoint which was encoded with `to_bytes`
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `bytes` is not a valid
+    /// checkpoint of PANOC
+    pub fn from_bytes(bytes: &[u8]) -> Result<PanocCheckpoint, SolverError> {
+        let mut decoder = Decoder::new(bytes, HEADER)?;
+        let iterate = decoder.vector()?;
+        let gamma = decoder.f64()?;
+        let sigma = decoder.f64()?;
+        let lipschitz_constant = decoder.f64()?;
+        let mut lbfgs = LbfgsMemory {
+            gamma: decoder.f64()?,
+            ..LbfgsMemory::default()
+        };
+        for _ in 0..decoder.usize()? {
+            lbfgs.s.push(decoder.vector()?);
+            lbfgs.y.push(decoder.vector()?);
+            lbfgs.rho.push(decoder.f64()?);
+        }
+        if decoder.bool()? {
+            lbfgs.old_state_and_g = Some((decoder.vector()?, decoder.vector()?));
+        }
+        decoder.finish()?;
+        let checkpoint = PanocCheckpoint {
+            iterate,
+            gamma,
+            sigma,
+            lipschitz_constant,
+            lbfgs,
+        };
+        checkpoint.check(checkpoint.iterate.len())?;
+        Ok(checkpoint)
+    }
+
+    /// Checks that the checkpoint is consistent and of problem size `n`
+    pub(crate) fn check(&self, n: usize) -> Result<(), SolverError> {
+        let lbfgs = &self.lbfgs;
+        let mismatch = |actual: usize| SolverError::DimensionMismatch {
+            name: "checkpoint",
+            expected: n,
+            actual,
+        };
+        if let Some(len) = std::iter::once(&self.iterate)
+            .chain(&lbfgs.s)
+            .chain(&lbfgs.y)
+            .chain(lbfgs.old_state_and_g.iter().flat_map(|(x, g)| [x, g]))
+            .map(Vec::len)
+            .find(|&len| len != n)
+        {
+            return Err(mismatch(len));
+        }
+        ensure(
+            lbfgs.y.len() == lbfgs.s.len() && lbfgs.rho.len() == lbfgs.s.len(),
+            "checkpoint",
+            "has an inconsistent L-BFGS memory",
+        )?;
+        ensure(
+            self.gamma > 0.0 && self.gamma.is_finite(),
+            "checkpoint",
+            "has no valid step size (the cache has not been used)",
+        )
+    }
+}
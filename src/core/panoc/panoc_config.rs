@@ -0,0 +1,124 @@
+//! Configuration of PANOC
+//!
+use std::time;
+
+/// Default maximum number of iterations of PANOC
+pub(crate) const DEFAULT_MAX_ITER: usize = 100_usize;
+
+/// Configuration of [PANOCOptimizer](struct.PANOCOptimizer.html)
+///
+/// This structure mirrors the `with_*` setter methods of `PANOCOptimizer`
+/// and can be applied to an optimizer using `PANOCOptimizer::with_config`.
+/// Fields which are `None` are not applied, that is, the corresponding
+/// values of the optimizer (and its cache) are kept.
+///
+/// If OpEn is compiled with the feature `serde`, `PanocConfig` can be
+/// deserialized, e.g., from a JSON or a TOML file, so that tolerances and
+/// iteration limits can be tuned without recompiling. Missing fields take
+/// their default values.
+///
+/// # Example
+///
+/// ```ignore
+/// let json = std::fs::read_to_string("panoc.json")?;
+/// let config: PanocConfig = serde_json::from_str(&json)?;
+/// let mut panoc = PANOCOptimizer::new(problem, &mut cache).with_config(&config)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PanocConfig {
+    /// Tolerance on the norm of the fixed-point residual (see
+    /// `PANOCOptimizer::with_tolerance`)
+    pub tolerance: Option<f64>,
+    /// AKKT-specific tolerance (see `PANOCOptimizer::with_akkt_tolerance`)
+    pub akkt_tolerance: Option<f64>,
+    /// Maximum number of iterations (see `PANOCOptimizer::with_max_iter`)
+    pub max_iter: usize,
+    /// Maximum solution time (see `PANOCOptimizer::with_max_duration`)
+    pub max_duration: Option<time::Duration>,
+    /// Whether to record the cost and FPR at every iteration (see
+    /// `PANOCOptimizer::with_history`)
+    pub record_history: bool,
+    /// Whether to screen the outputs of the cost and its gradient for
+    /// NaN/infinite values (see `PANOCOptimizer::with_nan_screening`)
+    pub nan_screening: bool,
+    /// Initial trust-region radius; if set, the trust-region globalization
+    /// replaces the line search (see `PANOCOptimizer::with_trust_region`)
+    pub trust_region_radius: Option<f64>,
+    /// Memory of the L-SR1 buffer; if set, SR1 directions replace the
+    /// L-BFGS directions (see `PANOCOptimizer::with_sr1`)
+    pub sr1_memory: Option<usize>,
+}
+
+impl Default for PanocConfig {
+    fn default() -> Self {
+        PanocConfig {
+            tolerance: None,
+            akkt_tolerance: None,
+            max_iter: DEFAULT_MAX_ITER,
+            max_duration: None,
+            record_history: false,
+            nan_screening: false,
+            trust_region_radius: None,
+            sr1_memory: None,
+        }
+    }
+}
+
+impl PanocConfig {
+    /// Constructs a new configuration with default values
+    pub fn new() -> Self {
+        PanocConfig::default()
+    }
+
+    /// Sets the tolerance on the norm of the fixed-point residual
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Sets the AKKT-specific tolerance
+    pub fn with_akkt_tolerance(mut self, akkt_tolerance: f64) -> Self {
+        self.akkt_tolerance = Some(akkt_tolerance);
+        self
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Sets whether to record the cost and FPR at every iteration
+    pub fn with_history(mut self, record_history: bool) -> Self {
+        self.record_history = record_history;
+        self
+    }
+
+    /// Sets whether to screen the outputs of the cost and its gradient
+    pub fn with_nan_screening(mut self, nan_screening: bool) -> Self {
+        self.nan_screening = nan_screening;
+        self
+    }
+
+    /// Sets the initial trust-region radius (and activates the trust-region
+    /// globalization)
+    pub fn with_trust_region(mut self, initial_radius: f64) -> Self {
+        self.trust_region_radius = Some(initial_radius);
+        self
+    }
+
+    /// Sets the memory of the L-SR1 buffer (and activates the SR1
+    /// directions)
+    pub fn with_sr1(mut self, memory: usize) -> Self {
+        self.sr1_memory = Some(memory);
+        self
+    }
+}
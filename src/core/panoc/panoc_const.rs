@@ -0,0 +1,650 @@
+//! Heap-free PANOC with compile-time dimensions
+//!
+//! [`PANOCCacheConst`] stores all the buffers of PANOC (including the L-BFGS
+//! buffer) in arrays whose sizes, the problem size `N` and the L-BFGS memory
+//! `MEM`, are known at compile time, and [`PANOCOptimizerConst`] solves a
+//! [`Problem`] using such a cache. Neither the construction of the cache nor
+//! `solve` allocates memory on the heap, so the solver can run on
+//! microcontrollers without an allocator (the cache can be, e.g., a
+//! `static`).
+//!
+//! Compared to [`PANOCOptimizer`], the heap-free optimizer supports the
+//! L-BFGS directions, the line search and the AKKT termination criterion,
+//! but not the alternative directions (SR1, Gauss-Newton, semi-smooth Newton),
+//! the trust region, NaN screening, progress reports or histories.
+//!
+//! [`PANOCCacheConst`]: struct.PANOCCacheConst.html
+//! [`PANOCOptimizerConst`]: struct.PANOCOptimizerConst.html
+//! [`Problem`]: ../struct.Problem.html
+//! [`PANOCOptimizer`]: struct.PANOCOptimizer.html
+use crate::{
+    constraints,
+    core::{
+        panoc::{panoc_cache::*, panoc_engine::*},
+        Clock, ExitStatus, OracleCalls, Problem, SolverStatus, StdClock,
+    },
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+/// L-BFGS buffer stored in arrays (see `Lbfgs`)
+#[derive(Debug, Clone)]
+struct LbfgsConst<const N: usize, const MEM: usize, T> {
+    active_size: usize,
+    gamma: T,
+    /// s_0 holds the most recent state difference
+    s: [[T; N]; MEM],
+    /// y_0 holds the most recent difference of residuals
+    y: [[T; N]; MEM],
+    /// temporary area of the candidate pair (s, y)
+    s_new: [T; N],
+    y_new: [T; N],
+    alpha: [T; MEM],
+    rho: [T; MEM],
+    old_state: [T; N],
+    old_g: [T; N],
+    first_old: bool,
+}
+
+impl<const N: usize, const MEM: usize, T: OpEnFloat> LbfgsConst<N, MEM, T> {
+    fn new() -> Self {
+        LbfgsConst {
+            active_size: 0,
+            gamma: T::one(),
+            s: [[T::zero(); N]; MEM],
+            y: [[T::zero(); N]; MEM],
+            s_new: [T::zero(); N],
+            y_new: [T::zero(); N],
+            alpha: [T::zero(); MEM],
+            rho: [T::zero(); MEM],
+            old_state: [T::zero(); N],
+            old_g: [T::zero(); N],
+            first_old: true,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.active_size = 0;
+        self.first_old = true;
+    }
+
+    /// Two-loop recursion (see `Lbfgs::apply_hessian`)
+    fn apply_hessian(&mut self, q: &mut [T]) {
+        let active = self.active_size;
+        if active == 0 {
+            return;
+        }
+        for k in 0..active {
+            let a = self.rho[k] * matrix_operations::inner_product(&self.s[k], q);
+            self.alpha[k] = a;
+            q.iter_mut()
+                .zip(self.y[k].iter())
+                .for_each(|(q_i, &y_i)| *q_i -= a * y_i);
+        }
+        let gamma = self.gamma;
+        q.iter_mut().for_each(|q_i| *q_i *= gamma);
+        for k in (0..active).rev() {
+            let beta = self.rho[k] * matrix_operations::inner_product(&self.y[k], q);
+            let coefficient = self.alpha[k] - beta;
+            q.iter_mut()
+                .zip(self.s[k].iter())
+                .for_each(|(q_i, &s_i)| *q_i += coefficient * s_i);
+        }
+    }
+
+    /// Saves the pair (s, y), if it satisfies the C-BFGS conditions with the
+    /// default parameters of `PANOCCache` (see `Lbfgs::update_hessian`)
+    fn update_hessian(&mut self, g: &[T], state: &[T]) {
+        if self.first_old {
+            self.first_old = false;
+            self.old_state.copy_from_slice(state);
+            self.old_g.copy_from_slice(g);
+            return;
+        }
+        for i in 0..N {
+            self.s_new[i] = state[i] - self.old_state[i];
+            self.y_new[i] = g[i] - self.old_g[i];
+        }
+        let ys = matrix_operations::inner_product(&self.s_new, &self.y_new);
+        let norm_s_squared = matrix_operations::norm2_squared(&self.s_new);
+        if norm_s_squared <= T::min_positive_value()
+            || ys <= T::from_f64(DEFAULT_SY_EPSILON)
+            || ys / norm_s_squared
+                <= T::from_f64(DEFAULT_CBFGS_EPSILON)
+                    * matrix_operations::norm2(g).powf(T::from_f64(DEFAULT_CBFGS_ALPHA))
+        {
+            return;
+        }
+        self.old_state.copy_from_slice(state);
+        self.old_g.copy_from_slice(g);
+        self.s.rotate_right(1);
+        self.y.rotate_right(1);
+        self.rho.rotate_right(1);
+        self.s[0] = self.s_new;
+        self.y[0] = self.y_new;
+        self.rho[0] = T::one() / ys;
+        self.gamma = ys / matrix_operations::norm2_squared(&self.y_new);
+        self.active_size = MEM.min(self.active_size + 1);
+    }
+}
+
+/// Cache of the heap-free PANOC optimizer, whose buffers are arrays
+///
+/// The problem size is `N` and the memory of the L-BFGS buffer is `MEM`; the
+/// cache holds `(2 * MEM + 11) * N + 2 * MEM` floats (of type `T`), e.g.,
+/// `PANOCCacheConst<10, 5>` holds 220 floats.
+#[derive(Debug, Clone)]
+pub struct PANOCCacheConst<const N: usize, const MEM: usize, T = f64> {
+    lbfgs: LbfgsConst<N, MEM, T>,
+    pub(crate) gradient_u: [T; N],
+    gradient_u_previous: [T; N],
+    pub(crate) u_half_step: [T; N],
+    gradient_step: [T; N],
+    direction_lbfgs: [T; N],
+    u_plus: [T; N],
+    gamma_fpr: [T; N],
+    rhs_ls: T,
+    lhs_ls: T,
+    gamma: T,
+    pub(crate) tolerance: T,
+    pub(crate) norm_gamma_fpr: T,
+    tau: T,
+    lipschitz_constant: T,
+    sigma: T,
+    pub(crate) cost_value: T,
+    iteration: usize,
+    pub(crate) akkt_tolerance: Option<T>,
+    pub(crate) oracle_calls: OracleCalls,
+}
+
+impl<const N: usize, const MEM: usize, T: OpEnFloat> PANOCCacheConst<N, MEM, T> {
+    /// Constructs a new cache with the given tolerance (see `PANOCCache::new`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not
+    /// positive, or if `N` or `MEM` is zero
+    pub fn new(tolerance: T) -> Result<Self, SolverError> {
+        ensure(N > 0, "problem_size", "must be positive")?;
+        ensure(MEM > 0, "lbfgs_memory_size", "must be positive")?;
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        Ok(PANOCCacheConst {
+            lbfgs: LbfgsConst::new(),
+            gradient_u: [T::zero(); N],
+            gradient_u_previous: [T::zero(); N],
+            u_half_step: [T::zero(); N],
+            gradient_step: [T::zero(); N],
+            direction_lbfgs: [T::zero(); N],
+            u_plus: [T::zero(); N],
+            gamma_fpr: [T::zero(); N],
+            rhs_ls: T::zero(),
+            lhs_ls: T::zero(),
+            gamma: T::zero(),
+            tolerance,
+            norm_gamma_fpr: T::infinity(),
+            tau: T::one(),
+            lipschitz_constant: T::zero(),
+            sigma: T::zero(),
+            cost_value: T::zero(),
+            iteration: 0,
+            akkt_tolerance: None,
+            oracle_calls: OracleCalls::default(),
+        })
+    }
+
+    /// Sets the AKKT-specific tolerance and activates the corresponding
+    /// termination criterion (see `PANOCCache::set_akkt_tolerance`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `akkt_tolerance` is nonpositive
+    pub fn set_akkt_tolerance(&mut self, akkt_tolerance: T) -> Result<(), SolverError> {
+        ensure(
+            akkt_tolerance > T::zero(),
+            "akkt_tolerance",
+            "must be positive",
+        )?;
+        self.activate_akkt_tolerance(akkt_tolerance);
+        Ok(())
+    }
+
+    /// Same as `set_akkt_tolerance`, for callers which have already checked
+    /// that `akkt_tolerance` is positive
+    pub(crate) fn activate_akkt_tolerance(&mut self, akkt_tolerance: T) {
+        self.akkt_tolerance = Some(akkt_tolerance);
+        self.gradient_u_previous = [T::zero(); N];
+    }
+
+    /// Resets the cache to its initial state (see `PANOCCache::reset`)
+    pub fn reset(&mut self) {
+        self.lbfgs.reset();
+        self.lhs_ls = T::zero();
+        self.rhs_ls = T::zero();
+        self.tau = T::one();
+        self.lipschitz_constant = T::zero();
+        self.sigma = T::zero();
+        self.cost_value = T::zero();
+        self.iteration = 0;
+        self.gamma = T::zero();
+        self.oracle_calls.reset();
+    }
+
+    /// Whether the FPR (and, if activated, the AKKT) termination criteria
+    /// are satisfied
+    fn exit_condition(&self) -> bool {
+        let akkt_condition = self.akkt_tolerance.is_none_or(|akkt_tolerance| {
+            let residual = (0..N)
+                .map(|i| {
+                    (self.gamma_fpr[i]
+                        + self.gamma * (self.gradient_u[i] - self.gradient_u_previous[i]))
+                        .powi(2)
+                })
+                .fold(T::zero(), |sum, r_i| sum + r_i)
+                .sqrt();
+            residual < akkt_tolerance
+        });
+        self.norm_gamma_fpr < self.tolerance && akkt_condition
+    }
+}
+
+/// Heap-free PANOC optimizer, which uses a [`PANOCCacheConst`]
+///
+/// See the [module documentation](index.html) of `panoc` and `PANOCOptimizer`
+/// for details on the algorithm.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{constraints::Ball2, panoc::*, FunctionCallResult, Optimizer, Problem};
+///
+/// let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+///     *c = (u[0] - 2.0).powi(2) + (u[1] + 1.0).powi(2);
+///     Ok(())
+/// };
+/// let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+///     g[0] = 2.0 * (u[0] - 2.0);
+///     g[1] = 2.0 * (u[1] + 1.0);
+///     Ok(())
+/// };
+/// let bounds = Ball2::new(None, 1.0)?;
+/// let mut cache = PANOCCacheConst::<2, 3>::new(1e-10)?;
+/// let mut u = [0.0; 2];
+/// let status = PANOCOptimizerConst::new(Problem::new(&bounds, grad, cost), &mut cache)
+///     .solve(&mut u)?;
+/// assert!(status.has_converged());
+/// assert!((u[0] - 2.0 / 5.0_f64.sqrt()).abs() < 1e-8);
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+///
+/// [`PANOCCacheConst`]: struct.PANOCCacheConst.html
+pub struct PANOCOptimizerConst<
+    'a,
+    GradientType,
+    ConstraintType,
+    CostType,
+    const N: usize,
+    const MEM: usize,
+    T = f64,
+> where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+    cache: &'a mut PANOCCacheConst<N, MEM, T>,
+    max_iter: usize,
+    max_duration: Option<time::Duration>,
+    clock: &'a dyn Clock,
+}
+
+impl<'a, GradientType, ConstraintType, CostType, const N: usize, const MEM: usize, T>
+    PANOCOptimizerConst<'a, GradientType, ConstraintType, CostType, N, MEM, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructs a new heap-free PANOC optimizer
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem`: problem definition
+    /// - `cache`: a cache, which can be reused by other optimizers
+    pub fn new(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: &'a mut PANOCCacheConst<N, MEM, T>,
+    ) -> Self {
+        PANOCOptimizerConst {
+            problem,
+            cache,
+            max_iter: crate::core::panoc::panoc_config::DEFAULT_MAX_ITER,
+            max_duration: None,
+            clock: &StdClock,
+        }
+    }
+
+    /// Sets the tolerance on the norm of the fixed-point residual
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        self.cache.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `max_iter` is zero
+    pub fn with_max_iter(mut self, max_iter: usize) -> Result<Self, SolverError> {
+        ensure(max_iter > 0, "max_iter", "must be positive")?;
+        self.max_iter = max_iter;
+        Ok(self)
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Sets the clock which measures the solution time (see `Clock`); the
+    /// default is `StdClock`
+    pub fn with_clock(mut self, clock: &'a dyn Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn cost(&mut self, u: &[T], cost_value: &mut T) -> FunctionCallResult {
+        self.cache.oracle_calls.cost += 1;
+        (self.problem.cost)(u, cost_value)
+    }
+
+    /// gradient_step ← u - gamma * gradient_u
+    fn gradient_step(&mut self, u: &[T]) {
+        let cache = &mut *self.cache;
+        let gamma = cache.gamma;
+        cache
+            .gradient_step
+            .iter_mut()
+            .zip(u.iter())
+            .zip(cache.gradient_u.iter())
+            .for_each(|((grad_step, &u_i), &grad)| *grad_step = u_i - gamma * grad);
+    }
+
+    /// u_half_step ← projection(gradient_step)
+    fn half_step(&mut self) {
+        let cache = &mut *self.cache;
+        cache.u_half_step = cache.gradient_step;
+        self.problem.constraints.project(&mut cache.u_half_step);
+        cache.oracle_calls.projections += 1;
+    }
+
+    /// gamma_fpr ← u - u_half_step and its norm
+    fn compute_fpr(&mut self, u: &[T]) {
+        let cache = &mut *self.cache;
+        cache
+            .gamma_fpr
+            .iter_mut()
+            .zip(u.iter())
+            .zip(cache.u_half_step.iter())
+            .for_each(|((fpr, &u_i), &uhalf)| *fpr = u_i - uhalf);
+        cache.norm_gamma_fpr = matrix_operations::norm2(&cache.gamma_fpr);
+    }
+
+    /// Computes the gradient at `u` and estimates its Lipschitz constant (see
+    /// `LipschitzEstimator`); like the estimator, it sets `u` to the perturbed
+    /// point `u + h`
+    fn estimate_loc_lip(&mut self, u: &mut [T]) -> FunctionCallResult {
+        let cache = &mut *self.cache;
+        cache.oracle_calls.gradient += 2;
+        (self.problem.gradf)(u, &mut cache.gradient_u)?;
+        let (epsilon, delta) = (T::from_f64(EPSILON_LIPSCHITZ), T::from_f64(DELTA_LIPSCHITZ));
+        let mut norm_h_squared = T::zero();
+        u.iter_mut().for_each(|u_i| {
+            let h_i = if epsilon * *u_i > delta {
+                epsilon * *u_i
+            } else {
+                delta
+            };
+            norm_h_squared += h_i * h_i;
+            *u_i += h_i;
+        });
+        (self.problem.gradf)(u, &mut cache.gradient_step)?;
+        cache.lipschitz_constant =
+            matrix_operations::norm2_squared_diff(&cache.gradient_step, &cache.gradient_u).sqrt()
+                / norm_h_squared.sqrt();
+        Ok(())
+    }
+
+    fn init(&mut self, u: &mut [T]) -> FunctionCallResult {
+        self.cache.reset();
+        let mut cost_value = T::zero();
+        self.cost(u, &mut cost_value)?;
+        self.cache.cost_value = cost_value;
+        self.estimate_loc_lip(u)?;
+        let cache = &mut *self.cache;
+        cache.gamma =
+            T::from_f64(GAMMA_L_COEFF) / cache.lipschitz_constant.max(T::from_f64(MIN_L_ESTIMATE));
+        cache.sigma = T::from_f64(1.0 - GAMMA_L_COEFF) / (T::from_f64(4.0) * cache.gamma);
+        self.gradient_step(u);
+        self.half_step();
+        Ok(())
+    }
+
+    /// Right-hand side of the condition of the Lipschitz update
+    fn lipschitz_check_rhs(&self) -> T {
+        let cache = &*self.cache;
+        let inner_prod_grad_fpr =
+            matrix_operations::inner_product(&cache.gradient_u, &cache.gamma_fpr);
+        cache.cost_value + T::from_f64(LIPSCHITZ_UPDATE_EPSILON) * cache.cost_value.abs()
+            - inner_prod_grad_fpr
+            + (T::from_f64(GAMMA_L_COEFF) / (T::from_f64(2.0) * cache.gamma))
+                * cache.norm_gamma_fpr.powi(2)
+    }
+
+    fn update_lipschitz_constant(&mut self, u: &[T]) -> FunctionCallResult {
+        let mut cost_u_half_step = T::zero();
+        let u_half_step = self.cache.u_half_step;
+        self.cost(&u_half_step, &mut cost_u_half_step)?;
+        let mut cost_value = T::zero();
+        self.cost(u, &mut cost_value)?;
+        self.cache.cost_value = cost_value;
+
+        let mut it_lipschitz_search = 0;
+        while cost_u_half_step > self.lipschitz_check_rhs()
+            && it_lipschitz_search < MAX_LIPSCHITZ_UPDATE_ITERATIONS
+            && self.cache.lipschitz_constant < T::from_f64(MAX_LIPSCHITZ_CONSTANT)
+        {
+            self.cache.lbfgs.reset();
+            self.cache.lipschitz_constant *= T::from_f64(2.0);
+            self.cache.gamma /= T::from_f64(2.0);
+            self.gradient_step(u);
+            self.half_step();
+            let u_half_step = self.cache.u_half_step;
+            self.cost(&u_half_step, &mut cost_u_half_step)?;
+            self.compute_fpr(u);
+            it_lipschitz_search += 1;
+        }
+        self.cache.sigma = T::from_f64(1.0 - GAMMA_L_COEFF) / (T::from_f64(4.0) * self.cache.gamma);
+        Ok(())
+    }
+
+    /// Updates the L-BFGS buffer and computes the direction
+    fn lbfgs_direction(&mut self, u: &[T]) {
+        let cache = &mut *self.cache;
+        cache.lbfgs.update_hessian(&cache.gamma_fpr, u);
+        if cache.iteration > 0 {
+            cache.direction_lbfgs = cache.gamma_fpr;
+            cache.lbfgs.apply_hessian(&mut cache.direction_lbfgs);
+        }
+    }
+
+    /// Forward-backward envelope at the current point: cost - (gamma/2)
+    /// |gradient|^2 + |gradient_step - u_half_step|^2 / (2 gamma)
+    fn fbe(&self) -> T {
+        let cache = &*self.cache;
+        let half = T::from_f64(0.5);
+        cache.cost_value - half * cache.gamma * matrix_operations::norm2_squared(&cache.gradient_u)
+            + half * matrix_operations::norm2_squared_diff(&cache.gradient_step, &cache.u_half_step)
+                / cache.gamma
+    }
+
+    /// Evaluates the cost, the gradient and the forward-backward step at
+    /// u_plus ← u - (1-tau) * gamma_fpr - tau * direction; returns whether the
+    /// line search should continue
+    fn line_search_condition(&mut self, u: &[T]) -> Result<bool, SolverError> {
+        let cache = &mut *self.cache;
+        let tau = cache.tau;
+        cache
+            .u_plus
+            .iter_mut()
+            .zip(u.iter())
+            .zip(cache.gamma_fpr.iter())
+            .zip(cache.direction_lbfgs.iter())
+            .for_each(|(((u_plus_i, &u_i), &fpr_i), &dir_i)| {
+                *u_plus_i = u_i - (T::one() - tau) * fpr_i - tau * dir_i;
+            });
+        let u_plus = cache.u_plus;
+        let mut cost_value = T::zero();
+        self.cost(&u_plus, &mut cost_value)?;
+        self.cache.cost_value = cost_value;
+        self.cache.oracle_calls.gradient += 1;
+        (self.problem.gradf)(&u_plus, &mut self.cache.gradient_u)?;
+        self.gradient_step(&u_plus);
+        self.half_step();
+        self.cache.lhs_ls = self.fbe();
+        Ok(self.cache.lhs_ls > self.cache.rhs_ls)
+    }
+
+    fn update_no_linesearch(&mut self, u: &mut [T]) -> FunctionCallResult {
+        u.copy_from_slice(&self.cache.u_half_step);
+        let mut cost_value = T::zero();
+        self.cost(u, &mut cost_value)?;
+        self.cache.cost_value = cost_value;
+        self.cache.oracle_calls.gradient += 1;
+        (self.problem.gradf)(u, &mut self.cache.gradient_u)?;
+        self.gradient_step(u);
+        self.half_step();
+        Ok(())
+    }
+
+    fn linesearch(&mut self, u: &mut [T]) -> FunctionCallResult {
+        self.cache.rhs_ls = self.fbe() - self.cache.sigma * self.cache.norm_gamma_fpr.powi(2);
+        self.cache.tau = T::one();
+        let mut num_ls_iters = 0;
+        while self.line_search_condition(u)? {
+            if num_ls_iters == MAX_LINESEARCH_ITERATIONS {
+                // forward-backward step
+                self.cache.tau = T::zero();
+                self.line_search_condition(u)?;
+                break;
+            }
+            self.cache.tau /= T::from_f64(2.0);
+            num_ls_iters += 1;
+        }
+        u.copy_from_slice(&self.cache.u_plus);
+        Ok(())
+    }
+
+    /// PANOC step (see `PANOCEngine::step`); returns `false` if the
+    /// termination criteria are satisfied
+    fn step(&mut self, u: &mut [T]) -> Result<bool, SolverError> {
+        if self.cache.iteration >= 1 {
+            self.cache.gradient_u_previous = self.cache.gradient_u;
+        }
+        self.compute_fpr(u);
+        if self.cache.exit_condition() {
+            return Ok(false);
+        }
+        self.update_lipschitz_constant(u)?;
+        self.lbfgs_direction(u);
+        if self.cache.iteration == 0 {
+            self.update_no_linesearch(u)?;
+        } else {
+            self.linesearch(u)?;
+        }
+        self.cache.iteration += 1;
+        Ok(true)
+    }
+}
+
+impl<'a, GradientType, ConstraintType, CostType, const N: usize, const MEM: usize, T>
+    crate::core::Optimizer<T>
+    for PANOCOptimizerConst<'a, GradientType, ConstraintType, CostType, N, MEM, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = self.clock.now();
+        if u.len() != N {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: N,
+                actual: u.len(),
+            });
+        }
+        self.init(u)?;
+
+        let mut num_iter: usize = 0;
+        let mut continue_num_iters = true;
+        let mut continue_runtime = true;
+        let mut step_flag = self.step(u)?;
+        while step_flag && continue_num_iters && continue_runtime {
+            num_iter += 1;
+            continue_num_iters = num_iter < self.max_iter;
+            continue_runtime = self
+                .max_duration
+                .is_none_or(|dur| self.clock.elapsed(now) <= dur);
+            step_flag = self.step(u)?;
+        }
+
+        let cache = &*self.cache;
+        if !cache.cost_value.is_finite() {
+            return Err(SolverError::NotFiniteCost {
+                location: "PANOC",
+                iteration: num_iter,
+            });
+        }
+        if let Some(index) = matrix_operations::first_non_finite(&cache.gradient_u) {
+            return Err(SolverError::NotFiniteGradient {
+                location: "PANOC",
+                index,
+                iteration: num_iter,
+            });
+        }
+        if !matrix_operations::is_finite(&cache.u_half_step) {
+            return Err(SolverError::ProjectionFailed {
+                location: "PANOC",
+                iteration: num_iter,
+            });
+        }
+
+        let exit_status = if !continue_num_iters {
+            ExitStatus::NotConvergedIterations
+        } else if !continue_runtime {
+            ExitStatus::NotConvergedOutOfTime
+        } else {
+            ExitStatus::Converged
+        };
+        u.copy_from_slice(&cache.u_half_step);
+        Ok(SolverStatus::new(
+            exit_status,
+            num_iter,
+            self.clock.elapsed(now),
+            cache.norm_gamma_fpr.as_f64(),
+            cache.cost_value.as_f64(),
+        )
+        .with_gradient_norm(matrix_operations::norm2(&cache.gradient_u).as_f64())
+        .with_oracle_calls(cache.oracle_calls))
+    }
+}
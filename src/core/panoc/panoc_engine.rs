@@ -1,57 +1,161 @@
 use crate::{
     constraints,
-    core::{panoc::PANOCCache, AlgorithmEngine, Problem},
-    matrix_operations, FunctionCallResult, SolverError,
+    core::{
+        batch_oracle::BatchPrefetch,
+        maybe_owned::MaybeOwnedMut,
+        panoc::{gauss_newton::GaussNewtonDirection, PANOCCache},
+        phase_times::Phase,
+        screen_output, AlgorithmEngine, Clock, Problem,
+    },
+    manifold::Manifold,
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
 };
+use std::time::Duration;
 
 /// Mimum estimated Lipschitz constant (initial estimate)
-const MIN_L_ESTIMATE: f64 = 1e-10;
+pub(crate) const MIN_L_ESTIMATE: f64 = 1e-10;
 
 /// gamma = GAMMA_L_COEFF/L
-const GAMMA_L_COEFF: f64 = 0.95;
+pub(crate) const GAMMA_L_COEFF: f64 = 0.95;
 
 //const SIGMA_COEFF: f64 = 0.49;
 
 /// Delta in the estimation of the initial Lipschitz constant
-const DELTA_LIPSCHITZ: f64 = 1e-12;
+pub(crate) const DELTA_LIPSCHITZ: f64 = 1e-12;
 
 /// Epsilon in the estimation of the initial Lipschitz constant
-const EPSILON_LIPSCHITZ: f64 = 1e-6;
+pub(crate) const EPSILON_LIPSCHITZ: f64 = 1e-6;
 
 /// Safety parameter used to check a strict inequality in the update of the Lipschitz constant
-const LIPSCHITZ_UPDATE_EPSILON: f64 = 1e-6;
+pub(crate) const LIPSCHITZ_UPDATE_EPSILON: f64 = 1e-6;
 
 /// Maximum iterations of updating the Lipschitz constant
-const MAX_LIPSCHITZ_UPDATE_ITERATIONS: usize = 10;
+pub(crate) const MAX_LIPSCHITZ_UPDATE_ITERATIONS: usize = 10;
 
 /// Maximum possible Lipschitz constant
-const MAX_LIPSCHITZ_CONSTANT: f64 = 1e9;
+pub(crate) const MAX_LIPSCHITZ_CONSTANT: f64 = 1e9;
+
+/// Default maximum number of rejected candidates of the line search (see
+/// `PANOCOptimizer::with_max_backtracks`)
+pub(crate) const MAX_LINESEARCH_ITERATIONS: u32 = 10;
+
+/// Maximum trust-region radius
+const MAX_TRUST_REGION_RADIUS: f64 = 1e12;
+
+/// Safeguards of the interpolating line search: the next value of tau is in
+/// `[MIN_TAU_REDUCTION * tau, MAX_TAU_REDUCTION * tau]`
+const MIN_TAU_REDUCTION: f64 = 0.1;
+const MAX_TAU_REDUCTION: f64 = 0.5;
+
+/// Next value of tau in the interpolating line search
+///
+/// Let `psi(tau)` be the FBE at the candidate point minus the RHS of the line
+/// search condition, so that a candidate is accepted if `psi(tau) <= 0`.
+/// Given a model value `psi0` of `psi(0)` and the rejected candidates
+/// `(tau, psi(tau))` (most recent last), this returns the minimizer of the
+/// quadratic which interpolates `psi` at zero and at the last two rejected
+/// candidates, safeguarded to `[0.1 * tau, 0.5 * tau]`, where `tau` is the
+/// last rejected value. After the first rejected candidate (or if the values
+/// are not finite), tau is halved.
+pub(crate) fn interpolated_tau<T: OpEnFloat>(psi0: T, rejected: &[(T, T)]) -> T {
+    let (tau, psi) = rejected[rejected.len() - 1];
+    let lower = T::from_f64(MIN_TAU_REDUCTION) * tau;
+    let upper = T::from_f64(MAX_TAU_REDUCTION) * tau;
+    if !psi.is_finite() {
+        return lower;
+    }
+    if rejected.len() < 2 {
+        return upper;
+    }
+    let (tau_previous, psi_previous) = rejected[rejected.len() - 2];
+    if !(psi0.is_finite() && psi_previous.is_finite()) {
+        return upper;
+    }
 
-/// Maximum number of linesearch iterations
-const MAX_LINESEARCH_ITERATIONS: u32 = 10;
+    // psi(t) ≈ psi0 + c1 * t + c2 * t * (t - tau_previous) (Newton form)
+    let c1 = (psi_previous - psi0) / tau_previous;
+    let c2 = ((psi - psi0) / tau - c1) / (tau - tau_previous);
+    let interpolant = |t: T| psi0 + c1 * t + c2 * t * (t - tau_previous);
+    let next_tau = if c2 > T::zero() {
+        (T::from_f64(0.5) * (tau_previous - c1 / c2))
+            .max(lower)
+            .min(upper)
+    } else if interpolant(lower) < interpolant(upper) {
+        lower
+    } else {
+        upper
+    };
+    if next_tau.is_finite() {
+        next_tau
+    } else {
+        upper
+    }
+}
+
+/// Batched evaluation of the candidates of the line search (see
+/// `PANOCOptimizer::with_batch_line_search`)
+pub(crate) struct BatchLineSearch<'a, T> {
+    /// oracle which evaluates the candidates
+    pub(crate) oracle: &'a dyn BatchPrefetch<T>,
+    /// candidates of the line search, one after the other
+    pub(crate) candidates: Vec<T>,
+}
+
+/// Computes the candidate point of the line search,
+/// `u_plus ← u - (1-tau) * gamma_fpr - tau * dir`
+fn candidate_point<T: OpEnFloat>(
+    u: &[T],
+    gamma_fpr: &[T],
+    direction: &[T],
+    tau: T,
+    u_plus: &mut [T],
+) {
+    let temp_ = T::one() - tau;
+    u_plus
+        .iter_mut()
+        .zip(u.iter())
+        .zip(gamma_fpr.iter())
+        .zip(direction.iter())
+        .for_each(|(((u_plus_i, &u_i), &fpr_i), &dir_i)| {
+            *u_plus_i = u_i - temp_ * fpr_i - tau * dir_i;
+        });
+}
 
 /// Engine for PANOC algorithm
-pub struct PANOCEngine<'a, GradientType, ConstraintType, CostType>
+pub struct PANOCEngine<'a, GradientType, ConstraintType, CostType, T = f64>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
-    problem: Problem<'a, GradientType, ConstraintType, CostType>,
-    pub(crate) cache: &'a mut PANOCCache,
+    pub(crate) problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+    pub(crate) cache: MaybeOwnedMut<'a, PANOCCache<T>>,
+    /// Gauss-Newton directions, which replace the L-BFGS directions (if any)
+    pub(crate) gauss_newton: Option<GaussNewtonDirection<'a, T>>,
+    /// Manifold on which the decision variables live (if any), which
+    /// replaces the constraints of the problem
+    pub(crate) manifold: Option<&'a dyn Manifold<T>>,
+    /// Batched evaluation of the candidates of the line search (if any)
+    pub(crate) batch_line_search: Option<BatchLineSearch<'a, T>>,
+    /// Clock which measures the time of the phases of the solver (if phase
+    /// timing is activated)
+    pub(crate) phase_clock: Option<&'a dyn Clock>,
 }
 
-impl<'a, GradientType, ConstraintType, CostType>
-    PANOCEngine<'a, GradientType, ConstraintType, CostType>
+impl<'a, GradientType, ConstraintType, CostType, T>
+    PANOCEngine<'a, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
     /// Construct a new Engine for PANOC
     ///
-    /// The engine owns a problem specification borrows a mutable reference
-    /// to a `PANOCCache` object which is created externally once
+    /// The engine owns a problem specification and either borrows a mutable
+    /// reference to a `PANOCCache` object which is created externally once, or
+    /// owns the cache
     ///
     /// ## Arguments
     ///
@@ -60,46 +164,130 @@ where
     ///
     ///
     pub fn new(
-        problem: Problem<'a, GradientType, ConstraintType, CostType>,
-        cache: &'a mut PANOCCache,
-    ) -> PANOCEngine<'a, GradientType, ConstraintType, CostType> {
-        PANOCEngine { problem, cache }
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: &'a mut PANOCCache<T>,
+    ) -> PANOCEngine<'a, GradientType, ConstraintType, CostType, T> {
+        PANOCEngine {
+            problem,
+            cache: MaybeOwnedMut::Borrowed(cache),
+            gauss_newton: None,
+            manifold: None,
+            batch_line_search: None,
+            phase_clock: None,
+        }
+    }
+
+    /// Construct a new Engine for PANOC which owns its cache
+    pub(crate) fn new_owned(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: PANOCCache<T>,
+    ) -> PANOCEngine<'a, GradientType, ConstraintType, CostType, T> {
+        PANOCEngine {
+            problem,
+            cache: MaybeOwnedMut::Owned(cache),
+            gauss_newton: None,
+            manifold: None,
+            batch_line_search: None,
+            phase_clock: None,
+        }
+    }
+
+    /// Starts measuring the time of a phase (if phase timing is activated);
+    /// returns the current time and the total time of the phases so far
+    fn tic(&self) -> Option<(Duration, Duration)> {
+        self.phase_clock
+            .map(|clock| (clock.now(), self.cache.phase_times.total()))
+    }
+
+    /// Adds the time since `tic` to `phase`, excluding the time of the phases
+    /// which were measured in the meantime (e.g., the evaluations of the cost
+    /// during a line search)
+    fn toc(&mut self, phase: Phase, tic: Option<(Duration, Duration)>) {
+        if let (Some(clock), Some((start, total))) = (self.phase_clock, tic) {
+            let nested = self.cache.phase_times.total().saturating_sub(total);
+            let elapsed = clock.elapsed(start).saturating_sub(nested);
+            self.cache.phase_times.add(phase, elapsed);
+        }
+    }
+
+    /// Screens the given cost value for NaN/infinite values, if screening
+    /// is activated
+    fn screen_cost(&self, cost_value: T) -> FunctionCallResult {
+        if self.cache.nan_screening {
+            screen_output(
+                "PANOC",
+                "cost",
+                self.cache.iteration,
+                std::slice::from_ref(&cost_value),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Screens the gradient stored in `cache.gradient_u` for NaN/infinite
+    /// values, if screening is activated
+    fn screen_gradient(&self) -> FunctionCallResult {
+        if self.cache.nan_screening {
+            screen_output(
+                "PANOC",
+                "gradient",
+                self.cache.iteration,
+                &self.cache.gradient_u,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Projects the gradient stored in `cache.gradient_u` on the tangent
+    /// space at `u`, if the decision variables live on a manifold
+    fn project_gradient(&mut self, u: &[T]) {
+        if let Some(manifold) = self.manifold {
+            manifold.project_tangent(u, &mut self.cache.gradient_u);
+        }
     }
 
     /// Estimate the local Lipschitz constant at `u`
-    fn estimate_loc_lip(&mut self, u: &mut [f64]) -> FunctionCallResult {
-        let mut lipest = crate::lipschitz_estimator::LipschitzEstimator::new(
+    fn estimate_loc_lip(&mut self, u: &mut [T]) -> FunctionCallResult {
+        let tic = self.tic();
+        let cache = &mut *self.cache;
+        // the gradient step, which is computed after the estimation, is the
+        // workspace of the estimator
+        let mut lipest = crate::lipschitz_estimator::LipschitzEstimator::with_workspace(
             u,
-            &self.problem.gradf,
-            &mut self.cache.gradient_u,
+            &mut self.problem.gradf,
+            &mut cache.gradient_u,
+            &mut cache.gradient_step,
         )
-        .with_delta(DELTA_LIPSCHITZ)
-        .with_epsilon(EPSILON_LIPSCHITZ);
-        self.cache.lipschitz_constant = lipest.estimate_local_lipschitz()?;
+        .with_delta(T::from_f64(DELTA_LIPSCHITZ))?
+        .with_epsilon(T::from_f64(EPSILON_LIPSCHITZ))?;
+        cache.lipschitz_constant = lipest.estimate_local_lipschitz()?;
+        // the estimator evaluates the gradient at `u` and at a perturbed point
+        self.cache.oracle_calls.gradient += 2;
+        self.toc(Phase::Gradient, tic);
 
         Ok(())
     }
 
     /// Computes the FPR and its norm
-    fn compute_fpr(&mut self, u_current: &[f64]) {
+    fn compute_fpr(&mut self, u_current: &[T]) {
         // compute the FPR:
         // fpr ← u - u_half_step
-        let cache = &mut self.cache;
+        let cache = &mut *self.cache;
         cache
             .gamma_fpr
             .iter_mut()
             .zip(u_current.iter())
             .zip(cache.u_half_step.iter())
-            .for_each(|((fpr, u), uhalf)| *fpr = u - uhalf);
+            .for_each(|((fpr, &u), &uhalf)| *fpr = u - uhalf);
         // compute the norm of FPR
         cache.norm_gamma_fpr = matrix_operations::norm2(&cache.gamma_fpr);
     }
 
     /// Computes a gradient step; does not compute the gradient
-    fn gradient_step(&mut self, u_current: &[f64]) {
+    fn gradient_step(&mut self, u_current: &[T]) {
         // take a gradient step:
         // gradient_step ← u_current - gamma * gradient
-        let cache = &mut self.cache;
+        let cache = &mut *self.cache;
         let gamma = cache.gamma;
         cache
             .gradient_step
@@ -113,7 +301,7 @@ where
     fn gradient_step_uplus(&mut self) {
         // take a gradient step:
         // gradient_step ← u_plus - gamma * gradient
-        let cache = &mut self.cache;
+        let cache = &mut *self.cache;
         let gamma = cache.gamma;
         cache
             .gradient_step
@@ -125,15 +313,37 @@ where
 
     /// Computes a projection on `gradient_step`
     fn half_step(&mut self) {
-        let cache = &mut self.cache;
+        let tic = self.tic();
+        let cache = &mut *self.cache;
         // u_half_step ← projection(gradient_step)
         cache.u_half_step.copy_from_slice(&cache.gradient_step);
         self.problem.constraints.project(&mut cache.u_half_step);
+        cache.oracle_calls.projections += 1;
+        self.toc(Phase::Projection, tic);
+    }
+
+    /// Computes the forward step from `base`, that is, the projection on
+    /// `gradient_step` or, on a manifold, the retraction of the tangent
+    /// vector `-gamma * gradient` at `base`; updates `cache.u_half_step`
+    fn forward_step(&mut self, base: &[T]) {
+        match self.manifold {
+            Some(manifold) => {
+                let cache = &mut *self.cache;
+                let gamma = cache.gamma;
+                cache
+                    .tangent
+                    .iter_mut()
+                    .zip(cache.gradient_u.iter())
+                    .for_each(|(t, &g)| *t = -gamma * g);
+                manifold.retract(base, &cache.tangent, &mut cache.u_half_step);
+            }
+            None => self.half_step(),
+        }
     }
 
     /// Computes an LBFGS direction; updates `cache.direction_lbfgs`
-    fn lbfgs_direction(&mut self, u_current: &[f64]) {
-        let cache = &mut self.cache;
+    fn lbfgs_direction(&mut self, u_current: &[T]) {
+        let cache = &mut *self.cache;
         // update the LBFGS buffer
         cache.lbfgs.update_hessian(&cache.gamma_fpr, u_current);
 
@@ -145,10 +355,40 @@ where
         }
     }
 
+    /// Computes an L-SR1 direction; updates `cache.direction_lbfgs`
+    fn sr1_direction(&mut self, u_current: &[T]) {
+        let cache = &mut *self.cache;
+        if let Some(sr1) = &mut cache.sr1 {
+            sr1.update_hessian(&cache.gamma_fpr, u_current);
+            if cache.iteration > 0 {
+                cache.direction_lbfgs.copy_from_slice(&cache.gamma_fpr);
+                sr1.apply_hessian(&mut cache.direction_lbfgs);
+                if cache.trust_region.is_none() {
+                    sr1.limit_step(&mut cache.direction_lbfgs, &cache.gamma_fpr);
+                }
+            }
+        }
+    }
+
+    /// Computes a Gauss-Newton direction; updates `cache.direction_lbfgs`
+    fn gauss_newton_direction(&mut self, u_current: &[T]) -> FunctionCallResult {
+        let cache = &mut *self.cache;
+        if let Some(gauss_newton) = self.gauss_newton.as_mut().filter(|_| cache.iteration > 0) {
+            gauss_newton.compute(
+                u_current,
+                cache.gamma,
+                &cache.gradient_step,
+                &cache.u_half_step,
+                &mut cache.direction_lbfgs,
+            )?;
+        }
+        Ok(())
+    }
+
     /// Returns the RHS of the Lipschitz update
     /// Computes rhs = cost + LIP_EPS * |f| - gamma * <gradfx, fpr> + (L/2/gamma) ||gamma * fpr||^2
-    fn lipschitz_check_rhs(&mut self) -> f64 {
-        let cache = &mut self.cache;
+    fn lipschitz_check_rhs(&mut self) -> T {
+        let cache = &mut *self.cache;
         let gamma = cache.gamma;
         let cost_value = cache.cost_value;
         // inner_prod_grad_fpr ← <gradfx, gamma_fpr>
@@ -156,69 +396,121 @@ where
             matrix_operations::inner_product(&cache.gradient_u, &cache.gamma_fpr);
 
         // rhs ← cost + LIP_EPS * |f| - <gradfx, gamma_fpr> + (L/2/gamma) ||gamma_fpr||^2
-        cost_value + LIPSCHITZ_UPDATE_EPSILON * cost_value.abs() - inner_prod_grad_fpr
-            + (GAMMA_L_COEFF / (2.0 * gamma)) * (cache.norm_gamma_fpr.powi(2))
+        cost_value + T::from_f64(LIPSCHITZ_UPDATE_EPSILON) * cost_value.abs() - inner_prod_grad_fpr
+            + (T::from_f64(GAMMA_L_COEFF) / (T::from_f64(2.0) * gamma))
+                * (cache.norm_gamma_fpr.powi(2))
     }
 
     /// Updates the estimate of the Lipscthiz constant
-    fn update_lipschitz_constant(&mut self, u_current: &[f64]) -> FunctionCallResult {
-        let mut cost_u_half_step = 0.0;
+    fn update_lipschitz_constant(&mut self, u_current: &[T]) -> FunctionCallResult {
+        let mut cost_u_half_step = T::zero();
 
         // Compute the cost at the half step
+        let tic = self.tic();
+        self.cache.oracle_calls.cost += 1;
         (self.problem.cost)(&self.cache.u_half_step, &mut cost_u_half_step)?;
+        self.screen_cost(cost_u_half_step)?;
 
         // Compute the cost at u_current (save it in `cache.cost_value`)
+        self.cache.oracle_calls.cost += 1;
         (self.problem.cost)(u_current, &mut self.cache.cost_value)?;
+        self.screen_cost(self.cache.cost_value)?;
+        self.toc(Phase::Cost, tic);
 
         let mut it_lipschitz_search = 0;
 
         while cost_u_half_step > self.lipschitz_check_rhs()
             && it_lipschitz_search < MAX_LIPSCHITZ_UPDATE_ITERATIONS
-            && self.cache.lipschitz_constant < MAX_LIPSCHITZ_CONSTANT
+            && self.cache.lipschitz_constant < T::from_f64(MAX_LIPSCHITZ_CONSTANT)
         {
             self.cache.lbfgs.reset(); // invalidate the L-BFGS buffer
+            if let Some(sr1) = &mut self.cache.sr1 {
+                sr1.reset();
+            }
 
             // update L, sigma and gamma...
-            self.cache.lipschitz_constant *= 2.;
-            self.cache.gamma /= 2.;
+            self.cache.lipschitz_constant *= T::from_f64(2.0);
+            self.cache.gamma /= T::from_f64(2.0);
+            trace_event!(
+                lipschitz_constant = self.cache.lipschitz_constant.as_f64(),
+                gamma = self.cache.gamma.as_f64(),
+                "PANOC step size reduced"
+            );
 
             // recompute the half step...
             self.gradient_step(u_current); // updates self.cache.gradient_step
-            self.half_step(); // updates self.cache.u_half_step
+            self.forward_step(u_current); // updates self.cache.u_half_step
 
             // recompute the cost at the half step
             // update `cost_u_half_step`
+            let tic = self.tic();
+            self.cache.oracle_calls.cost += 1;
             (self.problem.cost)(&self.cache.u_half_step, &mut cost_u_half_step)?;
+            self.screen_cost(cost_u_half_step)?;
+            self.toc(Phase::Cost, tic);
 
             // recompute the FPR and the square of its norm
             self.compute_fpr(u_current);
             it_lipschitz_search += 1;
         }
-        self.cache.sigma = (1.0 - GAMMA_L_COEFF) / (4.0 * self.cache.gamma);
+        self.cache.cost_half_step = cost_u_half_step;
+        self.cache.sigma = T::from_f64(1.0 - GAMMA_L_COEFF) / (T::from_f64(4.0) * self.cache.gamma);
 
         Ok(())
     }
 
     /// Computes u_plus ← u - gamma * (1-tau) * fpr - tau * dir,
-    fn compute_u_plus(&mut self, u: &[f64]) {
-        let cache = &mut self.cache;
-        let _gamma = cache.gamma;
-        let tau = cache.tau;
-        let temp_ = 1.0 - tau;
-        cache
-            .u_plus
-            .iter_mut()
-            .zip(u.iter())
-            .zip(cache.gamma_fpr.iter())
-            .zip(cache.direction_lbfgs.iter())
-            .for_each(|(((u_plus_i, &u_i), &fpr_i), &dir_i)| {
-                *u_plus_i = u_i - temp_ * fpr_i - tau * dir_i;
-            });
+    ///
+    /// On a manifold, the step is projected on the tangent space at `u` and
+    /// retracted
+    fn compute_u_plus(&mut self, u: &[T]) {
+        if let Some(manifold) = self.manifold {
+            let cache = &mut *self.cache;
+            let tau = cache.tau;
+            let temp_ = T::one() - tau;
+            cache
+                .tangent
+                .iter_mut()
+                .zip(cache.gamma_fpr.iter())
+                .zip(cache.direction_lbfgs.iter())
+                .for_each(|((t, &fpr_i), &dir_i)| *t = -temp_ * fpr_i - tau * dir_i);
+            manifold.project_tangent(u, &mut cache.tangent);
+            manifold.retract(u, &cache.tangent, &mut cache.u_plus);
+            return;
+        }
+        let cache = &mut *self.cache;
+        candidate_point(
+            u,
+            &cache.gamma_fpr,
+            &cache.direction_lbfgs,
+            cache.tau,
+            &mut cache.u_plus,
+        );
+    }
+
+    /// Evaluates the first candidates of the line search (of the schedule
+    /// of the line search, e.g., `tau = 1, 1/2, 1/4, ...`), in a single batch
+    /// (if batched line searches are activated; not on manifolds)
+    fn prefetch_candidates(&mut self, u: &[T]) -> FunctionCallResult {
+        if self.manifold.is_some() {
+            return Ok(());
+        }
+        if let Some(batch) = &mut self.batch_line_search {
+            let cache = &*self.cache;
+            let n = u.len();
+            let mut tau = cache.line_search.initial_tau;
+            for candidate in batch.candidates.chunks_exact_mut(n) {
+                candidate_point(u, &cache.gamma_fpr, &cache.direction_lbfgs, tau, candidate);
+                tau *= cache.line_search.shrink_factor;
+            }
+            batch.oracle.prefetch(&batch.candidates, n)?;
+        }
+        Ok(())
     }
 
     /// Computes the RHS of the linesearch condition
     fn compute_rhs_ls(&mut self) {
-        let cache = &mut self.cache;
+        let cache = &mut *self.cache;
 
         // dist squared ← norm(gradient step - u half step)^2
         let dist_squared =
@@ -227,16 +519,17 @@ where
         // rhs_ls ← f - (gamma/2) * norm(gradf)^2
         //            + 0.5 * dist squared / gamma
         //            - sigma * norm_gamma_fpr^2
+        let half = T::from_f64(0.5);
         let fbe = cache.cost_value
-            - 0.5 * cache.gamma * matrix_operations::norm2_squared(&cache.gradient_u)
-            + 0.5 * dist_squared / cache.gamma;
+            - half * cache.gamma * matrix_operations::norm2_squared(&cache.gradient_u)
+            + half * dist_squared / cache.gamma;
         let sigma_fpr_sq = cache.sigma * cache.norm_gamma_fpr.powi(2);
         cache.rhs_ls = fbe - sigma_fpr_sq;
     }
 
     /// Computes the left hand side of the line search condition and compares it with the RHS;
     /// returns `true` if and only if lhs > rhs (when the line search should continue)
-    fn line_search_condition(&mut self, u: &[f64]) -> Result<bool, SolverError> {
+    fn line_search_condition(&mut self, u: &[T]) -> Result<bool, SolverError> {
         let gamma = self.cache.gamma;
 
         // u_plus ← u - (1-tau)*gamma_fpr + tau*direction
@@ -245,11 +538,28 @@ where
         // Note: Here `cache.cost_value` and `cache.gradient_u` are overwritten
         // with the values of the cost and its gradient at the next (candidate)
         // point `u_plus`
-        (self.problem.cost)(&self.cache.u_plus, &mut self.cache.cost_value)?;
-        (self.problem.gradf)(&self.cache.u_plus, &mut self.cache.gradient_u)?;
-
+        let tic = self.tic();
+        let cache = &mut *self.cache;
+        cache.oracle_calls.cost += 1;
+        (self.problem.cost)(&cache.u_plus, &mut cache.cost_value)?;
+        self.screen_cost(self.cache.cost_value)?;
+        self.toc(Phase::Cost, tic);
+        let tic = self.tic();
+        let cache = &mut *self.cache;
+        cache.oracle_calls.gradient += 1;
+        (self.problem.gradf)(&cache.u_plus, &mut cache.gradient_u)?;
+        self.screen_gradient()?;
+        self.toc(Phase::Gradient, tic);
+
+        // `u_plus` is moved out of the cache while it is borrowed as the base
+        // of the forward step
+        let u_plus = std::mem::take(&mut self.cache.u_plus);
+        self.project_gradient(&u_plus);
+        self.cache.u_plus = u_plus;
         self.gradient_step_uplus(); // gradient_step ← u_plus - gamma * gradient_u
-        self.half_step(); // u_half_step ← project(gradient_step)
+        let u_plus = std::mem::take(&mut self.cache.u_plus);
+        self.forward_step(&u_plus); // u_half_step ← project(gradient_step)
+        self.cache.u_plus = u_plus;
 
         // Compute: dist_squared ← norm(gradient_step - u_half_step)^2
         let dist_squared = matrix_operations::norm2_squared_diff(
@@ -258,52 +568,156 @@ where
         );
 
         // Update the LHS of the line search condition
+        let half = T::from_f64(0.5);
         self.cache.lhs_ls = self.cache.cost_value
-            - 0.5 * gamma * matrix_operations::norm2_squared(&self.cache.gradient_u)
-            + 0.5 * dist_squared / self.cache.gamma;
+            - half * gamma * matrix_operations::norm2_squared(&self.cache.gradient_u)
+            + half * dist_squared / self.cache.gamma;
 
         Ok(self.cache.lhs_ls > self.cache.rhs_ls)
     }
 
     /// Update without performing a line search; this is executed at the first iteration
-    fn update_no_linesearch(&mut self, u_current: &mut [f64]) -> FunctionCallResult {
+    fn update_no_linesearch(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        self.cache.tau = T::zero(); // forward-backward step
         u_current.copy_from_slice(&self.cache.u_half_step); // set u_current ← u_half_step
+        let tic = self.tic();
+        self.cache.oracle_calls.cost += 1;
         (self.problem.cost)(u_current, &mut self.cache.cost_value)?; // cost value
+        self.screen_cost(self.cache.cost_value)?;
+        self.toc(Phase::Cost, tic);
+        let tic = self.tic();
+        self.cache.oracle_calls.gradient += 1;
         (self.problem.gradf)(u_current, &mut self.cache.gradient_u)?; // compute gradient
+        self.screen_gradient()?;
+        self.toc(Phase::Gradient, tic);
+        self.project_gradient(u_current);
         self.gradient_step(u_current); // updated self.cache.gradient_step
-        self.half_step(); // updates self.cache.u_half_step
+        self.forward_step(u_current); // updates self.cache.u_half_step
 
         Ok(())
     }
 
     /// Performs a line search to select tau
-    fn linesearch(&mut self, u_current: &mut [f64]) -> FunctionCallResult {
+    ///
+    /// The candidates follow the schedule of the line search (by default,
+    /// `tau = 1, 1/2, ..., 1/1024`); if all of them are rejected, or if tau
+    /// would drop below the minimum admissible tau, the forward-backward step
+    /// (tau = 0) is taken
+    fn linesearch(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        let schedule = self.cache.line_search;
         // perform line search
         self.compute_rhs_ls(); // compute the right hand side of the line search
-        self.cache.tau = 1.0; // initialise tau ← 1.0
+        self.cache.tau = schedule.initial_tau; // initialise tau (by default, 1.0)
+        self.prefetch_candidates(u_current)?;
+        // the prefetched candidates are those of the schedule
+        let interpolate = self.cache.interpolating_line_search && self.batch_line_search.is_none();
+        // model of the FBE at tau = 0 minus the RHS: at the forward-backward
+        // step, the FBE is at most the cost (the step is feasible), and the
+        // condition holds with a margin of sigma * |gamma * fpr|^2
+        let psi0 = (self.cache.cost_half_step - self.cache.rhs_ls)
+            .min(-self.cache.sigma * self.cache.norm_gamma_fpr.powi(2));
+        // the last two rejected candidates (most recent last)
+        let mut rejected = [(T::zero(), T::zero()); 2];
         let mut num_ls_iters = 0;
-        while self.line_search_condition(u_current)? && num_ls_iters < MAX_LINESEARCH_ITERATIONS {
-            self.cache.tau /= 2.0;
+        let mut failed = false;
+        while self.line_search_condition(u_current)? {
+            if num_ls_iters == schedule.max_backtracks {
+                failed = true;
+                break;
+            }
+            rejected = [
+                rejected[1],
+                (self.cache.tau, self.cache.lhs_ls - self.cache.rhs_ls),
+            ];
             num_ls_iters += 1;
+            self.cache.tau = if interpolate {
+                interpolated_tau(psi0, &rejected[2 - num_ls_iters.min(2)..])
+            } else {
+                self.cache.tau * schedule.shrink_factor
+            };
+            if self.cache.tau < schedule.min_tau {
+                failed = true;
+                break;
+            }
+            trace_event!(
+                retry = num_ls_iters,
+                tau = self.cache.tau.as_f64(),
+                "PANOC line search retry"
+            );
         }
-        if num_ls_iters == MAX_LINESEARCH_ITERATIONS {
-            self.cache.tau = 0.;
-            u_current.copy_from_slice(&self.cache.u_half_step);
+        if failed {
+            debug_event!(
+                retries = num_ls_iters,
+                "PANOC line search failed; taking the projected gradient step"
+            );
+            // evaluates the cost and its gradient at the forward-backward step
+            self.cache.tau = T::zero();
+            self.line_search_condition(u_current)?;
         }
         // Sets `u_current` to `u_plus` (u_current ← u_plus)
         u_current.copy_from_slice(&self.cache.u_plus);
 
         Ok(())
     }
+
+    /// Trust-region variant of the line search
+    ///
+    /// The candidate point is `u - (1-tau)*gamma_fpr - tau*direction`, where
+    /// `tau = min(1, radius/|direction - gamma_fpr|)`, that is, the distance of
+    /// the candidate from the forward-backward step is at most the radius. If
+    /// the candidate satisfies the condition of the line search, it is
+    /// accepted and the radius is doubled if the candidate is on the boundary
+    /// of the trust region; otherwise, the radius is divided by four and the
+    /// forward-backward step (tau = 0) is taken, so at most two candidates are
+    /// evaluated per iteration.
+    fn trust_region_step(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        let radius = match self.cache.trust_region {
+            Some(trust_region) => trust_region.radius,
+            None => return self.linesearch(u_current),
+        };
+        self.compute_rhs_ls();
+        let deviation = matrix_operations::norm2_squared_diff(
+            &self.cache.direction_lbfgs,
+            &self.cache.gamma_fpr,
+        )
+        .sqrt();
+        let on_boundary = deviation > radius;
+        self.cache.tau = if on_boundary {
+            radius / deviation
+        } else {
+            T::one()
+        };
+        let rejected = self.line_search_condition(u_current)? || !self.cache.lhs_ls.is_finite();
+        if rejected {
+            trace_event!(
+                radius = radius.as_f64(),
+                "PANOC trust region shrunk; taking the forward-backward step"
+            );
+            self.cache.tau = T::zero();
+            self.line_search_condition(u_current)?;
+        }
+        if let Some(trust_region) = &mut self.cache.trust_region {
+            if rejected {
+                trust_region.radius = radius.min(deviation) / T::from_f64(4.0);
+            } else if on_boundary {
+                trust_region.radius =
+                    (T::from_f64(2.0) * radius).min(T::from_f64(MAX_TRUST_REGION_RADIUS));
+            }
+        }
+        u_current.copy_from_slice(&self.cache.u_plus);
+
+        Ok(())
+    }
 }
 
 /// Implementation of the `step` and `init` methods of [trait.AlgorithmEngine.html]
-impl<'a, GradientType, ConstraintType, CostType> AlgorithmEngine
-    for PANOCEngine<'a, GradientType, ConstraintType, CostType>
+impl<'a, GradientType, ConstraintType, CostType, T> AlgorithmEngine<T>
+    for PANOCEngine<'a, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
     /// PANOC step
     ///
@@ -315,7 +729,7 @@ where
     ///   iterate of PANOC
     ///
     ///
-    fn step(&mut self, u_current: &mut [f64]) -> Result<bool, SolverError> {
+    fn step(&mut self, u_current: &mut [T]) -> Result<bool, SolverError> {
         // caches the previous gradient vector (copies df to df_previous)
         self.cache.cache_previous_gradient();
 
@@ -323,17 +737,37 @@ where
         self.compute_fpr(u_current);
 
         // exit if the exit conditions are satisfied (||gamma*fpr|| < eps and,
-        // if activated, ||gamma*r + df - df_prev|| < eps_akkt)
-        if self.cache.exit_condition() {
+        // if activated, ||gamma*r + df - df_prev|| < eps_akkt), or if the
+        // change of the iterate is within the step tolerance (if activated)
+        if self.cache.exit_condition() || self.cache.step_exit_condition(u_current) {
             return Ok(false);
         }
+        self.cache.cache_previous_iterate(u_current);
         self.update_lipschitz_constant(u_current)?; // update lipschitz constant
-        self.lbfgs_direction(u_current); // compute LBFGS direction (update LBFGS buffer)
+        let tic = self.tic();
+        if self.gauss_newton.is_some() {
+            self.gauss_newton_direction(u_current)?;
+        } else if self.cache.sr1.is_some() {
+            self.sr1_direction(u_current);
+        } else {
+            self.lbfgs_direction(u_current); // compute LBFGS direction (update LBFGS buffer)
+        }
+        self.toc(Phase::Direction, tic);
         if self.cache.iteration == 0 {
             // first iteration, no line search is performed
             self.update_no_linesearch(u_current)?;
+        } else if self.cache.trust_region.is_some() {
+            let tic = self.tic();
+            self.trust_region_step(u_current)?;
+            self.toc(Phase::LineSearch, tic);
         } else {
+            let tic = self.tic();
             self.linesearch(u_current)?;
+            self.toc(Phase::LineSearch, tic);
+            let tau = self.cache.tau;
+            if let Some(sr1) = &mut self.cache.sr1 {
+                sr1.adapt_radius(tau);
+            }
         }
 
         self.cache.iteration += 1;
@@ -349,14 +783,49 @@ where
     /// gradient of the cost at the initial point, initial estimates for `gamma` and `sigma`,
     /// a gradient step and a half step (projected gradient step)
     ///
-    fn init(&mut self, u_current: &mut [f64]) -> FunctionCallResult {
+    fn init(&mut self, u_current: &mut [T]) -> FunctionCallResult {
         self.cache.reset();
+        // after a restored checkpoint, the step size and the L-BFGS memory
+        // of the checkpoint are used instead of a new estimate
+        let resumed = self.cache.resume();
+        let tic = self.tic();
+        self.cache.oracle_calls.cost += 1;
         (self.problem.cost)(u_current, &mut self.cache.cost_value)?; // cost value
-        self.estimate_loc_lip(u_current)?; // computes the gradient as well! (self.cache.gradient_u)
-        self.cache.gamma = GAMMA_L_COEFF / f64::max(self.cache.lipschitz_constant, MIN_L_ESTIMATE);
-        self.cache.sigma = (1.0 - GAMMA_L_COEFF) / (4.0 * self.cache.gamma);
+        self.screen_cost(self.cache.cost_value)?;
+        self.toc(Phase::Cost, tic);
+        let known_lipschitz_constant = self.cache.known_lipschitz_constant.filter(|_| !resumed);
+        if resumed || known_lipschitz_constant.is_some() {
+            let tic = self.tic();
+            self.cache.oracle_calls.gradient += 1;
+            (self.problem.gradf)(u_current, &mut self.cache.gradient_u)?;
+            self.toc(Phase::Gradient, tic);
+            if let Some(lipschitz_constant) = known_lipschitz_constant {
+                self.cache.lipschitz_constant = lipschitz_constant;
+            }
+        } else {
+            // the estimator perturbs `u_current`, which is restored on manifolds
+            // (on which the perturbed point need not lie)
+            if self.manifold.is_some() {
+                self.cache.tangent.copy_from_slice(u_current);
+            }
+            self.estimate_loc_lip(u_current)?; // computes the gradient as well! (self.cache.gradient_u)
+            if self.manifold.is_some() {
+                u_current.copy_from_slice(&self.cache.tangent);
+            }
+        }
+        self.screen_gradient()?;
+        self.project_gradient(u_current);
+        if !resumed {
+            self.cache.gamma = T::from_f64(GAMMA_L_COEFF)
+                / self
+                    .cache
+                    .lipschitz_constant
+                    .max(T::from_f64(MIN_L_ESTIMATE));
+            self.cache.sigma =
+                T::from_f64(1.0 - GAMMA_L_COEFF) / (T::from_f64(4.0) * self.cache.gamma);
+        }
         self.gradient_step(u_current); // updated self.cache.gradient_step
-        self.half_step(); // updates self.cache.u_half_step
+        self.forward_step(u_current); // updates self.cache.u_half_step
 
         Ok(())
     }
@@ -380,7 +849,7 @@ mod tests {
         let mem = 5;
         let box_constraints = constraints::NoConstraints::new();
         let problem = Problem::new(&box_constraints, mocks::my_gradient, mocks::my_cost);
-        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem);
+        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem).unwrap();
         let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
         let u = [0.75, -1.4];
@@ -409,7 +878,7 @@ mod tests {
         let mem = 5;
         let bounds = constraints::NoConstraints::new();
         let problem = Problem::new(&bounds, mocks::void_gradient, mocks::void_cost);
-        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem);
+        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem).unwrap();
         let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
         let u = [-0.11, -1.35];
@@ -432,7 +901,7 @@ mod tests {
         let mem = 5;
         let bounds = constraints::NoConstraints::new();
         let problem = Problem::new(&bounds, mocks::void_gradient, mocks::void_cost);
-        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem);
+        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem).unwrap();
         let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
         panoc_engine.cache.gamma = 0.789;
@@ -456,9 +925,9 @@ mod tests {
     fn t_half_step() {
         let n = 2;
         let mem = 5;
-        let bounds = constraints::Ball2::new(None, 0.5);
+        let bounds = constraints::Ball2::new(None, 0.5).unwrap();
         let problem = Problem::new(&bounds, mocks::void_gradient, mocks::void_cost);
-        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem);
+        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem).unwrap();
         let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
         panoc_engine
@@ -483,7 +952,7 @@ mod tests {
         let mem = 5;
         let bounds = constraints::NoConstraints::new();
         let problem = Problem::new(&bounds, mocks::void_gradient, mocks::void_cost);
-        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem);
+        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem).unwrap();
         let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
         // u = [-0.13, -0.12, -0.10];
@@ -521,9 +990,9 @@ mod tests {
     fn t_compute_rhs_ls() {
         let n = 2;
         let mem = 5;
-        let bounds = constraints::Ball2::new(None, 0.5);
+        let bounds = constraints::Ball2::new(None, 0.5).unwrap();
         let problem = Problem::new(&bounds, mocks::void_gradient, mocks::void_cost);
-        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem);
+        let mut panoc_cache = PANOCCache::new(n, 1e-6, mem).unwrap();
         let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
         panoc_engine
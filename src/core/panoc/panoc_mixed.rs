@@ -0,0 +1,344 @@
+//! Mixed-precision PANOC
+//!
+//! [`PANOCOptimizerMixed`] runs the bulk of the iterations of PANOC in single
+//! precision (`f32`), which halves the memory traffic of every iteration of
+//! a large problem (iterates, gradients and L-BFGS buffer), and switches to
+//! double precision (`f64`) once the fixed-point residual drops below a
+//! threshold, so that the final accuracy is that of an `f64` solve. The
+//! double-precision optimizer starts from the single-precision iterate and
+//! typically needs a few iterations only.
+//!
+//! Both phases are ordinary [`PANOCOptimizer`]s, one for the problem in
+//! `f32` and one for the problem in `f64` (the cost, its gradient and the
+//! constraints are written for both scalar types, e.g., with a generic
+//! function), so they are configured with their own methods, e.g., the
+//! maximum numbers of iterations and the (final) tolerance.
+//!
+//! [`PANOCOptimizerMixed`]: struct.PANOCOptimizerMixed.html
+//! [`PANOCOptimizer`]: struct.PANOCOptimizer.html
+use crate::{
+    constraints,
+    core::{panoc::PANOCOptimizer, Optimizer, SolverStatus},
+    FunctionCallResult, SolverError,
+};
+
+/// Mixed-precision PANOC optimizer: single-precision iterations followed by
+/// a double-precision refinement
+///
+/// The switching threshold is the tolerance of the single-precision
+/// optimizer (see `with_switch_tolerance`): its iterations stop once the norm
+/// of its fixed-point residual is below the threshold (or when it reaches
+/// its own limits of iterations or time), and the double-precision optimizer
+/// refines the solution until its tolerance is met. If the single-precision
+/// phase fails (e.g., because a value overflows in `f32`), the refinement
+/// starts from the initial guess instead.
+///
+/// The status of a solve reports the total number of iterations and the
+/// total time of both phases, the oracle calls and warnings of both phases
+/// (and their concatenated histories, if recorded), and the exit status,
+/// residual and cost of the refinement; the status of the single-precision
+/// phase is returned by `low_precision_status`.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{constraints::Ball2, panoc::*, *};
+///
+/// fn gradient<T: OpEnFloat>(u: &[T], grad: &mut [T]) -> FunctionCallResult {
+///     for (i, (g, &x)) in grad.iter_mut().zip(u.iter()).enumerate() {
+///         *g = T::from_f64(i as f64 + 1.0) * (x - T::one());
+///     }
+///     Ok(())
+/// }
+///
+/// fn cost<T: OpEnFloat>(u: &[T], c: &mut T) -> FunctionCallResult {
+///     *c = T::zero();
+///     for (i, &x) in u.iter().enumerate() {
+///         *c += T::from_f64(0.5 * (i as f64 + 1.0)) * (x - T::one()).powi(2);
+///     }
+///     Ok(())
+/// }
+///
+/// let n = 100;
+/// let (ball_f32, ball_f64) = (Ball2::new(None, 5.0_f32)?, Ball2::new(None, 5.0)?);
+/// let low_precision = PANOCOptimizer::from_dimensions(
+///     Problem::new(&ball_f32, gradient::<f32>, cost::<f32>),
+///     n,
+///     1e-3,
+///     5,
+/// )?;
+/// let refinement = PANOCOptimizer::from_dimensions(
+///     Problem::new(&ball_f64, gradient::<f64>, cost::<f64>),
+///     n,
+///     1e-10,
+///     5,
+/// )?;
+/// let mut optimizer = PANOCOptimizerMixed::new(low_precision, refinement)?;
+/// let mut u = vec![0.0; n];
+/// let status = optimizer.solve(&mut u)?;
+/// assert!(status.has_converged() && status.norm_fpr() < 1e-10);
+/// # Ok::<(), SolverError>(())
+/// ```
+pub struct PANOCOptimizerMixed<'a, G32, C32, F32, G64, C64, F64>
+where
+    G32: FnMut(&[f32], &mut [f32]) -> FunctionCallResult,
+    F32: FnMut(&[f32], &mut f32) -> FunctionCallResult,
+    C32: constraints::Constraint<f32>,
+    G64: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    F64: FnMut(&[f64], &mut f64) -> FunctionCallResult,
+    C64: constraints::Constraint<f64>,
+{
+    low_precision: PANOCOptimizer<'a, G32, C32, F32, f32>,
+    refinement: PANOCOptimizer<'a, G64, C64, F64, f64>,
+    /// iterate of the single-precision phase
+    u_low: Vec<f32>,
+    /// status of the single-precision phase of the last solve
+    low_precision_status: Option<SolverStatus>,
+}
+
+impl<'a, G32, C32, F32, G64, C64, F64> PANOCOptimizerMixed<'a, G32, C32, F32, G64, C64, F64>
+where
+    G32: FnMut(&[f32], &mut [f32]) -> FunctionCallResult,
+    F32: FnMut(&[f32], &mut f32) -> FunctionCallResult,
+    C32: constraints::Constraint<f32>,
+    G64: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    F64: FnMut(&[f64], &mut f64) -> FunctionCallResult,
+    C64: constraints::Constraint<f64>,
+{
+    /// Constructs a mixed-precision optimizer
+    ///
+    /// ## Arguments
+    ///
+    /// - `low_precision`: optimizer of the problem in `f32`, whose tolerance
+    ///   is the threshold of the switch to `f64`
+    /// - `refinement`: optimizer of the problem in `f64`, whose tolerance is
+    ///   the tolerance of the solution
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the two optimizers solve
+    /// problems of different dimensions
+    ///
+    pub fn new(
+        low_precision: PANOCOptimizer<'a, G32, C32, F32, f32>,
+        refinement: PANOCOptimizer<'a, G64, C64, F64, f64>,
+    ) -> Result<Self, SolverError> {
+        let n = refinement.dimension();
+        if low_precision.dimension() != n {
+            return Err(SolverError::DimensionMismatch {
+                name: "low_precision",
+                expected: n,
+                actual: low_precision.dimension(),
+            });
+        }
+        Ok(PANOCOptimizerMixed {
+            low_precision,
+            refinement,
+            u_low: vec![0.0; n],
+            low_precision_status: None,
+        })
+    }
+
+    /// Sets the threshold of the norm of the fixed-point residual below which
+    /// the iterations switch to double precision (the tolerance of the
+    /// single-precision optimizer)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the threshold is not
+    /// positive
+    ///
+    pub fn with_switch_tolerance(mut self, switch_tolerance: f32) -> Result<Self, SolverError> {
+        self.low_precision = self.low_precision.with_tolerance(switch_tolerance)?;
+        Ok(self)
+    }
+
+    /// Status of the single-precision phase of the last solve (`None` before
+    /// the first solve or if that phase failed)
+    pub fn low_precision_status(&self) -> Option<&SolverStatus> {
+        self.low_precision_status.as_ref()
+    }
+}
+
+/// Concatenates the histories of the two phases (if both are recorded)
+fn concat_history(low: Option<&[f64]>, refinement: Option<&[f64]>) -> Option<Vec<f64>> {
+    Some([low?, refinement?].concat())
+}
+
+/// Status of a mixed-precision solve, from the statuses of its phases
+fn combine(low: Option<&SolverStatus>, refinement: SolverStatus) -> SolverStatus {
+    let low = match low {
+        Some(low) => low,
+        None => return refinement,
+    };
+    let mut oracle_calls = low.oracle_calls();
+    oracle_calls += refinement.oracle_calls();
+    let warnings = [low.warnings(), refinement.warnings()].concat();
+    let mut status = SolverStatus::new(
+        refinement.exit_status(),
+        low.iterations() + refinement.iterations(),
+        low.solve_time() + refinement.solve_time(),
+        refinement.norm_fpr(),
+        refinement.cost_value(),
+    )
+    .with_oracle_calls(oracle_calls)
+    .with_warnings(warnings);
+    if let Some(gradient_norm) = refinement.gradient_norm() {
+        status = status.with_gradient_norm(gradient_norm);
+    }
+    if let Some(active_set) = refinement.active_set() {
+        status = status.with_active_set(active_set.clone());
+    }
+    let cost_history = concat_history(low.cost_history(), refinement.cost_history());
+    let fpr_history = concat_history(low.fpr_history(), refinement.fpr_history());
+    if let (Some(cost_history), Some(fpr_history)) = (cost_history, fpr_history) {
+        status = status.with_history(cost_history, fpr_history);
+    }
+    if let Some(gamma_history) = concat_history(low.gamma_history(), refinement.gamma_history()) {
+        status = status.with_gamma_history(gamma_history);
+    }
+    let direction_norm_history = concat_history(
+        low.direction_norm_history(),
+        refinement.direction_norm_history(),
+    );
+    let tau_history = concat_history(low.tau_history(), refinement.tau_history());
+    if let (Some(direction_norm_history), Some(tau_history)) = (direction_norm_history, tau_history)
+    {
+        status = status.with_direction_history(direction_norm_history, tau_history);
+    }
+    status
+}
+
+impl<'life, G32, C32, F32, G64, C64, F64> Optimizer<f64>
+    for PANOCOptimizerMixed<'life, G32, C32, F32, G64, C64, F64>
+where
+    G32: FnMut(&[f32], &mut [f32]) -> FunctionCallResult + 'life,
+    F32: FnMut(&[f32], &mut f32) -> FunctionCallResult,
+    C32: constraints::Constraint<f32> + 'life,
+    G64: FnMut(&[f64], &mut [f64]) -> FunctionCallResult + 'life,
+    F64: FnMut(&[f64], &mut f64) -> FunctionCallResult,
+    C64: constraints::Constraint<f64> + 'life,
+{
+    fn solve(&mut self, u: &mut [f64]) -> Result<SolverStatus, SolverError> {
+        if u.len() != self.u_low.len() {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: self.u_low.len(),
+                actual: u.len(),
+            });
+        }
+        self.u_low
+            .iter_mut()
+            .zip(u.iter())
+            .for_each(|(u_low, &u)| *u_low = u as f32);
+        self.low_precision_status = self.low_precision.solve(&mut self.u_low).ok();
+        if self.low_precision_status.is_some() {
+            u.iter_mut()
+                .zip(self.u_low.iter())
+                .for_each(|(u, &u_low)| *u = f64::from(u_low));
+        }
+        let refinement = self.refinement.solve(u)?;
+        Ok(combine(self.low_precision_status.as_ref(), refinement))
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::*, core::Problem, OpEnFloat};
+
+    /// Gradient of 0.5 sum_i (i + 1) (u_i - 1)^2 + 0.25 sum_i u_i^4
+    fn gradient<T: OpEnFloat>(u: &[T], grad: &mut [T]) -> FunctionCallResult {
+        for (i, (g, &x)) in grad.iter_mut().zip(u.iter()).enumerate() {
+            *g = T::from_f64(i as f64 + 1.0) * (x - T::one()) + x.powi(3);
+        }
+        Ok(())
+    }
+
+    fn cost<T: OpEnFloat>(u: &[T], c: &mut T) -> FunctionCallResult {
+        *c = T::zero();
+        for (i, &x) in u.iter().enumerate() {
+            *c += T::from_f64(0.5 * (i as f64 + 1.0)) * (x - T::one()).powi(2)
+                + T::from_f64(0.25) * x.powi(4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn t_panoc_mixed_precision() {
+        let n = 50;
+        let bounds_f32 = Rectangle::new(Some(&[-0.5_f32; 50]), Some(&[0.8_f32; 50])).unwrap();
+        let bounds_f64 = Rectangle::new(Some(&[-0.5; 50]), Some(&[0.8; 50])).unwrap();
+        let low_precision = PANOCOptimizer::from_dimensions(
+            Problem::new(&bounds_f32, gradient::<f32>, cost::<f32>),
+            n,
+            1e-6,
+            5,
+        )
+        .unwrap()
+        .with_history(true);
+        let refinement = PANOCOptimizer::from_dimensions(
+            Problem::new(&bounds_f64, gradient::<f64>, cost::<f64>),
+            n,
+            1e-11,
+            5,
+        )
+        .unwrap()
+        .with_history(true);
+        let mut optimizer = PANOCOptimizerMixed::new(low_precision, refinement)
+            .unwrap()
+            .with_switch_tolerance(1e-3)
+            .unwrap();
+        assert!(optimizer.low_precision_status().is_none());
+
+        let mut u = vec![0.0; n];
+        let status = optimizer.solve(&mut u).unwrap();
+        assert!(status.has_converged());
+        assert!(status.norm_fpr() < 1e-11);
+        let low = optimizer.low_precision_status().unwrap();
+        assert!(low.has_converged() && low.norm_fpr() < 1e-3 && low.iterations() > 0);
+        assert!(status.iterations() > low.iterations());
+        assert_eq!(status.iterations() + 2, status.fpr_history().unwrap().len());
+
+        // the solution matches the one of a solve in double precision
+        let mut u_f64 = vec![0.0; n];
+        PANOCOptimizer::from_dimensions(
+            Problem::new(&bounds_f64, gradient::<f64>, cost::<f64>),
+            n,
+            1e-11,
+            5,
+        )
+        .unwrap()
+        .solve(&mut u_f64)
+        .unwrap();
+        unit_test_utils::assert_nearly_equal_array(&u_f64, &u, 1e-8, 1e-8, "u");
+        // u[0] is the real root of u + u^3 = 1
+        unit_test_utils::assert_nearly_equal(0.682_327_803_828_019_3, u[0], 1e-9, 1e-9, "u[0]");
+
+        assert!(optimizer.solve(&mut [0.0; 3]).is_err());
+    }
+
+    #[test]
+    fn t_panoc_mixed_precision_dimensions() {
+        let ball_f32 = Ball2::new(None, 1.0_f32).unwrap();
+        let ball_f64 = Ball2::new(None, 1.0).unwrap();
+        let low_precision = PANOCOptimizer::from_dimensions(
+            Problem::new(&ball_f32, gradient::<f32>, cost::<f32>),
+            3,
+            1e-4,
+            5,
+        )
+        .unwrap();
+        let refinement = PANOCOptimizer::from_dimensions(
+            Problem::new(&ball_f64, gradient::<f64>, cost::<f64>),
+            4,
+            1e-8,
+            5,
+        )
+        .unwrap();
+        assert!(PANOCOptimizerMixed::new(low_precision, refinement).is_err());
+    }
+}
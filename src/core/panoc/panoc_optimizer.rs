@@ -3,35 +3,145 @@
 use crate::{
     constraints,
     core::{
-        panoc::panoc_engine::PANOCEngine, panoc::PANOCCache, AlgorithmEngine, ExitStatus,
-        Optimizer, Problem, SolverStatus,
+        batch_oracle::BatchPrefetch,
+        panoc::gauss_newton::GaussNewtonDirection,
+        panoc::lsr1::Lsr1,
+        panoc::panoc_cache::TrustRegion,
+        panoc::panoc_config::DEFAULT_MAX_ITER,
+        panoc::panoc_engine::{BatchLineSearch, PANOCEngine, MAX_LINESEARCH_ITERATIONS},
+        panoc::PANOCCache,
+        panoc::PanocConfig,
+        panoc::SemismoothNewtonCache,
+        problem::{BoxedCost, BoxedGradient},
+        ActiveSet, AlgorithmEngine, Clock, ExitStatus, Optimizer, Problem, Progress, ProgressEvent,
+        SolverStatus, SolverWarning, StdClock, Verbosity,
     },
-    matrix_operations, FunctionCallResult, SolverError,
+    ensure,
+    least_squares::GaussNewtonHessian,
+    manifold::Manifold,
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
 };
 use std::time;
 
-const MAX_ITER: usize = 100_usize;
+/// Default threshold of the step size below which PANOC reports a
+/// `SolverWarning::GammaCollapse`
+const DEFAULT_MIN_GAMMA: f64 = 1e-12;
+
+/// Default threshold of the growth of the Lipschitz estimate above which
+/// PANOC reports a `SolverWarning::LipschitzGrowth`
+const DEFAULT_MAX_LIPSCHITZ_GROWTH: f64 = 1e8;
+
+/// Thresholds of the detection of ill-conditioning (see
+/// `PANOCOptimizer::with_ill_conditioning_detection`)
+#[derive(Debug, Clone, Copy)]
+struct ConditioningCheck {
+    min_gamma: f64,
+    max_lipschitz_growth: f64,
+    abort: bool,
+}
+
+impl Default for ConditioningCheck {
+    fn default() -> Self {
+        ConditioningCheck {
+            min_gamma: DEFAULT_MIN_GAMMA,
+            max_lipschitz_growth: DEFAULT_MAX_LIPSCHITZ_GROWTH,
+            abort: false,
+        }
+    }
+}
+
+/// Per-iteration history of a solve (see `PANOCOptimizer::with_history`)
+#[derive(Debug, Default)]
+struct History {
+    cost: Vec<f64>,
+    fpr: Vec<f64>,
+    gamma: Vec<f64>,
+    direction_norm: Vec<f64>,
+    tau: Vec<f64>,
+}
+
+/// PANOC optimizer for type-erased problems (see `DynProblem`)
+pub type DynPANOCOptimizer<'a, T = f64> = PANOCOptimizer<
+    'a,
+    BoxedGradient<'a, T>,
+    constraints::BoxedConstraint<'a, T>,
+    BoxedCost<'a, T>,
+    T,
+>;
 
 /// Optimizer using the PANOC algorithm
 ///
+/// The type parameter `T` is the scalar type (`f64` by default)
+///
+/// The optimizer either borrows a cache (see `PANOCOptimizer::new`), which
+/// can then be reused by other optimizers, or owns it (see
+/// `PANOCOptimizer::new_owned` and `PANOCOptimizer::from_dimensions`). In the
+/// latter case, if the problem owns its constraints (see `Problem::new_owned`)
+/// and its cost and gradient do not borrow any data, the optimizer does not
+/// borrow anything (its lifetime parameter can be `'static`), so it can be
+/// stored in a long-lived struct, such as a controller.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{constraints::Ball2, panoc::*, *};
+///
+/// type Oracle = fn(&[f64], &mut [f64]) -> FunctionCallResult;
+/// type CostOracle = fn(&[f64], &mut f64) -> FunctionCallResult;
+///
+/// struct Controller {
+///     optimizer: PANOCOptimizer<'static, Oracle, Ball2<'static>, CostOracle>,
+/// }
+///
+/// fn gradient(u: &[f64], grad: &mut [f64]) -> FunctionCallResult {
+///     grad.copy_from_slice(u);
+///     Ok(())
+/// }
+///
+/// fn cost(u: &[f64], c: &mut f64) -> FunctionCallResult {
+///     *c = 0.5 * matrix_operations::norm2_squared(u);
+///     Ok(())
+/// }
 ///
-pub struct PANOCOptimizer<'a, GradientType, ConstraintType, CostType>
+/// let problem = Problem::new_owned(
+///     Ball2::new(None, 1.0)?,
+///     gradient as Oracle,
+///     cost as CostOracle,
+/// );
+/// let mut controller = Controller {
+///     optimizer: PANOCOptimizer::from_dimensions(problem, 2, 1e-6, 5)?,
+/// };
+/// let mut u = [1.0, 2.0];
+/// let status = controller.optimizer.solve(&mut u)?;
+/// assert!(status.has_converged());
+/// # Ok::<(), SolverError>(())
+/// ```
+pub struct PANOCOptimizer<'a, GradientType, ConstraintType, CostType, T = f64>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
-    panoc_engine: PANOCEngine<'a, GradientType, ConstraintType, CostType>,
+    panoc_engine: PANOCEngine<'a, GradientType, ConstraintType, CostType, T>,
     max_iter: usize,
     max_duration: Option<time::Duration>,
+    record_history: bool,
+    progress: Option<Progress>,
+    newton_cache: Option<&'a mut SemismoothNewtonCache<T>>,
+    clock: &'a dyn Clock,
+    conditioning: ConditioningCheck,
+    active_set_tolerance: Option<T>,
+    phase_timing: bool,
 }
 
-impl<'a, GradientType, ConstraintType, CostType>
-    PANOCOptimizer<'a, GradientType, ConstraintType, CostType>
+impl<'a, GradientType, ConstraintType, CostType, T>
+    PANOCOptimizer<'a, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
     /// Constructor of `PANOCOptimizer`
     ///
@@ -44,29 +154,98 @@ where
     ///
     /// Does not panic
     pub fn new(
-        problem: Problem<'a, GradientType, ConstraintType, CostType>,
-        cache: &'a mut PANOCCache,
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: &'a mut PANOCCache<T>,
     ) -> Self {
         PANOCOptimizer {
             panoc_engine: PANOCEngine::new(problem, cache),
-            max_iter: MAX_ITER,
+            max_iter: DEFAULT_MAX_ITER,
             max_duration: None,
+            record_history: false,
+            progress: None,
+            newton_cache: None,
+            clock: &StdClock,
+            conditioning: ConditioningCheck::default(),
+            active_set_tolerance: None,
+            phase_timing: false,
         }
     }
 
+    /// Constructor of `PANOCOptimizer` which takes ownership of its cache
+    ///
+    /// The optimizer is `Send` if the closures and the constraints of
+    /// `problem` are, so it can be moved to a worker thread (e.g., a blocking
+    /// task of an async runtime) together with its cache; it is not `Sync`.
+    ///
+    /// ## Arguments
+    ///
+    /// - problem: definition of optimization problem
+    /// - cache: cache object, which is owned by the optimizer
+    ///
+    /// ## Panic
+    ///
+    /// Does not panic
+    pub fn new_owned(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: PANOCCache<T>,
+    ) -> Self {
+        PANOCOptimizer {
+            panoc_engine: PANOCEngine::new_owned(problem, cache),
+            max_iter: DEFAULT_MAX_ITER,
+            max_duration: None,
+            record_history: false,
+            progress: None,
+            newton_cache: None,
+            clock: &StdClock,
+            conditioning: ConditioningCheck::default(),
+            active_set_tolerance: None,
+            phase_timing: false,
+        }
+    }
+
+    /// Constructor of `PANOCOptimizer` which allocates its own cache
+    ///
+    /// ## Arguments
+    ///
+    /// - problem: definition of optimization problem
+    /// - problem_size: dimension of the decision variables of the optimization problem
+    /// - tolerance: specified tolerance
+    /// - lbfgs_memory_size: the L-BFGS memory size
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified `tolerance` is
+    /// not positive, or if `problem_size` or `lbfgs_memory_size` is zero (see
+    /// `PANOCCache::new`)
+    pub fn from_dimensions(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        problem_size: usize,
+        tolerance: T,
+        lbfgs_memory_size: usize,
+    ) -> Result<Self, SolverError> {
+        let cache = PANOCCache::new(problem_size, tolerance, lbfgs_memory_size)?;
+        Ok(PANOCOptimizer::new_owned(problem, cache))
+    }
+
+    /// Number of decision variables
+    pub(crate) fn dimension(&self) -> usize {
+        self.panoc_engine.cache.gradient_u.len()
+    }
+
     /// Sets the tolerance on the norm of the fixed-point residual
     ///
     /// The algorithm will exit if the form of gamma*FPR drops below
     /// this tolerance
     ///
-    /// ## Panics
+    /// ## Errors
     ///
-    /// The method panics if the specified tolerance is not positive
-    pub fn with_tolerance(self, tolerance: f64) -> Self {
-        assert!(tolerance > 0.0, "tolerance must be larger than 0");
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(mut self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
 
         self.panoc_engine.cache.tolerance = tolerance;
-        self
+        Ok(self)
     }
 
     /// Specify the tolerance $\epsilon$ related to the AKKT condition
@@ -82,30 +261,361 @@ where
     ///
     /// ## Returns
     ///
-    /// Returns the current mutable and updated instance of the provided object
+    /// Returns the current mutable and updated instance of the provided object,
+    /// or `SolverError::InvalidParameter` if the provided value of the
+    /// AKKT-specific tolerance is not positive.
     ///
-    ///  
-    /// ## Panics
+    pub fn with_akkt_tolerance(mut self, akkt_tolerance: T) -> Result<Self, SolverError> {
+        self.panoc_engine.cache.set_akkt_tolerance(akkt_tolerance)?;
+        Ok(self)
+    }
+
+    /// Activates the termination criterion on the change of the iterate
+    ///
+    /// $$
+    /// \Vert{}u^{k} - u^{k-1}{}\Vert \leq \epsilon_{\rm rel} \Vert{}u^{k}{}\Vert + \epsilon_{\rm abs}
+    /// $$
+    ///
+    /// The algorithm then also exits (with `ExitStatus::Converged`) if the
+    /// change of the iterate is within these tolerances, even if the norm of
+    /// gamma*FPR is above the tolerance. This is useful for very flat costs,
+    /// for which the iterates stall although the FPR criterion is not
+    /// satisfied; the norm of the FPR at the solution is reported in the
+    /// status. The criterion is not checked by the semi-smooth Newton stage
+    /// (see `with_semismooth_newton`).
     ///
-    /// The method panics if the provided value of the AKKT-specific tolerance is
-    /// not positive.
+    /// Like the AKKT tolerance, the tolerances are stored in the cache (see
+    /// `PANOCCache::set_step_tolerance`), so they persist and also apply to
+    /// later optimizers which are constructed with the same cache.
+    ///
+    /// ## Arguments
     ///
-    pub fn with_akkt_tolerance(self, akkt_tolerance: f64) -> Self {
-        assert!(akkt_tolerance > 0.0, "akkt_tolerance must be positive");
-        self.panoc_engine.cache.set_akkt_tolerance(akkt_tolerance);
+    /// - `relative`: tolerance relative to the norm of the iterate, $\epsilon_{\rm rel}$
+    /// - `absolute`: absolute tolerance, $\epsilon_{\rm abs}$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if either tolerance is
+    /// negative or both are zero
+    ///
+    pub fn with_step_tolerance(mut self, relative: T, absolute: T) -> Result<Self, SolverError> {
+        self.panoc_engine
+            .cache
+            .set_step_tolerance(relative, absolute)?;
+        Ok(self)
+    }
+
+    /// Activates the semi-smooth Newton stage (see `SemismoothNewtonCache`)
+    ///
+    /// When the norm of gamma*FPR drops below the switch tolerance of
+    /// `newton_cache`, the optimizer switches from PANOC to semi-smooth Newton
+    /// iterations, which, for problems with box constraints and a twice
+    /// differentiable cost, converge to the tolerance of PANOC in a few
+    /// iterations. If the Newton iterations fail, PANOC resumes. The AKKT
+    /// termination condition (see `with_akkt_tolerance`) is not checked by
+    /// the Newton stage.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the problem size of
+    /// `newton_cache` differs from the problem size of the PANOC cache
+    pub fn with_semismooth_newton(
+        mut self,
+        newton_cache: &'a mut SemismoothNewtonCache<T>,
+    ) -> Result<Self, SolverError> {
+        let problem_size = self.panoc_engine.cache.gradient_u.len();
+        if newton_cache.problem_size() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "newton_cache",
+                expected: problem_size,
+                actual: newton_cache.problem_size(),
+            });
+        }
+        self.newton_cache = Some(newton_cache);
+        Ok(self)
+    }
+
+    /// Replaces the line search with a trust-region globalization
+    ///
+    /// Instead of halving the step along the L-BFGS (or Gauss-Newton)
+    /// direction until the forward-backward envelope decreases sufficiently,
+    /// PANOC limits the distance of the candidate point from the
+    /// forward-backward step to a radius, which is doubled after successful
+    /// steps on the boundary of the trust region and divided by four after
+    /// unsuccessful ones, when the forward-backward step is taken. At most
+    /// two candidates are evaluated per iteration, which is useful when the
+    /// gradient of the cost is noisy and backtracking is unreliable.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `initial_radius` is not
+    /// positive and finite
+    pub fn with_trust_region(mut self, initial_radius: T) -> Result<Self, SolverError> {
+        ensure(
+            initial_radius > T::zero() && initial_radius.is_finite(),
+            "initial_radius",
+            "must be positive and finite",
+        )?;
+        self.panoc_engine.cache.trust_region = Some(TrustRegion {
+            initial_radius,
+            radius: initial_radius,
+        });
+        Ok(self)
+    }
+
+    /// Replaces the L-BFGS directions with limited-memory SR1 directions
+    ///
+    /// Unlike L-BFGS, the SR1 estimate of the inverse Jacobian of the
+    /// fixed-point residual need not be positive definite, so it captures
+    /// negative curvature, which pays off on some nonconvex problems. Since
+    /// the SR1 directions can be arbitrarily long, their norm is limited to
+    /// a trust-region radius, which is halved when the line search rejects
+    /// the full step and doubled when a step on the boundary is accepted
+    /// (if the trust-region globalization is active, see
+    /// `with_trust_region`, it is used instead).
+    ///
+    /// ## Arguments
+    ///
+    /// - `memory`: number of (s, y) pairs of the L-SR1 buffer
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `memory` is zero
+    pub fn with_sr1(mut self, memory: usize) -> Result<Self, SolverError> {
+        let problem_size = self.panoc_engine.cache.gradient_u.len();
+        self.panoc_engine.cache.sr1 = Some(Lsr1::new(problem_size, memory)?);
+        Ok(self)
+    }
+
+    /// Sets the Lipschitz constant of the gradient of the cost, if it is
+    /// known (e.g., $\Vert Q \Vert_2$ for a quadratic cost, see
+    /// `QuadraticCost`)
+    ///
+    /// The initial step size is then computed from this constant instead of
+    /// an estimate at the initial point, which saves two evaluations of the
+    /// gradient. The step size is still reduced if the constant turns out to
+    /// be too small.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `lipschitz_constant` is
+    /// negative or not finite
+    pub fn with_lipschitz_constant(mut self, lipschitz_constant: T) -> Result<Self, SolverError> {
+        ensure(
+            lipschitz_constant >= T::zero() && lipschitz_constant.is_finite(),
+            "lipschitz_constant",
+            "must be nonnegative and finite",
+        )?;
+        self.panoc_engine.cache.known_lipschitz_constant = Some(lipschitz_constant);
+        Ok(self)
+    }
+
+    /// Replaces the L-BFGS directions with Gauss-Newton directions for costs
+    /// of the form $\tfrac{1}{2}\Vert r(u)\Vert^2$ (see `LeastSquares`)
+    ///
+    /// The directions are computed with conjugate gradient iterations on the
+    /// free variables, using products of `hessian` (e.g., $J(u)^\top J(u)$)
+    /// with vectors; the line search of PANOC is unchanged.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the dimension of `hessian`
+    /// differs from the problem size of the PANOC cache
+    pub fn with_gauss_newton(
+        mut self,
+        hessian: &'a dyn GaussNewtonHessian<T>,
+    ) -> Result<Self, SolverError> {
+        let problem_size = self.panoc_engine.cache.gradient_u.len();
+        if hessian.dimension() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "hessian",
+                expected: problem_size,
+                actual: hessian.dimension(),
+            });
+        }
+        self.panoc_engine.gauss_newton = Some(GaussNewtonDirection::new(hessian));
+        Ok(self)
+    }
+
+    /// Solves the problem over a manifold (e.g., a sphere or SO(3), see the
+    /// module `manifold`), on which the decision variables live
+    ///
+    /// The gradient of the cost is projected on the tangent space at every
+    /// iterate, the forward step is the retraction of the projected gradient
+    /// step and the directions are retracted too, so the iterates stay on
+    /// the manifold. The constraints of the problem are ignored (use
+    /// `NoConstraints`) and the initial guess must lie on the manifold. The
+    /// manifold mode is meant for the L-BFGS and SR1 directions; it should not
+    /// be combined with Gauss-Newton directions or with the semi-smooth Newton
+    /// stage.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the dimension of the
+    /// manifold (if not zero) differs from the problem size of the PANOC cache
+    pub fn with_manifold(mut self, manifold: &'a dyn Manifold<T>) -> Result<Self, SolverError> {
+        let problem_size = self.panoc_engine.cache.gradient_u.len();
+        if manifold.dimension() != 0 && manifold.dimension() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "manifold",
+                expected: problem_size,
+                actual: manifold.dimension(),
+            });
+        }
+        self.panoc_engine.cache.tangent = vec![T::zero(); problem_size];
+        self.panoc_engine.manifold = Some(manifold);
+        Ok(self)
+    }
+
+    /// Evaluates the first `num_candidates` candidates of every line search
+    /// (with $\tau = 1, 1/2, 1/4, \ldots$) in a single batch, using `oracle`
+    /// (e.g., a `BatchOracle` whose cost and gradient are those of the
+    /// problem), before the line search requests the cost and the gradient
+    /// at the candidates
+    ///
+    /// The iterates are the same as without batching; only the evaluations
+    /// of the oracle are grouped (the oracle calls of the status still count
+    /// the requests of the solver). This is not supported on manifolds, nor
+    /// with the trust-region globalization, where it has no effect.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `num_candidates` is zero or
+    /// larger than the maximum number of candidates of a line search, 11
+    pub fn with_batch_line_search(
+        mut self,
+        oracle: &'a dyn BatchPrefetch<T>,
+        num_candidates: usize,
+    ) -> Result<Self, SolverError> {
+        ensure(
+            num_candidates > 0 && num_candidates <= MAX_LINESEARCH_ITERATIONS as usize + 1,
+            "num_candidates",
+            "must be between 1 and 11",
+        )?;
+        let problem_size = self.panoc_engine.cache.gradient_u.len();
+        self.panoc_engine.batch_line_search = Some(BatchLineSearch {
+            oracle,
+            candidates: vec![T::zero(); num_candidates * problem_size],
+        });
+        Ok(self)
+    }
+
+    /// Whether PANOC should switch to the semi-smooth Newton stage
+    fn newton_switch(&self, newton_pending: bool) -> bool {
+        newton_pending
+            && self.newton_cache.as_ref().is_some_and(|newton_cache| {
+                self.panoc_engine.cache.norm_gamma_fpr < newton_cache.switch_tolerance
+            })
+    }
+
+    /// Runs the semi-smooth Newton stage from the current PANOC iterate
+    /// `u_half_step`; if it converges, its solution, cost and residual are
+    /// copied to the PANOC cache and `true` is returned
+    fn semismooth_newton(&mut self) -> Result<bool, SolverError> {
+        let newton_cache = match &mut self.newton_cache {
+            Some(newton_cache) => newton_cache,
+            None => return Ok(false),
+        };
+        let engine = &mut self.panoc_engine;
+        let cache = &mut *engine.cache;
+        newton_cache.x.copy_from_slice(&cache.u_half_step);
+        let converged = newton_cache.solve(
+            &mut engine.problem,
+            cache.gamma,
+            cache.tolerance,
+            &mut cache.oracle_calls,
+        )?;
+        if converged {
+            cache.oracle_calls.cost += 1;
+            (engine.problem.cost)(&newton_cache.x_bar, &mut cache.cost_value)?;
+            cache.u_half_step.copy_from_slice(&newton_cache.x_bar);
+            cache.gradient_u.copy_from_slice(&newton_cache.gradient);
+            cache.norm_gamma_fpr = newton_cache.norm_residual;
+        }
+        Ok(converged)
+    }
+
+    /// Sets the thresholds of the detection of ill-conditioned problems
+    ///
+    /// PANOC reports a `SolverWarning` in its status if its step size,
+    /// $\gamma$, drops below `min_gamma`, or if its estimate of the Lipschitz
+    /// constant of the gradient grows by a factor larger than
+    /// `max_lipschitz_growth` (relative to the initial estimate); this
+    /// usually means that the problem is badly scaled (see
+    /// `diagnostics::ScalingDiagnostics`) or that the gradient is not
+    /// Lipschitz continuous. If `abort` is true, PANOC stops with
+    /// `ExitStatus::Diverged` instead of iterating with tiny steps. By
+    /// default, the thresholds are $10^{-12}$ and $10^8$ and PANOC does not
+    /// abort.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `min_gamma` is negative or
+    /// `max_lipschitz_growth` is smaller than 1
+    pub fn with_ill_conditioning_detection(
+        mut self,
+        min_gamma: f64,
+        max_lipschitz_growth: f64,
+        abort: bool,
+    ) -> Result<Self, SolverError> {
+        ensure(min_gamma >= 0.0, "min_gamma", "must be nonnegative")?;
+        ensure(
+            max_lipschitz_growth >= 1.0,
+            "max_lipschitz_growth",
+            "must be at least 1",
+        )?;
+        self.conditioning = ConditioningCheck {
+            min_gamma,
+            max_lipschitz_growth,
+            abort,
+        };
+        Ok(self)
+    }
+
+    /// Identifies the active set of the constraints at the solution within
+    /// `tolerance`
+    ///
+    /// The coordinates of the solution at which the constraints are active
+    /// (see `ActiveSet`) are reported in the status; this involves $2n$
+    /// projections at the end of every solve.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not positive
+    pub fn with_active_set_tolerance(mut self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(
+            tolerance > T::zero(),
+            "active set tolerance",
+            "must be positive",
+        )?;
+        self.active_set_tolerance = Some(tolerance);
+        Ok(self)
+    }
+
+    /// Whether to measure the time spent in the phases of the solver
+    ///
+    /// If enabled, the time spent in evaluations of the cost and its gradient,
+    /// in projections, in the computation of directions and in the line
+    /// search is measured with the clock of the optimizer (see `with_clock`)
+    /// and reported in the status (see `SolverStatus::phase_times` and
+    /// `PhaseTimes`). This is disabled by default since it reads the clock
+    /// several times per iteration.
+    pub fn with_phase_timing(mut self, phase_timing: bool) -> Self {
+        self.phase_timing = phase_timing;
         self
     }
 
     /// Sets the maximum number of iterations
     ///
-    /// ## Panics
+    /// ## Errors
     ///
-    /// Panics if the provided number of iterations is equal to zero
-    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
-        assert!(max_iter > 0, "max_iter must be larger than 0");
+    /// Returns `SolverError::InvalidParameter` if the provided number of
+    /// iterations is equal to zero
+    pub fn with_max_iter(mut self, max_iter: usize) -> Result<Self, SolverError> {
+        ensure(max_iter > 0, "max_iter", "must be positive")?;
 
         self.max_iter = max_iter;
-        self
+        Ok(self)
     }
 
     /// Sets the maximum solution time, useful in real-time applications
@@ -113,17 +623,326 @@ where
         self.max_duration = Some(max_duation);
         self
     }
+
+    /// Sets the clock which measures the solution time (see `Clock`); the
+    /// default is `StdClock`
+    pub fn with_clock(mut self, clock: &'a dyn Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Whether to record the cost, the norm of the fixed-point residual, the
+    /// step size, $\gamma$, the norm of the direction and the value of tau of
+    /// the line search at every iteration
+    ///
+    /// If enabled, the recorded values are available via `SolverStatus::cost_history`,
+    /// `SolverStatus::fpr_history`, `SolverStatus::gamma_history`,
+    /// `SolverStatus::direction_norm_history` and `SolverStatus::tau_history`
+    /// (and `SolverStatus::trace`); tau is zero where the forward-backward
+    /// step was taken. This is disabled by default since it
+    /// requires memory allocation at every iteration.
+    pub fn with_history(mut self, record_history: bool) -> Self {
+        self.record_history = record_history;
+        self
+    }
+
+    /// Reports the progress of the solver, e.g., to a GUI
+    ///
+    /// A `ProgressEvent::Iteration` is emitted every `interval` iterations
+    /// (see [`Progress`](../struct.Progress.html)) and a
+    /// `ProgressEvent::Finished` is emitted when the solver returns a status.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Prints a compact table of the iterations (iteration, cost and norm of
+    /// the fixed-point residual) every `interval` iterations to stderr
+    ///
+    /// This is a shorthand for `with_progress(Progress::verbose(verbosity,
+    /// interval, std::io::stderr()))`; use `Progress::verbose` to print to
+    /// another `Write`. It replaces the receiver of `with_progress` (if any).
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use optimization_engine::{constraints::Ball2, core::*, panoc::*, FunctionCallResult};
+    ///
+    /// let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+    ///     grad.iter_mut().zip(u).for_each(|(g, u_i)| *g = u_i - 1.0);
+    ///     Ok(())
+    /// };
+    /// let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+    ///     *c = 0.5 * u.iter().map(|u_i| (u_i - 1.0).powi(2)).sum::<f64>();
+    ///     Ok(())
+    /// };
+    /// let bounds = Ball2::new(None, 1.0)?;
+    /// let mut cache = PANOCCache::new(2, 1e-8, 5)?;
+    /// // prints a row every 5 iterations and a final line to stderr
+    /// let status = PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache)
+    ///     .with_verbosity(Verbosity::Iterations, 5)
+    ///     .solve(&mut [0.0, 0.0])?;
+    /// assert!(status.has_converged());
+    /// # Ok::<(), optimization_engine::SolverError>(())
+    /// ```
+    pub fn with_verbosity(self, verbosity: Verbosity, interval: usize) -> Self {
+        self.with_progress(Progress::verbose(verbosity, interval, std::io::stderr()))
+    }
+
+    /// Activates the screening of the outputs of the cost and its gradient
+    ///
+    /// If activated, the cost and its gradient are checked for NaN and infinite
+    /// values after every evaluation; if such a value is found, the solver
+    /// stops immediately (before the L-BFGS buffer is updated) and returns
+    /// `SolverError::NotFiniteOutput`, which indicates the function, the first
+    /// non-finite index and the iteration. This is disabled by default.
+    pub fn with_nan_screening(mut self, nan_screening: bool) -> Self {
+        self.panoc_engine.cache.nan_screening = nan_screening;
+        self
+    }
+
+    /// Activates the interpolating line search
+    ///
+    /// By default, the line search halves `tau` after every rejected
+    /// candidate. If activated, the next value of `tau` is the root of a
+    /// quadratic or cubic interpolant of the FBE at the rejected candidates
+    /// (safeguarded to `[0.1 tau, 0.5 tau]`), so that bad L-BFGS directions
+    /// are abandoned after fewer evaluations of the cost and its gradient.
+    /// Accepted candidates satisfy the same condition as in the halving line
+    /// search. This has no effect on batched line searches (see
+    /// `with_batch_line_search`), whose candidates are those of the halving
+    /// line search, and is disabled by default.
+    pub fn with_interpolating_line_search(mut self, interpolating_line_search: bool) -> Self {
+        self.panoc_engine.cache.interpolating_line_search = interpolating_line_search;
+        self
+    }
+
+    /// Sets the value of tau of the first candidate of the line search
+    ///
+    /// The candidates of the line search are
+    /// `u - (1-tau) * gamma * fpr - tau * d`, where `d` is the (quasi-Newton)
+    /// direction; after a rejected candidate, tau is multiplied by the
+    /// shrink factor (see `with_tau_shrink_factor`). The default initial tau
+    /// is 1, that is, the first candidate is the full quasi-Newton step;
+    /// smaller values are more cautious with directions which are too long,
+    /// e.g., on stiff problems.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `initial_tau` is not in
+    /// `(0, 1]`
+    pub fn with_initial_tau(mut self, initial_tau: T) -> Result<Self, SolverError> {
+        ensure(
+            initial_tau > T::zero() && initial_tau <= T::one(),
+            "initial_tau",
+            "must be in (0, 1]",
+        )?;
+        self.panoc_engine.cache.line_search.initial_tau = initial_tau;
+        Ok(self)
+    }
+
+    /// Sets the factor by which tau is multiplied after a rejected candidate
+    /// of the line search (the default is 0.5)
+    ///
+    /// If the interpolating line search is active (see
+    /// `with_interpolating_line_search`), tau is chosen by interpolation
+    /// instead.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `shrink_factor` is not in
+    /// `(0, 1)`
+    pub fn with_tau_shrink_factor(mut self, shrink_factor: T) -> Result<Self, SolverError> {
+        ensure(
+            shrink_factor > T::zero() && shrink_factor < T::one(),
+            "shrink_factor",
+            "must be in (0, 1)",
+        )?;
+        self.panoc_engine.cache.line_search.shrink_factor = shrink_factor;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of rejected candidates of the line search
+    /// (the default is 10)
+    ///
+    /// If the first candidate and the next `max_backtracks` candidates are
+    /// rejected, the forward-backward (projected gradient) step, with
+    /// tau = 0, is taken; this requires one more evaluation of the cost and
+    /// its gradient. With `max_backtracks` equal to zero, the forward-backward
+    /// step is taken whenever the first candidate is rejected.
+    pub fn with_max_backtracks(mut self, max_backtracks: usize) -> Self {
+        self.panoc_engine.cache.line_search.max_backtracks = max_backtracks;
+        self
+    }
+
+    /// Sets the smallest admissible tau of the line search (the default is
+    /// zero)
+    ///
+    /// Once tau would drop below `min_tau`, the line search stops and the
+    /// forward-backward (projected gradient) step, with tau = 0, is taken, as
+    /// after the maximum number of rejected candidates (see
+    /// `with_max_backtracks`). This avoids evaluating candidates which are
+    /// barely different from the forward-backward step.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `min_tau` is not in
+    /// `[0, 1)`
+    pub fn with_min_tau(mut self, min_tau: T) -> Result<Self, SolverError> {
+        ensure(
+            min_tau >= T::zero() && min_tau < T::one(),
+            "min_tau",
+            "must be in [0, 1)",
+        )?;
+        self.panoc_engine.cache.line_search.min_tau = min_tau;
+        Ok(self)
+    }
+
+    /// Applies a configuration to this optimizer
+    ///
+    /// The configuration is applied using the corresponding setter methods
+    /// (e.g., `with_tolerance` and `with_max_iter`); fields of `config` which
+    /// are `None` are ignored.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the configuration contains
+    /// invalid values (see the corresponding setter methods)
+    pub fn with_config(self, config: &PanocConfig) -> Result<Self, SolverError> {
+        let mut optimizer = self.with_max_iter(config.max_iter)?;
+        if let Some(tolerance) = config.tolerance {
+            optimizer = optimizer.with_tolerance(T::from_f64(tolerance))?;
+        }
+        if let Some(akkt_tolerance) = config.akkt_tolerance {
+            optimizer = optimizer.with_akkt_tolerance(T::from_f64(akkt_tolerance))?;
+        }
+        if let Some(max_duration) = config.max_duration {
+            optimizer = optimizer.with_max_duration(max_duration);
+        }
+        if let Some(initial_radius) = config.trust_region_radius {
+            optimizer = optimizer.with_trust_region(T::from_f64(initial_radius))?;
+        }
+        if let Some(memory) = config.sr1_memory {
+            optimizer = optimizer.with_sr1(memory)?;
+        }
+        Ok(optimizer
+            .with_history(config.record_history)
+            .with_nan_screening(config.nan_screening))
+    }
+
+    /// Performs an iteration of PANOC in a trace-level span, records it and
+    /// checks the conditioning; returns whether PANOC should continue
+    /// iterating and whether it has diverged
+    fn iterate(
+        &mut self,
+        iteration: usize,
+        u: &mut [T],
+        history: &mut Option<History>,
+        initial_gamma: f64,
+        warnings: &mut Vec<SolverWarning>,
+    ) -> Result<(bool, bool), SolverError> {
+        enter_trace_span!("panoc_iteration", iteration = iteration);
+        let step_flag = self.panoc_engine.step(u)?;
+        self.record(iteration, history);
+        let diverged = self.check_conditioning(iteration, initial_gamma, warnings);
+        Ok((step_flag, diverged))
+    }
+
+    /// Logs the current iteration (at trace level), reports the progress (if
+    /// requested), appends the current cost, norm of the fixed-point
+    /// residual, step size, norm of the direction and tau to the history (if
+    /// it is being recorded) and the norm of the fixed-point residual to the
+    /// recent history of the cache (if it is kept)
+    fn record(&mut self, iteration: usize, history: &mut Option<History>) {
+        let cache = &self.panoc_engine.cache;
+        // the direction of the last step (the norm is only computed if it is
+        // reported)
+        let direction_norm = || matrix_operations::norm2(&cache.direction_lbfgs).as_f64();
+        if let Some(progress) = &mut self.progress {
+            progress.iteration(iteration, || ProgressEvent::Iteration {
+                iteration,
+                fpr: cache.norm_gamma_fpr.as_f64(),
+                cost: cache.cost_value.as_f64(),
+                direction_norm: direction_norm(),
+                tau: cache.tau.as_f64(),
+                forward_backward: cache.tau == T::zero(),
+            });
+        }
+        trace_event!(
+            iteration = iteration,
+            fpr = cache.norm_gamma_fpr.as_f64(),
+            cost = cache.cost_value.as_f64(),
+            gamma = cache.gamma.as_f64(),
+            tau = cache.tau.as_f64(),
+            "PANOC iteration"
+        );
+        if let Some(history) = history {
+            history.cost.push(cache.cost_value.as_f64());
+            history.fpr.push(cache.norm_gamma_fpr.as_f64());
+            history.gamma.push(cache.gamma.as_f64());
+            history.direction_norm.push(direction_norm());
+            history.tau.push(cache.tau.as_f64());
+        }
+        self.panoc_engine.cache.record_fpr();
+    }
+
+    /// Reports (once) a collapse of the step size and a growth of the
+    /// Lipschitz estimate, relative to the initial step size `initial_gamma`,
+    /// and returns whether PANOC should stop
+    fn check_conditioning(
+        &self,
+        iteration: usize,
+        initial_gamma: f64,
+        warnings: &mut Vec<SolverWarning>,
+    ) -> bool {
+        let gamma = self.panoc_engine.cache.gamma.as_f64();
+        let check = &self.conditioning;
+        let collapse = gamma < check.min_gamma;
+        if collapse
+            && !warnings
+                .iter()
+                .any(|w| matches!(w, SolverWarning::GammaCollapse { .. }))
+        {
+            warnings.push(SolverWarning::GammaCollapse { iteration, gamma });
+        }
+        // the step size is inversely proportional to the Lipschitz estimate
+        let growth = initial_gamma / gamma;
+        let growing = growth > check.max_lipschitz_growth;
+        if growing
+            && !warnings
+                .iter()
+                .any(|w| matches!(w, SolverWarning::LipschitzGrowth { .. }))
+        {
+            warnings.push(SolverWarning::LipschitzGrowth { iteration, growth });
+        }
+        check.abort && (collapse || growing)
+    }
 }
 
-impl<'life, GradientType, ConstraintType, CostType> Optimizer
-    for PANOCOptimizer<'life, GradientType, ConstraintType, CostType>
+impl<'life, GradientType, ConstraintType, CostType, T>
+    PANOCOptimizer<'life, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'life,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint + 'life,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T> + 'life,
 {
-    fn solve(&mut self, u: &mut [f64]) -> Result<SolverStatus, SolverError> {
-        let now = instant::Instant::now();
+    /// Runs the algorithm, which started at time `now` (see `solve`)
+    fn run(&mut self, u: &mut [T], now: time::Duration) -> Result<SolverStatus, SolverError> {
+        let problem_size = self.panoc_engine.cache.gradient_u.len();
+        if u.len() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: problem_size,
+                actual: u.len(),
+            });
+        }
+
+        self.panoc_engine.phase_clock = if self.phase_timing {
+            Some(self.clock)
+        } else {
+            None
+        };
 
         /*
          * Initialise [call panoc_engine.init()]
@@ -136,29 +955,76 @@ where
         let mut continue_num_iters = true;
         let mut continue_runtime = true;
 
-        let mut step_flag = self.panoc_engine.step(u)?;
-        if let Some(dur) = self.max_duration {
-            while step_flag && continue_num_iters && continue_runtime {
-                num_iter += 1;
-                continue_num_iters = num_iter < self.max_iter;
-                continue_runtime = now.elapsed() <= dur;
-                step_flag = self.panoc_engine.step(u)?;
-            }
+        let mut history = if self.record_history {
+            Some(History::default())
         } else {
-            while step_flag && continue_num_iters {
+            None
+        };
+
+        if let Some(newton_cache) = &mut self.newton_cache {
+            newton_cache.reset();
+        }
+        let mut newton_pending = self.newton_cache.is_some();
+
+        let initial_gamma = self.panoc_engine.cache.gamma.as_f64();
+        let mut warnings = Vec::new();
+        let (mut step_flag, mut diverged) =
+            self.iterate(num_iter, u, &mut history, initial_gamma, &mut warnings)?;
+        loop {
+            while step_flag
+                && continue_num_iters
+                && continue_runtime
+                && !diverged
+                && !self.newton_switch(newton_pending)
+            {
                 num_iter += 1;
                 continue_num_iters = num_iter < self.max_iter;
-                step_flag = self.panoc_engine.step(u)?;
+                continue_runtime = self
+                    .max_duration
+                    .is_none_or(|dur| self.clock.elapsed(now) <= dur);
+                (step_flag, diverged) =
+                    self.iterate(num_iter, u, &mut history, initial_gamma, &mut warnings)?;
             }
+            // the Newton stage runs at most once; if it fails, PANOC resumes
+            if step_flag && continue_num_iters && continue_runtime && !diverged && newton_pending {
+                newton_pending = false;
+                if self.semismooth_newton()? {
+                    break;
+                }
+                continue;
+            }
+            break;
         }
 
         // check for possible NaN/inf
+        let cache = &self.panoc_engine.cache;
+        if !cache.cost_value.is_finite() {
+            return Err(SolverError::NotFiniteCost {
+                location: "PANOC",
+                iteration: num_iter,
+            });
+        }
+        if let Some(index) = matrix_operations::first_non_finite(&cache.gradient_u) {
+            return Err(SolverError::NotFiniteGradient {
+                location: "PANOC",
+                index,
+                iteration: num_iter,
+            });
+        }
         if !matrix_operations::is_finite(u) {
             return Err(SolverError::NotFiniteComputation);
         }
+        if !matrix_operations::is_finite(&cache.u_half_step) {
+            return Err(SolverError::ProjectionFailed {
+                location: "PANOC",
+                iteration: num_iter,
+            });
+        }
 
         // exit status
-        let exit_status = if !continue_num_iters {
+        let exit_status = if diverged && step_flag {
+            ExitStatus::Diverged
+        } else if !continue_num_iters {
             ExitStatus::NotConvergedIterations
         } else if !continue_runtime {
             ExitStatus::NotConvergedOutOfTime
@@ -171,13 +1037,72 @@ where
         u.copy_from_slice(&self.panoc_engine.cache.u_half_step);
 
         // export solution status (exit status, num iterations and more)
-        Ok(SolverStatus::new(
+        let cache = &self.panoc_engine.cache;
+        let status = SolverStatus::new(
             exit_status,
             num_iter,
-            now.elapsed(),
-            self.panoc_engine.cache.norm_gamma_fpr,
-            self.panoc_engine.cache.cost_value,
-        ))
+            self.clock.elapsed(now),
+            cache.norm_gamma_fpr.as_f64(),
+            cache.cost_value.as_f64(),
+        )
+        .with_gradient_norm(matrix_operations::norm2(&cache.gradient_u).as_f64())
+        .with_oracle_calls(cache.oracle_calls)
+        .with_warnings(warnings);
+        let status = match self.active_set_tolerance {
+            Some(tolerance) => status.with_active_set(ActiveSet::identify(
+                &*self.panoc_engine.problem.constraints,
+                u,
+                tolerance,
+            )),
+            None => status,
+        };
+        let status = if self.phase_timing {
+            status.with_phase_times(cache.phase_times)
+        } else {
+            status
+        };
+        debug_event!(
+            iterations = status.iterations(),
+            fpr = status.norm_fpr(),
+            cost = status.cost_value(),
+            solve_time = status.solve_time().as_secs_f64(),
+            "PANOC finished: {}",
+            status.exit_status()
+        );
+        if let Some(progress) = &mut self.progress {
+            progress.emit(ProgressEvent::Finished {
+                exit_status: status.exit_status(),
+                iterations: status.iterations(),
+                solve_time: status.solve_time(),
+            });
+        }
+        Ok(match history {
+            Some(history) => status
+                .with_history(history.cost, history.fpr)
+                .with_gamma_history(history.gamma)
+                .with_direction_history(history.direction_norm, history.tau),
+            None => status,
+        })
+    }
+}
+
+impl<'life, GradientType, ConstraintType, CostType, T> Optimizer<T>
+    for PANOCOptimizer<'life, GradientType, ConstraintType, CostType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T> + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = self.clock.now();
+        let result = self.run(u, now);
+        let solve_time = self.clock.elapsed(now);
+        self.panoc_engine
+            .cache
+            .statistics
+            .record_result(&result, solve_time);
+        result
     }
 }
 
@@ -190,7 +1115,7 @@ mod tests {
     use crate::core::constraints::*;
     use crate::core::panoc::*;
     use crate::core::*;
-    use crate::{mocks, FunctionCallResult};
+    use crate::{mocks, FunctionCallResult, SolverError};
 
     #[test]
     fn t_panoc_optimizer_rosenbrock() {
@@ -214,10 +1139,12 @@ mod tests {
         };
         /* CONSTRAINTS */
         let radius = 2.0;
-        let bounds = constraints::Ball2::new(None, radius);
-        let mut panoc_cache = PANOCCache::new(n_dimension, tolerance, lbfgs_memory);
+        let bounds = constraints::Ball2::new(None, radius).unwrap();
+        let mut panoc_cache = PANOCCache::new(n_dimension, tolerance, lbfgs_memory).unwrap();
         let problem = Problem::new(&bounds, cost_gradient, cost_function);
-        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache).with_max_iter(max_iters);
+        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_max_iter(max_iters)
+            .unwrap();
         let now = instant::Instant::now();
         let status = panoc.solve(&mut u_solution).unwrap();
 
@@ -252,7 +1179,7 @@ mod tests {
         let lbfgs_memory = 10;
         let max_iters = 100;
         let mut u_solution = [-1.5, 0.9];
-        let mut panoc_cache = PANOCCache::new(n_dimension, tolerance, lbfgs_memory);
+        let mut panoc_cache = PANOCCache::new(n_dimension, tolerance, lbfgs_memory).unwrap();
         for i in 1..=100 {
             b_param *= 1.01;
             a_param -= 1e-3;
@@ -267,9 +1194,11 @@ mod tests {
                 *c = mocks::rosenbrock_cost(a_param, b_param, u);
                 Ok(())
             };
-            let bounds = constraints::Ball2::new(None, radius);
+            let bounds = constraints::Ball2::new(None, radius).unwrap();
             let problem = Problem::new(&bounds, cost_gradient, cost_function);
-            let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache).with_max_iter(max_iters);
+            let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
+                .with_max_iter(max_iters)
+                .unwrap();
 
             let status = panoc.solve(&mut u_solution).unwrap();
 
@@ -319,14 +1248,16 @@ mod tests {
         };
 
         let radius = 1.2;
-        let bounds = constraints::Ball2::new(None, radius);
+        let bounds = constraints::Ball2::new(None, radius).unwrap();
 
-        let mut panoc_cache = PANOCCache::new(n_dimension, tolerance, lbfgs_memory);
+        let mut panoc_cache = PANOCCache::new(n_dimension, tolerance, lbfgs_memory).unwrap();
         let problem = Problem::new(&bounds, cost_gradient, cost_function);
 
         let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
             .with_max_iter(max_iters)
-            .with_akkt_tolerance(akkt_tolerance);
+            .unwrap()
+            .with_akkt_tolerance(akkt_tolerance)
+            .unwrap();
 
         let status = panoc.solve(&mut u_solution).unwrap();
 
@@ -335,4 +1266,247 @@ mod tests {
         assert!(status.iterations() < max_iters);
         assert!(status.norm_fpr() < tolerance);
     }
+
+    #[test]
+    fn t_panoc_optimizer_dimension_mismatch() {
+        let bounds = constraints::Ball2::new(None, 1.0).unwrap();
+        let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+        let mut panoc_cache = PANOCCache::new(2, 1e-6, 5).unwrap();
+        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache);
+        let mut u = [0.0; 3];
+        let result = panoc.solve(&mut u);
+        assert_eq!(
+            Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: 2,
+                actual: 3
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn t_panoc_optimizer_not_finite_cost() {
+        let bounds = constraints::NoConstraints::new();
+        let cost = |_u: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = f64::NAN;
+            Ok(())
+        };
+        let problem = Problem::new(&bounds, mocks::my_gradient, cost);
+        let mut panoc_cache = PANOCCache::new(2, 1e-6, 5).unwrap();
+        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_max_iter(10)
+            .unwrap();
+        let mut u = [1.0, 2.0];
+        match panoc.solve(&mut u) {
+            Err(SolverError::NotFiniteCost { location, .. }) => assert_eq!("PANOC", location),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn t_panoc_optimizer_statistics() {
+        let bounds = constraints::Ball2::new(None, 2.0).unwrap();
+        let mut panoc_cache = PANOCCache::new(2, 1e-8, 5).unwrap();
+        let mut solve = |u: &mut [f64], max_iters: usize| {
+            let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+            PANOCOptimizer::new(problem, &mut panoc_cache)
+                .with_max_iter(max_iters)
+                .unwrap()
+                .solve(u)
+        };
+        let status = solve(&mut [1.0, -1.0], 100).unwrap();
+        assert!(status.has_converged());
+        assert!(solve(&mut [1.0, -1.0], 1).is_ok());
+        assert!(solve(&mut [1.0], 100).is_err());
+
+        // the statistics are kept across solves
+        let statistics = *panoc_cache.statistics();
+        assert_eq!(3, statistics.num_solves());
+        assert_eq!(2, statistics.num_failures());
+        assert_eq!(1, statistics.num_errors());
+        assert_eq!(status.iterations(), statistics.max_iterations());
+        assert_eq!(
+            (status.iterations() + 1) as f64 / 2.0,
+            statistics.mean_iterations()
+        );
+        assert_eq!(0.0, statistics.max_infeasibility());
+        panoc_cache.reset_statistics();
+        assert_eq!(0, panoc_cache.statistics().num_solves());
+    }
+
+    #[test]
+    fn t_panoc_optimizer_fpr_history() {
+        let bounds = constraints::Ball2::new(None, 2.0).unwrap();
+        let mut panoc_cache = PANOCCache::new(2, 1e-10, 5)
+            .unwrap()
+            .with_fpr_history(4)
+            .unwrap();
+        let solve = |cache: &mut PANOCCache, u: &mut [f64], max_iters: usize| {
+            let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+            PANOCOptimizer::new(problem, cache)
+                .with_max_iter(max_iters)
+                .unwrap()
+                .with_history(true)
+                .solve(u)
+                .unwrap()
+        };
+        let status = solve(&mut panoc_cache, &mut [1.0, -1.0], 100);
+        assert!(status.iterations() >= 4);
+        let fpr_history = status.fpr_history().unwrap();
+        let recent: Vec<f64> = panoc_cache.fpr_history().collect();
+        assert_eq!(&fpr_history[fpr_history.len() - 4..], &recent[..]);
+        assert_eq!(Some(&status.norm_fpr()), recent.last());
+
+        // the recent history is that of the last solve
+        let status = solve(&mut panoc_cache, &mut [1.0, -1.0], 1);
+        assert_eq!(
+            status.fpr_history().unwrap(),
+            &panoc_cache.fpr_history().collect::<Vec<f64>>()[..]
+        );
+        assert!(PANOCCache::new(2, 1e-10, 5)
+            .unwrap()
+            .with_fpr_history(0)
+            .is_err());
+    }
+
+    #[test]
+    fn t_panoc_optimizer_ill_conditioning() {
+        // f(u) = |u_0| + |u_1| is not differentiable at the solution, so the
+        // Lipschitz estimate grows without bound
+        let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad.iter_mut()
+                .zip(u)
+                .for_each(|(g, u_i)| *g = u_i.signum());
+            Ok(())
+        };
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = u.iter().map(|u_i| u_i.abs()).sum();
+            Ok(())
+        };
+        let bounds = constraints::Ball2::new(None, 10.0).unwrap();
+        let mut panoc_cache = PANOCCache::new(2, 1e-12, 5).unwrap();
+
+        let problem = Problem::new(&bounds, gradient, cost);
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_max_iter(200)
+            .unwrap()
+            .solve(&mut [1.0, -0.5])
+            .unwrap();
+        assert_ne!(ExitStatus::Diverged, status.exit_status());
+        assert!(status
+            .warnings()
+            .iter()
+            .any(|w| matches!(w, SolverWarning::LipschitzGrowth { .. })));
+
+        let problem = Problem::new(&bounds, gradient, cost);
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_max_iter(200)
+            .unwrap()
+            .with_ill_conditioning_detection(1e-6, f64::INFINITY, true)
+            .unwrap()
+            .solve(&mut [1.0, -0.5])
+            .unwrap();
+        assert_eq!(ExitStatus::Diverged, status.exit_status());
+        match status.warnings().first() {
+            Some(SolverWarning::GammaCollapse { gamma, iteration }) => {
+                assert!(*gamma < 1e-6);
+                assert_eq!(status.iterations(), *iteration);
+            }
+            other => panic!("unexpected warning: {:?}", other),
+        }
+
+        // a well-conditioned problem has no warnings
+        let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .solve(&mut [1.0, -0.5])
+            .unwrap();
+        assert!(status.has_converged());
+        assert!(status.warnings().is_empty());
+
+        let problem = Problem::new(&bounds, gradient, cost);
+        assert!(PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_ill_conditioning_detection(1e-6, 0.5, true)
+            .is_err());
+    }
+
+    #[test]
+    fn t_panoc_optimizer_active_set() {
+        // f(u) = 0.5*||u - (2, -2, 0.5)||^2 on the box [-1, 1]^3, whose solution
+        // is u = (1, -1, 0.5)
+        let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad[0] = u[0] - 2.0;
+            grad[1] = u[1] + 2.0;
+            grad[2] = u[2] - 0.5;
+            Ok(())
+        };
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = 0.5 * ((u[0] - 2.0).powi(2) + (u[1] + 2.0).powi(2) + (u[2] - 0.5).powi(2));
+            Ok(())
+        };
+        let (xmin, xmax) = ([-1.0; 3], [1.0; 3]);
+        let bounds = Rectangle::new(Some(&xmin), Some(&xmax)).unwrap();
+        let mut panoc_cache = PANOCCache::new(3, 1e-8, 5).unwrap();
+
+        let problem = Problem::new(&bounds, gradient, cost);
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .solve(&mut [0.0; 3])
+            .unwrap();
+        assert!(status.active_set().is_none());
+
+        let problem = Problem::new(&bounds, gradient, cost);
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_active_set_tolerance(1e-6)
+            .unwrap()
+            .solve(&mut [0.0; 3])
+            .unwrap();
+        assert!(status.has_converged());
+        let active_set = status.active_set().unwrap();
+        assert_eq!(&[1], active_set.lower());
+        assert_eq!(&[0], active_set.upper());
+
+        let problem = Problem::new(&bounds, gradient, cost);
+        assert!(PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_active_set_tolerance(0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn t_panoc_optimizer_phase_timing() {
+        let tolerance = 1e-8;
+        let mut panoc_cache = PANOCCache::new(3, tolerance, 5).unwrap();
+        let bounds = Ball2::new(None, 0.2).unwrap();
+        let problem = Problem::new(
+            &bounds,
+            mocks::hard_quadratic_gradient,
+            mocks::hard_quadratic_cost,
+        );
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .solve(&mut [0.0; 3])
+            .unwrap();
+        assert!(status.phase_times().is_none());
+
+        // every reading of the clock takes 1ms, so every measured phase has
+        // taken some time
+        let clock = ManualClock::new().with_tick(std::time::Duration::from_millis(1));
+        let problem = Problem::new(
+            &bounds,
+            mocks::hard_quadratic_gradient,
+            mocks::hard_quadratic_cost,
+        );
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_clock(&clock)
+            .with_phase_timing(true)
+            .solve(&mut [0.0; 3])
+            .unwrap();
+        assert!(status.has_converged());
+        assert!(status.iterations() > 1);
+        let phase_times = status.phase_times().unwrap();
+        assert!(phase_times.cost() > std::time::Duration::ZERO);
+        assert!(phase_times.gradient() > std::time::Duration::ZERO);
+        assert!(phase_times.projection() > std::time::Duration::ZERO);
+        assert!(phase_times.direction() > std::time::Duration::ZERO);
+        assert!(phase_times.line_search() > std::time::Duration::ZERO);
+        assert!(phase_times.total() <= status.solve_time());
+    }
 }
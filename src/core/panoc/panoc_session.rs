@@ -0,0 +1,182 @@
+//! Recording and replay of PANOC solves
+//!
+use crate::{
+    constraints,
+    core::{
+        panoc::{PANOCCache, PANOCOptimizer, PanocConfig},
+        Optimizer, Problem, SolverStatus,
+    },
+    FunctionCallResult, SolverError,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+/// Record of a PANOC solve, which can be saved, loaded and replayed
+///
+/// A session contains everything that is needed to reproduce a solve, except
+/// for the problem itself: the parameter of the problem, the initial guess,
+/// the settings of the solver, the solution and the solver status, including
+/// the cost and the norm of the fixed-point residual at every iteration.
+/// When a solver misbehaves in the field, the session can be saved to a
+/// (JSON) file with `save` and attached to a bug report; the session is then
+/// loaded with `load` and replayed against the same functions with `replay`.
+///
+/// This requires the feature `session`.
+///
+/// # Example
+///
+/// ```no_run
+/// use optimization_engine::{constraints::*, panoc::*, *};
+///
+/// fn make_problem<'a>(p: &'a [f64], bounds: &'a NoConstraints) -> Problem<'a,
+///     impl FnMut(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
+///     NoConstraints,
+///     impl FnMut(&[f64], &mut f64) -> FunctionCallResult + 'a> {
+///     Problem::new(
+///         bounds,
+///         move |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+///             g[0] = 2.0 * (u[0] - p[0]);
+///             Ok(())
+///         },
+///         move |u: &[f64], c: &mut f64| -> FunctionCallResult {
+///             *c = (u[0] - p[0]).powi(2);
+///             Ok(())
+///         },
+///     )
+/// }
+///
+/// let (p, bounds) = ([3.0], NoConstraints::new());
+/// let mut cache = PANOCCache::new(1, 1e-8, 5)?;
+/// let mut u = [0.0];
+/// let (status, session) = PanocSession::record(
+///     make_problem(&p, &bounds), &p, &mut u, &mut cache, &PanocConfig::new())?;
+/// session.save("session.json").expect("cannot save session");
+///
+/// // later (e.g., in a unit test)
+/// let session = PanocSession::load("session.json").expect("cannot load session");
+/// let (solution, replayed_status) = session.replay(make_problem(&session.parameter, &bounds))?;
+/// assert_eq!(session.solution, solution);
+/// # Ok::<(), SolverError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PanocSession {
+    /// parameter of the problem
+    pub parameter: Vec<f64>,
+    /// initial guess
+    pub initial_guess: Vec<f64>,
+    /// tolerance of the cache (see `PANOCCache::new`)
+    pub tolerance: f64,
+    /// memory of the L-BFGS buffer
+    pub lbfgs_memory: usize,
+    /// settings of the solver
+    pub config: PanocConfig,
+    /// solution (if the solve has failed, the last iterate)
+    pub solution: Vec<f64>,
+    /// solver status (with the per-iteration history), or the error message
+    /// if the solver has failed
+    pub status: Result<SolverStatus, String>,
+}
+
+impl PanocSession {
+    /// Solves a problem and records the session
+    ///
+    /// # Arguments
+    ///
+    /// - `problem`: problem to be solved
+    /// - `parameter`: parameter of the problem, which is stored in the session
+    ///   (the problem depends on it, but OpEn has no access to it otherwise)
+    /// - `u`: initial guess, which is overwritten with the solution
+    /// - `cache`: cache of PANOC
+    /// - `config`: settings of the solver (the per-iteration history is
+    ///   always recorded)
+    ///
+    /// # Returns
+    ///
+    /// The result of the solver and the session, which is returned even if the
+    /// solver has failed (that is, for the bug reports which matter most)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (and no session) if `config` is invalid
+    pub fn record<'a, GradientType, ConstraintType, CostType>(
+        problem: Problem<'a, GradientType, ConstraintType, CostType>,
+        parameter: &[f64],
+        u: &mut [f64],
+        cache: &'a mut PANOCCache,
+        config: &PanocConfig,
+    ) -> Result<(Result<SolverStatus, SolverError>, PanocSession), SolverError>
+    where
+        GradientType: FnMut(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
+        CostType: FnMut(&[f64], &mut f64) -> FunctionCallResult + 'a,
+        ConstraintType: constraints::Constraint + 'a,
+    {
+        let config = config.with_history(true);
+        let initial_guess = u.to_vec();
+        let tolerance = cache.tolerance;
+        let lbfgs_memory = cache.lbfgs.buffer_size();
+        let result = PANOCOptimizer::new(problem, cache)
+            .with_config(&config)?
+            .solve(u);
+        let session = PanocSession {
+            parameter: parameter.to_vec(),
+            initial_guess,
+            tolerance,
+            lbfgs_memory,
+            config,
+            solution: u.to_vec(),
+            status: result
+                .as_ref()
+                .map(Clone::clone)
+                .map_err(ToString::to_string),
+        };
+        Ok((result, session))
+    }
+
+    /// Solves the problem again, with the recorded initial guess and settings
+    /// and a new cache
+    ///
+    /// The problem must be constructed with the recorded parameter. Returns the
+    /// solution and the solver status, which can be compared with the recorded
+    /// ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recorded settings are invalid or if the solver
+    /// fails
+    pub fn replay<'a, GradientType, ConstraintType, CostType>(
+        &self,
+        problem: Problem<'a, GradientType, ConstraintType, CostType>,
+    ) -> Result<(Vec<f64>, SolverStatus), SolverError>
+    where
+        GradientType: FnMut(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
+        CostType: FnMut(&[f64], &mut f64) -> FunctionCallResult + 'a,
+        ConstraintType: constraints::Constraint + 'a,
+    {
+        let mut u = self.initial_guess.clone();
+        let cache = PANOCCache::new(u.len(), self.tolerance, self.lbfgs_memory)?;
+        let status = PANOCOptimizer::new_owned(problem, cache)
+            .with_config(&self.config)?
+            .solve(&mut u)?;
+        Ok((u, status))
+    }
+
+    /// Saves the session to a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Loads a session from a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid session
+    pub fn load(path: impl AsRef<Path>) -> io::Result<PanocSession> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
@@ -0,0 +1,324 @@
+//! Semi-smooth Newton refinement of PANOC solutions
+//!
+use crate::{
+    constraints,
+    core::{OracleCalls, Problem},
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+
+const DEFAULT_MAX_ITER: usize = 20;
+/// Maximum number of halvings of the Newton step
+const MAX_BACKTRACKING: usize = 10;
+/// Required relative decrease of the norm of the residual (per unit step)
+const SUFFICIENT_DECREASE: f64 = 1e-4;
+
+/// Cache of the semi-smooth Newton stage of PANOC
+///
+/// When the norm of $\gamma$ times the fixed-point residual of PANOC,
+/// $R(u) = u - \Pi_U(u - \gamma\nabla f(u))$, drops below the *switch
+/// tolerance*, `PANOCOptimizer` (see `PANOCOptimizer::with_semismooth_newton`)
+/// switches to a semi-smooth Newton method for the equation $R(u) = 0$. The
+/// generalized Jacobian of $R$ is $I - P(I - \gamma\nabla^2 f(u))$, where $P$
+/// is the diagonal matrix whose $i$-th diagonal element is 0 if $u_i - \gamma
+/// \nabla_i f(u)$ is changed by the projection (that is, if the $i$-th bound
+/// is active) and 1 otherwise. The Newton system reduces to a system on the
+/// free variables, which is solved with (truncated) conjugate gradient
+/// iterations, using Hessian-vector products that are approximated by
+/// finite differences of the gradient; a backtracking line search on the
+/// norm of $R$ safeguards the iterations.
+///
+/// This generalized Jacobian is exact when $U$ is a box (`Rectangle`) or
+/// $U = \mathbb{R}^n$ (`NoConstraints`). If the Newton iterations fail to
+/// decrease the residual, PANOC resumes from where it stopped.
+///
+#[derive(Debug)]
+pub struct SemismoothNewtonCache<T = f64> {
+    pub(crate) x: Vec<T>,
+    x_previous: Vec<T>,
+    pub(crate) gradient: Vec<T>,
+    pub(crate) x_bar: Vec<T>,
+    residual: Vec<T>,
+    active: Vec<bool>,
+    direction: Vec<T>,
+    cg_residual: Vec<T>,
+    cg_direction: Vec<T>,
+    hessian_product: Vec<T>,
+    work_x: Vec<T>,
+    work_gradient: Vec<T>,
+    pub(crate) switch_tolerance: T,
+    max_iter: usize,
+    iterations: usize,
+    pub(crate) norm_residual: T,
+}
+
+impl<T: OpEnFloat> SemismoothNewtonCache<T> {
+    /// Constructs a new cache for the semi-smooth Newton stage
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem_size`: number of decision variables
+    /// - `switch_tolerance`: PANOC switches to semi-smooth Newton iterations
+    ///   when the norm of $\gamma$ times its fixed-point residual drops below
+    ///   `switch_tolerance`
+    ///
+    /// ## Memory allocation
+    ///
+    /// This method allocates `11*problem_size` floats and `problem_size` booleans
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `problem_size` is zero or
+    /// `switch_tolerance` is not positive
+    ///
+    pub fn new(problem_size: usize, switch_tolerance: T) -> Result<Self, SolverError> {
+        ensure(problem_size > 0, "problem_size", "must be positive")?;
+        ensure(
+            switch_tolerance > T::zero(),
+            "switch_tolerance",
+            "must be positive",
+        )?;
+        let zeros = || vec![T::zero(); problem_size];
+        Ok(SemismoothNewtonCache {
+            x: zeros(),
+            x_previous: zeros(),
+            gradient: zeros(),
+            x_bar: zeros(),
+            residual: zeros(),
+            active: vec![false; problem_size],
+            direction: zeros(),
+            cg_residual: zeros(),
+            cg_direction: zeros(),
+            hessian_product: zeros(),
+            work_x: zeros(),
+            work_gradient: zeros(),
+            switch_tolerance,
+            max_iter: DEFAULT_MAX_ITER,
+            iterations: 0,
+            norm_residual: T::infinity(),
+        })
+    }
+
+    /// Sets the maximum number of Newton iterations (the default is 20)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `max_iter` is zero
+    ///
+    pub fn with_max_iter(mut self, max_iter: usize) -> Result<Self, SolverError> {
+        ensure(max_iter > 0, "max_iter", "must be positive")?;
+        self.max_iter = max_iter;
+        Ok(self)
+    }
+
+    /// Number of decision variables
+    pub fn problem_size(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Number of Newton iterations of the last solve (0 if PANOC did not
+    /// switch to the Newton stage)
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.iterations = 0;
+        self.norm_residual = T::infinity();
+    }
+
+    /// Computes the gradient at `x`, `x_bar` $= \Pi_U(x - \gamma\nabla f(x))$,
+    /// the active set and the residual, $R(x) = x - \bar{x}$; returns the
+    /// norm of the residual
+    fn compute_residual<G, C, F>(
+        &mut self,
+        problem: &mut Problem<'_, G, C, F, T>,
+        gamma: T,
+        oracle_calls: &mut OracleCalls,
+    ) -> Result<T, SolverError>
+    where
+        G: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+        F: FnMut(&[T], &mut T) -> FunctionCallResult,
+        C: constraints::Constraint<T>,
+    {
+        oracle_calls.gradient += 1;
+        (problem.gradf)(&self.x, &mut self.gradient)?;
+        for i in 0..self.x.len() {
+            self.residual[i] = self.x[i] - gamma * self.gradient[i];
+        }
+        self.x_bar.copy_from_slice(&self.residual);
+        problem.constraints.project(&mut self.x_bar);
+        oracle_calls.projections += 1;
+        for i in 0..self.x.len() {
+            self.active[i] = self.x_bar[i] != self.residual[i];
+            self.residual[i] = self.x[i] - self.x_bar[i];
+        }
+        Ok(matrix_operations::norm2(&self.residual))
+    }
+
+    /// Approximates the product of the Hessian at `x` with `cg_direction`
+    /// by finite differences of the gradient and writes it into
+    /// `hessian_product`
+    fn hessian_vector_product<G, C, F>(
+        &mut self,
+        problem: &mut Problem<'_, G, C, F, T>,
+        oracle_calls: &mut OracleCalls,
+    ) -> FunctionCallResult
+    where
+        G: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+        F: FnMut(&[T], &mut T) -> FunctionCallResult,
+        C: constraints::Constraint<T>,
+    {
+        let norm_v = matrix_operations::norm2(&self.cg_direction);
+        if norm_v == T::zero() {
+            self.hessian_product.iter_mut().for_each(|h| *h = T::zero());
+            return Ok(());
+        }
+        let epsilon =
+            T::epsilon().sqrt() * T::one().max(matrix_operations::norm2(&self.x)) / norm_v;
+        for i in 0..self.x.len() {
+            self.work_x[i] = self.x[i] + epsilon * self.cg_direction[i];
+        }
+        oracle_calls.gradient += 1;
+        (problem.gradf)(&self.work_x, &mut self.work_gradient)?;
+        for i in 0..self.x.len() {
+            self.hessian_product[i] = (self.work_gradient[i] - self.gradient[i]) / epsilon;
+        }
+        Ok(())
+    }
+
+    /// Computes the Newton direction at `x` (after `compute_residual`)
+    ///
+    /// The direction is $d_i = -R_i$ on the active set, $A$, and, on the free
+    /// variables, $F$, the (approximate) solution of the system
+    /// $\nabla^2_{FF} f(x) d_F = -\nabla_F f(x) - \nabla^2_{FA} f(x) d_A$,
+    /// computed with conjugate gradient iterations, which are truncated at
+    /// directions of nonpositive curvature
+    fn compute_direction<G, C, F>(
+        &mut self,
+        problem: &mut Problem<'_, G, C, F, T>,
+        oracle_calls: &mut OracleCalls,
+    ) -> FunctionCallResult
+    where
+        G: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+        F: FnMut(&[T], &mut T) -> FunctionCallResult,
+        C: constraints::Constraint<T>,
+    {
+        let n = self.x.len();
+        let num_free = self.active.iter().filter(|&&active| !active).count();
+        // d = (-R_A, 0), and cg_residual = b = -g_F - H_FA d_A
+        for i in 0..n {
+            let d_i = if self.active[i] {
+                -self.residual[i]
+            } else {
+                T::zero()
+            };
+            self.direction[i] = d_i;
+            self.cg_direction[i] = d_i;
+        }
+        if num_free < n {
+            self.hessian_vector_product(problem, oracle_calls)?;
+        } else {
+            self.hessian_product.iter_mut().for_each(|h| *h = T::zero());
+        }
+        let mut rho = T::zero();
+        for i in 0..n {
+            self.cg_residual[i] = if self.active[i] {
+                T::zero()
+            } else {
+                -self.gradient[i] - self.hessian_product[i]
+            };
+            self.cg_direction[i] = self.cg_residual[i];
+            rho += self.cg_residual[i] * self.cg_residual[i];
+        }
+        // forcing term of the inexact Newton method
+        let norm_residual = matrix_operations::norm2(&self.residual);
+        let cg_tolerance = T::from_f64(0.1).min(norm_residual.sqrt()) * rho.sqrt();
+        for k in 0..num_free {
+            if rho.sqrt() <= cg_tolerance {
+                break;
+            }
+            self.hessian_vector_product(problem, oracle_calls)?;
+            let mut curvature = T::zero();
+            for i in 0..n {
+                if self.active[i] {
+                    self.hessian_product[i] = T::zero();
+                }
+                curvature += self.cg_direction[i] * self.hessian_product[i];
+            }
+            if curvature <= T::zero() {
+                if k == 0 {
+                    // steepest descent on the free variables
+                    for i in 0..n {
+                        if !self.active[i] {
+                            self.direction[i] = self.cg_residual[i];
+                        }
+                    }
+                }
+                break;
+            }
+            let alpha = rho / curvature;
+            let mut rho_next = T::zero();
+            for i in 0..n {
+                self.direction[i] += alpha * self.cg_direction[i];
+                self.cg_residual[i] -= alpha * self.hessian_product[i];
+                rho_next += self.cg_residual[i] * self.cg_residual[i];
+            }
+            let beta = rho_next / rho;
+            rho = rho_next;
+            for i in 0..n {
+                self.cg_direction[i] = self.cg_residual[i] + beta * self.cg_direction[i];
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs semi-smooth Newton iterations starting from `x` until the norm of
+    /// the residual drops below `tolerance`
+    ///
+    /// Returns `Ok(true)` if the iterations converged and `Ok(false)` if they
+    /// failed to reduce the residual or reached the maximum number of
+    /// iterations. On success, `x_bar` is the solution, `gradient` is the
+    /// gradient at `x` and `norm_residual` is the norm of the residual.
+    pub(crate) fn solve<G, C, F>(
+        &mut self,
+        problem: &mut Problem<'_, G, C, F, T>,
+        gamma: T,
+        tolerance: T,
+        oracle_calls: &mut OracleCalls,
+    ) -> Result<bool, SolverError>
+    where
+        G: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+        F: FnMut(&[T], &mut T) -> FunctionCallResult,
+        C: constraints::Constraint<T>,
+    {
+        self.iterations = 0;
+        self.norm_residual = self.compute_residual(problem, gamma, oracle_calls)?;
+        let sigma = T::from_f64(SUFFICIENT_DECREASE);
+        while self.norm_residual > tolerance || !self.norm_residual.is_finite() {
+            if self.iterations == self.max_iter || !self.norm_residual.is_finite() {
+                return Ok(false);
+            }
+            self.iterations += 1;
+            self.compute_direction(problem, oracle_calls)?;
+            self.x_previous.copy_from_slice(&self.x);
+            let mut step = T::one();
+            let mut accepted = false;
+            for _ in 0..MAX_BACKTRACKING {
+                for i in 0..self.x.len() {
+                    self.x[i] = self.x_previous[i] + step * self.direction[i];
+                }
+                let norm_residual = self.compute_residual(problem, gamma, oracle_calls)?;
+                if norm_residual <= (T::one() - sigma * step) * self.norm_residual {
+                    self.norm_residual = norm_residual;
+                    accepted = true;
+                    break;
+                }
+                step /= T::from_f64(2.0);
+            }
+            if !accepted {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
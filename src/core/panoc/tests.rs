@@ -1,15 +1,15 @@
 use crate::core::panoc::panoc_engine::PANOCEngine;
 use crate::core::panoc::*;
-use crate::core::*;
+use crate::core::{lbfgs::InitialScaling, *};
 use crate::{mocks, FunctionCallResult};
 
 const N_DIM: usize = 2;
 #[test]
 fn t_panoc_init() {
     let radius = 0.2;
-    let ball = constraints::Ball2::new(None, radius);
+    let ball = constraints::Ball2::new(None, radius).unwrap();
     let problem = Problem::new(&ball, mocks::my_gradient, mocks::my_cost);
-    let mut panoc_cache = PANOCCache::new(N_DIM, 1e-6, 5);
+    let mut panoc_cache = PANOCCache::new(N_DIM, 1e-6, 5).unwrap();
 
     {
         let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
@@ -39,8 +39,8 @@ fn t_panoc_init() {
 fn print_panoc_engine<GradientType, ConstraintType, CostType>(
     panoc_engine: &PANOCEngine<GradientType, ConstraintType, CostType>,
 ) where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
+    GradientType: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    CostType: FnMut(&[f64], &mut f64) -> FunctionCallResult,
     ConstraintType: constraints::Constraint,
 {
     println!("> fpr       = {:?}", &panoc_engine.cache.gamma_fpr);
@@ -53,10 +53,10 @@ fn print_panoc_engine<GradientType, ConstraintType, CostType>(
 
 #[test]
 fn t_test_panoc_basic() {
-    let bounds = constraints::Ball2::new(None, 0.2);
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
     let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
     let tolerance = 1e-9;
-    let mut panoc_cache = PANOCCache::new(2, tolerance, 5);
+    let mut panoc_cache = PANOCCache::new(2, tolerance, 5).unwrap();
     let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
     let mut u = [0.0, 0.0];
@@ -82,7 +82,7 @@ fn t_test_panoc_basic() {
 #[test]
 fn t_test_panoc_hard() {
     let radius: f64 = 0.05;
-    let bounds = constraints::Ball2::new(None, radius);
+    let bounds = constraints::Ball2::new(None, radius).unwrap();
     let problem = Problem::new(
         &bounds,
         mocks::hard_quadratic_gradient,
@@ -91,7 +91,7 @@ fn t_test_panoc_hard() {
     let n: usize = 3;
     let lbfgs_memory: usize = 10;
     let tolerance_fpr: f64 = 1e-12;
-    let mut panoc_cache = PANOCCache::new(n, tolerance_fpr, lbfgs_memory);
+    let mut panoc_cache = PANOCCache::new(n, tolerance_fpr, lbfgs_memory).unwrap();
     let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
 
     let mut u = [-20., 10., 0.2];
@@ -127,9 +127,12 @@ fn t_test_panoc_rosenbrock() {
         *c = mocks::rosenbrock_cost(a_param, b_param, u);
         Ok(())
     };
-    let bounds = constraints::Ball2::new(None, 1.0);
+    let bounds = constraints::Ball2::new(None, 1.0).unwrap();
     let problem = Problem::new(&bounds, cost_gradient, cost_function);
-    let mut panoc_cache = PANOCCache::new(2, tolerance, 2).with_cbfgs_parameters(2.0, 1e-6, 1e-12);
+    let mut panoc_cache = PANOCCache::new(2, tolerance, 2)
+        .unwrap()
+        .with_cbfgs_parameters(2.0, 1e-6, 1e-12)
+        .unwrap();
     let mut panoc_engine = PANOCEngine::new(problem, &mut panoc_cache);
     let mut u_solution = [-1.5, 0.9];
     panoc_engine.init(&mut u_solution).unwrap();
@@ -144,7 +147,7 @@ fn t_test_panoc_rosenbrock() {
 #[test]
 fn t_zero_gamma_l() {
     let tolerance = 1e-8;
-    let mut panoc_cache = PANOCCache::new(1, tolerance, 5);
+    let mut panoc_cache = PANOCCache::new(1, tolerance, 5).unwrap();
     let u = &mut [1e6];
 
     // Define the cost function and its gradient.
@@ -164,7 +167,9 @@ fn t_zero_gamma_l() {
     // Problem statement.
     let problem = Problem::new(&bounds, df, f);
 
-    let mut panoc_engine = PANOCOptimizer::new(problem, &mut panoc_cache).with_max_iter(100);
+    let mut panoc_engine = PANOCOptimizer::new(problem, &mut panoc_cache)
+        .with_max_iter(100)
+        .unwrap();
 
     // Invoke the solver.
     let _status = panoc_engine.solve(u);
@@ -177,7 +182,7 @@ fn t_zero_gamma_l() {
 #[test]
 fn t_zero_gamma_huber() {
     let tolerance = 1e-8;
-    let mut panoc_cache = PANOCCache::new(1, tolerance, 10);
+    let mut panoc_cache = PANOCCache::new(1, tolerance, 10).unwrap();
     let u = &mut [1e6];
     let huber_delta = 1e-6;
 
@@ -202,12 +207,14 @@ fn t_zero_gamma_huber() {
         Ok(())
     };
 
-    let bounds = constraints::BallInf::new(None, 10000.);
+    let bounds = constraints::BallInf::new(None, 10000.).unwrap();
 
     // Problem statement.
     let problem = Problem::new(&bounds, df, huber_norm);
 
-    let mut panoc_engine = PANOCOptimizer::new(problem, &mut panoc_cache).with_max_iter(100);
+    let mut panoc_engine = PANOCOptimizer::new(problem, &mut panoc_cache)
+        .with_max_iter(100)
+        .unwrap();
 
     // Invoke the solver.
     let _status = panoc_engine.solve(u);
@@ -216,3 +223,936 @@ fn t_zero_gamma_huber() {
     println!("iters = {}", panoc_cache.iteration);
     assert!(panoc_cache.norm_gamma_fpr <= tolerance);
 }
+
+#[test]
+fn t_panoc_single_precision() {
+    let tolerance = 1e-4_f32;
+    let mut panoc_cache = PANOCCache::<f32>::new(2, tolerance, 5).unwrap();
+    let df = |u: &[f32], grad: &mut [f32]| -> FunctionCallResult {
+        grad[0] = u[0] + u[1] + 1.0;
+        grad[1] = u[0] + 2. * u[1] - 1.0;
+        Ok(())
+    };
+    let f = |u: &[f32], c: &mut f32| -> FunctionCallResult {
+        *c = 0.5 * u[0] * u[0] + u[1] * u[1] + u[0] * u[1] + u[0] - u[1] + 3.0;
+        Ok(())
+    };
+    let bounds = constraints::Ball2::new(None, 0.2_f32).unwrap();
+    let problem = Problem::new(&bounds, df, f);
+    let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
+        .with_max_iter(100)
+        .unwrap();
+    let mut u = [0.75_f32, -1.4];
+    let status = panoc.solve(&mut u).unwrap();
+
+    assert!(status.has_converged());
+    assert!(status.norm_fpr() < tolerance as f64);
+    assert!(crate::matrix_operations::norm2(&u) <= 0.2 + 1e-6);
+    let u_f64 = [u[0] as f64, u[1] as f64];
+    unit_test_utils::assert_nearly_equal_array(
+        &mocks::SOLUTION_A,
+        &u_f64,
+        1e-4,
+        1e-5,
+        "solution (f32)",
+    );
+}
+
+#[test]
+fn t_panoc_with_config() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let mut panoc_cache = PANOCCache::new(2, 1e-6, 5).unwrap();
+    let config = PanocConfig::new()
+        .with_tolerance(1e-9)
+        .with_akkt_tolerance(1e-7)
+        .with_max_iter(3)
+        .with_trust_region(0.5);
+
+    let mut u = [0.0, 0.0];
+    let status = {
+        let mut panoc = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_config(&config)
+            .unwrap();
+        panoc.solve(&mut u).unwrap()
+    };
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+    assert_eq!(3, status.iterations());
+    assert_eq!(1e-9, panoc_cache.tolerance);
+    assert_eq!(Some(1e-7), panoc_cache.akkt_tolerance);
+    assert!(panoc_cache.trust_region_radius().is_some());
+}
+
+#[test]
+fn t_panoc_step_tolerance() {
+    // a flat cost, f(u) = (u1^4 + u2^4) / 4, whose FPR is tiny long before
+    // the iterates stall
+    let df = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+        grad.iter_mut().zip(u).for_each(|(g, &ui)| *g = ui.powi(3));
+        Ok(())
+    };
+    let f = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = 0.25 * u.iter().map(|ui| ui.powi(4)).sum::<f64>();
+        Ok(())
+    };
+    let bounds = constraints::NoConstraints::new();
+    let mut panoc_cache = PANOCCache::new(2, 1e-30, 5).unwrap();
+
+    let mut u = [1.0, -0.5];
+    let reference = PANOCOptimizer::new(Problem::new(&bounds, df, f), &mut panoc_cache)
+        .with_max_iter(200)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+
+    // the iterates converge sublinearly, so the FPR criterion is not met
+    // within 200 iterations, whereas the step criterion is
+    for (relative, absolute) in [(0.0, 1e-3), (1e-2, 0.0)] {
+        let mut u = [1.0, -0.5];
+        let status = PANOCOptimizer::new(Problem::new(&bounds, df, f), &mut panoc_cache)
+            .with_max_iter(200)
+            .unwrap()
+            .with_step_tolerance(relative, absolute)
+            .unwrap()
+            .solve(&mut u)
+            .unwrap();
+        assert!(status.has_converged());
+        assert!(status.iterations() < reference.iterations());
+        assert!(status.norm_fpr() > 1e-30);
+        assert!(crate::matrix_operations::norm2(&u) < 1e-2);
+    }
+
+    // the tolerances are stored in the cache, so they carry over to later
+    // optimizers which use the same cache
+    let mut u = [1.0, -0.5];
+    let status = PANOCOptimizer::new(Problem::new(&bounds, df, f), &mut panoc_cache)
+        .with_max_iter(200)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+
+    assert!(
+        PANOCOptimizer::new(Problem::new(&bounds, df, f), &mut panoc_cache)
+            .with_step_tolerance(-1e-3, 1e-6)
+            .is_err()
+    );
+    assert!(
+        PANOCOptimizer::new(Problem::new(&bounds, df, f), &mut panoc_cache)
+            .with_step_tolerance(0.0, 0.0)
+            .is_err()
+    );
+}
+
+#[test]
+fn t_panoc_default_config() {
+    let config = PanocConfig::default();
+    assert_eq!(None, config.tolerance);
+    assert_eq!(None, config.akkt_tolerance);
+    assert_eq!(100, config.max_iter);
+    assert_eq!(None, config.max_duration);
+    assert_eq!(None, config.trust_region_radius);
+    assert_eq!(None, config.sr1_memory);
+}
+
+#[test]
+fn t_panoc_history() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let mut panoc_cache = PANOCCache::new(2, 1e-9, 5).unwrap();
+
+    let mut u = [0.0, 0.0];
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+        .with_history(true)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.gradient_norm().unwrap().is_finite());
+    let cost_history = status.cost_history().unwrap();
+    let fpr_history = status.fpr_history().unwrap();
+    assert_eq!(status.iterations() + 1, cost_history.len());
+    assert_eq!(cost_history.len(), fpr_history.len());
+    assert_eq!(status.norm_fpr(), *fpr_history.last().unwrap());
+    let gamma_history = status.gamma_history().unwrap();
+    assert_eq!(cost_history.len(), gamma_history.len());
+    assert!(gamma_history.windows(2).all(|g| g[1] <= g[0]));
+    let trace = status.trace().unwrap();
+    assert_eq!(cost_history, trace.cost());
+    assert_eq!(fpr_history, trace.fpr());
+    assert_eq!(Some(gamma_history), trace.gamma());
+    // the first step is the forward-backward step; tau is in [0, 1]
+    let tau_history = status.tau_history().unwrap();
+    assert_eq!(cost_history.len(), tau_history.len());
+    assert_eq!(0.0, tau_history[0]);
+    assert!(tau_history.iter().all(|&tau| (0.0..=1.0).contains(&tau)));
+    let direction_norm_history = status.direction_norm_history().unwrap();
+    assert!(direction_norm_history
+        .iter()
+        .all(|d| d.is_finite() && *d >= 0.0));
+    assert_eq!(Some(tau_history), trace.tau());
+    assert_eq!(Some(direction_norm_history), trace.direction_norm());
+
+    // the history is not recorded by default
+    let mut u = [0.0, 0.0];
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.cost_history().is_none());
+    assert!(status.fpr_history().is_none());
+    assert!(status.trace().is_none());
+}
+
+#[test]
+fn t_panoc_owned_cache() {
+    type Gradient = fn(&[f64], &mut [f64]) -> FunctionCallResult;
+    type Cost = fn(&[f64], &mut f64) -> FunctionCallResult;
+
+    // an optimizer which owns its cache and constraints can be stored in a struct
+    struct Controller {
+        optimizer: PANOCOptimizer<'static, Gradient, constraints::Ball2<'static>, Cost>,
+    }
+
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let mut panoc_cache = PANOCCache::new(2, 1e-8, 5).unwrap();
+    let mut u_borrowed = [0.0, 0.0];
+    let status_borrowed = PANOCOptimizer::new(problem, &mut panoc_cache)
+        .solve(&mut u_borrowed)
+        .unwrap();
+
+    let problem = Problem::new_owned(
+        constraints::Ball2::new(None, 0.2).unwrap(),
+        mocks::my_gradient as Gradient,
+        mocks::my_cost as Cost,
+    );
+    let mut controller = Controller {
+        optimizer: PANOCOptimizer::from_dimensions(problem, 2, 1e-8, 5).unwrap(),
+    };
+    for _ in 0..2 {
+        let mut u = [0.0, 0.0];
+        let status = controller.optimizer.solve(&mut u).unwrap();
+        assert_eq!(u_borrowed, u);
+        assert_eq!(status_borrowed.iterations(), status.iterations());
+    }
+
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let mut u = [0.0, 0.0];
+    let status = PANOCOptimizer::new_owned(problem, PANOCCache::new(2, 1e-8, 5).unwrap())
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(u_borrowed, u);
+    assert_eq!(status_borrowed.iterations(), status.iterations());
+
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    assert!(matches!(
+        PANOCOptimizer::from_dimensions(problem, 2, 0.0, 5),
+        Err(crate::SolverError::InvalidParameter {
+            name: "tolerance",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn t_panoc_send() {
+    fn assert_send_sync<S: Send + Sync>() {}
+    assert_send_sync::<PANOCCache>();
+    assert_send_sync::<PANOCCache<f32>>();
+
+    // an optimizer which owns its cache is solved on a worker thread
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let mut u_reference = [0.0, 0.0];
+    let mut panoc_cache = PANOCCache::new(N_DIM, 1e-8, 5).unwrap();
+    PANOCOptimizer::new(
+        Problem::new(&bounds, mocks::my_gradient, mocks::my_cost),
+        &mut panoc_cache,
+    )
+    .solve(&mut u_reference)
+    .unwrap();
+    let problem = Problem::new_owned(
+        constraints::Ball2::new(None, 0.2).unwrap(),
+        mocks::my_gradient,
+        mocks::my_cost,
+    );
+    let cache = PANOCCache::new(N_DIM, 1e-8, 5).unwrap();
+    let mut optimizer = PANOCOptimizer::new_owned(problem, cache);
+    let u = std::thread::spawn(move || {
+        let mut u = [0.0, 0.0];
+        optimizer.solve(&mut u).unwrap();
+        u
+    })
+    .join()
+    .unwrap();
+    assert_eq!(u_reference, u);
+
+    // ... and so is an optimizer which borrows a clock
+    let clock = ManualClock::new();
+    let mut optimizer = PANOCOptimizer::new(
+        Problem::new(&bounds, mocks::my_gradient, mocks::my_cost),
+        &mut panoc_cache,
+    )
+    .with_clock(&clock);
+    let mut u = [0.0, 0.0];
+    std::thread::scope(|scope| scope.spawn(|| optimizer.solve(&mut u)).join())
+        .unwrap()
+        .unwrap();
+    assert_eq!(u_reference, u);
+}
+
+#[test]
+fn t_panoc_fnmut_oracles() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let mut u_reference = [0.0, 0.0];
+    let mut panoc_cache = PANOCCache::new(N_DIM, 1e-8, 5).unwrap();
+    let status_reference = PANOCOptimizer::new(
+        Problem::new(&bounds, mocks::my_gradient, mocks::my_cost),
+        &mut panoc_cache,
+    )
+    .solve(&mut u_reference)
+    .unwrap();
+
+    // the oracles own a workspace and count their calls
+    let (mut cost_calls, mut gradient_calls) = (0, 0);
+    let mut workspace = vec![0.0; N_DIM];
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        cost_calls += 1;
+        mocks::my_cost(u, c)
+    };
+    let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+        gradient_calls += 1;
+        mocks::my_gradient(u, &mut workspace)?;
+        grad.copy_from_slice(&workspace);
+        Ok(())
+    };
+    let mut u = [0.0, 0.0];
+    let status = PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut panoc_cache)
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(u_reference, u);
+    assert_eq!(status_reference.iterations(), status.iterations());
+    assert_eq!(status.oracle_calls().cost(), cost_calls);
+    assert_eq!(status.oracle_calls().gradient(), gradient_calls);
+}
+
+#[cfg(feature = "session")]
+#[test]
+fn t_panoc_session_record_replay() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let mut panoc_cache = PANOCCache::new(2, 1e-8, 7).unwrap();
+    let mut u = [0.1, -0.3];
+    let config = PanocConfig::new().with_max_iter(50);
+    let (status, session) = PanocSession::record(
+        Problem::new(&bounds, mocks::my_gradient, mocks::my_cost),
+        &[1.5],
+        &mut u,
+        &mut panoc_cache,
+        &config,
+    )
+    .unwrap();
+    let status = status.unwrap();
+    assert_eq!(vec![1.5], session.parameter);
+    assert_eq!(vec![0.1, -0.3], session.initial_guess);
+    assert_eq!(7, session.lbfgs_memory);
+    assert_eq!(1e-8, session.tolerance);
+    assert_eq!(50, session.config.max_iter);
+    assert!(session.config.record_history);
+    assert_eq!(u.to_vec(), session.solution);
+    assert_eq!(Ok(status.clone()), session.status);
+    assert!(status.fpr_history().is_some_and(|h| !h.is_empty()));
+
+    let path = std::env::temp_dir().join(format!("open_session_{}.json", std::process::id()));
+    session.save(&path).unwrap();
+    let loaded_session = PanocSession::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(session, loaded_session);
+
+    let (solution, replayed_status) = loaded_session
+        .replay(Problem::new(&bounds, mocks::my_gradient, mocks::my_cost))
+        .unwrap();
+    assert_eq!(session.solution, solution);
+    assert_eq!(status.iterations(), replayed_status.iterations());
+    assert_eq!(status.cost_history(), replayed_status.cost_history());
+    assert_eq!(status.fpr_history(), replayed_status.fpr_history());
+}
+
+#[cfg(feature = "session")]
+#[test]
+fn t_panoc_session_failed_solve() {
+    let bounds = constraints::NoConstraints::new();
+    let mut panoc_cache = PANOCCache::new(2, 1e-8, 5).unwrap();
+    let mut u = [0.0, 0.0];
+    let (status, session) = PanocSession::record(
+        Problem::new(
+            &bounds,
+            mocks::my_gradient,
+            |_u: &[f64], _c: &mut f64| -> FunctionCallResult {
+                Err(SolverError::user("simulator crashed"))
+            },
+        ),
+        &[],
+        &mut u,
+        &mut panoc_cache,
+        &PanocConfig::new(),
+    )
+    .unwrap();
+    assert!(status.is_err());
+    assert!(session
+        .status
+        .as_ref()
+        .unwrap_err()
+        .contains("simulator crashed"));
+}
+
+#[test]
+fn t_panoc_progress() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let mut panoc_cache = PANOCCache::new(2, 1e-8, 5).unwrap();
+    let mut u = [0.0, 0.0];
+    let mut events = Vec::new();
+    {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+        let status = PANOCOptimizer::new(problem, &mut panoc_cache)
+            .with_progress(Progress::from_sender(sender, 3))
+            .solve(&mut u)
+            .unwrap();
+        events.extend(receiver.iter());
+        assert_eq!(status.iterations() / 3 + 2, events.len());
+        assert!(matches!(
+            events.last(),
+            Some(ProgressEvent::Finished { exit_status: ExitStatus::Converged, iterations, .. })
+                if *iterations == status.iterations()
+        ));
+    }
+    assert!(matches!(
+        events[1],
+        ProgressEvent::Iteration { iteration: 3, fpr, cost, tau, forward_backward, .. }
+            if fpr > 0.0 && cost.is_finite() && forward_backward == (tau == 0.0)
+    ));
+
+    // a callback which only counts the events; no event is emitted at the
+    // iterations if the interval is zero
+    let count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = count.clone();
+    let mut u = [0.0, 0.0];
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    PANOCOptimizer::new(problem, &mut panoc_cache)
+        .with_progress(Progress::from_callback(
+            move |_event| {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            },
+            0,
+        ))
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(1, count.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[test]
+fn t_panoc_semismooth_newton() {
+    let bounds = constraints::Rectangle::new(Some(&[-1.0, -1.0, 0.05]), Some(&[1.0; 3])).unwrap();
+    let problem = || {
+        Problem::new(
+            &bounds,
+            mocks::hard_quadratic_gradient,
+            mocks::hard_quadratic_cost,
+        )
+    };
+    let tolerance = 1e-11;
+
+    let mut u_panoc = [0.0; 3];
+    let status_panoc = PANOCOptimizer::from_dimensions(problem(), 3, tolerance, 3)
+        .unwrap()
+        .with_max_iter(100_000)
+        .unwrap()
+        .solve(&mut u_panoc)
+        .unwrap();
+    assert!(status_panoc.has_converged());
+
+    let mut newton_cache = SemismoothNewtonCache::new(3, 1e-3).unwrap();
+    let mut u = [0.0; 3];
+    let status = PANOCOptimizer::from_dimensions(problem(), 3, tolerance, 3)
+        .unwrap()
+        .with_max_iter(100_000)
+        .unwrap()
+        .with_semismooth_newton(&mut newton_cache)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.norm_fpr() < tolerance);
+    assert!(status.iterations() < status_panoc.iterations());
+    unit_test_utils::assert_nearly_equal_array(&u_panoc, &u, 1e-8, 1e-9, "u");
+    // the lower bound of u[2] is active at the solution
+    assert_eq!(0.05, u[2]);
+    let newton_iterations = newton_cache.iterations();
+    assert!(newton_iterations > 0 && newton_iterations <= 5);
+}
+
+#[test]
+fn t_panoc_semismooth_newton_errors() {
+    let bounds = constraints::NoConstraints::new();
+    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+    let mut newton_cache = SemismoothNewtonCache::new(3, 1e-3).unwrap();
+    let result = PANOCOptimizer::from_dimensions(problem, N_DIM, 1e-8, 3)
+        .unwrap()
+        .with_semismooth_newton(&mut newton_cache);
+    assert!(matches!(
+        result,
+        Err(SolverError::DimensionMismatch {
+            name: "newton_cache",
+            expected: 2,
+            actual: 3,
+        })
+    ));
+    assert!(SemismoothNewtonCache::<f64>::new(0, 1e-3).is_err());
+    assert!(SemismoothNewtonCache::new(2, 0.0).is_err());
+    assert!(SemismoothNewtonCache::new(2, 1e-3)
+        .unwrap()
+        .with_max_iter(0)
+        .is_err());
+}
+
+#[test]
+fn t_panoc_trust_region() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    // gradient with a (small) high-frequency error
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        g.iter_mut()
+            .zip(u.iter())
+            .for_each(|(g_i, u_i)| *g_i += 1e-3 * (1e4 * u_i).sin());
+        Ok(())
+    };
+    let bounds = constraints::Ball2::new(None, 1.0).unwrap();
+    let mut solutions = vec![];
+    for trust_region in [false, true] {
+        let mut cache = PANOCCache::new(2, 1e-6, 5).unwrap();
+        let mut optimizer = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+            .with_max_iter(5000)
+            .unwrap();
+        if trust_region {
+            optimizer = optimizer.with_trust_region(1.0).unwrap();
+        }
+        let mut u = [-1.5, 0.9];
+        let status = optimizer.solve(&mut u).unwrap();
+        assert!(status.has_converged());
+        if trust_region {
+            // at most two candidates per iteration (and two gradients in `init`)
+            assert!(status.oracle_calls().gradient() <= 2 * status.iterations() + 2);
+            assert!(cache
+                .trust_region_radius()
+                .is_some_and(|radius| radius > 0.0));
+        } else {
+            assert_eq!(None, cache.trust_region_radius());
+        }
+        solutions.push(u);
+    }
+    unit_test_utils::assert_nearly_equal_array(&solutions[0], &solutions[1], 1e-4, 1e-5, "u");
+
+    let problem = Problem::new(&bounds, grad, cost);
+    assert!(PANOCOptimizer::from_dimensions(problem, 2, 1e-6, 5)
+        .unwrap()
+        .with_trust_region(0.0)
+        .is_err());
+}
+
+#[test]
+fn t_panoc_sr1() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    // the constraint u[1] <= 0.5 is active at the solution
+    let bounds = constraints::Rectangle::new(Some(&[-2.0, -2.0]), Some(&[2.0, 0.5])).unwrap();
+    let mut solutions = vec![];
+    for sr1 in [false, true] {
+        let mut cache = PANOCCache::new(2, 1e-10, 5).unwrap();
+        let mut optimizer = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+            .with_max_iter(2000)
+            .unwrap();
+        if sr1 {
+            optimizer = optimizer.with_sr1(5).unwrap();
+        }
+        let mut u = [-1.2, 1.0];
+        let status = optimizer.solve(&mut u).unwrap();
+        assert!(status.has_converged());
+        solutions.push(u);
+    }
+    assert_eq!(0.5, solutions[1][1]);
+    unit_test_utils::assert_nearly_equal_array(&solutions[0], &solutions[1], 1e-6, 1e-7, "u");
+
+    let problem = Problem::new(&bounds, grad, cost);
+    assert!(PANOCOptimizer::from_dimensions(problem, 2, 1e-6, 5)
+        .unwrap()
+        .with_sr1(0)
+        .is_err());
+}
+
+#[test]
+fn t_panoc_lbfgs_initial_scaling() {
+    // stiff quadratic cost with curvatures from 1 to 1e4
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = u
+            .iter()
+            .enumerate()
+            .map(|(i, u_i)| 0.5 * 10f64.powi(i as i32) * (u_i - 1.0).powi(2))
+            .sum();
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        g.iter_mut()
+            .zip(u.iter())
+            .enumerate()
+            .for_each(|(i, (g_i, u_i))| *g_i = 10f64.powi(i as i32) * (u_i - 1.0));
+        Ok(())
+    };
+    let bounds = constraints::Ball2::new(None, 1.5).unwrap();
+    let scalings = [
+        InitialScaling::SyOverYy,
+        InitialScaling::SsOverSy,
+        InitialScaling::GeometricMean,
+        InitialScaling::Constant(1.0),
+    ];
+    let mut solutions = vec![];
+    for scaling in scalings {
+        let mut cache = PANOCCache::new(5, 1e-10, 5)
+            .unwrap()
+            .with_lbfgs_initial_scaling(scaling)
+            .unwrap();
+        let mut u = [0.0; 5];
+        let status = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+            .with_max_iter(1000)
+            .unwrap()
+            .solve(&mut u)
+            .unwrap();
+        assert!(status.has_converged());
+        solutions.push(u);
+    }
+    for u in solutions.iter().skip(1) {
+        unit_test_utils::assert_nearly_equal_array(&solutions[0], u, 1e-5, 1e-6, "u");
+    }
+    assert!(PANOCCache::new(5, 1e-10, 5)
+        .unwrap()
+        .with_lbfgs_initial_scaling(InitialScaling::Constant(-1.0))
+        .is_err());
+}
+
+#[test]
+fn t_panoc_const() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    let bounds = constraints::Ball2::new(None, 1.0).unwrap();
+
+    let mut cache = PANOCCache::new(2, 1e-10, 5).unwrap();
+    let mut u = [-1.5, 0.9];
+    let status = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+        .with_max_iter(1000)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+
+    // the heap-free optimizer performs the same iterations
+    let mut cache_const = PANOCCacheConst::<2, 5>::new(1e-10).unwrap();
+    let mut u_const = [-1.5, 0.9];
+    let status_const =
+        PANOCOptimizerConst::new(Problem::new(&bounds, grad, cost), &mut cache_const)
+            .with_max_iter(1000)
+            .unwrap()
+            .solve(&mut u_const)
+            .unwrap();
+    assert!(status_const.has_converged());
+    assert_eq!(status.iterations(), status_const.iterations());
+    assert_eq!(status.oracle_calls(), status_const.oracle_calls());
+    unit_test_utils::assert_nearly_equal_array(&u, &u_const, 1e-12, 1e-14, "u");
+
+    let mut u_wrong = [0.0; 3];
+    assert!(
+        PANOCOptimizerConst::new(Problem::new(&bounds, grad, cost), &mut cache_const)
+            .solve(&mut u_wrong)
+            .is_err()
+    );
+    assert!(PANOCCacheConst::<0, 5>::new(1e-10).is_err());
+    assert!(PANOCCacheConst::<2, 0>::new(1e-10).is_err());
+    assert!(PANOCCacheConst::<2, 5>::new(0.0).is_err());
+}
+
+#[test]
+fn t_panoc_clock() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    let bounds = constraints::Ball2::new(None, 1.0).unwrap();
+    let mut cache = PANOCCache::new(2, 1e-14, 5).unwrap();
+    let mut u = [-1.5, 0.9];
+    // every reading of the clock takes 1ms
+    let clock = ManualClock::new().with_tick(std::time::Duration::from_millis(1));
+    let status = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+        .with_max_iter(1000)
+        .unwrap()
+        .with_max_duration(std::time::Duration::from_millis(5))
+        .with_clock(&clock)
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(ExitStatus::NotConvergedOutOfTime, status.exit_status());
+    assert_eq!(6, status.iterations());
+    assert_eq!(std::time::Duration::from_millis(7), status.solve_time());
+}
+
+#[test]
+fn t_panoc_cache_memory_footprint() {
+    let (n, lbfgs_mem) = (10, 5);
+    let small = PANOCCache::<f64>::new(n, 1e-6, lbfgs_mem).unwrap();
+    let large = PANOCCache::<f64>::new(2 * n, 1e-6, lbfgs_mem).unwrap();
+    // the L-BFGS buffers s, y (of length lbfgs_mem + 1), the previous iterate
+    // and gradient, and the six vectors of the cache grow with the problem size
+    assert_eq!(
+        n * (2 * (lbfgs_mem + 1) + 2 + 6) * std::mem::size_of::<f64>(),
+        large.memory_footprint() - small.memory_footprint()
+    );
+    assert!(small.memory_footprint() > std::mem::size_of::<PANOCCache>());
+
+    let footprint = small.memory_footprint();
+    let small_with_history = small.with_fpr_history(100).unwrap();
+    assert!(small_with_history.memory_footprint() >= footprint + 100 * std::mem::size_of::<f64>());
+}
+
+#[test]
+fn t_panoc_checkpoint() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    let bounds = constraints::Ball2::new(None, 1.0).unwrap();
+    let mut cache = PANOCCache::new(2, 1e-10, 5).unwrap();
+    assert!(cache.restore(&cache.checkpoint(&[0.0; 2])).is_err());
+    let mut u = [-1.5, 0.9];
+    let status = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+        .with_max_iter(10)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+
+    let checkpoint = cache.checkpoint(&u);
+    assert_eq!(&u, checkpoint.iterate());
+    assert_eq!(cache.gamma, checkpoint.gamma());
+    assert!(checkpoint.lbfgs_pairs() > 0);
+    let bytes = checkpoint.to_bytes();
+    assert_eq!(checkpoint, PanocCheckpoint::from_bytes(&bytes).unwrap());
+    assert!(PanocCheckpoint::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(PanocCheckpoint::from_bytes(&[bytes.clone(), vec![0]].concat()).is_err());
+    assert!(PanocCheckpoint::from_bytes(b"OpEnALM1").is_err());
+    assert!(PANOCCache::new(3, 1e-10, 5)
+        .unwrap()
+        .restore(&checkpoint)
+        .is_err());
+
+    // the restored cache (with a smaller L-BFGS memory) resumes with the
+    // step size of the checkpoint and does not estimate it anew
+    let mut restored_cache = PANOCCache::new(2, 1e-10, 2).unwrap();
+    restored_cache.restore(&checkpoint).unwrap();
+    {
+        let mut panoc_engine =
+            PANOCEngine::new(Problem::new(&bounds, grad, cost), &mut restored_cache);
+        panoc_engine.init(&mut u.clone()).unwrap();
+        assert_eq!(checkpoint.gamma(), panoc_engine.cache.gamma);
+        assert_eq!(2, panoc_engine.cache.lbfgs.export_memory().s.len());
+        // the checkpoint is used by one solve only
+        panoc_engine.init(&mut u.clone()).unwrap();
+        assert_ne!(checkpoint.gamma(), panoc_engine.cache.gamma);
+    }
+    let mut restored_cache = PANOCCache::new(2, 1e-10, 2).unwrap();
+    restored_cache.restore(&checkpoint).unwrap();
+    let mut u = checkpoint.iterate().to_vec();
+    let status = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut restored_cache)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    let mut u_uninterrupted = [-1.5, 0.9];
+    PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+        .solve(&mut u_uninterrupted)
+        .unwrap();
+    unit_test_utils::assert_nearly_equal_array(&u_uninterrupted, &u, 1e-6, 1e-6, "u");
+}
+
+// subscribers (such as the one of the tests of the tracing events) may allocate
+#[cfg(all(feature = "alloc_counter", not(feature = "tracing")))]
+#[test]
+fn t_panoc_no_allocations() {
+    let bounds = constraints::Ball2::new(None, 0.2).unwrap();
+    let mut panoc_cache = PANOCCache::new(N_DIM, 1e-8, 5).unwrap();
+    let mut optimizer = PANOCOptimizer::new(
+        Problem::new(&bounds, mocks::my_gradient, mocks::my_cost),
+        &mut panoc_cache,
+    )
+    .with_akkt_tolerance(1e-6)
+    .unwrap();
+    let mut u = [0.75, -1.4];
+    // neither the first solve nor the next ones allocate
+    for _ in 0..2 {
+        let (status, allocations) =
+            crate::alloc_counter::count_allocations(|| optimizer.solve(&mut u));
+        assert!(status.unwrap().has_converged());
+        assert_eq!(0, allocations);
+    }
+}
+
+#[test]
+fn t_panoc_interpolating_line_search() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    // the constraint u[1] <= 0.8 is active at the solution
+    let bounds = constraints::Rectangle::new(Some(&[-5.0, -5.0]), Some(&[5.0, 0.8])).unwrap();
+    let mut cost_calls = [0, 0];
+    for u0 in [-2.5, -1.5, 0.5, 1.5, 2.5] {
+        let mut solutions = vec![];
+        for interpolate in [false, true] {
+            let mut cache = PANOCCache::new(2, 1e-10, 5).unwrap();
+            let mut u = [u0, -u0];
+            let status = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+                .with_max_iter(1000)
+                .unwrap()
+                .with_interpolating_line_search(interpolate)
+                .solve(&mut u)
+                .unwrap();
+            assert!(status.has_converged());
+            cost_calls[interpolate as usize] += status.oracle_calls().cost();
+            solutions.push(u);
+        }
+        unit_test_utils::assert_nearly_equal_array(&solutions[0], &solutions[1], 1e-6, 1e-7, "u");
+    }
+    // bad directions are abandoned after fewer evaluations
+    assert!(cost_calls[1] < cost_calls[0]);
+}
+
+#[test]
+fn t_panoc_interpolated_tau() {
+    use crate::core::panoc::panoc_engine::interpolated_tau;
+    // tau is halved after the first rejected candidate
+    assert_eq!(0.5, interpolated_tau(-1.0, &[(1.0, 3.0)]));
+    // the minimizer of psi, 0.075, is in [0.1 * tau, 0.5 * tau]
+    let psi = |t: f64| 2000.0 * (t - 0.1) * (t - 0.05) - 1.0;
+    let tau = interpolated_tau(psi(0.0), &[(0.5, psi(0.5)), (0.25, psi(0.25))]);
+    unit_test_utils::assert_nearly_equal(0.075, tau, 1e-12, 1e-14, "tau");
+    // safeguards
+    let psi = |t: f64| 100.0 * t * t - 1.0;
+    assert_eq!(
+        0.025,
+        interpolated_tau(psi(0.0), &[(0.5, psi(0.5)), (0.25, psi(0.25))])
+    );
+    // concave interpolant
+    assert_eq!(0.025, interpolated_tau(-1.0, &[(0.5, 1.0), (0.25, 0.9)]));
+    assert_eq!(
+        0.025,
+        interpolated_tau(-1.0, &[(0.5, 1.0), (0.25, f64::INFINITY)])
+    );
+    assert_eq!(
+        0.125,
+        interpolated_tau(-1.0, &[(0.5, f64::NAN), (0.25, 1.0)])
+    );
+}
+
+#[test]
+fn t_panoc_line_search_schedule() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    let bounds = constraints::Rectangle::new(Some(&[-5.0, -5.0]), Some(&[5.0, 0.8])).unwrap();
+    let solve = |schedule: &dyn Fn(
+        PANOCOptimizer<'_, _, _, _, f64>,
+    ) -> PANOCOptimizer<'_, _, _, _, f64>| {
+        let mut cache = PANOCCache::new(2, 1e-10, 5).unwrap();
+        let mut u = [-1.2, 1.0];
+        let optimizer = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+            .with_max_iter(1000)
+            .unwrap();
+        let status = schedule(optimizer).solve(&mut u).unwrap();
+        assert!(status.has_converged());
+        (status, u)
+    };
+
+    // the default schedule
+    let (status, u) = solve(&|optimizer| optimizer);
+    let (status_default, u_default) = solve(&|optimizer| {
+        optimizer
+            .with_initial_tau(1.0)
+            .unwrap()
+            .with_tau_shrink_factor(0.5)
+            .unwrap()
+            .with_max_backtracks(10)
+            .with_min_tau(0.0)
+            .unwrap()
+    });
+    assert_eq!(u, u_default);
+    assert_eq!(status.oracle_calls(), status_default.oracle_calls());
+
+    // candidates with tau = 1 and 0.5, then the forward-backward step
+    let (status, u_short) = solve(&|optimizer| optimizer.with_min_tau(0.3).unwrap());
+    assert!(status.oracle_calls().gradient() <= 3 * status.iterations() + 2);
+    unit_test_utils::assert_nearly_equal_array(&u, &u_short, 1e-6, 1e-7, "u");
+    let (status, u_short) = solve(&|optimizer| {
+        optimizer
+            .with_tau_shrink_factor(0.2)
+            .unwrap()
+            .with_max_backtracks(1)
+    });
+    assert!(status.oracle_calls().gradient() <= 3 * status.iterations() + 2);
+    unit_test_utils::assert_nearly_equal_array(&u, &u_short, 1e-6, 1e-7, "u");
+    let (_, u_cautious) = solve(&|optimizer| optimizer.with_initial_tau(0.99).unwrap());
+    unit_test_utils::assert_nearly_equal_array(&u, &u_cautious, 1e-6, 1e-7, "u");
+    // the forward-backward step after every rejected first candidate
+    let (status, u_short) = solve(&|optimizer| optimizer.with_max_backtracks(0));
+    assert!(status.oracle_calls().gradient() <= 2 * status.iterations() + 2);
+    unit_test_utils::assert_nearly_equal_array(&u, &u_short, 1e-6, 1e-7, "u");
+
+    let optimizer =
+        || PANOCOptimizer::from_dimensions(Problem::new(&bounds, grad, cost), 2, 1e-6, 5).unwrap();
+    assert!(optimizer().with_initial_tau(0.0).is_err());
+    assert!(optimizer().with_initial_tau(1.5).is_err());
+    assert!(optimizer().with_tau_shrink_factor(1.0).is_err());
+    assert!(optimizer().with_tau_shrink_factor(0.0).is_err());
+    assert!(optimizer().with_min_tau(-0.1).is_err());
+    assert!(optimizer().with_min_tau(1.0).is_err());
+}
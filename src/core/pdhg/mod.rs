@@ -0,0 +1,113 @@
+//! Primal-dual hybrid gradient (Chambolle-Pock) algorithm
+//!
+//! ## About
+//!
+//! This module offers an implementation of the primal-dual hybrid gradient
+//! (PDHG) algorithm of Chambolle and Pock for problems of the form
+//!
+//! $$\mathrm{Minimize}\ f(u) + g(Ku),$$
+//!
+//! where $f$ and $g$ are closed, proper, convex functions and $K$ is a
+//! linear operator, which are given through the proximal operators of $f$
+//! and of the convex conjugate, $g^*$, of $g$, and the products with $K$ and
+//! $K^\top$ (see [`PDHGProblem`]). Starting from $(u^0, y^0)$, PDHG performs
+//! the iterations
+//!
+//! $$\begin{aligned}
+//! u^{k+1} &= \mathrm{prox}_{\tau f}(u^k - \tau K^\top y^k),
+//! \\\\
+//! y^{k+1} &= \mathrm{prox}_{\sigma g^*}(y^k + \sigma K(2u^{k+1} - u^k)),
+//! \end{aligned}$$
+//!
+//! where the step sizes satisfy $\tau\sigma\Vert K\Vert^2 < 1$, until the
+//! primal and dual residuals,
+//!
+//! $$\begin{aligned}
+//! p^{k+1} &= (u^k - u^{k+1})/\tau - K^\top(y^k - y^{k+1}),
+//! \\\\
+//! d^{k+1} &= (y^k - y^{k+1})/\sigma - K(u^k - u^{k+1}),
+//! \end{aligned}$$
+//!
+//! (which are elements of $\partial f(u^{k+1}) + K^\top y^{k+1}$ and
+//! $\partial g^*(y^{k+1}) - Ku^{k+1}$) drop below the tolerance in the
+//! infinity norm.
+//!
+//! Unlike ALM, PDHG needs no inner solver, so it is well suited, e.g., to
+//! problems with total-variation regularization, or with constraints
+//! $Ku \in C$ (in which case $\mathrm{prox}_{\sigma g^*}$ is given by
+//! [`conjugate_projection`]).
+//!
+//! ## Example: projection on $\\{u : Ku \in C\\}$
+//!
+//! ```
+//! use optimization_engine::{constraints::*, pdhg::*, *};
+//!
+//! // minimize 0.5 |u - a|^2 subject to u[0] + u[1] <= 1
+//! let a = [1.0, 2.0];
+//! let prox_f = |v: &[f64], tau: f64, u: &mut [f64]| -> FunctionCallResult {
+//!     u.iter_mut()
+//!         .zip(v.iter().zip(a.iter()))
+//!         .for_each(|(ui, (vi, ai))| *ui = (vi + tau * ai) / (1.0 + tau));
+//!     Ok(())
+//! };
+//! let k = |u: &[f64], ku: &mut [f64]| -> FunctionCallResult {
+//!     ku[0] = u[0] + u[1];
+//!     Ok(())
+//! };
+//! let k_transpose = |y: &[f64], kty: &mut [f64]| -> FunctionCallResult {
+//!     kty.fill(y[0]);
+//!     Ok(())
+//! };
+//! let set_c = Rectangle::new(None, Some(&[1.0]))?;
+//! let problem = PDHGProblem::new(prox_f, conjugate_projection(&set_c), k, k_transpose);
+//! let mut cache = PDHGCache::new(2, 1, 1e-9)?;
+//! let mut u = [0.0; 2];
+//! let status = PDHGOptimizer::new(problem, &mut cache)
+//!     .with_max_iter(10_000)
+//!     .solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!(u[0].abs() < 1e-7 && (u[1] - 1.0).abs() < 1e-7);
+//! assert!((cache.dual()[0] - 1.0).abs() < 1e-7);
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! [`PDHGProblem`]: struct.PDHGProblem.html
+//! [`conjugate_projection`]: fn.conjugate_projection.html
+
+mod pdhg_cache;
+mod pdhg_engine;
+mod pdhg_optimizer;
+mod pdhg_problem;
+
+pub use pdhg_cache::PDHGCache;
+pub use pdhg_optimizer::PDHGOptimizer;
+pub use pdhg_problem::PDHGProblem;
+
+use crate::{constraints::Constraint, FunctionCallResult, OpEnFloat};
+
+/// Proximal operator of the convex conjugate of the indicator function of a
+/// closed convex set $C$, which (by the Moreau decomposition) is
+/// $\mathrm{prox}_{\sigma g^*}(v) = v - \sigma\Pi_C(v/\sigma)$
+pub fn conjugate_projection<T: OpEnFloat, C: Constraint<T>>(
+    set: &C,
+) -> impl Fn(&[T], T, &mut [T]) -> FunctionCallResult + '_ {
+    move |v: &[T], sigma: T, result: &mut [T]| {
+        result
+            .iter_mut()
+            .zip(v.iter())
+            .for_each(|(r, &vi)| *r = vi / sigma);
+        set.project(result);
+        result
+            .iter_mut()
+            .zip(v.iter())
+            .for_each(|(r, &vi)| *r = vi - sigma * *r);
+        Ok(())
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+/*          TESTS                                                                                */
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests;
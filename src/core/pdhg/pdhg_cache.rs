@@ -0,0 +1,90 @@
+//! PDHG Cache
+//!
+use crate::{ensure, OpEnFloat, SolverError};
+
+/// Cache for the primal-dual hybrid gradient (PDHG) algorithm
+///
+/// This struct allocates memory needed for the PDHG algorithm and stores the
+/// dual variable, $y$, which is used to warm start the next solve (see
+/// `PDHGCache::reset`).
+pub struct PDHGCache<T = f64> {
+    /// dual variable, $y$
+    pub(crate) y: Vec<T>,
+    pub(crate) y_previous: Vec<T>,
+    /// $Ku$ and $Ku$ at the previous iterate
+    pub(crate) k_u: Vec<T>,
+    pub(crate) k_u_previous: Vec<T>,
+    /// $K^\top y$ and $K^\top y$ at the previous iterate
+    pub(crate) k_transpose_y: Vec<T>,
+    pub(crate) k_transpose_y_previous: Vec<T>,
+    /// argument of the proximal operators
+    pub(crate) work_u: Vec<T>,
+    pub(crate) work_y: Vec<T>,
+    pub(crate) u_previous: Vec<T>,
+    pub(crate) tolerance: T,
+    pub(crate) primal_residual: T,
+    pub(crate) dual_residual: T,
+}
+
+impl<T: OpEnFloat> PDHGCache<T> {
+    /// Construct a new instance of `PDHGCache`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n` number of decision variables
+    /// - `m` dimension of the range of $K$
+    /// - `tolerance` tolerance used for termination
+    ///
+    /// ## Memory allocation
+    ///
+    /// This method allocates memory for `4*n + 5*m + 3` floats
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `n` or `m` is zero, or if
+    /// `tolerance` is not positive
+    ///
+    pub fn new(n: usize, m: usize, tolerance: T) -> Result<PDHGCache<T>, SolverError> {
+        ensure(n > 0, "n", "must be positive")?;
+        ensure(m > 0, "m", "must be positive")?;
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        Ok(PDHGCache {
+            y: vec![T::zero(); m],
+            y_previous: vec![T::zero(); m],
+            k_u: vec![T::zero(); m],
+            k_u_previous: vec![T::zero(); m],
+            k_transpose_y: vec![T::zero(); n],
+            k_transpose_y_previous: vec![T::zero(); n],
+            work_u: vec![T::zero(); n],
+            work_y: vec![T::zero(); m],
+            u_previous: vec![T::zero(); n],
+            tolerance,
+            primal_residual: T::infinity(),
+            dual_residual: T::infinity(),
+        })
+    }
+
+    /// Dual variable, $y$, at the last iterate (that is, the Lagrange
+    /// multiplier associated with $Ku$)
+    pub fn dual(&self) -> &[T] {
+        &self.y
+    }
+
+    /// Infinity norm of the primal residual at the last iterate
+    pub fn primal_residual(&self) -> T {
+        self.primal_residual
+    }
+
+    /// Infinity norm of the dual residual at the last iterate
+    pub fn dual_residual(&self) -> T {
+        self.dual_residual
+    }
+
+    /// Resets the dual variable to zero (otherwise, every solve is warm
+    /// started from the dual variable of the previous one)
+    pub fn reset(&mut self) {
+        self.y.iter_mut().for_each(|yi| *yi = T::zero());
+        self.primal_residual = T::infinity();
+        self.dual_residual = T::infinity();
+    }
+}
@@ -0,0 +1,162 @@
+//! PDHG Engine
+//!
+use crate::{
+    core::{
+        pdhg::{PDHGCache, PDHGProblem},
+        AlgorithmEngine, OracleCalls,
+    },
+    linear_operator::{self, NORM_SAFETY_FACTOR},
+    FunctionCallResult, OpEnFloat, SolverError,
+};
+
+/// Maximum number of power iterations for the estimation of $\Vert K\Vert$
+const POWER_ITERATIONS: usize = 100;
+/// Relative tolerance of the power iterations
+const POWER_ITERATION_TOLERANCE: f64 = 1e-6;
+
+/// The PDHG Engine defines the steps of the PDHG algorithm and the termination criterion
+///
+pub struct PDHGEngine<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T = f64>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult,
+{
+    pub(crate) problem: PDHGProblem<ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>,
+    pub(crate) cache: &'a mut PDHGCache<T>,
+    pub(crate) tau: T,
+    pub(crate) sigma: T,
+    pub(crate) oracle_calls: OracleCalls,
+}
+
+impl<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+    PDHGEngine<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult,
+{
+    /// Constructor for instances of `PDHGEngine` (with $\tau = \sigma = 1$)
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem` problem definition
+    /// - mutable reference to a `cache` a cache (which is created once); the cache is reuseable
+    ///
+    pub fn new(
+        problem: PDHGProblem<ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>,
+        cache: &'a mut PDHGCache<T>,
+    ) -> Self {
+        PDHGEngine {
+            problem,
+            cache,
+            tau: T::one(),
+            sigma: T::one(),
+            oracle_calls: OracleCalls::default(),
+        }
+    }
+
+    /// Estimates $\Vert K\Vert$ (slightly overestimated) with power iterations
+    /// on $K^\top K$
+    pub(crate) fn estimate_operator_norm(&mut self) -> Result<T, SolverError> {
+        let norm_k = linear_operator::power_iteration(
+            &mut self.cache.work_u,
+            &mut self.cache.work_y,
+            &self.problem.operator_k,
+            &self.problem.operator_k_transpose,
+            POWER_ITERATIONS,
+            T::from_f64(POWER_ITERATION_TOLERANCE),
+        )?;
+        Ok(norm_k * T::from_f64(NORM_SAFETY_FACTOR))
+    }
+}
+
+impl<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T> AlgorithmEngine<T>
+    for PDHGEngine<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'a,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'a,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult + 'a,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult + 'a,
+{
+    /// Take a PDHG step and check whether the algorithm should terminate
+    ///
+    /// ## Returns
+    ///
+    /// - A boolean flag which is`true` if and only if the algorithm should not
+    ///   terminate
+    ///
+    fn step(&mut self, u_current: &mut [T]) -> Result<bool, SolverError> {
+        let (tau, sigma) = (self.tau, self.sigma);
+        let problem = &self.problem;
+        let cache = &mut *self.cache;
+        cache.u_previous.copy_from_slice(u_current);
+        cache.y_previous.copy_from_slice(&cache.y);
+        std::mem::swap(&mut cache.k_u, &mut cache.k_u_previous);
+        std::mem::swap(&mut cache.k_transpose_y, &mut cache.k_transpose_y_previous);
+
+        // primal step: u = prox_{tau f}(u - tau K'y)
+        cache
+            .work_u
+            .iter_mut()
+            .zip(
+                cache
+                    .u_previous
+                    .iter()
+                    .zip(cache.k_transpose_y_previous.iter()),
+            )
+            .for_each(|(w, (&u, &kty))| *w = u - tau * kty);
+        (problem.prox_f)(&cache.work_u, tau, u_current)?;
+        (problem.operator_k)(u_current, &mut cache.k_u)?;
+
+        // dual step: y = prox_{sigma g*}(y + sigma K(2u - u_previous))
+        cache
+            .work_y
+            .iter_mut()
+            .zip(cache.y_previous.iter())
+            .zip(cache.k_u.iter().zip(cache.k_u_previous.iter()))
+            .for_each(|((w, &y), (&ku, &ku_prev))| *w = y + sigma * (ku + ku - ku_prev));
+        (problem.prox_g_conjugate)(&cache.work_y, sigma, &mut cache.y)?;
+        (problem.operator_k_transpose)(&cache.y, &mut cache.k_transpose_y)?;
+        self.oracle_calls.projections += 2;
+
+        cache.primal_residual = u_current
+            .iter()
+            .zip(cache.u_previous.iter())
+            .zip(
+                cache
+                    .k_transpose_y
+                    .iter()
+                    .zip(cache.k_transpose_y_previous.iter()),
+            )
+            .fold(T::zero(), |r, ((&u, &u_prev), (&kty, &kty_prev))| {
+                r.max(((u_prev - u) / tau - (kty_prev - kty)).abs())
+            });
+        cache.dual_residual = cache
+            .y
+            .iter()
+            .zip(cache.y_previous.iter())
+            .zip(cache.k_u.iter().zip(cache.k_u_previous.iter()))
+            .fold(T::zero(), |r, ((&y, &y_prev), (&ku, &ku_prev))| {
+                r.max(((y_prev - y) / sigma - (ku_prev - ku)).abs())
+            });
+
+        Ok(cache.primal_residual.max(cache.dual_residual) > cache.tolerance)
+    }
+
+    /// Computes $Ku$ and $K^\top y$ at the initial point
+    fn init(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        self.oracle_calls.reset();
+        let cache = &mut *self.cache;
+        (self.problem.operator_k)(u_current, &mut cache.k_u)?;
+        (self.problem.operator_k_transpose)(&cache.y, &mut cache.k_transpose_y)?;
+        cache.primal_residual = T::infinity();
+        cache.dual_residual = T::infinity();
+        Ok(())
+    }
+}
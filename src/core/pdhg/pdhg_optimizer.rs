@@ -0,0 +1,218 @@
+//! PDHG Algorithm
+//!
+use crate::{
+    core::{
+        pdhg::{pdhg_engine::PDHGEngine, PDHGCache, PDHGProblem},
+        AlgorithmEngine, ExitStatus, Optimizer, SolverStatus,
+    },
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+const MAX_ITER: usize = 1000_usize;
+/// The default step sizes are $\tau = \sigma = $ `STEP_FACTOR` $/\Vert K\Vert$
+const STEP_FACTOR: f64 = 0.95;
+
+/// Optimiser using primal-dual hybrid gradient (Chambolle-Pock) iterations
+///
+/// The argument of `solve` is, on entry, the initial guess of $u$ and, on
+/// exit, the solution; the dual variable is stored in the cache (see
+/// `PDHGCache::dual`).
+///
+/// Unless the step sizes are given (see `with_step_sizes`), they are
+/// $\tau = \sigma = 0.95/\Vert K\Vert$, where $\Vert K\Vert$ is estimated
+/// with power iterations at the beginning of every solve.
+///
+/// Since PDHG only uses proximal operators, the cost value of the returned
+/// `SolverStatus` is NaN, its norm of the fixed-point residual is the
+/// maximum of the primal and dual residuals, and each evaluation of a
+/// proximal operator is counted as a projection in its `OracleCalls`.
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct PDHGOptimizer<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T = f64>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult,
+{
+    pdhg_engine: PDHGEngine<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>,
+    step_sizes: Option<(T, T)>,
+    max_iter: usize,
+    max_duration: Option<time::Duration>,
+}
+
+impl<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+    PDHGOptimizer<'a, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult,
+{
+    /// Constructs a new instance of `PDHGOptimizer`
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem`: problem definition
+    /// - `cache`: instance of `PDHGCache`
+    pub fn new(
+        problem: PDHGProblem<ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>,
+        cache: &'a mut PDHGCache<T>,
+    ) -> Self {
+        PDHGOptimizer {
+            pdhg_engine: PDHGEngine::new(problem, cache),
+            step_sizes: None,
+            max_iter: MAX_ITER,
+            max_duration: None,
+        }
+    }
+
+    /// Sets the tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        self.pdhg_engine.cache.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the primal and dual step sizes, $\tau$ and $\sigma$, which
+    /// should satisfy $\tau\sigma\Vert K\Vert^2 < 1$ (this is not checked)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tau` or `sigma` is not
+    /// positive and finite
+    pub fn with_step_sizes(mut self, tau: T, sigma: T) -> Result<Self, SolverError> {
+        ensure(
+            tau > T::zero() && tau.is_finite(),
+            "tau",
+            "must be positive and finite",
+        )?;
+        ensure(
+            sigma > T::zero() && sigma.is_finite(),
+            "sigma",
+            "must be positive and finite",
+        )?;
+        self.step_sizes = Some((tau, sigma));
+        Ok(self)
+    }
+
+    /// Sets the norm of $K$ (e.g., the `norm_estimate` of a
+    /// `LinearOperator`, see `linear_operator::estimate_norm`), so that the
+    /// default step sizes, $\tau = \sigma = 0.95/\Vert K\Vert$, are computed
+    /// without power iterations
+    ///
+    /// `norm_k` must not underestimate $\Vert K\Vert$, otherwise the step
+    /// sizes violate $\tau\sigma\Vert K\Vert^2 < 1$ and the iterates may
+    /// diverge (`estimate_norm` scales its power iterations by a safety
+    /// factor for this reason)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `norm_k` is not positive
+    /// and finite
+    pub fn with_operator_norm(self, norm_k: T) -> Result<Self, SolverError> {
+        ensure(
+            norm_k > T::zero() && norm_k.is_finite(),
+            "norm_k",
+            "must be positive and finite",
+        )?;
+        let step = T::from_f64(STEP_FACTOR) / norm_k;
+        self.with_step_sizes(step, step)
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+}
+
+impl<'life, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T> Optimizer<T>
+    for PDHGOptimizer<'life, ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'life,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult + 'life,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = instant::Instant::now();
+
+        let problem_size = self.pdhg_engine.cache.u_previous.len();
+        if u.len() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: problem_size,
+                actual: u.len(),
+            });
+        }
+
+        let (tau, sigma) = match self.step_sizes {
+            Some(step_sizes) => step_sizes,
+            None => {
+                let norm_k = self.pdhg_engine.estimate_operator_norm()?;
+                let step = if norm_k > T::zero() {
+                    T::from_f64(STEP_FACTOR) / norm_k
+                } else {
+                    T::one()
+                };
+                (step, step)
+            }
+        };
+        self.pdhg_engine.tau = tau;
+        self.pdhg_engine.sigma = sigma;
+        self.pdhg_engine.init(u)?;
+
+        let mut num_iter: usize = 0;
+        let mut exit_status = ExitStatus::Converged;
+        loop {
+            num_iter += 1;
+            if !self.pdhg_engine.step(u)? {
+                break;
+            }
+            if num_iter >= self.max_iter {
+                exit_status = ExitStatus::NotConvergedIterations;
+                break;
+            }
+            if let Some(max_duration) = self.max_duration {
+                if now.elapsed() > max_duration {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+        }
+
+        let cache = &*self.pdhg_engine.cache;
+        let norm_residual = cache.primal_residual.max(cache.dual_residual);
+        if !matrix_operations::is_finite(u)
+            || !matrix_operations::is_finite(&cache.y)
+            || !norm_residual.is_finite()
+        {
+            return Err(SolverError::NotFiniteComputation);
+        }
+
+        Ok(SolverStatus::new(
+            exit_status,
+            num_iter,
+            now.elapsed(),
+            norm_residual.as_f64(),
+            f64::NAN,
+        )
+        .with_oracle_calls(self.pdhg_engine.oracle_calls))
+    }
+}
@@ -0,0 +1,73 @@
+//! PDHG Problem
+//!
+use crate::{FunctionCallResult, OpEnFloat};
+use std::marker::PhantomData;
+
+/// Definition of an optimization problem to be solved with `PDHGOptimizer`
+///
+/// The problem is to minimize $f(u) + g(Ku)$, with $u\in\mathbb{R}^n$ and
+/// $K\in\mathbb{R}^{m\times n}$, and is specified by
+///
+/// - `prox_f`, which, given `v` and $\tau > 0$, computes
+///   $\mathrm{prox}_{\tau f}(v)$,
+/// - `prox_g_conjugate`, which, given `v` and $\sigma > 0$, computes
+///   $\mathrm{prox}_{\sigma g^*}(v)$, where $g^*$ is the convex conjugate of
+///   $g$ (by the Moreau decomposition, $\mathrm{prox}_{\sigma g^*}(v) = v -
+///   \sigma\,\mathrm{prox}_{g/\sigma}(v/\sigma)$; see also
+///   `pdhg::conjugate_projection`),
+/// - `operator_k` and `operator_k_transpose`, which compute $Ku$ and
+///   $K^\top y$.
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct PDHGProblem<ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T = f64>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult,
+{
+    /// proximal operator of $f$
+    pub(crate) prox_f: ProxF,
+    /// proximal operator of $g^*$
+    pub(crate) prox_g_conjugate: ProxGConjugate,
+    /// operator $u \mapsto Ku$
+    pub(crate) operator_k: OperatorK,
+    /// operator $y \mapsto K^\top y$
+    pub(crate) operator_k_transpose: OperatorKTranspose,
+    marker: PhantomData<T>,
+}
+
+impl<ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+    PDHGProblem<ProxF, ProxGConjugate, OperatorK, OperatorKTranspose, T>
+where
+    T: OpEnFloat,
+    ProxF: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    ProxGConjugate: Fn(&[T], T, &mut [T]) -> FunctionCallResult,
+    OperatorK: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    OperatorKTranspose: Fn(&[T], &mut [T]) -> FunctionCallResult,
+{
+    /// Construct a new instance of a PDHG problem
+    ///
+    /// ## Arguments
+    ///
+    /// - `prox_f`: proximal operator of $f$, $(v, \tau, u) \mapsto u = \mathrm{prox}_{\tau f}(v)$
+    /// - `prox_g_conjugate`: proximal operator of $g^*$, $(v, \sigma, y) \mapsto y = \mathrm{prox}_{\sigma g^*}(v)$
+    /// - `operator_k`: $(u, Ku) \mapsto Ku$
+    /// - `operator_k_transpose`: $(y, K^\top y) \mapsto K^\top y$
+    ///
+    pub fn new(
+        prox_f: ProxF,
+        prox_g_conjugate: ProxGConjugate,
+        operator_k: OperatorK,
+        operator_k_transpose: OperatorKTranspose,
+    ) -> Self {
+        PDHGProblem {
+            prox_f,
+            prox_g_conjugate,
+            operator_k,
+            operator_k_transpose,
+            marker: PhantomData,
+        }
+    }
+}
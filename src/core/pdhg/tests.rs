@@ -0,0 +1,166 @@
+use super::*;
+use crate::{constraints::*, core::ExitStatus, Optimizer, SolverError};
+
+/// $u \mapsto Du$, where $D$ is the first-order difference operator
+fn differences(u: &[f64], du: &mut [f64]) -> FunctionCallResult {
+    du.iter_mut()
+        .enumerate()
+        .for_each(|(i, d)| *d = u[i + 1] - u[i]);
+    Ok(())
+}
+
+/// $y \mapsto D^\top y$
+fn differences_transpose(y: &[f64], dty: &mut [f64]) -> FunctionCallResult {
+    let m = y.len();
+    for (i, d) in dty.iter_mut().enumerate() {
+        let previous = if i > 0 { y[i - 1] } else { 0.0 };
+        let current = if i < m { y[i] } else { 0.0 };
+        *d = previous - current;
+    }
+    Ok(())
+}
+
+#[test]
+fn t_pdhg_total_variation() {
+    // minimize 0.5 |u - a|^2 + lambda |Du|_1, with g* the indicator of the
+    // infinity ball of radius lambda
+    let (a, lambda) = ([0.0, 1.0, 3.0], 0.25);
+    let prox_f = |v: &[f64], tau: f64, u: &mut [f64]| -> FunctionCallResult {
+        u.iter_mut()
+            .zip(v.iter().zip(a.iter()))
+            .for_each(|(ui, (vi, ai))| *ui = (vi + tau * ai) / (1.0 + tau));
+        Ok(())
+    };
+    let dual_ball = BallInf::new(None, lambda).unwrap();
+    let problem = PDHGProblem::new(
+        prox_f,
+        crate::drs::projection(&dual_ball),
+        differences,
+        differences_transpose,
+    );
+    let mut cache = PDHGCache::new(3, 2, 1e-10).unwrap();
+    let mut u = [0.0; 3];
+    let status = PDHGOptimizer::new(problem, &mut cache)
+        .with_max_iter(10_000)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.cost_value().is_nan());
+    assert_eq!(2 * status.iterations(), status.oracle_calls().projections());
+    unit_test_utils::assert_nearly_equal_array(&[0.25, 1.0, 2.75], &u, 1e-8, 1e-9, "u");
+    assert!(cache.primal_residual() <= 1e-10 && cache.dual_residual() <= 1e-10);
+    unit_test_utils::assert_nearly_equal_array(&[0.25, 0.25], cache.dual(), 1e-8, 1e-9, "y");
+
+    // the dominant right singular vector of D, (1, -2, 1), is orthogonal to
+    // increasing starting points such as (1, 2, 3)
+    let problem = PDHGProblem::new(
+        prox_f,
+        crate::drs::projection(&dual_ball),
+        differences,
+        differences_transpose,
+    );
+    let norm_d = pdhg_engine::PDHGEngine::new(problem, &mut cache)
+        .estimate_operator_norm()
+        .unwrap();
+    assert!(norm_d >= 3.0_f64.sqrt() && norm_d <= 1.05 * 3.0_f64.sqrt() + 1e-12);
+}
+
+#[test]
+fn t_pdhg_step_sizes_and_warm_start() {
+    let prox_f = |v: &[f64], tau: f64, u: &mut [f64]| -> FunctionCallResult {
+        u.iter_mut()
+            .zip(v.iter())
+            .for_each(|(ui, vi)| *ui = (vi + tau * 2.0) / (1.0 + tau));
+        Ok(())
+    };
+    let set_c = Rectangle::new(None, Some(&[0.5, 0.5])).unwrap();
+    let mut cache = PDHGCache::new(3, 2, 1e-9).unwrap();
+    let mut iterations = vec![];
+    for _ in 0..2 {
+        let problem = PDHGProblem::new(
+            prox_f,
+            conjugate_projection(&set_c),
+            differences,
+            differences_transpose,
+        );
+        // |D| <= 2
+        let mut u = [0.0; 3];
+        let status = PDHGOptimizer::new(problem, &mut cache)
+            .with_step_sizes(0.45, 1.0)
+            .unwrap()
+            .with_max_iter(10_000)
+            .solve(&mut u)
+            .unwrap();
+        assert!(status.has_converged());
+        // the constraints Du <= 0.5 are inactive at u = (2, 2, 2)
+        unit_test_utils::assert_nearly_equal_array(&[2.0; 3], &u, 1e-8, 1e-8, "u");
+        iterations.push(status.iterations());
+    }
+    // the second solve is warm started from the dual variable of the first
+    assert!(iterations[1] <= iterations[0]);
+    cache.reset();
+    assert_eq!(&[0.0, 0.0], cache.dual());
+}
+
+#[test]
+fn t_pdhg_max_iter_and_errors() {
+    let no_constraints = NoConstraints::new();
+    let prox_f = crate::drs::projection(&no_constraints);
+    let set_c = Rectangle::new(Some(&[1.0, 1.0]), Some(&[1.0, 1.0])).unwrap();
+    let problem = PDHGProblem::new(
+        prox_f,
+        conjugate_projection(&set_c),
+        differences,
+        differences_transpose,
+    );
+    let mut cache = PDHGCache::new(3, 2, 1e-12).unwrap();
+    let mut optimizer = PDHGOptimizer::new(problem, &mut cache).with_max_iter(5);
+    let mut u = [0.0; 3];
+    let status = optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+    assert_eq!(5, status.iterations());
+    assert!(matches!(
+        optimizer.solve(&mut [0.0; 2]),
+        Err(SolverError::DimensionMismatch {
+            name: "u",
+            expected: 3,
+            actual: 2,
+        })
+    ));
+    assert!(optimizer.with_step_sizes(1.0, 0.0).is_err());
+    assert!(PDHGCache::<f64>::new(3, 0, 1e-8).is_err());
+}
+
+#[test]
+fn t_pdhg_linear_operator() {
+    use crate::linear_operator::{self, DenseMatrix};
+    // the difference operator of t_pdhg_total_variation as a matrix
+    let d = DenseMatrix::new(2, 3, vec![-1.0, 1.0, 0.0, 0.0, -1.0, 1.0]).unwrap();
+    let norm_d = linear_operator::estimate_norm(&d).unwrap();
+    // the norm is sqrt(3), overestimated by at most 5%
+    assert!(norm_d >= 3.0_f64.sqrt() && norm_d <= 1.05 * 3.0_f64.sqrt() + 1e-12);
+    let (a, lambda) = ([0.0, 1.0, 3.0], 0.25);
+    let prox_f = |v: &[f64], tau: f64, u: &mut [f64]| -> FunctionCallResult {
+        u.iter_mut()
+            .zip(v.iter().zip(a.iter()))
+            .for_each(|(ui, (vi, ai))| *ui = (vi + tau * ai) / (1.0 + tau));
+        Ok(())
+    };
+    let dual_ball = BallInf::new(None, lambda).unwrap();
+    let problem = PDHGProblem::new(
+        prox_f,
+        crate::drs::projection(&dual_ball),
+        linear_operator::operator(&d),
+        linear_operator::adjoint(&d),
+    );
+    let mut cache = PDHGCache::new(3, 2, 1e-10).unwrap();
+    let mut u = [0.0; 3];
+    let status = PDHGOptimizer::new(problem, &mut cache)
+        .with_operator_norm(norm_d)
+        .unwrap()
+        .with_max_iter(10_000)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    unit_test_utils::assert_nearly_equal_array(&[0.25, 1.0, 2.75], &u, 1e-8, 1e-9, "u");
+}
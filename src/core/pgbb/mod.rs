@@ -0,0 +1,76 @@
+//! Projected gradient method with Barzilai-Borwein steps
+//!
+//! ## About
+//!
+//! This module offers a projected gradient method with Barzilai-Borwein
+//! (BB) step sizes for problems of the form
+//!
+//! $$\mathrm{Minimize}\ f(u) \text{ subject to } u \in U,$$
+//!
+//! that is, for the same `Problem` as PANOC and FBS. At every iteration, the
+//! method takes a projected gradient step,
+//!
+//! $$u^{k+1} = \Pi_U(u^k - \gamma_k \nabla f(u^k)),$$
+//!
+//! with the BB step size $\gamma_k = \Vert s\Vert^2 / \langle s, y\rangle$,
+//! where $s = u^k - u^{k-1}$ and $y = \nabla f(u^k) - \nabla f(u^{k-1})$,
+//! which is halved until the sufficient decrease condition
+//!
+//! $$f(u^{k+1}) \leq f(u^k) + \sigma \langle \nabla f(u^k), u^{k+1} - u^k\rangle$$
+//!
+//! holds. With `PGBBCache::with_memory`, the reference of this condition is
+//! the largest of the last $M$ costs, $\max_{0 \leq j < M} f(u^{k-j})$,
+//! instead of $f(u^k)$, which is the spectral projected gradient (SPG)
+//! method of Birgin, Martínez and Raydan: the cost may increase at some
+//! iterations, which lets more of the BB steps, which are often much longer
+//! than the monotone ones, be accepted. The iterations terminate when the
+//! residual of the first-order optimality conditions,
+//!
+//! $$\Vert (u^k - u^{k+1})/\gamma_k + \nabla f(u^{k+1}) - \nabla f(u^k)\Vert_\infty$$
+//!
+//! (which is an element of $\nabla f(u^{k+1}) + N_U(u^{k+1})$), drops below
+//! the tolerance.
+//!
+//! The method only stores three vectors, so it may be used instead of PANOC
+//! on very large problems, where even a small L-BFGS buffer is too heavy;
+//! it can also solve the inner problems of `AlmOptimizer` (see
+//! `AlmCache::with_pgbb_inner_solver`).
+//!
+//! ## Example
+//!
+//! ```
+//! use optimization_engine::{constraints::Ball2, pgbb::*, *};
+//! use std::num::NonZeroUsize;
+//!
+//! let df = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     grad[0] = u[0] - 2.0;
+//!     grad[1] = 10.0 * u[1];
+//!     Ok(())
+//! };
+//! let f = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = 0.5 * (u[0] - 2.0).powi(2) + 5.0 * u[1].powi(2);
+//!     Ok(())
+//! };
+//! let bounds = Ball2::new(None, 1.0)?;
+//! let problem = Problem::new(&bounds, df, f);
+//! let mut cache = PGBBCache::new(NonZeroUsize::new(2).unwrap(), 1e-8)?;
+//! let mut u = [0.0; 2];
+//! let status = PGBBOptimizer::new(problem, &mut cache).solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 1.0).abs() < 1e-8 && u[1].abs() < 1e-8);
+//! # Ok::<(), SolverError>(())
+//! ```
+
+mod pgbb_cache;
+mod pgbb_engine;
+mod pgbb_optimizer;
+
+pub use pgbb_cache::PGBBCache;
+pub use pgbb_optimizer::PGBBOptimizer;
+
+/* --------------------------------------------------------------------------------------------- */
+/*          TESTS                                                                                */
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests;
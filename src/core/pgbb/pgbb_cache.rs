@@ -0,0 +1,80 @@
+//! PGBB Cache
+//!
+use crate::{
+    core::{heap_size, OracleCalls},
+    ensure, OpEnFloat, SolverError,
+};
+use std::num::NonZeroUsize;
+
+/// Cache for the projected gradient method with Barzilai-Borwein steps
+///
+/// This struct allocates memory needed for the algorithm
+#[derive(Debug)]
+pub struct PGBBCache<T = f64> {
+    pub(crate) gradient_u: Vec<T>,
+    pub(crate) gradient_u_previous: Vec<T>,
+    pub(crate) u_previous: Vec<T>,
+    /// costs of the last iterates (circular buffer), whose maximum is the
+    /// reference of the (nonmonotone) line search
+    pub(crate) cost_history: Vec<T>,
+    pub(crate) cost_value: T,
+    pub(crate) gamma: T,
+    pub(crate) tolerance: T,
+    pub(crate) norm_fpr: T,
+    pub(crate) oracle_calls: OracleCalls,
+}
+
+impl<T: OpEnFloat> PGBBCache<T> {
+    /// Number of bytes held on the heap by the buffers
+    pub(crate) fn heap_size(&self) -> usize {
+        heap_size(&self.gradient_u)
+            + heap_size(&self.gradient_u_previous)
+            + heap_size(&self.u_previous)
+            + heap_size(&self.cost_history)
+    }
+
+    /// Construct a new instance of `PGBBCache`
+    ///
+    /// ## Arguments
+    ///
+    /// - `n` number of decision variables
+    /// - `tolerance` tolerance used for termination
+    ///
+    /// ## Memory allocation
+    ///
+    /// This method allocates memory for `3*n + 5` floats (with the default,
+    /// monotone line search); if an optimizer needs to be called more than
+    /// once, construct a `PGBBCache` only once.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `tolerance` is not positive
+    ///
+    pub fn new(n: NonZeroUsize, tolerance: T) -> Result<PGBBCache<T>, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        Ok(PGBBCache {
+            gradient_u: vec![T::zero(); n.get()],
+            gradient_u_previous: vec![T::zero(); n.get()],
+            u_previous: vec![T::zero(); n.get()],
+            cost_history: vec![T::neg_infinity(); 1],
+            cost_value: T::zero(),
+            gamma: T::one(),
+            tolerance,
+            norm_fpr: T::infinity(),
+            oracle_calls: OracleCalls::default(),
+        })
+    }
+
+    /// Sets the number of previous costs, $M$, whose maximum is the reference
+    /// of the sufficient decrease condition (the default is 1, that is, a
+    /// monotone line search)
+    ///
+    /// With $M > 1$, the method is the spectral projected gradient (SPG)
+    /// method of Birgin, Martínez and Raydan: the cost may increase at some
+    /// iterations, so more of the BB steps, which are often much longer than
+    /// the monotone ones, are accepted. Typical values are 5 to 10.
+    pub fn with_memory(mut self, memory: NonZeroUsize) -> Self {
+        self.cost_history = vec![T::neg_infinity(); memory.get()];
+        self
+    }
+}
@@ -0,0 +1,194 @@
+//! PGBB Engine
+//!
+use crate::{
+    constraints,
+    core::{pgbb::PGBBCache, AlgorithmEngine, Problem},
+    matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+
+/// Coefficient of the (nonmonotone) sufficient decrease condition
+const SUFFICIENT_DECREASE: f64 = 1e-4;
+/// Maximum number of halvings of the step size per iteration
+const MAX_BACKTRACKING: usize = 60;
+/// Bounds on the step size
+const MIN_STEP: f64 = 1e-12;
+const MAX_STEP: f64 = 1e12;
+
+/// The PGBB Engine defines the steps of the algorithm and the termination criterion
+///
+pub struct PGBBEngine<'a, GradientType, ConstraintType, CostType, T = f64>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    pub(crate) problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+    pub(crate) cache: &'a mut PGBBCache<T>,
+    /// number of iterations since the last `init`
+    iteration: usize,
+}
+
+impl<'a, GradientType, ConstraintType, CostType, T>
+    PGBBEngine<'a, GradientType, ConstraintType, CostType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructor for instances of `PGBBEngine`
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem` problem definition (cost function, gradient of the cost, constraints)
+    /// - mutable reference to a `cache` a cache (which is created once); the cache is reuseable
+    ///
+    pub fn new(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: &'a mut PGBBCache<T>,
+    ) -> PGBBEngine<'a, GradientType, ConstraintType, CostType, T> {
+        PGBBEngine {
+            problem,
+            cache,
+            iteration: 0,
+        }
+    }
+
+    fn cost(&mut self, u: &[T]) -> Result<T, SolverError> {
+        let mut cost_value = T::zero();
+        self.cache.oracle_calls.cost += 1;
+        (self.problem.cost)(u, &mut cost_value)?;
+        Ok(cost_value)
+    }
+
+    fn gradient(&mut self, u: &[T]) -> FunctionCallResult {
+        self.cache.oracle_calls.gradient += 1;
+        (self.problem.gradf)(u, &mut self.cache.gradient_u)
+    }
+
+    /// Computes `u = Proj(u_previous - gamma * gradient_u_previous)`
+    fn projected_gradient_step(&mut self, u: &mut [T]) {
+        let cache = &mut *self.cache;
+        u.iter_mut()
+            .zip(cache.u_previous.iter())
+            .zip(cache.gradient_u_previous.iter())
+            .for_each(|((ui, &u_prev), &df)| *ui = u_prev - cache.gamma * df);
+        self.problem.constraints.project(u);
+        cache.oracle_calls.projections += 1;
+    }
+
+    /// Barzilai-Borwein step size, $\Vert s\Vert^2/\langle s, y\rangle$, clipped
+    /// to `[MIN_STEP, MAX_STEP]` (or `MAX_STEP` if the curvature is not positive)
+    fn update_step_size(&mut self, u: &[T]) {
+        let cache = &mut *self.cache;
+        let (mut ss, mut sy) = (T::zero(), T::zero());
+        for (i, &ui) in u.iter().enumerate() {
+            let s = ui - cache.u_previous[i];
+            ss += s * s;
+            sy += s * (cache.gradient_u[i] - cache.gradient_u_previous[i]);
+        }
+        let (min_step, max_step) = (T::from_f64(MIN_STEP), T::from_f64(MAX_STEP));
+        cache.gamma = if sy > T::zero() {
+            (ss / sy).max(min_step).min(max_step)
+        } else {
+            max_step
+        };
+    }
+}
+
+impl<'a, GradientType, ConstraintType, CostType, T> AlgorithmEngine<T>
+    for PGBBEngine<'a, GradientType, ConstraintType, CostType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'a,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'a,
+    ConstraintType: constraints::Constraint<T> + 'a,
+{
+    /// Take a projected gradient step and check whether the algorithm should terminate
+    ///
+    /// ## Returns
+    ///
+    /// - A boolean flag which is`true` if and only if the algorithm should not
+    ///   terminate
+    ///
+    fn step(&mut self, u_current: &mut [T]) -> Result<bool, SolverError> {
+        self.cache.u_previous.copy_from_slice(u_current);
+        std::mem::swap(
+            &mut self.cache.gradient_u,
+            &mut self.cache.gradient_u_previous,
+        );
+        // the reference of the sufficient decrease condition is the largest
+        // of the last M costs (with M = 1, the line search is monotone)
+        let cost_reference = self
+            .cache
+            .cost_history
+            .iter()
+            .fold(self.cache.cost_value, |max, &f| max.max(f));
+        let sigma = T::from_f64(SUFFICIENT_DECREASE);
+
+        let mut backtracking = 0;
+        loop {
+            self.projected_gradient_step(u_current);
+            let cost_value = self.cost(u_current)?;
+            let decrease = u_current
+                .iter()
+                .zip(self.cache.u_previous.iter())
+                .zip(self.cache.gradient_u_previous.iter())
+                .fold(T::zero(), |sum, ((&ui, &u_prev), &df)| {
+                    sum + df * (ui - u_prev)
+                });
+            if cost_value <= cost_reference + sigma * decrease || backtracking == MAX_BACKTRACKING {
+                self.cache.cost_value = cost_value;
+                break;
+            }
+            backtracking += 1;
+            self.cache.gamma /= T::from_f64(2.0);
+        }
+        if !self.cache.cost_value.is_finite() {
+            return Err(SolverError::NotFiniteComputation);
+        }
+        self.iteration += 1;
+        let memory = self.cache.cost_history.len();
+        self.cache.cost_history[self.iteration % memory] = self.cache.cost_value;
+
+        self.gradient(u_current)?;
+        let cache = &mut *self.cache;
+        cache.norm_fpr = u_current
+            .iter()
+            .zip(cache.u_previous.iter())
+            .zip(
+                cache
+                    .gradient_u
+                    .iter()
+                    .zip(cache.gradient_u_previous.iter()),
+            )
+            .fold(T::zero(), |norm, ((&ui, &u_prev), (&df, &df_prev))| {
+                norm.max(((u_prev - ui) / cache.gamma + df - df_prev).abs())
+            });
+        self.update_step_size(u_current);
+
+        Ok(self.cache.norm_fpr > self.cache.tolerance)
+    }
+
+    /// Computes the cost and its gradient at the initial point and the
+    /// initial step size, $1/\Vert \nabla f(u^0)\Vert_\infty$
+    fn init(&mut self, u_current: &mut [T]) -> FunctionCallResult {
+        self.cache.oracle_calls.reset();
+        self.iteration = 0;
+        self.problem.constraints.project(u_current);
+        self.cache.oracle_calls.projections += 1;
+        self.cache.cost_value = self.cost(u_current)?;
+        self.cache.cost_history.fill(T::neg_infinity());
+        self.cache.cost_history[0] = self.cache.cost_value;
+        self.gradient(u_current)?;
+        let norm_gradient = matrix_operations::norm_inf(&self.cache.gradient_u);
+        self.cache.gamma = if norm_gradient > T::zero() {
+            (T::one() / norm_gradient).min(T::from_f64(MAX_STEP))
+        } else {
+            T::one()
+        };
+        self.cache.norm_fpr = T::infinity();
+        Ok(())
+    }
+}
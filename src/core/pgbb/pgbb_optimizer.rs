@@ -0,0 +1,145 @@
+//! PGBB Algorithm
+//!
+use crate::{
+    constraints,
+    core::{
+        pgbb::{pgbb_engine::PGBBEngine, PGBBCache},
+        AlgorithmEngine, Clock, ExitStatus, Optimizer, Problem, SolverStatus, StdClock,
+    },
+    ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+const MAX_ITER: usize = 1000_usize;
+
+/// Optimiser using projected gradient iterations with Barzilai-Borwein steps
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub struct PGBBOptimizer<'a, GradientType, ConstraintType, CostType, T = f64>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    pgbb_engine: PGBBEngine<'a, GradientType, ConstraintType, CostType, T>,
+    max_iter: usize,
+    max_duration: Option<time::Duration>,
+    clock: &'a dyn Clock,
+}
+
+impl<'a, GradientType, ConstraintType, CostType, T>
+    PGBBOptimizer<'a, GradientType, ConstraintType, CostType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
+{
+    /// Constructs a new instance of `PGBBOptimizer`
+    ///
+    /// ## Arguments
+    ///
+    /// - `problem`: problem definition
+    /// - `cache`: instance of `PGBBCache`
+    pub fn new(
+        problem: Problem<'a, GradientType, ConstraintType, CostType, T>,
+        cache: &'a mut PGBBCache<T>,
+    ) -> Self {
+        PGBBOptimizer {
+            pgbb_engine: PGBBEngine::new(problem, cache),
+            max_iter: MAX_ITER,
+            max_duration: None,
+            clock: &StdClock,
+        }
+    }
+
+    /// Sets the tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the specified tolerance is
+    /// not positive
+    pub fn with_tolerance(self, tolerance: T) -> Result<Self, SolverError> {
+        ensure(tolerance > T::zero(), "tolerance", "must be positive")?;
+        self.pgbb_engine.cache.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Sets the clock which measures the solution time (see `Clock`); the
+    /// default is `StdClock`
+    pub fn with_clock(mut self, clock: &'a dyn Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl<'life, GradientType, ConstraintType, CostType, T> Optimizer<T>
+    for PGBBOptimizer<'life, GradientType, ConstraintType, CostType, T>
+where
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'life,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult + 'life,
+    ConstraintType: constraints::Constraint<T> + 'life,
+{
+    fn solve(&mut self, u: &mut [T]) -> Result<SolverStatus, SolverError> {
+        let now = self.clock.now();
+
+        let problem_size = self.pgbb_engine.cache.gradient_u.len();
+        if u.len() != problem_size {
+            return Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: problem_size,
+                actual: u.len(),
+            });
+        }
+
+        self.pgbb_engine.init(u)?;
+
+        let mut num_iter: usize = 0;
+        let mut exit_status = ExitStatus::Converged;
+        loop {
+            num_iter += 1;
+            if !self.pgbb_engine.step(u)? {
+                break;
+            }
+            if num_iter >= self.max_iter {
+                exit_status = ExitStatus::NotConvergedIterations;
+                break;
+            }
+            if let Some(max_duration) = self.max_duration {
+                if self.clock.elapsed(now) > max_duration {
+                    exit_status = ExitStatus::NotConvergedOutOfTime;
+                    break;
+                }
+            }
+        }
+
+        let cache = &*self.pgbb_engine.cache;
+        if !matrix_operations::is_finite(u) || !cache.norm_fpr.is_finite() {
+            return Err(SolverError::NotFiniteComputation);
+        }
+
+        Ok(SolverStatus::new(
+            exit_status,
+            num_iter,
+            self.clock.elapsed(now),
+            cache.norm_fpr.as_f64(),
+            cache.cost_value.as_f64(),
+        )
+        .with_gradient_norm(matrix_operations::norm2(&cache.gradient_u).as_f64())
+        .with_oracle_calls(cache.oracle_calls))
+    }
+}
@@ -0,0 +1,132 @@
+use super::*;
+use crate::{
+    constraints,
+    core::{panoc::PANOCOptimizer, ExitStatus},
+    mocks, FunctionCallResult, Optimizer, Problem, SolverError,
+};
+use std::num::NonZeroUsize;
+
+#[test]
+fn t_pgbb_rosenbrock() {
+    let (a, b) = (1.0, 100.0);
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = mocks::rosenbrock_cost(a, b, u);
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        mocks::rosenbrock_grad(a, b, u, g);
+        Ok(())
+    };
+    let bounds = constraints::Rectangle::new(Some(&[-2.0, -2.0]), Some(&[0.5, 2.0])).unwrap();
+    let mut cache = PGBBCache::new(NonZeroUsize::new(2).unwrap(), 1e-9).unwrap();
+    let mut u = [-1.5, 1.5];
+    let status = PGBBOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+        .with_max_iter(10_000)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!(status.norm_fpr() <= 1e-9);
+
+    // same solution as PANOC
+    let mut u_panoc = [-1.5, 1.5];
+    PANOCOptimizer::from_dimensions(Problem::new(&bounds, grad, cost), 2, 1e-12, 5)
+        .unwrap()
+        .with_max_iter(10_000)
+        .unwrap()
+        .solve(&mut u_panoc)
+        .unwrap();
+    unit_test_utils::assert_nearly_equal_array(&u_panoc, &u, 1e-6, 1e-7, "u");
+    assert_eq!(0.5, u[0]);
+}
+
+#[test]
+fn t_pgbb_hard_quadratic_f32() {
+    // ill-conditioned quadratic
+    let bounds = constraints::NoConstraints::new();
+    let cost = |u: &[f32], c: &mut f32| -> FunctionCallResult {
+        *c = 0.5 * (u[0] * u[0] + 1000.0 * u[1] * u[1]) - u[0] - u[1];
+        Ok(())
+    };
+    let grad = |u: &[f32], g: &mut [f32]| -> FunctionCallResult {
+        g[0] = u[0] - 1.0;
+        g[1] = 1000.0 * u[1] - 1.0;
+        Ok(())
+    };
+    let mut cache = PGBBCache::new(NonZeroUsize::new(2).unwrap(), 1e-4_f32).unwrap();
+    let mut u = [10.0_f32, 10.0];
+    let status = PGBBOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+        .solve(&mut u)
+        .unwrap();
+    assert!(status.has_converged());
+    assert!((u[0] - 1.0).abs() < 1e-3 && (u[1] - 1e-3).abs() < 1e-6);
+    assert_eq!(status.oracle_calls().gradient(), status.iterations() + 1);
+}
+
+#[test]
+fn t_pgbb_nonmonotone_ill_conditioned() {
+    // ill-conditioned quadratic, where the nonmonotone line search (SPG)
+    // accepts more BB steps than the monotone one (M = 1)
+    let n = 50;
+    let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+        *c = u
+            .iter()
+            .enumerate()
+            .map(|(i, ui)| 0.5 * (1.0 + i as f64).powi(2) * (ui - 1.0).powi(2))
+            .sum();
+        Ok(())
+    };
+    let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+        g.iter_mut()
+            .zip(u.iter())
+            .enumerate()
+            .for_each(|(i, (gi, ui))| *gi = (1.0 + i as f64).powi(2) * (ui - 1.0));
+        Ok(())
+    };
+    let mut upper = vec![10.0; n];
+    upper[..5].fill(0.9);
+    let bounds = constraints::Rectangle::new(None, Some(&upper)).unwrap();
+    let n_nonzero = NonZeroUsize::new(n).unwrap();
+    let mut cost_evaluations = vec![];
+    for memory in [1, 10] {
+        let mut cache = PGBBCache::new(n_nonzero, 1e-8)
+            .unwrap()
+            .with_memory(NonZeroUsize::new(memory).unwrap());
+        let mut u = vec![0.0; n];
+        let status = PGBBOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+            .with_max_iter(100_000)
+            .solve(&mut u)
+            .unwrap();
+        assert!(status.has_converged());
+        assert!(u[..5].iter().all(|&ui| ui == 0.9));
+        assert!(u[5..].iter().all(|&ui| (ui - 1.0).abs() < 1e-8));
+        assert_eq!(status.oracle_calls().gradient(), status.iterations() + 1);
+        cost_evaluations.push(status.oracle_calls().cost());
+    }
+    assert!(cost_evaluations[1] < cost_evaluations[0]);
+}
+
+#[test]
+fn t_pgbb_max_iter_and_errors() {
+    let bounds = constraints::Ball2::new(None, 1.0).unwrap();
+    let problem = Problem::new(
+        &bounds,
+        mocks::hard_quadratic_gradient,
+        mocks::hard_quadratic_cost,
+    );
+    let mut cache = PGBBCache::new(NonZeroUsize::new(3).unwrap(), 1e-12).unwrap();
+    let mut optimizer = PGBBOptimizer::new(problem, &mut cache).with_max_iter(3);
+    let mut u = [0.0; 3];
+    let status = optimizer.solve(&mut u).unwrap();
+    assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+    assert_eq!(3, status.iterations());
+    assert!(matches!(
+        optimizer.solve(&mut [0.0; 2]),
+        Err(SolverError::DimensionMismatch {
+            name: "u",
+            expected: 3,
+            actual: 2,
+        })
+    ));
+    assert!(optimizer.with_tolerance(0.0).is_err());
+    assert!(PGBBCache::new(NonZeroUsize::new(3).unwrap(), -1.0).is_err());
+}
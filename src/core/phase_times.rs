@@ -0,0 +1,121 @@
+//! Time spent in the phases of a solver
+//!
+//!
+use std::{fmt, time::Duration};
+
+/// Phase of a solver, whose time is measured separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Phase {
+    /// evaluations of the cost function
+    Cost,
+    /// evaluations of the gradient of the cost function
+    Gradient,
+    /// projections on the set of constraints
+    Projection,
+    /// updates of the L-BFGS buffer and computations of directions
+    Direction,
+    /// line search (excluding its evaluations of the cost, the gradient and
+    /// projections)
+    LineSearch,
+}
+
+/// Time spent in the phases of a solver
+///
+/// If asked to (see `PANOCOptimizer::with_phase_timing` and
+/// `AlmOptimizer::with_phase_timing`), solvers measure separately the time
+/// spent in the evaluations of the cost function and of its gradient, in
+/// projections, in the updates of the L-BFGS buffer and the computation of
+/// directions (in the L-SR1 and Gauss-Newton variants too), and in the line
+/// search. The phases do not overlap: the time of the line search excludes
+/// the evaluations and projections within it, which are accounted for in
+/// the respective phases. The difference between the solve time and the
+/// `total` of the phases is the time spent in the rest of the solver
+/// (e.g., in the bookkeeping of the iterations).
+///
+/// This tells whether it pays off to speed up the oracles (the cost, its
+/// gradient and the projections) or to tune the algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhaseTimes {
+    /// time spent in evaluations of the cost function
+    cost: Duration,
+    /// time spent in evaluations of the gradient
+    gradient: Duration,
+    /// time spent in projections
+    projection: Duration,
+    /// time spent in L-BFGS updates and directions
+    direction: Duration,
+    /// time spent in line searches (excluding evaluations and projections)
+    line_search: Duration,
+}
+
+impl PhaseTimes {
+    /// Constructs new phase times (all zero)
+    pub fn new() -> Self {
+        PhaseTimes::default()
+    }
+
+    /// Adds `duration` to the time of `phase`
+    pub(crate) fn add(&mut self, phase: Phase, duration: Duration) {
+        let time = match phase {
+            Phase::Cost => &mut self.cost,
+            Phase::Gradient => &mut self.gradient,
+            Phase::Projection => &mut self.projection,
+            Phase::Direction => &mut self.direction,
+            Phase::LineSearch => &mut self.line_search,
+        };
+        *time += duration;
+    }
+
+    /// Time spent in evaluations of the cost function
+    pub fn cost(&self) -> Duration {
+        self.cost
+    }
+
+    /// Time spent in evaluations of the gradient of the cost function
+    pub fn gradient(&self) -> Duration {
+        self.gradient
+    }
+
+    /// Time spent in projections
+    pub fn projection(&self) -> Duration {
+        self.projection
+    }
+
+    /// Time spent in updates of the L-BFGS buffer and in the computation of
+    /// directions
+    pub fn direction(&self) -> Duration {
+        self.direction
+    }
+
+    /// Time spent in line searches, excluding the evaluations of the cost,
+    /// the gradient and projections
+    pub fn line_search(&self) -> Duration {
+        self.line_search
+    }
+
+    /// Total time of the phases
+    pub fn total(&self) -> Duration {
+        self.cost + self.gradient + self.projection + self.direction + self.line_search
+    }
+}
+
+impl std::ops::AddAssign for PhaseTimes {
+    fn add_assign(&mut self, other: PhaseTimes) {
+        self.cost += other.cost;
+        self.gradient += other.gradient;
+        self.projection += other.projection;
+        self.direction += other.direction;
+        self.line_search += other.line_search;
+    }
+}
+
+impl fmt::Display for PhaseTimes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "cost        : {:.3?}", self.cost)?;
+        writeln!(f, "gradient    : {:.3?}", self.gradient)?;
+        writeln!(f, "projection  : {:.3?}", self.projection)?;
+        writeln!(f, "direction   : {:.3?}", self.direction)?;
+        write!(f, "line search : {:.3?}", self.line_search)
+    }
+}
@@ -6,7 +6,27 @@
 //! Cost functions are user defined. They can either be defined in Rust or in
 //! C (and then invoked from Rust via an interface such as icasadi).
 //!
-use crate::{constraints, FunctionCallResult};
+use crate::{
+    constraints::{self, BoxedConstraint},
+    core::maybe_owned::MaybeOwned,
+    FunctionCallResult, OpEnFloat,
+};
+use std::marker::PhantomData;
+
+/// Boxed (type-erased) gradient of the cost function
+pub type BoxedGradient<'a, T = f64> = Box<dyn FnMut(&[T], &mut [T]) -> FunctionCallResult + 'a>;
+
+/// Boxed (type-erased) cost function
+pub type BoxedCost<'a, T = f64> = Box<dyn FnMut(&[T], &mut T) -> FunctionCallResult + 'a>;
+
+/// Type-erased optimisation problem
+///
+/// All problems constructed with `Problem::new_boxed` have this type,
+/// regardless of the types of their cost, gradient and constraints, so
+/// applications which solve many different problems need to compile the
+/// optimizers only once (see, e.g., `DynPANOCOptimizer`)
+pub type DynProblem<'a, T = f64> =
+    Problem<'a, BoxedGradient<'a, T>, BoxedConstraint<'a, T>, BoxedCost<'a, T>, T>;
 
 /// Definition of an optimisation problem
 ///
@@ -15,25 +35,35 @@ use crate::{constraints, FunctionCallResult};
 /// - the cost function
 /// - the set of constraints, which is described by implementations of
 ///   [Constraint](../../panoc_rs/constraints/trait.Constraint.html)
-pub struct Problem<'a, GradientType, ConstraintType, CostType>
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+///
+/// The constraints are either borrowed (see `Problem::new`) or owned by the
+/// problem (see `Problem::new_owned`). The cost and its gradient are `FnMut`
+/// closures, so they may own mutable workspaces (e.g., scratch buffers).
+pub struct Problem<'a, GradientType, ConstraintType, CostType, T = f64>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
     /// constraints
-    pub(crate) constraints: &'a ConstraintType,
+    pub(crate) constraints: MaybeOwned<'a, ConstraintType>,
     /// gradient of the cost
     pub(crate) gradf: GradientType,
     /// cost function
     pub(crate) cost: CostType,
+    marker: PhantomData<T>,
 }
 
-impl<'a, GradientType, ConstraintType, CostType> Problem<'a, GradientType, ConstraintType, CostType>
+impl<'a, GradientType, ConstraintType, CostType, T>
+    Problem<'a, GradientType, ConstraintType, CostType, T>
 where
-    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
-    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
-    ConstraintType: constraints::Constraint,
+    T: OpEnFloat,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    ConstraintType: constraints::Constraint<T>,
 {
     /// Construct a new instance of an optimisation problem
     ///
@@ -50,11 +80,115 @@ where
         constraints: &'a ConstraintType,
         cost_gradient: GradientType,
         cost: CostType,
-    ) -> Problem<'a, GradientType, ConstraintType, CostType> {
+    ) -> Problem<'a, GradientType, ConstraintType, CostType, T> {
+        Problem {
+            constraints: MaybeOwned::Borrowed(constraints),
+            gradf: cost_gradient,
+            cost,
+            marker: PhantomData,
+        }
+    }
+
+    /// Construct a new instance of an optimisation problem which takes
+    /// ownership of its constraints
+    ///
+    /// Unlike `Problem::new`, the problem does not borrow the constraints,
+    /// so, if the cost and its gradient do not borrow any data, the lifetime
+    /// parameter `'a` can be `'static`. Together with an optimizer which owns
+    /// its cache (e.g., `PANOCOptimizer::new_owned`), this allows storing the
+    /// optimizer in a long-lived struct.
+    ///
+    /// ## Arguments
+    ///
+    /// - `constraints` constraints
+    /// - `cost_gradient` gradient of the cost function
+    /// - `cost` cost function
+    ///
+    /// ## Returns
+    ///
+    /// New instance of `Problem`
+    pub fn new_owned(
+        constraints: ConstraintType,
+        cost_gradient: GradientType,
+        cost: CostType,
+    ) -> Problem<'a, GradientType, ConstraintType, CostType, T> {
         Problem {
-            constraints,
+            constraints: MaybeOwned::Owned(constraints),
             gradf: cost_gradient,
             cost,
+            marker: PhantomData,
         }
     }
 }
+
+impl<'a, T> DynProblem<'a, T>
+where
+    T: OpEnFloat,
+{
+    /// Construct a new type-erased instance of an optimisation problem
+    ///
+    /// The cost, its gradient and the constraints are boxed and the problem
+    /// owns its constraints.
+    ///
+    /// ## Arguments
+    ///
+    /// - `constraints` constraints
+    /// - `cost_gradient` gradient of the cost function
+    /// - `cost` cost function
+    ///
+    /// ## Returns
+    ///
+    /// New instance of `DynProblem`
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use optimization_engine::{constraints::*, core::DynProblem, panoc::*, *};
+    ///
+    /// // problems with different types of constraints have the same type
+    /// let problems: Vec<DynProblem> = vec![
+    ///     Problem::new_boxed(
+    ///         Ball2::new(None, 1.0)?,
+    ///         |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+    ///             grad.copy_from_slice(u);
+    ///             Ok(())
+    ///         },
+    ///         |u: &[f64], c: &mut f64| -> FunctionCallResult {
+    ///             *c = 0.5 * matrix_operations::norm2_squared(u);
+    ///             Ok(())
+    ///         },
+    ///     ),
+    ///     Problem::new_boxed(
+    ///         NoConstraints::new(),
+    ///         |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+    ///             grad.iter_mut().zip(u).for_each(|(g, ui)| *g = ui - 1.0);
+    ///             Ok(())
+    ///         },
+    ///         |u: &[f64], c: &mut f64| -> FunctionCallResult {
+    ///             *c = u.iter().map(|ui| 0.5 * (ui - 1.0).powi(2)).sum();
+    ///             Ok(())
+    ///         },
+    ///     ),
+    /// ];
+    ///
+    /// let mut cache = PANOCCache::new(2, 1e-8, 5)?;
+    /// for problem in problems {
+    ///     let mut u = [2.0, 2.0];
+    ///     let status = PANOCOptimizer::new(problem, &mut cache).solve(&mut u)?;
+    ///     assert!(status.has_converged());
+    /// }
+    /// # Ok::<(), SolverError>(())
+    /// ```
+    pub fn new_boxed<C, G, F>(constraints: C, cost_gradient: G, cost: F) -> DynProblem<'a, T>
+    where
+        C: constraints::Constraint<T> + 'a,
+        G: FnMut(&[T], &mut [T]) -> FunctionCallResult + 'a,
+        F: FnMut(&[T], &mut T) -> FunctionCallResult + 'a,
+    {
+        Problem::new_owned(
+            Box::new(constraints),
+            Box::new(cost_gradient),
+            Box::new(cost),
+        )
+    }
+}
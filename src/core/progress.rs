@@ -0,0 +1,314 @@
+//! Progress reporting of long solves
+//!
+//!
+use crate::core::ExitStatus;
+use std::{
+    fmt,
+    io::{self, Write},
+    sync::mpsc::Sender,
+    time,
+};
+
+/// Progress event, which is emitted by a solver while it is running
+///
+/// See [`Progress`](struct.Progress.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProgressEvent {
+    /// An iteration of PANOC has been completed
+    Iteration {
+        /// iteration count (starting at 0)
+        iteration: usize,
+        /// norm of the fixed-point residual
+        fpr: f64,
+        /// cost value
+        cost: f64,
+        /// norm of the (quasi-Newton) direction of the iteration
+        direction_norm: f64,
+        /// value of tau chosen by the line search
+        tau: f64,
+        /// whether the forward-backward (projected gradient) step was taken
+        /// instead of a step along the direction (then `tau` is zero)
+        forward_backward: bool,
+    },
+    /// An outer iteration of ALM has been completed
+    OuterIteration {
+        /// outer iteration count (starting at 0)
+        iteration: usize,
+        /// total number of inner iterations so far
+        inner_iterations: usize,
+        /// norm of the fixed-point residual of the inner problem
+        fpr: f64,
+        /// cost of the inner problem at its solution
+        cost: f64,
+        /// penalty parameter
+        penalty: f64,
+        /// infeasibility of the ALM-type constraints, $F_1(u) \in C$
+        alm_infeasibility: f64,
+        /// infeasibility of the PM-type constraints, $F_2(u) = 0$
+        pm_infeasibility: f64,
+    },
+    /// The solver has finished
+    Finished {
+        /// exit status of the solver
+        exit_status: ExitStatus,
+        /// number of iterations (for ALM, outer iterations)
+        iterations: usize,
+        /// solve time
+        solve_time: time::Duration,
+    },
+}
+
+/// Level of detail of the verbose mode of the solvers (see
+/// `Progress::verbose`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    /// Nothing is printed
+    #[default]
+    Silent,
+    /// A line is printed when the solver finishes
+    Summary,
+    /// A row of a table is printed every few iterations (for ALM, outer
+    /// iterations) and a line when the solver finishes
+    Iterations,
+}
+
+/// Receiver of progress events of a solver
+///
+/// A solver which is configured with `with_progress` (see
+/// `PANOCOptimizer::with_progress` and `AlmOptimizer::with_progress`) emits
+/// a `ProgressEvent` every `interval` iterations (for ALM, outer iterations)
+/// and once it finishes, so that, for example, a GUI can display a live
+/// progress indicator of a solve running on a worker thread. The events are
+/// either sent over a channel or passed to a callback, which must implement
+/// `Send` and must not borrow local variables (so that the solver can be
+/// moved to another thread).
+///
+/// # Example
+///
+/// ```rust
+/// use optimization_engine::{constraints::Ball2, core::*, panoc::*, FunctionCallResult};
+/// use std::{sync::mpsc, thread};
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let worker = thread::spawn(move || {
+///     let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+///         *c = (1.0 - u[0]).powi(2) + 10.0 * (u[1] - u[0].powi(2)).powi(2);
+///         Ok(())
+///     };
+///     let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+///         grad[0] = 2.0 * (u[0] - 1.0) - 40.0 * u[0] * (u[1] - u[0].powi(2));
+///         grad[1] = 20.0 * (u[1] - u[0].powi(2));
+///         Ok(())
+///     };
+///     let bounds = Ball2::new(None, 2.0).unwrap();
+///     let mut cache = PANOCCache::new(2, 1e-8, 5).unwrap();
+///     let mut u = [0.0, 0.0];
+///     PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache)
+///         .with_progress(Progress::from_sender(sender, 10))
+///         .solve(&mut u)
+/// });
+/// for event in receiver {
+///     println!("{}", event);
+/// }
+/// assert!(worker.join().unwrap().unwrap().has_converged());
+/// ```
+pub struct Progress {
+    callback: Box<dyn FnMut(ProgressEvent) + Send>,
+    interval: usize,
+}
+
+impl Progress {
+    /// Constructs a progress receiver which calls `callback` with every event
+    ///
+    /// ## Arguments
+    ///
+    /// - `callback`: function which is called with every event
+    /// - `interval`: events are emitted every `interval` iterations (if
+    ///   `interval` is zero, only `ProgressEvent::Finished` is emitted)
+    ///
+    pub fn from_callback<F>(callback: F, interval: usize) -> Self
+    where
+        F: FnMut(ProgressEvent) + Send + 'static,
+    {
+        Progress {
+            callback: Box::new(callback),
+            interval,
+        }
+    }
+
+    /// Constructs a progress receiver which sends every event over a channel
+    ///
+    /// Events which cannot be sent, because the receiver has been dropped,
+    /// are discarded (the solver keeps running).
+    ///
+    /// ## Arguments
+    ///
+    /// - `sender`: sending half of a channel
+    /// - `interval`: events are emitted every `interval` iterations (if
+    ///   `interval` is zero, only `ProgressEvent::Finished` is emitted)
+    ///
+    pub fn from_sender(sender: Sender<ProgressEvent>, interval: usize) -> Self {
+        Progress::from_callback(
+            move |event| {
+                let _ = sender.send(event);
+            },
+            interval,
+        )
+    }
+
+    /// Constructs a progress receiver which prints a compact table of the
+    /// iterations to `writer` (e.g., `std::io::stderr()`)
+    ///
+    /// With `Verbosity::Iterations`, a row is printed every `interval`
+    /// iterations (for ALM, outer iterations): the iteration, the cost and
+    /// the norm of the fixed-point residual for PANOC and, for ALM, the
+    /// outer iteration, the number of inner iterations so far, the cost and
+    /// the norm of the fixed-point residual of the inner problem, the penalty
+    /// parameter and the infeasibilities. The header of the table is printed
+    /// before the first row of every solve. Errors of `writer` are ignored.
+    ///
+    /// ## Arguments
+    ///
+    /// - `verbosity`: level of detail
+    /// - `interval`: rows are printed every `interval` iterations (if
+    ///   `interval` is zero, only the final line is printed)
+    /// - `writer`: destination of the table
+    ///
+    pub fn verbose<W>(verbosity: Verbosity, interval: usize, mut writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        let interval = match verbosity {
+            Verbosity::Iterations => interval,
+            _ => 0,
+        };
+        let mut header = false;
+        Progress::from_callback(
+            move |event| {
+                if verbosity != Verbosity::Silent {
+                    let _ = write_row(&mut writer, &mut header, &event);
+                }
+            },
+            interval,
+        )
+    }
+
+    /// Emits the event of an iteration, if it is due at `iteration`
+    pub(crate) fn iteration(&mut self, iteration: usize, event: impl FnOnce() -> ProgressEvent) {
+        if iteration.checked_rem(self.interval) == Some(0) {
+            (self.callback)(event());
+        }
+    }
+
+    /// Emits an event
+    pub(crate) fn emit(&mut self, event: ProgressEvent) {
+        (self.callback)(event);
+    }
+}
+
+/// Writes the row of the table of `Progress::verbose` of `event` (and the
+/// header, if it has not been written since the last solve)
+fn write_row<W: Write>(writer: &mut W, header: &mut bool, event: &ProgressEvent) -> io::Result<()> {
+    match *event {
+        ProgressEvent::Iteration {
+            iteration,
+            fpr,
+            cost,
+            ..
+        } => {
+            if !*header {
+                writeln!(writer, "{:>6} {:>13} {:>13}", "iter", "cost", "FPR")?;
+                *header = true;
+            }
+            writeln!(writer, "{:>6} {:>13.6e} {:>13.6e}", iteration, cost, fpr)
+        }
+        ProgressEvent::OuterIteration {
+            iteration,
+            inner_iterations,
+            fpr,
+            cost,
+            penalty,
+            alm_infeasibility,
+            pm_infeasibility,
+        } => {
+            if !*header {
+                writeln!(
+                    writer,
+                    "{:>6} {:>7} {:>13} {:>13} {:>13} {:>13} {:>13}",
+                    "outer", "inner", "cost", "FPR", "penalty", "infeas (ALM)", "infeas (PM)"
+                )?;
+                *header = true;
+            }
+            writeln!(
+                writer,
+                "{:>6} {:>7} {:>13.6e} {:>13.6e} {:>13.6e} {:>13.6e} {:>13.6e}",
+                iteration,
+                inner_iterations,
+                cost,
+                fpr,
+                penalty,
+                alm_infeasibility,
+                pm_infeasibility
+            )
+        }
+        ProgressEvent::Finished { .. } => {
+            *header = false;
+            writeln!(writer, "{}", event)
+        }
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressEvent::Iteration {
+                iteration,
+                fpr,
+                cost,
+                ..
+            } => write!(
+                f,
+                "iteration {}: FPR = {:.6e}, cost = {:.6e}",
+                iteration, fpr, cost
+            ),
+            ProgressEvent::OuterIteration {
+                iteration,
+                inner_iterations,
+                fpr,
+                cost,
+                penalty,
+                alm_infeasibility,
+                pm_infeasibility,
+            } => write!(
+                f,
+                "outer iteration {} ({} inner): FPR = {:.6e}, cost = {:.6e}, \
+                 penalty = {:.6e}, infeasibility = ({:.6e}, {:.6e})",
+                iteration,
+                inner_iterations,
+                fpr,
+                cost,
+                penalty,
+                alm_infeasibility,
+                pm_infeasibility
+            ),
+            ProgressEvent::Finished {
+                exit_status,
+                iterations,
+                solve_time,
+            } => write!(
+                f,
+                "finished after {} iterations ({:.3?}): {}",
+                iterations, solve_time, exit_status
+            ),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Statistics of repeated solves
+//!
+//!
+use crate::core::{ExitStatus, SolverStatus};
+use crate::SolverError;
+use std::{fmt, time::Duration};
+
+/// Aggregated statistics of the solves of an optimizer
+///
+/// The caches of `PANOCOptimizer`, `FBSOptimizer` and `AlmOptimizer` hold
+/// statistics which are updated by every call of `solve` (they are not
+/// reset when a new problem is solved), so that long-running controllers can
+/// report the health of the solver, e.g., the mean and the maximum number of
+/// iterations and solve time and the number of failures, without any
+/// bookkeeping. A failure is a solve which did not converge or which
+/// returned an error. The infeasibility of a solve is, for `AlmOptimizer`,
+/// the largest of $\Vert\Delta y\Vert/\max\{c, 1\}$ and $\Vert F_2(u)\Vert$,
+/// and zero for solvers whose solutions are feasible.
+///
+/// Statistics can also be recorded manually (e.g., for other solvers) with
+/// `record` and `record_error`.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::core::{ExitStatus, SolverStatistics};
+/// use std::time::Duration;
+///
+/// let mut statistics = SolverStatistics::new();
+/// statistics.record(ExitStatus::Converged, 10, Duration::from_millis(2), 0.0);
+/// statistics.record(ExitStatus::NotConvergedIterations, 30, Duration::from_millis(4), 1e-3);
+/// assert_eq!(2, statistics.num_solves());
+/// assert_eq!(1, statistics.num_failures());
+/// assert_eq!(20.0, statistics.mean_iterations());
+/// assert_eq!(Duration::from_millis(4), statistics.max_solve_time());
+/// statistics.reset();
+/// assert_eq!(0, statistics.num_solves());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverStatistics {
+    /// number of solves (including those which returned an error)
+    num_solves: usize,
+    /// number of solves which did not converge
+    num_not_converged: usize,
+    /// number of solves which returned an error
+    num_errors: usize,
+    /// total number of iterations of the solves which returned a status
+    total_iterations: usize,
+    /// maximum number of iterations of a solve
+    max_iterations: usize,
+    /// total solve time
+    total_solve_time: Duration,
+    /// maximum solve time
+    max_solve_time: Duration,
+    /// sum of the infeasibilities of the solves which returned a status
+    total_infeasibility: f64,
+    /// maximum infeasibility
+    max_infeasibility: f64,
+}
+
+impl SolverStatistics {
+    /// Constructs new statistics (of zero solves)
+    pub fn new() -> Self {
+        SolverStatistics::default()
+    }
+
+    /// Records a solve which returned a status
+    ///
+    /// # Arguments
+    ///
+    /// - `exit_status`: exit status of the solver
+    /// - `iterations`: number of iterations
+    /// - `solve_time`: solve time
+    /// - `infeasibility`: measure of infeasibility of the solution
+    ///
+    pub fn record(
+        &mut self,
+        exit_status: ExitStatus,
+        iterations: usize,
+        solve_time: Duration,
+        infeasibility: f64,
+    ) {
+        self.num_solves += 1;
+        if exit_status != ExitStatus::Converged {
+            self.num_not_converged += 1;
+        }
+        self.total_iterations += iterations;
+        self.max_iterations = self.max_iterations.max(iterations);
+        self.record_solve_time(solve_time);
+        self.total_infeasibility += infeasibility;
+        self.max_infeasibility = self.max_infeasibility.max(infeasibility);
+    }
+
+    /// Records a solve which returned an error after `solve_time`
+    pub fn record_error(&mut self, solve_time: Duration) {
+        self.num_solves += 1;
+        self.num_errors += 1;
+        self.record_solve_time(solve_time);
+    }
+
+    /// Records the result of a solve of an optimizer which returns a
+    /// `SolverStatus` (the infeasibility is zero)
+    pub(crate) fn record_result(
+        &mut self,
+        result: &Result<SolverStatus, SolverError>,
+        solve_time: Duration,
+    ) {
+        match result {
+            Ok(status) => self.record(
+                status.exit_status(),
+                status.iterations(),
+                status.solve_time(),
+                0.0,
+            ),
+            Err(_) => self.record_error(solve_time),
+        }
+    }
+
+    fn record_solve_time(&mut self, solve_time: Duration) {
+        self.total_solve_time += solve_time;
+        self.max_solve_time = self.max_solve_time.max(solve_time);
+    }
+
+    /// Resets the statistics (to zero solves)
+    pub fn reset(&mut self) {
+        *self = SolverStatistics::default();
+    }
+
+    /// Number of solves (including those which returned an error)
+    pub fn num_solves(&self) -> usize {
+        self.num_solves
+    }
+
+    /// Number of failures, that is, of solves which did not converge or which
+    /// returned an error
+    pub fn num_failures(&self) -> usize {
+        self.num_not_converged + self.num_errors
+    }
+
+    /// Number of solves which returned an error
+    pub fn num_errors(&self) -> usize {
+        self.num_errors
+    }
+
+    /// Fraction of the solves which failed (zero if there are no solves)
+    pub fn failure_rate(&self) -> f64 {
+        if self.num_solves == 0 {
+            0.0
+        } else {
+            self.num_failures() as f64 / self.num_solves as f64
+        }
+    }
+
+    /// Mean number of iterations of the solves which returned a status
+    /// (zero if there are none)
+    pub fn mean_iterations(&self) -> f64 {
+        self.total_iterations as f64 / self.num_statuses().max(1) as f64
+    }
+
+    /// Maximum number of iterations of a solve
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Mean solve time (zero if there are no solves)
+    pub fn mean_solve_time(&self) -> Duration {
+        self.total_solve_time / self.num_solves.max(1) as u32
+    }
+
+    /// Maximum solve time
+    pub fn max_solve_time(&self) -> Duration {
+        self.max_solve_time
+    }
+
+    /// Mean infeasibility of the solves which returned a status (zero if
+    /// there are none)
+    pub fn mean_infeasibility(&self) -> f64 {
+        self.total_infeasibility / self.num_statuses().max(1) as f64
+    }
+
+    /// Maximum infeasibility
+    pub fn max_infeasibility(&self) -> f64 {
+        self.max_infeasibility
+    }
+
+    /// Number of solves which returned a status
+    fn num_statuses(&self) -> usize {
+        self.num_solves - self.num_errors
+    }
+}
+
+impl fmt::Display for SolverStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "solves              : {}", self.num_solves)?;
+        writeln!(
+            f,
+            "failures            : {} ({} errors)",
+            self.num_failures(),
+            self.num_errors
+        )?;
+        writeln!(
+            f,
+            "iterations          : {:.1} (mean), {} (max)",
+            self.mean_iterations(),
+            self.max_iterations
+        )?;
+        writeln!(
+            f,
+            "solve time          : {:.3?} (mean), {:.3?} (max)",
+            self.mean_solve_time(),
+            self.max_solve_time
+        )?;
+        write!(
+            f,
+            "infeasibility       : {:.6e} (mean), {:.6e} (max)",
+            self.mean_infeasibility(),
+            self.max_infeasibility
+        )
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_solver_statistics() {
+        let mut statistics = SolverStatistics::new();
+        assert_eq!(0.0, statistics.failure_rate());
+        assert_eq!(Duration::ZERO, statistics.mean_solve_time());
+        statistics.record(ExitStatus::Converged, 5, Duration::from_millis(1), 0.0);
+        statistics.record(
+            ExitStatus::NotConvergedOutOfTime,
+            15,
+            Duration::from_millis(5),
+            2e-3,
+        );
+        statistics.record_error(Duration::from_millis(3));
+        assert_eq!(3, statistics.num_solves());
+        assert_eq!(2, statistics.num_failures());
+        assert_eq!(1, statistics.num_errors());
+        assert_eq!(10.0, statistics.mean_iterations());
+        assert_eq!(15, statistics.max_iterations());
+        assert_eq!(Duration::from_millis(3), statistics.mean_solve_time());
+        assert_eq!(Duration::from_millis(5), statistics.max_solve_time());
+        assert_eq!(1e-3, statistics.mean_infeasibility());
+        assert_eq!(2e-3, statistics.max_infeasibility());
+        assert!(format!("{}", statistics).contains("failures            : 2 (1 errors)"));
+        statistics.reset();
+        assert_eq!(SolverStatistics::new(), statistics);
+    }
+}
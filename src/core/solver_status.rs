@@ -1,15 +1,38 @@
 //! Status of the result of a solver (number of iterations, etc)
 //!
 //!
-use crate::core::ExitStatus;
-use std::time;
+use crate::core::{ActiveSet, ExitStatus, OracleCalls, PhaseTimes, SolverTrace};
+use std::{fmt, time};
 
 /// Solver status
 ///
 /// This structure contais information about the solver status. Instances of
 /// `SolverStatus` are returned by optimizers.
 ///
-#[derive(Debug, PartialEq, Copy, Clone)]
+/// Optionally, optimizers can record the cost and the norm of the fixed-point
+/// residual at every iteration (see, for example, `PANOCOptimizer::with_history`);
+/// these are then available via `cost_history` and `fpr_history` (PANOC also
+/// records the step size, see `gamma_history`, and the norm of the direction
+/// and the value of tau of the line search, see `direction_norm_history` and
+/// `tau_history`), and can be exported to a CSV or a JSON file using `trace`.
+///
+/// The numbers of evaluations of the cost, its gradient and of projections
+/// are available via `oracle_calls`.
+///
+/// `SolverStatus` implements `Display`, which prints a summary of the
+/// exit status, the number of iterations, the solve time, the norm of the
+/// fixed-point residual and the cost, one per line, e.g.,
+///
+/// ```text
+/// exit status : converged
+/// iterations  : 6
+/// solve time  : 21.714µs
+/// FPR         : 4.423603e-7
+/// cost        : 1.189468e-1
+/// ```
+///
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SolverStatus {
     /// exit status of the algorithm
     exit_status: ExitStatus,
@@ -21,6 +44,76 @@ pub struct SolverStatus {
     fpr_norm: f64,
     /// cost value at the candidate solution
     cost_value: f64,
+    /// norm of the gradient of the cost at the last iterate (if provided)
+    gradient_norm: Option<f64>,
+    /// cost values at every iteration (if recorded)
+    cost_history: Option<Vec<f64>>,
+    /// norms of the fixed-point residual at every iteration (if recorded)
+    fpr_history: Option<Vec<f64>>,
+    /// step sizes at every iteration (if recorded)
+    #[cfg_attr(feature = "serde", serde(default))]
+    gamma_history: Option<Vec<f64>>,
+    /// norms of the (quasi-Newton) direction at every iteration (if recorded)
+    #[cfg_attr(feature = "serde", serde(default))]
+    direction_norm_history: Option<Vec<f64>>,
+    /// values of tau of the line search at every iteration (if recorded)
+    #[cfg_attr(feature = "serde", serde(default))]
+    tau_history: Option<Vec<f64>>,
+    /// numbers of evaluations of the user-defined functions and projections
+    #[cfg_attr(feature = "serde", serde(default))]
+    oracle_calls: OracleCalls,
+    /// warnings of the solver (e.g., about the conditioning of the problem)
+    #[cfg_attr(feature = "serde", serde(default))]
+    warnings: Vec<SolverWarning>,
+    /// active set of the constraints at the solution (if identified)
+    #[cfg_attr(feature = "serde", serde(default))]
+    active_set: Option<ActiveSet>,
+    /// time spent in the phases of the solver (if measured)
+    #[cfg_attr(feature = "serde", serde(default))]
+    phase_times: Option<PhaseTimes>,
+}
+
+/// Warning about a solve, which is reported in a `SolverStatus`
+///
+/// Warnings do not, by themselves, stop the solver; for instance, PANOC stops
+/// with `ExitStatus::Diverged` on an ill-conditioned problem only if asked to
+/// (see `PANOCOptimizer::with_ill_conditioning_detection`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SolverWarning {
+    /// The step size, $\gamma$, dropped below the threshold at `iteration`
+    GammaCollapse {
+        /// iteration
+        iteration: usize,
+        /// step size
+        gamma: f64,
+    },
+    /// The estimate of the Lipschitz constant of the gradient grew, relative
+    /// to the initial estimate, by a factor `growth`, which exceeds the
+    /// threshold, at `iteration`
+    LipschitzGrowth {
+        /// iteration
+        iteration: usize,
+        /// ratio of the estimate of the Lipschitz constant to the initial one
+        growth: f64,
+    },
+}
+
+impl fmt::Display for SolverWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverWarning::GammaCollapse { iteration, gamma } => write!(
+                f,
+                "step size collapsed to {:e} at iteration {}",
+                gamma, iteration
+            ),
+            SolverWarning::LipschitzGrowth { iteration, growth } => write!(
+                f,
+                "Lipschitz estimate grew by a factor of {:e} at iteration {}",
+                growth, iteration
+            ),
+        }
+    }
 }
 
 impl SolverStatus {
@@ -48,9 +141,156 @@ impl SolverStatus {
             solve_time,
             fpr_norm,
             cost_value,
+            gradient_norm: None,
+            cost_history: None,
+            fpr_history: None,
+            gamma_history: None,
+            direction_norm_history: None,
+            tau_history: None,
+            oracle_calls: OracleCalls::default(),
+            warnings: Vec::new(),
+            active_set: None,
+            phase_times: None,
         }
     }
 
+    /// Sets the norm of the gradient of the cost at the last iterate
+    ///
+    /// ## Arguments
+    ///
+    /// - `gradient_norm`: norm of the gradient
+    ///
+    pub fn with_gradient_norm(mut self, gradient_norm: f64) -> SolverStatus {
+        self.gradient_norm = Some(gradient_norm);
+        self
+    }
+
+    /// Sets the numbers of evaluations of the user-defined functions and of
+    /// projections
+    ///
+    /// ## Arguments
+    ///
+    /// - `oracle_calls`: evaluation counters
+    ///
+    pub fn with_oracle_calls(mut self, oracle_calls: OracleCalls) -> SolverStatus {
+        self.oracle_calls = oracle_calls;
+        self
+    }
+
+    /// Sets the warnings of the solver
+    ///
+    /// ## Arguments
+    ///
+    /// - `warnings`: warnings (see `SolverWarning`)
+    ///
+    pub fn with_warnings(mut self, warnings: Vec<SolverWarning>) -> SolverStatus {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Sets the active set of the constraints at the solution
+    ///
+    /// ## Arguments
+    ///
+    /// - `active_set`: active set (see `ActiveSet`)
+    ///
+    pub fn with_active_set(mut self, active_set: ActiveSet) -> SolverStatus {
+        self.active_set = Some(active_set);
+        self
+    }
+
+    /// Sets the time spent in the phases of the solver
+    ///
+    /// ## Arguments
+    ///
+    /// - `phase_times`: time of the phases (see `PhaseTimes`)
+    ///
+    pub fn with_phase_times(mut self, phase_times: PhaseTimes) -> SolverStatus {
+        self.phase_times = Some(phase_times);
+        self
+    }
+
+    /// Sets the per-iteration cost values and norms of the fixed-point residual
+    ///
+    /// ## Arguments
+    ///
+    /// - `cost_history`: cost values at every iteration
+    /// - `fpr_history`: norms of the fixed-point residual at every iteration
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the two vectors do not have the same length
+    ///
+    pub fn with_history(mut self, cost_history: Vec<f64>, fpr_history: Vec<f64>) -> SolverStatus {
+        assert_eq!(
+            cost_history.len(),
+            fpr_history.len(),
+            "cost_history and fpr_history must have the same length"
+        );
+        self.cost_history = Some(cost_history);
+        self.fpr_history = Some(fpr_history);
+        self
+    }
+
+    /// Sets the per-iteration step sizes
+    ///
+    /// ## Arguments
+    ///
+    /// - `gamma_history`: step sizes at every iteration
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the history of the cost and the norm of the FPR
+    /// (see `with_history`) has not been set, or if it does not have the same
+    /// length as `gamma_history`
+    ///
+    pub fn with_gamma_history(mut self, gamma_history: Vec<f64>) -> SolverStatus {
+        assert_eq!(
+            self.fpr_history.as_ref().map(Vec::len),
+            Some(gamma_history.len()),
+            "gamma_history and fpr_history must have the same length"
+        );
+        self.gamma_history = Some(gamma_history);
+        self
+    }
+
+    /// Sets the per-iteration norms of the direction and values of tau of
+    /// the line search
+    ///
+    /// ## Arguments
+    ///
+    /// - `direction_norm_history`: norms of the (quasi-Newton) direction at
+    ///   every iteration
+    /// - `tau_history`: values of tau of the line search at every iteration
+    ///   (zero if the forward-backward step was taken)
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the history of the cost and the norm of the FPR
+    /// (see `with_history`) has not been set, or if it does not have the same
+    /// length as `direction_norm_history` and `tau_history`
+    ///
+    pub fn with_direction_history(
+        mut self,
+        direction_norm_history: Vec<f64>,
+        tau_history: Vec<f64>,
+    ) -> SolverStatus {
+        let length = self.fpr_history.as_ref().map(Vec::len);
+        assert_eq!(
+            length,
+            Some(direction_norm_history.len()),
+            "direction_norm_history and fpr_history must have the same length"
+        );
+        assert_eq!(
+            length,
+            Some(tau_history.len()),
+            "tau_history and fpr_history must have the same length"
+        );
+        self.direction_norm_history = Some(direction_norm_history);
+        self.tau_history = Some(tau_history);
+        self
+    }
+
     /// whether the algorithm has converged
     pub fn has_converged(&self) -> bool {
         self.exit_status == ExitStatus::Converged
@@ -80,4 +320,95 @@ impl SolverStatus {
     pub fn exit_status(&self) -> ExitStatus {
         self.exit_status
     }
+
+    /// norm of the gradient of the cost at the last iterate, or `None` if it
+    /// has not been provided by the optimizer
+    pub fn gradient_norm(&self) -> Option<f64> {
+        self.gradient_norm
+    }
+
+    /// warnings of the solver (e.g., about an ill-conditioned problem)
+    pub fn warnings(&self) -> &[SolverWarning] {
+        &self.warnings
+    }
+
+    /// active set of the constraints at the solution, if the optimizer has
+    /// been asked to identify it, otherwise `None`
+    pub fn active_set(&self) -> Option<&ActiveSet> {
+        self.active_set.as_ref()
+    }
+
+    /// time spent in the phases of the solver, if the optimizer has been
+    /// asked to measure it, otherwise `None`
+    pub fn phase_times(&self) -> Option<PhaseTimes> {
+        self.phase_times
+    }
+
+    /// cost values at every iteration, if the optimizer has been asked to
+    /// record them, otherwise `None`
+    pub fn cost_history(&self) -> Option<&[f64]> {
+        self.cost_history.as_deref()
+    }
+
+    /// norms of the fixed-point residual at every iteration, if the optimizer
+    /// has been asked to record them, otherwise `None`
+    pub fn fpr_history(&self) -> Option<&[f64]> {
+        self.fpr_history.as_deref()
+    }
+
+    /// step sizes at every iteration, if the optimizer has been asked to
+    /// record them, otherwise `None`
+    pub fn gamma_history(&self) -> Option<&[f64]> {
+        self.gamma_history.as_deref()
+    }
+
+    /// norms of the (quasi-Newton) direction at every iteration, if the
+    /// optimizer has been asked to record them, otherwise `None`
+    pub fn direction_norm_history(&self) -> Option<&[f64]> {
+        self.direction_norm_history.as_deref()
+    }
+
+    /// values of tau of the line search at every iteration (zero where the
+    /// forward-backward step was taken), if the optimizer has been asked to
+    /// record them, otherwise `None`
+    pub fn tau_history(&self) -> Option<&[f64]> {
+        self.tau_history.as_deref()
+    }
+
+    /// per-iteration history (cost, norm of the FPR and, if available, step
+    /// size, norm of the direction and tau) as a `SolverTrace`, which can be
+    /// written to a CSV or a JSON file, or `None` if the history has not been
+    /// recorded
+    pub fn trace(&self) -> Option<SolverTrace> {
+        let (cost, fpr) = (self.cost_history.as_ref()?, self.fpr_history.as_ref()?);
+        let mut trace = SolverTrace::new(fpr.clone(), cost.clone());
+        if let Some(gamma) = &self.gamma_history {
+            trace = trace.with_gamma(gamma.clone());
+        }
+        if let (Some(direction_norm), Some(tau)) = (&self.direction_norm_history, &self.tau_history)
+        {
+            trace = trace.with_direction(direction_norm.clone(), tau.clone());
+        }
+        Some(trace)
+    }
+
+    /// numbers of evaluations of the cost function, its gradient and of
+    /// projections
+    pub fn oracle_calls(&self) -> OracleCalls {
+        self.oracle_calls
+    }
+}
+
+impl fmt::Display for SolverStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "exit status : {}", self.exit_status)?;
+        writeln!(f, "iterations  : {}", self.num_iter)?;
+        writeln!(f, "solve time  : {:.3?}", self.solve_time)?;
+        writeln!(f, "FPR         : {:.6e}", self.fpr_norm)?;
+        write!(f, "cost        : {:.6e}", self.cost_value)?;
+        for warning in &self.warnings {
+            write!(f, "\nwarning     : {}", warning)?;
+        }
+        Ok(())
+    }
 }
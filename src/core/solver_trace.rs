@@ -0,0 +1,440 @@
+//! Per-iteration traces of solvers and their export to CSV and JSON files
+//!
+//!
+use crate::core::heap_size;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Per-iteration data recorded by a solver
+///
+/// A trace has one record per iteration (for `AlmOptimizer`, one per outer
+/// iteration) with the norm of the fixed-point residual and the cost and,
+/// depending on the solver, the step size, $\gamma$ (PANOC), the penalty
+/// parameter and the infeasibilities (ALM). PANOC also records the norm of
+/// the direction and the value of tau of the line search, which is zero
+/// where the forward-backward (projected gradient) step was taken. Traces
+/// are obtained using `SolverStatus::trace` and `AlmOptimizerStatus::trace`
+/// when the solver has been asked to record its history (e.g., using
+/// `PANOCOptimizer::with_history`).
+///
+/// Traces can be written to CSV files (using `write_csv`), with one row per
+/// iteration, and to JSON files (using `write_json`), with one array per
+/// column, so that convergence plots can be produced, e.g., with
+/// `pandas.read_csv` or `pandas.read_json`. With the feature `npy`, traces
+/// can also be written to NumPy `.npz` files (using `write_npz`), with one
+/// array per column, which are loaded with `numpy.load`. The columns are
+/// `iteration`,
+/// `fpr`, `cost` and, if recorded, `gamma`, `direction_norm`, `tau`,
+/// `penalty`, `alm_infeasibility` and `pm_infeasibility`.
+///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverTrace {
+    /// norms of the fixed-point residual
+    fpr: Vec<f64>,
+    /// cost values
+    cost: Vec<f64>,
+    /// step sizes (if recorded)
+    gamma: Option<Vec<f64>>,
+    /// norms of the direction (if recorded)
+    direction_norm: Option<Vec<f64>>,
+    /// values of tau of the line search (if recorded)
+    tau: Option<Vec<f64>>,
+    /// penalty parameters (if recorded)
+    penalty: Option<Vec<f64>>,
+    /// infeasibilities of the ALM-type constraints, $F_1(u) \in C$ (if recorded)
+    alm_infeasibility: Option<Vec<f64>>,
+    /// infeasibilities of the PM-type constraints, $F_2(u) = 0$ (if recorded)
+    pm_infeasibility: Option<Vec<f64>>,
+}
+
+impl SolverTrace {
+    /// Number of bytes held on the heap by the records
+    pub(crate) fn heap_size(&self) -> usize {
+        [
+            Some(&self.fpr),
+            Some(&self.cost),
+            self.gamma.as_ref(),
+            self.direction_norm.as_ref(),
+            self.tau.as_ref(),
+            self.penalty.as_ref(),
+            self.alm_infeasibility.as_ref(),
+            self.pm_infeasibility.as_ref(),
+        ]
+        .iter()
+        .flatten()
+        .map(|record| heap_size(record))
+        .sum()
+    }
+
+    /// Constructs a new trace
+    ///
+    /// ## Arguments
+    ///
+    /// - `fpr`: norms of the fixed-point residual at every iteration
+    /// - `cost`: cost values at every iteration
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the two vectors do not have the same length
+    ///
+    pub fn new(fpr: Vec<f64>, cost: Vec<f64>) -> SolverTrace {
+        assert_eq!(
+            fpr.len(),
+            cost.len(),
+            "fpr and cost must have the same length"
+        );
+        SolverTrace {
+            fpr,
+            cost,
+            gamma: None,
+            direction_norm: None,
+            tau: None,
+            penalty: None,
+            alm_infeasibility: None,
+            pm_infeasibility: None,
+        }
+    }
+
+    fn checked_column(&self, name: &str, column: Vec<f64>) -> Option<Vec<f64>> {
+        assert_eq!(
+            self.fpr.len(),
+            column.len(),
+            "{} must have the same length as fpr",
+            name
+        );
+        Some(column)
+    }
+
+    /// Sets the step sizes at every iteration
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if `gamma` does not have the length of the trace
+    ///
+    pub fn with_gamma(mut self, gamma: Vec<f64>) -> SolverTrace {
+        self.gamma = self.checked_column("gamma", gamma);
+        self
+    }
+
+    /// Sets the norms of the direction and the values of tau of the line
+    /// search at every iteration
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if either vector does not have the length of the trace
+    ///
+    pub fn with_direction(mut self, direction_norm: Vec<f64>, tau: Vec<f64>) -> SolverTrace {
+        self.direction_norm = self.checked_column("direction_norm", direction_norm);
+        self.tau = self.checked_column("tau", tau);
+        self
+    }
+
+    /// Sets the penalty parameters at every iteration
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if `penalty` does not have the length of the trace
+    ///
+    pub fn with_penalty(mut self, penalty: Vec<f64>) -> SolverTrace {
+        self.penalty = self.checked_column("penalty", penalty);
+        self
+    }
+
+    /// Sets the infeasibilities at every iteration
+    ///
+    /// ## Arguments
+    ///
+    /// - `alm_infeasibility`: infeasibilities of the constraints $F_1(u) \in C$
+    /// - `pm_infeasibility`: infeasibilities of the constraints $F_2(u) = 0$
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if either vector does not have the length of the trace
+    ///
+    pub fn with_infeasibility(
+        mut self,
+        alm_infeasibility: Vec<f64>,
+        pm_infeasibility: Vec<f64>,
+    ) -> SolverTrace {
+        self.alm_infeasibility = self.checked_column("alm_infeasibility", alm_infeasibility);
+        self.pm_infeasibility = self.checked_column("pm_infeasibility", pm_infeasibility);
+        self
+    }
+
+    /// Appends the record of an outer iteration of ALM (the trace must have
+    /// been constructed with penalty parameters and infeasibilities)
+    pub(crate) fn push_outer_iteration(
+        &mut self,
+        fpr: f64,
+        cost: f64,
+        penalty: f64,
+        alm_infeasibility: f64,
+        pm_infeasibility: f64,
+    ) {
+        self.fpr.push(fpr);
+        self.cost.push(cost);
+        let columns = [
+            (&mut self.penalty, penalty),
+            (&mut self.alm_infeasibility, alm_infeasibility),
+            (&mut self.pm_infeasibility, pm_infeasibility),
+        ];
+        for (column, value) in columns {
+            if let Some(column) = column {
+                column.push(value);
+            }
+        }
+    }
+
+    /// number of records (iterations)
+    pub fn len(&self) -> usize {
+        self.fpr.len()
+    }
+
+    /// whether the trace has no records
+    pub fn is_empty(&self) -> bool {
+        self.fpr.is_empty()
+    }
+
+    /// norms of the fixed-point residual
+    pub fn fpr(&self) -> &[f64] {
+        &self.fpr
+    }
+
+    /// cost values
+    pub fn cost(&self) -> &[f64] {
+        &self.cost
+    }
+
+    /// step sizes, if recorded
+    pub fn gamma(&self) -> Option<&[f64]> {
+        self.gamma.as_deref()
+    }
+
+    /// norms of the direction, if recorded
+    pub fn direction_norm(&self) -> Option<&[f64]> {
+        self.direction_norm.as_deref()
+    }
+
+    /// values of tau of the line search (zero where the forward-backward
+    /// step was taken), if recorded
+    pub fn tau(&self) -> Option<&[f64]> {
+        self.tau.as_deref()
+    }
+
+    /// penalty parameters, if recorded
+    pub fn penalty(&self) -> Option<&[f64]> {
+        self.penalty.as_deref()
+    }
+
+    /// infeasibilities of the constraints $F_1(u) \in C$, if recorded
+    pub fn alm_infeasibility(&self) -> Option<&[f64]> {
+        self.alm_infeasibility.as_deref()
+    }
+
+    /// infeasibilities of the constraints $F_2(u) = 0$, if recorded
+    pub fn pm_infeasibility(&self) -> Option<&[f64]> {
+        self.pm_infeasibility.as_deref()
+    }
+
+    /// names and values of the recorded columns (except `iteration`)
+    fn columns(&self) -> Vec<(&'static str, &[f64])> {
+        let mut columns = vec![("fpr", &self.fpr[..]), ("cost", &self.cost[..])];
+        let optional_columns = [
+            ("gamma", &self.gamma),
+            ("direction_norm", &self.direction_norm),
+            ("tau", &self.tau),
+            ("penalty", &self.penalty),
+            ("alm_infeasibility", &self.alm_infeasibility),
+            ("pm_infeasibility", &self.pm_infeasibility),
+        ];
+        for (name, column) in optional_columns {
+            if let Some(column) = column {
+                columns.push((name, &column[..]));
+            }
+        }
+        columns
+    }
+
+    /// Writes the trace in CSV format, with a header and one row per iteration
+    ///
+    /// ## Errors
+    ///
+    /// Returns the errors of `writer`
+    ///
+    pub fn write_csv_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let columns = self.columns();
+        write!(writer, "iteration")?;
+        for (name, _) in &columns {
+            write!(writer, ",{}", name)?;
+        }
+        writeln!(writer)?;
+        for i in 0..self.len() {
+            write!(writer, "{}", i)?;
+            for (_, column) in &columns {
+                write!(writer, ",{:?}", column[i])?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+
+    /// Writes the trace in JSON format, as an object with one array per column
+    /// (NaN and infinite values, which are not valid in JSON, are written as
+    /// `null`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns the errors of `writer`
+    ///
+    pub fn write_json_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "{{\"iteration\":[")?;
+        for i in 0..self.len() {
+            write!(writer, "{}{}", if i > 0 { "," } else { "" }, i)?;
+        }
+        write!(writer, "]")?;
+        for (name, column) in self.columns() {
+            write!(writer, ",\"{}\":[", name)?;
+            for (i, x) in column.iter().enumerate() {
+                let separator = if i > 0 { "," } else { "" };
+                if x.is_finite() {
+                    write!(writer, "{}{:?}", separator, x)?;
+                } else {
+                    write!(writer, "{}null", separator)?;
+                }
+            }
+            write!(writer, "]")?;
+        }
+        writeln!(writer, "}}")?;
+        writer.flush()
+    }
+
+    /// Writes the trace to a CSV file (see `write_csv_to`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the file cannot be created or written
+    ///
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_csv_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes the trace to a JSON file (see `write_json_to`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the file cannot be created or written
+    ///
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_json_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes the trace in NumPy `.npz` format, as an archive with one array
+    /// per column (`iteration` is an array of integers)
+    ///
+    /// ## Errors
+    ///
+    /// Returns the errors of `writer`
+    ///
+    #[cfg(feature = "npy")]
+    pub fn write_npz_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut npz = crate::npy::NpzWriter::new(writer);
+        let iterations: Vec<i64> = (0..self.len() as i64).collect();
+        npz.add("iteration", &[self.len()], &iterations)?;
+        for (name, column) in self.columns() {
+            npz.add_array(name, &[self.len()], column)?;
+        }
+        npz.finish().map(drop)
+    }
+
+    /// Writes the trace to a NumPy `.npz` file (see `write_npz_to`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the file cannot be created or written
+    ///
+    #[cfg(feature = "npy")]
+    pub fn write_npz<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_npz_to(BufWriter::new(File::create(path)?))
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_trace_csv() {
+        let trace = SolverTrace::new(vec![0.5, 1e-8], vec![2.0, 1.25]).with_gamma(vec![0.1, 0.05]);
+        let mut csv = Vec::new();
+        trace.write_csv_to(&mut csv).unwrap();
+        assert_eq!(
+            "iteration,fpr,cost,gamma\n0,0.5,2.0,0.1\n1,1e-8,1.25,0.05\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+
+    #[test]
+    fn t_trace_json() {
+        let mut trace = SolverTrace::new(vec![], vec![])
+            .with_penalty(vec![])
+            .with_infeasibility(vec![], vec![]);
+        trace.push_outer_iteration(0.5, 3.0, 10.0, 0.25, f64::NAN);
+        trace.push_outer_iteration(1e-6, 2.5, 50.0, 1e-3, 0.0);
+        assert_eq!(2, trace.len());
+        assert_eq!(Some(&[10.0, 50.0][..]), trace.penalty());
+        assert!(trace.gamma().is_none());
+        let mut json = Vec::new();
+        trace.write_json_to(&mut json).unwrap();
+        assert_eq!(
+            "{\"iteration\":[0,1],\"fpr\":[0.5,1e-6],\"cost\":[3.0,2.5],\"penalty\":[10.0,50.0],\
+             \"alm_infeasibility\":[0.25,0.001],\"pm_infeasibility\":[null,0.0]}\n",
+            String::from_utf8(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn t_trace_write_files() {
+        let trace = SolverTrace::new(vec![1.0], vec![-1.0]);
+        let path = std::env::temp_dir().join(format!("open_trace_{}", std::process::id()));
+        let (csv_path, json_path) = (path.with_extension("csv"), path.with_extension("json"));
+        trace.write_csv(&csv_path).unwrap();
+        trace.write_json(&json_path).unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let json = std::fs::read_to_string(&json_path).unwrap();
+        std::fs::remove_file(csv_path).unwrap();
+        std::fs::remove_file(json_path).unwrap();
+        assert_eq!("iteration,fpr,cost\n0,1.0,-1.0\n", csv);
+        assert_eq!("{\"iteration\":[0],\"fpr\":[1.0],\"cost\":[-1.0]}\n", json);
+    }
+
+    #[test]
+    #[cfg(feature = "npy")]
+    fn t_trace_npz() {
+        let trace = SolverTrace::new(vec![0.5, 0.25], vec![2.0, 1.0]).with_gamma(vec![0.1, 0.1]);
+        let path = std::env::temp_dir().join(format!("open_trace_{}.npz", std::process::id()));
+        trace.write_npz(&path).unwrap();
+        let npz = std::fs::read(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let names: Vec<&[u8]> = vec![b"iteration.npy", b"fpr.npy", b"cost.npy", b"gamma.npy"];
+        for name in names {
+            assert!(npz.windows(name.len()).any(|window| window == name));
+        }
+        assert!(npz
+            .windows(15)
+            .any(|window| window == &b"{'descr': '<i8'"[..]));
+        assert_eq!(&0x0605_4b50_u32.to_le_bytes(), &npz[npz.len() - 22..][..4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn t_trace_length_mismatch() {
+        let _trace = SolverTrace::new(vec![1.0, 2.0], vec![1.0, 2.0]).with_gamma(vec![1.0]);
+    }
+}
@@ -0,0 +1,930 @@
+//! Scaling and conditioning diagnostics
+//!
+//! Badly scaled problems, where the variables or the constraints differ by
+//! orders of magnitude, make the first-order solvers of OpEn slow. Given the
+//! gradient of the cost (and, optionally, a constraint mapping $F$),
+//! [`ScalingDiagnostics`] samples them near the initial point and reports, in
+//! a [`ScalingReport`],
+//!
+//! - the magnitudes of the partial derivatives of the cost,
+//! - the diagonal of the Hessian of the cost (by finite differences of the
+//!   gradient),
+//! - the norms of the rows of the Jacobian of $F$ (by finite differences),
+//!
+//! and recommends a scaling of the variables, $u = Dv$ with $D =
+//! \mathrm{diag}(d)$, which equilibrates the diagonal of the Hessian, and a
+//! scaling of the rows of $F$ which normalizes the rows of its Jacobian.
+//!
+//! Before the first solve, [`validate_problem`] checks the oracles of an
+//! [`AlmProblem`] at a test point for common setup bugs (see
+//! [`ProblemIssue`]).
+//!
+//! [`ScalingDiagnostics`]: struct.ScalingDiagnostics.html
+//! [`ScalingReport`]: struct.ScalingReport.html
+//! [`validate_problem`]: fn.validate_problem.html
+//! [`AlmProblem`]: ../alm/struct.AlmProblem.html
+//! [`ProblemIssue`]: enum.ProblemIssue.html
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{diagnostics::ScalingDiagnostics, FunctionCallResult};
+//!
+//! // f(u) = u_0^2 + 10^4 u_1^2 is badly scaled
+//! let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     grad[0] = 2.0 * u[0];
+//!     grad[1] = 2e4 * u[1];
+//!     Ok(())
+//! };
+//! let report = ScalingDiagnostics::new(&[1.0, 1.0]).analyze(gradient)?;
+//! assert!(report.curvature_ratio() > 1e3);
+//! // with u = D v, the curvatures along v_0 and v_1 are equal
+//! let d = report.variable_scaling();
+//! assert!((d[0] / d[1] - 100.0).abs() < 1e-3);
+//! println!("{}", report);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+use crate::{
+    alm::AlmProblem, constraints::Constraint, ensure, matrix_operations, multistart::SplitMix64,
+    FunctionCallResult, SolverError,
+};
+use std::fmt;
+
+/// Default number of sampled points
+const DEFAULT_NUM_SAMPLES: usize = 5;
+
+/// Default (relative) radius of the sampled neighbourhood
+const DEFAULT_RADIUS: f64 = 1e-2;
+
+/// Relative step of the finite differences
+const FINITE_DIFFERENCE_STEP: f64 = 1e-6;
+
+/// Relative tolerance of the check of the gradient by finite differences
+const GRADIENT_TOLERANCE: f64 = 1e-4;
+
+/// Relative tolerance of the check of the idempotence of projections
+const PROJECTION_TOLERANCE: f64 = 1e-10;
+
+/// Sampler of the gradient of the cost and of a constraint mapping near an
+/// initial point, which reports their scaling (see the
+/// [module documentation](index.html))
+///
+/// The points are the initial point, $u^0$, and points sampled uniformly at
+/// random in the box $u^0_i \pm r\max\\{1, |u^0_i|\\}$, where $r$ is the
+/// radius. At every point, the gradient is evaluated $n + 1$ times and the
+/// mapping (if any) $n + 1$ times, where $n$ is the number of variables.
+#[derive(Debug, Clone)]
+pub struct ScalingDiagnostics<'a> {
+    initial_point: &'a [f64],
+    num_samples: usize,
+    radius: f64,
+    seed: u64,
+}
+
+impl<'a> ScalingDiagnostics<'a> {
+    /// Constructs new diagnostics at the initial point `initial_point`, with
+    /// 5 points and radius $0.01$
+    pub fn new(initial_point: &'a [f64]) -> Self {
+        ScalingDiagnostics {
+            initial_point,
+            num_samples: DEFAULT_NUM_SAMPLES,
+            radius: DEFAULT_RADIUS,
+            seed: 0,
+        }
+    }
+
+    /// Sets the number of points (including the initial point)
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `num_samples` is zero
+    pub fn with_num_samples(mut self, num_samples: usize) -> Result<Self, SolverError> {
+        ensure(num_samples > 0, "num_samples", "must be positive")?;
+        self.num_samples = num_samples;
+        Ok(self)
+    }
+
+    /// Sets the (relative) radius of the neighbourhood of the initial point
+    /// where the points are sampled
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `radius` is negative or
+    /// not finite
+    pub fn with_radius(mut self, radius: f64) -> Result<Self, SolverError> {
+        ensure(
+            radius >= 0.0 && radius.is_finite(),
+            "radius",
+            "must be nonnegative and finite",
+        )?;
+        self.radius = radius;
+        Ok(self)
+    }
+
+    /// Sets the seed of the random number generator
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Reports the scaling of the cost function, given its gradient
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the initial point is empty,
+    /// `SolverError::NotFiniteComputation` if the gradient is not finite and
+    /// the errors of the gradient
+    pub fn analyze<G>(&self, gradient: G) -> Result<ScalingReport, SolverError>
+    where
+        G: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    {
+        let n = self.initial_point.len();
+        ensure(n > 0, "initial_point", "must not be empty")?;
+        let mut report = ScalingReport {
+            gradient_magnitudes: vec![0.0; n],
+            curvatures: vec![0.0; n],
+            variable_scaling: vec![1.0; n],
+            constraint_row_norms: Vec::new(),
+            constraint_scaling: Vec::new(),
+        };
+        let (mut grad, mut grad_perturbed) = (vec![0.0; n], vec![0.0; n]);
+        self.for_each_sample(|u| {
+            gradient(u, &mut grad)?;
+            ensure_finite(&grad)?;
+            report
+                .gradient_magnitudes
+                .iter_mut()
+                .zip(&grad)
+                .for_each(|(magnitude, g)| *magnitude = magnitude.max(g.abs()));
+            for i in 0..n {
+                let step = finite_difference_step(u[i]);
+                u[i] += step;
+                let result = gradient(u, &mut grad_perturbed);
+                u[i] -= step;
+                result?;
+                ensure_finite(&grad_perturbed)?;
+                let curvature = ((grad_perturbed[i] - grad[i]) / step).abs();
+                report.curvatures[i] = report.curvatures[i].max(curvature);
+            }
+            Ok(())
+        })?;
+
+        // d_i = 1 / sqrt(H_ii), normalized so that its geometric mean is 1;
+        // coordinates with (almost) zero curvature are not scaled
+        let max_curvature = report.curvatures.iter().cloned().fold(0.0, f64::max);
+        let threshold = max_curvature * f64::EPSILON.sqrt();
+        if max_curvature > 0.0 {
+            report
+                .variable_scaling
+                .iter_mut()
+                .zip(&report.curvatures)
+                .filter(|(_, &curvature)| curvature > threshold)
+                .for_each(|(d, &curvature)| *d = 1.0 / curvature.sqrt());
+            let log_mean = report.variable_scaling.iter().map(|d| d.ln()).sum::<f64>() / n as f64;
+            let normalization = log_mean.exp();
+            report
+                .variable_scaling
+                .iter_mut()
+                .for_each(|d| *d /= normalization);
+        }
+        Ok(report)
+    }
+
+    /// Reports the scaling of the cost function, given its gradient, and of
+    /// a mapping $F:\mathbb{R}^n\to\mathbb{R}^m$ (e.g., the mapping $F_1$ or
+    /// $F_2$ of an ALM problem), where $m$ is `mapping_dimension`
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `analyze`, `SolverError::InvalidParameter` if
+    /// `mapping_dimension` is zero and the errors of the mapping
+    pub fn analyze_with_mapping<G, F>(
+        &self,
+        gradient: G,
+        mapping: F,
+        mapping_dimension: usize,
+    ) -> Result<ScalingReport, SolverError>
+    where
+        G: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+        F: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    {
+        ensure(
+            mapping_dimension > 0,
+            "mapping_dimension",
+            "must be positive",
+        )?;
+        let mut report = self.analyze(gradient)?;
+        let n = self.initial_point.len();
+        let (mut f, mut f_perturbed) = (vec![0.0; mapping_dimension], vec![0.0; mapping_dimension]);
+        let mut squared_row_norms = vec![0.0; mapping_dimension];
+        report.constraint_row_norms = vec![0.0; mapping_dimension];
+        self.for_each_sample(|u| {
+            mapping(u, &mut f)?;
+            ensure_finite(&f)?;
+            squared_row_norms.iter_mut().for_each(|s| *s = 0.0);
+            for i in 0..n {
+                let step = finite_difference_step(u[i]);
+                u[i] += step;
+                let result = mapping(u, &mut f_perturbed);
+                u[i] -= step;
+                result?;
+                ensure_finite(&f_perturbed)?;
+                squared_row_norms
+                    .iter_mut()
+                    .zip(f_perturbed.iter().zip(&f))
+                    .for_each(|(s, (fp_j, f_j))| *s += ((fp_j - f_j) / step).powi(2));
+            }
+            report
+                .constraint_row_norms
+                .iter_mut()
+                .zip(&squared_row_norms)
+                .for_each(|(norm, s)| *norm = norm.max(s.sqrt()));
+            Ok(())
+        })?;
+        report.constraint_scaling = report
+            .constraint_row_norms
+            .iter()
+            .map(|&norm| if norm > 0.0 { 1.0 / norm } else { 1.0 })
+            .collect();
+        Ok(report)
+    }
+
+    /// Invokes `f` at every sampled point (the first one is the initial
+    /// point)
+    fn for_each_sample<F>(&self, mut f: F) -> FunctionCallResult
+    where
+        F: FnMut(&mut [f64]) -> FunctionCallResult,
+    {
+        let mut generator = SplitMix64(self.seed);
+        let mut u = self.initial_point.to_vec();
+        for sample in 0..self.num_samples {
+            if sample > 0 {
+                u.iter_mut()
+                    .zip(self.initial_point)
+                    .for_each(|(u_i, &u0_i)| {
+                        let perturbation = 2.0 * generator.next_uniform() - 1.0;
+                        *u_i = u0_i + self.radius * u0_i.abs().max(1.0) * perturbation;
+                    });
+            }
+            f(&mut u)?;
+        }
+        Ok(())
+    }
+}
+
+/// Step of the finite differences at $u_i$
+fn finite_difference_step(u_i: f64) -> f64 {
+    FINITE_DIFFERENCE_STEP * u_i.abs().max(1.0)
+}
+
+fn ensure_finite(x: &[f64]) -> FunctionCallResult {
+    if x.iter().all(|x_i| x_i.is_finite()) {
+        Ok(())
+    } else {
+        Err(SolverError::NotFiniteComputation)
+    }
+}
+
+/// Report of `ScalingDiagnostics`
+///
+/// The magnitudes and the curvatures are the largest ones over the sampled
+/// points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingReport {
+    gradient_magnitudes: Vec<f64>,
+    curvatures: Vec<f64>,
+    variable_scaling: Vec<f64>,
+    constraint_row_norms: Vec<f64>,
+    constraint_scaling: Vec<f64>,
+}
+
+impl ScalingReport {
+    /// Magnitudes of the partial derivatives of the cost, $|\partial f/
+    /// \partial u_i|$
+    pub fn gradient_magnitudes(&self) -> &[f64] {
+        &self.gradient_magnitudes
+    }
+
+    /// Estimates of the diagonal of the Hessian of the cost (in absolute
+    /// value), $|\partial^2 f/\partial u_i^2|$
+    pub fn curvatures(&self) -> &[f64] {
+        &self.curvatures
+    }
+
+    /// Recommended scaling $d$ of the variables, $u = \mathrm{diag}(d)v$,
+    /// so that the curvatures along all $v_i$ are equal
+    ///
+    /// The scaling is normalized so that the geometric mean of $d$ is 1,
+    /// and the variables along which the cost has (almost) no curvature are
+    /// not scaled
+    pub fn variable_scaling(&self) -> &[f64] {
+        &self.variable_scaling
+    }
+
+    /// Norms of the rows of the Jacobian of the mapping (empty if there is
+    /// no mapping)
+    pub fn constraint_row_norms(&self) -> &[f64] {
+        &self.constraint_row_norms
+    }
+
+    /// Recommended scaling of the rows of the mapping, that is, the inverse
+    /// norms of the rows of its Jacobian (empty if there is no mapping)
+    pub fn constraint_scaling(&self) -> &[f64] {
+        &self.constraint_scaling
+    }
+
+    /// Ratio of the largest to the smallest positive curvature, which
+    /// estimates the condition number of the Hessian (1 if all curvatures
+    /// are zero)
+    pub fn curvature_ratio(&self) -> f64 {
+        ratio(&self.curvatures)
+    }
+
+    /// Ratio of the largest to the smallest positive norm of the rows of the
+    /// Jacobian of the mapping (1 if there is no mapping)
+    pub fn constraint_row_norm_ratio(&self) -> f64 {
+        ratio(&self.constraint_row_norms)
+    }
+}
+
+/// Ratio of the largest to the smallest positive element of `x` (1 if there
+/// are none)
+fn ratio(x: &[f64]) -> f64 {
+    let (min, max) = x
+        .iter()
+        .filter(|&&x_i| x_i > 0.0)
+        .fold((f64::INFINITY, 0.0_f64), |(min, max), &x_i| {
+            (min.min(x_i), max.max(x_i))
+        });
+    if max > 0.0 {
+        max / min
+    } else {
+        1.0
+    }
+}
+
+impl fmt::Display for ScalingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = |x: &[f64]| {
+            let min = x.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = x.iter().cloned().fold(0.0, f64::max);
+            format!("{:.3e} to {:.3e}", min, max)
+        };
+        writeln!(
+            f,
+            "gradient magnitudes : {}",
+            range(&self.gradient_magnitudes)
+        )?;
+        writeln!(f, "curvatures          : {}", range(&self.curvatures))?;
+        writeln!(f, "curvature ratio     : {:.3e}", self.curvature_ratio())?;
+        write!(f, "variable scaling    : {}", range(&self.variable_scaling))?;
+        if !self.constraint_row_norms.is_empty() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "row norms (F)       : {}",
+                range(&self.constraint_row_norms)
+            )?;
+            write!(
+                f,
+                "row norm ratio (F)  : {:.3e}",
+                self.constraint_row_norm_ratio()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Setup bug of an `AlmProblem` which is found by `validate_problem`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProblemIssue {
+    /// A function returned a NaN or infinite value
+    NotFinite {
+        /// function (`"cost"`, `"gradient"`, `"F1"`, `"F2"` or the
+        /// projection on `"U"`, `"C"` or `"Y"`)
+        function: &'static str,
+        /// first non-finite entry of the output
+        index: usize,
+    },
+    /// A function did not write all the entries of its output, e.g., because
+    /// the dimension `n1` or `n2` of the problem is larger than the dimension
+    /// of the mapping
+    UnwrittenOutput {
+        /// function (`"gradient"`, `"F1"` or `"F2"`)
+        function: &'static str,
+        /// first entry which has not been written
+        index: usize,
+    },
+    /// A partial derivative of the gradient does not agree with the finite
+    /// differences of the cost
+    InconsistentGradient {
+        /// index of the partial derivative
+        index: usize,
+        /// partial derivative returned by the gradient
+        gradient: f64,
+        /// estimate of the partial derivative by central differences
+        finite_difference: f64,
+    },
+    /// The projection on a set is not idempotent, that is,
+    /// $\Pi(\Pi(x)) \neq \Pi(x)$
+    NotIdempotentProjection {
+        /// set (`"U"`, `"C"` or `"Y"`)
+        set: &'static str,
+        /// norm of $\Pi(\Pi(x)) - \Pi(x)$
+        error: f64,
+    },
+}
+
+impl fmt::Display for ProblemIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemIssue::NotFinite { function, index } => {
+                write!(f, "{} is not finite at index {}", function, index)
+            }
+            ProblemIssue::UnwrittenOutput { function, index } => write!(
+                f,
+                "{} did not write its output at index {} (dimension mismatch?)",
+                function, index
+            ),
+            ProblemIssue::InconsistentGradient {
+                index,
+                gradient,
+                finite_difference,
+            } => write!(
+                f,
+                "gradient at index {} is {:e}, but finite differences give {:e}",
+                index, gradient, finite_difference
+            ),
+            ProblemIssue::NotIdempotentProjection { set, error } => write!(
+                f,
+                "projection on {} is not idempotent (error {:e})",
+                set, error
+            ),
+        }
+    }
+}
+
+/// Report of `validate_problem`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProblemReport {
+    issues: Vec<ProblemIssue>,
+    max_gradient_error: f64,
+}
+
+impl ProblemReport {
+    /// Issues which have been found (empty if the problem passed all checks)
+    pub fn issues(&self) -> &[ProblemIssue] {
+        &self.issues
+    }
+
+    /// Whether the problem passed all checks
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Largest relative error of the partial derivatives of the gradient
+    /// with respect to the finite differences of the cost (zero if the
+    /// gradient has not been checked because the cost or the gradient is
+    /// not finite)
+    pub fn max_gradient_error(&self) -> f64 {
+        self.max_gradient_error
+    }
+}
+
+impl fmt::Display for ProblemReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gradient error      : {:.3e}", self.max_gradient_error)?;
+        if self.issues.is_empty() {
+            write!(f, "\nno issues found")?;
+        }
+        for issue in &self.issues {
+            write!(f, "\nissue               : {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the oracles of an `AlmProblem` at the test point `u` for common
+/// setup bugs
+///
+/// The checks are
+///
+/// - the finiteness of the outputs of all functions and projections,
+/// - the agreement of the dimensions of the outputs of the gradient and of
+///   the mappings $F_1$ and $F_2$ with $n_u$ (the dimension of `u`), $n_1$
+///   and $n_2$ respectively, by detecting entries which are left unwritten
+///   (a function which writes more entries than its dimension panics),
+/// - the consistency of the gradient with the cost, by central differences,
+/// - the idempotence of the projections, $\Pi(\Pi(x)) = \Pi(x)$, on $U$
+///   at `u` and on $C$ and $Y$ at $F_1(u)$.
+///
+/// The cost and its gradient are those of the problem, $\psi(u; \xi)$ and
+/// $\nabla_u \psi(u; \xi)$, with the penalty parameter $c = 1$ and the
+/// Lagrange multipliers $y = 0$ in $\xi = (c, y)$. All functions are
+/// evaluated twice, and the cost $2n_u$ more times.
+///
+/// # Errors
+///
+/// Returns `SolverError::InvalidParameter` if `u` is empty and the errors of
+/// the user-defined functions
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{
+///     alm::*, constraints::*, diagnostics::validate_problem, FunctionCallResult,
+/// };
+///
+/// let mut problem = AlmProblem::new(
+///     Ball2::new(None, 1.0)?,
+///     NO_SET,
+///     NO_SET,
+///     |u: &[f64], _xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+///         *cost = u[0].powi(2) + u[1].powi(2);
+///         Ok(())
+///     },
+///     // wrong: the gradient of u_0^2 is 2 u_0
+///     |u: &[f64], _xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+///         grad[0] = u[0];
+///         grad[1] = 2.0 * u[1];
+///         Ok(())
+///     },
+///     NO_MAPPING,
+///     NO_MAPPING,
+///     0,
+///     0,
+/// )?;
+/// let report = validate_problem(&mut problem, &[0.5, 0.5])?;
+/// assert!(!report.is_ok());
+/// println!("{}", report);
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+pub fn validate_problem<
+    MappingAlm,
+    MappingPm,
+    ParametricGradientType,
+    ParametricCostType,
+    ConstraintsType,
+    AlmSetC,
+    LagrangeSetY,
+>(
+    problem: &mut AlmProblem<
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+    >,
+    u: &[f64],
+) -> Result<ProblemReport, SolverError>
+where
+    MappingAlm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    MappingPm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[f64], &[f64], &mut f64) -> FunctionCallResult,
+    ConstraintsType: Constraint,
+    AlmSetC: Constraint,
+    LagrangeSetY: Constraint,
+{
+    let n = u.len();
+    ensure(n > 0, "u", "must not be empty")?;
+    let mut issues = Vec::new();
+
+    // xi = (c, y) with c = 1 and y = 0
+    let mut xi = vec![
+        0.0;
+        if problem.n1 + problem.n2 > 0 {
+            problem.n1 + 1
+        } else {
+            0
+        }
+    ];
+    if let Some(c) = xi.first_mut() {
+        *c = 1.0;
+    }
+    let parametric_cost = &mut problem.parametric_cost;
+    let mut cost = |u: &[f64]| -> Result<f64, SolverError> {
+        let mut cost = 0.0;
+        parametric_cost(u, &xi, &mut cost)?;
+        Ok(cost)
+    };
+    if !cost(u)?.is_finite() {
+        issues.push(ProblemIssue::NotFinite {
+            function: "cost",
+            index: 0,
+        });
+    }
+    let parametric_gradient = &mut problem.parametric_gradient;
+    let gradient = evaluate(&mut issues, "gradient", n, |grad| {
+        parametric_gradient(u, &xi, grad)
+    })?;
+
+    // central differences of the cost (if the cost and the gradient are finite)
+    let mut max_gradient_error = 0.0_f64;
+    if issues.is_empty() {
+        let mut x = u.to_vec();
+        for i in 0..n {
+            let step = f64::EPSILON.cbrt() * u[i].abs().max(1.0);
+            x[i] = u[i] + step;
+            let cost_plus = cost(&x)?;
+            x[i] = u[i] - step;
+            let cost_minus = cost(&x)?;
+            x[i] = u[i];
+            let finite_difference = (cost_plus - cost_minus) / (2.0 * step);
+            let error = (gradient[i] - finite_difference).abs()
+                / gradient[i].abs().max(finite_difference.abs()).max(1.0);
+            max_gradient_error = max_gradient_error.max(error);
+            if error > GRADIENT_TOLERANCE {
+                issues.push(ProblemIssue::InconsistentGradient {
+                    index: i,
+                    gradient: gradient[i],
+                    finite_difference,
+                });
+            }
+        }
+    }
+
+    let f1_u = match &mut problem.mapping_f1 {
+        Some(f1) if problem.n1 > 0 => {
+            Some(evaluate(&mut issues, "F1", problem.n1, |f1_u| f1(u, f1_u))?)
+        }
+        _ => None,
+    };
+    if let Some(f2) = &mut problem.mapping_f2 {
+        if problem.n2 > 0 {
+            evaluate(&mut issues, "F2", problem.n2, |f2_u| f2(u, f2_u))?;
+        }
+    }
+
+    check_projection(&mut issues, "U", &problem.constraints, u);
+    if let Some(f1_u) = &f1_u {
+        if let Some(set_c) = &problem.alm_set_c {
+            check_projection(&mut issues, "C", set_c, f1_u);
+        }
+        if let Some(set_y) = &problem.alm_set_y {
+            check_projection(&mut issues, "Y", set_y, f1_u);
+        }
+    }
+
+    Ok(ProblemReport {
+        issues,
+        max_gradient_error,
+    })
+}
+
+/// Evaluates `function` (twice) into an output of dimension `dimension` and
+/// records the entries which are not written or not finite in `issues`
+fn evaluate<F>(
+    issues: &mut Vec<ProblemIssue>,
+    name: &'static str,
+    dimension: usize,
+    mut function: F,
+) -> Result<Vec<f64>, SolverError>
+where
+    F: FnMut(&mut [f64]) -> FunctionCallResult,
+{
+    // the entries which keep both (different) initial values are not written
+    let mut output = vec![f64::MAX; dimension];
+    function(&mut output)?;
+    let mut output_check = vec![f64::MIN; dimension];
+    function(&mut output_check)?;
+    if let Some(index) = output
+        .iter()
+        .zip(&output_check)
+        .position(|(&x, &x_check)| x == f64::MAX && x_check == f64::MIN)
+    {
+        issues.push(ProblemIssue::UnwrittenOutput {
+            function: name,
+            index,
+        });
+    } else if let Some(index) = matrix_operations::first_non_finite(&output) {
+        issues.push(ProblemIssue::NotFinite {
+            function: name,
+            index,
+        });
+    }
+    Ok(output)
+}
+
+/// Checks that the projection on `set` at `x` is finite and idempotent and
+/// records the issues in `issues`
+fn check_projection<C>(issues: &mut Vec<ProblemIssue>, name: &'static str, set: &C, x: &[f64])
+where
+    C: Constraint,
+{
+    let mut projection = x.to_vec();
+    set.project(&mut projection);
+    if let Some(index) = matrix_operations::first_non_finite(&projection) {
+        issues.push(ProblemIssue::NotFinite {
+            function: name,
+            index,
+        });
+        return;
+    }
+    let mut projection_twice = projection.clone();
+    set.project(&mut projection_twice);
+    let error = matrix_operations::norm2_squared_diff(&projection, &projection_twice).sqrt();
+    if error > PROJECTION_TOLERANCE * matrix_operations::norm2(&projection).max(1.0) {
+        issues.push(ProblemIssue::NotIdempotentProjection { set: name, error });
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks;
+
+    #[test]
+    fn t_scaling_diagnostics_quadratic() {
+        // f(u) = 0.5 * (u_0^2 + 100 u_1^2 + 10^4 u_2^2)
+        let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad[0] = u[0];
+            grad[1] = 100.0 * u[1];
+            grad[2] = 1e4 * u[2];
+            Ok(())
+        };
+        let mapping = |u: &[f64], f: &mut [f64]| -> FunctionCallResult {
+            f[0] = u[0] + u[1];
+            f[1] = 1e3 * u[2];
+            Ok(())
+        };
+        let report = ScalingDiagnostics::new(&[1.0, -1.0, 2.0])
+            .with_num_samples(3)
+            .unwrap()
+            .with_radius(0.1)
+            .unwrap()
+            .analyze_with_mapping(gradient, mapping, 2)
+            .unwrap();
+        unit_test_utils::assert_nearly_equal_array(
+            &[1.0, 100.0, 1e4],
+            report.curvatures(),
+            1e-6,
+            1e-4,
+            "curvatures",
+        );
+        assert!((report.curvature_ratio() - 1e4).abs() < 1.0);
+        unit_test_utils::assert_nearly_equal_array(
+            &[10.0, 1.0, 0.1],
+            report.variable_scaling(),
+            1e-6,
+            1e-4,
+            "variable scaling",
+        );
+        // the largest gradient is at a sampled point near u_2 = 2.2
+        assert!(report.gradient_magnitudes()[2] >= 2e4);
+        assert!(report.gradient_magnitudes()[2] <= 2.2e4);
+        unit_test_utils::assert_nearly_equal_array(
+            &[2.0_f64.sqrt(), 1e3],
+            report.constraint_row_norms(),
+            1e-6,
+            1e-4,
+            "row norms",
+        );
+        assert!((report.constraint_scaling()[1] - 1e-3).abs() < 1e-9);
+        assert!(format!("{}", report).contains("row norm ratio (F)"));
+    }
+
+    #[test]
+    fn t_scaling_diagnostics_rosenbrock() {
+        let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            mocks::rosenbrock_grad(1.0, 100.0, u, grad);
+            Ok(())
+        };
+        let report = ScalingDiagnostics::new(&[0.0, 0.0])
+            .with_seed(7)
+            .analyze(gradient)
+            .unwrap();
+        assert!(report.constraint_row_norms().is_empty());
+        assert_eq!(1.0, report.constraint_row_norm_ratio());
+        // at the origin, the curvatures are about 2 and 200
+        assert!(report.curvature_ratio() > 10.0);
+        assert!(report.variable_scaling()[0] > report.variable_scaling()[1]);
+    }
+
+    #[test]
+    fn t_scaling_diagnostics_errors() {
+        let gradient = |_u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad.iter_mut().for_each(|g| *g = f64::NAN);
+            Ok(())
+        };
+        assert!(matches!(
+            ScalingDiagnostics::new(&[1.0]).analyze(gradient),
+            Err(SolverError::NotFiniteComputation)
+        ));
+        assert!(ScalingDiagnostics::new(&[]).analyze(gradient).is_err());
+        assert!(ScalingDiagnostics::new(&[1.0]).with_num_samples(0).is_err());
+        assert!(ScalingDiagnostics::new(&[1.0]).with_radius(-1.0).is_err());
+    }
+
+    #[test]
+    fn t_validate_problem() {
+        use crate::{alm::AlmProblem, constraints::*};
+        let f1 = |u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+            f1[0] = u[0] + u[1];
+            f1[1] = u[0] * u[1];
+            Ok(())
+        };
+        let f2 = |u: &[f64], f2: &mut [f64]| -> FunctionCallResult {
+            f2[0] = u[0] - u[1];
+            Ok(())
+        };
+        let mut problem = AlmProblem::new(
+            Ball2::new(None, 1.0).unwrap(),
+            Some(Ball2::new(None, 0.1).unwrap()),
+            Some(Ball2::new(None, 100.0).unwrap()),
+            mocks::psi_cost_dummy,
+            mocks::psi_gradient_dummy,
+            Some(f1),
+            Some(f2),
+            2,
+            1,
+        )
+        .unwrap();
+        let report = validate_problem(&mut problem, &[0.6, -0.3, 0.1, 0.2]).unwrap();
+        assert!(report.is_ok(), "{}", report);
+        assert!(report.max_gradient_error() < 1e-6);
+        assert!(format!("{}", report).contains("no issues found"));
+        assert!(validate_problem(&mut problem, &[]).is_err());
+    }
+
+    /// Projection which is not idempotent (it halves its argument)
+    struct Halving;
+
+    impl Constraint for Halving {
+        fn project(&self, x: &mut [f64]) {
+            x.iter_mut().for_each(|x_i| *x_i *= 0.5);
+        }
+
+        fn is_convex(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn t_validate_problem_issues() {
+        use crate::{alm::AlmProblem, constraints::*};
+        // f1 has dimension 1, but the problem declares n1 = 2
+        let f1 = |u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+            f1[0] = u[0];
+            Ok(())
+        };
+        let f2 = |_u: &[f64], f2: &mut [f64]| -> FunctionCallResult {
+            f2[0] = f64::NAN;
+            Ok(())
+        };
+        let mut problem = AlmProblem::new(
+            Halving,
+            Some(Ball2::new(None, 1.0).unwrap()),
+            Some(NoConstraints::new()),
+            |u: &[f64], _xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                *cost = u[0].powi(2) + u[1].powi(3);
+                Ok(())
+            },
+            // wrong: the second partial derivative is 3 u_1^2
+            |u: &[f64], _xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                grad[0] = 2.0 * u[0];
+                grad[1] = 2.0 * u[1].powi(2);
+                Ok(())
+            },
+            Some(f1),
+            Some(f2),
+            2,
+            1,
+        )
+        .unwrap();
+        let report = validate_problem(&mut problem, &[1.0, 2.0]).unwrap();
+        assert!(!report.is_ok());
+        assert!(report.max_gradient_error() > 0.1);
+        let issues = report.issues();
+        assert_eq!(4, issues.len(), "{}", report);
+        assert!(matches!(
+            issues[0],
+            ProblemIssue::InconsistentGradient { index: 1, .. }
+        ));
+        assert_eq!(
+            ProblemIssue::UnwrittenOutput {
+                function: "F1",
+                index: 1
+            },
+            issues[1]
+        );
+        assert_eq!(
+            ProblemIssue::NotFinite {
+                function: "F2",
+                index: 0
+            },
+            issues[2]
+        );
+        assert!(matches!(
+            issues[3],
+            ProblemIssue::NotIdempotentProjection { set: "U", .. }
+        ));
+        assert!(format!("{}", report).contains("dimension mismatch"));
+    }
+}
@@ -0,0 +1,856 @@
+#![deny(missing_docs)]
+//! C interface
+//!
+//! This module (which requires the feature `ffi`) exposes `extern "C"`
+//! functions which construct an ALM/PM solver from C function pointers, solve
+//! the problem on caller-provided buffers and report the solver status. It
+//! allows OpEn to be called from C/C++ code (e.g., flight software) without
+//! writing any bindings. The corresponding declarations are in the header file
+//! `include/open.h`.
+//!
+//! The problem is
+//!
+//! $$\begin{aligned}
+//! \mathrm{Minimize}\ f(u)
+//! \\\\
+//! u \in U
+//! \\\\
+//! F_1(u) \in C
+//! \end{aligned}$$
+//!
+//! where $U$ and $C$ are rectangles (boxes) with bounds provided by the caller.
+//! The functions $f$, $\nabla f$ and $F_1$ are C functions which return `0` on
+//! success; any other return value aborts the solver. Every callback receives
+//! the `user_data` pointer which was given to `open_solver_new`, so it can
+//! access the caller's data (e.g., the parameters of the problem).
+//!
+//! As in [`AlmProblem`](../alm/struct.AlmProblem.html), if `n1` is positive,
+//! the cost function is the augmented cost $\psi(u; \xi)$, where
+//! $\xi = (c, y)$ contains the penalty parameter and the Lagrange multipliers,
+//! and the gradient is $\nabla_u \psi(u; \xi)$.
+//!
+//! In order to link against OpEn from C, create a crate of type `staticlib`
+//! (or `cdylib`) which depends on OpEn with the feature `ffi` and re-exports
+//! this module:
+//!
+//! ```ignore
+//! pub use optimization_engine::ffi::*;
+//! ```
+//!
+//! # Example
+//!
+//! ```c
+//! int cost(const double *u, const double *xi, double *c, void *data) {
+//!     *c = u[0] * u[0] + u[1] * u[1];
+//!     return 0;
+//! }
+//!
+//! /* ... */
+//! double umin[2] = {-1.0, 0.5}, umax[2] = {1.0, 1.0}, u[2] = {0.0, 0.0};
+//! OpenSolver *solver = open_solver_new(2, 0, cost, grad, NULL,
+//!                                      umin, umax, NULL, NULL, NULL);
+//! OpenSolverStatus status = open_solver_solve(solver, u, NULL, NULL);
+//! open_solver_free(solver);
+//! ```
+//!
+use crate::{
+    alm::{AlmCache, AlmConfig, AlmOptimizer, AlmOptimizerStatus, AlmProblem, NO_MAPPING},
+    constraints::{BallInf, BoxedConstraint, NoConstraints, Rectangle},
+    core::{
+        panoc::{PANOCCache, PanocConfig},
+        ExitStatus, OracleCalls, SolverStatus,
+    },
+    FunctionCallResult, SolverError,
+};
+use std::{
+    os::raw::{c_double, c_int, c_ulong, c_ulonglong, c_void},
+    slice,
+};
+
+/// Default memory of the L-BFGS buffer
+const DEFAULT_LBFGS_MEMORY: usize = 10;
+
+/// Radius of the set $Y$ of Lagrange multipliers
+const LAGRANGE_SET_RADIUS: f64 = 1e12;
+
+/// Cost function, $f$
+///
+/// Computes `*cost` = $f(u; \xi)$, where `u` has length `nu` and `xi` has
+/// length `n1 + 1` (or `0` if `n1` is zero); returns `0` on success
+pub type OpenCostFunction = unsafe extern "C" fn(
+    u: *const c_double,
+    xi: *const c_double,
+    cost: *mut c_double,
+    user_data: *mut c_void,
+) -> c_int;
+
+/// Gradient of the cost function, $\nabla f$
+///
+/// Computes `grad` = $\nabla_u f(u; \xi)$ (of length `nu`); returns `0` on
+/// success
+pub type OpenGradientFunction = unsafe extern "C" fn(
+    u: *const c_double,
+    xi: *const c_double,
+    grad: *mut c_double,
+    user_data: *mut c_void,
+) -> c_int;
+
+/// Mapping $F_1$
+///
+/// Computes `f1u` = $F_1(u)$ (of length `n1`); returns `0` on success
+pub type OpenMappingFunction =
+    unsafe extern "C" fn(u: *const c_double, f1u: *mut c_double, user_data: *mut c_void) -> c_int;
+
+/// Exit status of the solver
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenExitStatus {
+    /// The algorithm has converged
+    Converged,
+    /// Failed to converge because the maximum number of iterations was reached
+    NotConvergedIterations,
+    /// Failed to converge because the maximum execution time was reached
+    NotConvergedOutOfTime,
+    /// The cost function, its gradient or the mapping $F_1$ failed (returned a
+    /// nonzero value)
+    NotConvergedCost,
+    /// Computation failed and NaN/Infinite value was obtained
+    NotConvergedNotFiniteComputation,
+    /// The solver could not start because of invalid input (e.g., a null
+    /// pointer or an invalid initial penalty); no solution is available
+    InvalidInput,
+    /// The solver stopped because the problem is ill-conditioned
+    Diverged,
+}
+
+/// Status of the solver
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpenSolverStatus {
+    /// Exit status
+    pub exit_status: OpenExitStatus,
+    /// Number of outer iterations
+    pub num_outer_iterations: c_ulong,
+    /// Total number of inner iterations
+    pub num_inner_iterations: c_ulong,
+    /// Norm of the fixed-point residual of the last inner problem
+    pub last_problem_norm_fpr: c_double,
+    /// Total solve time in nanoseconds
+    pub solve_time_ns: c_ulonglong,
+    /// Penalty parameter
+    pub penalty: c_double,
+    /// Norm of delta y divided by the penalty parameter
+    pub delta_y_norm_over_c: c_double,
+    /// Value of the cost function at the solution
+    pub cost: c_double,
+    /// Norm of $F_2(u)$
+    pub f2_norm: c_double,
+    /// Numbers of evaluations of the oracles
+    pub oracle_calls: OpenOracleCalls,
+}
+
+/// Numbers of evaluations of the oracles of a solve (mirror of `OracleCalls`)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OpenOracleCalls {
+    /// Evaluations of the cost function
+    pub cost: c_ulong,
+    /// Evaluations of the gradient of the cost function
+    pub gradient: c_ulong,
+    /// Evaluations of the mapping $F_1$
+    pub mapping_f1: c_ulong,
+    /// Evaluations of the mapping $F_2$
+    pub mapping_f2: c_ulong,
+    /// Projections on sets
+    pub projections: c_ulong,
+}
+
+impl From<OracleCalls> for OpenOracleCalls {
+    fn from(oracle_calls: OracleCalls) -> Self {
+        OpenOracleCalls {
+            cost: oracle_calls.cost() as c_ulong,
+            gradient: oracle_calls.gradient() as c_ulong,
+            mapping_f1: oracle_calls.mapping_f1() as c_ulong,
+            mapping_f2: oracle_calls.mapping_f2() as c_ulong,
+            projections: oracle_calls.projections() as c_ulong,
+        }
+    }
+}
+
+/// Status of PANOC and the other inner solvers (mirror of `SolverStatus`)
+///
+/// The constrained solver of this module reports an `OpenSolverStatus`; this
+/// structure is meant for C interfaces of other solvers (e.g., in crates
+/// which re-export this module), which convert their `SolverStatus` with
+/// `From`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpenInnerSolverStatus {
+    /// Exit status
+    pub exit_status: OpenExitStatus,
+    /// Number of iterations
+    pub num_iterations: c_ulong,
+    /// Norm of the fixed-point residual
+    pub norm_fpr: c_double,
+    /// Value of the cost function at the solution
+    pub cost: c_double,
+    /// Norm of the gradient at the solution (NaN if it was not computed)
+    pub gradient_norm: c_double,
+    /// Solve time in nanoseconds
+    pub solve_time_ns: c_ulonglong,
+    /// Numbers of evaluations of the oracles
+    pub oracle_calls: OpenOracleCalls,
+}
+
+impl From<&SolverStatus> for OpenInnerSolverStatus {
+    fn from(status: &SolverStatus) -> Self {
+        OpenInnerSolverStatus {
+            exit_status: status.exit_status().into(),
+            num_iterations: status.iterations() as c_ulong,
+            norm_fpr: status.norm_fpr(),
+            cost: status.cost_value(),
+            gradient_norm: status.gradient_norm().unwrap_or(c_double::NAN),
+            solve_time_ns: status.solve_time().as_nanos() as c_ulonglong,
+            oracle_calls: status.oracle_calls().into(),
+        }
+    }
+}
+
+/// Configuration of the constrained (ALM/PM) solver (mirror of `AlmConfig`)
+///
+/// Optional values are encoded as zero: a `max_duration_ns` of zero means no
+/// time limit and an `initial_penalty` of zero means that the penalty
+/// parameter of the cache is kept. The initial Lagrange multipliers are not
+/// part of the configuration; they are passed to `open_solver_solve`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpenAlmConfig {
+    /// Maximum number of outer iterations
+    pub max_outer_iterations: c_ulong,
+    /// Maximum number of inner iterations
+    pub max_inner_iterations: c_ulong,
+    /// Maximum duration in nanoseconds (zero: no limit)
+    pub max_duration_ns: c_ulonglong,
+    /// Tolerance $\delta$ (infeasibility)
+    pub delta_tolerance: c_double,
+    /// Tolerance $\epsilon$ (tolerance of the inner problems)
+    pub epsilon_tolerance: c_double,
+    /// Penalty update factor
+    pub penalty_update_factor: c_double,
+    /// Update factor of the inner tolerance
+    pub inner_tolerance_update_factor: c_double,
+    /// Initial inner tolerance, $\epsilon_0$
+    pub initial_inner_tolerance: c_double,
+    /// Sufficient decrease coefficient
+    pub sufficient_decrease_coefficient: c_double,
+    /// Initial penalty parameter, $c_0$ (zero: the penalty of the cache)
+    pub initial_penalty: c_double,
+    /// Whether to screen the outputs of the callbacks for NaN/infinite values
+    pub nan_screening: bool,
+    /// Whether to record the history of the outer iterations
+    pub record_history: bool,
+    /// Whether to use the quadratic penalty method
+    pub penalty_method: bool,
+    /// Whether to govern the outer iterations with a filter
+    pub filter: bool,
+    /// Whether to use the real-time iteration mode
+    pub real_time_iterations: bool,
+}
+
+/// Configuration of PANOC (mirror of `PanocConfig`)
+///
+/// Optional values are encoded as zero: a `tolerance`, `akkt_tolerance` or
+/// `trust_region_radius` of zero is not applied, a `max_duration_ns` of zero
+/// means no time limit and an `sr1_memory` of zero means L-BFGS directions.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpenPanocConfig {
+    /// Tolerance on the norm of the fixed-point residual (zero: not applied)
+    pub tolerance: c_double,
+    /// AKKT-specific tolerance (zero: not applied)
+    pub akkt_tolerance: c_double,
+    /// Maximum number of iterations
+    pub max_iter: c_ulong,
+    /// Maximum duration in nanoseconds (zero: no limit)
+    pub max_duration_ns: c_ulonglong,
+    /// Whether to record the cost and FPR at every iteration
+    pub record_history: bool,
+    /// Whether to screen the outputs of the cost and its gradient for
+    /// NaN/infinite values
+    pub nan_screening: bool,
+    /// Initial trust-region radius (zero: line search)
+    pub trust_region_radius: c_double,
+    /// Memory of the L-SR1 buffer (zero: L-BFGS directions)
+    pub sr1_memory: c_ulong,
+}
+
+/// Duration in nanoseconds, where zero means `None`
+fn duration_ns(duration: Option<std::time::Duration>) -> c_ulonglong {
+    duration.map_or(0, |duration| duration.as_nanos().max(1) as c_ulonglong)
+}
+
+fn duration_from_ns(duration_ns: c_ulonglong) -> Option<std::time::Duration> {
+    (duration_ns > 0).then(|| std::time::Duration::from_nanos(duration_ns))
+}
+
+/// Value, where zero means `None`
+fn optional(value: c_double) -> Option<f64> {
+    (value != 0.0).then_some(value)
+}
+
+impl From<&AlmConfig> for OpenAlmConfig {
+    fn from(config: &AlmConfig) -> Self {
+        OpenAlmConfig {
+            max_outer_iterations: config.max_outer_iterations as c_ulong,
+            max_inner_iterations: config.max_inner_iterations as c_ulong,
+            max_duration_ns: duration_ns(config.max_duration),
+            delta_tolerance: config.delta_tolerance,
+            epsilon_tolerance: config.epsilon_tolerance,
+            penalty_update_factor: config.penalty_update_factor,
+            inner_tolerance_update_factor: config.inner_tolerance_update_factor,
+            initial_inner_tolerance: config.initial_inner_tolerance,
+            sufficient_decrease_coefficient: config.sufficient_decrease_coefficient,
+            initial_penalty: config.initial_penalty.unwrap_or(0.0),
+            nan_screening: config.nan_screening,
+            record_history: config.record_history,
+            penalty_method: config.penalty_method,
+            filter: config.filter,
+            real_time_iterations: config.real_time_iterations,
+        }
+    }
+}
+
+impl From<&OpenAlmConfig> for AlmConfig {
+    fn from(config: &OpenAlmConfig) -> Self {
+        AlmConfig {
+            max_outer_iterations: config.max_outer_iterations as usize,
+            max_inner_iterations: config.max_inner_iterations as usize,
+            max_duration: duration_from_ns(config.max_duration_ns),
+            delta_tolerance: config.delta_tolerance,
+            epsilon_tolerance: config.epsilon_tolerance,
+            penalty_update_factor: config.penalty_update_factor,
+            inner_tolerance_update_factor: config.inner_tolerance_update_factor,
+            initial_inner_tolerance: config.initial_inner_tolerance,
+            sufficient_decrease_coefficient: config.sufficient_decrease_coefficient,
+            initial_lagrange_multipliers: None,
+            initial_penalty: optional(config.initial_penalty),
+            nan_screening: config.nan_screening,
+            record_history: config.record_history,
+            penalty_method: config.penalty_method,
+            filter: config.filter,
+            real_time_iterations: config.real_time_iterations,
+        }
+    }
+}
+
+impl From<&PanocConfig> for OpenPanocConfig {
+    fn from(config: &PanocConfig) -> Self {
+        OpenPanocConfig {
+            tolerance: config.tolerance.unwrap_or(0.0),
+            akkt_tolerance: config.akkt_tolerance.unwrap_or(0.0),
+            max_iter: config.max_iter as c_ulong,
+            max_duration_ns: duration_ns(config.max_duration),
+            record_history: config.record_history,
+            nan_screening: config.nan_screening,
+            trust_region_radius: config.trust_region_radius.unwrap_or(0.0),
+            sr1_memory: config.sr1_memory.unwrap_or(0) as c_ulong,
+        }
+    }
+}
+
+impl From<&OpenPanocConfig> for PanocConfig {
+    fn from(config: &OpenPanocConfig) -> Self {
+        PanocConfig {
+            tolerance: optional(config.tolerance),
+            akkt_tolerance: optional(config.akkt_tolerance),
+            max_iter: config.max_iter as usize,
+            max_duration: duration_from_ns(config.max_duration_ns),
+            record_history: config.record_history,
+            nan_screening: config.nan_screening,
+            trust_region_radius: optional(config.trust_region_radius),
+            sr1_memory: (config.sr1_memory > 0).then_some(config.sr1_memory as usize),
+        }
+    }
+}
+
+impl OpenSolverStatus {
+    fn failed(exit_status: OpenExitStatus) -> Self {
+        OpenSolverStatus {
+            exit_status,
+            num_outer_iterations: 0,
+            num_inner_iterations: 0,
+            last_problem_norm_fpr: c_double::INFINITY,
+            solve_time_ns: 0,
+            penalty: 0.0,
+            delta_y_norm_over_c: c_double::INFINITY,
+            cost: c_double::INFINITY,
+            f2_norm: c_double::INFINITY,
+            oracle_calls: OpenOracleCalls::default(),
+        }
+    }
+}
+
+impl From<ExitStatus> for OpenExitStatus {
+    fn from(exit_status: ExitStatus) -> Self {
+        match exit_status {
+            ExitStatus::Converged => OpenExitStatus::Converged,
+            ExitStatus::NotConvergedIterations => OpenExitStatus::NotConvergedIterations,
+            ExitStatus::NotConvergedOutOfTime => OpenExitStatus::NotConvergedOutOfTime,
+            ExitStatus::Diverged => OpenExitStatus::Diverged,
+        }
+    }
+}
+
+impl From<&AlmOptimizerStatus> for OpenSolverStatus {
+    fn from(status: &AlmOptimizerStatus) -> Self {
+        OpenSolverStatus {
+            exit_status: status.exit_status().into(),
+            num_outer_iterations: status.num_outer_iterations() as c_ulong,
+            num_inner_iterations: status.num_inner_iterations() as c_ulong,
+            last_problem_norm_fpr: status.last_problem_norm_fpr(),
+            solve_time_ns: status.solve_time().as_nanos() as c_ulonglong,
+            penalty: status.penalty(),
+            delta_y_norm_over_c: status.delta_y_norm_over_c(),
+            cost: status.cost(),
+            f2_norm: status.f2_norm(),
+            oracle_calls: status.oracle_calls().into(),
+        }
+    }
+}
+
+impl From<&SolverError> for OpenExitStatus {
+    fn from(error: &SolverError) -> Self {
+        match error {
+            SolverError::Cost | SolverError::User(_) => OpenExitStatus::NotConvergedCost,
+            SolverError::DimensionMismatch { .. } | SolverError::InvalidParameter { .. } => {
+                OpenExitStatus::InvalidInput
+            }
+            _ => OpenExitStatus::NotConvergedNotFiniteComputation,
+        }
+    }
+}
+
+/// C callbacks and the caller's data
+#[derive(Clone, Copy)]
+struct Callbacks {
+    cost: OpenCostFunction,
+    gradient: OpenGradientFunction,
+    mapping_f1: Option<OpenMappingFunction>,
+    user_data: *mut c_void,
+}
+
+/// Converts the return value of a callback into a `FunctionCallResult`
+fn check_return_code(code: c_int, function: &str) -> FunctionCallResult {
+    if code == 0 {
+        Ok(())
+    } else {
+        Err(SolverError::user(format!(
+            "{} returned error code {}",
+            function, code
+        )))
+    }
+}
+
+/// Solver (opaque to C)
+///
+/// Constructed with `open_solver_new` and destroyed with `open_solver_free`
+pub struct OpenSolver {
+    cache: AlmCache,
+    config: AlmConfig,
+    callbacks: Callbacks,
+    u_min: Option<Vec<f64>>,
+    u_max: Option<Vec<f64>>,
+    c_min: Option<Vec<f64>>,
+    c_max: Option<Vec<f64>>,
+    nu: usize,
+    n1: usize,
+    status: OpenSolverStatus,
+    lagrange_multipliers: Vec<f64>,
+}
+
+impl OpenSolver {
+    fn solve(
+        &mut self,
+        u: &mut [f64],
+        y0: Option<&[f64]>,
+        c0: Option<f64>,
+    ) -> Result<AlmOptimizerStatus, SolverError> {
+        let callbacks = self.callbacks;
+        let constraints: BoxedConstraint = match (self.u_min.as_deref(), self.u_max.as_deref()) {
+            (None, None) => Box::new(NoConstraints::new()),
+            (u_min, u_max) => Box::new(Rectangle::new(u_min, u_max)?),
+        };
+        let (set_c, set_y, mapping_f1) = if self.n1 > 0 {
+            let mapping_f1 = move |u: &[f64], f1u: &mut [f64]| -> FunctionCallResult {
+                let f1 = callbacks.mapping_f1.ok_or(SolverError::InvalidParameter {
+                    name: "mapping_f1",
+                    reason: "is null, but n1 is positive",
+                })?;
+                let code = unsafe { f1(u.as_ptr(), f1u.as_mut_ptr(), callbacks.user_data) };
+                check_return_code(code, "F1")
+            };
+            (
+                Some(Rectangle::new(
+                    self.c_min.as_deref(),
+                    self.c_max.as_deref(),
+                )?),
+                Some(BallInf::new(None, LAGRANGE_SET_RADIUS)?),
+                Some(mapping_f1),
+            )
+        } else {
+            (None, None, None)
+        };
+        let alm_problem = AlmProblem::new(
+            constraints,
+            set_c,
+            set_y,
+            move |u: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                let code =
+                    unsafe { (callbacks.cost)(u.as_ptr(), xi.as_ptr(), cost, callbacks.user_data) };
+                check_return_code(code, "cost function")
+            },
+            move |u: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                let code = unsafe {
+                    (callbacks.gradient)(
+                        u.as_ptr(),
+                        xi.as_ptr(),
+                        grad.as_mut_ptr(),
+                        callbacks.user_data,
+                    )
+                };
+                check_return_code(code, "gradient")
+            },
+            mapping_f1,
+            NO_MAPPING,
+            self.n1,
+            0,
+        )?;
+        let mut optimizer =
+            AlmOptimizer::new(&mut self.cache, alm_problem).with_config(&self.config)?;
+        if let Some(y0) = y0 {
+            optimizer = optimizer.with_initial_lagrange_multipliers(y0)?;
+        }
+        if let Some(c0) = c0 {
+            optimizer = optimizer.with_initial_penalty(c0)?;
+        }
+        optimizer.solve(u)
+    }
+}
+
+/// Copies `len` values from `ptr` (`None` if `ptr` is null)
+unsafe fn to_vec(ptr: *const c_double, len: usize) -> Option<Vec<f64>> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(ptr, len).to_vec())
+    }
+}
+
+/// Allocates and sets up a new solver
+///
+/// # Arguments
+///
+/// - `nu`: number of decision variables (positive)
+/// - `n1`: dimension of $F_1$ (zero if there are no such constraints)
+/// - `cost`, `gradient`: cost function and its gradient
+/// - `mapping_f1`: mapping $F_1$ (may be null if `n1` is zero)
+/// - `u_min`, `u_max`: bounds of $U$ of length `nu`; either may be null
+///   (unbounded below/above)
+/// - `c_min`, `c_max`: bounds of $C$ of length `n1`; either may be null, but not
+///   both if `n1` is positive
+/// - `user_data`: pointer which is passed to all callbacks (may be null)
+///
+/// The bounds are copied. The solver uses the default settings of
+/// `AlmOptimizer` and an L-BFGS memory of 10.
+///
+/// # Returns
+///
+/// A new solver (to be destroyed with `open_solver_free`) or null if the
+/// arguments are invalid
+///
+/// # Safety
+///
+/// All non-null pointers must point to arrays of the above lengths, and the
+/// callbacks must be safe to call with `user_data` for the lifetime of the
+/// solver
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn open_solver_new(
+    nu: c_ulong,
+    n1: c_ulong,
+    cost: Option<OpenCostFunction>,
+    gradient: Option<OpenGradientFunction>,
+    mapping_f1: Option<OpenMappingFunction>,
+    u_min: *const c_double,
+    u_max: *const c_double,
+    c_min: *const c_double,
+    c_max: *const c_double,
+    user_data: *mut c_void,
+) -> *mut OpenSolver {
+    let (nu, n1) = (nu as usize, n1 as usize);
+    let (cost, gradient) = match (cost, gradient) {
+        (Some(cost), Some(gradient)) => (cost, gradient),
+        _ => return std::ptr::null_mut(),
+    };
+    if nu == 0 || (n1 > 0 && (mapping_f1.is_none() || (c_min.is_null() && c_max.is_null()))) {
+        return std::ptr::null_mut();
+    }
+    let config = AlmConfig::default();
+    let panoc_cache = match PANOCCache::new(nu, config.epsilon_tolerance, DEFAULT_LBFGS_MEMORY) {
+        Ok(panoc_cache) => panoc_cache,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let solver = OpenSolver {
+        cache: AlmCache::new(panoc_cache, n1, 0),
+        config,
+        callbacks: Callbacks {
+            cost,
+            gradient,
+            mapping_f1,
+            user_data,
+        },
+        u_min: to_vec(u_min, nu),
+        u_max: to_vec(u_max, nu),
+        c_min: to_vec(c_min, n1),
+        c_max: to_vec(c_max, n1),
+        nu,
+        n1,
+        status: OpenSolverStatus::failed(OpenExitStatus::InvalidInput),
+        lagrange_multipliers: vec![0.0; n1],
+    };
+    Box::into_raw(Box::new(solver))
+}
+
+/// Sets the tolerances $\epsilon$ (of the inner problems) and $\delta$ (on
+/// the infeasibility)
+///
+/// Returns `0` on success and `-1` if the solver is null or a tolerance is
+/// not positive (in which case the settings are not changed)
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new`
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_set_tolerances(
+    solver: *mut OpenSolver,
+    epsilon_tolerance: c_double,
+    delta_tolerance: c_double,
+) -> c_int {
+    match solver.as_mut() {
+        Some(solver) if epsilon_tolerance > 0.0 && delta_tolerance > 0.0 => {
+            solver.config.epsilon_tolerance = epsilon_tolerance;
+            solver.config.delta_tolerance = delta_tolerance;
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Sets the maximum numbers of outer and inner iterations and the maximum
+/// solution time in microseconds (zero means no time limit)
+///
+/// Returns `0` on success and `-1` if the solver is null or a number of
+/// iterations is zero (in which case the settings are not changed)
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new`
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_set_limits(
+    solver: *mut OpenSolver,
+    max_outer_iterations: c_ulong,
+    max_inner_iterations: c_ulong,
+    max_duration_us: c_ulonglong,
+) -> c_int {
+    match solver.as_mut() {
+        Some(solver) if max_outer_iterations > 0 && max_inner_iterations > 0 => {
+            solver.config.max_outer_iterations = max_outer_iterations as usize;
+            solver.config.max_inner_iterations = max_inner_iterations as usize;
+            solver.config.max_duration = if max_duration_us > 0 {
+                Some(std::time::Duration::from_micros(max_duration_us))
+            } else {
+                None
+            };
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Default configuration of the constrained solver (the default values of
+/// `AlmOptimizer`)
+#[no_mangle]
+pub extern "C" fn open_alm_config_default() -> OpenAlmConfig {
+    OpenAlmConfig::from(&AlmConfig::default())
+}
+
+/// Default configuration of PANOC
+#[no_mangle]
+pub extern "C" fn open_panoc_config_default() -> OpenPanocConfig {
+    OpenPanocConfig::from(&PanocConfig::default())
+}
+
+/// Copies the configuration of the solver into `config`
+///
+/// Returns `0` on success and `-1` if a pointer is null
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new` and
+/// `config` must be null or valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_get_config(
+    solver: *const OpenSolver,
+    config: *mut OpenAlmConfig,
+) -> c_int {
+    match (solver.as_ref(), config.as_mut()) {
+        (Some(solver), Some(config)) => {
+            *config = OpenAlmConfig::from(&solver.config);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Replaces the configuration of the solver (e.g., a configuration from
+/// `open_alm_config_default` with some modified fields)
+///
+/// Returns `0` on success and `-1` if a pointer is null, a tolerance is not
+/// positive or a number of iterations is zero (in which case the settings
+/// are not changed); the other values are checked when the problem is
+/// solved (invalid values make `open_solver_solve` fail with the exit status
+/// `InvalidInput`)
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new` and
+/// `config` must be null or point to a valid configuration
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_set_config(
+    solver: *mut OpenSolver,
+    config: *const OpenAlmConfig,
+) -> c_int {
+    match (solver.as_mut(), config.as_ref()) {
+        (Some(solver), Some(config))
+            if config.epsilon_tolerance > 0.0
+                && config.delta_tolerance > 0.0
+                && config.max_outer_iterations > 0
+                && config.max_inner_iterations > 0 =>
+        {
+            solver.config = AlmConfig::from(config);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Solves the problem
+///
+/// # Arguments
+///
+/// - `solver`: the solver
+/// - `u`: initial guess of length `nu`, which is overwritten with the solution
+/// - `y0`: initial Lagrange multipliers of length `n1` (null: warm start from
+///   the previous solution)
+/// - `c0`: initial penalty parameter (null: default value)
+///
+/// # Returns
+///
+/// The solver status, which is also available from `open_solver_status`. If
+/// the solver cannot start (e.g., `solver` or `u` is null), the exit status
+/// is `InvalidInput`.
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new`, and the
+/// non-null pointers `u`, `y0` and `c0` must point to arrays of the above lengths
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_solve(
+    solver: *mut OpenSolver,
+    u: *mut c_double,
+    y0: *const c_double,
+    c0: *const c_double,
+) -> OpenSolverStatus {
+    let solver = match solver.as_mut() {
+        Some(solver) => solver,
+        None => return OpenSolverStatus::failed(OpenExitStatus::InvalidInput),
+    };
+    if u.is_null() {
+        solver.status = OpenSolverStatus::failed(OpenExitStatus::InvalidInput);
+        return solver.status;
+    }
+    let u = slice::from_raw_parts_mut(u, solver.nu);
+    let y0 = if y0.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(y0, solver.n1))
+    };
+    solver.status = match solver.solve(u, y0, c0.as_ref().copied()) {
+        Ok(status) => {
+            if let Some(y) = status.lagrange_multipliers() {
+                solver.lagrange_multipliers.copy_from_slice(y);
+            }
+            OpenSolverStatus::from(&status)
+        }
+        Err(error) => OpenSolverStatus::failed(OpenExitStatus::from(&error)),
+    };
+    solver.status
+}
+
+/// Returns the status of the last call of `open_solver_solve`
+///
+/// If the problem has not been solved yet or `solver` is null, the exit
+/// status is `InvalidInput`
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new`
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_status(solver: *const OpenSolver) -> OpenSolverStatus {
+    match solver.as_ref() {
+        Some(solver) => solver.status,
+        None => OpenSolverStatus::failed(OpenExitStatus::InvalidInput),
+    }
+}
+
+/// Copies the Lagrange multipliers of the last solution into `y` (of length
+/// `n1`)
+///
+/// Returns `0` on success and `-1` if a pointer is null
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new` and `y`
+/// must be null or point to an array of length `n1`
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_lagrange_multipliers(
+    solver: *const OpenSolver,
+    y: *mut c_double,
+) -> c_int {
+    match solver.as_ref() {
+        Some(solver) if !y.is_null() => {
+            slice::from_raw_parts_mut(y, solver.n1).copy_from_slice(&solver.lagrange_multipliers);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Destroys a solver which was constructed with `open_solver_new`
+///
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `open_solver_new` which
+/// has not been freed
+#[no_mangle]
+pub unsafe extern "C" fn open_solver_free(solver: *mut OpenSolver) {
+    if !solver.is_null() {
+        drop(Box::from_raw(solver));
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,323 @@
+use super::*;
+use std::ptr;
+
+/// Data of the test problems (passed to the callbacks as `user_data`)
+struct Data {
+    a: f64,
+    n1: usize,
+}
+
+/// Cost $\psi(u; \xi) = \|u - a\|^2 + \frac{c}{2}\mathrm{dist}_C^2(F_1(u) + y/c)$,
+/// with $F_1(u) = u_1 + u_2$ and $C = (-\infty, 1]$ (if `n1` is 1)
+fn penalty_term(u: &[f64], xi: &[f64]) -> f64 {
+    if xi.is_empty() {
+        return 0.0;
+    }
+    let (c, y) = (xi[0], xi[1]);
+    (u[0] + u[1] + y / c - 1.0).max(0.0)
+}
+
+unsafe extern "C" fn cost(
+    u: *const c_double,
+    xi: *const c_double,
+    cost: *mut c_double,
+    user_data: *mut c_void,
+) -> c_int {
+    let data = &*(user_data as *const Data);
+    let a = data.a;
+    let u = slice::from_raw_parts(u, 2);
+    let xi = slice::from_raw_parts(xi, if data.n1 > 0 { data.n1 + 1 } else { 0 });
+    let d = penalty_term(u, xi);
+    let c = xi.first().copied().unwrap_or(0.0);
+    *cost = (u[0] - a).powi(2) + (u[1] - a).powi(2) + 0.5 * c * d * d;
+    0
+}
+
+unsafe extern "C" fn gradient(
+    u: *const c_double,
+    xi: *const c_double,
+    grad: *mut c_double,
+    user_data: *mut c_void,
+) -> c_int {
+    let data = &*(user_data as *const Data);
+    let a = data.a;
+    let u = slice::from_raw_parts(u, 2);
+    let xi = slice::from_raw_parts(xi, if data.n1 > 0 { data.n1 + 1 } else { 0 });
+    let grad = slice::from_raw_parts_mut(grad, 2);
+    let d = penalty_term(u, xi);
+    let c = xi.first().copied().unwrap_or(0.0);
+    grad[0] = 2.0 * (u[0] - a) + c * d;
+    grad[1] = 2.0 * (u[1] - a) + c * d;
+    0
+}
+
+unsafe extern "C" fn mapping_f1(
+    u: *const c_double,
+    f1u: *mut c_double,
+    _user_data: *mut c_void,
+) -> c_int {
+    *f1u = *u + *u.add(1);
+    0
+}
+
+unsafe extern "C" fn failing_cost(
+    _u: *const c_double,
+    _xi: *const c_double,
+    _cost: *mut c_double,
+    _user_data: *mut c_void,
+) -> c_int {
+    -5
+}
+
+#[test]
+fn t_ffi_box_constraints() {
+    let mut data = Data { a: 2.0, n1: 0 };
+    let u_max = [1.0, 0.5];
+    let mut u = [0.0; 2];
+    unsafe {
+        let solver = open_solver_new(
+            2,
+            0,
+            Some(cost),
+            Some(gradient),
+            None,
+            ptr::null(),
+            u_max.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            &mut data as *mut Data as *mut c_void,
+        );
+        assert!(!solver.is_null());
+        assert_eq!(
+            OpenExitStatus::InvalidInput,
+            open_solver_status(solver).exit_status
+        );
+        let status = open_solver_solve(solver, u.as_mut_ptr(), ptr::null(), ptr::null());
+        assert_eq!(OpenExitStatus::Converged, status.exit_status);
+        assert_eq!(status, open_solver_status(solver));
+        assert!(status.num_inner_iterations > 0);
+        open_solver_free(solver);
+    }
+    unit_test_utils::assert_nearly_equal_array(&[1.0, 0.5], &u, 1e-6, 1e-6, "u");
+}
+
+#[test]
+fn t_ffi_alm_constraints() {
+    let mut data = Data { a: 2.0, n1: 1 };
+    let c_max = [1.0];
+    let mut u = [0.0; 2];
+    let mut y = [0.0; 1];
+    unsafe {
+        let solver = open_solver_new(
+            2,
+            1,
+            Some(cost),
+            Some(gradient),
+            Some(mapping_f1),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            c_max.as_ptr(),
+            &mut data as *mut Data as *mut c_void,
+        );
+        assert!(!solver.is_null());
+        assert_eq!(0, open_solver_set_tolerances(solver, 1e-6, 1e-5));
+        assert_eq!(-1, open_solver_set_tolerances(solver, -1.0, 1e-5));
+        assert_eq!(0, open_solver_set_limits(solver, 100, 1000, 0));
+        assert_eq!(-1, open_solver_set_limits(solver, 0, 1000, 0));
+        let c0 = 10.0;
+        let status = open_solver_solve(solver, u.as_mut_ptr(), ptr::null(), &c0);
+        assert_eq!(OpenExitStatus::Converged, status.exit_status);
+        assert!(status.delta_y_norm_over_c < 1e-5);
+        assert!(status.oracle_calls.mapping_f1 > 0);
+        assert!(status.oracle_calls.gradient >= status.num_inner_iterations);
+        assert_eq!(0, open_solver_lagrange_multipliers(solver, y.as_mut_ptr()));
+        assert_eq!(
+            -1,
+            open_solver_lagrange_multipliers(solver, ptr::null_mut())
+        );
+        open_solver_free(solver);
+    }
+    unit_test_utils::assert_nearly_equal_array(&[0.5, 0.5], &u, 1e-4, 1e-4, "u");
+    // y = c * dist, which approximates the multiplier of u1 + u2 <= 1, that is 3
+    unit_test_utils::assert_nearly_equal(3.0, y[0], 1e-3, 1e-3, "y");
+}
+
+#[test]
+fn t_ffi_config() {
+    let mut data = Data { a: 2.0, n1: 0 };
+    let mut u = [0.0; 2];
+    unsafe {
+        let solver = open_solver_new(
+            2,
+            0,
+            Some(cost),
+            Some(gradient),
+            None,
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            &mut data as *mut Data as *mut c_void,
+        );
+        let mut config = open_alm_config_default();
+        assert_eq!(0, open_solver_get_config(solver, &mut config));
+        assert_eq!(open_alm_config_default(), config);
+        assert_eq!(50, config.max_outer_iterations);
+        assert_eq!(0, config.max_duration_ns);
+
+        config.max_outer_iterations = 1;
+        config.max_inner_iterations = 1;
+        config.max_duration_ns = 5_000_000_000;
+        assert_eq!(0, open_solver_set_config(solver, &config));
+        let mut stored = open_alm_config_default();
+        open_solver_get_config(solver, &mut stored);
+        assert_eq!(config, stored);
+        let status = open_solver_solve(solver, u.as_mut_ptr(), ptr::null(), ptr::null());
+        assert_eq!(
+            (1, 1),
+            (status.num_outer_iterations, status.num_inner_iterations)
+        );
+
+        config.epsilon_tolerance = 0.0;
+        assert_eq!(-1, open_solver_set_config(solver, &config));
+        assert_eq!(-1, open_solver_set_config(solver, ptr::null()));
+        assert_eq!(-1, open_solver_get_config(ptr::null(), &mut stored));
+        open_solver_free(solver);
+    }
+
+    // the conversions preserve the optional values
+    let alm_config = AlmConfig {
+        max_duration: Some(std::time::Duration::from_millis(3)),
+        initial_penalty: Some(20.0),
+        filter: true,
+        ..AlmConfig::default()
+    };
+    assert_eq!(
+        alm_config,
+        AlmConfig::from(&OpenAlmConfig::from(&alm_config))
+    );
+    let panoc_config = PanocConfig::new()
+        .with_tolerance(1e-5)
+        .with_sr1(4)
+        .with_max_iter(30);
+    assert_eq!(
+        panoc_config,
+        PanocConfig::from(&OpenPanocConfig::from(&panoc_config))
+    );
+    assert_eq!(0, open_panoc_config_default().sr1_memory);
+    assert_eq!(
+        PanocConfig::default(),
+        PanocConfig::from(&open_panoc_config_default())
+    );
+}
+
+#[test]
+fn t_ffi_inner_solver_status() {
+    let bounds = crate::constraints::Ball2::new(None, 1.0).unwrap();
+    let mut cache = PANOCCache::new(2, 1e-8, 5).unwrap();
+    let problem =
+        crate::core::Problem::new(&bounds, crate::mocks::my_gradient, crate::mocks::my_cost);
+    let mut u = [0.75, 0.0];
+    let status = crate::core::Optimizer::solve(
+        &mut crate::panoc::PANOCOptimizer::new(problem, &mut cache),
+        &mut u,
+    )
+    .unwrap();
+    let c_status = OpenInnerSolverStatus::from(&status);
+    assert_eq!(OpenExitStatus::Converged, c_status.exit_status);
+    assert_eq!(status.iterations() as c_ulong, c_status.num_iterations);
+    assert_eq!(status.norm_fpr(), c_status.norm_fpr);
+    assert_eq!(
+        status.oracle_calls().cost() as c_ulong,
+        c_status.oracle_calls.cost
+    );
+    assert_eq!(
+        status.gradient_norm().is_none(),
+        c_status.gradient_norm.is_nan()
+    );
+    assert_eq!(
+        OpenExitStatus::Diverged,
+        OpenExitStatus::from(ExitStatus::Diverged)
+    );
+}
+
+#[test]
+fn t_ffi_invalid_arguments() {
+    let u_max = [1.0, 1.0];
+    unsafe {
+        // missing gradient
+        assert!(open_solver_new(
+            2,
+            0,
+            Some(cost),
+            None,
+            None,
+            ptr::null(),
+            u_max.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null_mut(),
+        )
+        .is_null());
+        // n1 > 0, but no mapping F1
+        assert!(open_solver_new(
+            2,
+            1,
+            Some(cost),
+            Some(gradient),
+            None,
+            ptr::null(),
+            u_max.as_ptr(),
+            u_max.as_ptr(),
+            ptr::null(),
+            ptr::null_mut(),
+        )
+        .is_null());
+        // no decision variables
+        assert!(open_solver_new(
+            0,
+            0,
+            Some(cost),
+            Some(gradient),
+            None,
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null_mut(),
+        )
+        .is_null());
+        let status = open_solver_solve(ptr::null_mut(), ptr::null_mut(), ptr::null(), ptr::null());
+        assert_eq!(OpenExitStatus::InvalidInput, status.exit_status);
+        open_solver_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn t_ffi_failing_callback() {
+    let mut data = Data { a: 2.0, n1: 0 };
+    let mut u = [0.0; 2];
+    unsafe {
+        let solver = open_solver_new(
+            2,
+            0,
+            Some(failing_cost),
+            Some(gradient),
+            None,
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            &mut data as *mut Data as *mut c_void,
+        );
+        assert!(!solver.is_null());
+        let status = open_solver_solve(solver, u.as_mut_ptr(), ptr::null(), ptr::null());
+        assert_eq!(OpenExitStatus::NotConvergedCost, status.exit_status);
+        // invalid initial penalty
+        let c0 = -1.0;
+        let status = open_solver_solve(solver, u.as_mut_ptr(), ptr::null(), &c0);
+        assert_eq!(OpenExitStatus::InvalidInput, status.exit_status);
+        open_solver_free(solver);
+    }
+}
@@ -0,0 +1,578 @@
+//! Gradients by finite differences
+//!
+//! [`FiniteDifferenceGradient`] wraps a cost function and approximates its
+//! gradient by forward differences,
+//!
+//! $$
+//! \frac{\partial f(u)}{\partial u_i} \approx \frac{f(u + h_i e_i) - f(u)}{h_i},
+//! $$
+//!
+//! or central differences,
+//!
+//! $$
+//! \frac{\partial f(u)}{\partial u_i} \approx \frac{f(u + h_i e_i) - f(u - h_i e_i)}{2h_i},
+//! $$
+//!
+//! with $h_i = \delta \max\\{1, |u_i|\\}$, where $\delta$ is the (relative)
+//! step. This is useful for prototyping, when an analytic gradient is not
+//! available yet; the approximation error (about $\delta$ for forward and
+//! $\delta^2$ for central differences, plus the rounding errors of the cost
+//! function divided by $\delta$) may slow down the solvers or prevent them
+//! from reaching tight tolerances. Exact gradients can be computed using
+//! [`autodiff`](../autodiff/index.html).
+//!
+//! If the cost function is analytic and can be evaluated at complex points,
+//! [`ComplexStepGradient`] computes the gradient by the complex step,
+//!
+//! $$
+//! \frac{\partial f(u)}{\partial u_i} \approx \frac{\mathrm{Im}\, f(u + \mathrm{i} h e_i)}{h},
+//! $$
+//!
+//! which involves no subtraction, hence no cancellation: the step can be
+//! tiny (by default, $h = 10^{-20}$) and the error of the gradient is of the
+//! order of the machine epsilon.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     constraints::Ball2, finite_difference::FiniteDifferenceGradient, panoc::*,
+//!     FunctionCallResult, Optimizer, Problem,
+//! };
+//!
+//! let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = (1.0 - u[0]).powi(2) + 10.0 * (u[1] - u[0].powi(2)).powi(2);
+//!     Ok(())
+//! };
+//! let finite_difference = FiniteDifferenceGradient::new(2, cost);
+//! let bounds = Ball2::new(None, 2.0)?;
+//! let problem = Problem::new(
+//!     &bounds,
+//!     |u: &[f64], grad: &mut [f64]| finite_difference.gradient(u, grad),
+//!     cost,
+//! );
+//! let mut u = [0.0, 0.0];
+//! let status = PANOCOptimizer::from_dimensions(problem, 2, 1e-6, 5)?.solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 1.0).abs() < 1e-4);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`FiniteDifferenceGradient`]: struct.FiniteDifferenceGradient.html
+//! [`ComplexStepGradient`]: struct.ComplexStepGradient.html
+
+use crate::{FunctionCallResult, OpEnFloat, SolverError};
+use num::complex::Complex;
+use std::{cell::RefCell, fmt};
+
+/// Default step of the complex-step method
+const DEFAULT_COMPLEX_STEP: f64 = 1e-20;
+
+/// Finite-difference scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FiniteDifferenceScheme {
+    /// Forward differences ($n + 1$ evaluations of the cost function per
+    /// gradient; error of the order of the step)
+    Forward,
+    /// Central differences ($2n$ evaluations of the cost function per
+    /// gradient; error of the order of the square of the step)
+    #[default]
+    Central,
+}
+
+impl FiniteDifferenceScheme {
+    /// Default relative step of the scheme, which balances the truncation
+    /// and the rounding errors (the square root and the cubic root of the
+    /// machine epsilon for forward and central differences respectively)
+    fn default_step<T: OpEnFloat>(self) -> T {
+        match self {
+            FiniteDifferenceScheme::Forward => T::epsilon().sqrt(),
+            FiniteDifferenceScheme::Central => T::epsilon().cbrt(),
+        }
+    }
+}
+
+/// Gradient of a cost function by finite differences
+///
+/// The methods `cost` and `gradient` can be passed to `Problem::new` (see
+/// the [module documentation](index.html)). A workspace of `n` elements is
+/// allocated once, when a `FiniteDifferenceGradient` is constructed, so that
+/// no memory is allocated when gradients are computed.
+pub struct FiniteDifferenceGradient<F, T = f64>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut T) -> FunctionCallResult,
+{
+    /// cost function
+    cost: F,
+    /// finite-difference scheme
+    scheme: FiniteDifferenceScheme,
+    /// relative step (if `None`, the default step of the scheme is used)
+    step: Option<T>,
+    /// perturbed point
+    workspace: RefCell<Vec<T>>,
+}
+
+impl<F, T> FiniteDifferenceGradient<F, T>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut T) -> FunctionCallResult,
+{
+    /// Constructs a new instance, which uses central differences with the
+    /// default step
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: number of decision variables
+    /// - `cost`: cost function
+    ///
+    pub fn new(n: usize, cost: F) -> Self {
+        FiniteDifferenceGradient {
+            cost,
+            scheme: FiniteDifferenceScheme::default(),
+            step: None,
+            workspace: RefCell::new(vec![T::zero(); n]),
+        }
+    }
+
+    /// Sets the finite-difference scheme (the default is
+    /// `FiniteDifferenceScheme::Central`)
+    pub fn with_scheme(mut self, scheme: FiniteDifferenceScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Sets the relative step, $\delta$ (by default, $\epsilon^{1/2}$ for
+    /// forward and $\epsilon^{1/3}$ for central differences, where
+    /// $\epsilon$ is the machine epsilon)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `step` is not positive and
+    /// finite
+    ///
+    pub fn with_step(mut self, step: T) -> Result<Self, SolverError> {
+        crate::ensure(
+            step > T::zero() && step.is_finite(),
+            "step",
+            "must be positive and finite",
+        )?;
+        self.step = Some(step);
+        Ok(self)
+    }
+
+    /// Number of decision variables
+    pub fn dimension(&self) -> usize {
+        self.workspace.borrow().len()
+    }
+
+    /// Computes the cost at `u` (using the wrapped cost function)
+    pub fn cost(&self, u: &[T], cost: &mut T) -> FunctionCallResult {
+        (self.cost)(u, cost)
+    }
+
+    fn check_dimension(&self, name: &'static str, actual: usize) -> FunctionCallResult {
+        let expected = self.dimension();
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(SolverError::DimensionMismatch {
+                name,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    /// Approximates the gradient of the cost at `u`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` or `grad` do not have
+    /// `n` elements, or the error of the cost function
+    ///
+    pub fn gradient(&self, u: &[T], grad: &mut [T]) -> FunctionCallResult {
+        self.check_dimension("u", u.len())?;
+        self.check_dimension("grad", grad.len())?;
+        let step = self.step.unwrap_or_else(|| self.scheme.default_step());
+        let mut workspace = self.workspace.borrow_mut();
+        workspace.copy_from_slice(u);
+        let central = self.scheme == FiniteDifferenceScheme::Central;
+        let mut cost_at_u = T::zero();
+        if !central {
+            (self.cost)(u, &mut cost_at_u)?;
+        }
+        for (i, grad_i) in grad.iter_mut().enumerate() {
+            // the step is rounded, so that u[i] + h is representable
+            let h = step * T::one().max(u[i].abs());
+            let h = (u[i] + h) - u[i];
+            let (mut cost_plus, mut cost_minus) = (T::zero(), cost_at_u);
+            workspace[i] = u[i] + h;
+            (self.cost)(&workspace, &mut cost_plus)?;
+            if central {
+                workspace[i] = u[i] - h;
+                (self.cost)(&workspace, &mut cost_minus)?;
+            }
+            workspace[i] = u[i];
+            *grad_i = (cost_plus - cost_minus) / if central { h + h } else { h };
+        }
+        Ok(())
+    }
+}
+
+impl<F, T> fmt::Debug for FiniteDifferenceGradient<F, T>
+where
+    T: OpEnFloat,
+    F: Fn(&[T], &mut T) -> FunctionCallResult,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FiniteDifferenceGradient")
+            .field("dimension", &self.dimension())
+            .field("scheme", &self.scheme)
+            .field("step", &self.step)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Gradient of a cost function by the complex-step method
+///
+/// The cost function is evaluated at complex points: it is typically
+/// written for a generic scalar, `T: ComplexFloat<Real = f64>` (see
+/// `num::complex::ComplexFloat`), so that the same function can be
+/// evaluated at `f64` and at `Complex<f64>`. The cost function needs to be
+/// analytic, that is, to be composed of arithmetic operations and analytic
+/// functions such as `exp`, `sin` and `powi`; functions like `abs`, `conj`
+/// and comparisons do not propagate the imaginary part correctly.
+///
+/// The gradient requires $n$ evaluations of the cost function. A workspace
+/// of `n` elements is allocated once, when a `ComplexStepGradient` is
+/// constructed, so that no memory is allocated when gradients are computed.
+///
+/// # Example
+///
+/// ```
+/// use num::complex::ComplexFloat;
+/// use optimization_engine::{finite_difference::ComplexStepGradient, FunctionCallResult};
+///
+/// fn cost<T: ComplexFloat<Real = f64>>(u: &[T], c: &mut T) -> FunctionCallResult {
+///     *c = u[0].exp() * u[1].sin() + u[0].powi(2);
+///     Ok(())
+/// }
+///
+/// let complex_step = ComplexStepGradient::new(2, cost);
+/// let (mut c, mut grad) = (0.0, [0.0; 2]);
+/// complex_step.cost(&[1.0, 2.0], &mut c)?;
+/// complex_step.gradient(&[1.0, 2.0], &mut grad)?;
+/// assert!((grad[0] - (1.0_f64.exp() * 2.0_f64.sin() + 2.0)).abs() < 1e-14);
+/// assert!((grad[1] - 1.0_f64.exp() * 2.0_f64.cos()).abs() < 1e-14);
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+pub struct ComplexStepGradient<F>
+where
+    F: Fn(&[Complex<f64>], &mut Complex<f64>) -> FunctionCallResult,
+{
+    /// cost function
+    cost: F,
+    /// step, $h$
+    step: f64,
+    /// perturbed point
+    workspace: RefCell<Vec<Complex<f64>>>,
+}
+
+impl<F> ComplexStepGradient<F>
+where
+    F: Fn(&[Complex<f64>], &mut Complex<f64>) -> FunctionCallResult,
+{
+    /// Constructs a new instance with the default step, $h = 10^{-20}$
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: number of decision variables
+    /// - `cost`: cost function, evaluated at complex points
+    ///
+    pub fn new(n: usize, cost: F) -> Self {
+        ComplexStepGradient {
+            cost,
+            step: DEFAULT_COMPLEX_STEP,
+            workspace: RefCell::new(vec![Complex::new(0.0, 0.0); n]),
+        }
+    }
+
+    /// Sets the (absolute) step, $h$ (by default, $10^{-20}$)
+    ///
+    /// Since there is no cancellation, the step only needs to be small
+    /// enough for the truncation error, which is of the order of $h^2$, to
+    /// be negligible
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `step` is not positive and
+    /// finite
+    ///
+    pub fn with_step(mut self, step: f64) -> Result<Self, SolverError> {
+        crate::ensure(
+            step > 0.0 && step.is_finite(),
+            "step",
+            "must be positive and finite",
+        )?;
+        self.step = step;
+        Ok(self)
+    }
+
+    /// Number of decision variables
+    pub fn dimension(&self) -> usize {
+        self.workspace.borrow().len()
+    }
+
+    fn check_dimension(&self, name: &'static str, actual: usize) -> FunctionCallResult {
+        let expected = self.dimension();
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(SolverError::DimensionMismatch {
+                name,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    /// Computes the cost at `u`, that is, the real part of the cost
+    /// function at $u + 0\mathrm{i}$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` does not have `n`
+    /// elements, or the error of the cost function
+    ///
+    pub fn cost(&self, u: &[f64], cost: &mut f64) -> FunctionCallResult {
+        self.check_dimension("u", u.len())?;
+        let mut workspace = self.workspace.borrow_mut();
+        workspace
+            .iter_mut()
+            .zip(u.iter())
+            .for_each(|(w_i, &u_i)| *w_i = Complex::new(u_i, 0.0));
+        let mut cost_complex = Complex::new(0.0, 0.0);
+        (self.cost)(&workspace, &mut cost_complex)?;
+        *cost = cost_complex.re;
+        Ok(())
+    }
+
+    /// Computes the gradient of the cost at `u`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` or `grad` do not have
+    /// `n` elements, or the error of the cost function
+    ///
+    pub fn gradient(&self, u: &[f64], grad: &mut [f64]) -> FunctionCallResult {
+        self.check_dimension("u", u.len())?;
+        self.check_dimension("grad", grad.len())?;
+        let mut workspace = self.workspace.borrow_mut();
+        workspace
+            .iter_mut()
+            .zip(u.iter())
+            .for_each(|(w_i, &u_i)| *w_i = Complex::new(u_i, 0.0));
+        for (i, grad_i) in grad.iter_mut().enumerate() {
+            let mut cost_perturbed = Complex::new(0.0, 0.0);
+            workspace[i].im = self.step;
+            let result = (self.cost)(&workspace, &mut cost_perturbed);
+            workspace[i].im = 0.0;
+            result?;
+            *grad_i = cost_perturbed.im / self.step;
+        }
+        Ok(())
+    }
+}
+
+impl<F> fmt::Debug for ComplexStepGradient<F>
+where
+    F: Fn(&[Complex<f64>], &mut Complex<f64>) -> FunctionCallResult,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComplexStepGradient")
+            .field("dimension", &self.dimension())
+            .field("step", &self.step)
+            .finish_non_exhaustive()
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks;
+    use num::complex::ComplexFloat;
+
+    fn rosenbrock(u: &[f64], cost: &mut f64) -> FunctionCallResult {
+        *cost = mocks::rosenbrock_cost(1.5, 10.0, u);
+        Ok(())
+    }
+
+    #[test]
+    fn t_finite_difference_schemes() {
+        let u = [0.3, -70.0];
+        let mut grad_expected = [0.0; 2];
+        mocks::rosenbrock_grad(1.5, 10.0, &u, &mut grad_expected);
+        let schemes = [
+            (FiniteDifferenceScheme::Central, 1e-8),
+            (FiniteDifferenceScheme::Forward, 1e-4),
+        ];
+        for (scheme, tolerance) in schemes {
+            let finite_difference =
+                FiniteDifferenceGradient::new(2, rosenbrock).with_scheme(scheme);
+            let mut grad = [0.0; 2];
+            finite_difference.gradient(&u, &mut grad).unwrap();
+            unit_test_utils::assert_nearly_equal_array(
+                &grad_expected,
+                &grad,
+                tolerance,
+                1.0,
+                "grad",
+            );
+        }
+    }
+
+    #[test]
+    fn t_finite_difference_step_f32() {
+        let cost = |u: &[f32], c: &mut f32| -> FunctionCallResult {
+            *c = u[0].powi(3) + u[0] * u[1];
+            Ok(())
+        };
+        // central differences are exact for cubic polynomials, up to the
+        // truncation error h^2
+        let finite_difference = FiniteDifferenceGradient::new(2, cost)
+            .with_step(1e-2_f32)
+            .unwrap();
+        let mut grad = [0.0_f32; 2];
+        finite_difference.gradient(&[2.0, -1.0], &mut grad).unwrap();
+        assert!((grad[0] - 11.0).abs() < 1e-2 && (grad[1] - 2.0).abs() < 1e-3);
+        assert!(FiniteDifferenceGradient::new(2, cost)
+            .with_step(0.0)
+            .is_err());
+        assert!(FiniteDifferenceGradient::new(2, cost)
+            .with_step(f32::INFINITY)
+            .is_err());
+    }
+
+    #[test]
+    fn t_finite_difference_errors() {
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            if u[0] > 1.0 {
+                return Err(SolverError::Cost);
+            }
+            *c = u[0] + u[1];
+            Ok(())
+        };
+        let finite_difference = FiniteDifferenceGradient::new(2, cost);
+        let mut grad = [0.0; 2];
+        assert!(matches!(
+            finite_difference.gradient(&[0.0; 3], &mut grad),
+            Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: 2,
+                actual: 3,
+            })
+        ));
+        assert_eq!(
+            Err(SolverError::Cost),
+            finite_difference.gradient(&[1.0, 0.0], &mut grad)
+        );
+        finite_difference.gradient(&[0.0, 0.0], &mut grad).unwrap();
+        assert!((grad[0] - 1.0).abs() < 1e-9 && (grad[1] - 1.0).abs() < 1e-9);
+    }
+
+    fn rosenbrock_generic<T: ComplexFloat<Real = f64>>(
+        u: &[T],
+        cost: &mut T,
+    ) -> FunctionCallResult {
+        let (a, b) = (T::from(1.5).unwrap(), T::from(10.0).unwrap());
+        *cost = (a - u[0]).powi(2) + b * (u[1] - u[0].powi(2)).powi(2);
+        Ok(())
+    }
+
+    #[test]
+    fn t_complex_step_rosenbrock() {
+        let u = [0.3, -70.0];
+        let mut grad_expected = [0.0; 2];
+        mocks::rosenbrock_grad(1.5, 10.0, &u, &mut grad_expected);
+        let complex_step = ComplexStepGradient::new(2, rosenbrock_generic);
+        let mut grad = [0.0; 2];
+        complex_step.gradient(&u, &mut grad).unwrap();
+        unit_test_utils::assert_nearly_equal_array(&grad_expected, &grad, 1e-14, 1e-14, "grad");
+        // the same (generic) cost function, evaluated at f64
+        let (mut cost, mut cost_f64) = (0.0, 0.0);
+        complex_step.cost(&u, &mut cost).unwrap();
+        rosenbrock_generic(&u, &mut cost_f64).unwrap();
+        assert_eq!(mocks::rosenbrock_cost(1.5, 10.0, &u), cost);
+        assert_eq!(cost_f64, cost);
+    }
+
+    #[test]
+    fn t_complex_step_vs_finite_differences() {
+        // f(u) = exp(u0) / sqrt(sin(u1)^3 + cos(u1)^3), whose derivatives are
+        // poorly approximated by finite differences
+        fn cost<T: ComplexFloat<Real = f64>>(u: &[T], c: &mut T) -> FunctionCallResult {
+            *c = u[0].exp() / (u[1].sin().powi(3) + u[1].cos().powi(3)).sqrt();
+            Ok(())
+        }
+        let u = [1.5_f64, 0.4];
+        let (s, c) = (u[1].sin(), u[1].cos());
+        let denominator = s.powi(3) + c.powi(3);
+        let grad_expected = [
+            u[0].exp() / denominator.sqrt(),
+            -1.5 * u[0].exp() * (s * s * c - c * c * s) / denominator.powf(1.5),
+        ];
+        let mut grad = [0.0; 2];
+        ComplexStepGradient::new(2, cost)
+            .gradient(&u, &mut grad)
+            .unwrap();
+        unit_test_utils::assert_nearly_equal_array(&grad_expected, &grad, 1e-14, 1e-14, "grad");
+        let mut grad_fd = [0.0; 2];
+        FiniteDifferenceGradient::new(2, |u: &[f64], c: &mut f64| cost(u, c))
+            .gradient(&u, &mut grad_fd)
+            .unwrap();
+        let error = |g: &[f64]| {
+            g.iter()
+                .zip(grad_expected.iter())
+                .fold(0.0_f64, |e, (g_i, h_i)| e.max((g_i - h_i).abs()))
+        };
+        assert!(error(&grad) < error(&grad_fd));
+    }
+
+    #[test]
+    fn t_complex_step_errors() {
+        let cost = |u: &[Complex<f64>], c: &mut Complex<f64>| -> FunctionCallResult {
+            if u[0].re > 1.0 {
+                return Err(SolverError::Cost);
+            }
+            *c = u[0] * u[1];
+            Ok(())
+        };
+        let complex_step = ComplexStepGradient::new(2, cost);
+        let mut grad = [0.0; 2];
+        assert!(matches!(
+            complex_step.gradient(&[0.0; 2], &mut [0.0; 3]),
+            Err(SolverError::DimensionMismatch {
+                name: "grad",
+                expected: 2,
+                actual: 3,
+            })
+        ));
+        assert_eq!(
+            Err(SolverError::Cost),
+            complex_step.gradient(&[2.0, 0.0], &mut grad)
+        );
+        complex_step.gradient(&[0.5, 3.0], &mut grad).unwrap();
+        assert_eq!([3.0, 0.5], grad);
+        assert!(ComplexStepGradient::new(2, cost).with_step(0.0).is_err());
+        assert!(ComplexStepGradient::new(2, cost)
+            .with_step(f64::NAN)
+            .is_err());
+        let complex_step = ComplexStepGradient::new(2, cost).with_step(1e-3).unwrap();
+        complex_step.gradient(&[0.5, 3.0], &mut grad).unwrap();
+        assert_eq!([3.0, 0.5], grad);
+    }
+}
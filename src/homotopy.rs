@@ -0,0 +1,321 @@
+//! Homotopy (continuation) methods
+//!
+//! A [`Homotopy`] solves a family of problems, $P(\lambda)$, along a path of
+//! parameter values, $\lambda_0, \lambda_1, \ldots, \lambda_N$, warm starting
+//! every solve from the solution of the previous one, and records the
+//! solution path. This is useful for regularization sweeps, for the
+//! inflation of obstacles, and for hard nonconvex problems, which are easier
+//! to solve starting from the solution of a simpler, nearby problem (e.g.,
+//! with a smaller penalty weight).
+//!
+//! The solver is a closure which solves $P(\lambda)$ in place, given
+//! $\lambda$ and the initial guess, and returns a `SolverStatus`, so any
+//! optimizer of OpEn can be used. Paths of parameter values can be
+//! constructed with [`Homotopy::linear`] and [`Homotopy::geometric`].
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{constraints::NoConstraints, homotopy::Homotopy, panoc::*, *};
+//!
+//! // minimize (u - 2)^2 + lambda u^2 for lambda = 0, 0.25, ..., 1
+//! let bounds = NoConstraints::new();
+//! let mut cache = PANOCCache::new(1, 1e-10, 5)?;
+//! let mut u = [0.0];
+//! let path = Homotopy::linear(0.0, 1.0, 5)?.solve(&mut u, |lambda: f64, u: &mut [f64]| {
+//!     let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!         *c = (u[0] - 2.0).powi(2) + lambda * u[0].powi(2);
+//!         Ok(())
+//!     };
+//!     let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!         grad[0] = 2.0 * (u[0] - 2.0) + 2.0 * lambda * u[0];
+//!         Ok(())
+//!     };
+//!     PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache).solve(u)
+//! })?;
+//! assert_eq!(5, path.len());
+//! assert!((path.solutions()[4][0] - 1.0).abs() < 1e-8);
+//! assert_eq!(path.solutions()[4], u.to_vec());
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! [`Homotopy`]: struct.Homotopy.html
+//! [`Homotopy::linear`]: struct.Homotopy.html#method.linear
+//! [`Homotopy::geometric`]: struct.Homotopy.html#method.geometric
+
+use crate::{core::SolverStatus, ensure, OpEnFloat, SolverError};
+
+/// Homotopy (continuation) driver
+///
+/// See the [module documentation](index.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Homotopy<T = f64> {
+    /// path of parameter values
+    parameters: Vec<T>,
+    /// whether to stop at the first solve which does not converge
+    stop_on_failure: bool,
+}
+
+/// Solution path computed by `Homotopy::solve`
+///
+/// The $k$-th solution and status correspond to the $k$-th parameter value.
+#[derive(Debug, Clone)]
+pub struct HomotopyPath<T = f64> {
+    parameters: Vec<T>,
+    solutions: Vec<Vec<T>>,
+    statuses: Vec<SolverStatus>,
+}
+
+impl<T> HomotopyPath<T> {
+    /// Number of problems which were solved
+    pub fn len(&self) -> usize {
+        self.statuses.len()
+    }
+
+    /// Whether no problem was solved
+    pub fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+    }
+
+    /// Parameter values of the solved problems
+    pub fn parameters(&self) -> &[T] {
+        &self.parameters
+    }
+
+    /// Solutions of the problems
+    pub fn solutions(&self) -> &[Vec<T>] {
+        &self.solutions
+    }
+
+    /// Statuses of the solves
+    pub fn statuses(&self) -> &[SolverStatus] {
+        &self.statuses
+    }
+
+    /// Whether all solves converged
+    pub fn has_converged(&self) -> bool {
+        self.statuses.iter().all(SolverStatus::has_converged)
+    }
+
+    /// Total number of iterations of all solves
+    pub fn total_iterations(&self) -> usize {
+        self.statuses.iter().map(SolverStatus::iterations).sum()
+    }
+}
+
+impl<T: OpEnFloat> Homotopy<T> {
+    /// Constructs a homotopy along the given path of parameter values
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `parameters` is empty or
+    /// contains non-finite values
+    ///
+    pub fn new(parameters: &[T]) -> Result<Self, SolverError> {
+        ensure(!parameters.is_empty(), "parameters", "must not be empty")?;
+        ensure(
+            parameters.iter().all(|p| p.is_finite()),
+            "parameters",
+            "must be finite",
+        )?;
+        Ok(Homotopy {
+            parameters: parameters.to_vec(),
+            stop_on_failure: false,
+        })
+    }
+
+    /// Constructs a homotopy along `num_steps` equally spaced parameter
+    /// values from `start` to `end` (both included)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `num_steps` is smaller than
+    /// 2, or `start` or `end` is not finite
+    ///
+    pub fn linear(start: T, end: T, num_steps: usize) -> Result<Self, SolverError> {
+        ensure(num_steps >= 2, "num_steps", "must be at least 2")?;
+        let step = (end - start) / T::from_f64((num_steps - 1) as f64);
+        let mut parameters: Vec<T> = (0..num_steps)
+            .map(|k| start + T::from_f64(k as f64) * step)
+            .collect();
+        parameters[num_steps - 1] = end;
+        Homotopy::new(&parameters)
+    }
+
+    /// Constructs a homotopy along `num_steps` parameter values from `start`
+    /// to `end` (both included) with a constant ratio, which is useful for
+    /// penalty weights
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `num_steps` is smaller than
+    /// 2, or `start` or `end` is not positive and finite
+    ///
+    pub fn geometric(start: T, end: T, num_steps: usize) -> Result<Self, SolverError> {
+        ensure(num_steps >= 2, "num_steps", "must be at least 2")?;
+        ensure(
+            start > T::zero() && end > T::zero(),
+            "start, end",
+            "must be positive",
+        )?;
+        let ratio = (end / start).powf(T::one() / T::from_f64((num_steps - 1) as f64));
+        let mut parameters: Vec<T> = (0..num_steps)
+            .map(|k| start * ratio.powi(k as i32))
+            .collect();
+        parameters[num_steps - 1] = end;
+        Homotopy::new(&parameters)
+    }
+
+    /// Sets whether to stop at the first solve which does not converge (the
+    /// default is `false`, that is, the homotopy continues from the last
+    /// iterate of the solve)
+    pub fn with_stop_on_failure(mut self, stop_on_failure: bool) -> Self {
+        self.stop_on_failure = stop_on_failure;
+        self
+    }
+
+    /// The path of parameter values
+    pub fn parameters(&self) -> &[T] {
+        &self.parameters
+    }
+
+    /// Solves the problems along the path
+    ///
+    /// ## Arguments
+    ///
+    /// - `u`: on entry, the initial guess of the first problem; on exit, the
+    ///   solution of the last solved problem
+    /// - `solver`: closure which, given a parameter value and an initial
+    ///   guess, solves the corresponding problem in place
+    ///
+    /// ## Returns
+    ///
+    /// The solution path; if the homotopy stops on failure (see
+    /// `with_stop_on_failure`), the path ends with the solve which did not
+    /// converge.
+    ///
+    /// ## Errors
+    ///
+    /// Returns the first error of `solver`
+    ///
+    pub fn solve<F>(&self, u: &mut [T], mut solver: F) -> Result<HomotopyPath<T>, SolverError>
+    where
+        F: FnMut(T, &mut [T]) -> Result<SolverStatus, SolverError>,
+    {
+        let mut path = HomotopyPath {
+            parameters: Vec::with_capacity(self.parameters.len()),
+            solutions: Vec::with_capacity(self.parameters.len()),
+            statuses: Vec::with_capacity(self.parameters.len()),
+        };
+        for &parameter in &self.parameters {
+            let status = solver(parameter, u)?;
+            let converged = status.has_converged();
+            path.parameters.push(parameter);
+            path.solutions.push(u.to_vec());
+            path.statuses.push(status);
+            if self.stop_on_failure && !converged {
+                break;
+            }
+        }
+        Ok(path)
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constraints::NoConstraints,
+        core::{panoc::PANOCCache, panoc::PANOCOptimizer, ExitStatus},
+        FunctionCallResult, Optimizer, Problem,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn t_homotopy_regularization_path() {
+        let bounds = NoConstraints::new();
+        let mut cache = PANOCCache::new(1, 1e-10, 5).unwrap();
+        let mut u = [0.0];
+        let homotopy = Homotopy::geometric(1e-2, 1e2, 5).unwrap();
+        let path = homotopy
+            .solve(&mut u, |lambda: f64, u: &mut [f64]| {
+                let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+                    *c = (u[0] - 2.0).powi(2) + lambda * u[0].powi(2);
+                    Ok(())
+                };
+                let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                    grad[0] = 2.0 * (u[0] - 2.0) + 2.0 * lambda * u[0];
+                    Ok(())
+                };
+                PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache).solve(u)
+            })
+            .unwrap();
+        assert!(path.has_converged());
+        assert_eq!(homotopy.parameters(), path.parameters());
+        for (lambda, solution) in path.parameters().iter().zip(path.solutions()) {
+            assert!((solution[0] - 2.0 / (1.0 + lambda)).abs() < 1e-8);
+        }
+        assert_eq!(
+            path.total_iterations(),
+            path.statuses()
+                .iter()
+                .map(|s| s.iterations())
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn t_homotopy_paths() {
+        let linear = Homotopy::linear(0.0, 1.0, 5).unwrap();
+        assert_eq!(&[0.0, 0.25, 0.5, 0.75, 1.0], linear.parameters());
+        let geometric = Homotopy::geometric(1.0, 1000.0, 4).unwrap();
+        unit_test_utils::assert_nearly_equal_array(
+            &[1.0, 10.0, 100.0, 1000.0],
+            geometric.parameters(),
+            1e-12,
+            1e-12,
+            "parameters",
+        );
+        assert_eq!(1000.0, geometric.parameters()[3]);
+        assert!(Homotopy::linear(0.0, 1.0, 1).is_err());
+        assert!(Homotopy::geometric(0.0, 1.0, 3).is_err());
+        assert!(Homotopy::<f64>::new(&[]).is_err());
+        assert!(Homotopy::new(&[1.0, f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn t_homotopy_stop_on_failure() {
+        let homotopy = Homotopy::new(&[1.0, 3.0, 2.0]).unwrap();
+        let solver = |parameter: f64, u: &mut [f64]| {
+            // warm start: u is the previous solution
+            u[0] += parameter;
+            let exit_status = if parameter < 2.5 {
+                ExitStatus::Converged
+            } else {
+                ExitStatus::NotConvergedIterations
+            };
+            Ok(SolverStatus::new(exit_status, 1, Duration::ZERO, 0.0, 0.0))
+        };
+        let mut u = [0.0];
+        let path = homotopy.solve(&mut u, solver).unwrap();
+        assert_eq!(vec![vec![1.0], vec![4.0], vec![6.0]], path.solutions());
+        assert!(!path.has_converged());
+
+        let mut u = [0.0];
+        let homotopy = homotopy.with_stop_on_failure(true);
+        let path = homotopy.solve(&mut u, solver).unwrap();
+        assert_eq!(2, path.len());
+        assert_eq!([4.0], u);
+
+        let path = homotopy.solve(&mut u, |parameter: f64, u: &mut [f64]| {
+            if parameter > 1.5 {
+                return Err(SolverError::Cost);
+            }
+            solver(parameter, u)
+        });
+        assert!(matches!(path, Err(SolverError::Cost)));
+    }
+}
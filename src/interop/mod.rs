@@ -0,0 +1,21 @@
+//! Interoperability with linear algebra crates
+//!
+//! The optimizers of OpEn work on slices: initial guesses (which are
+//! overwritten with the solutions) are `&mut [f64]`, and parameters and the
+//! data of most constraints (e.g., the bounds of a `Rectangle` or the center
+//! of a `Ball2`) are `&[f64]`. Vectors of linear algebra crates which store
+//! their elements contiguously can therefore be passed to the optimizers,
+//! and receive their solutions, without any copies. This module provides
+//! views of slices as vectors of these crates (e.g., to write costs and
+//! gradients with their operations) and constructors of constraints and
+//! costs from their matrices:
+//!
+//! - [`nalgebra`](nalgebra/index.html) (feature `nalgebra`): `DVector` and
+//!   `DMatrix` of [nalgebra](https://nalgebra.org)
+//! - [`ndarray`](ndarray/index.html) (feature `ndarray`): `Array1` and
+//!   `Array2` of [ndarray](https://docs.rs/ndarray)
+//!
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra;
+#[cfg(feature = "ndarray")]
+pub mod ndarray;
@@ -0,0 +1,111 @@
+//! Interoperability with nalgebra
+//!
+//! A `DVector<f64>` is passed to an optimizer as a slice, which is not
+//! copied, and receives the solution:
+//!
+//! ```no_run
+//! # use optimization_engine::{constraints::*, panoc::*, *};
+//! # let (df, f) = (
+//! #     |_u: &[f64], _grad: &mut [f64]| -> FunctionCallResult { Ok(()) },
+//! #     |_u: &[f64], _c: &mut f64| -> FunctionCallResult { Ok(()) },
+//! # );
+//! # let mut cache = PANOCCache::new(2, 1e-8, 5)?;
+//! let mut u = nalgebra::DVector::from_vec(vec![0.5, 0.5]);
+//! let lower = nalgebra::DVector::from_element(2, -1.0);
+//! let bounds = Rectangle::new(Some(lower.as_slice()), None)?;
+//! let status = PANOCOptimizer::new(Problem::new(&bounds, df, f), &mut cache)
+//!     .solve(u.as_mut_slice())?;
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! Inside the closures of a problem (cost, gradient, mappings), the decision
+//! variables and the output buffers are slices; `vector` and `vector_mut`
+//! view them as nalgebra vectors, again without copies, e.g.,
+//!
+//! ```
+//! use optimization_engine::{interop::nalgebra::*, FunctionCallResult};
+//! use nalgebra::{DMatrix, DVector};
+//!
+//! let q = DMatrix::from_row_slice(2, 2, &[2.0, 0.5, 0.5, 1.0]);
+//! let b = DVector::from_vec(vec![1.0, -1.0]);
+//! // cost 0.5 u'Qu + b'u and its gradient Qu + b
+//! let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = 0.5 * vector(u).dot(&(&q * vector(u))) + b.dot(&vector(u));
+//!     Ok(())
+//! };
+//! let grad = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     vector_mut(grad).copy_from(&(&q * vector(u) + &b));
+//!     Ok(())
+//! };
+//! let mut g = [0.0; 2];
+//! grad(&[1.0, 0.0], &mut g).unwrap();
+//! assert_eq!([3.0, -0.5], g);
+//! ```
+//!
+//! Constraints which are defined by matrices are constructed from nalgebra
+//! matrices with `AffineSpace::from_nalgebra`.
+//!
+use nalgebra::{DVectorView, DVectorViewMut};
+
+/// View of a slice (e.g., the decision variables or the parameter of a
+/// problem) as a nalgebra vector
+pub fn vector(u: &[f64]) -> DVectorView<'_, f64> {
+    DVectorView::from_slice(u, u.len())
+}
+
+/// Mutable view of a slice (e.g., the gradient which is computed by a
+/// closure) as a nalgebra vector
+pub fn vector_mut(u: &mut [f64]) -> DVectorViewMut<'_, f64> {
+    let len = u.len();
+    DVectorViewMut::from_slice(u, len)
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::*, panoc::*, FunctionCallResult, Optimizer, Problem};
+    use nalgebra::{DMatrix, DVector};
+
+    #[test]
+    fn t_nalgebra_solve() {
+        // minimize 0.5 |u - p|^2 over u >= lower
+        let p = DVector::from_vec(vec![1.0, -2.0, 0.5]);
+        let lower = DVector::from_element(3, -1.0);
+        let bounds = Rectangle::new(Some(lower.as_slice()), None).unwrap();
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = 0.5 * (vector(u) - &p).norm_squared();
+            Ok(())
+        };
+        let grad = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            vector_mut(grad).copy_from(&(vector(u) - &p));
+            Ok(())
+        };
+        let mut cache = PANOCCache::new(3, 1e-10, 5).unwrap();
+        let mut u = DVector::zeros(3);
+        let status = PANOCOptimizer::new(Problem::new(&bounds, grad, cost), &mut cache)
+            .solve(u.as_mut_slice())
+            .unwrap();
+        assert!(status.has_converged());
+        let expected = DVector::from_vec(vec![1.0, -1.0, 0.5]);
+        assert!((u - expected).amax() < 1e-8);
+    }
+
+    #[test]
+    fn t_nalgebra_affine_space() {
+        let a = DMatrix::from_row_slice(2, 3, &[1.0, 2.0, 0.0, 0.0, 1.0, -1.0]);
+        let b = DVector::from_vec(vec![1.0, 2.0]);
+        let from_nalgebra = AffineSpace::from_nalgebra(&a, &b).unwrap();
+        let from_rows =
+            AffineSpace::new(vec![1.0, 2.0, 0.0, 0.0, 1.0, -1.0], vec![1.0, 2.0]).unwrap();
+        let mut x = DVector::from_vec(vec![3.0, -1.0, 4.0]);
+        let mut y = x.clone();
+        from_nalgebra.project(x.as_mut_slice());
+        from_rows.project(y.as_mut_slice());
+        assert_eq!(x, y);
+        assert!((&a * &x - &b).amax() < 1e-10);
+        assert!(AffineSpace::from_nalgebra(&a, &DVector::zeros(3)).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Interoperability with ndarray
+//!
+//! The optimizers take slices, which arrays in standard (row-major,
+//! contiguous) layout provide without copies: an `Array1<f64>` is passed as
+//! an initial guess (and receives the solution) with
+//! `as_slice_mut().unwrap()`. For arrays which may not be contiguous (e.g.,
+//! columns or strided views), `contiguous` returns their elements in
+//! row-major order, borrowed whenever possible and copied otherwise, so that
+//! parameters, bounds and matrix data can be passed without `to_vec()`:
+//!
+//! ```
+//! use ndarray::{array, Array1};
+//! use optimization_engine::{constraints::*, interop::ndarray::contiguous, SolverError};
+//!
+//! let limits = array![[-1.0, 1.0], [-2.0, 2.0], [0.0, 5.0]];
+//! // the columns are not contiguous, so they are copied
+//! let (lower, upper) = (limits.column(0), limits.column(1));
+//! let (lower, upper) = (contiguous(&lower), contiguous(&upper));
+//! let bounds = Rectangle::new(Some(&lower), Some(&upper))?;
+//!
+//! let mut u: Array1<f64> = array![-3.0, 0.5, 7.0];
+//! bounds.project(u.as_slice_mut().unwrap());
+//! assert_eq!(array![-1.0, 0.5, 5.0], u);
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! Inside the closures of a problem (cost, gradient, Jacobian products),
+//! `vector` and `vector_mut` view the slices as ndarray vectors. Linear
+//! least-squares problems, $\min \tfrac{1}{2}\Vert Au - b\Vert^2$, whose
+//! Jacobian is the matrix $A$, are constructed with `linear_least_squares`,
+//! and affine spaces with `AffineSpace::from_ndarray`.
+//!
+use crate::{least_squares::LeastSquares, FunctionCallResult, SolverError};
+use ndarray::{linalg, Array1, Array2, ArrayBase, ArrayView1, ArrayViewMut1, Data, Dimension};
+use std::{borrow::Cow, sync::Arc};
+
+/// View of a slice (e.g., the decision variables or the parameter of a
+/// problem) as an ndarray vector
+pub fn vector(u: &[f64]) -> ArrayView1<'_, f64> {
+    ArrayView1::from(u)
+}
+
+/// Mutable view of a slice (e.g., the gradient which is computed by a
+/// closure) as an ndarray vector
+pub fn vector_mut(u: &mut [f64]) -> ArrayViewMut1<'_, f64> {
+    ArrayViewMut1::from(u)
+}
+
+/// Elements of an array in row-major order, as a slice
+///
+/// The elements are borrowed if the array is in standard layout and copied
+/// otherwise.
+pub fn contiguous<S, D>(array: &ArrayBase<S, D>) -> Cow<'_, [f64]>
+where
+    S: Data<Elem = f64>,
+    D: Dimension,
+{
+    match array.as_slice() {
+        Some(elements) => Cow::Borrowed(elements),
+        None => Cow::Owned(array.iter().copied().collect()),
+    }
+}
+
+/// Least-squares cost of the linear residual $r(u) = Au - b$, whose Jacobian
+/// is $A$
+///
+/// The products with the Jacobian and its transpose do not allocate memory,
+/// so the cost can be used with Gauss-Newton directions (see
+/// `PANOCOptimizer::with_gauss_newton`).
+///
+/// ## Errors
+///
+/// Returns `SolverError::DimensionMismatch` if the number of rows of `a` is
+/// not the length of `b`
+///
+#[allow(clippy::type_complexity)]
+pub fn linear_least_squares(
+    a: Array2<f64>,
+    b: Array1<f64>,
+) -> Result<
+    LeastSquares<
+        impl Fn(&[f64], &mut [f64]) -> FunctionCallResult + Sync,
+        impl Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult + Sync,
+        impl Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult + Sync,
+    >,
+    SolverError,
+> {
+    let (m, n) = a.dim();
+    if b.len() != m {
+        return Err(SolverError::DimensionMismatch {
+            name: "b",
+            expected: m,
+            actual: b.len(),
+        });
+    }
+    let a = Arc::new(a);
+    let (a_product, a_transpose_product) = (Arc::clone(&a), Arc::clone(&a));
+    let residual = move |u: &[f64], r: &mut [f64]| -> FunctionCallResult {
+        let mut r = vector_mut(r);
+        r.assign(&b);
+        linalg::general_mat_vec_mul(1.0, &a, &vector(u), -1.0, &mut r);
+        Ok(())
+    };
+    let jacobian_product = move |_u: &[f64], v: &[f64], jv: &mut [f64]| -> FunctionCallResult {
+        linalg::general_mat_vec_mul(1.0, &a_product, &vector(v), 0.0, &mut vector_mut(jv));
+        Ok(())
+    };
+    let jacobian_transpose_product =
+        move |_u: &[f64], w: &[f64], jtw: &mut [f64]| -> FunctionCallResult {
+            let a_t = a_transpose_product.t();
+            linalg::general_mat_vec_mul(1.0, &a_t, &vector(w), 0.0, &mut vector_mut(jtw));
+            Ok(())
+        };
+    Ok(LeastSquares::new(
+        n,
+        m,
+        residual,
+        jacobian_product,
+        jacobian_transpose_product,
+    ))
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::*, panoc::*, Optimizer, Problem};
+    use ndarray::{array, s};
+
+    #[test]
+    fn t_ndarray_contiguous() {
+        let a = array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        assert!(matches!(contiguous(&a), Cow::Borrowed(_)));
+        assert!(matches!(contiguous(&a.row(1)), Cow::Borrowed(_)));
+        assert_eq!(&[2.0, 5.0][..], &*contiguous(&a.column(1)));
+        assert_eq!(&[1.0, 4.0, 2.0, 5.0, 3.0, 6.0][..], &*contiguous(&a.t()));
+        assert_eq!(&[1.0, 3.0][..], &*contiguous(&a.slice(s![0, ..;2])));
+    }
+
+    #[test]
+    fn t_ndarray_linear_least_squares() {
+        // fit of a line, y = u[0] + u[1] t, with u[1] <= 1.5
+        let t = array![0.0, 1.0, 2.0, 3.0];
+        let y = array![1.0, 3.0, 5.0, 7.0];
+        let mut a = Array2::ones((4, 2));
+        a.column_mut(1).assign(&t);
+        let least_squares = linear_least_squares(a, y).unwrap();
+        let bounds = Rectangle::new(None, Some(&[10.0, 1.5])).unwrap();
+        let problem = Problem::new(
+            &bounds,
+            |u: &[f64], grad: &mut [f64]| least_squares.gradient(u, grad),
+            |u: &[f64], c: &mut f64| least_squares.cost(u, c),
+        );
+        let mut cache = PANOCCache::new(2, 1e-10, 5).unwrap();
+        let mut u = Array1::zeros(2);
+        let status = PANOCOptimizer::new(problem, &mut cache)
+            .with_gauss_newton(&least_squares)
+            .unwrap()
+            .solve(u.as_slice_mut().unwrap())
+            .unwrap();
+        assert!(status.has_converged());
+        // with u[1] = 1.5, the optimal intercept is mean(y - 1.5 t) = 1.75
+        unit_test_utils::assert_nearly_equal_array(
+            &[1.75, 1.5],
+            u.as_slice().unwrap(),
+            1e-7,
+            1e-7,
+            "u",
+        );
+
+        let mut c = 0.0;
+        least_squares.cost(&[1.0, 2.0], &mut c).unwrap();
+        assert_eq!(0.0, c);
+        assert!(linear_least_squares(Array2::zeros((3, 2)), Array1::zeros(2)).is_err());
+    }
+
+    #[test]
+    fn t_ndarray_affine_space() {
+        let a = array![[1.0, 2.0, 0.0], [0.0, 1.0, -1.0]];
+        let b = array![1.0, 2.0];
+        // a matrix which is not in standard layout
+        let a_f = a.t().to_owned().reversed_axes();
+        let affine_space = AffineSpace::from_ndarray(&a_f, &b).unwrap();
+        let mut x = [3.0, -1.0, 4.0];
+        affine_space.project(&mut x);
+        let residual = a.dot(&vector(&x)) - &b;
+        assert!(residual.iter().all(|r| r.abs() < 1e-10));
+        assert!(AffineSpace::from_ndarray(&a, &array![1.0]).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+//! Nonlinear least-squares costs
+//!
+//! [`LeastSquares`] wraps the residual, $r: \mathbb{R}^n \to \mathbb{R}^m$, of
+//! a cost function of the form
+//!
+//! $$
+//! f(u) = \tfrac{1}{2}\Vert r(u)\Vert^2,
+//! $$
+//!
+//! which is common in estimation and fitting problems, together with the
+//! products of its Jacobian, $J(u)$, with vectors, $v \mapsto J(u)v$ and
+//! $w \mapsto J(u)^\top w$. The methods `cost` and `gradient`, where
+//! $\nabla f(u) = J(u)^\top r(u)$, can be passed to `Problem::new`.
+//!
+//! Besides, `LeastSquares` implements [`GaussNewtonHessian`], so PANOC can use
+//! Gauss-Newton directions, which use the Hessian approximation
+//! $J(u)^\top J(u)$, instead of L-BFGS directions (see
+//! `PANOCOptimizer::with_gauss_newton`).
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     constraints::NoConstraints, least_squares::LeastSquares, panoc::*, FunctionCallResult,
+//!     Optimizer, Problem,
+//! };
+//!
+//! // r(u) = (u[0] - 1, 10 (u[1] - u[0]^2)) (Rosenbrock)
+//! let residual = |u: &[f64], r: &mut [f64]| -> FunctionCallResult {
+//!     r[0] = u[0] - 1.0;
+//!     r[1] = 10.0 * (u[1] - u[0] * u[0]);
+//!     Ok(())
+//! };
+//! let jacobian_product = |u: &[f64], v: &[f64], jv: &mut [f64]| -> FunctionCallResult {
+//!     jv[0] = v[0];
+//!     jv[1] = 10.0 * (v[1] - 2.0 * u[0] * v[0]);
+//!     Ok(())
+//! };
+//! let jacobian_transpose_product = |u: &[f64], w: &[f64], jtw: &mut [f64]| -> FunctionCallResult {
+//!     jtw[0] = w[0] - 20.0 * u[0] * w[1];
+//!     jtw[1] = 10.0 * w[1];
+//!     Ok(())
+//! };
+//! let least_squares = LeastSquares::new(2, 2, residual, jacobian_product, jacobian_transpose_product);
+//! let no_constraints = NoConstraints::new();
+//! let problem = Problem::new(
+//!     &no_constraints,
+//!     |u: &[f64], grad: &mut [f64]| least_squares.gradient(u, grad),
+//!     |u: &[f64], c: &mut f64| least_squares.cost(u, c),
+//! );
+//! let mut cache = PANOCCache::new(2, 1e-10, 5)?;
+//! let mut u = [-1.2, 1.0];
+//! let status = PANOCOptimizer::new(problem, &mut cache)
+//!     .with_gauss_newton(&least_squares)?
+//!     .solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 1.0).abs() < 1e-8 && (u[1] - 1.0).abs() < 1e-8);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`LeastSquares`]: struct.LeastSquares.html
+//! [`GaussNewtonHessian`]: trait.GaussNewtonHessian.html
+
+use crate::{FunctionCallResult, OpEnFloat, SolverError};
+use std::{
+    fmt,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// Gauss-Newton approximation of the Hessian of a cost function
+///
+/// Implementations compute the product of $J(u)^\top J(u)$ (or of any other
+/// positive semidefinite approximation of the Hessian of the cost at $u$)
+/// with vectors. They are `Sync`, so that the optimizers which borrow them
+/// can be sent to other threads.
+pub trait GaussNewtonHessian<T = f64>: Sync {
+    /// Number of decision variables
+    fn dimension(&self) -> usize;
+
+    /// Computes the product of the Hessian approximation at `u` with `v` and
+    /// writes it into `product`
+    fn hessian_product(&self, u: &[T], v: &[T], product: &mut [T]) -> FunctionCallResult;
+}
+
+/// Least-squares cost function, $f(u) = \tfrac{1}{2}\Vert r(u)\Vert^2$
+///
+/// A workspace of `m` elements is allocated once, when a `LeastSquares` is
+/// constructed, so that no memory is allocated when the cost, the gradient
+/// and the Gauss-Newton Hessian-vector products are computed.
+pub struct LeastSquares<Residual, JacobianProduct, JacobianTransposeProduct, T = f64>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+{
+    /// number of decision variables
+    n: usize,
+    /// residual, $r(u)$
+    residual: Residual,
+    /// $(u, v) \mapsto J(u)v$
+    jacobian_product: JacobianProduct,
+    /// $(u, w) \mapsto J(u)^\top w$
+    jacobian_transpose_product: JacobianTransposeProduct,
+    /// residual or Jacobian-vector product
+    workspace: Mutex<Vec<T>>,
+}
+
+impl<Residual, JacobianProduct, JacobianTransposeProduct, T>
+    LeastSquares<Residual, JacobianProduct, JacobianTransposeProduct, T>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+{
+    /// Constructs a new instance
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: number of decision variables
+    /// - `m`: number of residuals
+    /// - `residual`: computes $r(u)$
+    /// - `jacobian_product`: computes $J(u)v$ given $u$ and $v$
+    /// - `jacobian_transpose_product`: computes $J(u)^\top w$ given $u$ and $w$
+    ///
+    pub fn new(
+        n: usize,
+        m: usize,
+        residual: Residual,
+        jacobian_product: JacobianProduct,
+        jacobian_transpose_product: JacobianTransposeProduct,
+    ) -> Self {
+        LeastSquares {
+            n,
+            residual,
+            jacobian_product,
+            jacobian_transpose_product,
+            workspace: Mutex::new(vec![T::zero(); m]),
+        }
+    }
+
+    /// Number of residuals
+    pub fn num_residuals(&self) -> usize {
+        self.workspace().len()
+    }
+
+    fn workspace(&self) -> MutexGuard<'_, Vec<T>> {
+        self.workspace
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn check_dimension(&self, name: &'static str, actual: usize) -> FunctionCallResult {
+        if actual == self.n {
+            Ok(())
+        } else {
+            Err(SolverError::DimensionMismatch {
+                name,
+                expected: self.n,
+                actual,
+            })
+        }
+    }
+
+    /// Computes the residual at `u`
+    pub fn residual(&self, u: &[T], r: &mut [T]) -> FunctionCallResult {
+        (self.residual)(u, r)
+    }
+
+    /// Computes the cost, $\tfrac{1}{2}\Vert r(u)\Vert^2$
+    pub fn cost(&self, u: &[T], cost: &mut T) -> FunctionCallResult {
+        let mut r = self.workspace();
+        (self.residual)(u, &mut r)?;
+        *cost = T::from_f64(0.5) * crate::matrix_operations::norm2_squared(&r);
+        Ok(())
+    }
+
+    /// Computes the gradient of the cost, $J(u)^\top r(u)$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` or `grad` do not have
+    /// `n` elements, or the error of the residual or the Jacobian product
+    ///
+    pub fn gradient(&self, u: &[T], grad: &mut [T]) -> FunctionCallResult {
+        self.check_dimension("u", u.len())?;
+        self.check_dimension("grad", grad.len())?;
+        let mut r = self.workspace();
+        (self.residual)(u, &mut r)?;
+        (self.jacobian_transpose_product)(u, &r, grad)
+    }
+
+    /// Number of decision variables
+    pub fn dimension(&self) -> usize {
+        self.n
+    }
+
+    /// Computes the Gauss-Newton Hessian-vector product, $J(u)^\top J(u) v$
+    pub fn hessian_product(&self, u: &[T], v: &[T], product: &mut [T]) -> FunctionCallResult {
+        let mut jv = self.workspace();
+        (self.jacobian_product)(u, v, &mut jv)?;
+        (self.jacobian_transpose_product)(u, &jv, product)
+    }
+}
+
+impl<Residual, JacobianProduct, JacobianTransposeProduct, T> GaussNewtonHessian<T>
+    for LeastSquares<Residual, JacobianProduct, JacobianTransposeProduct, T>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult + Sync,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult + Sync,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult + Sync,
+{
+    fn dimension(&self) -> usize {
+        LeastSquares::dimension(self)
+    }
+
+    /// Computes $J(u)^\top J(u) v$
+    fn hessian_product(&self, u: &[T], v: &[T], product: &mut [T]) -> FunctionCallResult {
+        LeastSquares::hessian_product(self, u, v, product)
+    }
+}
+
+impl<Residual, JacobianProduct, JacobianTransposeProduct, T> fmt::Debug
+    for LeastSquares<Residual, JacobianProduct, JacobianTransposeProduct, T>
+where
+    T: OpEnFloat,
+    Residual: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    JacobianProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+    JacobianTransposeProduct: Fn(&[T], &[T], &mut [T]) -> FunctionCallResult,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeastSquares")
+            .field("dimension", &self.n)
+            .field("num_residuals", &self.num_residuals())
+            .finish_non_exhaustive()
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::Rectangle, panoc::*, Optimizer, Problem};
+
+    const TIMES: [f64; 6] = [0.0, 0.5, 1.0, 1.5, 2.0, 3.0];
+
+    /// r_i(u) = u[0] exp(u[1] t_i) - y_i, with y_i = 2 exp(-t_i)
+    fn residual(u: &[f64], r: &mut [f64]) -> FunctionCallResult {
+        for (r_i, t) in r.iter_mut().zip(TIMES.iter()) {
+            *r_i = u[0] * (u[1] * t).exp() - 2.0 * (-t).exp();
+        }
+        Ok(())
+    }
+
+    fn jacobian_product(u: &[f64], v: &[f64], jv: &mut [f64]) -> FunctionCallResult {
+        for (jv_i, t) in jv.iter_mut().zip(TIMES.iter()) {
+            let e = (u[1] * t).exp();
+            *jv_i = e * v[0] + u[0] * t * e * v[1];
+        }
+        Ok(())
+    }
+
+    fn jacobian_transpose_product(u: &[f64], w: &[f64], jtw: &mut [f64]) -> FunctionCallResult {
+        jtw.fill(0.0);
+        for (w_i, t) in w.iter().zip(TIMES.iter()) {
+            let e = (u[1] * t).exp();
+            jtw[0] += e * w_i;
+            jtw[1] += u[0] * t * e * w_i;
+        }
+        Ok(())
+    }
+
+    macro_rules! exponential_fit {
+        () => {
+            LeastSquares::new(
+                2,
+                TIMES.len(),
+                residual,
+                jacobian_product,
+                jacobian_transpose_product,
+            )
+        };
+    }
+
+    #[test]
+    fn t_least_squares_cost_gradient() {
+        let least_squares = exponential_fit!();
+        assert_eq!(6, least_squares.num_residuals());
+        let u = [1.0, -0.5];
+        let mut cost = 0.0;
+        least_squares.cost(&u, &mut cost).unwrap();
+        let mut r = [0.0; 6];
+        least_squares.residual(&u, &mut r).unwrap();
+        let cost_expected = 0.5 * r.iter().map(|r_i| r_i * r_i).sum::<f64>();
+        assert!((cost - cost_expected).abs() < 1e-14);
+
+        let finite_difference =
+            crate::finite_difference::FiniteDifferenceGradient::new(2, |u: &[f64], c: &mut f64| {
+                least_squares.cost(u, c)
+            });
+        let (mut grad, mut grad_expected) = ([0.0; 2], [0.0; 2]);
+        least_squares.gradient(&u, &mut grad).unwrap();
+        finite_difference.gradient(&u, &mut grad_expected).unwrap();
+        unit_test_utils::assert_nearly_equal_array(&grad_expected, &grad, 1e-8, 1e-8, "grad");
+
+        // the first column of J'J at (2, -1) is sum_i (exp(-2 t_i), ...)
+        let mut product = [0.0; 2];
+        least_squares
+            .hessian_product(&[2.0, -1.0], &[1.0, 0.0], &mut product)
+            .unwrap();
+        let e_sum = TIMES.iter().map(|t| (-2.0 * t).exp()).sum::<f64>();
+        assert!((product[0] - e_sum).abs() < 1e-12);
+
+        assert!(matches!(
+            least_squares.gradient(&[0.0; 3], &mut grad),
+            Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: 2,
+                actual: 3,
+            })
+        ));
+    }
+
+    #[test]
+    fn t_least_squares_panoc_gauss_newton() {
+        let least_squares = exponential_fit!();
+        // the bound on u[0] is active at the solution
+        let bounds = Rectangle::new(Some(&[0.0, -5.0]), Some(&[1.5, 5.0])).unwrap();
+        let mut solutions = vec![];
+        let mut iterations = vec![];
+        for gauss_newton in [false, true] {
+            let problem = Problem::new(
+                &bounds,
+                |u: &[f64], grad: &mut [f64]| least_squares.gradient(u, grad),
+                |u: &[f64], c: &mut f64| least_squares.cost(u, c),
+            );
+            let mut cache = PANOCCache::new(2, 1e-10, 5).unwrap();
+            let mut optimizer = PANOCOptimizer::new(problem, &mut cache)
+                .with_max_iter(1000)
+                .unwrap();
+            if gauss_newton {
+                optimizer = optimizer.with_gauss_newton(&least_squares).unwrap();
+            }
+            let mut u = [1.0, 0.0];
+            let status = optimizer.solve(&mut u).unwrap();
+            assert!(status.has_converged());
+            solutions.push(u);
+            iterations.push(status.iterations());
+        }
+        assert!((solutions[1][0] - 1.5).abs() < 1e-9);
+        unit_test_utils::assert_nearly_equal_array(&solutions[0], &solutions[1], 1e-6, 1e-6, "u");
+        assert!(iterations[1] < iterations[0]);
+    }
+
+    #[test]
+    fn t_least_squares_gauss_newton_dimension() {
+        let least_squares = exponential_fit!();
+        let bounds = crate::constraints::NoConstraints::new();
+        let problem = Problem::new(
+            &bounds,
+            |u: &[f64], grad: &mut [f64]| least_squares.gradient(u, grad),
+            |u: &[f64], c: &mut f64| least_squares.cost(u, c),
+        );
+        let mut cache = PANOCCache::new(3, 1e-8, 5).unwrap();
+        assert!(matches!(
+            PANOCOptimizer::new(problem, &mut cache).with_gauss_newton(&least_squares),
+            Err(SolverError::DimensionMismatch {
+                name: "hessian",
+                expected: 3,
+                actual: 2,
+            })
+        ));
+    }
+}
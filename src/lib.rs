@@ -37,30 +37,393 @@
 //!   are mappings with smooth partial derivatives, and
 //! - $C\subseteq\mathbb{R}^{n_1}$ is a convex closed set on which we can easily compute projections.
 //!
+//! # Scalar types
+//!
+//! Constraints, caches and the PANOC, FBS and ALM/PM solvers are generic over
+//! the scalar type, which can be either `f64` (default) or `f32` (see
+//! [`OpEnFloat`]). `AffineSpace` and `EpigraphSquaredNorm` are only available
+//! in double precision.
+//!
+//! [`OpEnFloat`]: numeric/trait.OpEnFloat.html
+//!
 
 extern crate num;
 
+use std::fmt;
+
 /// Exceptions/Errors that may arise while solving a problem
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Apart from the generic variants `Cost` and `NotFiniteComputation`, which
+/// user-defined functions may return, the solvers report failures using
+/// variants which carry information about where (`location`) and at which
+/// iteration the failure happened.
+///
+/// User-defined functions (costs, gradients, mappings) can also return their
+/// own errors using the variant `User`, which the solvers propagate unchanged
+/// to the caller (see [`SolverError::user`]).
+///
+/// [`SolverError::user`]: enum.SolverError.html#method.user
+#[derive(Debug)]
 pub enum SolverError {
     /// If the gradient or cost function cannot be evaluated
     Cost,
     /// Computation failed and NaN/Infinite value was obtained
     NotFiniteComputation,
+    /// The cost function returned a NaN or infinite value
+    NotFiniteCost {
+        /// algorithm (or step of an algorithm) where the failure was detected
+        location: &'static str,
+        /// iteration at which the failure was detected
+        iteration: usize,
+    },
+    /// The gradient of the cost function contains a NaN or infinite value
+    NotFiniteGradient {
+        /// algorithm (or step of an algorithm) where the failure was detected
+        location: &'static str,
+        /// index of the first non-finite element of the gradient
+        index: usize,
+        /// iteration at which the failure was detected
+        iteration: usize,
+    },
+    /// The projection on a set of constraints failed, that is, it
+    /// produced a NaN or infinite value
+    ProjectionFailed {
+        /// algorithm (or step of an algorithm) where the failure was detected
+        location: &'static str,
+        /// iteration at which the failure was detected
+        iteration: usize,
+    },
+    /// The dimension of a given vector is not the expected one
+    DimensionMismatch {
+        /// name of the quantity with the wrong dimension
+        name: &'static str,
+        /// expected dimension
+        expected: usize,
+        /// actual dimension
+        actual: usize,
+    },
+    /// An invalid parameter was passed to a constructor or a setter method
+    InvalidParameter {
+        /// name of the parameter
+        name: &'static str,
+        /// description of the requirement that was violated
+        reason: &'static str,
+    },
+    /// A user-defined function (cost, gradient or mapping) returned a NaN or
+    /// infinite value; this is reported only if screening of the outputs of
+    /// user-defined functions is activated (e.g., using
+    /// `PANOCOptimizer::with_nan_screening`)
+    NotFiniteOutput {
+        /// algorithm (or step of an algorithm) where the failure was detected
+        location: &'static str,
+        /// user-defined function which returned the non-finite value
+        /// (e.g., `"cost"`, `"gradient"`, `"F1"` or `"F2"`)
+        function: &'static str,
+        /// index of the first non-finite element of the output (this is
+        /// `0` for scalar-valued functions)
+        index: usize,
+        /// iteration at which the failure was detected
+        iteration: usize,
+    },
+    /// Error raised by a user-defined function; the payload is propagated
+    /// unchanged to the caller
+    User(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl SolverError {
+    /// Constructs a user-defined error (variant `User`)
+    ///
+    /// ## Arguments
+    ///
+    /// - `error`: any error (or string) that can be converted into a
+    ///   `Box<dyn Error + Send + Sync>`
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use optimization_engine::{FunctionCallResult, SolverError};
+    ///
+    /// fn cost(u: &[f64], c: &mut f64) -> FunctionCallResult {
+    ///     if u[0] < 0.0 {
+    ///         return Err(SolverError::user("the simulator cannot handle u[0] < 0"));
+    ///     }
+    ///     *c = u[0] * u[0];
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut c = 0.0;
+    /// let error = cost(&[-1.0], &mut c).unwrap_err();
+    /// assert_eq!("user error: the simulator cannot handle u[0] < 0", error.to_string());
+    /// ```
+    pub fn user<E>(error: E) -> SolverError
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        SolverError::User(error.into())
+    }
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::Cost => write!(
+                f,
+                "the cost function or its gradient could not be evaluated"
+            ),
+            SolverError::NotFiniteComputation => {
+                write!(
+                    f,
+                    "computation failed: a NaN or infinite value was obtained"
+                )
+            }
+            SolverError::NotFiniteCost {
+                location,
+                iteration,
+            } => write!(
+                f,
+                "{}: the cost is not finite (iteration {})",
+                location, iteration
+            ),
+            SolverError::NotFiniteGradient {
+                location,
+                index,
+                iteration,
+            } => write!(
+                f,
+                "{}: the gradient of the cost is not finite at index {} (iteration {})",
+                location, index, iteration
+            ),
+            SolverError::ProjectionFailed {
+                location,
+                iteration,
+            } => write!(
+                f,
+                "{}: the projection on the set of constraints is not finite (iteration {})",
+                location, iteration
+            ),
+            SolverError::DimensionMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "`{}` has wrong dimension (expected {}, found {})",
+                name, expected, actual
+            ),
+            SolverError::InvalidParameter { name, reason } => {
+                write!(f, "invalid parameter `{}`: {}", name, reason)
+            }
+            SolverError::NotFiniteOutput {
+                location,
+                function,
+                index,
+                iteration,
+            } => write!(
+                f,
+                "{}: the output of `{}` is not finite at index {} (iteration {})",
+                location, function, index, iteration
+            ),
+            SolverError::User(error) => write!(f, "user error: {}", error),
+        }
+    }
+}
+
+/// Two user errors (variant `User`) are considered equal if their
+/// descriptions (as given by `Display`) are equal
+impl PartialEq for SolverError {
+    fn eq(&self, other: &Self) -> bool {
+        use SolverError::*;
+        match (self, other) {
+            (Cost, Cost) | (NotFiniteComputation, NotFiniteComputation) => true,
+            (
+                NotFiniteCost {
+                    location: l1,
+                    iteration: i1,
+                },
+                NotFiniteCost {
+                    location: l2,
+                    iteration: i2,
+                },
+            ) => l1 == l2 && i1 == i2,
+            (
+                NotFiniteGradient {
+                    location: l1,
+                    index: j1,
+                    iteration: i1,
+                },
+                NotFiniteGradient {
+                    location: l2,
+                    index: j2,
+                    iteration: i2,
+                },
+            ) => l1 == l2 && j1 == j2 && i1 == i2,
+            (
+                ProjectionFailed {
+                    location: l1,
+                    iteration: i1,
+                },
+                ProjectionFailed {
+                    location: l2,
+                    iteration: i2,
+                },
+            ) => l1 == l2 && i1 == i2,
+            (
+                DimensionMismatch {
+                    name: n1,
+                    expected: e1,
+                    actual: a1,
+                },
+                DimensionMismatch {
+                    name: n2,
+                    expected: e2,
+                    actual: a2,
+                },
+            ) => n1 == n2 && e1 == e2 && a1 == a2,
+            (
+                InvalidParameter {
+                    name: n1,
+                    reason: r1,
+                },
+                InvalidParameter {
+                    name: n2,
+                    reason: r2,
+                },
+            ) => n1 == n2 && r1 == r2,
+            (
+                NotFiniteOutput {
+                    location: l1,
+                    function: f1,
+                    index: j1,
+                    iteration: i1,
+                },
+                NotFiniteOutput {
+                    location: l2,
+                    function: f2,
+                    index: j2,
+                    iteration: i2,
+                },
+            ) => l1 == l2 && f1 == f2 && j1 == j2 && i1 == i2,
+            (User(e1), User(e2)) => e1.to_string() == e2.to_string(),
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for SolverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SolverError::User(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 /// Result of a function call (status)
 pub type FunctionCallResult = Result<(), SolverError>;
 
+/// Returns `SolverError::InvalidParameter` with the given name and reason,
+/// unless `condition` holds (used to validate arguments of constructors and
+/// setter methods)
+#[inline]
+pub(crate) fn ensure(
+    condition: bool,
+    name: &'static str,
+    reason: &'static str,
+) -> FunctionCallResult {
+    if condition {
+        Ok(())
+    } else {
+        Err(SolverError::InvalidParameter { name, reason })
+    }
+}
+
+/// Emits a debug-level event if OpEn is compiled with the feature `tracing`
+/// (otherwise, the arguments are not evaluated)
+macro_rules! debug_event {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)+);
+    };
+}
+
+/// Emits a trace-level event if OpEn is compiled with the feature `tracing`
+/// (otherwise, the arguments are not evaluated)
+macro_rules! trace_event {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)+);
+    };
+}
+
+/// Enters a debug-level span, which is exited at the end of the enclosing
+/// block, if OpEn is compiled with the feature `tracing`
+macro_rules! enter_debug_span {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($arg)+).entered();
+    };
+}
+
+/// Enters a trace-level span, which is exited at the end of the enclosing
+/// block, if OpEn is compiled with the feature `tracing`
+macro_rules! enter_trace_span {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($($arg)+).entered();
+    };
+}
+
+pub mod admm;
+#[cfg(feature = "alloc_counter")]
+pub mod alloc_counter;
 pub mod alm;
+pub mod autodiff;
+pub mod batch;
+#[cfg(all(feature = "casadi", unix))]
+pub mod casadi;
+#[cfg(feature = "codegen")]
+pub mod codegen;
 pub mod constraints;
 pub mod core;
+pub mod diagnostics;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod finite_difference;
+pub mod homotopy;
+#[cfg(any(feature = "nalgebra", feature = "ndarray"))]
+pub mod interop;
+pub mod least_squares;
+pub mod linear_operator;
 pub mod lipschitz_estimator;
+pub mod manifold;
 pub mod matrix_operations;
+pub mod minimize;
+pub mod mpc;
+pub mod multistart;
+#[cfg(feature = "npy")]
+pub mod npy;
+pub mod numeric;
+pub mod optimizer_builder;
+pub mod prox;
+pub mod quadratic_cost;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "test_problems")]
+pub mod test_problems;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+pub use crate::core::drs;
 pub use crate::core::fbs;
+pub use crate::core::lm;
+pub use crate::core::nelder_mead;
+pub use crate::core::newton_cg;
 pub use crate::core::panoc;
+pub use crate::core::pdhg;
+pub use crate::core::pgbb;
 pub use crate::core::{AlgorithmEngine, Optimizer, Problem};
+pub use crate::minimize::{minimize, MinimizeOptions};
+pub use crate::numeric::OpEnFloat;
 
 /* Use Jemalloc if the feature `jem` is activated */
 #[cfg(not(target_env = "msvc"))]
@@ -0,0 +1,428 @@
+//! Linear operators
+//!
+//! The trait [`LinearOperator`] describes a linear map,
+//! $A: \mathbb{R}^n \to \mathbb{R}^m$, through its products with vectors,
+//! $x \mapsto Ax$ and $y \mapsto A^\top y$, so the matrix $A$ need not be
+//! stored. This way, matrix-free operators (e.g., Jacobians which are
+//! computed by adjoint methods for PDEs or by sensitivity integrators for
+//! ODEs) can be used in place of matrices by
+//!
+//! - `AffineMapping` (module `alm`), for ALM-type constraints
+//!   $F_1(u) = Au + b$ in `AlmFactory` and `AlmProblem`,
+//! - `PDHGOptimizer`, whose operator $K$ is given by [`operator`] and
+//!   [`adjoint`] (and its norm, if known, by `with_operator_norm`),
+//! - the Gauss-Newton directions of PANOC (see
+//!   `PANOCOptimizer::with_gauss_newton`), via [`NormalOperator`], whose
+//!   products are $A^\top A v$.
+//!
+//! [`DenseMatrix`] is a linear operator which stores a (row-major) matrix.
+//!
+//! [`LinearOperator`]: trait.LinearOperator.html
+//! [`operator`]: fn.operator.html
+//! [`adjoint`]: fn.adjoint.html
+//! [`NormalOperator`]: struct.NormalOperator.html
+//! [`DenseMatrix`]: struct.DenseMatrix.html
+
+use crate::{
+    least_squares::GaussNewtonHessian, matrix_operations, multistart::SplitMix64,
+    FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Maximum number of power iterations of `estimate_norm`
+const MAX_POWER_ITERATIONS: usize = 500;
+
+/// Relative tolerance of the power iterations of `estimate_norm`
+const POWER_ITERATION_TOLERANCE: f64 = 1e-8;
+
+/// Seed of the pseudo-random starting point of the power iterations
+const POWER_ITERATION_SEED: u64 = 0x0b5e_55ed_c0ff_ee00;
+
+/// Safety factor of norms which are estimated with power iterations (which
+/// underestimate them)
+pub(crate) const NORM_SAFETY_FACTOR: f64 = 1.05;
+
+/// Linear operator, $A: \mathbb{R}^n \to \mathbb{R}^m$
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub trait LinearOperator<T = f64> {
+    /// Dimension of the range, $m$
+    fn rows(&self) -> usize;
+
+    /// Dimension of the domain, $n$
+    fn cols(&self) -> usize;
+
+    /// Computes $Ax$ and stores it in `y`
+    fn apply(&self, x: &[T], y: &mut [T]) -> FunctionCallResult;
+
+    /// Computes $A^\top y$ and stores it in `x`
+    fn apply_adjoint(&self, y: &[T], x: &mut [T]) -> FunctionCallResult;
+
+    /// Estimate (or upper bound) of the norm, $\Vert A \Vert_2$, if it is
+    /// known without computations, otherwise `None` (see `estimate_norm`)
+    fn norm_estimate(&self) -> Option<T> {
+        None
+    }
+}
+
+impl<T, Op> LinearOperator<T> for &Op
+where
+    Op: LinearOperator<T> + ?Sized,
+{
+    fn rows(&self) -> usize {
+        (**self).rows()
+    }
+
+    fn cols(&self) -> usize {
+        (**self).cols()
+    }
+
+    fn apply(&self, x: &[T], y: &mut [T]) -> FunctionCallResult {
+        (**self).apply(x, y)
+    }
+
+    fn apply_adjoint(&self, y: &[T], x: &mut [T]) -> FunctionCallResult {
+        (**self).apply_adjoint(y, x)
+    }
+
+    fn norm_estimate(&self) -> Option<T> {
+        (**self).norm_estimate()
+    }
+}
+
+/// Checks that the dimension of a vector is `expected`
+fn check_dimension(name: &'static str, expected: usize, actual: usize) -> FunctionCallResult {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(SolverError::DimensionMismatch {
+            name,
+            expected,
+            actual,
+        })
+    }
+}
+
+/// Estimates $\Vert A \Vert_2$ with (at most `max_iter`) power iterations on
+/// $A^\top A$, where `apply` and `apply_adjoint` compute $Ax$ and $A^\top y$
+///
+/// The starting point is pseudo-random (with a fixed seed), so that it is
+/// almost surely not orthogonal to the dominant right singular vector, as
+/// structured vectors (e.g., constant or increasing ones) can be, for
+/// instance, for difference operators. The iterations stop when two
+/// successive estimates agree up to the relative `tolerance`. The estimates
+/// increase to $\Vert A \Vert_2$, so the result is an underestimate, which
+/// callers who need an upper bound scale by `NORM_SAFETY_FACTOR`.
+///
+/// `x` and `y` are workspaces of $n$ and $m$ elements.
+pub(crate) fn power_iteration<T, A, At>(
+    x: &mut [T],
+    y: &mut [T],
+    apply: A,
+    apply_adjoint: At,
+    max_iter: usize,
+    tolerance: T,
+) -> Result<T, SolverError>
+where
+    T: OpEnFloat,
+    A: Fn(&[T], &mut [T]) -> FunctionCallResult,
+    At: Fn(&[T], &mut [T]) -> FunctionCallResult,
+{
+    if x.is_empty() || y.is_empty() {
+        return Ok(T::zero());
+    }
+    let mut generator = SplitMix64(POWER_ITERATION_SEED);
+    x.iter_mut()
+        .for_each(|x_i| *x_i = T::from_f64(2.0 * generator.next_uniform() - 1.0));
+    let mut norm_squared = T::zero();
+    for _ in 0..max_iter {
+        let norm_x = matrix_operations::norm2(x);
+        if norm_x == T::zero() {
+            break;
+        }
+        x.iter_mut().for_each(|x_i| *x_i /= norm_x);
+        apply(x, y)?;
+        apply_adjoint(y, x)?;
+        let norm_squared_previous = norm_squared;
+        norm_squared = matrix_operations::norm2(x);
+        if (norm_squared - norm_squared_previous).abs() <= tolerance * norm_squared {
+            break;
+        }
+    }
+    if !norm_squared.is_finite() {
+        return Err(SolverError::NotFiniteComputation);
+    }
+    Ok(norm_squared.sqrt())
+}
+
+/// Returns the norm estimate of `op`, if available, and otherwise estimates
+/// $\Vert A \Vert_2$ with power iterations on $A^\top A$
+///
+/// The power iterations start from a pseudo-random point and their result
+/// is scaled by a safety factor of 1.05, since they underestimate the norm,
+/// so the estimate can be passed to `PDHGOptimizer::with_operator_norm`.
+///
+/// ## Errors
+///
+/// Returns the errors of the products of `op`, or
+/// `SolverError::NotFiniteComputation` if they are not finite
+pub fn estimate_norm<T, Op>(op: &Op) -> Result<T, SolverError>
+where
+    T: OpEnFloat,
+    Op: LinearOperator<T> + ?Sized,
+{
+    if let Some(norm) = op.norm_estimate() {
+        return Ok(norm);
+    }
+    let mut x = vec![T::zero(); op.cols()];
+    let mut y = vec![T::zero(); op.rows()];
+    let norm = power_iteration(
+        &mut x,
+        &mut y,
+        |x: &[T], y: &mut [T]| op.apply(x, y),
+        |y: &[T], x: &mut [T]| op.apply_adjoint(y, x),
+        MAX_POWER_ITERATIONS,
+        T::from_f64(POWER_ITERATION_TOLERANCE),
+    )?;
+    Ok(norm * T::from_f64(NORM_SAFETY_FACTOR))
+}
+
+/// The product $x \mapsto Ax$ as a closure (e.g., the operator $K$ of
+/// `PDHGProblem`)
+pub fn operator<T, Op>(op: &Op) -> impl Fn(&[T], &mut [T]) -> FunctionCallResult + '_
+where
+    Op: LinearOperator<T> + ?Sized,
+{
+    move |x: &[T], y: &mut [T]| op.apply(x, y)
+}
+
+/// The product $y \mapsto A^\top y$ as a closure (e.g., the operator
+/// $K^\top$ of `PDHGProblem`)
+pub fn adjoint<T, Op>(op: &Op) -> impl Fn(&[T], &mut [T]) -> FunctionCallResult + '_
+where
+    Op: LinearOperator<T> + ?Sized,
+{
+    move |y: &[T], x: &mut [T]| op.apply_adjoint(y, x)
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          DENSE MATRIX                                                        */
+/* ---------------------------------------------------------------------------- */
+
+/// Dense matrix, stored in row-major order, as a linear operator
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseMatrix<T = f64> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: OpEnFloat> DenseMatrix<T> {
+    /// Constructs a `rows`-by-`cols` matrix from its elements in row-major
+    /// order
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `data` does not have
+    /// `rows * cols` elements
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> Result<Self, SolverError> {
+        check_dimension("data", rows * cols, data.len())?;
+        Ok(DenseMatrix { rows, cols, data })
+    }
+
+    /// Elements of the matrix in row-major order
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T: OpEnFloat> LinearOperator<T> for DenseMatrix<T> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn apply(&self, x: &[T], y: &mut [T]) -> FunctionCallResult {
+        check_dimension("x", self.cols, x.len())?;
+        check_dimension("y", self.rows, y.len())?;
+        let cols = self.cols;
+        y.iter_mut().enumerate().for_each(|(i, y_i)| {
+            *y_i = matrix_operations::inner_product(&self.data[i * cols..(i + 1) * cols], x)
+        });
+        Ok(())
+    }
+
+    fn apply_adjoint(&self, y: &[T], x: &mut [T]) -> FunctionCallResult {
+        check_dimension("y", self.rows, y.len())?;
+        check_dimension("x", self.cols, x.len())?;
+        x.fill(T::zero());
+        self.data
+            .chunks_exact(self.cols.max(1))
+            .zip(y.iter())
+            .for_each(|(row, &y_i)| {
+                x.iter_mut()
+                    .zip(row.iter())
+                    .for_each(|(x_j, &a_ij)| *x_j += a_ij * y_i)
+            });
+        Ok(())
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          NORMAL OPERATOR                                                     */
+/* ---------------------------------------------------------------------------- */
+
+/// The operator $A^\top A$ of a linear operator $A$, which is the
+/// (Gauss-Newton) Hessian of $\tfrac{1}{2}\Vert Au - b\Vert^2$ or of
+/// $\tfrac{1}{2}\Vert r(u)\Vert^2$ with Jacobian $A$
+///
+/// `NormalOperator` implements `GaussNewtonHessian`, so it can be passed to
+/// `PANOCOptimizer::with_gauss_newton`. A workspace of $m$ elements is
+/// allocated once, when it is constructed.
+#[derive(Debug)]
+pub struct NormalOperator<Op, T = f64> {
+    op: Op,
+    /// $Av$
+    workspace: Mutex<Vec<T>>,
+}
+
+impl<Op, T> NormalOperator<Op, T>
+where
+    T: OpEnFloat,
+    Op: LinearOperator<T>,
+{
+    /// Constructs the operator $A^\top A$, where $A$ is `op`
+    pub fn new(op: Op) -> Self {
+        let workspace = Mutex::new(vec![T::zero(); op.rows()]);
+        NormalOperator { op, workspace }
+    }
+
+    /// The operator $A$
+    pub fn operator(&self) -> &Op {
+        &self.op
+    }
+
+    fn workspace(&self) -> MutexGuard<'_, Vec<T>> {
+        self.workspace
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<Op, T> GaussNewtonHessian<T> for NormalOperator<Op, T>
+where
+    T: OpEnFloat,
+    Op: LinearOperator<T> + Sync,
+{
+    fn dimension(&self) -> usize {
+        self.op.cols()
+    }
+
+    /// Computes $A^\top A v$
+    fn hessian_product(&self, _u: &[T], v: &[T], product: &mut [T]) -> FunctionCallResult {
+        let mut av = self.workspace();
+        self.op.apply(v, &mut av)?;
+        self.op.apply_adjoint(&av, product)
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The operator of `DenseMatrix::new(2, 3, [1, 2, 0, 0, 1, -1])`, which
+    /// is given by closures
+    struct MatrixFree;
+
+    impl LinearOperator for MatrixFree {
+        fn rows(&self) -> usize {
+            2
+        }
+
+        fn cols(&self) -> usize {
+            3
+        }
+
+        fn apply(&self, x: &[f64], y: &mut [f64]) -> FunctionCallResult {
+            y[0] = x[0] + 2.0 * x[1];
+            y[1] = x[1] - x[2];
+            Ok(())
+        }
+
+        fn apply_adjoint(&self, y: &[f64], x: &mut [f64]) -> FunctionCallResult {
+            x[0] = y[0];
+            x[1] = 2.0 * y[0] + y[1];
+            x[2] = -y[1];
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn t_dense_matrix() {
+        let a = DenseMatrix::new(2, 3, vec![1.0, 2.0, 0.0, 0.0, 1.0, -1.0]).unwrap();
+        let x = [1.0, -2.0, 0.5];
+        let (mut y, mut y_expected) = ([0.0; 2], [0.0; 2]);
+        a.apply(&x, &mut y).unwrap();
+        MatrixFree.apply(&x, &mut y_expected).unwrap();
+        assert_eq!(y_expected, y);
+        let (mut aty, mut aty_expected) = ([0.0; 3], [0.0; 3]);
+        a.apply_adjoint(&y, &mut aty).unwrap();
+        MatrixFree.apply_adjoint(&y, &mut aty_expected).unwrap();
+        assert_eq!(aty_expected, aty);
+        assert!(matches!(
+            a.apply(&[0.0; 2], &mut y),
+            Err(SolverError::DimensionMismatch { name: "x", .. })
+        ));
+        assert!(DenseMatrix::new(2, 2, vec![1.0; 3]).is_err());
+    }
+
+    #[test]
+    fn t_estimate_norm() {
+        // A A' = [5 2; 2 2], whose largest eigenvalue is 6
+        let norm = estimate_norm::<f64, _>(&MatrixFree).unwrap();
+        assert!((norm - NORM_SAFETY_FACTOR * 6.0_f64.sqrt()).abs() < 1e-6);
+        let a = DenseMatrix::new(2, 3, vec![1.0, 2.0, 0.0, 0.0, 1.0, -1.0]).unwrap();
+        assert!((estimate_norm(&a).unwrap() - norm).abs() < 1e-10);
+
+        let normal = NormalOperator::new(&a);
+        assert_eq!(3, normal.dimension());
+        let mut product = [0.0; 3];
+        normal
+            .hessian_product(&[0.0; 3], &[1.0, 0.0, 0.0], &mut product)
+            .unwrap();
+        // first column of A'A
+        assert_eq!([1.0, 2.0, 0.0], product);
+    }
+
+    #[test]
+    fn t_estimate_norm_difference_operator() {
+        // the dominant right singular vector of D, (1, -2, 1), is orthogonal
+        // to constant and to linearly increasing vectors
+        let d = DenseMatrix::new(2, 3, vec![-1.0, 1.0, 0.0, 0.0, -1.0, 1.0]).unwrap();
+        let norm = estimate_norm(&d).unwrap();
+        assert!(norm >= 3.0_f64.sqrt() && norm <= NORM_SAFETY_FACTOR * 3.0_f64.sqrt() + 1e-10);
+
+        // a longer difference operator, whose norm is 2 cos(pi / (2n))
+        let n = 50;
+        let mut data = vec![0.0; (n - 1) * n];
+        (0..n - 1).for_each(|i| {
+            data[i * n + i] = -1.0;
+            data[i * n + i + 1] = 1.0;
+        });
+        let d = DenseMatrix::new(n - 1, n, data).unwrap();
+        let norm_d = 2.0 * (std::f64::consts::PI / (2.0 * n as f64)).cos();
+        let norm = estimate_norm(&d).unwrap();
+        assert!(norm >= norm_d && norm <= NORM_SAFETY_FACTOR * norm_d + 1e-10);
+
+        assert_eq!(
+            0.0,
+            estimate_norm(&DenseMatrix::new(0, 3, Vec::<f64>::new()).unwrap()).unwrap()
+        );
+    }
+}
@@ -37,37 +37,39 @@
 //! ```
 //!
 
-use crate::{matrix_operations, SolverError};
+use crate::{core::maybe_owned::MaybeOwnedMut, matrix_operations, OpEnFloat, SolverError};
 
 const DEFAULT_DELTA: f64 = 1e-6;
 const DEFAULT_EPSILON: f64 = 1e-6;
 
 /// Structure for the computation of estimates of the Lipschitz constant of mappings
-pub struct LipschitzEstimator<'a, F>
+pub struct LipschitzEstimator<'a, F, T = f64>
 where
-    F: Fn(&[f64], &mut [f64]) -> Result<(), SolverError>,
+    T: OpEnFloat,
+    F: FnMut(&[T], &mut [T]) -> Result<(), SolverError>,
 {
     /// `u_decision_var` is the point where the Lipschitz constant is estimated
-    u_decision_var: &'a mut [f64],
-    ///  internally allocated workspace memory
-    workspace: Vec<f64>,
+    u_decision_var: &'a mut [T],
+    ///  workspace memory (internally allocated, unless it is provided)
+    workspace: MaybeOwnedMut<'a, Vec<T>>,
     /// `function_value_at_u` a vector which is updated with the
     /// value of the given function, `F`, at `u`; the provided value
     /// of `function_value_at_u_p` is not used
-    function_value_at_u: &'a mut [f64],
+    function_value_at_u: &'a mut [T],
     ///
     /// Function whose Lipschitz constant is to be approximated
     ///
     /// For example, in optimization, this is the gradient (Jacobian matrix)
     /// of the cost function (this is a closure)
-    function: &'a F,
-    epsilon_lip: f64,
-    delta_lip: f64,
+    function: F,
+    epsilon_lip: T,
+    delta_lip: T,
 }
 
-impl<'a, F> LipschitzEstimator<'a, F>
+impl<'a, F, T> LipschitzEstimator<'a, F, T>
 where
-    F: Fn(&[f64], &mut [f64]) -> Result<(), SolverError>,
+    T: OpEnFloat,
+    F: FnMut(&[T], &mut [T]) -> Result<(), SolverError>,
 {
     /// Creates a new instance of this structure
     ///
@@ -78,7 +80,8 @@ where
     ///    reference). The value of `u_` at exit is slightly perturbed. If you need
     ///    to keep the original value of `u_`, you need to make a copy of the variable
     ///    before you provide it to this method.
-    /// - `f_` given closure
+    /// - `f_` given closure (or a reference to it); the closure may mutate
+    ///   its state (`FnMut`), e.g., a workspace
     /// - `function_value_` externally allocated memory which on exit stores the
     ///    value of the given function at `u_`, that is `f_(u_)`
     ///
@@ -88,18 +91,48 @@ where
     ///
     ///
     pub fn new(
-        u_: &'a mut [f64],
-        f_: &'a F,
-        function_value_: &'a mut [f64],
-    ) -> LipschitzEstimator<'a, F> {
+        u_: &'a mut [T],
+        f_: F,
+        function_value_: &'a mut [T],
+    ) -> LipschitzEstimator<'a, F, T> {
         let n: usize = u_.len();
+        LipschitzEstimator::with_workspace_storage(
+            u_,
+            f_,
+            function_value_,
+            MaybeOwnedMut::Owned(vec![T::zero(); n]),
+        )
+    }
+
+    /// Same as `new`, with an externally allocated workspace (of the same
+    /// dimension as `u_`), so that no memory is allocated
+    pub(crate) fn with_workspace(
+        u_: &'a mut [T],
+        f_: F,
+        function_value_: &'a mut [T],
+        workspace: &'a mut Vec<T>,
+    ) -> LipschitzEstimator<'a, F, T> {
+        LipschitzEstimator::with_workspace_storage(
+            u_,
+            f_,
+            function_value_,
+            MaybeOwnedMut::Borrowed(workspace),
+        )
+    }
+
+    fn with_workspace_storage(
+        u_: &'a mut [T],
+        f_: F,
+        function_value_: &'a mut [T],
+        workspace: MaybeOwnedMut<'a, Vec<T>>,
+    ) -> LipschitzEstimator<'a, F, T> {
         LipschitzEstimator {
             u_decision_var: u_,
-            workspace: vec![0.0_f64; n],
+            workspace,
             function_value_at_u: function_value_,
             function: f_,
-            epsilon_lip: DEFAULT_EPSILON,
-            delta_lip: DEFAULT_DELTA,
+            epsilon_lip: T::from_f64(DEFAULT_EPSILON),
+            delta_lip: T::from_f64(DEFAULT_DELTA),
         }
     }
 
@@ -110,13 +143,13 @@ where
     ///
     /// - `delta`: parameter delta (the default value is `1e-6`)
     ///
-    /// # Panics
-    /// The method will panic if `delta` is non positive
+    /// # Errors
+    /// Returns `SolverError::InvalidParameter` if `delta` is non positive
     ///
-    pub fn with_delta(mut self, delta: f64) -> Self {
-        assert!(delta > 0.0);
+    pub fn with_delta(mut self, delta: T) -> Result<Self, SolverError> {
+        crate::ensure(delta > T::zero(), "delta", "must be positive")?;
         self.delta_lip = delta;
-        self
+        Ok(self)
     }
 
     ///
@@ -126,13 +159,13 @@ where
     ///
     /// - `epsilon`: parameter epsilon  (the default value is `1e-6`)
     ///
-    /// # Panics
-    /// The method will panic if `epsilon` is non positive
+    /// # Errors
+    /// Returns `SolverError::InvalidParameter` if `epsilon` is non positive
     ///
-    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
-        assert!(epsilon > 0.0);
+    pub fn with_epsilon(mut self, epsilon: T) -> Result<Self, SolverError> {
+        crate::ensure(epsilon > T::zero(), "epsilon", "must be positive")?;
         self.epsilon_lip = epsilon;
-        self
+        Ok(self)
     }
     ///
     /// Getter method for the Jacobian
@@ -143,7 +176,7 @@ where
     ///
     /// If `estimate_local_lipschitz` has not been computed, the result
     /// will point to a zero vector.
-    pub fn get_function_value(&self) -> &[f64] {
+    pub fn get_function_value(&self) -> &[T] {
         self.function_value_at_u
     }
 
@@ -180,7 +213,7 @@ where
     /// No rust-side panics, unless the C function which is called via this interface
     /// fails.
     ///
-    pub fn estimate_local_lipschitz(&mut self) -> Result<f64, SolverError> {
+    pub fn estimate_local_lipschitz(&mut self) -> Result<T, SolverError> {
         // function_value = gradient(u, p)
         (self.function)(self.u_decision_var, self.function_value_at_u)?;
         let epsilon_lip = self.epsilon_lip;
@@ -236,7 +269,9 @@ mod tests {
 
         let mut lip_estimator = LipschitzEstimator::new(&mut u, &f, &mut function_value)
             .with_delta(1e-4)
-            .with_epsilon(1e-4);
+            .unwrap()
+            .with_epsilon(1e-4)
+            .unwrap();
         let lip = lip_estimator.estimate_local_lipschitz().unwrap();
 
         unit_test_utils::assert_nearly_equal(
@@ -251,25 +286,25 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn t_test_lip_delta_epsilon_panic1() {
+    fn t_test_lip_delta_epsilon_invalid1() {
         let mut u: [f64; 3] = [1.0, 2.0, 3.0];
         let mut function_value = [0.0; 3];
 
-        let _lip_estimator =
+        let lip_estimator =
             LipschitzEstimator::new(&mut u, &mocks::lipschitz_mock, &mut function_value)
                 .with_epsilon(0.0);
+        assert!(lip_estimator.is_err());
     }
 
     #[test]
-    #[should_panic]
-    fn t_test_lip_delta_epsilon_panic2() {
+    fn t_test_lip_delta_epsilon_invalid2() {
         let mut u: [f64; 3] = [1.0, 2.0, 3.0];
         let mut function_value = [0.0; 3];
 
-        let _lip_estimator =
+        let lip_estimator =
             LipschitzEstimator::new(&mut u, &mocks::lipschitz_mock, &mut function_value)
-                .with_epsilon(0.0);
+                .with_delta(0.0);
+        assert!(lip_estimator.is_err());
     }
 
     #[test]
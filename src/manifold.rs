@@ -0,0 +1,389 @@
+//! Optimization on manifolds
+//!
+//! Variables which live on a smooth manifold, $M$, embedded in
+//! $\mathbb{R}^n$ (e.g., unit vectors or rotation matrices) can be handled by
+//! PANOC natively (see `PANOCOptimizer::with_manifold`), instead of through
+//! projections on a set of constraints. A manifold is described by the trait
+//! [`Manifold`], that is, by
+//!
+//! - the orthogonal projection on the tangent space at $x \in M$,
+//!   $T_x M$, which maps the gradient of the cost to the Riemannian
+//!   gradient, and
+//! - a retraction, $R_x: T_x M \to M$, which moves from $x$ along a tangent
+//!   vector while staying on the manifold.
+//!
+//! In the manifold mode, the forward step of PANOC is $R_u(-\gamma\,
+//! \mathrm{grad} f(u))$, the directions are projected on $T_u M$, and the
+//! candidate points are retracted on $M$, so the iterates stay on the
+//! manifold (up to rounding errors). This module offers the sphere
+//! ([`Sphere`]) and the special orthogonal group ([`SpecialOrthogonal3`]);
+//! other manifolds (e.g., fixed-rank matrices) can be supported by
+//! implementing [`Manifold`].
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     constraints::NoConstraints, manifold::Sphere, panoc::*, FunctionCallResult, Optimizer,
+//!     Problem,
+//! };
+//!
+//! // maximize <c, u> over the unit sphere
+//! let c = [3.0, 4.0];
+//! let cost = |u: &[f64], f: &mut f64| -> FunctionCallResult {
+//!     *f = -c[0] * u[0] - c[1] * u[1];
+//!     Ok(())
+//! };
+//! let grad = |_u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+//!     g[0] = -c[0];
+//!     g[1] = -c[1];
+//!     Ok(())
+//! };
+//! let sphere = Sphere::new(1.0)?;
+//! let bounds = NoConstraints::new();
+//! let problem = Problem::new(&bounds, grad, cost);
+//! let mut u = [1.0, 0.0];
+//! let status = PANOCOptimizer::from_dimensions(problem, 2, 1e-10, 3)?
+//!     .with_manifold(&sphere)?
+//!     .solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 0.6).abs() < 1e-8 && (u[1] - 0.8).abs() < 1e-8);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`Manifold`]: trait.Manifold.html
+//! [`Sphere`]: struct.Sphere.html
+//! [`SpecialOrthogonal3`]: struct.SpecialOrthogonal3.html
+
+use crate::{matrix_operations, OpEnFloat, SolverError};
+
+/// A smooth manifold embedded in $\mathbb{R}^n$, given by the projection on
+/// its tangent spaces and a retraction
+///
+/// The type parameter `T` is the scalar type (`f64` by default). Manifolds
+/// are `Sync`, so that the optimizers which borrow them can be sent to other
+/// threads.
+pub trait Manifold<T = f64>: Sync {
+    /// Dimension, $n$, of the (Euclidean) space in which the manifold is
+    /// embedded, that is, the number of decision variables, or zero (the
+    /// default) if the manifold can be embedded in a space of any dimension
+    fn dimension(&self) -> usize {
+        0
+    }
+
+    /// Projects `v` on the tangent space at `x` (in place)
+    ///
+    /// ## Arguments
+    ///
+    /// - `x`: a point on the manifold
+    /// - `v`: on entry, a vector of $\mathbb{R}^n$; on exit, its orthogonal
+    ///   projection on the tangent space at `x`
+    fn project_tangent(&self, x: &[T], v: &mut [T]);
+
+    /// Computes the retraction $y = R_x(v)$
+    ///
+    /// ## Arguments
+    ///
+    /// - `x`: a point on the manifold
+    /// - `v`: a tangent vector at `x`
+    /// - `y`: on exit, a point on the manifold
+    fn retract(&self, x: &[T], v: &[T], y: &mut [T]);
+}
+
+/// Sphere of a given radius (centered at the origin), $\\{x : \Vert x\Vert = r\\}$
+///
+/// The retraction is $R_x(v) = r(x + v)/\Vert x + v\Vert$; the sphere can
+/// have any dimension (`dimension` returns 0).
+#[derive(Debug, Clone, Copy)]
+pub struct Sphere<T = f64> {
+    radius: T,
+}
+
+impl<T: OpEnFloat> Sphere<T> {
+    /// Constructs a new sphere of given radius
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `radius` is not positive
+    /// and finite
+    pub fn new(radius: T) -> Result<Self, SolverError> {
+        crate::ensure(
+            radius > T::zero() && radius.is_finite(),
+            "radius",
+            "must be positive and finite",
+        )?;
+        Ok(Sphere { radius })
+    }
+}
+
+impl<T: OpEnFloat> Manifold<T> for Sphere<T> {
+    fn project_tangent(&self, x: &[T], v: &mut [T]) {
+        let coefficient =
+            matrix_operations::inner_product(x, v) / matrix_operations::norm2_squared(x);
+        v.iter_mut()
+            .zip(x.iter())
+            .for_each(|(v_i, &x_i)| *v_i -= coefficient * x_i);
+    }
+
+    fn retract(&self, x: &[T], v: &[T], y: &mut [T]) {
+        y.iter_mut()
+            .zip(x.iter().zip(v.iter()))
+            .for_each(|(y_i, (&x_i, &v_i))| *y_i = x_i + v_i);
+        let scaling = self.radius / matrix_operations::norm2(y);
+        y.iter_mut().for_each(|y_i| *y_i *= scaling);
+    }
+}
+
+/// Special orthogonal group, SO(3), that is, the rotation matrices of
+/// $\mathbb{R}^{3\times 3}$, stored in row-major order (9 decision variables)
+///
+/// The tangent space at $R$ consists of the matrices $R\Omega$, where
+/// $\Omega$ is skew-symmetric, and the retraction is the exponential map,
+/// $R_R(R\Omega) = R\exp(\Omega)$, computed with the formula of Rodrigues.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpecialOrthogonal3;
+
+impl SpecialOrthogonal3 {
+    /// Constructs a new instance of SO(3)
+    pub fn new() -> Self {
+        SpecialOrthogonal3
+    }
+}
+
+/// Computes the axial vector of the skew-symmetric part of $R^\top V$
+fn skew_of_product<T: OpEnFloat>(r: &[T], v: &[T]) -> [T; 3] {
+    // (R^T V)_{ij} = sum_k R_{ki} V_{kj}
+    let product =
+        |i: usize, j: usize| (0..3).fold(T::zero(), |s, k| s + r[3 * k + i] * v[3 * k + j]);
+    let half = T::from_f64(0.5);
+    [
+        half * (product(2, 1) - product(1, 2)),
+        half * (product(0, 2) - product(2, 0)),
+        half * (product(1, 0) - product(0, 1)),
+    ]
+}
+
+/// Computes $y = R M$, where $M$ is given by the function `m`
+fn multiply<T: OpEnFloat>(r: &[T], m: impl Fn(usize, usize) -> T, y: &mut [T]) {
+    for i in 0..3 {
+        for j in 0..3 {
+            y[3 * i + j] = (0..3).fold(T::zero(), |s, k| s + r[3 * i + k] * m(k, j));
+        }
+    }
+}
+
+/// Element $(i, j)$ of the skew-symmetric matrix of the axial vector `w`
+fn skew<T: OpEnFloat>(w: &[T; 3], i: usize, j: usize) -> T {
+    match (i, j) {
+        (0, 1) => -w[2],
+        (0, 2) => w[1],
+        (1, 0) => w[2],
+        (1, 2) => -w[0],
+        (2, 0) => -w[1],
+        (2, 1) => w[0],
+        _ => T::zero(),
+    }
+}
+
+impl<T: OpEnFloat> Manifold<T> for SpecialOrthogonal3 {
+    fn dimension(&self) -> usize {
+        9
+    }
+
+    fn project_tangent(&self, x: &[T], v: &mut [T]) {
+        let w = skew_of_product(x, v);
+        multiply(x, |i, j| skew(&w, i, j), v);
+    }
+
+    fn retract(&self, x: &[T], v: &[T], y: &mut [T]) {
+        let w = skew_of_product(x, v);
+        let theta_squared = w[0] * w[0] + w[1] * w[1] + w[2] * w[2];
+        let theta = theta_squared.sqrt();
+        // exp(W) = I + a W + b W^2, with a = sin(theta)/theta and
+        // b = (1 - cos(theta))/theta^2 (Taylor expansions for small angles)
+        let (a, b) = if theta < T::from_f64(1e-4) {
+            (
+                T::one() - theta_squared / T::from_f64(6.0),
+                T::from_f64(0.5) - theta_squared / T::from_f64(24.0),
+            )
+        } else {
+            (
+                theta.sin() / theta,
+                (T::one() - theta.cos()) / theta_squared,
+            )
+        };
+        let exponential = |i: usize, j: usize| {
+            let identity = if i == j { T::one() } else { T::zero() };
+            let w_squared = (0..3).fold(T::zero(), |s, k| s + skew(&w, i, k) * skew(&w, k, j));
+            identity + a * skew(&w, i, j) + b * w_squared
+        };
+        multiply(x, exponential, y);
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constraints::NoConstraints, core::panoc::PANOCOptimizer, FunctionCallResult, Optimizer,
+        Problem,
+    };
+
+    /// Computes R^T R - I (row-major)
+    fn orthogonality_error(r: &[f64]) -> f64 {
+        let mut error: f64 = 0.0;
+        for i in 0..3 {
+            for j in 0..3 {
+                let rtr: f64 = (0..3).map(|k| r[3 * k + i] * r[3 * k + j]).sum();
+                error = error.max((rtr - if i == j { 1.0 } else { 0.0 }).abs());
+            }
+        }
+        error
+    }
+
+    #[test]
+    fn t_manifold_tangent_and_retraction() {
+        let sphere = Sphere::new(2.0).unwrap();
+        let x = [0.0_f64, 2.0, 0.0];
+        let mut v = [1.0, 3.0, -1.0];
+        sphere.project_tangent(&x, &mut v);
+        assert_eq!([1.0, 0.0, -1.0], v);
+        let mut y = [0.0; 3];
+        sphere.retract(&x, &v, &mut y);
+        assert!((matrix_operations::norm2(&y) - 2.0).abs() < 1e-12);
+        assert!(Sphere::new(0.0).is_err());
+
+        // rotation by 90 degrees about the z axis
+        let so3 = SpecialOrthogonal3::new();
+        let r = [0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+        let mut v = [0.3, 0.1, -2.0, 0.5, 1.0, 0.7, 0.2, -0.4, 0.9];
+        so3.project_tangent(&r, &mut v);
+        // tangent vectors satisfy R^T V + V^T R = 0
+        let w = skew_of_product(&r, &v);
+        let mut v_again = v;
+        so3.project_tangent(&r, &mut v_again);
+        unit_test_utils::assert_nearly_equal_array(&v, &v_again, 1e-12, 1e-12, "P(P(v))");
+        let mut y = [0.0; 9];
+        so3.retract(&r, &v, &mut y);
+        assert!(orthogonality_error(&y) < 1e-12);
+        // small steps: R exp(W) = R (I + W) + O(|W|^2)
+        let w_small = [1e-6 * w[0], 1e-6 * w[1], 1e-6 * w[2]];
+        let mut v_small = [0.0; 9];
+        multiply(&r, |i, j| skew(&w_small, i, j), &mut v_small);
+        so3.retract(&r, &v_small, &mut y);
+        for k in 0..9 {
+            assert!((y[k] - r[k] - v_small[k]).abs() < 1e-11);
+        }
+        assert_eq!(9, Manifold::<f64>::dimension(&so3));
+    }
+
+    #[test]
+    fn t_manifold_panoc_sphere_eigenvector() {
+        // minimize u'Au over the unit sphere: eigenvector of the smallest
+        // eigenvalue of A = diag(3, 1, 2) + 0.1 (ones - I)
+        let a_product = |u: &[f64], au: &mut [f64]| {
+            let diagonal = [3.0, 1.0, 2.0];
+            let sum: f64 = u.iter().sum();
+            for i in 0..3 {
+                au[i] = diagonal[i] * u[i] + 0.1 * (sum - u[i]);
+            }
+        };
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            let mut au = [0.0; 3];
+            a_product(u, &mut au);
+            *c = matrix_operations::inner_product(u, &au);
+            Ok(())
+        };
+        let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+            a_product(u, g);
+            g.iter_mut().for_each(|g_i| *g_i *= 2.0);
+            Ok(())
+        };
+        let sphere = Sphere::new(1.0).unwrap();
+        let mut u = [0.5, 0.5, 0.5_f64.sqrt()];
+        let status = PANOCOptimizer::from_dimensions(
+            Problem::new(&NoConstraints::new(), grad, cost),
+            3,
+            1e-10,
+            5,
+        )
+        .unwrap()
+        .with_max_iter(500)
+        .unwrap()
+        .with_manifold(&sphere)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+        assert!(status.has_converged());
+        assert!((matrix_operations::norm2(&u) - 1.0).abs() < 1e-12);
+        // A u = lambda u
+        let mut au = [0.0; 3];
+        a_product(&u, &mut au);
+        let lambda = matrix_operations::inner_product(&u, &au);
+        for i in 0..3 {
+            assert!((au[i] - lambda * u[i]).abs() < 1e-8);
+        }
+        assert!(u[1].abs() > 0.9 && lambda < 1.0);
+    }
+
+    #[test]
+    fn t_manifold_panoc_wahba() {
+        // Wahba's problem: minimize |R - B|^2 over SO(3); the solution is
+        // the rotation which is closest to B
+        let angle: f64 = 0.7;
+        let rotation = [
+            angle.cos(),
+            -angle.sin(),
+            0.0,
+            angle.sin(),
+            angle.cos(),
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        ];
+        // B = 1.5 * rotation * diag(1, 1.2, 0.8) (positive singular values)
+        let scale = [1.5, 1.8, 1.2];
+        let b: Vec<f64> = (0..9).map(|k| rotation[k] * scale[k % 3]).collect();
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = matrix_operations::norm2_squared_diff(u, &b);
+            Ok(())
+        };
+        let grad = |u: &[f64], g: &mut [f64]| -> FunctionCallResult {
+            for k in 0..9 {
+                g[k] = 2.0 * (u[k] - b[k]);
+            }
+            Ok(())
+        };
+        let so3 = SpecialOrthogonal3::new();
+        let mut u = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        let status = PANOCOptimizer::from_dimensions(
+            Problem::new(&NoConstraints::new(), grad, cost),
+            9,
+            1e-10,
+            5,
+        )
+        .unwrap()
+        .with_manifold(&so3)
+        .unwrap()
+        .solve(&mut u)
+        .unwrap();
+        assert!(status.has_converged());
+        assert!(orthogonality_error(&u) < 1e-10);
+        unit_test_utils::assert_nearly_equal_array(&rotation, &u, 1e-7, 1e-8, "R");
+
+        // the dimension of the manifold must match the problem
+        assert!(PANOCOptimizer::from_dimensions(
+            Problem::new(&NoConstraints::new(), grad, cost),
+            3,
+            1e-10,
+            5
+        )
+        .unwrap()
+        .with_manifold(&so3)
+        .is_err());
+    }
+}
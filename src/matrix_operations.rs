@@ -35,11 +35,33 @@
 //! assert!(norm_inf_b == 6.);
 //! ```
 //!
+//! # Reproducibility
+//!
+//! All reductions (inner products, norms and sums) add up their terms one
+//! by one, in increasing order of their indices, starting from zero; they
+//! do not depend on the implementation of `Sum` by the standard library.
+//! Since every operation is rounded according to IEEE 754, the results are
+//! bitwise identical across runs and platforms for the same inputs. With
+//! the feature `deterministic`, this summation order is guaranteed even if
+//! faster reductions (e.g., parallel or SIMD ones, which sum in a different
+//! order) are added in the future: these are never used with the feature.
+//!
 
 use num::{Float, Zero};
 use std::iter::Sum;
 use std::ops::Mul;
 
+/// Adds up `terms` one by one, in the order of the iterator, starting from
+/// zero (this is the fixed summation order of all reductions)
+#[inline(always)]
+fn sum_in_order<T, I>(terms: I) -> T
+where
+    T: Float,
+    I: Iterator<Item = T>,
+{
+    terms.fold(T::zero(), |sum, term| sum + term)
+}
+
 /// Calculate the inner product of two vectors
 #[inline(always)]
 pub fn inner_product<T>(a: &[T], b: &[T]) -> T
@@ -48,7 +70,7 @@ where
 {
     assert!(a.len() == b.len());
 
-    a.iter().zip(b.iter()).map(|(x, y)| (*x) * (*y)).sum()
+    sum_in_order(a.iter().zip(b.iter()).map(|(x, y)| (*x) * (*y)))
 }
 
 /// Calculate the 1-norm of a vector
@@ -57,7 +79,7 @@ pub fn norm1<T>(a: &[T]) -> T
 where
     T: Float + Sum<T>,
 {
-    a.iter().map(|x| x.abs()).sum()
+    sum_in_order(a.iter().map(|x| x.abs()))
 }
 
 /// Calculate the 2-norm of a vector
@@ -76,10 +98,7 @@ pub fn norm2_squared_diff<T>(a: &[T], b: &[T]) -> T
 where
     T: Float + Sum<T> + Mul<T, Output = T> + std::ops::AddAssign,
 {
-    a.iter().zip(b.iter()).fold(T::zero(), |mut sum, (&x, &y)| {
-        sum += (x - y).powi(2);
-        sum
-    })
+    sum_in_order(a.iter().zip(b.iter()).map(|(&x, &y)| (x - y) * (x - y)))
 }
 
 /// Calculate the 2-norm of a vector
@@ -88,7 +107,7 @@ pub fn norm2_squared<T>(a: &[T]) -> T
 where
     T: Float + Sum<T> + Mul<T, Output = T>,
 {
-    let norm: T = a.iter().map(|x| (*x) * (*x)).sum();
+    let norm: T = sum_in_order(a.iter().map(|x| (*x) * (*x)));
     norm
 }
 
@@ -98,7 +117,7 @@ pub fn sum<T>(a: &[T]) -> T
 where
     T: Float + Sum<T> + Mul<T, Output = T>,
 {
-    let norm: T = a.iter().copied().sum();
+    let norm: T = sum_in_order(a.iter().copied());
     norm
 }
 
@@ -140,6 +159,20 @@ where
     !a.iter().any(|&xi| !xi.is_finite())
 }
 
+/// Finds the first element of a vector which is not finite
+///
+/// ## Returns
+///
+/// Returns the index of the first element which is either NaN or
+/// Infinity, or `None` if all elements are finite
+#[inline(always)]
+pub fn first_non_finite<T>(a: &[T]) -> Option<usize>
+where
+    T: Float,
+{
+    a.iter().position(|&xi| !xi.is_finite())
+}
+
 /* ---------------------------------------------------------------------------- */
 /*          TESTS                                                               */
 /* ---------------------------------------------------------------------------- */
@@ -241,4 +274,27 @@ mod tests {
         let norm2sq = matrix_operations::norm2_squared_diff(&x, &y);
         unit_test_utils::assert_nearly_equal(190., norm2sq, 1e-10, 1e-12, "norm sq diff");
     }
+
+    #[test]
+    fn t_summation_order() {
+        // in increasing order of the indices, 1e16 + 1 rounds to 1e16, so that
+        // only the last term survives the cancellation (other orders give 0 or 2)
+        let a = [1e16, 1.0, -1e16, 1.0];
+        assert_eq!(1.0, matrix_operations::sum(&a));
+        assert_eq!(1.0, matrix_operations::inner_product(&a, &[1.0; 4]));
+        assert_eq!(2e16, matrix_operations::norm1(&a));
+        // the sum of no terms is zero (not negative zero)
+        assert!(matrix_operations::sum::<f64>(&[]).is_sign_positive());
+        assert!(matrix_operations::norm2_squared::<f64>(&[]).is_sign_positive());
+    }
+
+    #[test]
+    fn t_first_non_finite() {
+        assert_eq!(None, matrix_operations::first_non_finite(&[1.0, -2.0, 3.0]));
+        assert_eq!(
+            Some(1),
+            matrix_operations::first_non_finite(&[1.0, f64::NAN, f64::INFINITY])
+        );
+        assert_eq!(None, matrix_operations::first_non_finite::<f64>(&[]));
+    }
 }
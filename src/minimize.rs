@@ -0,0 +1,252 @@
+//! One-call entry point of PANOC
+//!
+//! [`minimize`] solves a problem with PANOC without the boilerplate of
+//! constructing a `Problem`, a `PANOCCache` and a `PANOCOptimizer`: it takes
+//! the cost, its gradient, the constraints and an initial guess and returns
+//! the solution together with the status of the solver. The tolerance, the
+//! L-BFGS memory and the configuration of the solver are set with
+//! [`MinimizeOptions`].
+//!
+//! Every call allocates a new cache, so `minimize` is meant for one-off
+//! solves (e.g., in scripts, tests and prototypes); for repeated solves
+//! (e.g., in MPC), construct the cache once and reuse it.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{constraints::Ball2, *};
+//!
+//! let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = (u[0] - 2.0).powi(2) + (u[1] - 2.0).powi(2);
+//!     Ok(())
+//! };
+//! let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     grad[0] = 2.0 * (u[0] - 2.0);
+//!     grad[1] = 2.0 * (u[1] - 2.0);
+//!     Ok(())
+//! };
+//! let ball = Ball2::new(None, 1.0)?;
+//! let options = MinimizeOptions::new().with_tolerance(1e-8)?;
+//! let (u, status) = minimize(cost, gradient, &ball, &[0.0, 0.0], &options)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 0.5_f64.sqrt()).abs() < 1e-6);
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! [`minimize`]: fn.minimize.html
+//! [`MinimizeOptions`]: struct.MinimizeOptions.html
+
+use crate::{
+    constraints::Constraint,
+    core::{
+        panoc::{PANOCOptimizer, PanocConfig},
+        Optimizer, Problem, SolverStatus,
+    },
+    ensure, FunctionCallResult, OpEnFloat, SolverError,
+};
+use std::time;
+
+/// Default tolerance of `minimize`
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// Default L-BFGS memory of `minimize`
+const DEFAULT_LBFGS_MEMORY: usize = 10;
+
+/// Options of [`minimize`](fn.minimize.html)
+///
+/// The tolerance and the L-BFGS memory are used to construct the cache; all
+/// other settings of PANOC are given by a `PanocConfig` (whose tolerance,
+/// if set, takes precedence).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MinimizeOptions {
+    tolerance: f64,
+    lbfgs_memory: usize,
+    config: PanocConfig,
+}
+
+impl Default for MinimizeOptions {
+    fn default() -> Self {
+        MinimizeOptions {
+            tolerance: DEFAULT_TOLERANCE,
+            lbfgs_memory: DEFAULT_LBFGS_MEMORY,
+            config: PanocConfig::default(),
+        }
+    }
+}
+
+impl MinimizeOptions {
+    /// Constructs new options with default values (tolerance `1e-6`, L-BFGS
+    /// memory `10` and the default `PanocConfig`)
+    pub fn new() -> Self {
+        MinimizeOptions::default()
+    }
+
+    /// Sets the tolerance on the norm of the fixed-point residual
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the tolerance is not
+    /// positive
+    pub fn with_tolerance(mut self, tolerance: f64) -> Result<Self, SolverError> {
+        ensure(tolerance > 0.0, "tolerance", "must be positive")?;
+        self.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Sets the memory of the L-BFGS buffer
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the memory is zero
+    pub fn with_lbfgs_memory(mut self, lbfgs_memory: usize) -> Result<Self, SolverError> {
+        ensure(lbfgs_memory > 0, "lbfgs_memory", "must be positive")?;
+        self.lbfgs_memory = lbfgs_memory;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.config.max_iter = max_iter;
+        self
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.config.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Sets the configuration of PANOC (see `PANOCOptimizer::with_config`)
+    pub fn with_config(mut self, config: PanocConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Tolerance on the norm of the fixed-point residual
+    pub fn tolerance(&self) -> f64 {
+        self.config.tolerance.unwrap_or(self.tolerance)
+    }
+
+    /// Memory of the L-BFGS buffer
+    pub fn lbfgs_memory(&self) -> usize {
+        self.lbfgs_memory
+    }
+
+    /// Configuration of PANOC
+    pub fn config(&self) -> &PanocConfig {
+        &self.config
+    }
+}
+
+/// Minimizes `cost` over `constraints` with PANOC, starting from `u0`
+///
+/// The function constructs the problem, the cache and the optimizer,
+/// solves the problem and returns the solution together with the status of
+/// the solver.
+///
+/// ## Arguments
+///
+/// - `cost`: cost function, $f$
+/// - `gradient`: gradient of the cost function, $\nabla f$
+/// - `constraints`: set of constraints, $U$
+/// - `u0`: initial guess (which determines the number of decision variables)
+/// - `options`: tolerance, L-BFGS memory and configuration of PANOC
+///
+/// ## Errors
+///
+/// Returns `SolverError::InvalidParameter` if `u0` is empty or the
+/// configuration is invalid, and otherwise the errors of
+/// `PANOCOptimizer::solve`
+pub fn minimize<T, CostType, GradientType, ConstraintType>(
+    cost: CostType,
+    gradient: GradientType,
+    constraints: &ConstraintType,
+    u0: &[T],
+    options: &MinimizeOptions,
+) -> Result<(Vec<T>, SolverStatus), SolverError>
+where
+    T: OpEnFloat,
+    CostType: FnMut(&[T], &mut T) -> FunctionCallResult,
+    GradientType: FnMut(&[T], &mut [T]) -> FunctionCallResult,
+    ConstraintType: Constraint<T>,
+{
+    ensure(!u0.is_empty(), "u0", "must not be empty")?;
+    let problem = Problem::new(constraints, gradient, cost);
+    let mut optimizer = PANOCOptimizer::from_dimensions(
+        problem,
+        u0.len(),
+        T::from_f64(options.tolerance),
+        options.lbfgs_memory,
+    )?
+    .with_config(&options.config)?;
+    let mut u = u0.to_vec();
+    let status = optimizer.solve(&mut u)?;
+    Ok((u, status))
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::Rectangle, core::ExitStatus, mocks};
+
+    #[test]
+    fn t_minimize() {
+        let bounds = Rectangle::new(Some(&[-1.0, -1.0]), Some(&[1.0, 1.0])).unwrap();
+        let options = MinimizeOptions::new().with_tolerance(1e-10).unwrap();
+        let (u, status) = minimize(
+            mocks::my_cost,
+            mocks::my_gradient,
+            &bounds,
+            &[0.75, 0.0],
+            &options,
+        )
+        .unwrap();
+        assert!(status.has_converged());
+        assert!(status.norm_fpr() < 1e-10);
+
+        // the same problem solved with an explicit cache
+        let mut cache = crate::panoc::PANOCCache::new(2, 1e-10, 10).unwrap();
+        let mut u_cache = [0.75, 0.0];
+        PANOCOptimizer::new(
+            Problem::new(&bounds, mocks::my_gradient, mocks::my_cost),
+            &mut cache,
+        )
+        .solve(&mut u_cache)
+        .unwrap();
+        assert_eq!(&u_cache[..], &u[..]);
+    }
+
+    #[test]
+    fn t_minimize_options() {
+        let bounds = Rectangle::new(Some(&[-1.0, -1.0]), Some(&[1.0, 1.0])).unwrap();
+        let options = MinimizeOptions::new().with_max_iter(1);
+        let (_, status) = minimize(
+            mocks::my_cost,
+            mocks::my_gradient,
+            &bounds,
+            &[0.75, 0.0],
+            &options,
+        )
+        .unwrap();
+        assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+        assert_eq!(1, status.iterations());
+
+        let config = PanocConfig::new().with_tolerance(1e-3);
+        assert_eq!(1e-3, options.with_config(config).tolerance());
+        assert_eq!(DEFAULT_TOLERANCE, options.tolerance());
+        assert!(MinimizeOptions::new().with_tolerance(0.0).is_err());
+        assert!(MinimizeOptions::new().with_lbfgs_memory(0).is_err());
+        let empty: [f64; 0] = [];
+        assert!(minimize(
+            mocks::my_cost,
+            mocks::my_gradient,
+            &bounds,
+            &empty,
+            &options
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,1129 @@
+//! Model predictive control
+//!
+//! A [`RecedingHorizonController`] wraps an [`AlmOptimizer`] which solves an
+//! optimal control problem over a horizon of $N$ stages, whose decision
+//! variables are the control actions, $u = (u_0, u_1, \ldots, u_{N-1})$,
+//! stored stage after stage. At every sampling time, the controller
+//!
+//! - writes the current state (and, optionally, the reference) to the
+//!   parameters of the problem (see [`MpcParameters`]),
+//! - solves the problem, warm starting from the previous solution shifted
+//!   forward by one stage (the last stage is repeated), and
+//! - returns the first control action, $u_0$.
+//!
+//! The cost and the constraints of the problem read the state and the
+//! reference from the shared [`MpcParameters`], a handle of which is moved
+//! into the closures which define the problem.
+//!
+//! Rate constraints of the control actions, $|u_{k+1} - u_k| \leq
+//! \Delta_{\max}$, can be imposed as ALM constraints using the mapping, its
+//! Jacobian and the set constructed by [`RateConstraints`]. For long
+//! horizons, the problem size can be reduced by move blocking (see
+//! [`MoveBlocking`]), which holds the control actions constant over blocks
+//! of stages.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{alm::*, constraints::Rectangle, mpc::*, FunctionCallResult};
+//!
+//! // x+ = x + u, horizon N = 5, cost sum (x_k - r)^2 + u_k^2, |u_k| <= 1
+//! let horizon = 5;
+//! let parameters = MpcParameters::new(1, 1);
+//! let (p_cost, p_grad) = (parameters.clone(), parameters.clone());
+//! let psi = move |u: &[f64], _xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+//!     let (mut x, r) = (p_cost.state()[0], p_cost.reference()[0]);
+//!     *cost = 0.0;
+//!     for &u_k in u {
+//!         x += u_k;
+//!         *cost += (x - r).powi(2) + u_k.powi(2);
+//!     }
+//!     Ok(())
+//! };
+//! let d_psi = move |u: &[f64], _xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     let (mut x, r) = (p_grad.state()[0], p_grad.reference()[0]);
+//!     let mut errors = vec![0.0; u.len()];
+//!     for (e_k, &u_k) in errors.iter_mut().zip(u.iter()) {
+//!         x += u_k;
+//!         *e_k = 2.0 * (x - r);
+//!     }
+//!     // the control action u_k affects the states x_{k+1}, ..., x_N
+//!     let mut adjoint = 0.0;
+//!     for k in (0..u.len()).rev() {
+//!         adjoint += errors[k];
+//!         grad[k] = adjoint + 2.0 * u[k];
+//!     }
+//!     Ok(())
+//! };
+//! let (umin, umax) = (vec![-1.0; horizon], vec![1.0; horizon]);
+//! let bounds = Rectangle::new(Some(&umin), Some(&umax))?;
+//! let problem = AlmProblem::new(bounds, NO_SET, NO_SET, psi, d_psi, NO_MAPPING, NO_MAPPING, 0, 0)?;
+//! let optimizer = AlmOptimizer::from_dimensions(problem, horizon, 1e-8, 5)?;
+//! let mut controller = RecedingHorizonController::new(optimizer, parameters, 1, horizon)?;
+//!
+//! controller.set_reference(&[2.0])?;
+//! let mut x = 0.0;
+//! for _ in 0..20 {
+//!     x += controller.compute_control(&[x])?[0];
+//! }
+//! assert!((x - 2.0).abs() < 1e-6);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`RecedingHorizonController`]: struct.RecedingHorizonController.html
+//! [`AlmOptimizer`]: ../alm/struct.AlmOptimizer.html
+//! [`MpcParameters`]: struct.MpcParameters.html
+//! [`RateConstraints`]: struct.RateConstraints.html
+//! [`MoveBlocking`]: struct.MoveBlocking.html
+
+use crate::{
+    alm::{AlmOptimizer, AlmOptimizerStatus},
+    constraints, ensure, FunctionCallResult, SolverError,
+};
+use std::{
+    cell::{Ref, RefCell},
+    rc::Rc,
+};
+
+/// Parameters of an MPC problem: the current state, followed by the
+/// reference
+///
+/// Clones of an `MpcParameters` share the same values, so a clone can be
+/// moved into every closure which defines the problem, while the
+/// [`RecedingHorizonController`](struct.RecedingHorizonController.html)
+/// updates the values.
+#[derive(Debug, Clone)]
+pub struct MpcParameters {
+    state_dimension: usize,
+    values: Rc<RefCell<Vec<f64>>>,
+}
+
+impl MpcParameters {
+    /// Constructs new parameters (initially zero) for a state of dimension
+    /// `state_dimension` and a reference of dimension `reference_dimension`
+    /// (which can be zero)
+    pub fn new(state_dimension: usize, reference_dimension: usize) -> Self {
+        MpcParameters {
+            state_dimension,
+            values: Rc::new(RefCell::new(vec![
+                0.0;
+                state_dimension + reference_dimension
+            ])),
+        }
+    }
+
+    /// Dimension of the state
+    pub fn state_dimension(&self) -> usize {
+        self.state_dimension
+    }
+
+    /// Dimension of the reference
+    pub fn reference_dimension(&self) -> usize {
+        self.values.borrow().len() - self.state_dimension
+    }
+
+    /// All parameters: the state, followed by the reference
+    pub fn values(&self) -> Ref<'_, [f64]> {
+        Ref::map(self.values.borrow(), |values| values.as_slice())
+    }
+
+    /// Current state
+    pub fn state(&self) -> Ref<'_, [f64]> {
+        let n = self.state_dimension;
+        Ref::map(self.values.borrow(), |values| &values[..n])
+    }
+
+    /// Current reference
+    pub fn reference(&self) -> Ref<'_, [f64]> {
+        let n = self.state_dimension;
+        Ref::map(self.values.borrow(), |values| &values[n..])
+    }
+
+    /// Sets the state
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the length of `state` is
+    /// not equal to the dimension of the state
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the parameters are borrowed (e.g., by `state`)
+    pub fn set_state(&self, state: &[f64]) -> FunctionCallResult {
+        check_dimension("state", self.state_dimension, state.len())?;
+        self.values.borrow_mut()[..self.state_dimension].copy_from_slice(state);
+        Ok(())
+    }
+
+    /// Sets the reference
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the length of `reference`
+    /// is not equal to the dimension of the reference
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the parameters are borrowed (e.g., by `state`)
+    pub fn set_reference(&self, reference: &[f64]) -> FunctionCallResult {
+        check_dimension("reference", self.reference_dimension(), reference.len())?;
+        self.values.borrow_mut()[self.state_dimension..].copy_from_slice(reference);
+        Ok(())
+    }
+}
+
+fn check_dimension(name: &'static str, expected: usize, actual: usize) -> FunctionCallResult {
+    if expected != actual {
+        return Err(SolverError::DimensionMismatch {
+            name,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Receding-horizon (MPC) controller
+///
+/// See the [module documentation](index.html).
+pub struct RecedingHorizonController<
+    'life,
+    MappingAlm,
+    MappingPm,
+    ParametricGradientType,
+    ParametricCostType,
+    ConstraintsType,
+    AlmSetC,
+    LagrangeSetY,
+> where
+    MappingAlm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    MappingPm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[f64], &[f64], &mut f64) -> FunctionCallResult,
+    ConstraintsType: constraints::Constraint,
+    AlmSetC: constraints::Constraint,
+    LagrangeSetY: constraints::Constraint,
+{
+    optimizer: AlmOptimizer<
+        'life,
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+    >,
+    parameters: MpcParameters,
+    control_dimension: usize,
+    /// Initial guess of the next solve
+    warm_start: Vec<f64>,
+    /// Sequence of control actions computed by the last solve
+    solution: Vec<f64>,
+    /// Status of the last solve
+    status: Option<AlmOptimizerStatus>,
+    /// Move-blocking pattern, if the decision variables are the control
+    /// actions of blocks of stages
+    move_blocking: Option<MoveBlocking>,
+}
+
+impl<
+        'life,
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+    >
+    RecedingHorizonController<
+        'life,
+        MappingAlm,
+        MappingPm,
+        ParametricGradientType,
+        ParametricCostType,
+        ConstraintsType,
+        AlmSetC,
+        LagrangeSetY,
+    >
+where
+    MappingAlm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    MappingPm: FnMut(&[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricGradientType: FnMut(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
+    ParametricCostType: FnMut(&[f64], &[f64], &mut f64) -> FunctionCallResult,
+    ConstraintsType: constraints::Constraint,
+    AlmSetC: constraints::Constraint,
+    LagrangeSetY: constraints::Constraint,
+{
+    /// Constructs a new controller
+    ///
+    /// The initial guess of the first solve is zero (see `with_initial_guess`)
+    ///
+    /// ## Arguments
+    ///
+    /// - `optimizer`: optimizer of the problem, whose decision variables are
+    ///   the control actions of all stages, stored stage after stage
+    /// - `parameters`: parameters read by the problem (see `MpcParameters`)
+    /// - `control_dimension`: dimension of the control action of a stage
+    /// - `horizon`: number of stages
+    ///
+    /// ## Errors
+    ///
+    /// - `SolverError::InvalidParameter` if `control_dimension` or `horizon`
+    ///   is zero
+    /// - `SolverError::DimensionMismatch` if the number of decision variables
+    ///   of `optimizer` is not `control_dimension * horizon`
+    pub fn new(
+        optimizer: AlmOptimizer<
+            'life,
+            MappingAlm,
+            MappingPm,
+            ParametricGradientType,
+            ParametricCostType,
+            ConstraintsType,
+            AlmSetC,
+            LagrangeSetY,
+        >,
+        parameters: MpcParameters,
+        control_dimension: usize,
+        horizon: usize,
+    ) -> Result<Self, SolverError> {
+        ensure(
+            control_dimension > 0,
+            "control_dimension",
+            "must be positive",
+        )?;
+        ensure(horizon > 0, "horizon", "must be positive")?;
+        let problem_size = control_dimension * horizon;
+        check_dimension("optimizer", problem_size, optimizer.problem_size())?;
+        Ok(RecedingHorizonController {
+            optimizer,
+            parameters,
+            control_dimension,
+            warm_start: vec![0.0; problem_size],
+            solution: vec![0.0; problem_size],
+            status: None,
+            move_blocking: None,
+        })
+    }
+
+    /// Constructs a new controller with move blocking
+    ///
+    /// The decision variables of `optimizer` are the control actions of the
+    /// blocks of `move_blocking` (see `MoveBlocking`), from which the full
+    /// sequence of control actions is computed after every solve. The next
+    /// solve is warm started from the full sequence shifted by one stage (the
+    /// control action of every block is taken from its first stage).
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the number of decision
+    /// variables of `optimizer` is not `move_blocking.reduced_dimension()`
+    pub fn from_move_blocking(
+        optimizer: AlmOptimizer<
+            'life,
+            MappingAlm,
+            MappingPm,
+            ParametricGradientType,
+            ParametricCostType,
+            ConstraintsType,
+            AlmSetC,
+            LagrangeSetY,
+        >,
+        parameters: MpcParameters,
+        move_blocking: MoveBlocking,
+    ) -> Result<Self, SolverError> {
+        let reduced_dimension = move_blocking.reduced_dimension();
+        check_dimension("optimizer", reduced_dimension, optimizer.problem_size())?;
+        Ok(RecedingHorizonController {
+            optimizer,
+            parameters,
+            control_dimension: move_blocking.control_dimension(),
+            warm_start: vec![0.0; reduced_dimension],
+            solution: vec![0.0; move_blocking.full_dimension()],
+            status: None,
+            move_blocking: Some(move_blocking),
+        })
+    }
+
+    /// Sets the initial guess of the first solve (the control actions of all
+    /// stages or, with move blocking, of all blocks)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the length of
+    /// `initial_guess` is not the number of decision variables of the
+    /// optimizer
+    pub fn with_initial_guess(mut self, initial_guess: &[f64]) -> Result<Self, SolverError> {
+        check_dimension("initial_guess", self.warm_start.len(), initial_guess.len())?;
+        self.warm_start.copy_from_slice(initial_guess);
+        Ok(self)
+    }
+
+    /// Sets the reference of the next solves
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if the length of `reference`
+    /// is not equal to the dimension of the reference
+    pub fn set_reference(&mut self, reference: &[f64]) -> FunctionCallResult {
+        self.parameters.set_reference(reference)
+    }
+
+    /// Computes the control action at the state `state`
+    ///
+    /// The problem is solved from the previous solution, shifted by one stage
+    /// (the last stage is repeated), and the first control action, $u_0$, is
+    /// returned; the status of the solve is available from `status` (the
+    /// control action is returned even if the solver has not converged)
+    ///
+    /// ## Errors
+    ///
+    /// - `SolverError::DimensionMismatch` if the length of `state` is not
+    ///   equal to the dimension of the state
+    /// - the errors of `AlmOptimizer::solve`
+    pub fn compute_control(&mut self, state: &[f64]) -> Result<&[f64], SolverError> {
+        self.parameters.set_state(state)?;
+        self.status = None;
+        let status = self.optimizer.solve(&mut self.warm_start)?;
+        self.status = Some(status);
+        let nu = self.control_dimension;
+        // shift the solution forward by one stage
+        match &self.move_blocking {
+            Some(move_blocking) => {
+                move_blocking.expand(&self.warm_start, &mut self.solution);
+                move_blocking.reduce_shifted(&self.solution, 1, &mut self.warm_start);
+            }
+            None => {
+                self.solution.copy_from_slice(&self.warm_start);
+                self.warm_start.copy_within(nu.., 0);
+            }
+        }
+        Ok(&self.solution[..nu])
+    }
+
+    /// Sequence of control actions computed by the last solve (stage after
+    /// stage, also with move blocking)
+    pub fn predicted_controls(&self) -> &[f64] {
+        &self.solution
+    }
+
+    /// Status of the last solve, or `None` if no solve has succeeded yet
+    pub fn status(&self) -> Option<&AlmOptimizerStatus> {
+        self.status.as_ref()
+    }
+
+    /// Parameters of the problem
+    pub fn parameters(&self) -> &MpcParameters {
+        &self.parameters
+    }
+
+    /// Resets the initial guess of the next solve to zero
+    pub fn reset(&mut self) {
+        self.warm_start.iter_mut().for_each(|u_i| *u_i = 0.0);
+    }
+}
+
+/// Rate (slew-rate) constraints of the control actions
+///
+/// For a horizon of $N$ stages and control actions of dimension $n_u$, the
+/// constraints $|u_{k+1} - u_k| \leq \Delta_{\max}$ (element-wise), for
+/// $k = 0, \ldots, N-2$, are encoded as $F_1(u) \in C$, where
+/// $F_1(u) = (u_1 - u_0, \ldots, u_{N-1} - u_{N-2})$ is a linear mapping into
+/// $\mathbb{R}^{n_1}$ with $n_1 = (N-1)n_u$ and $C$ is the Cartesian product
+/// of $N-1$ boxes $[-\Delta_{\max}, \Delta_{\max}]$. The mapping, its
+/// (transposed) Jacobian and the set can be passed to an `AlmFactory` and an
+/// `AlmProblem`.
+///
+/// Note that the change with respect to the previously applied control action,
+/// $u_0 - u_{-1}$, is not constrained.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::{constraints::Constraint, mpc::RateConstraints};
+///
+/// // two control actions over a horizon of three stages
+/// let rates = RateConstraints::new(2, 3, &[0.1, 1.0])?;
+/// assert_eq!(4, rates.dimension());
+/// let u = [0.0, 0.0, 0.5, 0.5, 0.5, 2.0];
+/// let mut f1 = [0.0; 4];
+/// (rates.mapping())(&u, &mut f1)?;
+/// assert_eq!([0.5, 0.5, 0.0, 1.5], f1);
+/// rates.set().project(&mut f1);
+/// assert_eq!([0.1, 0.5, 0.0, 1.0], f1);
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateConstraints {
+    control_dimension: usize,
+    horizon: usize,
+    min_rate: Vec<f64>,
+    max_rate: Vec<f64>,
+}
+
+impl RateConstraints {
+    /// Constructs the rate constraints of control actions of dimension
+    /// `control_dimension` over a horizon of `horizon` stages, with maximum
+    /// changes `max_rate` (of length `control_dimension`) between consecutive
+    /// stages
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `control_dimension` is zero,
+    /// if `horizon` is smaller than 2 or if an element of `max_rate` is
+    /// negative, and `SolverError::DimensionMismatch` if the length of
+    /// `max_rate` is not `control_dimension`
+    pub fn new(
+        control_dimension: usize,
+        horizon: usize,
+        max_rate: &[f64],
+    ) -> Result<Self, SolverError> {
+        ensure(
+            control_dimension > 0,
+            "control_dimension",
+            "must be positive",
+        )?;
+        ensure(horizon >= 2, "horizon", "must be at least 2")?;
+        check_dimension("max_rate", control_dimension, max_rate.len())?;
+        ensure(
+            max_rate.iter().all(|&rate| rate >= 0.0),
+            "max_rate",
+            "must be nonnegative",
+        )?;
+        Ok(RateConstraints {
+            control_dimension,
+            horizon,
+            min_rate: max_rate.iter().map(|&rate| -rate).collect(),
+            max_rate: max_rate.to_vec(),
+        })
+    }
+
+    /// Dimension $n_1 = (N-1)n_u$ of the range of $F_1$
+    pub fn dimension(&self) -> usize {
+        (self.horizon - 1) * self.control_dimension
+    }
+
+    /// Mapping $F_1(u) = (u_1 - u_0, \ldots, u_{N-1} - u_{N-2})$
+    pub fn mapping(&self) -> impl Fn(&[f64], &mut [f64]) -> FunctionCallResult {
+        let nu = self.control_dimension;
+        move |u: &[f64], f1: &mut [f64]| -> FunctionCallResult {
+            f1.iter_mut()
+                .zip(u[nu..].iter().zip(u.iter()))
+                .for_each(|(f1_i, (&u_next, &u_i))| *f1_i = u_next - u_i);
+            Ok(())
+        }
+    }
+
+    /// Product $JF_1(u)^\top d$ (which does not depend on $u$, as $F_1$ is
+    /// linear)
+    pub fn jacobian_mapping_trans(
+        &self,
+    ) -> impl Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult {
+        let nu = self.control_dimension;
+        move |_u: &[f64], d: &[f64], result: &mut [f64]| -> FunctionCallResult {
+            result.iter_mut().for_each(|r_i| *r_i = 0.0);
+            result[nu..]
+                .iter_mut()
+                .zip(d.iter())
+                .for_each(|(r_i, &d_i)| *r_i += d_i);
+            result
+                .iter_mut()
+                .zip(d.iter())
+                .for_each(|(r_i, &d_i)| *r_i -= d_i);
+            Ok(())
+        }
+    }
+
+    /// Set $C$, the Cartesian product of $N-1$ boxes
+    /// $[-\Delta_{\max}, \Delta_{\max}]$
+    pub fn set(&self) -> constraints::CartesianProduct<'_> {
+        let nu = self.control_dimension;
+        (1..self.horizon).fold(
+            constraints::CartesianProduct::new_with_capacity(self.horizon - 1),
+            |set, k| {
+                let rectangle =
+                    constraints::Rectangle::new(Some(&self.min_rate), Some(&self.max_rate))
+                        .expect("the maximum rates are nonnegative");
+                set.add_constraint(k * nu, rectangle)
+                    .expect("the indices are increasing")
+            },
+        )
+    }
+}
+
+/// Move blocking of the control actions
+///
+/// The horizon of $N$ stages is split into consecutive blocks, over each of
+/// which the control action is held constant, so that the decision variables
+/// are the control actions of the blocks, $v = (v_0, \ldots, v_{M-1})$, and
+/// the problem size is reduced from $N n_u$ to $M n_u$. The full sequence of
+/// control actions is $u = Ev$, where $E$ repeats $v_i$ over the stages of
+/// the $i$-th block; accordingly, gradients (and products with transposed
+/// Jacobians) with respect to $u$ are mapped to gradients with respect to $v$
+/// by $E^\top$, which sums them over the stages of each block.
+///
+/// The cost, its gradient and the mappings of a problem which are defined on
+/// the full sequence of control actions can be wrapped with `cost`,
+/// `gradient` and `mapping`; the resulting functions are defined on $v$ and
+/// are passed to an `AlmProblem` (the constraints on $u$ need to be expressed
+/// as constraints on $v$). A `RecedingHorizonController` with move blocking
+/// is constructed with `RecedingHorizonController::from_move_blocking`.
+///
+/// # Example
+///
+/// ```
+/// use optimization_engine::mpc::MoveBlocking;
+///
+/// // one control action, blocks of 1, 2 and 3 stages
+/// let blocking = MoveBlocking::new(1, &[1, 2, 3])?;
+/// assert_eq!((6, 3), (blocking.horizon(), blocking.reduced_dimension()));
+/// let mut u = [0.0; 6];
+/// blocking.expand(&[1.0, 2.0, 3.0], &mut u);
+/// assert_eq!([1.0, 2.0, 2.0, 3.0, 3.0, 3.0], u);
+/// let mut grad_v = [0.0; 3];
+/// blocking.reduce_gradient(&[1.0; 6], &mut grad_v);
+/// assert_eq!([1.0, 2.0, 3.0], grad_v);
+/// # Ok::<(), optimization_engine::SolverError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveBlocking {
+    control_dimension: usize,
+    blocks: Vec<usize>,
+}
+
+impl MoveBlocking {
+    /// Constructs a move-blocking pattern of control actions of dimension
+    /// `control_dimension`, where `blocks` are the numbers of stages of the
+    /// consecutive blocks (whose sum is the horizon)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `control_dimension` is zero,
+    /// if `blocks` is empty or if a block has no stages
+    pub fn new(control_dimension: usize, blocks: &[usize]) -> Result<Self, SolverError> {
+        ensure(
+            control_dimension > 0,
+            "control_dimension",
+            "must be positive",
+        )?;
+        ensure(!blocks.is_empty(), "blocks", "must not be empty")?;
+        ensure(
+            blocks.iter().all(|&block| block > 0),
+            "blocks",
+            "must have at least one stage each",
+        )?;
+        Ok(MoveBlocking {
+            control_dimension,
+            blocks: blocks.to_vec(),
+        })
+    }
+
+    /// Dimension of the control action of a stage
+    pub fn control_dimension(&self) -> usize {
+        self.control_dimension
+    }
+
+    /// Numbers of stages of the blocks
+    pub fn blocks(&self) -> &[usize] {
+        &self.blocks
+    }
+
+    /// Number of stages, $N$
+    pub fn horizon(&self) -> usize {
+        self.blocks.iter().sum()
+    }
+
+    /// Dimension of the full sequence of control actions, $N n_u$
+    pub fn full_dimension(&self) -> usize {
+        self.horizon() * self.control_dimension
+    }
+
+    /// Dimension of the reduced decision vector, $M n_u$
+    pub fn reduced_dimension(&self) -> usize {
+        self.blocks.len() * self.control_dimension
+    }
+
+    /// Computes the full sequence of control actions, $u = Ev$
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the lengths of `v` and `u` are not
+    /// `reduced_dimension()` and `full_dimension()`
+    pub fn expand(&self, v: &[f64], u: &mut [f64]) {
+        assert_eq!(self.reduced_dimension(), v.len(), "wrong length of v");
+        assert_eq!(self.full_dimension(), u.len(), "wrong length of u");
+        let mut stages = u.chunks_exact_mut(self.control_dimension);
+        for (v_i, &block) in v.chunks_exact(self.control_dimension).zip(&self.blocks) {
+            stages
+                .by_ref()
+                .take(block)
+                .for_each(|u_k| u_k.copy_from_slice(v_i));
+        }
+    }
+
+    /// Computes the gradient with respect to $v$, $E^\top \nabla_u$, from the
+    /// gradient `grad_u` with respect to $u$
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the lengths of `grad_u` and `grad_v` are not
+    /// `full_dimension()` and `reduced_dimension()`
+    pub fn reduce_gradient(&self, grad_u: &[f64], grad_v: &mut [f64]) {
+        assert_eq!(
+            self.full_dimension(),
+            grad_u.len(),
+            "wrong length of grad_u"
+        );
+        assert_eq!(
+            self.reduced_dimension(),
+            grad_v.len(),
+            "wrong length of grad_v"
+        );
+        let mut stages = grad_u.chunks_exact(self.control_dimension);
+        for (grad_v_i, &block) in grad_v
+            .chunks_exact_mut(self.control_dimension)
+            .zip(&self.blocks)
+        {
+            grad_v_i.iter_mut().for_each(|g| *g = 0.0);
+            for grad_u_k in stages.by_ref().take(block) {
+                grad_v_i
+                    .iter_mut()
+                    .zip(grad_u_k.iter())
+                    .for_each(|(g, &g_k)| *g += g_k);
+            }
+        }
+    }
+
+    /// Computes a reduced decision vector from a full sequence of control
+    /// actions, taking the control action of the first stage of every block
+    ///
+    /// ## Panics
+    ///
+    /// The method panics if the lengths of `u` and `v` are not
+    /// `full_dimension()` and `reduced_dimension()`
+    pub fn reduce(&self, u: &[f64], v: &mut [f64]) {
+        self.reduce_shifted(u, 0, v);
+    }
+
+    /// Same as `reduce`, with the stages of `u` shifted by `shift` (the last
+    /// stage is repeated)
+    fn reduce_shifted(&self, u: &[f64], shift: usize, v: &mut [f64]) {
+        assert_eq!(self.full_dimension(), u.len(), "wrong length of u");
+        assert_eq!(self.reduced_dimension(), v.len(), "wrong length of v");
+        let (nu, last_stage) = (self.control_dimension, self.horizon() - 1);
+        let mut first_stage = 0;
+        for (v_i, &block) in v.chunks_exact_mut(nu).zip(&self.blocks) {
+            let stage = (first_stage + shift).min(last_stage);
+            v_i.copy_from_slice(&u[stage * nu..(stage + 1) * nu]);
+            first_stage += block;
+        }
+    }
+
+    /// Cost of the reduced problem, $\psi(Ev; \xi)$, where `psi` is the cost
+    /// (with the signature of the cost of an `AlmProblem`) of the full
+    /// sequence of control actions
+    pub fn cost<Psi>(&self, psi: Psi) -> impl Fn(&[f64], &[f64], &mut f64) -> FunctionCallResult
+    where
+        Psi: Fn(&[f64], &[f64], &mut f64) -> FunctionCallResult,
+    {
+        let blocking = self.clone();
+        let u = RefCell::new(vec![0.0; self.full_dimension()]);
+        move |v: &[f64], xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+            let mut u = u.borrow_mut();
+            blocking.expand(v, &mut u);
+            psi(&u, xi, cost)
+        }
+    }
+
+    /// Gradient of the reduced cost, $E^\top \nabla_u \psi(Ev; \xi)$, where
+    /// `d_psi` is the gradient of the cost of the full sequence of control
+    /// actions
+    ///
+    /// Products with the transposed Jacobian of a mapping, $(u, d) \mapsto
+    /// JF(u)^\top d$, have the same signature and are wrapped in the same way
+    pub fn gradient<DPsi>(
+        &self,
+        d_psi: DPsi,
+    ) -> impl Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult
+    where
+        DPsi: Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult,
+    {
+        let blocking = self.clone();
+        let workspace = RefCell::new((
+            vec![0.0; self.full_dimension()],
+            vec![0.0; self.full_dimension()],
+        ));
+        move |v: &[f64], xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            let (u, grad_u) = &mut *workspace.borrow_mut();
+            blocking.expand(v, u);
+            d_psi(u, xi, grad_u)?;
+            blocking.reduce_gradient(grad_u, grad);
+            Ok(())
+        }
+    }
+
+    /// Mapping of the reduced problem, $F(Ev)$, where `mapping` (e.g., $F_1$
+    /// or $F_2$ of an `AlmProblem`) is defined on the full sequence of
+    /// control actions
+    pub fn mapping<F>(&self, mapping: F) -> impl Fn(&[f64], &mut [f64]) -> FunctionCallResult
+    where
+        F: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    {
+        let blocking = self.clone();
+        let u = RefCell::new(vec![0.0; self.full_dimension()]);
+        move |v: &[f64], f: &mut [f64]| -> FunctionCallResult {
+            let mut u = u.borrow_mut();
+            blocking.expand(v, &mut u);
+            mapping(&u, f)
+        }
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alm::*,
+        constraints::{Constraint, Rectangle},
+    };
+
+    /// Cost, sum (p_k - r)^2 + v_k^2 + 0.1 u_k^2, of the double integrator,
+    /// x+ = (p + v, v + u), and its gradient
+    macro_rules! double_integrator_cost {
+        ($parameters:expr) => {{
+            let (p_cost, p_grad) = ($parameters.clone(), $parameters.clone());
+            let psi = move |u: &[f64], _xi: &[f64], cost: &mut f64| -> FunctionCallResult {
+                let (state, r) = (p_cost.state(), p_cost.reference()[0]);
+                let (mut p, mut v) = (state[0], state[1]);
+                *cost = 0.0;
+                for &u_k in u {
+                    p += v;
+                    v += u_k;
+                    *cost += (p - r).powi(2) + v.powi(2) + 0.1 * u_k.powi(2);
+                }
+                Ok(())
+            };
+            let d_psi = move |u: &[f64], _xi: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+                let (state, r) = (p_grad.state(), p_grad.reference()[0]);
+                let (mut p, mut v) = (state[0], state[1]);
+                let mut errors = vec![(0.0, 0.0); u.len()];
+                for (e_k, &u_k) in errors.iter_mut().zip(u.iter()) {
+                    p += v;
+                    v += u_k;
+                    *e_k = (2.0 * (p - r), 2.0 * v);
+                }
+                // adjoint (lambda_p, lambda_v) of the state after stage k
+                let (mut lambda_p, mut lambda_v) = (0.0, 0.0);
+                for k in (0..u.len()).rev() {
+                    lambda_p += errors[k].0;
+                    lambda_v += errors[k].1;
+                    grad[k] = lambda_v + 0.2 * u[k];
+                    // v_k enters p_{k+1} and v_{k+1}
+                    lambda_v += lambda_p;
+                }
+                Ok(())
+            };
+            (psi, d_psi)
+        }};
+    }
+
+    /// Optimizer of the double integrator with horizon `horizon`
+    macro_rules! double_integrator {
+        ($parameters:expr, $horizon:expr, $umin:expr, $umax:expr) => {{
+            let (psi, d_psi) = double_integrator_cost!($parameters);
+            let bounds = Rectangle::new(Some($umin), Some($umax)).unwrap();
+            let problem = AlmProblem::new(
+                bounds, NO_SET, NO_SET, psi, d_psi, NO_MAPPING, NO_MAPPING, 0, 0,
+            )
+            .unwrap();
+            AlmOptimizer::from_dimensions(problem, $horizon, 1e-9, 5).unwrap()
+        }};
+    }
+
+    #[test]
+    fn t_mpc_double_integrator() {
+        let horizon = 15;
+        let (umin, umax) = (vec![-0.5; horizon], vec![0.5; horizon]);
+        let parameters = MpcParameters::new(2, 1);
+        let optimizer = double_integrator!(parameters, horizon, &umin, &umax);
+        let mut controller =
+            RecedingHorizonController::new(optimizer, parameters, 1, horizon).unwrap();
+        controller.set_reference(&[3.0]).unwrap();
+        let mut x = [0.0, 0.0];
+        for _ in 0..60 {
+            let u = controller.compute_control(&x).unwrap()[0];
+            assert!(u.abs() <= 0.5 + 1e-12);
+            x = [x[0] + x[1], x[1] + u];
+            assert!(
+                controller.status().unwrap().exit_status() == crate::core::ExitStatus::Converged
+            );
+        }
+        assert!((x[0] - 3.0).abs() < 1e-4 && x[1].abs() < 1e-4);
+        assert_eq!(&[3.0], &*controller.parameters().reference());
+    }
+
+    #[test]
+    fn t_mpc_warm_start_shift() {
+        let horizon = 8;
+        let (umin, umax) = (vec![-1.0; horizon], vec![1.0; horizon]);
+        let parameters = MpcParameters::new(2, 1);
+        let optimizer = double_integrator!(parameters, horizon, &umin, &umax);
+        let mut controller =
+            RecedingHorizonController::new(optimizer, parameters, 1, horizon).unwrap();
+        controller.set_reference(&[1.0]).unwrap();
+        let u0 = controller.compute_control(&[0.0, 0.0]).unwrap()[0];
+        let predicted = controller.predicted_controls().to_vec();
+        assert_eq!(predicted[0], u0);
+        // the next initial guess is the solution shifted by one stage
+        assert_eq!(&predicted[1..], &controller.warm_start[..horizon - 1]);
+        assert_eq!(predicted[horizon - 1], controller.warm_start[horizon - 1]);
+        controller.reset();
+        assert!(controller.warm_start.iter().all(|&u_i| u_i == 0.0));
+    }
+
+    #[test]
+    fn t_mpc_dimensions() {
+        let horizon = 4;
+        let (umin, umax) = (vec![-1.0; horizon], vec![1.0; horizon]);
+        let parameters = MpcParameters::new(2, 1);
+        assert_eq!(
+            (2, 1),
+            (
+                parameters.state_dimension(),
+                parameters.reference_dimension()
+            )
+        );
+        let optimizer = double_integrator!(parameters, horizon, &umin, &umax);
+        assert!(matches!(
+            RecedingHorizonController::new(optimizer, parameters.clone(), 2, horizon),
+            Err(SolverError::DimensionMismatch {
+                name: "optimizer",
+                expected: 8,
+                actual: 4,
+            })
+        ));
+        let optimizer = double_integrator!(parameters, horizon, &umin, &umax);
+        let mut controller = RecedingHorizonController::new(optimizer, parameters, 1, horizon)
+            .unwrap()
+            .with_initial_guess(&[0.1; 4])
+            .unwrap();
+        assert!(controller.compute_control(&[1.0]).is_err());
+        assert!(controller.set_reference(&[1.0, 2.0]).is_err());
+        assert!(controller.status().is_none());
+        assert!(controller.compute_control(&[1.0, 0.0]).is_ok());
+    }
+
+    #[test]
+    fn t_rate_constraints_mapping() {
+        let rates = RateConstraints::new(2, 3, &[0.5, 1.0]).unwrap();
+        let n1 = rates.dimension();
+        let (u, v) = ([1.0, 2.0, -1.0, 4.0, 0.5, 3.0], [0.3, -2.0, 1.5, 0.7]);
+        let mut f1 = vec![0.0; n1];
+        (rates.mapping())(&u, &mut f1).unwrap();
+        assert_eq!(vec![-2.0, 2.0, 1.5, -1.0], f1);
+        // <JF1' v, u> = <v, F1(u)>
+        let mut jf1t_v = vec![0.0; u.len()];
+        (rates.jacobian_mapping_trans())(&u, &v, &mut jf1t_v).unwrap();
+        let lhs: f64 = jf1t_v.iter().zip(u.iter()).map(|(a, b)| a * b).sum();
+        let rhs: f64 = v.iter().zip(f1.iter()).map(|(a, b)| a * b).sum();
+        assert!((lhs - rhs).abs() < 1e-12);
+        rates.set().project(&mut f1);
+        assert_eq!(vec![-0.5, 1.0, 0.5, -1.0], f1);
+
+        assert!(RateConstraints::new(0, 3, &[]).is_err());
+        assert!(RateConstraints::new(1, 1, &[1.0]).is_err());
+        assert!(RateConstraints::new(1, 3, &[-1.0]).is_err());
+        assert!(matches!(
+            RateConstraints::new(2, 3, &[1.0]),
+            Err(SolverError::DimensionMismatch {
+                name: "max_rate",
+                expected: 2,
+                actual: 1,
+            })
+        ));
+    }
+
+    #[test]
+    fn t_mpc_rate_constraints() {
+        // track the steps of the targets, t, with two inputs over four stages
+        let (horizon, nu) = (4, 2);
+        let target = [0.0, 1.0, 0.0, 1.0, 2.0, 1.0, 2.0, 1.0];
+        let max_rate = [0.5, 1.0];
+        let rates = RateConstraints::new(nu, horizon, &max_rate).unwrap();
+        let n1 = rates.dimension();
+        let f = |u: &[f64], cost: &mut f64| -> FunctionCallResult {
+            *cost = u
+                .iter()
+                .zip(target.iter())
+                .map(|(u_i, t_i)| (u_i - t_i).powi(2))
+                .sum();
+            Ok(())
+        };
+        let df = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad.iter_mut()
+                .zip(u.iter().zip(target.iter()))
+                .for_each(|(g_i, (u_i, t_i))| *g_i = 2.0 * (u_i - t_i));
+            Ok(())
+        };
+        let factory = AlmFactory::new(
+            f,
+            df,
+            Some(rates.mapping()),
+            Some(rates.jacobian_mapping_trans()),
+            NO_MAPPING,
+            NO_JACOBIAN_MAPPING,
+            Some(rates.set()),
+            0,
+        )
+        .unwrap();
+        let problem = AlmProblem::new(
+            constraints::NoConstraints::new(),
+            Some(rates.set()),
+            Some(Rectangle::new(Some(&[-1e4; 6]), Some(&[1e4; 6])).unwrap()),
+            |u: &[f64], xi: &[f64], cost: &mut f64| factory.psi(u, xi, cost),
+            |u: &[f64], xi: &[f64], grad: &mut [f64]| factory.d_psi(u, xi, grad),
+            Some(rates.mapping()),
+            NO_MAPPING,
+            n1,
+            0,
+        )
+        .unwrap();
+        let mut optimizer = AlmOptimizer::from_dimensions(problem, horizon * nu, 1e-6, 5)
+            .unwrap()
+            .with_delta_tolerance(1e-6)
+            .unwrap()
+            .with_epsilon_tolerance(1e-6)
+            .unwrap();
+        let mut u = vec![0.0; horizon * nu];
+        let status = optimizer.solve(&mut u).unwrap();
+        assert_eq!(crate::core::ExitStatus::Converged, status.exit_status());
+        for (u_k, u_next) in u.chunks(nu).zip(u[nu..].chunks(nu)) {
+            for ((a, b), rate) in u_next.iter().zip(u_k.iter()).zip(max_rate.iter()) {
+                assert!((a - b).abs() <= rate + 1e-5);
+            }
+        }
+        // the step of the first input is spread over the horizon, whereas the
+        // second input tracks its target
+        assert!((u[6] - u[0] - 1.5).abs() < 1e-4);
+        assert!(u
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|&u_i| (u_i - 1.0).abs() < 1e-4));
+    }
+
+    #[test]
+    fn t_move_blocking() {
+        let blocking = MoveBlocking::new(2, &[1, 3, 2]).unwrap();
+        assert_eq!(
+            (2, 6, 12, 6),
+            (
+                blocking.control_dimension(),
+                blocking.horizon(),
+                blocking.full_dimension(),
+                blocking.reduced_dimension()
+            )
+        );
+        assert_eq!(&[1, 3, 2], blocking.blocks());
+        let v = [1.0, -1.0, 2.0, -2.0, 3.0, -3.0];
+        let mut u = [0.0; 12];
+        blocking.expand(&v, &mut u);
+        assert_eq!(
+            [1.0, -1.0, 2.0, -2.0, 2.0, -2.0, 2.0, -2.0, 3.0, -3.0, 3.0, -3.0],
+            u
+        );
+        let mut v_reduced = [0.0; 6];
+        blocking.reduce(&u, &mut v_reduced);
+        assert_eq!(v, v_reduced);
+        blocking.reduce_shifted(&u, 1, &mut v_reduced);
+        assert_eq!([2.0, -2.0, 2.0, -2.0, 3.0, -3.0], v_reduced);
+        // <E' d, v> = <d, E v>
+        let d: Vec<f64> = (0..12).map(|i| (i as f64).sin()).collect();
+        let mut e_trans_d = [0.0; 6];
+        blocking.reduce_gradient(&d, &mut e_trans_d);
+        let lhs: f64 = e_trans_d.iter().zip(v.iter()).map(|(a, b)| a * b).sum();
+        let rhs: f64 = d.iter().zip(u.iter()).map(|(a, b)| a * b).sum();
+        assert!((lhs - rhs).abs() < 1e-12);
+
+        assert!(MoveBlocking::new(0, &[1]).is_err());
+        assert!(MoveBlocking::new(1, &[]).is_err());
+        assert!(MoveBlocking::new(1, &[2, 0]).is_err());
+    }
+
+    #[test]
+    fn t_move_blocking_cost_and_gradient() {
+        // cost sum_k k u_k^2 and mapping F(u) = sum_k u_k
+        let blocking = MoveBlocking::new(1, &[2, 3]).unwrap();
+        let cost = blocking.cost(
+            |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+                *c = u
+                    .iter()
+                    .enumerate()
+                    .map(|(k, u_k)| k as f64 * u_k * u_k)
+                    .sum();
+                Ok(())
+            },
+        );
+        let gradient = blocking.gradient(
+            |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+                g.iter_mut()
+                    .zip(u.iter().enumerate())
+                    .for_each(|(g_k, (k, u_k))| *g_k = 2.0 * k as f64 * u_k);
+                Ok(())
+            },
+        );
+        let mapping = blocking.mapping(|u: &[f64], f: &mut [f64]| -> FunctionCallResult {
+            f[0] = u.iter().sum();
+            Ok(())
+        });
+        let v = [1.0, 2.0];
+        let (mut c, mut g, mut f) = (0.0, [0.0; 2], [0.0]);
+        cost(&v, &[], &mut c).unwrap();
+        gradient(&v, &[], &mut g).unwrap();
+        mapping(&v, &mut f).unwrap();
+        assert_eq!(1.0 + 4.0 * (2.0 + 3.0 + 4.0), c);
+        assert_eq!([2.0, 4.0 * (2.0 + 3.0 + 4.0)], g);
+        assert_eq!([8.0], f);
+    }
+
+    #[test]
+    fn t_mpc_move_blocking() {
+        let horizon = 15;
+        let blocking = MoveBlocking::new(1, &[1, 2, 4, 8]).unwrap();
+        let parameters = MpcParameters::new(2, 1);
+        let (psi, d_psi) = double_integrator_cost!(parameters);
+        let (umin, umax) = (vec![-0.5; 4], vec![0.5; 4]);
+        let problem = AlmProblem::new(
+            Rectangle::new(Some(&umin), Some(&umax)).unwrap(),
+            NO_SET,
+            NO_SET,
+            blocking.cost(psi),
+            blocking.gradient(d_psi),
+            NO_MAPPING,
+            NO_MAPPING,
+            0,
+            0,
+        )
+        .unwrap();
+        let optimizer = AlmOptimizer::from_dimensions(problem, 4, 1e-9, 5).unwrap();
+        let mut controller =
+            RecedingHorizonController::from_move_blocking(optimizer, parameters, blocking).unwrap();
+        controller.set_reference(&[2.0]).unwrap();
+        let mut x = [0.0, 0.0];
+        for _ in 0..60 {
+            let u = controller.compute_control(&x).unwrap()[0];
+            assert!(u.abs() <= 0.5 + 1e-12);
+            assert_eq!(horizon, controller.predicted_controls().len());
+            x = [x[0] + x[1], x[1] + u];
+        }
+        assert!((x[0] - 2.0).abs() < 1e-3 && x[1].abs() < 1e-3);
+        // the predicted controls are constant over the blocks
+        let predicted = controller.predicted_controls();
+        assert!(predicted[3..7].iter().all(|&u_k| u_k == predicted[3]));
+    }
+}
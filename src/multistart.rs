@@ -0,0 +1,485 @@
+//! Multi-start global optimization
+//!
+//! Nonconvex problems may have many local minima, and local solvers, such
+//! as PANOC and ALM, converge to one of them, depending on the initial
+//! guess. [`MultiStart`] runs a solver from many initial points, which are
+//! given by the user or sampled uniformly at random in a box and projected
+//! on the constraints, optionally on several threads, merges the solutions
+//! which are (almost) the same and sorts them by cost.
+//!
+//! The solver is a closure which solves the problem from a given initial
+//! point (in place) and returns a `SolverStatus`, so any optimizer of OpEn
+//! can be used; if the solves run in parallel, every thread needs its own
+//! cache, e.g., from a [`CachePool`].
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     constraints::Rectangle, core::CachePool, multistart::MultiStart, panoc::*, *,
+//! };
+//!
+//! // f(u) = (u^2 - 1)^2 + 0.3 u has two local minima
+//! let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = (u[0] * u[0] - 1.0).powi(2) + 0.3 * u[0];
+//!     Ok(())
+//! };
+//! let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     grad[0] = 4.0 * u[0] * (u[0] * u[0] - 1.0) + 0.3;
+//!     Ok(())
+//! };
+//! let bounds = Rectangle::new(Some(&[-2.0]), Some(&[2.0]))?;
+//! let pool = CachePool::for_panoc(1, 1e-8, 3)?;
+//! let result = MultiStart::new(1)
+//!     .with_random_starts(20, &[-2.0], &[2.0], &bounds)?
+//!     .with_num_threads(4)?
+//!     .solve(|u: &mut [f64]| {
+//!         let mut cache = pool.acquire()?;
+//!         PANOCOptimizer::new(Problem::new(&bounds, gradient, cost), &mut cache).solve(u)
+//!     });
+//! assert_eq!(2, result.solutions().len());
+//! let best = result.best().unwrap();
+//! assert!((best.solution()[0] + 1.0356).abs() < 1e-4);
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! [`MultiStart`]: struct.MultiStart.html
+//! [`CachePool`]: ../core/struct.CachePool.html
+
+use crate::{
+    constraints::Constraint, core::SolverStatus, ensure, matrix_operations, OpEnFloat, SolverError,
+};
+use std::{cmp::Ordering, thread};
+
+/// Default tolerance for the merging of solutions
+const DEFAULT_MERGE_TOLERANCE: f64 = 1e-6;
+
+/// Multi-start driver
+///
+/// The initial points are added with `with_start` and `with_random_starts`;
+/// `solve` runs the solver from every initial point and returns a
+/// `MultiStartResult`.
+#[derive(Debug, Clone)]
+pub struct MultiStart<T = f64> {
+    /// number of decision variables
+    n: usize,
+    /// initial points
+    starts: Vec<Vec<T>>,
+    /// seed of the random number generator
+    seed: u64,
+    /// number of threads
+    num_threads: usize,
+    /// solutions whose (infinity-norm) distance is at most this tolerance
+    /// are merged
+    merge_tolerance: T,
+}
+
+/// A local solution found by `MultiStart`
+#[derive(Debug, Clone)]
+pub struct MultiStartSolution<T = f64> {
+    solution: Vec<T>,
+    status: SolverStatus,
+    start: usize,
+    count: usize,
+}
+
+impl<T> MultiStartSolution<T> {
+    /// The solution
+    pub fn solution(&self) -> &[T] {
+        &self.solution
+    }
+
+    /// Status of the solve which found the solution (the best of the merged
+    /// solves)
+    pub fn status(&self) -> &SolverStatus {
+        &self.status
+    }
+
+    /// Index of the initial point of the solve which found the solution
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Number of solves which converged to this solution
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Results of `MultiStart::solve`
+#[derive(Debug)]
+pub struct MultiStartResult<T = f64> {
+    solutions: Vec<MultiStartSolution<T>>,
+    failures: Vec<(usize, SolverError)>,
+}
+
+impl<T> MultiStartResult<T> {
+    /// The distinct solutions; solutions of converged solves come first and
+    /// are sorted by cost (from the lowest)
+    pub fn solutions(&self) -> &[MultiStartSolution<T>] {
+        &self.solutions
+    }
+
+    /// The best solution, or `None` if all solves failed
+    pub fn best(&self) -> Option<&MultiStartSolution<T>> {
+        self.solutions.first()
+    }
+
+    /// Indices of the initial points of the solves which failed, and their
+    /// errors
+    pub fn failures(&self) -> &[(usize, SolverError)] {
+        &self.failures
+    }
+}
+
+/// Generator of pseudo-random numbers (SplitMix64)
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    /// Returns a pseudo-random number, uniformly distributed in $[0, 1)$
+    pub(crate) fn next_uniform(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+/// Orders statuses with converged solves first, then by cost (NaN last)
+fn compare_statuses(a: &SolverStatus, b: &SolverStatus) -> Ordering {
+    let key = |status: &SolverStatus| {
+        let cost = status.cost_value();
+        (
+            !status.has_converged(),
+            if cost.is_nan() { f64::INFINITY } else { cost },
+        )
+    };
+    let (a, b) = (key(a), key(b));
+    a.0.cmp(&b.0).then(a.1.total_cmp(&b.1))
+}
+
+impl<T: OpEnFloat> MultiStart<T> {
+    /// Constructs a new multi-start driver (without initial points), which
+    /// solves the problems sequentially
+    ///
+    /// ## Arguments
+    ///
+    /// - `n`: number of decision variables
+    ///
+    pub fn new(n: usize) -> Self {
+        MultiStart {
+            n,
+            starts: Vec::new(),
+            seed: 0,
+            num_threads: 1,
+            merge_tolerance: T::from_f64(DEFAULT_MERGE_TOLERANCE),
+        }
+    }
+
+    /// Adds an initial point
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `start` does not have `n`
+    /// elements
+    ///
+    pub fn with_start(mut self, start: &[T]) -> Result<Self, SolverError> {
+        if start.len() != self.n {
+            return Err(SolverError::DimensionMismatch {
+                name: "start",
+                expected: self.n,
+                actual: start.len(),
+            });
+        }
+        self.starts.push(start.to_vec());
+        Ok(self)
+    }
+
+    /// Sets the seed of the random number generator of `with_random_starts`
+    /// (the default is 0); this affects the subsequent calls of
+    /// `with_random_starts`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Adds `num_starts` initial points, which are sampled uniformly at
+    /// random in the box $[\mathrm{lower}, \mathrm{upper}]$ and projected on
+    /// `constraints`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `lower` or `upper` do not
+    /// have `n` elements, or `SolverError::InvalidParameter` if the bounds
+    /// are not finite or `lower` is greater than `upper`
+    ///
+    pub fn with_random_starts<C: Constraint<T>>(
+        mut self,
+        num_starts: usize,
+        lower: &[T],
+        upper: &[T],
+        constraints: &C,
+    ) -> Result<Self, SolverError> {
+        for (name, bound) in [("lower", lower), ("upper", upper)] {
+            if bound.len() != self.n {
+                return Err(SolverError::DimensionMismatch {
+                    name,
+                    expected: self.n,
+                    actual: bound.len(),
+                });
+            }
+        }
+        ensure(
+            matrix_operations::is_finite(lower) && matrix_operations::is_finite(upper),
+            "bounds",
+            "must be finite",
+        )?;
+        ensure(
+            lower.iter().zip(upper.iter()).all(|(l, u)| l <= u),
+            "bounds",
+            "lower bounds must not exceed the upper bounds",
+        )?;
+        let mut generator = SplitMix64(self.seed);
+        for _ in 0..num_starts {
+            let mut start: Vec<T> = lower
+                .iter()
+                .zip(upper.iter())
+                .map(|(&l, &u)| l + (u - l) * T::from_f64(generator.next_uniform()))
+                .collect();
+            constraints.project(&mut start);
+            self.starts.push(start);
+        }
+        self.seed = generator.0;
+        Ok(self)
+    }
+
+    /// Sets the number of threads on which the solves run (the default is
+    /// 1, that is, the solves run sequentially on the calling thread)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `num_threads` is zero
+    ///
+    pub fn with_num_threads(mut self, num_threads: usize) -> Result<Self, SolverError> {
+        ensure(num_threads > 0, "num_threads", "must be positive")?;
+        self.num_threads = num_threads;
+        Ok(self)
+    }
+
+    /// Sets the tolerance for the merging of solutions: solutions whose
+    /// distance (in the infinity norm) is at most `merge_tolerance` are
+    /// merged (the default is `1e-6`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `merge_tolerance` is
+    /// negative
+    ///
+    pub fn with_merge_tolerance(mut self, merge_tolerance: T) -> Result<Self, SolverError> {
+        ensure(
+            merge_tolerance >= T::zero(),
+            "merge_tolerance",
+            "must be nonnegative",
+        )?;
+        self.merge_tolerance = merge_tolerance;
+        Ok(self)
+    }
+
+    /// The initial points
+    pub fn starts(&self) -> &[Vec<T>] {
+        &self.starts
+    }
+
+    /// Runs `solver` from every initial point
+    ///
+    /// The closure `solver` solves the problem from the initial point `u`
+    /// and overwrites `u` with the solution. The results do not depend on
+    /// the number of threads.
+    pub fn solve<F>(&self, solver: F) -> MultiStartResult<T>
+    where
+        T: Send,
+        F: Fn(&mut [T]) -> Result<SolverStatus, SolverError> + Sync,
+    {
+        let solve_from = |start: &Vec<T>| {
+            let mut u = start.clone();
+            solver(&mut u).map(|status| (u, status))
+        };
+        let outcomes: Vec<_> = if self.num_threads == 1 || self.starts.len() <= 1 {
+            self.starts.iter().map(solve_from).collect()
+        } else {
+            let chunk_size = self.starts.len().div_ceil(self.num_threads);
+            thread::scope(|scope| {
+                let workers: Vec<_> = self
+                    .starts
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || chunk.iter().map(solve_from).collect::<Vec<_>>())
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| match worker.join() {
+                        Ok(outcomes) => outcomes,
+                        Err(panic) => std::panic::resume_unwind(panic),
+                    })
+                    .collect()
+            })
+        };
+
+        let mut candidates = Vec::new();
+        let mut failures = Vec::new();
+        for (start, outcome) in outcomes.into_iter().enumerate() {
+            match outcome {
+                Ok((solution, status)) => candidates.push(MultiStartSolution {
+                    solution,
+                    status,
+                    start,
+                    count: 1,
+                }),
+                Err(error) => failures.push((start, error)),
+            }
+        }
+        // merge the candidates, from the best, into the distinct solutions
+        candidates.sort_by(|a, b| compare_statuses(&a.status, &b.status));
+        let mut solutions: Vec<MultiStartSolution<T>> = Vec::new();
+        for candidate in candidates {
+            match solutions.iter_mut().find(|solution| {
+                matrix_operations::norm_inf_diff(&solution.solution, &candidate.solution)
+                    <= self.merge_tolerance
+            }) {
+                Some(solution) => solution.count += 1,
+                None => solutions.push(candidate),
+            }
+        }
+        MultiStartResult {
+            solutions,
+            failures,
+        }
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constraints::{Ball2, NoConstraints},
+        core::{panoc::PANOCOptimizer, ExitStatus},
+        FunctionCallResult, Optimizer, Problem,
+    };
+    use std::time::Duration;
+
+    fn cost(u: &[f64], c: &mut f64) -> FunctionCallResult {
+        *c = (u[0] * u[0] - 1.0).powi(2) + 0.3 * u[0];
+        Ok(())
+    }
+
+    fn gradient(u: &[f64], grad: &mut [f64]) -> FunctionCallResult {
+        grad[0] = 4.0 * u[0] * (u[0] * u[0] - 1.0) + 0.3;
+        Ok(())
+    }
+
+    fn solve_double_well(u: &mut [f64]) -> Result<SolverStatus, SolverError> {
+        let bounds = NoConstraints::new();
+        PANOCOptimizer::from_dimensions(Problem::new(&bounds, gradient, cost), 1, 1e-10, 3)?
+            .solve(u)
+    }
+
+    #[test]
+    fn t_multistart_double_well() {
+        let mut multistart = MultiStart::new(1);
+        for start in [-2.0, -0.5, 0.5, 2.0, 1.9] {
+            multistart = multistart.with_start(&[start]).unwrap();
+        }
+        let result = multistart.solve(solve_double_well);
+        assert!(result.failures().is_empty());
+        let solutions = result.solutions();
+        assert_eq!(2, solutions.len());
+        assert!((solutions[0].solution()[0] + 1.035_579).abs() < 1e-5);
+        assert!((solutions[1].solution()[0] - 0.960_150).abs() < 1e-5);
+        assert_eq!((2, 3), (solutions[0].count(), solutions[1].count()));
+        assert!(solutions[0].start() <= 1);
+        assert!(solutions[0].status().cost_value() < solutions[1].status().cost_value());
+
+        // the same results on threads
+        let result_threads = multistart
+            .with_num_threads(3)
+            .unwrap()
+            .solve(solve_double_well);
+        for (a, b) in result_threads.solutions().iter().zip(solutions.iter()) {
+            assert_eq!(a.solution(), b.solution());
+            assert_eq!((a.start(), a.count()), (b.start(), b.count()));
+        }
+    }
+
+    #[test]
+    fn t_multistart_random_starts() {
+        let ball = Ball2::new(None, 1.0).unwrap();
+        let sample = |seed| {
+            MultiStart::new(2)
+                .with_seed(seed)
+                .with_random_starts(50, &[-3.0, -3.0], &[3.0, 3.0], &ball)
+                .unwrap()
+        };
+        let multistart = sample(7);
+        assert_eq!(50, multistart.starts().len());
+        assert!(multistart
+            .starts()
+            .iter()
+            .all(|start| matrix_operations::norm2(start) <= 1.0 + 1e-12));
+        assert_eq!(multistart.starts(), sample(7).starts());
+        assert_ne!(multistart.starts(), sample(8).starts());
+
+        assert!(matches!(
+            MultiStart::new(2).with_random_starts(5, &[0.0], &[1.0, 1.0], &ball),
+            Err(SolverError::DimensionMismatch {
+                name: "lower",
+                expected: 2,
+                actual: 1,
+            })
+        ));
+        assert!(MultiStart::new(1)
+            .with_random_starts(5, &[1.0], &[0.0], &ball)
+            .is_err());
+        assert!(MultiStart::new(1)
+            .with_random_starts(5, &[0.0], &[f64::INFINITY], &ball)
+            .is_err());
+        assert!(MultiStart::<f64>::new(1).with_num_threads(0).is_err());
+        assert!(MultiStart::new(1).with_start(&[0.0, 1.0]).is_err());
+    }
+
+    #[test]
+    fn t_multistart_failures() {
+        let multistart = MultiStart::new(1)
+            .with_start(&[-2.0])
+            .unwrap()
+            .with_start(&[2.0])
+            .unwrap()
+            .with_start(&[5.0])
+            .unwrap();
+        let result = multistart.solve(|u: &mut [f64]| {
+            if u[0] > 3.0 {
+                return Err(SolverError::Cost);
+            }
+            // a solve which does not converge is ranked after the converged ones
+            let exit_status = if u[0] < 0.0 {
+                ExitStatus::NotConvergedIterations
+            } else {
+                ExitStatus::Converged
+            };
+            Ok(SolverStatus::new(exit_status, 1, Duration::ZERO, 0.0, u[0]))
+        });
+        assert_eq!(1, result.failures().len());
+        assert_eq!(2, result.failures()[0].0);
+        assert_eq!(2.0, result.best().unwrap().solution()[0]);
+        assert_eq!(2, result.solutions().len());
+
+        let result = multistart.solve(|_u: &mut [f64]| Err(SolverError::Cost));
+        assert!(result.best().is_none());
+        assert_eq!(3, result.failures().len());
+    }
+}
@@ -0,0 +1,399 @@
+//! Export of arrays to NumPy `.npy` and `.npz` files
+//!
+//! This module (which requires the feature `npy`) writes arrays of `f64`
+//! in the format of NumPy, so that solutions, recorded iterates and traces
+//! of solver runs can be loaded with one line, e.g., `np.load("u.npy")`,
+//! without parsing CSV files. A `.npy` file holds one array; a `.npz` file
+//! (written with an [`NpzWriter`]) is an (uncompressed) ZIP archive of named
+//! arrays, which `np.load` returns as a dictionary. Traces are written with
+//! `SolverTrace::write_npz`.
+//!
+//! Arrays are given by their shape and their elements in row-major (C)
+//! order, so iterates which are collected during a solve (e.g., by a
+//! progress callback) are written as a matrix with one row per iteration:
+//!
+//! ```no_run
+//! use optimization_engine::npy;
+//!
+//! let iterates = vec![vec![1.0, 2.0], vec![0.5, 1.5], vec![0.25, 1.0]];
+//! npy::write_npy("iterates.npy", &[iterates.len(), 2], &iterates.concat())?;
+//!
+//! let mut npz = npy::NpzWriter::create("run.npz")?;
+//! npz.add_array("solution", &[2], &[0.25, 1.0])?;
+//! npz.add_array("iterates", &[3, 2], &iterates.concat())?;
+//! npz.finish()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ```python
+//! run = np.load("run.npz")
+//! plt.plot(run["iterates"][:, 0])
+//! ```
+//!
+//! [`NpzWriter`]: struct.NpzWriter.html
+//!
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Magic string and version (1.0) of the `.npy` format
+const NPY_MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+
+/// Alignment of the data of a `.npy` file (the length of the preamble and the
+/// header is a multiple of it)
+const NPY_ALIGNMENT: usize = 64;
+
+/// Date of the entries of a `.npz` archive (1 January 1980, in MS-DOS format)
+const ZIP_DATE: u16 = 0x21;
+
+/// Table of the CRC-32 (of ZIP archives)
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Element type of an array (`f64` or, for indices, `i64`)
+pub(crate) trait Element: Copy {
+    /// NumPy type descriptor
+    const DESCR: &'static str;
+
+    fn to_le_bytes(self) -> [u8; 8];
+}
+
+impl Element for f64 {
+    const DESCR: &'static str = "<f8";
+
+    fn to_le_bytes(self) -> [u8; 8] {
+        f64::to_le_bytes(self)
+    }
+}
+
+impl Element for i64 {
+    const DESCR: &'static str = "<i8";
+
+    fn to_le_bytes(self) -> [u8; 8] {
+        i64::to_le_bytes(self)
+    }
+}
+
+/// Writes an array with the given shape in `.npy` format
+pub(crate) fn write_array<W: Write, T: Element>(
+    mut writer: W,
+    shape: &[usize],
+    data: &[T],
+) -> io::Result<()> {
+    if shape.iter().product::<usize>() != data.len() {
+        return Err(invalid_input(
+            "the shape does not match the number of elements",
+        ));
+    }
+    let shape = match shape {
+        [n] => format!("({},)", n),
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        T::DESCR,
+        shape
+    );
+    // the header is padded with spaces and ends with a newline, so that the
+    // data are aligned
+    let preamble = NPY_MAGIC.len() + 2;
+    let padded = (preamble + header.len() + 1).div_ceil(NPY_ALIGNMENT) * NPY_ALIGNMENT;
+    header.extend(std::iter::repeat_n(
+        ' ',
+        padded - preamble - header.len() - 1,
+    ));
+    header.push('\n');
+    let header_len =
+        u16::try_from(header.len()).map_err(|_| invalid_input("too many dimensions"))?;
+    writer.write_all(NPY_MAGIC)?;
+    writer.write_all(&header_len.to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for &x in data {
+        writer.write_all(&x.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes an array of `f64` in `.npy` format
+///
+/// ## Arguments
+///
+/// - `writer`: output
+/// - `shape`: shape of the array, e.g., `[n]` for a vector or `[rows, columns]`
+///   for a matrix (`[]` for a scalar)
+/// - `data`: elements in row-major (C) order
+///
+/// ## Errors
+///
+/// Returns an error of kind `InvalidInput` if the number of elements does not
+/// match the shape, and the errors of `writer`
+///
+pub fn write_npy_to<W: Write>(mut writer: W, shape: &[usize], data: &[f64]) -> io::Result<()> {
+    write_array(&mut writer, shape, data)?;
+    writer.flush()
+}
+
+/// Writes an array of `f64` to a `.npy` file (see `write_npy_to`)
+///
+/// ## Errors
+///
+/// Returns an error if the shape is invalid or if the file cannot be created
+/// or written
+///
+pub fn write_npy<P: AsRef<Path>>(path: P, shape: &[usize], data: &[f64]) -> io::Result<()> {
+    write_npy_to(BufWriter::new(File::create(path)?), shape, data)
+}
+
+/// Entry of the central directory of a `.npz` archive
+struct ZipEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writer of `.npz` files (uncompressed ZIP archives of `.npy` arrays)
+///
+/// Arrays are added with `add_array` (under a name, without the extension
+/// `.npy`) and the archive is completed with `finish`; an archive which is
+/// not finished is invalid. Archives (and their arrays) are limited to
+/// 4 GiB. Mind that every array is held in memory while it is added.
+pub struct NpzWriter<W: Write> {
+    writer: W,
+    entries: Vec<ZipEntry>,
+    offset: u32,
+}
+
+impl NpzWriter<BufWriter<File>> {
+    /// Creates a `.npz` file
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the file cannot be created
+    ///
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(NpzWriter::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> NpzWriter<W> {
+    /// Constructs a new writer of an archive to `writer`
+    pub fn new(writer: W) -> Self {
+        NpzWriter {
+            writer,
+            entries: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Adds an array of `f64` with the given name, shape and elements (in
+    /// row-major order; see `write_npy_to`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if the name is empty or used
+    /// twice, if the number of elements does not match the shape or if the
+    /// archive exceeds 4 GiB, and the errors of the writer
+    ///
+    pub fn add_array(&mut self, name: &str, shape: &[usize], data: &[f64]) -> io::Result<()> {
+        self.add(name, shape, data)
+    }
+
+    pub(crate) fn add<T: Element>(
+        &mut self,
+        name: &str,
+        shape: &[usize],
+        data: &[T],
+    ) -> io::Result<()> {
+        let name = format!("{}.npy", name);
+        if name == ".npy" || self.entries.iter().any(|entry| entry.name == name) {
+            return Err(invalid_input("the name is empty or used twice"));
+        }
+        let mut array = Vec::new();
+        write_array(&mut array, shape, data)?;
+        let too_large = || invalid_input("the archive is too large");
+        let size = u32::try_from(array.len()).map_err(|_| too_large())?;
+        let name_len =
+            u16::try_from(name.len()).map_err(|_| invalid_input("the name is too long"))?;
+        let entry = ZipEntry {
+            crc: crc32(&array),
+            size,
+            offset: self.offset,
+            name,
+        };
+        let header = local_file_header(&entry, name_len);
+        self.offset = u32::try_from(header.len() + array.len())
+            .ok()
+            .and_then(|len| self.offset.checked_add(len))
+            .ok_or_else(too_large)?;
+        self.writer.write_all(&header)?;
+        self.writer.write_all(&array)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Writes the central directory of the archive and returns the
+    /// underlying writer
+    ///
+    /// ## Errors
+    ///
+    /// Returns the errors of the writer
+    ///
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+            // version made by and version needed to extract (2.0)
+            directory.extend_from_slice(&20_u16.to_le_bytes());
+            directory.extend_from_slice(&20_u16.to_le_bytes());
+            directory.extend_from_slice(&entry_fields(entry, entry.name.len() as u16));
+            // comment length, disk number, internal and external attributes
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+        let num_entries = u16::try_from(self.entries.len())
+            .map_err(|_| invalid_input("too many arrays in the archive"))?;
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+        // number of this disk and of the disk with the central directory
+        end.extend_from_slice(&[0; 4]);
+        end.extend_from_slice(&num_entries.to_le_bytes());
+        end.extend_from_slice(&num_entries.to_le_bytes());
+        end.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        end.extend_from_slice(&self.offset.to_le_bytes());
+        // comment length
+        end.extend_from_slice(&[0; 2]);
+        self.writer.write_all(&directory)?;
+        self.writer.write_all(&end)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Fields of an entry which the local file header and the central directory
+/// have in common: flags, compression (none), time, date, CRC-32, sizes and
+/// lengths of the name and of the extra field
+fn entry_fields(entry: &ZipEntry, name_len: u16) -> Vec<u8> {
+    let mut fields = Vec::with_capacity(26);
+    fields.extend_from_slice(&[0; 6]);
+    fields.extend_from_slice(&ZIP_DATE.to_le_bytes());
+    fields.extend_from_slice(&entry.crc.to_le_bytes());
+    fields.extend_from_slice(&entry.size.to_le_bytes());
+    fields.extend_from_slice(&entry.size.to_le_bytes());
+    fields.extend_from_slice(&name_len.to_le_bytes());
+    fields.extend_from_slice(&[0; 2]);
+    fields
+}
+
+fn local_file_header(entry: &ZipEntry, name_len: u16) -> Vec<u8> {
+    let mut header = Vec::with_capacity(30 + entry.name.len());
+    header.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+    // version needed to extract (2.0)
+    header.extend_from_slice(&20_u16.to_le_bytes());
+    header.extend_from_slice(&entry_fields(entry, name_len));
+    header.extend_from_slice(entry.name.as_bytes());
+    header
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn t_npy_format() {
+        let mut npy = Vec::new();
+        write_npy_to(&mut npy, &[2, 3], &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        assert_eq!(b"\x93NUMPY\x01\x00\x76\x00", &npy[..10]);
+        let header = std::str::from_utf8(&npy[10..128]).unwrap();
+        assert!(header.starts_with("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with(" \n"));
+        assert_eq!(128 + 6 * 8, npy.len());
+        assert_eq!(&1.0_f64.to_le_bytes(), &npy[128..136]);
+        assert_eq!(&6.0_f64.to_le_bytes(), &npy[168..]);
+
+        let mut npy = Vec::new();
+        write_array(&mut npy, &[3], &[0_i64, 1, 2]).unwrap();
+        let header = std::str::from_utf8(&npy[10..128]).unwrap();
+        assert!(header.starts_with("{'descr': '<i8', 'fortran_order': False, 'shape': (3,), }"));
+        let mut npy = Vec::new();
+        write_npy_to(&mut npy, &[], &[2.5]).unwrap();
+        assert!(std::str::from_utf8(&npy[10..128])
+            .unwrap()
+            .contains("'shape': (), }"));
+        assert_eq!(128 + 8, npy.len());
+        assert!(write_npy_to(&mut Vec::new(), &[2, 2], &[1.0]).is_err());
+    }
+
+    #[test]
+    fn t_npz_archive() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        let mut npz = NpzWriter::new(Vec::new());
+        npz.add_array("u", &[2], &[1.0, -1.0]).unwrap();
+        npz.add_array("x", &[1, 1], &[0.5]).unwrap();
+        assert!(npz.add_array("u", &[1], &[0.0]).is_err());
+        assert!(npz.add_array("", &[1], &[0.0]).is_err());
+        let archive = npz.finish().unwrap();
+
+        // local file header of the first array, followed by the array
+        assert_eq!(&0x0403_4b50_u32.to_le_bytes(), &archive[..4]);
+        assert_eq!(b"u.npy", &archive[30..35]);
+        assert_eq!(b"\x93NUMPY", &archive[35..41]);
+        let crc = u32::from_le_bytes(archive[14..18].try_into().unwrap());
+        let size = u32::from_le_bytes(archive[18..22].try_into().unwrap()) as usize;
+        assert_eq!(128 + 16, size);
+        assert_eq!(crc32(&archive[35..35 + size]), crc);
+        // end of the central directory: two entries
+        let end = &archive[archive.len() - 22..];
+        assert_eq!(&0x0605_4b50_u32.to_le_bytes(), &end[..4]);
+        assert_eq!(2, u16::from_le_bytes([end[10], end[11]]));
+        let directory_offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(
+            &0x0201_4b50_u32.to_le_bytes(),
+            &archive[directory_offset..][..4]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Scalar types supported by Optimization Engine
+//!
+//! The numerical routines of OpEn (caches, constraints, matrix operations
+//! and solvers) are generic over a scalar type, which needs to implement
+//! the trait [`OpEnFloat`]. This trait is implemented for `f64` (double
+//! precision; this is the default scalar type everywhere) and `f32` (single
+//! precision, which is typically faster on microcontrollers without a
+//! double-precision floating-point unit). It is also implemented for the
+//! dual numbers of forward-mode automatic differentiation (see
+//! [`autodiff`](../autodiff/index.html)), so that cost functions which are
+//! generic over `OpEnFloat` can be differentiated automatically.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::OpEnFloat;
+//!
+//! fn half<T: OpEnFloat>(x: T) -> T {
+//!     T::from_f64(0.5) * x
+//! }
+//!
+//! assert_eq!(1.0_f32, half(2.0_f32));
+//! assert_eq!(1.0_f64, half(2.0_f64));
+//! ```
+//!
+//! [`OpEnFloat`]: trait.OpEnFloat.html
+
+use num::Float;
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+/// Floating-point scalar type used by Optimization Engine
+///
+/// This trait is implemented for `f32` and `f64`
+pub trait OpEnFloat:
+    Float
+    + Sum<Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Default
+    + Debug
+    + Display
+    + Send
+    + Sync
+    + 'static
+{
+    /// Converts an `f64` into this type (possibly with a loss of precision)
+    fn from_f64(x: f64) -> Self;
+
+    /// Converts a value of this type into an `f64`
+    fn as_f64(self) -> f64;
+}
+
+impl OpEnFloat for f64 {
+    #[inline(always)]
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    #[inline(always)]
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
+
+impl OpEnFloat for f32 {
+    #[inline(always)]
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    #[inline(always)]
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
@@ -0,0 +1,536 @@
+//! Construction of optimizers from a choice of algorithm
+//!
+//! [`OptimizerBuilder`] takes a `Problem`, an [`Algorithm`] and the options
+//! which all algorithms have in common (tolerance, maximum number of
+//! iterations and maximum duration) and returns a boxed `Optimizer`, which
+//! owns its cache. This way, applications can switch between solvers by
+//! changing a value, e.g., one which is read from a configuration file (with
+//! the feature `serde`, `Algorithm` can be deserialized), rather than their
+//! code.
+//!
+//! The ALM-type constraints ($F_1(u) \in C$) and the PM-type constraints
+//! ($F_2(u) = 0$) are given with `with_alm_constraints` and
+//! `with_pm_constraints` and require `Algorithm::Alm`; the builder
+//! constructs the augmented cost function (as `AlmFactory` does).
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{constraints::Ball2, optimizer_builder::*, *};
+//!
+//! let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+//!     grad[0] = u[0] - 1.0;
+//!     grad[1] = u[1] + 2.0;
+//!     Ok(())
+//! };
+//! let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+//!     *c = 0.5 * ((u[0] - 1.0).powi(2) + (u[1] + 2.0).powi(2));
+//!     Ok(())
+//! };
+//! let bounds = Ball2::new(None, 1.0)?;
+//! for algorithm in [Algorithm::panoc(), Algorithm::fbs(0.5), Algorithm::alm()] {
+//!     let mut optimizer = OptimizerBuilder::new(Problem::new(&bounds, gradient, cost), 2)
+//!         .with_algorithm(algorithm)
+//!         .with_tolerance(1e-8)?
+//!         .with_max_iter(1000)?
+//!         .build()?;
+//!     let mut u = [0.0; 2];
+//!     assert!(optimizer.solve(&mut u)?.has_converged());
+//! }
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+//! [`OptimizerBuilder`]: struct.OptimizerBuilder.html
+//! [`Algorithm`]: enum.Algorithm.html
+
+use crate::{
+    alm::{AlmConfig, AlmFactory, AlmOptimizer, AlmProblem},
+    constraints::{BoxedConstraint, Constraint},
+    core::{
+        fbs::{FBSCache, FBSOptimizer},
+        panoc::{PANOCCache, PANOCOptimizer},
+        Optimizer, Problem,
+    },
+    ensure, FunctionCallResult, SolverError,
+};
+use std::{num::NonZeroUsize, rc::Rc, time};
+
+/// Default tolerance of the optimizers of `OptimizerBuilder`
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// Default L-BFGS memory of PANOC (and of the inner solver of ALM)
+const DEFAULT_LBFGS_MEMORY: usize = 10;
+
+/// Boxed mapping which is shared by the augmented cost and the problem
+type SharedMapping<'a> = Rc<dyn Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'a>;
+
+/// Boxed clone of a shared mapping (which is `Fn`, as `AlmFactory` requires)
+type ClonedMapping<'a> = Box<dyn Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'a>;
+
+/// Boxed product of the transposed Jacobian of a mapping with a vector
+type BoxedJacobianProduct<'a> = Box<dyn Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult + 'a>;
+
+/// Algorithm of the optimizer which `OptimizerBuilder` constructs
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Algorithm {
+    /// PANOC (see `PANOCOptimizer`)
+    Panoc {
+        /// memory of the L-BFGS buffer
+        lbfgs_memory: usize,
+    },
+    /// Forward-backward splitting (projected gradient) with a constant step
+    /// size (see `FBSOptimizer`)
+    Fbs {
+        /// step size (which should be smaller than the inverse of the
+        /// Lipschitz constant of the gradient)
+        gamma: f64,
+    },
+    /// Augmented Lagrangian and penalty methods, with PANOC as the inner
+    /// solver (see `AlmOptimizer`)
+    Alm {
+        /// memory of the L-BFGS buffer of the inner solver
+        lbfgs_memory: usize,
+        /// configuration of the outer iterations (the common options of the
+        /// builder, if set, take precedence)
+        config: AlmConfig,
+    },
+}
+
+impl Algorithm {
+    /// PANOC with the default L-BFGS memory
+    pub fn panoc() -> Self {
+        Algorithm::Panoc {
+            lbfgs_memory: DEFAULT_LBFGS_MEMORY,
+        }
+    }
+
+    /// FBS with step size `gamma`
+    pub fn fbs(gamma: f64) -> Self {
+        Algorithm::Fbs { gamma }
+    }
+
+    /// ALM/PM with the default L-BFGS memory and configuration
+    pub fn alm() -> Self {
+        Algorithm::Alm {
+            lbfgs_memory: DEFAULT_LBFGS_MEMORY,
+            config: AlmConfig::default(),
+        }
+    }
+}
+
+impl Default for Algorithm {
+    fn default() -> Self {
+        Algorithm::panoc()
+    }
+}
+
+/// ALM-type constraints, $F_1(u) \in C$ (see `with_alm_constraints`)
+struct AlmConstraints<'a> {
+    mapping_f1: SharedMapping<'a>,
+    jacobian_mapping_f1_trans: BoxedJacobianProduct<'a>,
+    /// set C (for the augmented cost and for the update of the multipliers)
+    set_c: [BoxedConstraint<'a>; 2],
+    set_y: BoxedConstraint<'a>,
+    n1: usize,
+}
+
+/// PM-type constraints, $F_2(u) = 0$ (see `with_pm_constraints`)
+struct PmConstraints<'a> {
+    mapping_f2: SharedMapping<'a>,
+    jacobian_mapping_f2_trans: BoxedJacobianProduct<'a>,
+    n2: usize,
+}
+
+/// Builder of an `Optimizer` for a given `Algorithm`
+///
+/// The common options are applied to all algorithms; options which are not
+/// set keep the defaults of the optimizer (the default tolerance is `1e-6`).
+/// For ALM, the tolerance is the tolerance $\epsilon$ of the inner problems
+/// and the maximum number of iterations is that of each inner problem.
+pub struct OptimizerBuilder<'a, GradientType, ConstraintType, CostType>
+where
+    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult,
+    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult,
+    ConstraintType: Constraint,
+{
+    problem: Problem<'a, GradientType, ConstraintType, CostType>,
+    problem_size: usize,
+    algorithm: Algorithm,
+    tolerance: Option<f64>,
+    max_iter: Option<usize>,
+    max_duration: Option<time::Duration>,
+    alm_constraints: Option<AlmConstraints<'a>>,
+    pm_constraints: Option<PmConstraints<'a>>,
+}
+
+impl<'a, GradientType, ConstraintType, CostType>
+    OptimizerBuilder<'a, GradientType, ConstraintType, CostType>
+where
+    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
+    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult + 'a,
+    ConstraintType: Constraint + 'a,
+{
+    /// Constructs a builder for `problem`, whose decision variables have
+    /// dimension `problem_size`, with the default algorithm (PANOC)
+    pub fn new(
+        problem: Problem<'a, GradientType, ConstraintType, CostType>,
+        problem_size: usize,
+    ) -> Self {
+        OptimizerBuilder {
+            problem,
+            problem_size,
+            algorithm: Algorithm::default(),
+            tolerance: None,
+            max_iter: None,
+            max_duration: None,
+            alm_constraints: None,
+            pm_constraints: None,
+        }
+    }
+
+    /// Sets the algorithm
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the tolerance
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the tolerance is not
+    /// positive
+    pub fn with_tolerance(mut self, tolerance: f64) -> Result<Self, SolverError> {
+        ensure(tolerance > 0.0, "tolerance", "must be positive")?;
+        self.tolerance = Some(tolerance);
+        Ok(self)
+    }
+
+    /// Sets the maximum number of iterations
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the maximum number of
+    /// iterations is zero
+    pub fn with_max_iter(mut self, max_iter: usize) -> Result<Self, SolverError> {
+        ensure(max_iter > 0, "max_iter", "must be positive")?;
+        self.max_iter = Some(max_iter);
+        Ok(self)
+    }
+
+    /// Sets the maximum solution time
+    pub fn with_max_duration(mut self, max_duration: time::Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Sets the ALM-type constraints, $F_1(u) \in C$
+    ///
+    /// ## Arguments
+    ///
+    /// - `mapping_f1`: mapping $F_1$, with range dimension `n1`
+    /// - `jacobian_mapping_f1_trans`: mapping $(u, d) \mapsto JF_1(u)^\top d$
+    /// - `set_c`: convex closed set $C$
+    /// - `set_y`: compact set $Y$ of Lagrange multipliers (see `AlmProblem`)
+    /// - `n1`: range dimension of $F_1$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `n1` is zero
+    pub fn with_alm_constraints<F1, JF1, C, Y>(
+        mut self,
+        mapping_f1: F1,
+        jacobian_mapping_f1_trans: JF1,
+        set_c: C,
+        set_y: Y,
+        n1: usize,
+    ) -> Result<Self, SolverError>
+    where
+        F1: Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
+        JF1: Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult + 'a,
+        C: Constraint + Clone + 'a,
+        Y: Constraint + 'a,
+    {
+        ensure(n1 > 0, "n1", "must be positive")?;
+        self.alm_constraints = Some(AlmConstraints {
+            mapping_f1: Rc::new(mapping_f1),
+            jacobian_mapping_f1_trans: Box::new(jacobian_mapping_f1_trans),
+            set_c: [Box::new(set_c.clone()), Box::new(set_c)],
+            set_y: Box::new(set_y),
+            n1,
+        });
+        Ok(self)
+    }
+
+    /// Sets the PM-type constraints, $F_2(u) = 0$
+    ///
+    /// ## Arguments
+    ///
+    /// - `mapping_f2`: mapping $F_2$, with range dimension `n2`
+    /// - `jacobian_mapping_f2_trans`: mapping $(u, d) \mapsto JF_2(u)^\top d$
+    /// - `n2`: range dimension of $F_2$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `n2` is zero
+    pub fn with_pm_constraints<F2, JF2>(
+        mut self,
+        mapping_f2: F2,
+        jacobian_mapping_f2_trans: JF2,
+        n2: usize,
+    ) -> Result<Self, SolverError>
+    where
+        F2: Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
+        JF2: Fn(&[f64], &[f64], &mut [f64]) -> FunctionCallResult + 'a,
+    {
+        ensure(n2 > 0, "n2", "must be positive")?;
+        self.pm_constraints = Some(PmConstraints {
+            mapping_f2: Rc::new(mapping_f2),
+            jacobian_mapping_f2_trans: Box::new(jacobian_mapping_f2_trans),
+            n2,
+        });
+        Ok(self)
+    }
+
+    /// Constructs the optimizer (and its cache)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if the problem size is zero,
+    /// if the parameters of the algorithm are invalid, or if ALM-type or
+    /// PM-type constraints are given for an algorithm other than
+    /// `Algorithm::Alm`
+    pub fn build(self) -> Result<Box<dyn Optimizer + 'a>, SolverError> {
+        ensure(
+            matches!(self.algorithm, Algorithm::Alm { .. })
+                || (self.alm_constraints.is_none() && self.pm_constraints.is_none()),
+            "algorithm",
+            "ALM-type and PM-type constraints require Algorithm::Alm",
+        )?;
+        let tolerance = self.tolerance.unwrap_or(DEFAULT_TOLERANCE);
+        match self.algorithm {
+            Algorithm::Panoc { lbfgs_memory } => {
+                let cache = PANOCCache::new(self.problem_size, tolerance, lbfgs_memory)?;
+                let mut optimizer = PANOCOptimizer::new_owned(self.problem, cache);
+                if let Some(max_iter) = self.max_iter {
+                    optimizer = optimizer.with_max_iter(max_iter)?;
+                }
+                if let Some(max_duration) = self.max_duration {
+                    optimizer = optimizer.with_max_duration(max_duration);
+                }
+                Ok(Box::new(optimizer))
+            }
+            Algorithm::Fbs { gamma } => {
+                let problem_size =
+                    NonZeroUsize::new(self.problem_size).ok_or(SolverError::InvalidParameter {
+                        name: "problem_size",
+                        reason: "must be positive",
+                    })?;
+                let cache = FBSCache::new(problem_size, gamma, tolerance)?;
+                let mut optimizer = FBSOptimizer::new_owned(self.problem, cache);
+                if let Some(max_iter) = self.max_iter {
+                    optimizer = optimizer.with_max_iter(max_iter);
+                }
+                if let Some(max_duration) = self.max_duration {
+                    optimizer = optimizer.with_max_duration(max_duration);
+                }
+                Ok(Box::new(optimizer))
+            }
+            Algorithm::Alm {
+                lbfgs_memory,
+                ref config,
+            } => {
+                let mut optimizer = AlmOptimizer::from_dimensions(
+                    alm_problem(self.problem, self.alm_constraints, self.pm_constraints)?,
+                    self.problem_size,
+                    tolerance,
+                    lbfgs_memory,
+                )?
+                .with_config(config)?;
+                if let Some(tolerance) = self.tolerance {
+                    optimizer = optimizer.with_epsilon_tolerance(tolerance)?;
+                }
+                if let Some(max_iter) = self.max_iter {
+                    optimizer = optimizer.with_max_inner_iterations(max_iter)?;
+                }
+                if let Some(max_duration) = self.max_duration {
+                    optimizer = optimizer.with_max_duration(max_duration);
+                }
+                Ok(Box::new(optimizer))
+            }
+        }
+    }
+}
+
+/// Constructs the (type-erased) ALM/PM problem, whose augmented cost is
+/// constructed with `AlmFactory`
+fn alm_problem<'a, GradientType, ConstraintType, CostType>(
+    problem: Problem<'a, GradientType, ConstraintType, CostType>,
+    alm_constraints: Option<AlmConstraints<'a>>,
+    pm_constraints: Option<PmConstraints<'a>>,
+) -> Result<crate::alm::DynAlmProblem<'a>, SolverError>
+where
+    GradientType: Fn(&[f64], &mut [f64]) -> FunctionCallResult + 'a,
+    CostType: Fn(&[f64], &mut f64) -> FunctionCallResult + 'a,
+    ConstraintType: Constraint + 'a,
+{
+    let shared = |mapping: &SharedMapping<'a>| -> ClonedMapping<'a> {
+        let mapping = Rc::clone(mapping);
+        Box::new(move |u: &[f64], result: &mut [f64]| mapping(u, result))
+    };
+    let (mapping_f1, jacobian_mapping_f1_trans, set_c, set_y, n1) = match alm_constraints {
+        Some(alm) => {
+            let [set_c_factory, set_c] = alm.set_c;
+            (
+                Some((shared(&alm.mapping_f1), shared(&alm.mapping_f1))),
+                Some(alm.jacobian_mapping_f1_trans),
+                Some((set_c_factory, set_c)),
+                Some(alm.set_y),
+                alm.n1,
+            )
+        }
+        None => (None, None, None, None, 0),
+    };
+    let (mapping_f2, jacobian_mapping_f2_trans, n2) = match pm_constraints {
+        Some(pm) => (
+            Some((shared(&pm.mapping_f2), shared(&pm.mapping_f2))),
+            Some(pm.jacobian_mapping_f2_trans),
+            pm.n2,
+        ),
+        None => (None, None, 0),
+    };
+    let (f1_factory, f1) = mapping_f1.unzip();
+    let (f2_factory, f2) = mapping_f2.unzip();
+    let (set_c_factory, set_c) = set_c.unzip();
+    let factory = Rc::new(AlmFactory::new(
+        problem.cost,
+        problem.gradf,
+        f1_factory,
+        jacobian_mapping_f1_trans,
+        f2_factory,
+        jacobian_mapping_f2_trans,
+        set_c_factory,
+        n2,
+    )?);
+    let factory_gradient = Rc::clone(&factory);
+    AlmProblem::new_boxed(
+        problem.constraints,
+        set_c,
+        set_y,
+        move |u: &[f64], xi: &[f64], cost: &mut f64| factory.psi(u, xi, cost),
+        move |u: &[f64], xi: &[f64], grad: &mut [f64]| factory_gradient.d_psi(u, xi, grad),
+        f1,
+        f2,
+        n1,
+        n2,
+    )
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::*, core::ExitStatus, mocks};
+
+    #[test]
+    fn t_optimizer_builder_algorithms() {
+        let bounds = Rectangle::new(Some(&[-1.0, -1.0]), Some(&[1.0, 1.0])).unwrap();
+        let solutions: Vec<Vec<f64>> =
+            vec![Algorithm::panoc(), Algorithm::fbs(0.1), Algorithm::alm()]
+                .into_iter()
+                .map(|algorithm| {
+                    let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+                    let mut optimizer = OptimizerBuilder::new(problem, 2)
+                        .with_algorithm(algorithm)
+                        .with_tolerance(1e-10)
+                        .unwrap()
+                        .with_max_iter(10_000)
+                        .unwrap()
+                        .build()
+                        .unwrap();
+                    let mut u = [0.75, 0.0];
+                    let status = optimizer.solve(&mut u).unwrap();
+                    assert!(status.has_converged());
+                    u.to_vec()
+                })
+                .collect();
+        for u in &solutions[1..] {
+            unit_test_utils::assert_nearly_equal_array(&solutions[0], u, 1e-6, 1e-6, "u");
+        }
+
+        // the common options are applied
+        let problem = Problem::new(&bounds, mocks::my_gradient, mocks::my_cost);
+        let mut optimizer = OptimizerBuilder::new(problem, 2)
+            .with_algorithm(Algorithm::fbs(0.1))
+            .with_max_iter(2)
+            .unwrap()
+            .build()
+            .unwrap();
+        let status = optimizer.solve(&mut [0.75, 0.0]).unwrap();
+        assert_eq!(ExitStatus::NotConvergedIterations, status.exit_status());
+        assert_eq!(2, status.iterations());
+    }
+
+    #[test]
+    fn t_optimizer_builder_alm() {
+        // minimize 0.5 |u|^2 subject to u_1 + u_2 = 1 (PM) and u_1 <= 0.2 (ALM)
+        let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            grad.copy_from_slice(u);
+            Ok(())
+        };
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = 0.5 * crate::matrix_operations::norm2_squared(u);
+            Ok(())
+        };
+        let f1 = |u: &[f64], f1u: &mut [f64]| -> FunctionCallResult {
+            f1u[0] = u[0];
+            Ok(())
+        };
+        let jf1t = |_u: &[f64], d: &[f64], res: &mut [f64]| -> FunctionCallResult {
+            res[0] = d[0];
+            res[1] = 0.0;
+            Ok(())
+        };
+        let f2 = |u: &[f64], f2u: &mut [f64]| -> FunctionCallResult {
+            f2u[0] = u[0] + u[1] - 1.0;
+            Ok(())
+        };
+        let jf2t = |_u: &[f64], d: &[f64], res: &mut [f64]| -> FunctionCallResult {
+            res.iter_mut().for_each(|r| *r = d[0]);
+            Ok(())
+        };
+        let bounds = NoConstraints::new();
+        let builder = || {
+            OptimizerBuilder::new(Problem::new(&bounds, gradient, cost), 2)
+                .with_alm_constraints(
+                    f1,
+                    jf1t,
+                    Rectangle::new(None, Some(&[0.2])).unwrap(),
+                    BallInf::new(None, 1e6).unwrap(),
+                    1,
+                )
+                .unwrap()
+                .with_pm_constraints(f2, jf2t, 1)
+                .unwrap()
+        };
+        let mut optimizer = builder()
+            .with_algorithm(Algorithm::alm())
+            .with_tolerance(1e-6)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut u = [0.0; 2];
+        assert!(optimizer.solve(&mut u).unwrap().has_converged());
+        unit_test_utils::assert_nearly_equal_array(&[0.2, 0.8], &u, 1e-3, 1e-3, "u");
+
+        // the constraints require ALM
+        assert!(builder().build().is_err());
+        assert!(builder()
+            .with_algorithm(Algorithm::fbs(0.1))
+            .build()
+            .is_err());
+    }
+}
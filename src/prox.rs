@@ -0,0 +1,582 @@
+//! Proximal operators of nonsmooth and nonconvex regularizers
+//!
+//! This module offers the proximal operators,
+//!
+//! $$\mathrm{prox}_{\gamma g}(v) = \mathrm{argmin}_x g(x) + \tfrac{1}{2\gamma}\Vert x - v\Vert^2,$$
+//!
+//! of common sparsity-inducing regularizers, $g$:
+//!
+//! - [`L1Norm`], $g(x) = \lambda\Vert x \Vert_1$ (soft thresholding),
+//! - [`GroupL1Norm`], $g(x) = \lambda\sum_j \Vert x_{G_j} \Vert_2$ (group soft
+//!   thresholding),
+//! - [`L0Norm`], $g(x) = \lambda\Vert x \Vert_0$, the number of nonzero
+//!   elements (hard thresholding),
+//! - [`Mcp`], the minimax concave penalty, and [`Scad`], the smoothly clipped
+//!   absolute deviation penalty, which are nonconvex penalties that do not
+//!   shrink large elements.
+//!
+//! They implement [`ProximalOperator`] and do not allocate any memory, so
+//! they can be used in the proximal operators of `DRSProblem`, `PDHGProblem`
+//! and `AdmmProblem`, e.g., `|v: &[f64], gamma: f64, x: &mut [f64]|
+//! l1.prox(v, gamma, x)`. For the nonconvex regularizers, the proximal
+//! operator may be set-valued; one of its elements is returned.
+//!
+//! The block soft thresholding is also available as a function,
+//! [`block_soft_threshold`].
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::prox::{L1Norm, ProximalOperator};
+//!
+//! let l1 = L1Norm::new(0.5)?;
+//! let mut x = [0.0; 3];
+//! l1.prox(&[2.0, -0.4, -1.0], 2.0, &mut x)?;
+//! assert_eq!([1.0, 0.0, 0.0], x);
+//! assert_eq!(1.5, l1.value(&[2.0, -1.0]));
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`ProximalOperator`]: trait.ProximalOperator.html
+//! [`L1Norm`]: struct.L1Norm.html
+//! [`GroupL1Norm`]: struct.GroupL1Norm.html
+//! [`block_soft_threshold`]: fn.block_soft_threshold.html
+//! [`L0Norm`]: struct.L0Norm.html
+//! [`Mcp`]: struct.Mcp.html
+//! [`Scad`]: struct.Scad.html
+
+use crate::{ensure, matrix_operations, FunctionCallResult, OpEnFloat, SolverError};
+
+/// Proximal operator of a function $g$
+///
+/// The type parameter `T` is the scalar type (`f64` by default)
+pub trait ProximalOperator<T = f64> {
+    /// Computes $\mathrm{prox}_{\gamma g}(v)$ and stores it in `result`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `v` and `result` are
+    /// incompatible, and `SolverError::InvalidParameter` if `gamma` is not
+    /// positive
+    fn prox(&self, v: &[T], gamma: T, result: &mut [T]) -> FunctionCallResult;
+
+    /// Computes $g(x)$
+    fn value(&self, x: &[T]) -> T;
+}
+
+/// Checks the arguments of `ProximalOperator::prox`
+fn check_arguments<T: OpEnFloat>(v: &[T], gamma: T, result: &[T]) -> FunctionCallResult {
+    ensure(gamma > T::zero(), "gamma", "must be positive")?;
+    if v.len() != result.len() {
+        return Err(SolverError::DimensionMismatch {
+            name: "result",
+            expected: v.len(),
+            actual: result.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that the weight of a regularizer is nonnegative and finite
+fn check_lambda<T: OpEnFloat>(lambda: T) -> FunctionCallResult {
+    ensure(
+        lambda >= T::zero() && lambda.is_finite(),
+        "lambda",
+        "must be nonnegative and finite",
+    )
+}
+
+/// Soft thresholding of a scalar, $\mathrm{sign}(v)\max\\{|v| - \kappa, 0\\}$
+fn soft_threshold<T: OpEnFloat>(v: T, kappa: T) -> T {
+    v.signum() * (v.abs() - kappa).max(T::zero())
+}
+
+/// Block soft thresholding of `x` (in place), that is,
+///
+/// $$x \gets \max\\{1 - \kappa/\Vert x \Vert_2, 0\\} x,$$
+///
+/// which is the proximal operator of $\kappa\Vert x \Vert_2$ with $\gamma = 1$;
+/// it is applied to the groups of [`GroupL1Norm`] and is used to project on
+/// `GroupNormBall` (see `constraints`)
+///
+/// [`GroupL1Norm`]: struct.GroupL1Norm.html
+pub fn block_soft_threshold<T: OpEnFloat>(x: &mut [T], kappa: T) {
+    let norm = matrix_operations::norm2(x);
+    let scaling = if norm > kappa {
+        T::one() - kappa / norm
+    } else {
+        T::zero()
+    };
+    x.iter_mut().for_each(|xi| *xi = scaling * *xi);
+}
+
+/// Applies the elementwise proximal operator `prox_scalar` to `v`
+fn prox_elementwise<T: OpEnFloat>(v: &[T], result: &mut [T], prox_scalar: impl Fn(T) -> T) {
+    result
+        .iter_mut()
+        .zip(v.iter())
+        .for_each(|(x, &vi)| *x = prox_scalar(vi));
+}
+
+/// Among the `candidates`, returns the minimizer of
+/// $\tfrac{1}{2\gamma}(x - v)^2 + p(x)$, where $p$ is the `penalty`
+fn best_candidate<T: OpEnFloat>(v: T, gamma: T, candidates: [T; 3], penalty: impl Fn(T) -> T) -> T {
+    let objective = |x: T| (x - v) * (x - v) / (gamma + gamma) + penalty(x);
+    candidates.iter().skip(1).fold(candidates[0], |best, &x| {
+        if objective(x) < objective(best) {
+            x
+        } else {
+            best
+        }
+    })
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          L1 NORM                                                             */
+/* ---------------------------------------------------------------------------- */
+
+/// The $\ell_1$ norm, $g(x) = \lambda\Vert x \Vert_1$, whose proximal operator
+/// is the soft thresholding,
+/// $\mathrm{prox}_{\gamma g}(v)_i = \mathrm{sign}(v_i)\max\\{|v_i| - \gamma\lambda, 0\\}$
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct L1Norm<T = f64> {
+    lambda: T,
+}
+
+impl<T: OpEnFloat> L1Norm<T> {
+    /// Constructs the $\ell_1$ norm with weight `lambda`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `lambda` is negative or
+    /// not finite
+    pub fn new(lambda: T) -> Result<Self, SolverError> {
+        check_lambda(lambda)?;
+        Ok(L1Norm { lambda })
+    }
+}
+
+impl<T: OpEnFloat> ProximalOperator<T> for L1Norm<T> {
+    fn prox(&self, v: &[T], gamma: T, result: &mut [T]) -> FunctionCallResult {
+        check_arguments(v, gamma, result)?;
+        let kappa = gamma * self.lambda;
+        prox_elementwise(v, result, |vi| soft_threshold(vi, kappa));
+        Ok(())
+    }
+
+    fn value(&self, x: &[T]) -> T {
+        self.lambda * matrix_operations::norm1(x)
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          GROUP L1 NORM                                                       */
+/* ---------------------------------------------------------------------------- */
+
+/// The group $\ell_1$ norm, $g(x) = \lambda\sum_j \Vert x_{G_j} \Vert_2$, where
+/// the groups $G_j$ are consecutive blocks of $x$, whose proximal operator is
+/// the group soft thresholding,
+/// $\mathrm{prox}_{\gamma g}(v)_{G_j} = \max\\{1 - \gamma\lambda/\Vert v_{G_j}\Vert_2, 0\\} v_{G_j}$
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupL1Norm<T = f64> {
+    lambda: T,
+    group_sizes: Vec<usize>,
+    dimension: usize,
+}
+
+impl<T: OpEnFloat> GroupL1Norm<T> {
+    /// Constructs the group $\ell_1$ norm with weight `lambda`, where the
+    /// $j$-th group consists of the next `group_sizes[j]` elements of $x$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `lambda` is negative or
+    /// not finite, or if `group_sizes` is empty or contains zeros
+    pub fn new(lambda: T, group_sizes: &[usize]) -> Result<Self, SolverError> {
+        check_lambda(lambda)?;
+        ensure(
+            !group_sizes.is_empty() && group_sizes.iter().all(|&size| size > 0),
+            "group_sizes",
+            "must be nonempty and positive",
+        )?;
+        Ok(GroupL1Norm {
+            lambda,
+            group_sizes: group_sizes.to_vec(),
+            dimension: group_sizes.iter().sum(),
+        })
+    }
+
+    /// Dimension of $x$, that is, the sum of the sizes of the groups
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn groups<'v>(&'v self, x: &'v [T]) -> impl Iterator<Item = &'v [T]> + 'v {
+        self.group_sizes.iter().scan(0, move |start, &size| {
+            let group = &x[*start..*start + size];
+            *start += size;
+            Some(group)
+        })
+    }
+}
+
+impl<T: OpEnFloat> ProximalOperator<T> for GroupL1Norm<T> {
+    fn prox(&self, v: &[T], gamma: T, result: &mut [T]) -> FunctionCallResult {
+        check_arguments(v, gamma, result)?;
+        if v.len() != self.dimension {
+            return Err(SolverError::DimensionMismatch {
+                name: "v",
+                expected: self.dimension,
+                actual: v.len(),
+            });
+        }
+        let kappa = gamma * self.lambda;
+        result.copy_from_slice(v);
+        let mut start = 0;
+        for &size in self.group_sizes.iter() {
+            block_soft_threshold(&mut result[start..start + size], kappa);
+            start += size;
+        }
+        Ok(())
+    }
+
+    fn value(&self, x: &[T]) -> T {
+        self.lambda
+            * self
+                .groups(x)
+                .map(matrix_operations::norm2)
+                .fold(T::zero(), |sum, norm| sum + norm)
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          L0 NORM                                                             */
+/* ---------------------------------------------------------------------------- */
+
+/// The $\ell_0$ "norm", $g(x) = \lambda\Vert x \Vert_0$, that is, $\lambda$ times
+/// the number of nonzero elements of $x$, whose proximal operator is the hard
+/// thresholding, $\mathrm{prox}_{\gamma g}(v)_i = v_i$ if
+/// $|v_i| > \sqrt{2\gamma\lambda}$ and $0$ otherwise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct L0Norm<T = f64> {
+    lambda: T,
+}
+
+impl<T: OpEnFloat> L0Norm<T> {
+    /// Constructs the $\ell_0$ "norm" with weight `lambda`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `lambda` is negative or
+    /// not finite
+    pub fn new(lambda: T) -> Result<Self, SolverError> {
+        check_lambda(lambda)?;
+        Ok(L0Norm { lambda })
+    }
+}
+
+impl<T: OpEnFloat> ProximalOperator<T> for L0Norm<T> {
+    fn prox(&self, v: &[T], gamma: T, result: &mut [T]) -> FunctionCallResult {
+        check_arguments(v, gamma, result)?;
+        let threshold = (T::from_f64(2.0) * gamma * self.lambda).sqrt();
+        prox_elementwise(
+            v,
+            result,
+            |vi| {
+                if vi.abs() > threshold {
+                    vi
+                } else {
+                    T::zero()
+                }
+            },
+        );
+        Ok(())
+    }
+
+    fn value(&self, x: &[T]) -> T {
+        let nonzeros = x.iter().filter(|&&xi| xi != T::zero()).count();
+        self.lambda * T::from_f64(nonzeros as f64)
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          MCP                                                                 */
+/* ---------------------------------------------------------------------------- */
+
+/// The minimax concave penalty (MCP), $g(x) = \sum_i p(x_i)$, with
+///
+/// $$p(t) = \begin{cases}\lambda|t| - t^2/(2a), & |t| \leq a\lambda,
+/// \\\\ a\lambda^2/2, & \text{otherwise,}\end{cases}$$
+///
+/// where $a > 1$. If $\gamma < a$, its proximal operator is the firm
+/// thresholding, $\mathrm{prox}_{\gamma g}(v)_i = 0$ if $|v_i| \leq
+/// \gamma\lambda$, $\mathrm{sign}(v_i)(|v_i| - \gamma\lambda)/(1 - \gamma/a)$
+/// if $\gamma\lambda < |v_i| \leq a\lambda$, and $v_i$ otherwise; if
+/// $\gamma \geq a$, it is a hard thresholding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mcp<T = f64> {
+    lambda: T,
+    a: T,
+}
+
+impl<T: OpEnFloat> Mcp<T> {
+    /// Constructs the MCP with weight `lambda` and concavity parameter `a`
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `lambda` is negative or
+    /// not finite, or `a` is not larger than 1 and finite
+    pub fn new(lambda: T, a: T) -> Result<Self, SolverError> {
+        check_lambda(lambda)?;
+        ensure(a > T::one() && a.is_finite(), "a", "must be larger than 1")?;
+        Ok(Mcp { lambda, a })
+    }
+
+    fn penalty(&self, t: T) -> T {
+        let (lambda, a) = (self.lambda, self.a);
+        if t.abs() <= a * lambda {
+            lambda * t.abs() - t * t / (a + a)
+        } else {
+            a * lambda * lambda / T::from_f64(2.0)
+        }
+    }
+}
+
+impl<T: OpEnFloat> ProximalOperator<T> for Mcp<T> {
+    fn prox(&self, v: &[T], gamma: T, result: &mut [T]) -> FunctionCallResult {
+        check_arguments(v, gamma, result)?;
+        let (lambda, a) = (self.lambda, self.a);
+        prox_elementwise(v, result, |vi| {
+            let (sign, magnitude) = (vi.signum(), vi.abs());
+            // minimizers on |x| <= a lambda (where the objective is convex if
+            // gamma < a, otherwise concave, so that the minimizer is 0 or
+            // a lambda) and on |x| >= a lambda
+            let inner = if gamma < a {
+                ((magnitude - gamma * lambda) / (T::one() - gamma / a))
+                    .max(T::zero())
+                    .min(a * lambda)
+            } else {
+                T::zero()
+            };
+            let outer = magnitude.max(a * lambda);
+            best_candidate(vi, gamma, [T::zero(), sign * inner, sign * outer], |x| {
+                self.penalty(x)
+            })
+        });
+        Ok(())
+    }
+
+    fn value(&self, x: &[T]) -> T {
+        x.iter().fold(T::zero(), |sum, &xi| sum + self.penalty(xi))
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          SCAD                                                                */
+/* ---------------------------------------------------------------------------- */
+
+/// The smoothly clipped absolute deviation (SCAD) penalty, $g(x) = \sum_i p(x_i)$,
+/// with
+///
+/// $$p(t) = \begin{cases}\lambda|t|, & |t| \leq \lambda,
+/// \\\\ (2a\lambda|t| - t^2 - \lambda^2)/(2(a-1)), & \lambda < |t| \leq a\lambda,
+/// \\\\ (a+1)\lambda^2/2, & \text{otherwise,}\end{cases}$$
+///
+/// where $a > 2$. If $\gamma = 1$, its proximal operator is the SCAD
+/// thresholding rule of Fan and Li (2001); for other values of $\gamma$, the
+/// minimizers on the three pieces of $p$ are compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scad<T = f64> {
+    lambda: T,
+    a: T,
+}
+
+impl<T: OpEnFloat> Scad<T> {
+    /// Constructs the SCAD penalty with weight `lambda` and parameter `a`
+    /// (typically, `a = 3.7`)
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::InvalidParameter` if `lambda` is negative or
+    /// not finite, or `a` is not larger than 2 and finite
+    pub fn new(lambda: T, a: T) -> Result<Self, SolverError> {
+        check_lambda(lambda)?;
+        ensure(
+            a > T::from_f64(2.0) && a.is_finite(),
+            "a",
+            "must be larger than 2",
+        )?;
+        Ok(Scad { lambda, a })
+    }
+
+    fn penalty(&self, t: T) -> T {
+        let (lambda, a, two) = (self.lambda, self.a, T::from_f64(2.0));
+        let magnitude = t.abs();
+        if magnitude <= lambda {
+            lambda * magnitude
+        } else if magnitude <= a * lambda {
+            (two * a * lambda * magnitude - t * t - lambda * lambda) / (two * (a - T::one()))
+        } else {
+            (a + T::one()) * lambda * lambda / two
+        }
+    }
+}
+
+impl<T: OpEnFloat> ProximalOperator<T> for Scad<T> {
+    fn prox(&self, v: &[T], gamma: T, result: &mut [T]) -> FunctionCallResult {
+        check_arguments(v, gamma, result)?;
+        let (lambda, a) = (self.lambda, self.a);
+        let a_minus_one = a - T::one();
+        prox_elementwise(v, result, |vi| {
+            let (sign, magnitude) = (vi.signum(), vi.abs());
+            // minimizers on |x| <= lambda, lambda <= |x| <= a lambda (where the
+            // objective is convex if gamma < a - 1, otherwise concave, so that
+            // the minimizer is lambda or a lambda) and |x| >= a lambda
+            let first = soft_threshold(magnitude, gamma * lambda).min(lambda);
+            let second = if gamma < a_minus_one {
+                ((a_minus_one * magnitude - gamma * a * lambda) / (a_minus_one - gamma))
+                    .max(lambda)
+                    .min(a * lambda)
+            } else {
+                // the minimizer is an endpoint, which is another candidate
+                lambda
+            };
+            let third = magnitude.max(a * lambda);
+            best_candidate(
+                vi,
+                gamma,
+                [sign * first, sign * second, sign * third],
+                |x| self.penalty(x),
+            )
+        });
+        Ok(())
+    }
+
+    fn value(&self, x: &[T]) -> T {
+        x.iter().fold(T::zero(), |sum, &xi| sum + self.penalty(xi))
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks `prox` against the minimizer of the proximal objective on a
+    /// fine grid (for elementwise regularizers)
+    fn assert_prox_by_grid_search(g: &impl ProximalOperator, gamma: f64) {
+        for k in -40..=40 {
+            let v = 0.1 * k as f64;
+            let mut x = [0.0];
+            g.prox(&[v], gamma, &mut x).unwrap();
+            let objective = |x: f64| g.value(&[x]) + (x - v).powi(2) / (2.0 * gamma);
+            let best_on_grid = (-3000..=3000)
+                .map(|j| objective(0.002 * j as f64))
+                .fold(f64::INFINITY, f64::min);
+            assert!(
+                objective(x[0]) <= best_on_grid + 1e-9,
+                "prox({}) = {} is not optimal",
+                v,
+                x[0]
+            );
+        }
+    }
+
+    #[test]
+    fn t_prox_l1_l0_group() {
+        let v = [3.0, -0.5, 1.0, 0.0, -4.0];
+        let mut x = [0.0; 5];
+        L1Norm::new(1.0).unwrap().prox(&v, 0.8, &mut x).unwrap();
+        unit_test_utils::assert_nearly_equal_array(
+            &[2.2, 0.0, 0.2, 0.0, -3.2],
+            &x,
+            1e-12,
+            1e-12,
+            "l1",
+        );
+        L0Norm::new(0.5).unwrap().prox(&v, 1.0, &mut x).unwrap();
+        assert_eq!([3.0, 0.0, 0.0, 0.0, -4.0], x);
+        assert_eq!(1.5, L0Norm::new(0.5).unwrap().value(&[1.0, 0.0, -2.0, 3.0]));
+
+        let group = GroupL1Norm::new(2.0, &[2, 1, 2]).unwrap();
+        assert_eq!(5, group.dimension());
+        group.prox(&v, 0.5, &mut x).unwrap();
+        let scaling = 1.0 - 1.0 / 9.25_f64.sqrt();
+        unit_test_utils::assert_nearly_equal_array(
+            &[3.0 * scaling, -0.5 * scaling, 0.0, 0.0, -3.0],
+            &x,
+            1e-12,
+            1e-12,
+            "group",
+        );
+        assert!((group.value(&v) - 2.0 * (9.25_f64.sqrt() + 1.0 + 4.0)).abs() < 1e-12);
+
+        let mut block = [3.0, -4.0];
+        block_soft_threshold(&mut block, 2.5);
+        assert_eq!([1.5, -2.0], block);
+        block_soft_threshold(&mut block, 2.5);
+        assert_eq!([0.0, 0.0], block);
+    }
+
+    #[test]
+    fn t_prox_mcp_scad() {
+        let mcp = Mcp::new(1.0, 3.0).unwrap();
+        let mut x = [0.0; 4];
+        mcp.prox(&[0.5, 1.5, -2.0, 5.0], 1.0, &mut x).unwrap();
+        unit_test_utils::assert_nearly_equal_array(
+            &[0.0, 0.75, -1.5, 5.0],
+            &x,
+            1e-12,
+            1e-12,
+            "mcp",
+        );
+        let scad = Scad::new(1.0, 3.7).unwrap();
+        scad.prox(&[0.5, 1.5, -3.0, 5.0], 1.0, &mut x).unwrap();
+        // Fan and Li: soft thresholding if |v| <= 2 lambda, then
+        // ((a - 1) v - sign(v) a lambda)/(a - 2), and v if |v| > a lambda
+        unit_test_utils::assert_nearly_equal_array(
+            &[0.0, 0.5, -(2.7 * 3.0 - 3.7) / 1.7, 5.0],
+            &x,
+            1e-12,
+            1e-12,
+            "scad",
+        );
+        for gamma in [0.5, 1.0, 2.5, 4.0] {
+            assert_prox_by_grid_search(&mcp, gamma);
+            assert_prox_by_grid_search(&scad, gamma);
+            assert_prox_by_grid_search(&L0Norm::new(0.7).unwrap(), gamma);
+            assert_prox_by_grid_search(&L1Norm::new(0.7).unwrap(), gamma);
+        }
+    }
+
+    #[test]
+    fn t_prox_errors() {
+        let l1 = L1Norm::new(1.0).unwrap();
+        let mut x = [0.0; 2];
+        assert!(matches!(
+            l1.prox(&[1.0; 3], 1.0, &mut x),
+            Err(SolverError::DimensionMismatch { name: "result", .. })
+        ));
+        assert!(l1.prox(&[1.0; 2], 0.0, &mut x).is_err());
+        let group = GroupL1Norm::new(1.0, &[3]).unwrap();
+        assert!(matches!(
+            group.prox(&[1.0; 2], 1.0, &mut x),
+            Err(SolverError::DimensionMismatch { name: "v", .. })
+        ));
+        assert!(L1Norm::new(-1.0).is_err());
+        assert!(L0Norm::new(f64::NAN).is_err());
+        assert!(GroupL1Norm::new(1.0, &[2, 0]).is_err());
+        assert!(GroupL1Norm::<f64>::new(1.0, &[]).is_err());
+        assert!(Mcp::new(1.0, 1.0).is_err());
+        assert!(Scad::new(1.0, 2.0).is_err());
+        // f32
+        let mut x = [0.0_f32; 2];
+        L1Norm::new(1.0_f32)
+            .unwrap()
+            .prox(&[2.0, -0.5], 1.0, &mut x)
+            .unwrap();
+        assert_eq!([1.0, 0.0], x);
+    }
+}
@@ -0,0 +1,457 @@
+//! Quadratic costs
+//!
+//! [`QuadraticCost`] stores the data of a quadratic cost function,
+//!
+//! $$
+//! f(u) = \tfrac{1}{2}u^\top Q u + q^\top u,
+//! $$
+//!
+//! where $Q$ is a symmetric (dense or sparse) matrix, and computes the cost
+//! and its gradient, $\nabla f(u) = Qu + q$, without any user closures and
+//! without allocating memory. Together with box constraints (see
+//! `Rectangle`), this gives box-constrained quadratic programs, which are
+//! solved with PANOC as follows:
+//!
+//! - `QuadraticCost::problem` constructs the `Problem`,
+//! - the Lipschitz constant of the gradient, $\Vert Q \Vert_2$, is computed
+//!   by power iteration when the cost is constructed and is passed to
+//!   `PANOCOptimizer::with_lipschitz_constant`, so PANOC does not need to
+//!   estimate it at the initial point (the power iterations may slightly
+//!   underestimate it, in which case PANOC increases it during its line
+//!   search, as it does with its own estimates),
+//! - `QuadraticCost` implements [`GaussNewtonHessian`] (with the exact
+//!   Hessian, $Q$), so if $Q$ is positive semidefinite, PANOC can use Newton
+//!   directions on the free variables (see `PANOCOptimizer::with_gauss_newton`)
+//!   instead of L-BFGS directions.
+//!
+//! # Example
+//!
+//! ```
+//! use optimization_engine::{
+//!     constraints::Rectangle, panoc::*, quadratic_cost::QuadraticCost, Optimizer,
+//! };
+//!
+//! // f(u) = u0^2 + u1^2 + u0 u1 - 3 u0, subject to 0 <= u <= 1
+//! let qp = QuadraticCost::new_dense(vec![2.0, 1.0, 1.0, 2.0], vec![-3.0, 0.0])?;
+//! let bounds = Rectangle::new(Some(&[0.0, 0.0]), Some(&[1.0, 1.0]))?;
+//! let mut cache = PANOCCache::new(2, 1e-10, 5)?;
+//! let mut u = [0.0_f64; 2];
+//! let status = PANOCOptimizer::new(qp.problem(&bounds), &mut cache)
+//!     .with_lipschitz_constant(qp.lipschitz_constant())?
+//!     .with_gauss_newton(&qp)?
+//!     .solve(&mut u)?;
+//! assert!(status.has_converged());
+//! assert!((u[0] - 1.0).abs() < 1e-8 && u[1].abs() < 1e-8);
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+//! [`QuadraticCost`]: struct.QuadraticCost.html
+//! [`GaussNewtonHessian`]: ../least_squares/trait.GaussNewtonHessian.html
+
+use crate::{
+    constraints::Constraint, ensure, least_squares::GaussNewtonHessian, linear_operator,
+    matrix_operations, FunctionCallResult, OpEnFloat, Problem, SolverError,
+};
+
+/// Maximum number of power iterations for the computation of $\Vert Q \Vert_2$
+const MAX_POWER_ITERATIONS: usize = 500;
+
+/// Relative tolerance of the power iterations
+const POWER_ITERATION_TOLERANCE: f64 = 1e-8;
+
+/// Problem of minimizing a `QuadraticCost` subject to constraints of type
+/// `C` (see `QuadraticCost::problem`)
+pub type QuadraticProblem<'a, C, T = f64> = Problem<
+    'a,
+    Box<dyn Fn(&[T], &mut [T]) -> FunctionCallResult + 'a>,
+    C,
+    Box<dyn Fn(&[T], &mut T) -> FunctionCallResult + 'a>,
+    T,
+>;
+
+/// Storage of the matrix $Q$
+#[derive(Debug, Clone, PartialEq)]
+enum Matrix<T> {
+    /// Row-major dense matrix
+    Dense(Vec<T>),
+    /// Matrix in compressed sparse row (CSR) format
+    Sparse {
+        row_ptr: Vec<usize>,
+        col_idx: Vec<usize>,
+        values: Vec<T>,
+    },
+}
+
+/// Quadratic cost function, $f(u) = \tfrac{1}{2}u^\top Q u + q^\top u$
+///
+/// The matrix $Q$ must be symmetric; its spectral norm, which is the
+/// Lipschitz constant of the gradient, is computed once, when a
+/// `QuadraticCost` is constructed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuadraticCost<T = f64> {
+    /// matrix $Q$
+    matrix: Matrix<T>,
+    /// vector $q$
+    q: Vec<T>,
+    /// $\Vert Q \Vert_2$
+    lipschitz_constant: T,
+}
+
+impl<T: OpEnFloat> QuadraticCost<T> {
+    /// Constructs a quadratic cost with a dense matrix
+    ///
+    /// ## Arguments
+    ///
+    /// - `q_matrix`: the elements of $Q$ in row-major order
+    /// - `q`: the vector $q$, whose length, $n$, is the number of decision
+    ///   variables
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `q_matrix` does not have
+    /// $n^2$ elements
+    pub fn new_dense(q_matrix: Vec<T>, q: Vec<T>) -> Result<Self, SolverError> {
+        let n = q.len();
+        if q_matrix.len() != n * n {
+            return Err(SolverError::DimensionMismatch {
+                name: "q_matrix",
+                expected: n * n,
+                actual: q_matrix.len(),
+            });
+        }
+        Ok(Self::with_matrix(Matrix::Dense(q_matrix), q))
+    }
+
+    /// Constructs a quadratic cost with a sparse matrix in compressed sparse
+    /// row (CSR) format
+    ///
+    /// ## Arguments
+    ///
+    /// - `row_ptr`: the nonzero elements of the $i$-th row of $Q$ are those
+    ///   with indices `row_ptr[i]..row_ptr[i + 1]` in `col_idx` and `values`
+    /// - `col_idx`: column indices of the nonzero elements
+    /// - `values`: values of the nonzero elements
+    /// - `q`: the vector $q$, whose length, $n$, is the number of decision
+    ///   variables
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `row_ptr` does not have
+    /// $n + 1$ elements or if `col_idx` and `values` have different lengths,
+    /// and `SolverError::InvalidParameter` if `row_ptr` does not start at zero,
+    /// is decreasing or does not end at the number of nonzero elements, or if
+    /// a column index is not smaller than $n$
+    pub fn new_sparse(
+        row_ptr: Vec<usize>,
+        col_idx: Vec<usize>,
+        values: Vec<T>,
+        q: Vec<T>,
+    ) -> Result<Self, SolverError> {
+        let n = q.len();
+        if row_ptr.len() != n + 1 {
+            return Err(SolverError::DimensionMismatch {
+                name: "row_ptr",
+                expected: n + 1,
+                actual: row_ptr.len(),
+            });
+        }
+        if values.len() != col_idx.len() {
+            return Err(SolverError::DimensionMismatch {
+                name: "values",
+                expected: col_idx.len(),
+                actual: values.len(),
+            });
+        }
+        ensure(
+            row_ptr[0] == 0
+                && row_ptr.windows(2).all(|w| w[0] <= w[1])
+                && row_ptr[n] == col_idx.len(),
+            "row_ptr",
+            "must be nondecreasing from zero to the number of nonzero elements",
+        )?;
+        ensure(
+            col_idx.iter().all(|&j| j < n),
+            "col_idx",
+            "must be smaller than the number of decision variables",
+        )?;
+        Ok(Self::with_matrix(
+            Matrix::Sparse {
+                row_ptr,
+                col_idx,
+                values,
+            },
+            q,
+        ))
+    }
+
+    fn with_matrix(matrix: Matrix<T>, q: Vec<T>) -> Self {
+        let mut cost = QuadraticCost {
+            matrix,
+            q,
+            lipschitz_constant: T::zero(),
+        };
+        cost.lipschitz_constant = cost.spectral_norm();
+        cost
+    }
+
+    /// Number of decision variables
+    pub fn dimension(&self) -> usize {
+        self.q.len()
+    }
+
+    /// Lipschitz constant of the gradient, that is, $\Vert Q \Vert_2$ (the
+    /// largest absolute value of the eigenvalues of $Q$), as computed by
+    /// power iteration
+    pub fn lipschitz_constant(&self) -> T {
+        self.lipschitz_constant
+    }
+
+    /// Inner product of the $i$-th row of $Q$ with `u`
+    fn row_product(&self, i: usize, u: &[T]) -> T {
+        match &self.matrix {
+            Matrix::Dense(data) => {
+                let n = u.len();
+                matrix_operations::inner_product(&data[i * n..(i + 1) * n], u)
+            }
+            Matrix::Sparse {
+                row_ptr,
+                col_idx,
+                values,
+            } => (row_ptr[i]..row_ptr[i + 1])
+                .fold(T::zero(), |sum, k| sum + values[k] * u[col_idx[k]]),
+        }
+    }
+
+    /// Computes $Qv$
+    fn multiply(&self, v: &[T], product: &mut [T]) {
+        product
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, product_i)| *product_i = self.row_product(i, v));
+    }
+
+    /// Computes $\Vert Q \Vert_2$ by power iteration (on $Q^\top Q = Q^2$)
+    fn spectral_norm(&self) -> T {
+        let n = self.dimension();
+        let (mut v, mut q_v) = (vec![T::zero(); n], vec![T::zero(); n]);
+        let multiply = |v: &[T], product: &mut [T]| -> FunctionCallResult {
+            self.multiply(v, product);
+            Ok(())
+        };
+        // the products do not fail, so the only error is a norm which is not
+        // finite (if Q has elements which are not finite)
+        linear_operator::power_iteration(
+            &mut v,
+            &mut q_v,
+            multiply,
+            multiply,
+            MAX_POWER_ITERATIONS,
+            T::from_f64(POWER_ITERATION_TOLERANCE),
+        )
+        .unwrap_or(T::infinity())
+    }
+
+    fn check_dimension(&self, name: &'static str, actual: usize) -> FunctionCallResult {
+        if actual == self.dimension() {
+            Ok(())
+        } else {
+            Err(SolverError::DimensionMismatch {
+                name,
+                expected: self.dimension(),
+                actual,
+            })
+        }
+    }
+
+    /// Computes the cost, $\tfrac{1}{2}u^\top Q u + q^\top u$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` does not have `n`
+    /// elements
+    pub fn cost(&self, u: &[T], cost: &mut T) -> FunctionCallResult {
+        self.check_dimension("u", u.len())?;
+        let half = T::from_f64(0.5);
+        *cost = (0..u.len()).fold(T::zero(), |sum, i| {
+            sum + u[i] * (half * self.row_product(i, u) + self.q[i])
+        });
+        Ok(())
+    }
+
+    /// Computes the gradient of the cost, $Qu + q$
+    ///
+    /// ## Errors
+    ///
+    /// Returns `SolverError::DimensionMismatch` if `u` or `grad` do not have
+    /// `n` elements
+    pub fn gradient(&self, u: &[T], grad: &mut [T]) -> FunctionCallResult {
+        self.check_dimension("u", u.len())?;
+        self.check_dimension("grad", grad.len())?;
+        self.multiply(u, grad);
+        grad.iter_mut()
+            .zip(self.q.iter())
+            .for_each(|(grad_i, &q_i)| *grad_i += q_i);
+        Ok(())
+    }
+
+    /// Constructs the problem of minimizing this cost subject to
+    /// `constraints` (e.g., box constraints)
+    ///
+    /// The gradient and the cost are boxed closures (which are allocated
+    /// once, here), so that the type of the problem can be named (see
+    /// `QuadraticProblem`)
+    pub fn problem<'a, C>(&'a self, constraints: &'a C) -> QuadraticProblem<'a, C, T>
+    where
+        C: Constraint<T>,
+    {
+        Problem::new(
+            constraints,
+            Box::new(move |u: &[T], grad: &mut [T]| self.gradient(u, grad)),
+            Box::new(move |u: &[T], cost: &mut T| self.cost(u, cost)),
+        )
+    }
+}
+
+impl<T: OpEnFloat> GaussNewtonHessian<T> for QuadraticCost<T> {
+    fn dimension(&self) -> usize {
+        QuadraticCost::dimension(self)
+    }
+
+    /// Computes $Qv$ (the Hessian of the cost does not depend on $u$)
+    fn hessian_product(&self, _u: &[T], v: &[T], product: &mut [T]) -> FunctionCallResult {
+        self.check_dimension("v", v.len())?;
+        self.check_dimension("product", product.len())?;
+        self.multiply(v, product);
+        Ok(())
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::Rectangle, panoc::*, Optimizer};
+
+    /// Tridiagonal matrix with 2 on the diagonal and -1 off the diagonal,
+    /// whose eigenvalues are 2 - 2 cos(k pi / (n + 1)), k = 1, ..., n
+    fn tridiagonal(n: usize) -> (Vec<f64>, Vec<usize>, Vec<usize>, Vec<f64>) {
+        let mut dense = vec![0.0; n * n];
+        let (mut row_ptr, mut col_idx, mut values) = (vec![0], vec![], vec![]);
+        for i in 0..n {
+            for j in i.saturating_sub(1)..(i + 2).min(n) {
+                let value = if i == j { 2.0 } else { -1.0 };
+                dense[i * n + j] = value;
+                col_idx.push(j);
+                values.push(value);
+            }
+            row_ptr.push(col_idx.len());
+        }
+        (dense, row_ptr, col_idx, values)
+    }
+
+    #[test]
+    fn t_quadratic_cost_dense_sparse() {
+        let n = 6;
+        let (dense, row_ptr, col_idx, values) = tridiagonal(n);
+        let q = vec![1.0, -2.0, 0.5, 0.0, 3.0, -1.0];
+        let qp_dense = QuadraticCost::new_dense(dense, q.clone()).unwrap();
+        let qp_sparse = QuadraticCost::new_sparse(row_ptr, col_idx, values, q).unwrap();
+        let u = [0.3, -1.0, 2.0, 0.5, 0.0, 1.5];
+        let (mut cost_dense, mut cost_sparse) = (0.0, 0.0);
+        qp_dense.cost(&u, &mut cost_dense).unwrap();
+        qp_sparse.cost(&u, &mut cost_sparse).unwrap();
+        assert!((cost_dense - cost_sparse).abs() < 1e-12);
+
+        let finite_difference =
+            crate::finite_difference::FiniteDifferenceGradient::new(n, |u: &[f64], c: &mut f64| {
+                qp_sparse.cost(u, c)
+            });
+        let (mut grad, mut grad_expected) = ([0.0; 6], [0.0; 6]);
+        qp_dense.gradient(&u, &mut grad).unwrap();
+        finite_difference.gradient(&u, &mut grad_expected).unwrap();
+        unit_test_utils::assert_nearly_equal_array(&grad_expected, &grad, 1e-6, 1e-6, "grad");
+
+        let norm_expected = 2.0 - 2.0 * (6.0 * std::f64::consts::PI / 7.0).cos();
+        assert!((qp_dense.lipschitz_constant() - norm_expected).abs() < 1e-5);
+        assert!((qp_sparse.lipschitz_constant() - norm_expected).abs() < 1e-5);
+
+        // the dominant eigenvector, (1, -2, 1), is orthogonal to constant and
+        // to linearly increasing vectors
+        let qp = QuadraticCost::new_dense(
+            vec![1.0_f64, -1.0, 0.0, -1.0, 2.0, -1.0, 0.0, -1.0, 1.0],
+            vec![0.0; 3],
+        )
+        .unwrap();
+        assert!((qp.lipschitz_constant() - 3.0).abs() < 1e-6);
+
+        assert!(matches!(
+            qp_dense.gradient(&[0.0; 3], &mut grad),
+            Err(SolverError::DimensionMismatch {
+                name: "u",
+                expected: 6,
+                actual: 3,
+            })
+        ));
+    }
+
+    #[test]
+    fn t_quadratic_cost_invalid() {
+        assert!(QuadraticCost::new_dense(vec![1.0; 3], vec![0.0; 2]).is_err());
+        // row_ptr of the wrong length, decreasing, or not ending at nnz
+        assert!(QuadraticCost::new_sparse(vec![0, 1], vec![0], vec![1.0], vec![0.0; 2]).is_err());
+        assert!(
+            QuadraticCost::new_sparse(vec![0, 2, 1], vec![0, 1], vec![1.0; 2], vec![0.0; 2])
+                .is_err()
+        );
+        assert!(
+            QuadraticCost::new_sparse(vec![0, 1, 1], vec![0, 1], vec![1.0; 2], vec![0.0; 2])
+                .is_err()
+        );
+        // column index out of range
+        assert!(
+            QuadraticCost::new_sparse(vec![0, 1, 2], vec![0, 2], vec![1.0; 2], vec![0.0; 2])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn t_quadratic_cost_box_qp() {
+        let n = 20;
+        let (_, row_ptr, col_idx, values) = tridiagonal(n);
+        let q: Vec<f64> = (0..n)
+            .map(|i| if i % 2 == 0 { -1.0 } else { 0.5 })
+            .collect();
+        let qp = QuadraticCost::new_sparse(row_ptr, col_idx, values, q).unwrap();
+        let bounds = Rectangle::new(Some(&[-0.5; 20]), Some(&[0.5; 20])).unwrap();
+        let mut solutions = vec![];
+        let mut gradient_calls = vec![];
+        for fast_path in [false, true] {
+            let mut cache = PANOCCache::new(n, 1e-10, 10).unwrap();
+            let mut optimizer = PANOCOptimizer::new(qp.problem(&bounds), &mut cache)
+                .with_max_iter(2000)
+                .unwrap();
+            if fast_path {
+                optimizer = optimizer
+                    .with_lipschitz_constant(qp.lipschitz_constant())
+                    .unwrap()
+                    .with_gauss_newton(&qp)
+                    .unwrap();
+            }
+            let mut u = vec![0.0; n];
+            let status = optimizer.solve(&mut u).unwrap();
+            assert!(status.has_converged());
+            solutions.push(u);
+            gradient_calls.push(status.oracle_calls().gradient());
+        }
+        // some elements of the solution are zero, so they are compared in the
+        // infinity norm
+        let difference = solutions[0]
+            .iter()
+            .zip(solutions[1].iter())
+            .fold(0.0_f64, |max, (a, b)| max.max((a - b).abs()));
+        assert!(difference < 1e-7);
+        assert!(gradient_calls[1] < gradient_calls[0]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Server for many simultaneous clients
+//!
+//! A [`ConcurrentServer`] serves every connection on its own thread, so that
+//! one solver process can serve several controllers (or experiment workers)
+//! at once. A connection is a session: it carries any number of requests
+//! (one JSON request per line, each answered with one JSON reply per line)
+//! and is bound to a cache, which is taken from a [`CachePool`] when the
+//! client connects and returned when it disconnects, and to its own
+//! warm-start data (initial guess, Lagrange multipliers and penalty). The
+//! requests and replies are those of the [`TcpServer`].
+//!
+//! [`ConcurrentServer`]: struct.ConcurrentServer.html
+//! [`CachePool`]: ../core/struct.CachePool.html
+//! [`TcpServer`]: struct.TcpServer.html
+//!
+use super::{error_message, SharedConfig, TcpServer, MAX_REQUEST_SIZE};
+use crate::{alm::AlmOptimizerStatus, core::CachePool, SolverError};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+/// TCP server which serves a parametric ALM/PM optimizer to many clients at
+/// once
+///
+/// The optimizer is provided as a closure with signature
+///
+/// ```ignore
+/// Fn(p: &[f64], cache: &mut C, u: &mut [f64], y0: Option<&[f64]>, c0: Option<f64>)
+///     -> Result<AlmOptimizerStatus, SolverError>
+/// ```
+///
+/// which is called concurrently (so it must be `Sync`) and solves the problem
+/// for the parameter `p` using the cache of the connection (e.g., an
+/// `AlmCache`), as the closure of a `TcpServer` does.
+///
+/// A `Kill` request closes the connection of the client and stops the server
+/// from accepting connections; the server returns once all connections are
+/// closed.
+///
+/// # Example
+///
+/// ```ignore
+/// let pool = CachePool::for_alm(nu, 1e-6, 5, n1, 0)?;
+/// let server = ConcurrentServer::new(nu, np, n1, pool, |p, cache, u, y0, c0| {
+///     AlmOptimizer::new(cache, make_problem(p)).solve(u)
+/// });
+/// server.run("127.0.0.1:3301")?;
+/// ```
+pub struct ConcurrentServer<C, S> {
+    pool: CachePool<C>,
+    solver: S,
+    num_decision_variables: usize,
+    num_parameters: usize,
+    n1: usize,
+    config: Option<SharedConfig>,
+}
+
+impl<C, S> ConcurrentServer<C, S>
+where
+    C: Send,
+    S: Fn(
+            &[f64],
+            &mut C,
+            &mut [f64],
+            Option<&[f64]>,
+            Option<f64>,
+        ) -> Result<AlmOptimizerStatus, SolverError>
+        + Sync,
+{
+    /// Constructs a new server
+    ///
+    /// # Arguments
+    ///
+    /// - `num_decision_variables`: number of decision variables
+    /// - `num_parameters`: number of parameters
+    /// - `n1`: number of Lagrange multipliers (range dimension of $F_1$)
+    /// - `pool`: pool of the caches of the connections (e.g., from
+    ///   `CachePool::for_alm`)
+    /// - `solver`: closure which solves the problem (see above)
+    ///
+    /// The initial guess of every connection is zero, unless specified by the
+    /// client.
+    pub fn new(
+        num_decision_variables: usize,
+        num_parameters: usize,
+        n1: usize,
+        pool: CachePool<C>,
+        solver: S,
+    ) -> Self {
+        ConcurrentServer {
+            pool,
+            solver,
+            num_decision_variables,
+            num_parameters,
+            n1,
+            config: None,
+        }
+    }
+
+    /// Shares a configuration with the solver, which clients can update with
+    /// `Configure` requests (see `TcpServer::with_config`); the configuration
+    /// is common to all connections
+    pub fn with_config(mut self, config: SharedConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// The pool of caches
+    pub fn pool(&self) -> &CachePool<C> {
+        &self.pool
+    }
+
+    /// Binds to the given address and serves connections until a `Kill`
+    /// request is received
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot bind to the given address or
+    /// accept incoming connections
+    pub fn run<A: ToSocketAddrs>(&self, address: A) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        self.serve(&listener)
+    }
+
+    /// Serves connections on a listener, each on its own thread, until a
+    /// `Kill` request is received and all connections are closed
+    ///
+    /// Connections which fail (e.g., because the client disconnects while
+    /// the reply is written) are closed and the server keeps serving.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot accept incoming connections
+    pub fn serve(&self, listener: &TcpListener) -> io::Result<()> {
+        let address = listener.local_addr()?;
+        let stopped = &AtomicBool::new(false);
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Acquire) {
+                    break;
+                }
+                let stream = stream?;
+                scope.spawn(move || {
+                    if let Ok(true) = self.serve_connection(stream) {
+                        stopped.store(true, Ordering::Release);
+                        // wake up the thread which accepts connections
+                        let _ = TcpStream::connect(address);
+                    }
+                });
+            }
+            Ok(())
+        })
+    }
+
+    /// Serves the requests of a connection until the client disconnects;
+    /// returns whether the client has requested to kill the server
+    fn serve_connection(&self, stream: TcpStream) -> io::Result<bool> {
+        let mut writer = stream.try_clone()?;
+        let mut cache = match self.pool.acquire() {
+            Ok(cache) => cache,
+            Err(error) => {
+                let reply = error_message(2000, &format!("no cache available ({})", error));
+                writeln!(writer, "{}", reply)?;
+                return Ok(false);
+            }
+        };
+        let solver = &self.solver;
+        let mut server = TcpServer::new(
+            self.num_decision_variables,
+            self.num_parameters,
+            self.n1,
+            |p: &[f64], u: &mut [f64], y0, c0| solver(p, &mut cache, u, y0, c0),
+        );
+        if let Some(config) = &self.config {
+            server = server.with_config(config.clone());
+        }
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let size = (&mut reader)
+                .take(MAX_REQUEST_SIZE as u64)
+                .read_line(&mut line)?;
+            if size == 0 {
+                return Ok(false);
+            }
+            if size == MAX_REQUEST_SIZE && !line.ends_with('\n') {
+                writeln!(writer, "{}", error_message(1100, "Request is too large"))?;
+                return Ok(false);
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            match server.handle_request(&line) {
+                Some(reply) => writeln!(writer, "{}", reply)?,
+                None => return Ok(true),
+            }
+        }
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alm::*, constraints::*, core::panoc::PANOCCache, FunctionCallResult};
+    use serde_json::Value;
+
+    /// Session of a client: sends requests and reads their replies
+    struct Session {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Session {
+        fn connect(address: std::net::SocketAddr) -> Session {
+            let writer = TcpStream::connect(address).unwrap();
+            Session {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+            }
+        }
+
+        fn request(&mut self, request: &str) -> Value {
+            writeln!(self.writer, "{}", request).unwrap();
+            let mut reply = String::new();
+            self.reader.read_line(&mut reply).unwrap();
+            serde_json::from_str(&reply).unwrap()
+        }
+    }
+
+    #[test]
+    fn t_concurrent_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let pool = CachePool::new(|| Ok(AlmCache::new(PANOCCache::new(2, 1e-6, 5)?, 0, 0)));
+        let server = ConcurrentServer::new(2, 1, 0, pool, |p: &[f64], cache, u, _y0, _c0| {
+            let p0 = p[0];
+            let alm_problem = AlmProblem::new(
+                Ball2::new(None, 1.0)?,
+                NO_SET,
+                NO_SET,
+                move |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+                    *c = (u[0] - p0).powi(2) + u[1].powi(2);
+                    Ok(())
+                },
+                move |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+                    g[0] = 2.0 * (u[0] - p0);
+                    g[1] = 2.0 * u[1];
+                    Ok(())
+                },
+                NO_MAPPING,
+                NO_MAPPING,
+                0,
+                0,
+            )?;
+            AlmOptimizer::new(cache, alm_problem).solve(u)
+        });
+
+        let clients = thread::spawn(move || {
+            // two sessions which are open at the same time, each with its own
+            // warm-start data
+            let mut first = Session::connect(address);
+            let mut second = Session::connect(address);
+            let reply = first.request(r#"{"Run": {"parameter": [0.5]}}"#);
+            assert_eq!("Converged", reply["exit_status"]);
+            let reply = second.request(r#"{"Run": {"parameter": [-0.25]}}"#);
+            let u0 = reply["solution"][0].as_f64().unwrap();
+            unit_test_utils::assert_nearly_equal(-0.25, u0, 1e-5, 1e-5, "u0");
+            let u0 = first.request(r#"{"Status": 1}"#)["initial_guess"][0]
+                .as_f64()
+                .unwrap();
+            unit_test_utils::assert_nearly_equal(0.5, u0, 1e-5, 1e-5, "u0");
+            assert_eq!(
+                3003,
+                second.request(r#"{"Run": {"parameter": []}}"#)["code"]
+            );
+            assert_eq!(1000, first.request("not json")["code"]);
+            assert_eq!(
+                Value::Null,
+                Session::connect(address).request(r#"{"Status": 2}"#)["last_solution"]
+            );
+            drop(second);
+            writeln!(first.writer, r#"{{"Kill": 1}}"#).unwrap();
+        });
+
+        server.serve(&listener).unwrap();
+        clients.join().unwrap();
+        // the caches of the closed connections are back in the pool
+        assert!(server.pool().idle() >= 2);
+    }
+}
@@ -0,0 +1,493 @@
+//! gRPC transport
+//!
+//! With the feature `grpc`, a parametric ALM/PM optimizer can be served as
+//! the gRPC service `OptimizerService` (package `open.solver`), which is
+//! defined in `proto/open_solver.proto`, so that it can be integrated in
+//! microservice architectures and called with the gRPC client of any
+//! language. The remote procedures are
+//!
+//! | RPC                | Request                                   | Reply                         |
+//! |--------------------|-------------------------------------------|-------------------------------|
+//! | `Solve`            | initial guess, multipliers and penalty    | solution and solver status    |
+//! | `UpdateParameters` | parameter                                 | none                          |
+//! | `GetStatus`        | none                                      | status of the last solve      |
+//!
+//! The parameter which is set with `UpdateParameters` is used by all
+//! subsequent calls of `Solve`. The fields of a `Solve` request are optional:
+//! if no initial guess (or no initial Lagrange multipliers) are given, the
+//! solver is warm-started from the previous solution (and its multipliers),
+//! and if no initial penalty is given, the default one is used. Invalid
+//! requests are answered with the status `INVALID_ARGUMENT` (wrong
+//! dimensions), a `Solve` before the parameter is set with
+//! `FAILED_PRECONDITION`, a `GetStatus` before the first solve with
+//! `NOT_FOUND`, and solver errors with `INTERNAL`.
+//!
+//! The messages, as well as a client, are in [`proto`](proto/index.html);
+//! they are generated from `proto/open_solver.proto` with
+//! `tonic-prost-build`. Calls of `Solve` are executed one at a time, on a
+//! thread on which blocking is allowed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use optimization_engine::{alm::*, constraints::*, panoc::*, server::grpc::GrpcServer, *};
+//!
+//! let (nu, np) = (2, 1);
+//! let mut cache = AlmCache::new(PANOCCache::new(nu, 1e-6, 5)?, 0, 0);
+//! let server = GrpcServer::new(nu, np, 0, move |p: &[f64], u: &mut [f64], _y0, _c0| {
+//!     let p0 = p[0];
+//!     let problem = AlmProblem::new(
+//!         Ball2::new(None, 1.0)?,
+//!         NO_SET,
+//!         NO_SET,
+//!         move |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+//!             *c = (u[0] - p0).powi(2) + u[1].powi(2);
+//!             Ok(())
+//!         },
+//!         move |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+//!             g[0] = 2.0 * (u[0] - p0);
+//!             g[1] = 2.0 * u[1];
+//!             Ok(())
+//!         },
+//!         NO_MAPPING,
+//!         NO_MAPPING,
+//!         0,
+//!         0,
+//!     )?;
+//!     AlmOptimizer::new(&mut cache, problem).solve(u)
+//! });
+//! server.run("127.0.0.1:50051")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use crate::{alm::AlmOptimizerStatus, core::ExitStatus, SolverError};
+use std::{
+    io,
+    net::ToSocketAddrs,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+use tonic::{Request, Response, Status};
+
+/// Messages, client and server of the service `OptimizerService`
+///
+/// This module is generated from `proto/open_solver.proto` with
+/// `tonic-prost-build`.
+#[allow(missing_docs, clippy::all, clippy::pedantic)]
+pub mod proto {
+    include!("grpc/open.solver.rs");
+}
+
+use proto::optimizer_service_server::{OptimizerService, OptimizerServiceServer};
+
+/// State of the server, which is shared by the calls of the service
+struct State<S> {
+    solver: S,
+    /// initial guess of the next solve (the previous solution)
+    u: Vec<f64>,
+    /// parameter (`None` until it is set by `UpdateParameters`)
+    parameter: Option<Vec<f64>>,
+    /// Lagrange multipliers of the previous solution
+    y: Option<Vec<f64>>,
+    /// status of the last solve, or the message of its error
+    last_status: Option<Result<proto::SolverStatus, String>>,
+}
+
+/// gRPC server which serves a parametric ALM/PM optimizer
+///
+/// The optimizer is provided as a closure with the same signature as that
+/// of a `TcpServer`,
+///
+/// ```ignore
+/// FnMut(p: &[f64], u: &mut [f64], y0: Option<&[f64]>, c0: Option<f64>)
+///     -> Result<AlmOptimizerStatus, SolverError>
+/// ```
+///
+/// which typically constructs an `AlmProblem` for the parameter `p` and
+/// solves it with an `AlmOptimizer` whose cache it owns. Since the service is
+/// called from the threads of an asynchronous runtime, the closure must be
+/// `Send` and `'static`.
+pub struct GrpcServer<S> {
+    state: Arc<Mutex<State<S>>>,
+    num_parameters: usize,
+    n1: usize,
+}
+
+impl<S> GrpcServer<S>
+where
+    S: FnMut(
+            &[f64],
+            &mut [f64],
+            Option<&[f64]>,
+            Option<f64>,
+        ) -> Result<AlmOptimizerStatus, SolverError>
+        + Send
+        + 'static,
+{
+    /// Constructs a new server
+    ///
+    /// # Arguments
+    ///
+    /// - `num_decision_variables`: number of decision variables
+    /// - `num_parameters`: number of parameters
+    /// - `n1`: number of Lagrange multipliers (range dimension of $F_1$)
+    /// - `solver`: closure which solves the problem (see above)
+    ///
+    /// The initial guess is zero, unless specified by the client. If there
+    /// are no parameters, `Solve` can be called without `UpdateParameters`.
+    pub fn new(num_decision_variables: usize, num_parameters: usize, n1: usize, solver: S) -> Self {
+        GrpcServer {
+            state: Arc::new(Mutex::new(State {
+                solver,
+                u: vec![0.0; num_decision_variables],
+                parameter: if num_parameters == 0 {
+                    Some(vec![])
+                } else {
+                    None
+                },
+                y: None,
+                last_status: None,
+            })),
+            num_parameters,
+            n1,
+        }
+    }
+
+    /// The service, e.g., to be added to a `tonic::transport::Server` (with
+    /// other services) which is run on an existing runtime
+    pub fn into_service(self) -> OptimizerServiceServer<Self> {
+        OptimizerServiceServer::new(self)
+    }
+
+    /// Serves the optimizer at the given address on a new (multi-threaded)
+    /// tokio runtime; this function blocks
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the address cannot be resolved or bound, or if
+    /// the runtime cannot be started
+    pub fn run<A: ToSocketAddrs>(self, address: A) -> io::Result<()> {
+        let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "address could not be resolved")
+        })?;
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime
+            .block_on(
+                tonic::transport::Server::builder()
+                    .add_service(self.into_service())
+                    .serve(address),
+            )
+            .map_err(io::Error::other)
+    }
+
+    fn state(&self) -> MutexGuard<'_, State<S>> {
+        lock(&self.state)
+    }
+}
+
+fn lock<S>(state: &Mutex<State<S>>) -> MutexGuard<'_, State<S>> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<S> State<S>
+where
+    S: FnMut(
+        &[f64],
+        &mut [f64],
+        Option<&[f64]>,
+        Option<f64>,
+    ) -> Result<AlmOptimizerStatus, SolverError>,
+{
+    fn solve(
+        &mut self,
+        request: &proto::SolveRequest,
+        n1: usize,
+    ) -> Result<proto::SolveReply, Status> {
+        if !request.initial_guess.is_empty() && request.initial_guess.len() != self.u.len() {
+            return Err(Status::invalid_argument(
+                "Initial guess has incompatible dimensions",
+            ));
+        }
+        if !request.initial_lagrange_multipliers.is_empty()
+            && request.initial_lagrange_multipliers.len() != n1
+        {
+            return Err(Status::invalid_argument(
+                "wrong dimension of Lagrange multipliers",
+            ));
+        }
+        let parameter = self.parameter.as_deref().ok_or_else(|| {
+            Status::failed_precondition("the parameter has not been set (see UpdateParameters)")
+        })?;
+        if !request.initial_guess.is_empty() {
+            self.u.copy_from_slice(&request.initial_guess);
+        }
+        let y0 = if request.initial_lagrange_multipliers.is_empty() {
+            self.y.as_deref()
+        } else {
+            Some(&request.initial_lagrange_multipliers[..])
+        };
+        match (self.solver)(parameter, &mut self.u, y0, request.initial_penalty) {
+            Ok(status) => {
+                self.y = status.lagrange_multipliers().clone();
+                let status = solver_status(&status);
+                self.last_status = Some(Ok(status.clone()));
+                Ok(proto::SolveReply {
+                    status: Some(status),
+                    solution: self.u.clone(),
+                })
+            }
+            Err(error) => {
+                let message = format!("Problem solution failed (solver error: {})", error);
+                self.last_status = Some(Err(message.clone()));
+                Err(Status::internal(message))
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<S> OptimizerService for GrpcServer<S>
+where
+    S: FnMut(
+            &[f64],
+            &mut [f64],
+            Option<&[f64]>,
+            Option<f64>,
+        ) -> Result<AlmOptimizerStatus, SolverError>
+        + Send
+        + 'static,
+{
+    async fn solve(
+        &self,
+        request: Request<proto::SolveRequest>,
+    ) -> Result<Response<proto::SolveReply>, Status> {
+        let (state, n1) = (Arc::clone(&self.state), self.n1);
+        let request = request.into_inner();
+        // the solver blocks, so it runs on a thread on which blocking is
+        // allowed, and not on the threads of the runtime
+        tokio::task::spawn_blocking(move || lock(&state).solve(&request, n1))
+            .await
+            .map_err(|error| Status::internal(error.to_string()))?
+            .map(Response::new)
+    }
+
+    async fn update_parameters(
+        &self,
+        request: Request<proto::UpdateParametersRequest>,
+    ) -> Result<Response<proto::UpdateParametersReply>, Status> {
+        let parameter = request.into_inner().parameter;
+        if parameter.len() != self.num_parameters {
+            return Err(Status::invalid_argument("wrong number of parameters"));
+        }
+        self.state().parameter = Some(parameter);
+        Ok(Response::new(proto::UpdateParametersReply {}))
+    }
+
+    async fn get_status(
+        &self,
+        _request: Request<proto::GetStatusRequest>,
+    ) -> Result<Response<proto::SolverStatus>, Status> {
+        match &self.state().last_status {
+            Some(Ok(status)) => Ok(Response::new(status.clone())),
+            Some(Err(message)) => Err(Status::internal(message.clone())),
+            None => Err(Status::not_found("no problem has been solved")),
+        }
+    }
+}
+
+fn solver_status(status: &AlmOptimizerStatus) -> proto::SolverStatus {
+    let exit_status = match status.exit_status() {
+        ExitStatus::Converged => proto::ExitStatus::Converged,
+        ExitStatus::NotConvergedIterations => proto::ExitStatus::NotConvergedIterations,
+        ExitStatus::NotConvergedOutOfTime => proto::ExitStatus::NotConvergedOutOfTime,
+        ExitStatus::Diverged => proto::ExitStatus::Diverged,
+    };
+    proto::SolverStatus {
+        exit_status: exit_status as i32,
+        num_outer_iterations: status.num_outer_iterations() as u64,
+        num_inner_iterations: status.num_inner_iterations() as u64,
+        last_problem_norm_fpr: status.last_problem_norm_fpr(),
+        delta_y_norm_over_c: status.delta_y_norm_over_c(),
+        f2_norm: status.f2_norm(),
+        solve_time_ms: (status.solve_time().as_nanos() as f64) / 1e6,
+        penalty: status.penalty(),
+        cost: status.cost(),
+        lagrange_multipliers: status
+            .lagrange_multipliers()
+            .as_deref()
+            .unwrap_or(&[])
+            .to_vec(),
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alm::*, constraints::Ball2, panoc::PANOCCache, FunctionCallResult};
+
+    type Solver = Box<
+        dyn FnMut(
+                &[f64],
+                &mut [f64],
+                Option<&[f64]>,
+                Option<f64>,
+            ) -> Result<AlmOptimizerStatus, SolverError>
+            + Send,
+    >;
+
+    /// Minimizes (u0 - p0)^2 + u1^2 subject to |u| <= 1
+    fn server() -> GrpcServer<Solver> {
+        let mut cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 0, 0);
+        GrpcServer::new(
+            2,
+            1,
+            0,
+            Box::new(move |p: &[f64], u: &mut [f64], _y0, _c0| {
+                if p[0].is_nan() {
+                    return Err(SolverError::user("not solved"));
+                }
+                let p0 = p[0];
+                let f = move |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+                    *c = (u[0] - p0).powi(2) + u[1].powi(2);
+                    Ok(())
+                };
+                let df = move |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+                    g[0] = 2.0 * (u[0] - p0);
+                    g[1] = 2.0 * u[1];
+                    Ok(())
+                };
+                let problem = AlmProblem::new(
+                    Ball2::new(None, 1.0)?,
+                    NO_SET,
+                    NO_SET,
+                    f,
+                    df,
+                    NO_MAPPING,
+                    NO_MAPPING,
+                    0,
+                    0,
+                )?;
+                AlmOptimizer::new(&mut cache, problem).solve(u)
+            }),
+        )
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Runtime::new().unwrap().block_on(future)
+    }
+
+    fn update_parameters(server: &GrpcServer<Solver>, parameter: Vec<f64>) -> Result<(), Status> {
+        let request = Request::new(proto::UpdateParametersRequest { parameter });
+        block_on(server.update_parameters(request)).map(|_| ())
+    }
+
+    #[test]
+    fn t_grpc_service() {
+        let server = server();
+        let solve = |request: proto::SolveRequest| {
+            block_on(server.solve(Request::new(request))).map(Response::into_inner)
+        };
+        let get_status = || {
+            block_on(server.get_status(Request::new(proto::GetStatusRequest {})))
+                .map(Response::into_inner)
+        };
+        assert_eq!(tonic::Code::NotFound, get_status().unwrap_err().code());
+        assert_eq!(
+            tonic::Code::FailedPrecondition,
+            solve(proto::SolveRequest::default()).unwrap_err().code()
+        );
+        assert_eq!(
+            tonic::Code::InvalidArgument,
+            update_parameters(&server, vec![0.5, 1.0])
+                .unwrap_err()
+                .code()
+        );
+
+        update_parameters(&server, vec![0.5]).unwrap();
+        let reply = solve(proto::SolveRequest::default()).unwrap();
+        let status = reply.status.unwrap();
+        assert_eq!(proto::ExitStatus::Converged as i32, status.exit_status);
+        assert!((reply.solution[0] - 0.5).abs() < 1e-6 && reply.solution[1].abs() < 1e-6);
+        assert_eq!(status, get_status().unwrap());
+
+        // the parameter is kept, and the solution is on the boundary
+        update_parameters(&server, vec![3.0]).unwrap();
+        let reply = solve(proto::SolveRequest {
+            initial_guess: vec![0.0, 0.5],
+            ..Default::default()
+        })
+        .unwrap();
+        assert!((reply.solution[0] - 1.0).abs() < 1e-6);
+        assert_eq!(
+            tonic::Code::InvalidArgument,
+            solve(proto::SolveRequest {
+                initial_guess: vec![0.0; 3],
+                ..Default::default()
+            })
+            .unwrap_err()
+            .code()
+        );
+        assert_eq!(
+            tonic::Code::InvalidArgument,
+            solve(proto::SolveRequest {
+                initial_lagrange_multipliers: vec![1.0],
+                ..Default::default()
+            })
+            .unwrap_err()
+            .code()
+        );
+
+        // solver errors are also reported by GetStatus
+        update_parameters(&server, vec![f64::NAN]).unwrap();
+        let error = solve(proto::SolveRequest::default()).unwrap_err();
+        assert_eq!(tonic::Code::Internal, error.code());
+        assert!(error.message().contains("not solved"));
+        assert_eq!(tonic::Code::Internal, get_status().unwrap_err().code());
+    }
+
+    #[test]
+    fn t_grpc_client() {
+        use proto::optimizer_service_client::OptimizerServiceClient;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (shutdown, shutdown_signal) = tokio::sync::oneshot::channel::<()>();
+        let serving = runtime.spawn(
+            tonic::transport::Server::builder()
+                .add_service(server().into_service())
+                .serve_with_shutdown(address, async {
+                    shutdown_signal.await.ok();
+                }),
+        );
+        runtime.block_on(async {
+            let mut client = loop {
+                match OptimizerServiceClient::connect(format!("http://{}", address)).await {
+                    Ok(client) => break client,
+                    Err(_) => tokio::task::yield_now().await,
+                }
+            };
+            client
+                .update_parameters(proto::UpdateParametersRequest {
+                    parameter: vec![-0.25],
+                })
+                .await
+                .unwrap();
+            let reply = client
+                .solve(proto::SolveRequest::default())
+                .await
+                .unwrap()
+                .into_inner();
+            assert!((reply.solution[0] + 0.25).abs() < 1e-6);
+            let status = client
+                .get_status(proto::GetStatusRequest {})
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(reply.status.unwrap(), status);
+        });
+        shutdown.send(()).unwrap();
+        runtime.block_on(serving).unwrap().unwrap();
+    }
+}
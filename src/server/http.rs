@@ -0,0 +1,359 @@
+//! HTTP (REST) transport
+//!
+//! With the feature `http`, a [`TcpServer`](../struct.TcpServer.html) can
+//! also be called over HTTP/1.1, e.g., from web dashboards, with `curl` or
+//! with the HTTP client of any scripting language. The routes are
+//!
+//! | Route          | Body                                     | Reply                              |
+//! |----------------|------------------------------------------|------------------------------------|
+//! | `POST /solve`  | `{"parameter": [...], ...}`              | solution and solver statistics     |
+//! | `GET /status`  | none                                     | reply to a `Status` request        |
+//! | `GET /schema`  | none                                     | JSON schema of the requests        |
+//! | `POST /kill`   | none                                     | `{"type": "Ok"}` (the server stops)|
+//!
+//! The body of `POST /solve` has the fields of a `Run` request of the TCP
+//! server (`parameter`, and optionally `initial_guess`,
+//! `initial_lagrange_multipliers` and `initial_penalty`) and the replies are
+//! the JSON replies of the TCP server; it is validated against the schema of
+//! `Run` (so the `path` of errors is relative to the body). Errors are answered with the status
+//! `400 Bad Request` (invalid requests), `404 Not Found` (unknown routes),
+//! `405 Method Not Allowed` or `500 Internal Server Error` (solver errors).
+//! The replies allow cross-origin requests, so that they can be read by
+//! scripts of web pages. Every connection carries one request.
+//!
+use super::{error_message, schema, ExecutionParameter, TcpServer};
+use crate::{alm::AlmOptimizerStatus, SolverError};
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+/// Maximum size of the request line and the headers in bytes
+const MAX_HEADER_SIZE: u64 = 1 << 16;
+
+/// Maximum size of a body in bytes (larger requests are rejected)
+const MAX_BODY_SIZE: usize = 1 << 24;
+
+/// An HTTP request
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads the request line, the headers and the body (of the length given by
+/// `Content-Length`) of a request
+fn read_request(stream: &mut impl BufRead) -> io::Result<Request> {
+    let mut head = stream.take(MAX_HEADER_SIZE);
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+    let mut words = line.split_whitespace();
+    let (method, path) = match (words.next(), words.next(), words.next()) {
+        (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/1.") => {
+            (method.to_string(), path.to_string())
+        }
+        _ => return Err(protocol_error("invalid request line")),
+    };
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if head.read_line(&mut line)? == 0 {
+            return Err(protocol_error("incomplete headers"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| protocol_error("invalid header"))?;
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .trim()
+                .parse()
+                .map_err(|_| protocol_error("invalid content length"))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(protocol_error("unsupported transfer encoding"));
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(protocol_error("body too large"));
+    }
+    let mut body = vec![0_u8; content_length];
+    head.into_inner().read_exact(&mut body)?;
+    Ok(Request { method, path, body })
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+fn write_response(stream: &mut impl Write, status: u16, body: &[u8]) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        reason_phrase(status),
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Status of the reply to `POST /solve`, given by its error code (if any)
+fn solve_status(reply: &Value) -> u16 {
+    match reply["code"].as_i64() {
+        None => 200,
+        Some(2000) => 500,
+        Some(_) => 400,
+    }
+}
+
+impl<S> TcpServer<S>
+where
+    S: FnMut(
+        &[f64],
+        &mut [f64],
+        Option<&[f64]>,
+        Option<f64>,
+    ) -> Result<AlmOptimizerStatus, SolverError>,
+{
+    /// Binds to the given address and serves HTTP requests until a
+    /// `POST /kill` request is received (requires the feature `http`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot bind to the given address or
+    /// accept incoming connections
+    pub fn run_http<A: ToSocketAddrs>(&mut self, address: A) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        self.serve_http(&listener)
+    }
+
+    /// Serves HTTP requests on a listener until a `POST /kill` request is
+    /// received (requires the feature `http`)
+    ///
+    /// The requests are served one at a time. Connections which fail while
+    /// the request is read or the reply is written are dropped and the
+    /// server keeps serving.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot accept incoming connections
+    pub fn serve_http(&mut self, listener: &TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            if !self.serve_http_connection(stream?) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serves a connection; returns `false` if a `POST /kill` request is
+    /// received
+    fn serve_http_connection(&mut self, stream: TcpStream) -> bool {
+        let mut reader = BufReader::new(stream);
+        let (status, reply, keep_serving) = match read_request(&mut reader) {
+            Ok(request) => self.http_reply(&request),
+            Err(_) => (400, error_message(1000, "Invalid request"), true),
+        };
+        let body = if status == 204 {
+            Vec::new()
+        } else {
+            reply.to_string().into_bytes()
+        };
+        // the client may have disconnected; keep serving
+        let _ = write_response(reader.get_mut(), status, &body);
+        keep_serving
+    }
+
+    /// Parameters of the run which is requested by the body of `POST /solve`
+    /// (which is validated against the schema of `Run`), or the error reply
+    fn execution_parameter(&self, body: &[u8]) -> Result<ExecutionParameter, Value> {
+        let invalid_request = |_| error_message(1000, "Invalid request");
+        let body = serde_json::from_slice::<Value>(body).map_err(invalid_request)?;
+        schema::validate(&self.schema["properties"]["Run"], &body, "")
+            .map_err(|violation| violation.reply())?;
+        serde_json::from_value(body).map_err(invalid_request)
+    }
+
+    /// Status and body of the reply to a request, and whether the server
+    /// keeps serving
+    fn http_reply(&mut self, request: &Request) -> (u16, Value, bool) {
+        let path = request.path.split('?').next().unwrap_or_default();
+        match (request.method.as_str(), path) {
+            ("POST", "/solve") => match self.execution_parameter(&request.body) {
+                Ok(execution_parameter) => {
+                    let reply = self.run_solver(&execution_parameter);
+                    (solve_status(&reply), reply, true)
+                }
+                Err(reply) => (400, reply, true),
+            },
+            ("GET", "/status") => (200, self.status(), true),
+            ("GET", "/schema") => (200, self.schema.clone(), true),
+            ("POST", "/kill") => (200, json!({ "type": "Ok" }), false),
+            ("OPTIONS", _) => (204, Value::Null, true),
+            (_, "/solve") | (_, "/status") | (_, "/schema") | (_, "/kill") => {
+                (405, error_message(1000, "Method not allowed"), true)
+            }
+            _ => (404, error_message(1000, "Not found"), true),
+        }
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alm::*, constraints::*, core::panoc::PANOCCache, FunctionCallResult};
+
+    /// Minimal HTTP client: returns the status and the body of the reply
+    fn request(
+        address: std::net::SocketAddr,
+        method: &str,
+        path: &str,
+        body: &str,
+    ) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        let (head, body) = reply.split_once("\r\n\r\n").unwrap();
+        assert!(head.contains("Access-Control-Allow-Origin: *"));
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = serde_json::from_str(body).unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    #[test]
+    fn t_http_read_request() {
+        let mut reader =
+            &b"POST /solve HTTP/1.1\r\nHost: x\r\ncontent-length: 4\r\n\r\nbodyrest"[..];
+        let request = read_request(&mut reader).unwrap();
+        assert_eq!(
+            Request {
+                method: "POST".to_string(),
+                path: "/solve".to_string(),
+                body: b"body".to_vec(),
+            },
+            request
+        );
+        assert_eq!(b"rest", reader);
+
+        for invalid in [
+            &b"GET /status\r\n\r\n"[..],
+            b"GET /status HTTP/1.1\r\nHost: x\r\n",
+            b"GET /status HTTP/1.1\r\nHost\r\n\r\n",
+            b"POST /solve HTTP/1.1\r\nContent-Length: -1\r\n\r\n",
+            b"POST /solve HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"POST /solve HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort",
+        ] {
+            assert!(read_request(&mut &invalid[..]).is_err());
+        }
+    }
+
+    #[test]
+    fn t_http_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let (status, reply) = request(address, "POST", "/solve", r#"{"parameter": [0.5]}"#);
+            assert_eq!(200, status);
+            assert_eq!("Converged", reply["exit_status"]);
+            let u0 = reply["solution"][0].as_f64().unwrap();
+            unit_test_utils::assert_nearly_equal(0.5, u0, 1e-5, 1e-5, "u0");
+
+            let (status, reply) = request(address, "GET", "/status", "");
+            assert_eq!(200, status);
+            assert_eq!(
+                json!([2, 1, 0]),
+                json!([
+                    reply["num_decision_variables"],
+                    reply["num_parameters"],
+                    reply["n1"]
+                ])
+            );
+            let u0 = reply["initial_guess"][0].as_f64().unwrap();
+            unit_test_utils::assert_nearly_equal(0.5, u0, 1e-5, 1e-5, "u0");
+
+            let (status, reply) = request(address, "POST", "/solve", r#"{"parameter": []}"#);
+            assert_eq!((400, 3003), (status, reply["code"].as_i64().unwrap()));
+            let (status, reply) = request(address, "POST", "/solve", r#"{"initial_guess": null}"#);
+            assert_eq!(
+                (400, "missing field `parameter`"),
+                (status, reply["message"].as_str().unwrap())
+            );
+            let (status, reply) = request(address, "GET", "/schema", "");
+            assert_eq!(
+                (200, 1),
+                (
+                    status,
+                    reply["properties"]["Run"]["properties"]["parameter"]["minItems"]
+                        .as_i64()
+                        .unwrap()
+                )
+            );
+            let (status, reply) = request(address, "POST", "/solve", "not json");
+            assert_eq!((400, 1000), (status, reply["code"].as_i64().unwrap()));
+            assert_eq!(405, request(address, "GET", "/solve", "").0);
+            assert_eq!(404, request(address, "GET", "/", "").0);
+            assert_eq!(204, request(address, "OPTIONS", "/solve", "").0);
+            assert_eq!(200, request(address, "POST", "/kill", "").0);
+        });
+
+        let mut cache = AlmCache::new(PANOCCache::new(2, 1e-6, 5).unwrap(), 0, 0);
+        let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+            let p0 = p[0];
+            let alm_problem = AlmProblem::new(
+                Ball2::new(None, 1.0)?,
+                NO_SET,
+                NO_SET,
+                move |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+                    *c = (u[0] - p0).powi(2) + u[1].powi(2);
+                    Ok(())
+                },
+                move |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+                    g[0] = 2.0 * (u[0] - p0);
+                    g[1] = 2.0 * u[1];
+                    Ok(())
+                },
+                NO_MAPPING,
+                NO_MAPPING,
+                0,
+                0,
+            )?;
+            AlmOptimizer::new(&mut cache, alm_problem).solve(u)
+        });
+        server.serve_http(&listener).unwrap();
+        client.join().unwrap();
+    }
+}
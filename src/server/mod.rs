@@ -0,0 +1,634 @@
+#![deny(missing_docs)]
+//! TCP server for ALM/PM optimizers
+//!
+//! This module (which requires the feature `server`) serves a parametric
+//! optimizer over a TCP socket, so that it can be called from other languages
+//! (e.g., Python or MATLAB) without any foreign function interface. Clients
+//! open a connection, send a JSON request, shut down their write side and
+//! read the JSON reply. The requests are
+//!
+//! | Request                                       | Reply                                |
+//! |-----------------------------------------------|--------------------------------------|
+//! | `{"Run": {"parameter": [...], ...}}`          | solution and solver statistics       |
+//! | `{"InitialGuess": [...]}`                     | `{"type": "Ok"}`                     |
+//! | `{"InitialLagrangeMultipliers": [...]}`       | `{"type": "Ok"}`                     |
+//! | `{"InitialPenalty": c0}`                      | `{"type": "Ok"}`                     |
+//! | `{"Reset": code}`                             | `{"type": "Ok"}`                     |
+//! | `{"Configure": {"epsilon_tolerance": 1e-5}}`  | `{"type": "Ok"}`                     |
+//! | `{"Status": code}`                            | state of the server and last reply   |
+//! | `{"Schema": code}`                            | JSON schema of the requests          |
+//! | `{"Ping": code}`                              | `{"Pong": code}`                     |
+//! | `{"Kill": code}`                              | none (the server stops)              |
+//!
+//! A `Run` request may also specify an `initial_guess`, `initial_lagrange_multipliers`
+//! and an `initial_penalty`; if no initial guess is given, the solver is warm-started
+//! from the previous solution (or from the initial guess which was set with
+//! `InitialGuess`). Initial Lagrange multipliers and penalties which are set with
+//! `InitialLagrangeMultipliers` and `InitialPenalty` are used by all subsequent
+//! runs which do not specify their own, until they are forgotten with `Reset`.
+//! `Configure` updates the given fields of the `AlmConfig` of the server (see
+//! `TcpServer::with_config`) and `Status` returns the dimensions, the warm-start
+//! data, the configuration and the reply to the last `Run` request (`null`
+//! before the first solve). Every request is validated against the JSON schema
+//! of the requests (see [`request_schema`](fn.request_schema.html)), which
+//! has the dimensions of the problem and which `Schema` returns. Invalid
+//! requests are answered with `{"type": "Error", "code": ..., "message": "..."}`;
+//! requests which violate the schema also have a `path`, the JSON pointer of
+//! the offending value (e.g., `"/Run/parameter"` if the parameter has the
+//! wrong dimension, or `"/Run"` if it is missing). The error codes are:
+//!
+//! | Code | Description                                    |
+//! |------|------------------------------------------------|
+//! | 1000 | Invalid request (malformed JSON or UTF-8, or a violation of the schema) |
+//! | 1100 | Request is too large (more than 16 MiB)      |
+//! | 1600 | Initial guess has incompatible dimensions      |
+//! | 1700 | Wrong dimension of Lagrange multipliers        |
+//! | 1800 | Initial penalty is not positive                |
+//! | 1900 | Invalid configuration (or none is shared)      |
+//! | 2000 | Problem solution failed (solver error)         |
+//! | 3003 | Wrong number of parameters                     |
+//!
+//! Requests can also be encoded in [MessagePack](https://msgpack.org) (a binary
+//! encoding of the same messages), which is much faster to parse for long
+//! parameter vectors; such requests are recognized by their first byte (a
+//! MessagePack map) and are answered in MessagePack.
+//!
+//! The protocol is the one of the TCP servers which are generated by `opengen`.
+//!
+//! A `TcpServer` handles one connection at a time and every connection
+//! carries one request. So that a client which stalls (or which never shuts
+//! down its write side) cannot block the server, a request must be received
+//! within a read timeout (10 s by default, see `TcpServer::with_read_timeout`);
+//! otherwise, the connection is dropped. A [`ConcurrentServer`](struct.ConcurrentServer.html)
+//! serves many clients at once: every connection is a session with its own
+//! thread, cache (from a `CachePool`) and warm-start data, which carries one
+//! JSON request per line.
+//!
+//! With the feature `zmq`, the same messages can also be exchanged over
+//! ZeroMQ: `TcpServer::run_zmq` serves `REQ` clients (ZMTP 3.0, `NULL`
+//! security mechanism) without requiring a native ZeroMQ library. With the
+//! feature `http`, `TcpServer::run_http` serves a minimal REST API
+//! (`POST /solve` with the fields of a `Run` request and `GET /status`), so
+//! that the solver can be called from web dashboards and scripting languages
+//! without any client code. With the feature `shm` (Unix only),
+//! `TcpServer::run_shm` exchanges parameters and solutions with co-located
+//! processes through a memory-mapped ring buffer (see [`shm`](shm/index.html)),
+//! with an overhead of microseconds per solve.
+//!
+//! With the feature `grpc`, a [`GrpcServer`](grpc/struct.GrpcServer.html)
+//! serves the optimizer as the gRPC service `OptimizerService`, which is
+//! defined in `proto/open_solver.proto` (see [`grpc`](grpc/index.html)), so
+//! that it can be called with the gRPC client of any language.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use optimization_engine::{alm::*, constraints::*, panoc::*, server::TcpServer, *};
+//!
+//! let (nu, np) = (2, 1);
+//! let mut cache = AlmCache::new(PANOCCache::new(nu, 1e-6, 5)?, 0, 0);
+//! let mut server = TcpServer::new(nu, np, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+//!     // the problem depends on the parameter, p
+//!     let alm_problem = AlmProblem::new(
+//!         Ball2::new(None, 1.0)?,
+//!         NO_SET,
+//!         NO_SET,
+//!         |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+//!             *c = (u[0] - p[0]).powi(2) + u[1].powi(2);
+//!             Ok(())
+//!         },
+//!         |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+//!             g[0] = 2.0 * (u[0] - p[0]);
+//!             g[1] = 2.0 * u[1];
+//!             Ok(())
+//!         },
+//!         NO_MAPPING,
+//!         NO_MAPPING,
+//!         0,
+//!         0,
+//!     )?;
+//!     AlmOptimizer::new(&mut cache, alm_problem).solve(u)
+//! });
+//! server.run("127.0.0.1:3301").expect("server failure");
+//! # Ok::<(), SolverError>(())
+//! ```
+//!
+use crate::{
+    alm::{AlmConfig, AlmOptimizerStatus},
+    SolverError,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+pub use concurrent::ConcurrentServer;
+pub use schema::request_schema;
+
+/// Parameters of a `Run` request
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ExecutionParameter {
+    /// Parameter
+    pub parameter: Vec<f64>,
+    /// Initial guess (can be null)
+    #[serde(default)]
+    pub initial_guess: Option<Vec<f64>>,
+    /// Initial Lagrange multipliers (can be null)
+    #[serde(default)]
+    pub initial_lagrange_multipliers: Option<Vec<f64>>,
+    /// Initial penalty parameter, c0 (can be null)
+    #[serde(default)]
+    pub initial_penalty: Option<f64>,
+}
+
+/// Request from the client
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum ClientRequest {
+    /// Command: run solver
+    Run(ExecutionParameter),
+    /// Command: set the initial guess of the next run
+    InitialGuess(Vec<f64>),
+    /// Command: set the initial Lagrange multipliers of the subsequent runs
+    InitialLagrangeMultipliers(Vec<f64>),
+    /// Command: set the initial penalty parameter of the subsequent runs
+    InitialPenalty(f64),
+    /// Command: forget the initial Lagrange multipliers and penalty
+    Reset(i32),
+    /// Command: update fields of the shared configuration
+    Configure(Map<String, Value>),
+    /// Command: query the state of the server and the last reply to `Run`
+    Status(i32),
+    /// Command: query the JSON schema of the requests
+    Schema(i32),
+    /// Command: ping (check if server is up)
+    Ping(i32),
+    /// Command: kill gracefully
+    Kill(i32),
+}
+
+/// Solution and solution status of the optimizer (reply to `Run`)
+#[derive(Serialize, Debug)]
+struct OptimizerSolution<'a> {
+    exit_status: String,
+    num_outer_iterations: usize,
+    num_inner_iterations: usize,
+    last_problem_norm_fpr: f64,
+    delta_y_norm_over_c: f64,
+    f2_norm: f64,
+    solve_time_ms: f64,
+    penalty: f64,
+    solution: &'a [f64],
+    lagrange_multipliers: &'a [f64],
+    cost: f64,
+}
+
+/// Maximum size of a request in bytes (larger requests are rejected with the
+/// error code 1100)
+const MAX_REQUEST_SIZE: usize = 1 << 24;
+
+/// Default time within which a `TcpServer` must receive a request
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Error reply
+#[derive(Serialize, Debug)]
+struct ErrorMessage<'a> {
+    #[serde(rename = "type")]
+    message_type: &'static str,
+    code: i32,
+    message: &'a str,
+}
+
+/// Configuration of ALM/PM which is shared by a server and its solver
+///
+/// The server updates the configuration on `Configure` requests; the solver
+/// closure reads it with `get` and applies it to its optimizer with
+/// `AlmOptimizer::with_config`. Invalid values are not detected when the
+/// configuration is updated, but make the subsequent runs fail.
+#[derive(Clone, Debug, Default)]
+pub struct SharedConfig {
+    config: Arc<Mutex<AlmConfig>>,
+}
+
+impl SharedConfig {
+    /// Constructs a new shared configuration
+    pub fn new(config: AlmConfig) -> Self {
+        SharedConfig {
+            config: Arc::new(Mutex::new(config)),
+        }
+    }
+
+    fn config(&self) -> MutexGuard<'_, AlmConfig> {
+        self.config.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Current configuration
+    pub fn get(&self) -> AlmConfig {
+        self.config().clone()
+    }
+
+    /// Replaces the configuration
+    pub fn set(&self, config: AlmConfig) {
+        *self.config() = config;
+    }
+}
+
+/// TCP server which serves a parametric ALM/PM optimizer
+///
+/// The optimizer is provided as a closure with signature
+///
+/// ```ignore
+/// FnMut(p: &[f64], u: &mut [f64], y0: Option<&[f64]>, c0: Option<f64>)
+///     -> Result<AlmOptimizerStatus, SolverError>
+/// ```
+///
+/// which solves the problem for the parameter `p`, starting from the initial
+/// guess `u` (which is overwritten with the solution), the initial Lagrange
+/// multipliers `y0` and the initial penalty `c0` (if provided). This closure
+/// typically constructs an `AlmProblem` for the given parameter and solves it
+/// with an `AlmOptimizer` which uses a cache that is allocated once.
+///
+/// The server handles one connection at a time; connections which do not
+/// deliver their request within the read timeout are dropped.
+pub struct TcpServer<S>
+where
+    S: FnMut(
+        &[f64],
+        &mut [f64],
+        Option<&[f64]>,
+        Option<f64>,
+    ) -> Result<AlmOptimizerStatus, SolverError>,
+{
+    solver: S,
+    u: Vec<f64>,
+    num_parameters: usize,
+    n1: usize,
+    y0: Option<Vec<f64>>,
+    c0: Option<f64>,
+    config: Option<SharedConfig>,
+    last_reply: Option<Value>,
+    schema: Value,
+    read_timeout: Option<Duration>,
+}
+
+impl<S> TcpServer<S>
+where
+    S: FnMut(
+        &[f64],
+        &mut [f64],
+        Option<&[f64]>,
+        Option<f64>,
+    ) -> Result<AlmOptimizerStatus, SolverError>,
+{
+    /// Constructs a new server
+    ///
+    /// # Arguments
+    ///
+    /// - `num_decision_variables`: number of decision variables
+    /// - `num_parameters`: number of parameters
+    /// - `n1`: number of Lagrange multipliers (range dimension of $F_1$)
+    /// - `solver`: closure which solves the problem (see above)
+    ///
+    /// The initial guess is zero, unless specified by the client.
+    pub fn new(num_decision_variables: usize, num_parameters: usize, n1: usize, solver: S) -> Self {
+        TcpServer {
+            solver,
+            u: vec![0.0; num_decision_variables],
+            num_parameters,
+            n1,
+            y0: None,
+            c0: None,
+            config: None,
+            last_reply: None,
+            schema: request_schema(num_decision_variables, num_parameters, n1),
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+        }
+    }
+
+    /// JSON schema of the requests to this server (see `request_schema`)
+    pub fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    /// Shares a configuration with the solver, which clients can then update
+    /// with `Configure` requests (which are rejected otherwise)
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let config = SharedConfig::default();
+    /// let solver_config = config.clone();
+    /// let mut server = TcpServer::new(nu, np, n1, move |p, u, y0, c0| {
+    ///     AlmOptimizer::new(&mut alm_cache, make_problem(p))
+    ///         .with_config(&solver_config.get())?
+    ///         .solve(u)
+    /// })
+    /// .with_config(config);
+    /// ```
+    pub fn with_config(mut self, config: SharedConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Time within which a client must send its request (and shut down its
+    /// write side) after it connects; connections which time out are dropped
+    ///
+    /// The default timeout is 10 s; with `None`, the server waits indefinitely,
+    /// so a single stalled client blocks it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the timeout is zero
+    pub fn with_read_timeout(mut self, read_timeout: Option<Duration>) -> Self {
+        assert!(
+            read_timeout != Some(Duration::ZERO),
+            "the read timeout must be positive"
+        );
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Handles a request and returns the reply
+    ///
+    /// Returns `None` if the client has requested to kill the server, otherwise
+    /// the JSON reply to be sent to the client
+    pub fn handle_request(&mut self, request: &str) -> Option<String> {
+        let reply = match serde_json::from_str::<Value>(request) {
+            Ok(request) => self.reply_value(request)?,
+            Err(_) => error_message(1000, "Invalid request"),
+        };
+        Some(reply.to_string())
+    }
+
+    /// Handles a request which is encoded in JSON or in MessagePack and
+    /// returns the reply in the same encoding
+    ///
+    /// Returns `None` if the client has requested to kill the server
+    pub fn handle_message(&mut self, message: &[u8]) -> Option<Vec<u8>> {
+        if !msgpack::is_msgpack(message) {
+            return match std::str::from_utf8(message) {
+                Ok(request) => self.handle_request(request).map(String::into_bytes),
+                Err(_) => Some(
+                    error_message(1000, "Invalid request")
+                        .to_string()
+                        .into_bytes(),
+                ),
+            };
+        }
+        let reply = match msgpack::decode(message) {
+            Ok(request) => self.reply_value(request)?,
+            Err(_) => error_message(1000, "Invalid request"),
+        };
+        Some(msgpack::encode(&reply))
+    }
+
+    /// Binds to the given address and serves requests until a `Kill` request
+    /// is received
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot bind to the given address or
+    /// accept incoming connections
+    pub fn run<A: ToSocketAddrs>(&mut self, address: A) -> std::io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        self.serve(&listener)
+    }
+
+    /// Serves requests on a listener until a `Kill` request is received
+    ///
+    /// Requests which are larger than 16 MiB are answered with the error code
+    /// 1100. Connections which time out or fail while the request is read or
+    /// the reply is written are dropped and the server keeps serving.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot accept incoming connections
+    pub fn serve(&mut self, listener: &TcpListener) -> std::io::Result<()> {
+        for stream in listener.incoming() {
+            let mut stream = stream?;
+            let reply = match read_request(&mut stream, self.read_timeout) {
+                Ok(Some(request)) => self.handle_message(&request),
+                Ok(None) => Some(
+                    error_message(1100, "Request is too large")
+                        .to_string()
+                        .into_bytes(),
+                ),
+                // the client timed out or disconnected
+                Err(_) => continue,
+            };
+            match reply {
+                Some(reply) => {
+                    // the client may have disconnected; keep serving
+                    let _ = stream.write_all(&reply);
+                }
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Reply to a (decoded) request, which is validated against the schema
+    /// (`None` if the server is to be killed)
+    fn reply_value(&mut self, request: Value) -> Option<Value> {
+        if let Err(violation) = schema::validate(&self.schema, &request, "") {
+            return Some(violation.reply());
+        }
+        match serde_json::from_value::<ClientRequest>(request) {
+            Ok(request) => self.reply(request),
+            Err(_) => Some(error_message(1000, "Invalid request")),
+        }
+    }
+
+    /// Reply to a request (`None` if the server is to be killed)
+    fn reply(&mut self, request: ClientRequest) -> Option<Value> {
+        match request {
+            ClientRequest::Run(execution_parameter) => Some(self.run_solver(&execution_parameter)),
+            ClientRequest::InitialGuess(u0) => Some(self.set_initial_guess(&u0)),
+            ClientRequest::InitialLagrangeMultipliers(y0) => {
+                Some(self.set_initial_lagrange_multipliers(y0))
+            }
+            ClientRequest::InitialPenalty(c0) => Some(self.set_initial_penalty(c0)),
+            ClientRequest::Reset(_) => {
+                self.y0 = None;
+                self.c0 = None;
+                Some(json!({ "type": "Ok" }))
+            }
+            ClientRequest::Configure(fields) => Some(self.configure(fields)),
+            ClientRequest::Status(_) => Some(self.status()),
+            ClientRequest::Schema(_) => Some(self.schema.clone()),
+            ClientRequest::Ping(code) => Some(json!({ "Pong": code })),
+            ClientRequest::Kill(_) => None,
+        }
+    }
+
+    fn set_initial_guess(&mut self, u0: &[f64]) -> Value {
+        if u0.len() != self.u.len() {
+            return error_message(1600, "Initial guess has incompatible dimensions");
+        }
+        self.u.copy_from_slice(u0);
+        json!({ "type": "Ok" })
+    }
+
+    fn set_initial_lagrange_multipliers(&mut self, y0: Vec<f64>) -> Value {
+        if y0.len() != self.n1 {
+            return error_message(1700, "wrong dimension of Lagrange multipliers");
+        }
+        self.y0 = Some(y0);
+        json!({ "type": "Ok" })
+    }
+
+    fn set_initial_penalty(&mut self, c0: f64) -> Value {
+        if c0.is_nan() || c0 <= 0.0 {
+            return error_message(1800, "initial penalty must be positive");
+        }
+        self.c0 = Some(c0);
+        json!({ "type": "Ok" })
+    }
+
+    /// Updates the given fields of the shared configuration (all fields are
+    /// checked before any is updated)
+    fn configure(&mut self, fields: Map<String, Value>) -> Value {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return error_message(1900, "the server has no shared configuration"),
+        };
+        let mut value = match serde_json::to_value(config.get()) {
+            Ok(Value::Object(value)) => value,
+            _ => return error_message(1900, "cannot serialize the configuration"),
+        };
+        for (field, field_value) in fields {
+            match value.get_mut(&field) {
+                Some(current) => *current = field_value,
+                None => {
+                    return error_message(1900, &format!("unknown configuration field `{}`", field))
+                }
+            }
+        }
+        match serde_json::from_value::<AlmConfig>(Value::Object(value)) {
+            Ok(new_config) => {
+                config.set(new_config);
+                json!({ "type": "Ok" })
+            }
+            Err(error) => error_message(1900, &format!("invalid configuration ({})", error)),
+        }
+    }
+
+    /// Dimensions, warm-start data, configuration and last reply to `Run`
+    fn status(&self) -> Value {
+        json!({
+            "type": "Ok",
+            "num_decision_variables": self.u.len(),
+            "num_parameters": self.num_parameters,
+            "n1": self.n1,
+            "initial_guess": self.u,
+            "initial_lagrange_multipliers": self.y0,
+            "initial_penalty": self.c0,
+            "config": self.config.as_ref().map(SharedConfig::get),
+            "last_solution": self.last_reply,
+        })
+    }
+
+    fn run_solver(&mut self, execution_parameter: &ExecutionParameter) -> Value {
+        if let Some(u0) = &execution_parameter.initial_guess {
+            if u0.len() != self.u.len() {
+                return error_message(1600, "Initial guess has incompatible dimensions");
+            }
+        }
+        if let Some(y0) = &execution_parameter.initial_lagrange_multipliers {
+            if y0.len() != self.n1 {
+                return error_message(1700, "wrong dimension of Lagrange multipliers");
+            }
+        }
+        if execution_parameter.parameter.len() != self.num_parameters {
+            return error_message(3003, "wrong number of parameters");
+        }
+        if let Some(u0) = &execution_parameter.initial_guess {
+            self.u.copy_from_slice(u0);
+        }
+
+        let status = (self.solver)(
+            &execution_parameter.parameter,
+            &mut self.u,
+            execution_parameter
+                .initial_lagrange_multipliers
+                .as_deref()
+                .or(self.y0.as_deref()),
+            execution_parameter.initial_penalty.or(self.c0),
+        );
+        let reply = match status {
+            Ok(status) => solution_message(&status, &self.u),
+            Err(error) => error_message(
+                2000,
+                &format!("Problem solution failed (solver error: {})", error),
+            ),
+        };
+        self.last_reply = Some(reply.clone());
+        reply
+    }
+}
+
+/// Reads the request from the stream (until the client shuts down its
+/// write side), or `None` if it is larger than `MAX_REQUEST_SIZE`
+fn read_request(
+    stream: &mut TcpStream,
+    read_timeout: Option<Duration>,
+) -> std::io::Result<Option<Vec<u8>>> {
+    stream.set_read_timeout(read_timeout)?;
+    let mut buffer = Vec::new();
+    // one byte more than the limit tells whether the request exceeds it
+    stream
+        .take(MAX_REQUEST_SIZE as u64 + 1)
+        .read_to_end(&mut buffer)?;
+    Ok(if buffer.len() > MAX_REQUEST_SIZE {
+        None
+    } else {
+        Some(buffer)
+    })
+}
+
+fn error_message(code: i32, message: &str) -> Value {
+    serde_json::to_value(ErrorMessage {
+        message_type: "Error",
+        code,
+        message,
+    })
+    .expect("cannot serialize error message")
+}
+
+fn solution_message(status: &AlmOptimizerStatus, solution: &[f64]) -> Value {
+    let solution = OptimizerSolution {
+        exit_status: format!("{:?}", status.exit_status()),
+        num_outer_iterations: status.num_outer_iterations(),
+        num_inner_iterations: status.num_inner_iterations(),
+        last_problem_norm_fpr: status.last_problem_norm_fpr(),
+        delta_y_norm_over_c: status.delta_y_norm_over_c(),
+        f2_norm: status.f2_norm(),
+        solve_time_ms: (status.solve_time().as_nanos() as f64) / 1e6,
+        penalty: status.penalty(),
+        solution,
+        lagrange_multipliers: status.lagrange_multipliers().as_deref().unwrap_or(&[]),
+        cost: status.cost(),
+    };
+    serde_json::to_value(solution).expect("cannot serialize solution")
+}
+
+mod concurrent;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "http")]
+mod http;
+mod msgpack;
+mod schema;
+#[cfg(all(feature = "shm", unix))]
+pub mod shm;
+#[cfg(feature = "zmq")]
+mod zmq;
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,276 @@
+//! MessagePack encoding of server messages
+//!
+//! Messages are converted between [MessagePack](https://msgpack.org) and
+//! `serde_json::Value`, so that both encodings share the same message types.
+//! Only the types which appear in JSON are supported (nil, booleans,
+//! integers, floats, strings, arrays and maps with string keys).
+//!
+use serde_json::{Map, Number, Value};
+
+/// Maximum nesting depth of decoded messages
+const MAX_DEPTH: usize = 32;
+
+/// Error while decoding a MessagePack message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DecodeError;
+
+/// Whether a message is encoded in MessagePack (messages are maps, which
+/// start with a byte which cannot start a JSON document)
+pub(crate) fn is_msgpack(message: &[u8]) -> bool {
+    matches!(message.first(), Some(0x80..=0x8f) | Some(0xde) | Some(0xdf))
+}
+
+/// Encodes a value in MessagePack
+pub(crate) fn encode(value: &Value) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    encode_into(value, &mut buffer);
+    buffer
+}
+
+fn encode_length(len: usize, fix: u8, fix_max: usize, codes: [u8; 3], buffer: &mut Vec<u8>) {
+    if len <= fix_max {
+        buffer.push(fix | len as u8);
+    } else if codes[0] != 0 && len <= u8::MAX as usize {
+        buffer.extend_from_slice(&[codes[0], len as u8]);
+    } else if len <= u16::MAX as usize {
+        buffer.push(codes[1]);
+        buffer.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        buffer.push(codes[2]);
+        buffer.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn encode_into(value: &Value, buffer: &mut Vec<u8>) {
+    match value {
+        Value::Null => buffer.push(0xc0),
+        Value::Bool(b) => buffer.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(x) => {
+            if let Some(n) = x.as_u64().filter(|&n| n < 0x80) {
+                buffer.push(n as u8);
+            } else if let Some(n) = x.as_i64().filter(|&n| (-32..0).contains(&n)) {
+                buffer.push(n as u8);
+            } else if let Some(n) = x.as_u64() {
+                buffer.push(0xcf);
+                buffer.extend_from_slice(&n.to_be_bytes());
+            } else if let Some(n) = x.as_i64() {
+                buffer.push(0xd3);
+                buffer.extend_from_slice(&n.to_be_bytes());
+            } else {
+                let x = x.as_f64().unwrap_or(f64::NAN);
+                buffer.push(0xcb);
+                buffer.extend_from_slice(&x.to_be_bytes());
+            }
+        }
+        Value::String(s) => {
+            encode_length(s.len(), 0xa0, 31, [0xd9, 0xda, 0xdb], buffer);
+            buffer.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(values) => {
+            encode_length(values.len(), 0x90, 15, [0, 0xdc, 0xdd], buffer);
+            values.iter().for_each(|v| encode_into(v, buffer));
+        }
+        Value::Object(map) => {
+            encode_length(map.len(), 0x80, 15, [0, 0xde, 0xdf], buffer);
+            for (key, v) in map {
+                encode_into(&Value::String(key.clone()), buffer);
+                encode_into(v, buffer);
+            }
+        }
+    }
+}
+
+/// Decodes a MessagePack message (which must not contain trailing bytes)
+pub(crate) fn decode(message: &[u8]) -> Result<Value, DecodeError> {
+    let mut decoder = Decoder { data: message };
+    let value = decoder.value(0)?;
+    if decoder.data.is_empty() {
+        Ok(value)
+    } else {
+        Err(DecodeError)
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.data.len() < n {
+            return Err(DecodeError);
+        }
+        let (bytes, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn uint(&mut self, n: usize) -> Result<u64, DecodeError> {
+        Ok(self
+            .bytes(n)?
+            .iter()
+            .fold(0_u64, |acc, &b| (acc << 8) | u64::from(b)))
+    }
+
+    fn int(&mut self, n: usize) -> Result<i64, DecodeError> {
+        let shift = 64 - 8 * n as u32;
+        Ok(((self.uint(n)? << shift) as i64) >> shift)
+    }
+
+    fn string(&mut self, len: usize) -> Result<Value, DecodeError> {
+        let bytes = self.bytes(len)?;
+        let s = std::str::from_utf8(bytes).map_err(|_| DecodeError)?;
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<Value, DecodeError> {
+        // each element takes at least one byte
+        if len > self.data.len() {
+            return Err(DecodeError);
+        }
+        let values = (0..len)
+            .map(|_| self.value(depth + 1))
+            .collect::<Result<Vec<Value>, DecodeError>>()?;
+        Ok(Value::Array(values))
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<Value, DecodeError> {
+        let mut map = Map::new();
+        for _ in 0..len {
+            match self.value(depth + 1)? {
+                Value::String(key) => {
+                    let v = self.value(depth + 1)?;
+                    map.insert(key, v);
+                }
+                _ => return Err(DecodeError),
+            }
+        }
+        Ok(Value::Object(map))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError);
+        }
+        let marker = self.bytes(1)?[0];
+        match marker {
+            0x00..=0x7f => Ok(Value::from(marker)),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize, depth),
+            0x90..=0x9f => self.array((marker & 0x0f) as usize, depth),
+            0xa0..=0xbf => self.string((marker & 0x1f) as usize),
+            0xc0 => Ok(Value::Null),
+            0xc2 => Ok(Value::Bool(false)),
+            0xc3 => Ok(Value::Bool(true)),
+            0xca => {
+                let x = f32::from_bits(self.uint(4)? as u32);
+                Ok(float(f64::from(x)))
+            }
+            0xcb => Ok(float(f64::from_bits(self.uint(8)?))),
+            0xcc => Ok(Value::from(self.uint(1)?)),
+            0xcd => Ok(Value::from(self.uint(2)?)),
+            0xce => Ok(Value::from(self.uint(4)?)),
+            0xcf => Ok(Value::from(self.uint(8)?)),
+            0xd0 => Ok(Value::from(self.int(1)?)),
+            0xd1 => Ok(Value::from(self.int(2)?)),
+            0xd2 => Ok(Value::from(self.int(4)?)),
+            0xd3 => Ok(Value::from(self.int(8)?)),
+            0xd9 => {
+                let len = self.uint(1)? as usize;
+                self.string(len)
+            }
+            0xda => {
+                let len = self.uint(2)? as usize;
+                self.string(len)
+            }
+            0xdb => {
+                let len = self.uint(4)? as usize;
+                self.string(len)
+            }
+            0xdc => {
+                let len = self.uint(2)? as usize;
+                self.array(len, depth)
+            }
+            0xdd => {
+                let len = self.uint(4)? as usize;
+                self.array(len, depth)
+            }
+            0xde => {
+                let len = self.uint(2)? as usize;
+                self.map(len, depth)
+            }
+            0xdf => {
+                let len = self.uint(4)? as usize;
+                self.map(len, depth)
+            }
+            0xe0..=0xff => Ok(Value::from(marker as i8)),
+            // bin, ext and unused markers
+            _ => Err(DecodeError),
+        }
+    }
+}
+
+/// JSON value of a float (NaN and infinite values, which are not valid in
+/// JSON, become null, as in `serde_json`)
+fn float(x: f64) -> Value {
+    Number::from_f64(x).map_or(Value::Null, Value::Number)
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn t_msgpack_roundtrip() {
+        let value = json!({
+            "Run": {
+                "parameter": [1.5, -2.0, 0.0],
+                "initial_guess": null,
+                "initial_penalty": 10,
+                "name": "a string which is longer than thirty-one bytes",
+                "flags": [true, false, -3, -200, 70000, u64::MAX],
+            }
+        });
+        let encoded = encode(&value);
+        assert!(is_msgpack(&encoded));
+        assert_eq!(Ok(value), decode(&encoded));
+        let long_array = Value::from(vec![0.25; 1000]);
+        assert_eq!(Ok(long_array.clone()), decode(&encode(&long_array)));
+    }
+
+    #[test]
+    fn t_msgpack_compact_formats() {
+        // {"Ping": 7} with a fixmap, a fixstr and a positive fixint
+        assert_eq!(
+            Ok(json!({ "Ping": 7 })),
+            decode(&[0x81, 0xa4, b'P', b'i', b'n', b'g', 0x07])
+        );
+        // negative fixint, float32, int8 and uint16
+        assert_eq!(
+            Ok(json!([-1, 0.5, -128, 65535])),
+            decode(&[0x94, 0xff, 0xca, 0x3f, 0x00, 0x00, 0x00, 0xd0, 0x80, 0xcd, 0xff, 0xff])
+        );
+    }
+
+    #[test]
+    fn t_msgpack_invalid() {
+        assert!(!is_msgpack(b"{\"Ping\": 1}"));
+        assert!(!is_msgpack(&[]));
+        assert_eq!(Err(DecodeError), decode(&[]));
+        // truncated string
+        assert_eq!(Err(DecodeError), decode(&[0x81, 0xa4, b'P', b'i']));
+        // trailing bytes
+        assert_eq!(Err(DecodeError), decode(&[0x80, 0x00]));
+        // non-string key
+        assert_eq!(Err(DecodeError), decode(&[0x81, 0x01, 0x02]));
+        // bin 8
+        assert_eq!(Err(DecodeError), decode(&[0xc4, 0x01, 0x00]));
+        // array whose length exceeds the message
+        assert_eq!(Err(DecodeError), decode(&[0xdd, 0xff, 0xff, 0xff, 0xff]));
+        // too deep
+        assert_eq!(Err(DecodeError), decode(&[0x91; 100]));
+    }
+}
@@ -0,0 +1,372 @@
+//! JSON schema of the requests
+//!
+//! The requests of the server are described by a [JSON Schema](https://json-schema.org)
+//! (draft 2020-12), with the dimensions of the served problem, which clients
+//! obtain with a `Schema` request and can use to validate (or generate) their
+//! requests. The server validates every request against the schema before
+//! it is handled, so that malformed requests are answered with an error
+//! which names the offending field (its JSON pointer, in `path`) instead of
+//! a generic "Invalid request".
+//!
+//! The validator supports the keywords which are used by the schema: `type`,
+//! `properties`, `required`, `additionalProperties` (`false`),
+//! `minProperties`, `maxProperties`, `items`, `minItems`, `maxItems`,
+//! `minimum`, `maximum` and `exclusiveMinimum`; other keywords (e.g.,
+//! `description`) are ignored.
+//!
+use super::error_message;
+use serde_json::{json, Value};
+
+/// Violation of the schema by a request
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Violation {
+    /// JSON pointer of the offending value
+    pub(crate) path: String,
+    /// Keyword which is violated
+    pub(crate) keyword: &'static str,
+    /// Description of the violation
+    pub(crate) message: String,
+}
+
+impl Violation {
+    /// Error code of the violation: the codes of the dimension (and penalty)
+    /// errors of the server, and 1000 for other invalid requests
+    pub(crate) fn code(&self) -> i32 {
+        // the fields of `Run` are also the body of `POST /solve`
+        let path = self.path.strip_prefix("/Run").unwrap_or(&self.path);
+        match (self.keyword, path) {
+            ("minItems" | "maxItems", "/parameter") => 3003,
+            ("minItems" | "maxItems", "/initial_guess" | "/InitialGuess") => 1600,
+            (
+                "minItems" | "maxItems",
+                "/initial_lagrange_multipliers" | "/InitialLagrangeMultipliers",
+            ) => 1700,
+            ("exclusiveMinimum", "/initial_penalty" | "/InitialPenalty") => 1800,
+            _ => 1000,
+        }
+    }
+
+    /// Error reply to the request
+    pub(crate) fn reply(&self) -> Value {
+        let mut reply = error_message(self.code(), &self.message);
+        reply["path"] = Value::String(self.path.clone());
+        reply
+    }
+}
+
+/// Schema of an array of `len` numbers (`null` is also accepted if
+/// `nullable`)
+fn vector_schema(len: usize, nullable: bool, description: &str) -> Value {
+    let array_type = if nullable {
+        json!(["array", "null"])
+    } else {
+        json!("array")
+    };
+    json!({
+        "description": description,
+        "type": array_type,
+        "items": { "type": "number" },
+        "minItems": len,
+        "maxItems": len,
+    })
+}
+
+fn code_schema(description: &str) -> Value {
+    json!({
+        "description": description,
+        "type": "integer",
+        "minimum": i32::MIN,
+        "maximum": i32::MAX,
+    })
+}
+
+/// JSON schema of the requests to a server with the given dimensions
+///
+/// A request is an object with exactly one field, the name of the request
+/// (e.g., `Run`), whose value is validated by the schema of the request. The
+/// dimensions of the vectors (parameters, initial guesses and Lagrange
+/// multipliers) are those of the problem.
+///
+/// # Arguments
+///
+/// - `num_decision_variables`: number of decision variables
+/// - `num_parameters`: number of parameters
+/// - `n1`: number of Lagrange multipliers (range dimension of $F_1$)
+///
+pub fn request_schema(num_decision_variables: usize, num_parameters: usize, n1: usize) -> Value {
+    let penalty = "initial penalty parameter (positive)";
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Request to an OpEn TCP server",
+        "type": "object",
+        "minProperties": 1,
+        "maxProperties": 1,
+        "additionalProperties": false,
+        "properties": {
+            "Run": {
+                "description": "solve the problem",
+                "type": "object",
+                "required": ["parameter"],
+                "properties": {
+                    "parameter": vector_schema(num_parameters, false, "parameter"),
+                    "initial_guess": vector_schema(num_decision_variables, true, "initial guess"),
+                    "initial_lagrange_multipliers":
+                        vector_schema(n1, true, "initial Lagrange multipliers"),
+                    "initial_penalty": {
+                        "description": penalty,
+                        "type": ["number", "null"],
+                        "exclusiveMinimum": 0,
+                    },
+                },
+            },
+            "InitialGuess": vector_schema(num_decision_variables, false, "initial guess of the next run"),
+            "InitialLagrangeMultipliers":
+                vector_schema(n1, false, "initial Lagrange multipliers of the subsequent runs"),
+            "InitialPenalty": {
+                "description": penalty,
+                "type": "number",
+                "exclusiveMinimum": 0,
+            },
+            "Reset": code_schema("forget the initial Lagrange multipliers and penalty"),
+            "Configure": {
+                "description": "fields of the configuration to update",
+                "type": "object",
+            },
+            "Status": code_schema("query the state of the server"),
+            "Schema": code_schema("query this schema"),
+            "Ping": code_schema("check whether the server is up"),
+            "Kill": code_schema("stop the server"),
+        },
+    })
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(x) if x.is_i64() || x.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// Checks a number of elements (or fields) against the bounds of a schema,
+/// given by the keywords `[minimum, maximum]`; returns the violated keyword
+/// and a description of the violation
+fn check_count(
+    count: usize,
+    schema: &Value,
+    keywords: [&'static str; 2],
+    noun: &str,
+) -> Option<(&'static str, String)> {
+    let bound = |keyword| schema.get(keyword).and_then(Value::as_u64);
+    let (min, max) = (bound(keywords[0]), bound(keywords[1]));
+    let count_u64 = count as u64;
+    let (keyword, bound) = match (min, max) {
+        (Some(min), _) if count_u64 < min => (keywords[0], format!("at least {}", min)),
+        (_, Some(max)) if count_u64 > max => (keywords[1], format!("at most {}", max)),
+        _ => return None,
+    };
+    let expected = match (min, max) {
+        (Some(min), Some(max)) if min == max => min.to_string(),
+        _ => bound,
+    };
+    Some((
+        keyword,
+        format!("expected {} {}, found {}", expected, noun, count),
+    ))
+}
+
+/// Escapes a field name in a JSON pointer
+fn pointer_token(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+/// Validates a value against a schema; `path` is the JSON pointer of the
+/// value
+///
+/// Returns the first violation which is found
+pub(crate) fn validate(schema: &Value, value: &Value, path: &str) -> Result<(), Violation> {
+    let violation = |keyword, message: String| Violation {
+        path: path.to_string(),
+        keyword,
+        message,
+    };
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => expected.as_str().into_iter().collect(),
+        };
+        if !types.iter().any(|expected| has_type(value, expected)) {
+            return Err(violation(
+                "type",
+                format!(
+                    "expected {}, found {}",
+                    types.join(" or "),
+                    type_name(value)
+                ),
+            ));
+        }
+    }
+    match value {
+        Value::Object(fields) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !fields.contains_key(name) {
+                        return Err(violation("required", format!("missing field `{}`", name)));
+                    }
+                }
+            }
+            if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                for name in fields.keys() {
+                    if !properties.is_some_and(|properties| properties.contains_key(name)) {
+                        return Err(violation(
+                            "additionalProperties",
+                            format!("unknown field `{}`", name),
+                        ));
+                    }
+                }
+            }
+            let keywords = ["minProperties", "maxProperties"];
+            if let Some((keyword, message)) = check_count(fields.len(), schema, keywords, "fields")
+            {
+                return Err(violation(keyword, message));
+            }
+            for (name, field) in fields {
+                if let Some(field_schema) = properties.and_then(|properties| properties.get(name)) {
+                    let field_path = format!("{}/{}", path, pointer_token(name));
+                    validate(field_schema, field, &field_path)?;
+                }
+            }
+        }
+        Value::Array(elements) => {
+            let keywords = ["minItems", "maxItems"];
+            if let Some((keyword, message)) =
+                check_count(elements.len(), schema, keywords, "elements")
+            {
+                return Err(violation(keyword, message));
+            }
+            if let Some(items) = schema.get("items") {
+                for (i, element) in elements.iter().enumerate() {
+                    validate(items, element, &format!("{}/{}", path, i))?;
+                }
+            }
+        }
+        Value::Number(x) => {
+            let x = x.as_f64().unwrap_or(f64::NAN);
+            let bound = |keyword| schema.get(keyword).and_then(Value::as_f64);
+            if let Some(min) = bound("exclusiveMinimum").filter(|&min| x <= min) {
+                return Err(violation(
+                    "exclusiveMinimum",
+                    format!("expected a number greater than {}", min),
+                ));
+            }
+            if let Some(min) = bound("minimum").filter(|&min| x < min) {
+                return Err(violation("minimum", format!("expected at least {}", min)));
+            }
+            if let Some(max) = bound("maximum").filter(|&max| x > max) {
+                return Err(violation("maximum", format!("expected at most {}", max)));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(request: &str) -> Result<(), Violation> {
+        let schema = request_schema(2, 1, 1);
+        validate(&schema, &serde_json::from_str(request).unwrap(), "")
+    }
+
+    #[test]
+    fn t_schema_accepts_valid_requests() {
+        let requests = [
+            r#"{"Run": {"parameter": [0.5]}}"#,
+            r#"{"Run": {"parameter": [1], "initial_guess": null, "initial_penalty": 10.0}}"#,
+            r#"{"Run": {"parameter": [1], "initial_guess": [1, 2], "initial_lagrange_multipliers": [0]}}"#,
+            r#"{"InitialGuess": [0.0, 1.0]}"#,
+            r#"{"InitialLagrangeMultipliers": [3.0]}"#,
+            r#"{"InitialPenalty": 5}"#,
+            r#"{"Configure": {"epsilon_tolerance": 1e-5}}"#,
+            r#"{"Reset": 1}"#,
+            r#"{"Schema": 1}"#,
+            r#"{"Ping": -4}"#,
+        ];
+        for request in requests.iter() {
+            assert_eq!(Ok(()), check(request), "{}", request);
+        }
+    }
+
+    #[test]
+    fn t_schema_violations() {
+        let violation = |request| {
+            let violation = check(request).unwrap_err();
+            (violation.code(), violation.keyword, violation.path)
+        };
+        assert_eq!(
+            (1000, "required", "/Run".to_string()),
+            violation(r#"{"Run": {"initial_guess": [1, 2]}}"#)
+        );
+        assert_eq!(
+            (3003, "minItems", "/Run/parameter".to_string()),
+            violation(r#"{"Run": {"parameter": []}}"#)
+        );
+        assert_eq!(
+            (1000, "type", "/Run/parameter/0".to_string()),
+            violation(r#"{"Run": {"parameter": ["a"]}}"#)
+        );
+        assert_eq!(
+            (1600, "maxItems", "/Run/initial_guess".to_string()),
+            violation(r#"{"Run": {"parameter": [1], "initial_guess": [1, 2, 3]}}"#)
+        );
+        assert_eq!(
+            (1700, "minItems", "/InitialLagrangeMultipliers".to_string()),
+            violation(r#"{"InitialLagrangeMultipliers": []}"#)
+        );
+        assert_eq!(
+            (1800, "exclusiveMinimum", "/InitialPenalty".to_string()),
+            violation(r#"{"InitialPenalty": 0.0}"#)
+        );
+        assert_eq!(
+            (1000, "additionalProperties", String::new()),
+            violation(r#"{"Jump": 1}"#)
+        );
+        assert_eq!(
+            (1000, "maxProperties", String::new()),
+            violation(r#"{"Ping": 1, "Kill": 1}"#)
+        );
+        assert_eq!((1000, "minProperties", String::new()), violation("{}"));
+        assert_eq!((1000, "type", String::new()), violation("[1]"));
+        assert_eq!(
+            (1000, "maximum", "/Kill".to_string()),
+            violation(r#"{"Kill": 3000000000}"#)
+        );
+        assert_eq!(
+            (1000, "type", "/Status".to_string()),
+            violation(r#"{"Status": 1.5}"#)
+        );
+
+        let reply = check(r#"{"Run": {"parameter": [1, 2]}}"#)
+            .unwrap_err()
+            .reply();
+        assert_eq!("Error", reply["type"]);
+        assert_eq!(3003, reply["code"]);
+        assert_eq!("expected 1 elements, found 2", reply["message"]);
+        assert_eq!("/Run/parameter", reply["path"]);
+    }
+}
@@ -0,0 +1,794 @@
+//! Shared-memory transport
+//!
+//! With the feature `shm` (on Unix-like targets), a
+//! [`TcpServer`](../struct.TcpServer.html) can also be called by processes
+//! which run on the same machine (e.g., a C++ or Python controller) through a
+//! memory-mapped file, typically in `/dev/shm`. Parameters and solutions are
+//! exchanged as raw `f64` arrays in a ring buffer of slots, without sockets,
+//! system calls or any encoding, so a solve costs a few microseconds more
+//! than calling the solver directly (the TCP transport costs tens of
+//! microseconds at least). In return, the server and the waiting client
+//! busy-wait (they spin, then yield), so each keeps a core busy.
+//!
+//! # Layout
+//!
+//! The file consists of a header of 64 bytes followed by `capacity` slots.
+//! All numbers are in native byte order; the fields marked as atomic are
+//! read with acquire and written with release semantics.
+//!
+//! | Offset | Type  | Header field                                            |
+//! |--------|-------|---------------------------------------------------------|
+//! | 0      | `u64` | magic number, the bytes `OpEnSHM\0`                     |
+//! | 8      | `u32` | version of the layout (`1`)                            |
+//! | 12     | `u32` | state of the server (atomic): 1 ready, 2 stopped        |
+//! | 16     | `u32` | kill flag (atomic): set to 1 by a client to stop the server |
+//! | 24     | `u64` | number of decision variables, `nu`                      |
+//! | 32     | `u64` | number of parameters, `np`                              |
+//! | 40     | `u64` | number of slots, `capacity`                             |
+//!
+//! Every slot has a size of `64 + 8 * (np + nu)` bytes, rounded up to a
+//! multiple of 64, and starts at the offset `64 + k * size`:
+//!
+//! | Offset   | Type         | Slot field                                        |
+//! |----------|--------------|---------------------------------------------------|
+//! | 0        | `u32`        | status (atomic): 0 empty, 1 request, 2 reply      |
+//! | 4        | `u32`        | flags of the request: 1 if an initial guess is given |
+//! | 8        | `i32`        | reply code: 0 on success, 2000 if the solver failed |
+//! | 12       | `u32`        | exit status: 0 converged, 1 maximum number of iterations, 2 out of time, 3 diverged |
+//! | 16       | `u64`        | number of outer iterations                        |
+//! | 24       | `u64`        | number of inner iterations                        |
+//! | 32       | `f64`        | norm of the fixed-point residual (last problem)   |
+//! | 40       | `f64`        | cost                                              |
+//! | 48       | `f64`        | penalty parameter                                 |
+//! | 56       | `u64`        | solve time in nanoseconds                         |
+//! | 64       | `[f64; np]`  | parameter                                         |
+//! | 64 + 8np | `[f64; nu]`  | initial guess (request) and solution (reply)      |
+//!
+//! # Protocol
+//!
+//! 1. The server creates the file under a temporary name, writes the header,
+//!    sets its state to 1 (ready) and renames the file to its final path, so
+//!    clients never see an incomplete file.
+//! 2. A client maps the file, waits until the state is 1 and checks the
+//!    magic number, the version and the dimensions.
+//! 3. The client writes its requests into the slots `0, 1, ..., capacity - 1,
+//!    0, ...` in turn: it writes the parameter (and the initial guess and its
+//!    flag) into an empty slot and then sets its status to 1.
+//! 4. The server serves the slots in the same order: it waits until the
+//!    status is 1, solves the problem, writes the solution and the statistics
+//!    and sets the status to 2.
+//! 5. The client waits until the status is 2, reads the reply and sets the
+//!    status back to 0.
+//!
+//! If no initial guess is given, the solver is warm-started from the
+//! previous solution; Lagrange multipliers and penalties cannot be passed.
+//! A client may submit up to `capacity` requests before it reads the first
+//! reply. The server is meant for one client at a time, which stops it by
+//! setting the kill flag; the server then sets its state to 2.
+//!
+//! [`ShmClient`] is a client for Rust processes (and the reference
+//! implementation of the protocol).
+//!
+//! [`ShmClient`]: struct.ShmClient.html
+//!
+use super::TcpServer;
+use crate::{alm::AlmOptimizerStatus, core::ExitStatus, SolverError};
+use std::{
+    convert::TryFrom,
+    fs::{self, File, OpenOptions},
+    io,
+    os::{
+        raw::{c_int, c_long, c_void},
+        unix::io::AsRawFd,
+    },
+    path::Path,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicU32, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Magic number at the start of the file
+const MAGIC: u64 = u64::from_ne_bytes(*b"OpEnSHM\0");
+
+/// Version of the layout
+const VERSION: u32 = 1;
+
+/// Size of the header and of the header of every slot in bytes
+const HEADER_SIZE: usize = 64;
+
+/// Offsets of the fields of the header
+const HEADER_VERSION: usize = 8;
+const HEADER_STATE: usize = 12;
+const HEADER_KILL: usize = 16;
+const HEADER_NUM_DECISION_VARIABLES: usize = 24;
+const HEADER_NUM_PARAMETERS: usize = 32;
+const HEADER_CAPACITY: usize = 40;
+
+/// Offsets of the fields of a slot
+const SLOT_STATUS: usize = 0;
+const SLOT_FLAGS: usize = 4;
+const SLOT_CODE: usize = 8;
+const SLOT_EXIT_STATUS: usize = 12;
+const SLOT_NUM_OUTER_ITERATIONS: usize = 16;
+const SLOT_NUM_INNER_ITERATIONS: usize = 24;
+const SLOT_NORM_FPR: usize = 32;
+const SLOT_COST: usize = 40;
+const SLOT_PENALTY: usize = 48;
+const SLOT_SOLVE_TIME: usize = 56;
+
+/// States of the server
+const READY: u32 = 1;
+const STOPPED: u32 = 2;
+
+/// Statuses of a slot
+const EMPTY: u32 = 0;
+const REQUEST: u32 = 1;
+const REPLY: u32 = 2;
+
+/// Flag of a request which carries an initial guess
+const FLAG_INITIAL_GUESS: u32 = 1;
+
+/// Reply code of a failed solve (as in the TCP server)
+const CODE_SOLVER_ERROR: i32 = 2000;
+
+/// Number of polls of a busy-wait before the thread starts yielding
+const SPIN_LIMIT: u32 = 10_000;
+
+/// `PROT_READ | PROT_WRITE` and `MAP_SHARED`, which have the same values on
+/// Linux and macOS
+const PROT_READ_WRITE: c_int = 3;
+const MAP_SHARED: c_int = 1;
+
+extern "C" {
+    // `off_t` is a `long` on the supported targets
+    fn mmap(
+        addr: *mut c_void,
+        length: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: c_long,
+    ) -> *mut c_void;
+    fn munmap(addr: *mut c_void, length: usize) -> c_int;
+}
+
+/// Dimensions of a shared-memory file and offsets of its slots
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Layout {
+    num_decision_variables: usize,
+    num_parameters: usize,
+    capacity: usize,
+}
+
+impl Layout {
+    fn slot_size(&self) -> usize {
+        let size = HEADER_SIZE + 8 * (self.num_parameters + self.num_decision_variables);
+        size.div_ceil(HEADER_SIZE) * HEADER_SIZE
+    }
+
+    /// Size of the file in bytes
+    fn size(&self) -> usize {
+        HEADER_SIZE + self.capacity * self.slot_size()
+    }
+
+    /// Offset of the `k`-th slot
+    fn slot(&self, k: usize) -> usize {
+        HEADER_SIZE + k * self.slot_size()
+    }
+
+    /// Offset of the parameter of the `k`-th slot
+    fn parameter(&self, k: usize) -> usize {
+        self.slot(k) + HEADER_SIZE
+    }
+
+    /// Offset of the initial guess/solution of the `k`-th slot
+    fn solution(&self, k: usize) -> usize {
+        self.parameter(k) + 8 * self.num_parameters
+    }
+}
+
+/// Memory-mapped file, which is unmapped when it is dropped
+///
+/// The memory is shared with other processes, which access it following the
+/// protocol of this module: the status of a slot is only changed with atomic
+/// operations and its data are only accessed by the side which owns it.
+struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+// SAFETY: the mapping is plain memory which can be accessed from any thread
+unsafe impl Send for Mapping {}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: the memory was mapped with this length in `Mapping::new`
+        unsafe {
+            munmap(self.ptr.as_ptr() as *mut c_void, self.len);
+        }
+    }
+}
+
+impl Mapping {
+    /// Maps the first `len` bytes of a file (which must be at least that long)
+    fn new(file: &File, len: usize) -> io::Result<Mapping> {
+        // SAFETY: the file descriptor is open for reading and writing; the
+        // kernel chooses the address of the mapping
+        let address = unsafe {
+            mmap(
+                ptr::null_mut(),
+                len,
+                PROT_READ_WRITE,
+                MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        // `MAP_FAILED` is `(void *) -1`
+        if address as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let ptr = NonNull::new(address as *mut u8)
+            .ok_or_else(|| io::Error::other("mmap returned a null pointer"))?;
+        Ok(Mapping { ptr, len })
+    }
+
+    /// Pointer to a value of type `T` at `offset`
+    fn at<T>(&self, offset: usize) -> *mut T {
+        assert!(offset + std::mem::size_of::<T>() <= self.len);
+        debug_assert_eq!(0, offset % std::mem::align_of::<T>());
+        // SAFETY: the offset lies within the mapping (which is page-aligned)
+        unsafe { self.ptr.as_ptr().add(offset) as *mut T }
+    }
+
+    fn atomic(&self, offset: usize) -> &AtomicU32 {
+        // SAFETY: the pointer is valid and aligned, and all accesses to the
+        // atomic fields are atomic
+        unsafe { &*self.at::<AtomicU32>(offset) }
+    }
+
+    fn read<T: Copy>(&self, offset: usize) -> T {
+        // SAFETY: the pointer is valid and aligned; the field belongs to this
+        // side of the protocol
+        unsafe { self.at::<T>(offset).read() }
+    }
+
+    fn write<T: Copy>(&self, offset: usize, value: T) {
+        // SAFETY: as in `read`
+        unsafe { self.at::<T>(offset).write(value) }
+    }
+
+    /// Pointer to an array of `len` values of type `f64` at `offset`
+    fn array(&self, offset: usize, len: usize) -> *mut f64 {
+        assert!(offset + 8 * len <= self.len);
+        debug_assert_eq!(0, offset % 8);
+        // SAFETY: the array lies within the mapping
+        unsafe { self.ptr.as_ptr().add(offset) as *mut f64 }
+    }
+
+    fn read_slice(&self, offset: usize, values: &mut [f64]) {
+        let source = self.array(offset, values.len());
+        // SAFETY: the source is valid and does not overlap with `values`
+        unsafe { ptr::copy_nonoverlapping(source, values.as_mut_ptr(), values.len()) }
+    }
+
+    fn write_slice(&self, offset: usize, values: &[f64]) {
+        let target = self.array(offset, values.len());
+        // SAFETY: as in `read_slice`
+        unsafe { ptr::copy_nonoverlapping(values.as_ptr(), target, values.len()) }
+    }
+}
+
+/// Polls `done` (spinning, then yielding) until it returns `true` or `stop`
+/// returns `true`; returns the last value of `done`
+fn wait(done: impl Fn() -> bool, stop: impl Fn() -> bool) -> bool {
+    let mut polls = 0;
+    while !done() {
+        if stop() {
+            return false;
+        }
+        if polls < SPIN_LIMIT {
+            polls += 1;
+            std::hint::spin_loop();
+        } else {
+            thread::yield_now();
+        }
+    }
+    true
+}
+
+fn exit_status_code(exit_status: ExitStatus) -> u32 {
+    match exit_status {
+        ExitStatus::Converged => 0,
+        ExitStatus::NotConvergedIterations => 1,
+        ExitStatus::NotConvergedOutOfTime => 2,
+        ExitStatus::Diverged => 3,
+    }
+}
+
+fn exit_status_from_code(code: u32) -> io::Result<ExitStatus> {
+    match code {
+        0 => Ok(ExitStatus::Converged),
+        1 => Ok(ExitStatus::NotConvergedIterations),
+        2 => Ok(ExitStatus::NotConvergedOutOfTime),
+        3 => Ok(ExitStatus::Diverged),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid exit status",
+        )),
+    }
+}
+
+/// Sets the state of the server to "stopped" when it is dropped (also if the
+/// solver panics), so that waiting clients return
+struct StopGuard<'a>(&'a Mapping);
+
+impl Drop for StopGuard<'_> {
+    fn drop(&mut self) {
+        self.0
+            .atomic(HEADER_STATE)
+            .store(STOPPED, Ordering::Release);
+    }
+}
+
+impl<S> TcpServer<S>
+where
+    S: FnMut(
+        &[f64],
+        &mut [f64],
+        Option<&[f64]>,
+        Option<f64>,
+    ) -> Result<AlmOptimizerStatus, SolverError>,
+{
+    /// Creates a shared-memory file with `capacity` slots at `path` (e.g.,
+    /// `/dev/shm/open_solver`) and serves requests until a client sets the
+    /// kill flag (see the [module](shm/index.html) for the protocol)
+    ///
+    /// A file which already exists at `path` is replaced; the file is left in
+    /// place (with the state "stopped") when the server returns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `capacity` is zero or if the file cannot be created
+    /// or mapped
+    pub fn run_shm(&mut self, path: impl AsRef<Path>, capacity: usize) -> io::Result<()> {
+        if capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the capacity must be positive",
+            ));
+        }
+        let layout = Layout {
+            num_decision_variables: self.u.len(),
+            num_parameters: self.num_parameters,
+            capacity,
+        };
+        let path = path.as_ref();
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(format!(".{}.tmp", std::process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temporary_path)?;
+        file.set_len(layout.size() as u64)?;
+        let mapping = Mapping::new(&file, layout.size())?;
+        mapping.write(0, MAGIC);
+        mapping.write(HEADER_VERSION, VERSION);
+        mapping.write(
+            HEADER_NUM_DECISION_VARIABLES,
+            layout.num_decision_variables as u64,
+        );
+        mapping.write(HEADER_NUM_PARAMETERS, layout.num_parameters as u64);
+        mapping.write(HEADER_CAPACITY, capacity as u64);
+        let _stop_guard = StopGuard(&mapping);
+        mapping.atomic(HEADER_STATE).store(READY, Ordering::Release);
+        fs::rename(&temporary_path, path)?;
+
+        let mut parameter = vec![0.0; self.num_parameters];
+        let killed = || mapping.atomic(HEADER_KILL).load(Ordering::Acquire) != 0;
+        for k in (0..capacity).cycle() {
+            let slot = layout.slot(k);
+            let status = mapping.atomic(slot + SLOT_STATUS);
+            if !wait(|| status.load(Ordering::Acquire) == REQUEST, killed) {
+                break;
+            }
+            mapping.read_slice(layout.parameter(k), &mut parameter);
+            if mapping.read::<u32>(slot + SLOT_FLAGS) & FLAG_INITIAL_GUESS != 0 {
+                mapping.read_slice(layout.solution(k), &mut self.u);
+            }
+            match (self.solver)(&parameter, &mut self.u, None, None) {
+                Ok(solver_status) => {
+                    mapping.write(slot + SLOT_CODE, 0_i32);
+                    write_status(&mapping, slot, &solver_status);
+                }
+                Err(_) => mapping.write(slot + SLOT_CODE, CODE_SOLVER_ERROR),
+            }
+            mapping.write_slice(layout.solution(k), &self.u);
+            status.store(REPLY, Ordering::Release);
+        }
+        Ok(())
+    }
+}
+
+fn write_status(mapping: &Mapping, slot: usize, status: &AlmOptimizerStatus) {
+    mapping.write(
+        slot + SLOT_EXIT_STATUS,
+        exit_status_code(status.exit_status()),
+    );
+    mapping.write(
+        slot + SLOT_NUM_OUTER_ITERATIONS,
+        status.num_outer_iterations() as u64,
+    );
+    mapping.write(
+        slot + SLOT_NUM_INNER_ITERATIONS,
+        status.num_inner_iterations() as u64,
+    );
+    mapping.write(slot + SLOT_NORM_FPR, status.last_problem_norm_fpr());
+    mapping.write(slot + SLOT_COST, status.cost());
+    mapping.write(slot + SLOT_PENALTY, status.penalty());
+    mapping.write(
+        slot + SLOT_SOLVE_TIME,
+        status.solve_time().as_nanos() as u64,
+    );
+}
+
+/// Solver statistics of a reply of the shared-memory server
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShmStatus {
+    /// Exit status
+    pub exit_status: ExitStatus,
+    /// Number of outer iterations
+    pub num_outer_iterations: usize,
+    /// Total number of inner iterations
+    pub num_inner_iterations: usize,
+    /// Norm of the fixed-point residual of the last inner problem
+    pub last_problem_norm_fpr: f64,
+    /// Cost at the solution
+    pub cost: f64,
+    /// Penalty parameter at the solution
+    pub penalty: f64,
+    /// Solve time
+    pub solve_time: Duration,
+}
+
+/// Client of a shared-memory server (see `TcpServer::run_shm`)
+///
+/// Requests are submitted with `submit` and their replies are received, in
+/// the same order, with `receive`; `solve` does both.
+pub struct ShmClient {
+    mapping: Mapping,
+    layout: Layout,
+    next_request: usize,
+    next_reply: usize,
+    pending: usize,
+}
+
+impl ShmClient {
+    /// Connects to the server of the shared-memory file at `path`, waiting
+    /// up to `timeout` for the server to create it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `TimedOut` if no server is ready within the
+    /// timeout, and of kind `InvalidData` if the file has another format
+    pub fn connect(path: impl AsRef<Path>, timeout: Duration) -> io::Result<ShmClient> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(client) = ShmClient::try_connect(path.as_ref())? {
+                return Ok(client);
+            }
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no shared-memory server is ready",
+                ));
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Connects if a ready server has created the file
+    fn try_connect(path: &Path) -> io::Result<Option<ShmClient>> {
+        let file = match OpenOptions::new().read(true).write(true).open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let file_size = file.metadata()?.len();
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid shared-memory file");
+        if file_size < HEADER_SIZE as u64 {
+            return Err(invalid());
+        }
+        let header = Mapping::new(&file, HEADER_SIZE)?;
+        if header.atomic(HEADER_STATE).load(Ordering::Acquire) != READY {
+            return Ok(None);
+        }
+        if header.read::<u64>(0) != MAGIC || header.read::<u32>(HEADER_VERSION) != VERSION {
+            return Err(invalid());
+        }
+        let dimension = |offset| usize::try_from(header.read::<u64>(offset)).map_err(|_| invalid());
+        let layout = Layout {
+            num_decision_variables: dimension(HEADER_NUM_DECISION_VARIABLES)?,
+            num_parameters: dimension(HEADER_NUM_PARAMETERS)?,
+            capacity: dimension(HEADER_CAPACITY)?,
+        };
+        if layout.capacity == 0 || file_size < layout.size() as u64 {
+            return Err(invalid());
+        }
+        Ok(Some(ShmClient {
+            mapping: Mapping::new(&file, layout.size())?,
+            layout,
+            next_request: 0,
+            next_reply: 0,
+            pending: 0,
+        }))
+    }
+
+    /// Number of decision variables
+    pub fn num_decision_variables(&self) -> usize {
+        self.layout.num_decision_variables
+    }
+
+    /// Number of parameters
+    pub fn num_parameters(&self) -> usize {
+        self.layout.num_parameters
+    }
+
+    /// Number of slots, that is, the maximum number of pending requests
+    pub fn capacity(&self) -> usize {
+        self.layout.capacity
+    }
+
+    /// Number of requests whose replies have not been received
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Submits a request for the parameter `parameter` (and, optionally, an
+    /// initial guess) without waiting for its reply
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if the dimensions are wrong and
+    /// of kind `WouldBlock` if all slots are pending
+    pub fn submit(&mut self, parameter: &[f64], initial_guess: Option<&[f64]>) -> io::Result<()> {
+        let wrong_dimension =
+            initial_guess.is_some_and(|u0| u0.len() != self.layout.num_decision_variables);
+        if parameter.len() != self.layout.num_parameters || wrong_dimension {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "wrong dimension of the parameter or the initial guess",
+            ));
+        }
+        if self.pending == self.layout.capacity {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "all slots are pending",
+            ));
+        }
+        let k = self.next_request;
+        let slot = self.layout.slot(k);
+        // the slot was emptied when its last reply was received
+        debug_assert_eq!(
+            EMPTY,
+            self.mapping
+                .atomic(slot + SLOT_STATUS)
+                .load(Ordering::Acquire)
+        );
+        self.mapping
+            .write_slice(self.layout.parameter(k), parameter);
+        let flags = match initial_guess {
+            Some(u0) => {
+                self.mapping.write_slice(self.layout.solution(k), u0);
+                FLAG_INITIAL_GUESS
+            }
+            None => 0,
+        };
+        self.mapping.write(slot + SLOT_FLAGS, flags);
+        self.mapping
+            .atomic(slot + SLOT_STATUS)
+            .store(REQUEST, Ordering::Release);
+        self.next_request = (k + 1) % self.layout.capacity;
+        self.pending += 1;
+        Ok(())
+    }
+
+    /// Waits for the reply to the oldest pending request, copies the solution
+    /// into `solution` and returns the solver statistics
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no request is pending, if `solution` has the wrong
+    /// dimension, if the server has stopped or if the solver failed
+    pub fn receive(&mut self, solution: &mut [f64]) -> io::Result<ShmStatus> {
+        if self.pending == 0 || solution.len() != self.layout.num_decision_variables {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no pending request or wrong dimension of the solution",
+            ));
+        }
+        let k = self.next_reply;
+        let slot = self.layout.slot(k);
+        let mapping = &self.mapping;
+        let status = mapping.atomic(slot + SLOT_STATUS);
+        let replied = wait(
+            || status.load(Ordering::Acquire) == REPLY,
+            || mapping.atomic(HEADER_STATE).load(Ordering::Acquire) == STOPPED,
+        );
+        if !replied {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "the shared-memory server has stopped",
+            ));
+        }
+        let code = mapping.read::<i32>(slot + SLOT_CODE);
+        mapping.read_slice(self.layout.solution(k), solution);
+        let reply =
+            exit_status_from_code(mapping.read(slot + SLOT_EXIT_STATUS)).map(|exit_status| {
+                ShmStatus {
+                    exit_status,
+                    num_outer_iterations: mapping.read::<u64>(slot + SLOT_NUM_OUTER_ITERATIONS)
+                        as usize,
+                    num_inner_iterations: mapping.read::<u64>(slot + SLOT_NUM_INNER_ITERATIONS)
+                        as usize,
+                    last_problem_norm_fpr: mapping.read(slot + SLOT_NORM_FPR),
+                    cost: mapping.read(slot + SLOT_COST),
+                    penalty: mapping.read(slot + SLOT_PENALTY),
+                    solve_time: Duration::from_nanos(mapping.read(slot + SLOT_SOLVE_TIME)),
+                }
+            });
+        status.store(EMPTY, Ordering::Release);
+        self.next_reply = (k + 1) % self.layout.capacity;
+        self.pending -= 1;
+        if code != 0 {
+            return Err(io::Error::other(format!(
+                "problem solution failed (code {})",
+                code
+            )));
+        }
+        reply
+    }
+
+    /// Submits a request and waits for its reply
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if requests are pending and the errors of `submit`
+    /// and `receive`
+    pub fn solve(
+        &mut self,
+        parameter: &[f64],
+        initial_guess: Option<&[f64]>,
+        solution: &mut [f64],
+    ) -> io::Result<ShmStatus> {
+        if self.pending > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "requests are pending",
+            ));
+        }
+        self.submit(parameter, initial_guess)?;
+        self.receive(solution)
+    }
+
+    /// Asks the server to stop (once it has served the pending requests)
+    pub fn kill(&self) {
+        self.mapping.atomic(HEADER_KILL).store(1, Ordering::Release);
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alm::*, constraints::*, core::panoc::PANOCCache, FunctionCallResult};
+
+    #[test]
+    fn t_shm_layout() {
+        let layout = Layout {
+            num_decision_variables: 5,
+            num_parameters: 3,
+            capacity: 4,
+        };
+        assert_eq!(128, layout.slot_size());
+        assert_eq!(64 + 4 * 128, layout.size());
+        assert_eq!(64 + 2 * 128 + 64, layout.parameter(2));
+        assert_eq!(layout.parameter(2) + 24, layout.solution(2));
+        for exit_status in [
+            ExitStatus::Converged,
+            ExitStatus::NotConvergedIterations,
+            ExitStatus::NotConvergedOutOfTime,
+            ExitStatus::Diverged,
+        ] {
+            let code = exit_status_code(exit_status);
+            assert_eq!(exit_status, exit_status_from_code(code).unwrap());
+        }
+        assert!(exit_status_from_code(4).is_err());
+    }
+
+    #[test]
+    fn t_shm_server() {
+        let path = std::env::temp_dir().join(format!("open_shm_test_{}", std::process::id()));
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            let mut client = ShmClient::connect(&client_path, Duration::from_secs(10)).unwrap();
+            assert_eq!(
+                (2, 1, 3),
+                (
+                    client.num_decision_variables(),
+                    client.num_parameters(),
+                    client.capacity()
+                )
+            );
+
+            let mut u = [0.0; 2];
+            let status = client.solve(&[0.5], None, &mut u).unwrap();
+            assert_eq!(ExitStatus::Converged, status.exit_status);
+            unit_test_utils::assert_nearly_equal(0.5, u[0], 1e-5, 1e-5, "u0");
+            assert!(status.num_inner_iterations > 0);
+
+            // pipelined requests are answered in order
+            for p in [0.2, 2.0, -0.3] {
+                client.submit(&[p], Some(&[0.0, 0.0])).unwrap();
+            }
+            let full = client.submit(&[1.0], None).unwrap_err();
+            assert_eq!(io::ErrorKind::WouldBlock, full.kind());
+            assert!(client.solve(&[1.0], None, &mut u).is_err());
+            for expected in [0.2, 1.0, -0.3] {
+                client.receive(&mut u).unwrap();
+                unit_test_utils::assert_nearly_equal(expected, u[0], 1e-5, 1e-5, "u0");
+            }
+            assert_eq!(0, client.pending());
+
+            // the slots are reused (cyclically) and the solver fails for NaN
+            assert!(client.solve(&[f64::NAN], None, &mut u).is_err());
+            client.solve(&[0.7], None, &mut u).unwrap();
+            unit_test_utils::assert_nearly_equal(0.7, u[0], 1e-5, 1e-5, "u0");
+            assert!(client.submit(&[1.0, 2.0], None).is_err());
+            assert!(client.receive(&mut u).is_err());
+            client.kill();
+            client
+        });
+
+        let mut cache = AlmCache::new(PANOCCache::new(2, 1e-6, 5).unwrap(), 0, 0);
+        let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+            let p0 = p[0];
+            if p0.is_nan() {
+                return Err(SolverError::user("invalid parameter"));
+            }
+            let alm_problem = AlmProblem::new(
+                Ball2::new(None, 1.0)?,
+                NO_SET,
+                NO_SET,
+                move |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+                    *c = (u[0] - p0).powi(2) + u[1].powi(2);
+                    Ok(())
+                },
+                move |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+                    g[0] = 2.0 * (u[0] - p0);
+                    g[1] = 2.0 * u[1];
+                    Ok(())
+                },
+                NO_MAPPING,
+                NO_MAPPING,
+                0,
+                0,
+            )?;
+            AlmOptimizer::new(&mut cache, alm_problem).solve(u)
+        });
+        assert!(server.run_shm(&path, 0).is_err());
+        server.run_shm(&path, 3).unwrap();
+
+        // the client returns once the server has stopped
+        let mut client = client.join().unwrap();
+        client.submit(&[0.5], None).unwrap();
+        let stopped = client.receive(&mut [0.0; 2]).unwrap_err();
+        assert_eq!(io::ErrorKind::ConnectionAborted, stopped.kind());
+        fs::remove_file(&path).unwrap();
+    }
+}
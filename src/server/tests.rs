@@ -0,0 +1,362 @@
+use crate::{
+    alm::*,
+    constraints::*,
+    core::panoc::PANOCCache,
+    server::{msgpack, ClientRequest, SharedConfig, TcpServer},
+    FunctionCallResult, SolverError,
+};
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+};
+
+/// Solves: minimize (u1 - p)^2 + u2^2 subject to ||u|| <= 1
+fn solve_mock(
+    cache: &mut AlmCache,
+    p: &[f64],
+    u: &mut [f64],
+) -> Result<AlmOptimizerStatus, SolverError> {
+    let p0 = p[0];
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 1.0)?,
+        NO_SET,
+        NO_SET,
+        move |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+            *c = (u[0] - p0).powi(2) + u[1].powi(2);
+            Ok(())
+        },
+        move |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+            g[0] = 2.0 * (u[0] - p0);
+            g[1] = 2.0 * u[1];
+            Ok(())
+        },
+        NO_MAPPING,
+        NO_MAPPING,
+        0,
+        0,
+    )?;
+    AlmOptimizer::new(cache, alm_problem).solve(u)
+}
+
+fn reply_json(reply: &str) -> serde_json::Value {
+    serde_json::from_str(reply).unwrap()
+}
+
+#[test]
+fn t_client_request_deserialization() {
+    let request: ClientRequest =
+        serde_json::from_str(r#"{"Run": {"parameter": [1.0, 2.0], "initial_penalty": 10.0}}"#)
+            .unwrap();
+    match request {
+        ClientRequest::Run(execution_parameter) => {
+            assert_eq!(vec![1.0, 2.0], execution_parameter.parameter);
+            assert_eq!(None, execution_parameter.initial_guess);
+            assert_eq!(Some(10.0), execution_parameter.initial_penalty);
+        }
+        _ => panic!("unexpected request: {:?}", request),
+    }
+    assert_eq!(
+        ClientRequest::Ping(42),
+        serde_json::from_str(r#"{"Ping": 42}"#).unwrap()
+    );
+}
+
+#[test]
+fn t_handle_requests() {
+    let mut cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 0, 0);
+    let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+        solve_mock(&mut cache, p, u)
+    });
+
+    assert_eq!(
+        Some("{\"Pong\":7}".to_string()),
+        server.handle_request(r#"{"Ping": 7}"#)
+    );
+
+    let reply = reply_json(
+        &server
+            .handle_request(r#"{"Run": {"parameter": [0.5]}}"#)
+            .unwrap(),
+    );
+    assert_eq!("Converged", reply["exit_status"]);
+    let solution = reply["solution"].as_array().unwrap();
+    assert!((solution[0].as_f64().unwrap() - 0.5).abs() < 1e-6);
+    assert!(solution[1].as_f64().unwrap().abs() < 1e-6);
+
+    // the solution is on the boundary of the ball
+    let reply = reply_json(
+        &server
+            .handle_request(r#"{"Run": {"parameter": [3.0]}}"#)
+            .unwrap(),
+    );
+    assert!((reply["solution"][0].as_f64().unwrap() - 1.0).abs() < 1e-6);
+
+    assert_eq!(
+        reply_json("{\"type\":\"Ok\"}"),
+        reply_json(
+            &server
+                .handle_request(r#"{"InitialGuess": [0.1, 0.1]}"#)
+                .unwrap()
+        )
+    );
+    assert!(server.handle_request(r#"{"Kill": 1}"#).is_none());
+}
+
+#[test]
+fn t_handle_warm_start_and_configuration_requests() {
+    let config = SharedConfig::default();
+    let solver_config = config.clone();
+    let mut inputs = Vec::new();
+    {
+        let mut server = TcpServer::new(2, 1, 1, |_p: &[f64], u: &mut [f64], y0, c0| {
+            inputs.push((y0.map(<[f64]>::to_vec), c0, solver_config.get()));
+            u.fill(1.0);
+            Err(SolverError::user("not solved"))
+        })
+        .with_config(config.clone());
+        let mut handle = |request: &str| reply_json(&server.handle_request(request).unwrap());
+
+        let status = handle(r#"{"Status": 1}"#);
+        assert_eq!(serde_json::Value::Null, status["last_solution"]);
+        assert_eq!(serde_json::Value::Null, status["initial_penalty"]);
+        assert_eq!(50, status["config"]["max_outer_iterations"]);
+
+        assert_eq!(
+            "Ok",
+            handle(r#"{"InitialLagrangeMultipliers": [2.0]}"#)["type"]
+        );
+        assert_eq!("Ok", handle(r#"{"InitialPenalty": 20.0}"#)["type"]);
+        assert_eq!(
+            "Ok",
+            handle(r#"{"Configure": {"epsilon_tolerance": 1e-5, "max_outer_iterations": 20}}"#)
+                ["type"]
+        );
+        assert_eq!(
+            1700,
+            handle(r#"{"InitialLagrangeMultipliers": []}"#)["code"]
+        );
+        assert_eq!(1800, handle(r#"{"InitialPenalty": -1.0}"#)["code"]);
+        assert_eq!(
+            1900,
+            handle(r#"{"Configure": {"tolerance": 1e-5}}"#)["code"]
+        );
+        assert_eq!(1900, handle(r#"{"Configure": {"filter": 3}}"#)["code"]);
+
+        // the stored multipliers and penalty are used unless a run specifies its own
+        handle(r#"{"Run": {"parameter": [0.0]}}"#);
+        handle(r#"{"Run": {"parameter": [0.0], "initial_penalty": 5.0}}"#);
+        let status = handle(r#"{"Status": 2}"#);
+        assert_eq!(2000, status["last_solution"]["code"]);
+        assert_eq!(serde_json::json!([1.0, 1.0]), status["initial_guess"]);
+        assert_eq!(
+            serde_json::json!([2.0]),
+            status["initial_lagrange_multipliers"]
+        );
+        assert_eq!(20, status["config"]["max_outer_iterations"]);
+        assert_eq!("Ok", handle(r#"{"Reset": 3}"#)["type"]);
+        handle(r#"{"Run": {"parameter": [0.0]}}"#);
+    }
+
+    assert_eq!(3, inputs.len());
+    assert_eq!(
+        (Some(vec![2.0]), Some(20.0)),
+        (inputs[0].0.clone(), inputs[0].1)
+    );
+    assert_eq!(Some(5.0), inputs[1].1);
+    assert_eq!((None, None), (inputs[2].0.clone(), inputs[2].1));
+    assert_eq!(1e-5, inputs[0].2.epsilon_tolerance);
+    assert_eq!(20, config.get().max_outer_iterations);
+    // the configuration which failed to update is unchanged
+    assert!(!config.get().filter);
+
+    let mut server = TcpServer::new(2, 1, 0, |_p: &[f64], _u: &mut [f64], _y0, _c0| {
+        Err(SolverError::user("not solved"))
+    });
+    let reply = reply_json(
+        &server
+            .handle_request(r#"{"Configure": {"epsilon_tolerance": 1e-5}}"#)
+            .unwrap(),
+    );
+    assert_eq!(1900, reply["code"]);
+}
+
+#[test]
+fn t_handle_invalid_requests() {
+    let mut cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 0, 0);
+    let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+        solve_mock(&mut cache, p, u)
+    });
+    let error_code = |server: &mut TcpServer<_>, request: &str| {
+        reply_json(&server.handle_request(request).unwrap())["code"]
+            .as_i64()
+            .unwrap()
+    };
+    assert_eq!(1000, error_code(&mut server, "not json"));
+    assert_eq!(1000, error_code(&mut server, r#"{"Jump": 1}"#));
+    assert_eq!(1600, error_code(&mut server, r#"{"InitialGuess": [1.0]}"#));
+    assert_eq!(
+        1600,
+        error_code(
+            &mut server,
+            r#"{"Run": {"parameter": [1.0], "initial_guess": [1.0]}}"#
+        )
+    );
+    assert_eq!(
+        1700,
+        error_code(
+            &mut server,
+            r#"{"Run": {"parameter": [1.0], "initial_lagrange_multipliers": [1.0]}}"#
+        )
+    );
+    assert_eq!(
+        3003,
+        error_code(&mut server, r#"{"Run": {"parameter": [1.0, 2.0]}}"#)
+    );
+}
+
+#[test]
+fn t_handle_schema_violations() {
+    let mut cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 0, 0);
+    let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+        solve_mock(&mut cache, p, u)
+    });
+    let mut handle = |request: &str| reply_json(&server.handle_request(request).unwrap());
+
+    let reply = handle(r#"{"Run": {"initial_guess": [1.0, 2.0]}}"#);
+    assert_eq!(1000, reply["code"]);
+    assert_eq!("/Run", reply["path"]);
+    assert_eq!("missing field `parameter`", reply["message"]);
+    let reply = handle(r#"{"Run": {"parameter": [1.0, 2.0]}}"#);
+    assert_eq!(3003, reply["code"]);
+    assert_eq!("/Run/parameter", reply["path"]);
+    assert_eq!("expected 1 elements, found 2", reply["message"]);
+    let reply = handle(r#"{"InitialGuess": [1.0, "2"]}"#);
+    assert_eq!("/InitialGuess/1", reply["path"]);
+    assert_eq!("expected number, found string", reply["message"]);
+    assert_eq!("unknown field `Jump`", handle(r#"{"Jump": 1}"#)["message"]);
+
+    let schema = handle(r#"{"Schema": 1}"#);
+    assert_eq!(
+        1,
+        schema["properties"]["Run"]["properties"]["parameter"]["maxItems"]
+    );
+    assert_eq!(2, schema["properties"]["InitialGuess"]["minItems"]);
+    let reply = handle(r#"{"Run": {"parameter": [0.5]}}"#);
+    assert_eq!("Converged", reply["exit_status"]);
+}
+
+#[test]
+fn t_handle_msgpack_requests() {
+    let mut cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 0, 0);
+    let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+        solve_mock(&mut cache, p, u)
+    });
+    let request = msgpack::encode(&serde_json::json!({ "Run": { "parameter": [0.5] } }));
+    let reply = msgpack::decode(&server.handle_message(&request).unwrap()).unwrap();
+    assert_eq!("Converged", reply["exit_status"]);
+    assert!((reply["solution"][0].as_f64().unwrap() - 0.5).abs() < 1e-6);
+
+    // {"Ping": 7}
+    let reply = server
+        .handle_message(&[0x81, 0xa4, b'P', b'i', b'n', b'g', 0x07])
+        .unwrap();
+    assert_eq!(vec![0x81, 0xa4, b'P', b'o', b'n', b'g', 0x07], reply);
+
+    // invalid MessagePack
+    let reply = msgpack::decode(&server.handle_message(&[0x81, 0xa4]).unwrap()).unwrap();
+    assert_eq!(1000, reply["code"]);
+
+    // JSON messages are answered in JSON
+    assert_eq!(
+        Some(b"{\"Pong\":3}".to_vec()),
+        server.handle_message(br#"{"Ping": 3}"#)
+    );
+    assert!(server
+        .handle_message(&msgpack::encode(&serde_json::json!({ "Kill": 1 })))
+        .is_none());
+}
+
+#[test]
+fn t_handle_solver_error() {
+    let mut server = TcpServer::new(2, 1, 0, |_p: &[f64], _u: &mut [f64], _y0, _c0| {
+        Err(SolverError::user("simulator crashed"))
+    });
+    let reply = reply_json(
+        &server
+            .handle_request(r#"{"Run": {"parameter": [1.0]}}"#)
+            .unwrap(),
+    );
+    assert_eq!("Error", reply["type"]);
+    assert_eq!(2000, reply["code"]);
+    assert!(reply["message"]
+        .as_str()
+        .unwrap()
+        .contains("simulator crashed"));
+}
+
+#[test]
+fn t_tcp_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server_thread = std::thread::spawn(move || {
+        let mut cache = AlmCache::new(PANOCCache::new(2, 1e-8, 5).unwrap(), 0, 0);
+        let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+            solve_mock(&mut cache, p, u)
+        });
+        server.serve(&listener).unwrap();
+    });
+
+    let send = |request: &str| -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        reply
+    };
+
+    assert_eq!("{\"Pong\":1}", send(r#"{"Ping": 1}"#));
+    let reply = reply_json(&send(r#"{"Run": {"parameter": [-0.25]}}"#));
+    assert!((reply["solution"][0].as_f64().unwrap() + 0.25).abs() < 1e-6);
+    assert_eq!("", send(r#"{"Kill": 1}"#));
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn t_tcp_server_stalled_and_large_requests() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server_thread = std::thread::spawn(move || {
+        let mut server = TcpServer::new(2, 1, 0, |_p: &[f64], _u: &mut [f64], _y0, _c0| {
+            Err(SolverError::user("not solved"))
+        })
+        .with_read_timeout(Some(std::time::Duration::from_millis(50)));
+        server.serve(&listener).unwrap();
+    });
+
+    let send = |request: &[u8]| -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        // the server may close the connection before it reads everything
+        let _ = stream.write_all(request);
+        let _ = stream.shutdown(Shutdown::Write);
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        reply
+    };
+
+    // a client which never completes its request is dropped, and the next
+    // client is served
+    let mut stalled = TcpStream::connect(address).unwrap();
+    stalled.write_all(br#"{"Ping": "#).unwrap();
+    assert_eq!("{\"Pong\":2}", send(br#"{"Ping": 2}"#));
+    let mut reply = String::new();
+    stalled.read_to_string(&mut reply).unwrap();
+    assert_eq!("", reply);
+
+    let large_request = vec![b' '; (1 << 24) + 1];
+    assert_eq!(1100, reply_json(&send(&large_request))["code"]);
+    assert_eq!("", send(br#"{"Kill": 1}"#));
+    server_thread.join().unwrap();
+}
@@ -0,0 +1,366 @@
+//! ZeroMQ request/reply transport
+//!
+//! With the feature `zmq`, a [`TcpServer`](../struct.TcpServer.html) can also
+//! act as a ZeroMQ `REP` socket, so that it can be called by `REQ` (or
+//! `DEALER`) clients, e.g., using `pyzmq`. The protocol is ZMTP 3.0 with the
+//! `NULL` security mechanism, which is implemented here on top of
+//! `std::net`, so no native ZeroMQ library is needed. The messages are the
+//! JSON (or MessagePack) messages of the TCP server (see the documentation
+//! of the module `server`); a connection can carry any number of requests.
+//!
+use super::TcpServer;
+use crate::{alm::AlmOptimizerStatus, SolverError};
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+/// Flag of frames which are followed by more frames of the same message
+const FLAG_MORE: u8 = 0x01;
+/// Flag of frames whose size is encoded in 8 bytes
+const FLAG_LONG: u8 = 0x02;
+/// Flag of command frames
+const FLAG_COMMAND: u8 = 0x04;
+
+/// Maximum size of a message in bytes, that is, of the sum of the sizes of its
+/// frames (larger frames and messages are rejected)
+const MAX_MESSAGE_SIZE: u64 = 1 << 24;
+
+/// A frame of a ZMTP connection
+struct Frame {
+    flags: u8,
+    body: Vec<u8>,
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Greeting of ZMTP 3.0 with the `NULL` mechanism
+fn greeting() -> [u8; 64] {
+    let mut greeting = [0_u8; 64];
+    greeting[0] = 0xff;
+    greeting[9] = 0x7f;
+    greeting[10] = 3; // major version
+    greeting[11] = 0; // minor version
+    greeting[12..16].copy_from_slice(b"NULL");
+    greeting
+}
+
+fn read_frame(stream: &mut impl Read) -> io::Result<Frame> {
+    let mut flags = [0_u8; 1];
+    stream.read_exact(&mut flags)?;
+    let flags = flags[0];
+    let size = if flags & FLAG_LONG != 0 {
+        let mut size = [0_u8; 8];
+        stream.read_exact(&mut size)?;
+        u64::from_be_bytes(size)
+    } else {
+        let mut size = [0_u8; 1];
+        stream.read_exact(&mut size)?;
+        u64::from(size[0])
+    };
+    if size > MAX_MESSAGE_SIZE {
+        return Err(protocol_error("frame too large"));
+    }
+    let mut body = vec![0_u8; size as usize];
+    stream.read_exact(&mut body)?;
+    Ok(Frame { flags, body })
+}
+
+fn write_frame(stream: &mut impl Write, flags: u8, body: &[u8]) -> io::Result<()> {
+    if body.len() > 255 {
+        stream.write_all(&[flags | FLAG_LONG])?;
+        stream.write_all(&(body.len() as u64).to_be_bytes())?;
+    } else {
+        stream.write_all(&[flags, body.len() as u8])?;
+    }
+    stream.write_all(body)
+}
+
+/// Name of a command (the body of a command frame starts with the length
+/// of the name and the name)
+fn command_name(body: &[u8]) -> &[u8] {
+    match body.first() {
+        Some(&len) if body.len() > len as usize => &body[1..=len as usize],
+        _ => &[],
+    }
+}
+
+/// Performs the ZMTP handshake (greeting and `READY` commands)
+fn handshake(stream: &mut TcpStream) -> io::Result<()> {
+    stream.write_all(&greeting())?;
+    let mut peer_greeting = [0_u8; 64];
+    stream.read_exact(&mut peer_greeting)?;
+    if peer_greeting[0] != 0xff || peer_greeting[9] != 0x7f || peer_greeting[10] < 3 {
+        return Err(protocol_error("unsupported ZMTP version"));
+    }
+    if &peer_greeting[12..16] != b"NULL" || peer_greeting[16] != 0 {
+        return Err(protocol_error("unsupported security mechanism"));
+    }
+
+    let mut ready = vec![5_u8];
+    ready.extend_from_slice(b"READY");
+    ready.push(11);
+    ready.extend_from_slice(b"Socket-Type");
+    ready.extend_from_slice(&3_u32.to_be_bytes());
+    ready.extend_from_slice(b"REP");
+    write_frame(stream, FLAG_COMMAND, &ready)?;
+
+    let peer_ready = read_frame(stream)?;
+    if peer_ready.flags & FLAG_COMMAND == 0 || command_name(&peer_ready.body) != b"READY" {
+        return Err(protocol_error("expected READY command"));
+    }
+    Ok(())
+}
+
+/// Reads a message and returns its envelope (the frames up to and including
+/// the empty delimiter) and its body (the concatenation of the other frames);
+/// `PING` commands are answered and other commands are ignored
+///
+/// Messages whose frames are together larger than `MAX_MESSAGE_SIZE` are
+/// rejected as soon as the limit is exceeded.
+fn read_message(stream: &mut TcpStream) -> io::Result<(Vec<Vec<u8>>, Vec<u8>)> {
+    let mut envelope = Vec::new();
+    let mut body = Vec::new();
+    let mut in_envelope = true;
+    let mut message_size = 0_u64;
+    loop {
+        let frame = read_frame(stream)?;
+        if frame.flags & FLAG_COMMAND != 0 {
+            if command_name(&frame.body) == b"PING" {
+                // PING: name, TTL (2 bytes), context; PONG: name, context
+                let context = frame.body.get(7..).unwrap_or(&[]);
+                let mut pong = vec![4_u8];
+                pong.extend_from_slice(b"PONG");
+                pong.extend_from_slice(context);
+                write_frame(stream, FLAG_COMMAND, &pong)?;
+            }
+            continue;
+        }
+        message_size += frame.body.len() as u64;
+        if message_size > MAX_MESSAGE_SIZE {
+            return Err(protocol_error("message too large"));
+        }
+        if in_envelope {
+            in_envelope = !frame.body.is_empty();
+            envelope.push(frame.body);
+            if in_envelope && frame.flags & FLAG_MORE == 0 {
+                // a message without delimiter (sent by a DEALER socket)
+                body = envelope.pop().unwrap_or_default();
+                return Ok((envelope, body));
+            }
+        } else {
+            body.extend_from_slice(&frame.body);
+        }
+        if frame.flags & FLAG_MORE == 0 {
+            return Ok((envelope, body));
+        }
+    }
+}
+
+fn write_message(stream: &mut TcpStream, envelope: &[Vec<u8>], body: &[u8]) -> io::Result<()> {
+    for frame in envelope {
+        write_frame(stream, FLAG_MORE, frame)?;
+    }
+    write_frame(stream, 0, body)
+}
+
+impl<S> TcpServer<S>
+where
+    S: FnMut(
+        &[f64],
+        &mut [f64],
+        Option<&[f64]>,
+        Option<f64>,
+    ) -> Result<AlmOptimizerStatus, SolverError>,
+{
+    /// Binds to the given address and serves ZeroMQ `REQ` clients until a
+    /// `Kill` request is received (requires the feature `zmq`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot bind to the given address or
+    /// accept incoming connections
+    pub fn run_zmq<A: ToSocketAddrs>(&mut self, address: A) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        self.serve_zmq(&listener)
+    }
+
+    /// Serves ZeroMQ `REQ` clients on a listener until a `Kill` request is
+    /// received (requires the feature `zmq`)
+    ///
+    /// The clients are served one at a time; each client may send any number
+    /// of requests. A `Kill` request is not answered. Connections which fail
+    /// or violate the protocol are dropped and the server keeps serving.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot accept incoming connections
+    pub fn serve_zmq(&mut self, listener: &TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let mut stream = stream?;
+            // the client may have disconnected or violated the protocol;
+            // keep serving
+            if let Ok(false) = self.serve_zmq_connection(&mut stream) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serves a connection; returns `false` if a `Kill` request is received
+    fn serve_zmq_connection(&mut self, stream: &mut TcpStream) -> io::Result<bool> {
+        handshake(stream)?;
+        loop {
+            let (envelope, body) = read_message(stream)?;
+            match self.handle_message(&body) {
+                Some(reply) => write_message(stream, &envelope, &reply)?,
+                None => return Ok(false),
+            }
+        }
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alm::*, constraints::*, core::panoc::PANOCCache, FunctionCallResult};
+
+    /// Minimal ZMTP `REQ` client: handshake
+    fn connect(address: std::net::SocketAddr) -> TcpStream {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(&greeting()).unwrap();
+        let mut peer_greeting = [0_u8; 64];
+        stream.read_exact(&mut peer_greeting).unwrap();
+        assert_eq!(greeting().to_vec(), peer_greeting.to_vec());
+        let ready = read_frame(&mut stream).unwrap();
+        assert_eq!(FLAG_COMMAND, ready.flags);
+        assert_eq!(b"READY", command_name(&ready.body));
+        assert!(ready.body.ends_with(b"Socket-Type\x00\x00\x00\x03REP"));
+        let mut ready = vec![5_u8];
+        ready.extend_from_slice(b"READY");
+        ready.push(11);
+        ready.extend_from_slice(b"Socket-Type");
+        ready.extend_from_slice(&3_u32.to_be_bytes());
+        ready.extend_from_slice(b"REQ");
+        write_frame(&mut stream, FLAG_COMMAND, &ready).unwrap();
+        stream
+    }
+
+    /// Minimal ZMTP `REQ` client: request and reply
+    fn request(stream: &mut TcpStream, request: &str) -> String {
+        write_frame(stream, FLAG_MORE, &[]).unwrap();
+        write_frame(stream, 0, request.as_bytes()).unwrap();
+        let delimiter = read_frame(stream).unwrap();
+        assert_eq!(FLAG_MORE, delimiter.flags);
+        assert!(delimiter.body.is_empty());
+        let reply = read_frame(stream).unwrap();
+        assert_eq!(0, reply.flags & FLAG_MORE);
+        String::from_utf8(reply.body).unwrap()
+    }
+
+    #[test]
+    fn t_zmq_frames() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, FLAG_MORE, b"abc").unwrap();
+        write_frame(&mut buffer, 0, &[7_u8; 300]).unwrap();
+        assert_eq!(&[FLAG_MORE, 3, b'a', b'b', b'c'], &buffer[..5]);
+        assert_eq!(FLAG_LONG, buffer[5]);
+        let mut reader = &buffer[..];
+        let frame = read_frame(&mut reader).unwrap();
+        assert_eq!((FLAG_MORE, b"abc".to_vec()), (frame.flags, frame.body));
+        let frame = read_frame(&mut reader).unwrap();
+        assert_eq!((FLAG_LONG, vec![7_u8; 300]), (frame.flags, frame.body));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn t_zmq_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = connect(address);
+            assert_eq!("{\"Pong\":7}", request(&mut stream, "{\"Ping\": 7}"));
+            let reply = request(&mut stream, "{\"Run\": {\"parameter\": [0.5]}}");
+            let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+            assert_eq!("Converged", reply["exit_status"]);
+            let u0 = reply["solution"][0].as_f64().unwrap();
+            unit_test_utils::assert_nearly_equal(0.5, u0, 1e-5, 1e-5, "u0");
+            let reply = request(&mut stream, "{\"Run\": {\"parameter\": []}}");
+            assert!(reply.contains("\"code\":3003"));
+            drop(stream);
+            // a new connection, which kills the server
+            let mut stream = connect(address);
+            write_frame(&mut stream, FLAG_MORE, &[]).unwrap();
+            write_frame(&mut stream, 0, b"{\"Kill\": 1}").unwrap();
+        });
+
+        let mut cache = AlmCache::new(PANOCCache::new(2, 1e-6, 5).unwrap(), 0, 0);
+        let mut server = TcpServer::new(2, 1, 0, |p: &[f64], u: &mut [f64], _y0, _c0| {
+            let p0 = p[0];
+            let alm_problem = AlmProblem::new(
+                Ball2::new(None, 1.0)?,
+                NO_SET,
+                NO_SET,
+                move |u: &[f64], _xi: &[f64], c: &mut f64| -> FunctionCallResult {
+                    *c = (u[0] - p0).powi(2) + u[1].powi(2);
+                    Ok(())
+                },
+                move |u: &[f64], _xi: &[f64], g: &mut [f64]| -> FunctionCallResult {
+                    g[0] = 2.0 * (u[0] - p0);
+                    g[1] = 2.0 * u[1];
+                    Ok(())
+                },
+                NO_MAPPING,
+                NO_MAPPING,
+                0,
+                0,
+            )?;
+            AlmOptimizer::new(&mut cache, alm_problem).solve(u)
+        });
+        server.serve_zmq(&listener).unwrap();
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn t_zmq_message_too_large() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            // every frame is within the limit, but the message is not, so the
+            // server drops the connection before the message is complete
+            let mut stream = connect(address);
+            write_frame(&mut stream, FLAG_MORE, &[]).unwrap();
+            let frame = vec![b' '; (MAX_MESSAGE_SIZE / 4) as usize];
+            let sent = (0..8).try_for_each(|_| write_frame(&mut stream, FLAG_MORE, &frame));
+            stream
+                .set_read_timeout(Some(std::time::Duration::from_secs(10)))
+                .unwrap();
+            let mut reply = Vec::new();
+            let dropped = sent.is_err()
+                || match stream.read_to_end(&mut reply) {
+                    Ok(_) => reply.is_empty(),
+                    Err(error) => !matches!(
+                        error.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ),
+                };
+            assert!(dropped, "the connection was not dropped");
+            drop(stream);
+            let mut stream = connect(address);
+            assert_eq!("{\"Pong\":1}", request(&mut stream, "{\"Ping\": 1}"));
+            write_frame(&mut stream, FLAG_MORE, &[]).unwrap();
+            write_frame(&mut stream, 0, b"{\"Kill\": 1}").unwrap();
+        });
+
+        let mut server = TcpServer::new(1, 1, 0, |_p: &[f64], _u: &mut [f64], _y0, _c0| {
+            Err(SolverError::user("not solved"))
+        });
+        server.serve_zmq(&listener).unwrap();
+        client.join().unwrap();
+    }
+}
@@ -0,0 +1,481 @@
+//! Benchmark problems with known solutions
+//!
+//! This module (which requires the feature `test_problems`) provides a suite
+//! of standard test problems &mdash; the Rosenbrock and the Himmelblau
+//! functions, a small model predictive control (MPC) problem and
+//! constrained variants &mdash; together with their known solutions, so
+//! that solver settings can be benchmarked and installations can be verified
+//! against reference results.
+//!
+//! Every problem implements [`TestProblem`], which provides the cost
+//! function, its gradient, the set of constraints, an initial guess and the
+//! (global) minimisers.
+//!
+//! # Example
+//!
+//! ```rust
+//! use optimization_engine::{panoc::*, test_problems::{self, TestProblem}, Optimizer, Problem};
+//!
+//! for test_problem in test_problems::all() {
+//!     let n = test_problem.dimension();
+//!     let constraints = test_problem.constraints();
+//!     let problem = Problem::new(
+//!         &constraints,
+//!         |u: &[f64], grad: &mut [f64]| test_problem.gradient(u, grad),
+//!         |u: &[f64], cost: &mut f64| test_problem.cost(u, cost),
+//!     );
+//!     let mut u = test_problem.initial_guess();
+//!     let status = PANOCOptimizer::from_dimensions(problem, n, 1e-10, 10)?
+//!         .with_max_iter(5000)?
+//!         .solve(&mut u)?;
+//!     assert!(status.has_converged(), "{}", test_problem.name());
+//!     assert!(test_problem.distance_to_solution(&u) < 1e-5);
+//! }
+//! # Ok::<(), optimization_engine::SolverError>(())
+//! ```
+//!
+use crate::{
+    constraints::{BallInf, BoxedConstraint, NoConstraints, Rectangle},
+    FunctionCallResult,
+};
+
+/// Optimization problem, $\min_{u \in U} f(u)$, with known solutions
+pub trait TestProblem: Send + Sync {
+    /// Name of the problem
+    fn name(&self) -> &'static str;
+
+    /// Number of decision variables
+    fn dimension(&self) -> usize;
+
+    /// Cost function, $f$
+    fn cost(&self, u: &[f64], cost: &mut f64) -> FunctionCallResult;
+
+    /// Gradient of the cost function, $\nabla f$
+    fn gradient(&self, u: &[f64], grad: &mut [f64]) -> FunctionCallResult;
+
+    /// Set of constraints, $U$
+    fn constraints(&self) -> BoxedConstraint<'_>;
+
+    /// Initial guess from which the problem is usually solved
+    fn initial_guess(&self) -> Vec<f64>;
+
+    /// Global minimisers
+    fn solutions(&self) -> Vec<Vec<f64>>;
+
+    /// Optimal cost (at the global minimisers)
+    fn optimal_cost(&self) -> f64;
+
+    /// Infinity-norm distance of `u` from the closest global minimiser
+    fn distance_to_solution(&self, u: &[f64]) -> f64 {
+        self.solutions()
+            .iter()
+            .map(|solution| {
+                solution
+                    .iter()
+                    .zip(u)
+                    .fold(0.0, |max, (s, u)| f64::max(max, (s - u).abs()))
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+/// Rosenbrock function, $f(u) = (a - u_1)^2 + b (u_2 - u_1^2)^2$
+///
+/// Without constraints, the minimiser is $(a, a^2)$. With the bound
+/// $u_1 \leq \bar{u}_1 < a$ (see `with_upper_bound`), the minimiser is
+/// $(\bar{u}_1, \bar{u}_1^2)$ and the optimal cost is $(a - \bar{u}_1)^2$.
+///
+/// The default values are $a = 1$ and $b = 100$ and the initial guess is
+/// $(-1.2, 1)$.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rosenbrock {
+    a: f64,
+    b: f64,
+    /// upper bounds on $u$ (if any)
+    u_max: Option<[f64; 2]>,
+}
+
+impl Rosenbrock {
+    /// Constructs the Rosenbrock function with parameters `a` and `b`
+    /// (without constraints)
+    pub fn new(a: f64, b: f64) -> Self {
+        Rosenbrock { a, b, u_max: None }
+    }
+
+    /// Imposes the constraint $u_1 \leq \bar{u}_1$, where $\bar{u}_1$ is
+    /// `u1_max`, which must be smaller than $a$ (so that the constraint is
+    /// active at the solution)
+    ///
+    /// # Panics
+    ///
+    /// The method panics if `u1_max` is not smaller than $a$
+    pub fn with_upper_bound(mut self, u1_max: f64) -> Self {
+        assert!(u1_max < self.a, "u1_max must be smaller than a");
+        self.u_max = Some([u1_max, f64::INFINITY]);
+        self
+    }
+}
+
+impl Default for Rosenbrock {
+    fn default() -> Self {
+        Rosenbrock::new(1.0, 100.0)
+    }
+}
+
+impl TestProblem for Rosenbrock {
+    fn name(&self) -> &'static str {
+        match self.u_max {
+            None => "rosenbrock",
+            Some(_) => "rosenbrock (bounded)",
+        }
+    }
+
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn cost(&self, u: &[f64], cost: &mut f64) -> FunctionCallResult {
+        *cost = (self.a - u[0]).powi(2) + self.b * (u[1] - u[0].powi(2)).powi(2);
+        Ok(())
+    }
+
+    fn gradient(&self, u: &[f64], grad: &mut [f64]) -> FunctionCallResult {
+        grad[0] = 2.0 * (u[0] - self.a) - 4.0 * self.b * u[0] * (u[1] - u[0].powi(2));
+        grad[1] = 2.0 * self.b * (u[1] - u[0].powi(2));
+        Ok(())
+    }
+
+    fn constraints(&self) -> BoxedConstraint<'_> {
+        match &self.u_max {
+            None => Box::new(NoConstraints::new()),
+            Some(u_max) => Box::new(Rectangle::new(None, Some(u_max)).expect("valid bounds")),
+        }
+    }
+
+    fn initial_guess(&self) -> Vec<f64> {
+        vec![-1.2, 1.0]
+    }
+
+    fn solutions(&self) -> Vec<Vec<f64>> {
+        let u1 = self.u_max.map_or(self.a, |u_max| u_max[0]);
+        vec![vec![u1, u1.powi(2)]]
+    }
+
+    fn optimal_cost(&self) -> f64 {
+        self.u_max.map_or(0.0, |u_max| (self.a - u_max[0]).powi(2))
+    }
+}
+
+/// Himmelblau function, $f(u) = (u_1^2 + u_2 - 11)^2 + (u_1 + u_2^2 - 7)^2$
+///
+/// The function has four global minimisers (with optimal cost $0$). On the
+/// nonnegative orthant (see `nonnegative`), the only global minimiser is
+/// $(3, 2)$. The initial guess is $(1, 1)$.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Himmelblau {
+    /// lower bounds on $u$ (if any)
+    u_min: Option<[f64; 2]>,
+}
+
+/// Global minimisers of the Himmelblau function
+const HIMMELBLAU_MINIMISERS: [[f64; 2]; 4] = [
+    [3.0, 2.0],
+    [-2.805_118_086_952_745, 3.131_312_518_250_573],
+    [-3.779_310_253_377_747, -3.283_185_991_286_17],
+    [3.584_428_340_330_492, -1.848_126_526_964_404],
+];
+
+impl Himmelblau {
+    /// Constructs the Himmelblau function (without constraints)
+    pub fn new() -> Self {
+        Himmelblau { u_min: None }
+    }
+
+    /// Constructs the Himmelblau function on the nonnegative orthant, $u \geq 0$
+    pub fn nonnegative() -> Self {
+        Himmelblau {
+            u_min: Some([0.0; 2]),
+        }
+    }
+}
+
+impl TestProblem for Himmelblau {
+    fn name(&self) -> &'static str {
+        match self.u_min {
+            None => "himmelblau",
+            Some(_) => "himmelblau (nonnegative)",
+        }
+    }
+
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn cost(&self, u: &[f64], cost: &mut f64) -> FunctionCallResult {
+        *cost = (u[0].powi(2) + u[1] - 11.0).powi(2) + (u[0] + u[1].powi(2) - 7.0).powi(2);
+        Ok(())
+    }
+
+    fn gradient(&self, u: &[f64], grad: &mut [f64]) -> FunctionCallResult {
+        let r1 = u[0].powi(2) + u[1] - 11.0;
+        let r2 = u[0] + u[1].powi(2) - 7.0;
+        grad[0] = 4.0 * u[0] * r1 + 2.0 * r2;
+        grad[1] = 2.0 * r1 + 4.0 * u[1] * r2;
+        Ok(())
+    }
+
+    fn constraints(&self) -> BoxedConstraint<'_> {
+        match &self.u_min {
+            None => Box::new(NoConstraints::new()),
+            Some(u_min) => Box::new(Rectangle::new(Some(u_min), None).expect("valid bounds")),
+        }
+    }
+
+    fn initial_guess(&self) -> Vec<f64> {
+        vec![1.0, 1.0]
+    }
+
+    fn solutions(&self) -> Vec<Vec<f64>> {
+        HIMMELBLAU_MINIMISERS
+            .iter()
+            .filter(|u| self.u_min.is_none() || u.iter().all(|&ui| ui >= 0.0))
+            .map(|u| u.to_vec())
+            .collect()
+    }
+
+    fn optimal_cost(&self) -> f64 {
+        0.0
+    }
+}
+
+/// Horizon of `DoubleIntegratorMpc`
+const MPC_HORIZON: usize = 10;
+/// Sampling time of `DoubleIntegratorMpc`
+const MPC_SAMPLING_TIME: f64 = 0.5;
+/// Initial state of `DoubleIntegratorMpc`
+const MPC_INITIAL_STATE: [f64; 2] = [2.0, 0.0];
+/// State weight (stage cost) of `DoubleIntegratorMpc`
+const MPC_STATE_WEIGHT: f64 = 1.0;
+/// Input weight of `DoubleIntegratorMpc`
+const MPC_INPUT_WEIGHT: f64 = 0.1;
+/// State weight (terminal cost) of `DoubleIntegratorMpc`
+const MPC_TERMINAL_WEIGHT: f64 = 10.0;
+/// Solution of `DoubleIntegratorMpc` (computed in exact arithmetic on the
+/// active set, to 16 significant digits)
+const MPC_SOLUTION: [f64; MPC_HORIZON] = [
+    -1.0,
+    -1.0,
+    0.142_254_146_533_738_6,
+    0.595_149_524_620_442_5,
+    0.479_017_759_452_721_7,
+    0.310_797_731_947_367_5,
+    0.185_960_358_509_392_2,
+    0.107_406_068_547_122,
+    0.070_286_044_296_696_91,
+    0.099_734_235_414_443_12,
+];
+
+/// Model predictive control of a double integrator with bounded inputs
+///
+/// The state, $x = (p, v)$, consists of the position and the velocity,
+/// the input, $u_t$, is the acceleration, with $|u_t| \leq 1$, and the
+/// dynamics (with sampling time $h = 0.5$) is
+///
+/// $$x_{t+1} = \begin{bmatrix}1 & h \\\\ 0 & 1\end{bmatrix} x_t +
+/// \begin{bmatrix}h^2/2 \\\\ h\end{bmatrix} u_t.$$
+///
+/// Starting from $x_0 = (2, 0)$, the cost
+///
+/// $$f(u) = \sum_{t=0}^{N-1} \left(\\|x_t\\|^2 + 0.1 u_t^2\right) + 10 \\|x_N\\|^2$$
+///
+/// is minimised over the horizon $N = 10$ (the decision variables are
+/// $u_0, \ldots, u_{N-1}$). The bounds are active at the first two inputs.
+/// The initial guess is $u = 0$.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DoubleIntegratorMpc;
+
+impl DoubleIntegratorMpc {
+    /// Constructs the MPC problem
+    pub fn new() -> Self {
+        DoubleIntegratorMpc
+    }
+
+    /// Simulates the system and returns the states $x_0, \ldots, x_N$
+    fn simulate(u: &[f64]) -> [[f64; 2]; MPC_HORIZON + 1] {
+        let h = MPC_SAMPLING_TIME;
+        let mut x = [MPC_INITIAL_STATE; MPC_HORIZON + 1];
+        for t in 0..MPC_HORIZON {
+            let [p, v] = x[t];
+            x[t + 1] = [p + h * v + 0.5 * h * h * u[t], v + h * u[t]];
+        }
+        x
+    }
+}
+
+impl TestProblem for DoubleIntegratorMpc {
+    fn name(&self) -> &'static str {
+        "double integrator mpc"
+    }
+
+    fn dimension(&self) -> usize {
+        MPC_HORIZON
+    }
+
+    fn cost(&self, u: &[f64], cost: &mut f64) -> FunctionCallResult {
+        let x = Self::simulate(u);
+        let norm2 = |x: &[f64; 2]| x[0] * x[0] + x[1] * x[1];
+        *cost = x[..MPC_HORIZON]
+            .iter()
+            .zip(u)
+            .map(|(x, u)| MPC_STATE_WEIGHT * norm2(x) + MPC_INPUT_WEIGHT * u * u)
+            .sum::<f64>()
+            + MPC_TERMINAL_WEIGHT * norm2(&x[MPC_HORIZON]);
+        Ok(())
+    }
+
+    fn gradient(&self, u: &[f64], grad: &mut [f64]) -> FunctionCallResult {
+        // backward (adjoint) recursion, where lambda is the gradient of the
+        // cost-to-go with respect to the state
+        let h = MPC_SAMPLING_TIME;
+        let x = Self::simulate(u);
+        let [p, v] = x[MPC_HORIZON];
+        let mut lambda = [2.0 * MPC_TERMINAL_WEIGHT * p, 2.0 * MPC_TERMINAL_WEIGHT * v];
+        for t in (0..MPC_HORIZON).rev() {
+            grad[t] = 2.0 * MPC_INPUT_WEIGHT * u[t] + 0.5 * h * h * lambda[0] + h * lambda[1];
+            let [p, v] = x[t];
+            lambda = [
+                2.0 * MPC_STATE_WEIGHT * p + lambda[0],
+                2.0 * MPC_STATE_WEIGHT * v + h * lambda[0] + lambda[1],
+            ];
+        }
+        Ok(())
+    }
+
+    fn constraints(&self) -> BoxedConstraint<'_> {
+        Box::new(BallInf::new(None, 1.0).expect("valid radius"))
+    }
+
+    fn initial_guess(&self) -> Vec<f64> {
+        vec![0.0; MPC_HORIZON]
+    }
+
+    fn solutions(&self) -> Vec<Vec<f64>> {
+        vec![MPC_SOLUTION.to_vec()]
+    }
+
+    fn optimal_cost(&self) -> f64 {
+        let mut cost = 0.0;
+        self.cost(&MPC_SOLUTION, &mut cost)
+            .expect("the cost can be evaluated");
+        cost
+    }
+}
+
+/// All test problems of this module (in their default configuration)
+pub fn all() -> Vec<Box<dyn TestProblem>> {
+    vec![
+        Box::new(Rosenbrock::default()),
+        Box::new(Rosenbrock::default().with_upper_bound(0.5)),
+        Box::new(Himmelblau::new()),
+        Box::new(Himmelblau::nonnegative()),
+        Box::new(DoubleIntegratorMpc::new()),
+    ]
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::panoc::PANOCOptimizer, Optimizer, Problem};
+
+    /// Compares the gradient with central finite differences
+    fn check_gradient(test_problem: &dyn TestProblem, u: &[f64]) {
+        let n = test_problem.dimension();
+        let mut grad = vec![0.0; n];
+        test_problem.gradient(u, &mut grad).unwrap();
+        for i in 0..n {
+            let (mut u_plus, mut u_minus) = (u.to_vec(), u.to_vec());
+            u_plus[i] += 1e-6;
+            u_minus[i] -= 1e-6;
+            let (mut f_plus, mut f_minus) = (0.0, 0.0);
+            test_problem.cost(&u_plus, &mut f_plus).unwrap();
+            test_problem.cost(&u_minus, &mut f_minus).unwrap();
+            unit_test_utils::assert_nearly_equal(
+                (f_plus - f_minus) / 2e-6,
+                grad[i],
+                1e-6,
+                1e-6,
+                test_problem.name(),
+            );
+        }
+    }
+
+    #[test]
+    fn t_test_problems_gradients() {
+        for test_problem in all() {
+            let u: Vec<f64> = (0..test_problem.dimension())
+                .map(|i| 0.3 - 0.1 * i as f64)
+                .collect();
+            check_gradient(test_problem.as_ref(), &u);
+            check_gradient(test_problem.as_ref(), &test_problem.initial_guess());
+        }
+    }
+
+    #[test]
+    fn t_test_problems_solutions() {
+        for test_problem in all() {
+            for solution in test_problem.solutions() {
+                let mut cost = 0.0;
+                test_problem.cost(&solution, &mut cost).unwrap();
+                assert!(
+                    (test_problem.optimal_cost() - cost).abs() < 1e-12,
+                    "{}",
+                    test_problem.name()
+                );
+                let mut projection = solution.clone();
+                test_problem.constraints().project(&mut projection);
+                assert_eq!(solution, projection, "{}", test_problem.name());
+            }
+        }
+        assert_eq!(4, Himmelblau::new().solutions().len());
+        assert_eq!(vec![vec![3.0, 2.0]], Himmelblau::nonnegative().solutions());
+        let rosenbrock = Rosenbrock::new(2.0, 50.0).with_upper_bound(1.5);
+        assert_eq!(vec![vec![1.5, 2.25]], rosenbrock.solutions());
+        assert_eq!(0.25, rosenbrock.optimal_cost());
+    }
+
+    #[test]
+    fn t_test_problems_panoc() {
+        for test_problem in all() {
+            let constraints = test_problem.constraints();
+            let problem = Problem::new(
+                &constraints,
+                |u: &[f64], grad: &mut [f64]| test_problem.gradient(u, grad),
+                |u: &[f64], cost: &mut f64| test_problem.cost(u, cost),
+            );
+            let mut u = test_problem.initial_guess();
+            let status =
+                PANOCOptimizer::from_dimensions(problem, test_problem.dimension(), 1e-10, 10)
+                    .unwrap()
+                    .with_max_iter(5000)
+                    .unwrap()
+                    .solve(&mut u)
+                    .unwrap();
+            assert!(status.has_converged(), "{}", test_problem.name());
+            assert!(
+                test_problem.distance_to_solution(&u) < 1e-5,
+                "{}: {:?}",
+                test_problem.name(),
+                u
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn t_rosenbrock_inactive_bound() {
+        let _rosenbrock = Rosenbrock::default().with_upper_bound(2.0);
+    }
+}
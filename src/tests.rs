@@ -1,10 +1,10 @@
-use crate::{constraints::*, core::fbs::*, core::*};
+use crate::{constraints::*, core::fbs::*, core::*, SolverError};
 use std::num::NonZeroUsize;
 
 #[test]
 fn t_access() {
     let radius = 0.2;
-    let box_constraints = Ball2::new(None, radius);
+    let box_constraints = Ball2::new(None, radius).unwrap();
     let problem = Problem::new(
         &box_constraints,
         super::mocks::my_gradient,
@@ -13,7 +13,7 @@ fn t_access() {
     let gamma = 0.1;
     let tolerance = 1e-6;
 
-    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), gamma, tolerance);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), gamma, tolerance).unwrap();
     let mut u = [0.0; 2];
     let mut optimizer = FBSOptimizer::new(problem, &mut fbs_cache);
 
@@ -24,3 +24,613 @@ fn t_access() {
     assert!((-0.14896 - u[0]).abs() < 1e-4);
     assert!((0.13346 - u[1]).abs() < 1e-4);
 }
+
+#[test]
+fn t_solver_error_display() {
+    let error = SolverError::NotFiniteGradient {
+        location: "PANOC",
+        index: 3,
+        iteration: 17,
+    };
+    assert_eq!(
+        "PANOC: the gradient of the cost is not finite at index 3 (iteration 17)",
+        format!("{}", error)
+    );
+
+    let error = SolverError::DimensionMismatch {
+        name: "u",
+        expected: 5,
+        actual: 4,
+    };
+    assert_eq!(
+        "`u` has wrong dimension (expected 5, found 4)",
+        error.to_string()
+    );
+
+    let boxed: Box<dyn std::error::Error> = Box::new(SolverError::user("simulator failed"));
+    assert_eq!("user error: simulator failed", boxed.to_string());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn t_serde_solver_status() {
+    let status = SolverStatus::new(
+        ExitStatus::NotConvergedIterations,
+        42,
+        std::time::Duration::from_micros(1500),
+        1e-3,
+        -2.5,
+    );
+    let json = serde_json::to_string(&status).unwrap();
+    let status_copy: SolverStatus = serde_json::from_str(&json).unwrap();
+    assert_eq!(status, status_copy);
+
+    let exit_status: ExitStatus = serde_json::from_str("\"Converged\"").unwrap();
+    assert_eq!(ExitStatus::Converged, exit_status);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn t_serde_alm_optimizer_status() {
+    let status = crate::alm::AlmOptimizerStatus::new(ExitStatus::Converged)
+        .with_outer_iterations(5)
+        .with_inner_iterations(120)
+        .with_lagrange_multipliers(&[1.0, -2.0])
+        .with_penalty(50.0)
+        .with_cost(3.5);
+    let json = serde_json::to_string(&status).unwrap();
+    let status_copy: crate::alm::AlmOptimizerStatus = serde_json::from_str(&json).unwrap();
+    assert_eq!(5, status_copy.num_outer_iterations());
+    assert_eq!(120, status_copy.num_inner_iterations());
+    assert_eq!(&Some(vec![1.0, -2.0]), status_copy.lagrange_multipliers());
+    assert_eq!(50.0, status_copy.penalty());
+    assert_eq!(3.5, status_copy.cost());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn t_serde_configurations() {
+    use crate::{alm::AlmConfig, core::panoc::PanocConfig};
+
+    let alm_config: AlmConfig =
+        serde_json::from_str(r#"{"max_outer_iterations": 20, "epsilon_tolerance": 1e-5}"#).unwrap();
+    assert_eq!(20, alm_config.max_outer_iterations);
+    assert_eq!(1e-5, alm_config.epsilon_tolerance);
+    assert_eq!(
+        AlmConfig::default().delta_tolerance,
+        alm_config.delta_tolerance
+    );
+    assert_eq!(None, alm_config.initial_penalty);
+
+    let panoc_config = PanocConfig::new()
+        .with_tolerance(1e-6)
+        .with_max_duration(std::time::Duration::from_millis(5));
+    let json = serde_json::to_string(&panoc_config).unwrap();
+    let panoc_config_copy: PanocConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(panoc_config, panoc_config_copy);
+}
+
+/// Error raised by a (fictitious) simulator used in user-defined functions
+#[derive(Debug)]
+struct SimulatorError {
+    code: i32,
+}
+
+impl std::fmt::Display for SimulatorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "simulator failed with code {}", self.code)
+    }
+}
+
+impl std::error::Error for SimulatorError {}
+
+fn failing_cost(_u: &[f64], _c: &mut f64) -> crate::FunctionCallResult {
+    Err(SolverError::user(SimulatorError { code: 42 }))
+}
+
+fn assert_simulator_error(error: SolverError) {
+    use std::error::Error;
+    assert_eq!(
+        "user error: simulator failed with code 42",
+        error.to_string()
+    );
+    let source = error.source().unwrap();
+    assert_eq!(42, source.downcast_ref::<SimulatorError>().unwrap().code);
+    match error {
+        SolverError::User(payload) => {
+            let payload = payload.downcast::<SimulatorError>().unwrap();
+            assert_eq!(42, payload.code);
+        }
+        _ => panic!("wrong error variant"),
+    }
+}
+
+#[test]
+fn t_user_error_propagation_panoc_fbs() {
+    let bounds = Ball2::new(None, 1.0).unwrap();
+
+    let problem = Problem::new(&bounds, super::mocks::my_gradient, failing_cost);
+    let mut panoc_cache = crate::panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+    let mut u = [0.0; 2];
+    let result = crate::panoc::PANOCOptimizer::new(problem, &mut panoc_cache).solve(&mut u);
+    assert_simulator_error(result.unwrap_err());
+
+    let problem = Problem::new(
+        &bounds,
+        |_u: &[f64], _g: &mut [f64]| -> crate::FunctionCallResult {
+            Err(SolverError::user(SimulatorError { code: 42 }))
+        },
+        super::mocks::my_cost,
+    );
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), 0.1, 1e-6).unwrap();
+    let result = FBSOptimizer::new(problem, &mut fbs_cache).solve(&mut u);
+    assert_simulator_error(result.unwrap_err());
+}
+
+#[test]
+fn t_user_error_propagation_alm() {
+    use crate::alm::*;
+
+    let panoc_cache = crate::panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+    let mut alm_cache = AlmCache::new(panoc_cache, 0, 0);
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 1.0).unwrap(),
+        NO_SET,
+        NO_SET,
+        |u: &[f64], _xi: &[f64], cost: &mut f64| failing_cost(u, cost),
+        |u: &[f64], _xi: &[f64], grad: &mut [f64]| super::mocks::my_gradient(u, grad),
+        NO_MAPPING,
+        NO_MAPPING,
+        0,
+        0,
+    )
+    .unwrap();
+    let mut u = [0.0; 2];
+    let result = AlmOptimizer::new(&mut alm_cache, alm_problem).solve(&mut u);
+    assert_simulator_error(result.unwrap_err());
+}
+
+#[test]
+fn t_user_error_eq() {
+    assert_eq!(SolverError::user("failure"), SolverError::user("failure"));
+    assert_ne!(SolverError::user("failure"), SolverError::user("other"));
+    assert_ne!(SolverError::user("failure"), SolverError::Cost);
+}
+
+/// Gradient of `my_cost` which is NaN at the second element whenever
+/// `u[0] > 0.1`; the initial point is `u = 0`
+fn gradient_nan_far_from_origin(u: &[f64], grad: &mut [f64]) -> crate::FunctionCallResult {
+    super::mocks::my_gradient(u, grad)?;
+    if u[0].abs() > 0.1 {
+        grad[1] = f64::NAN;
+    }
+    Ok(())
+}
+
+#[test]
+fn t_nan_screening_panoc_fbs() {
+    let bounds = Ball2::new(None, 10.0).unwrap();
+
+    let problem = Problem::new(&bounds, gradient_nan_far_from_origin, super::mocks::my_cost);
+    let mut panoc_cache = crate::panoc::PANOCCache::new(2, 1e-10, 5).unwrap();
+    let mut u = [0.0; 2];
+    let result = crate::panoc::PANOCOptimizer::new(problem, &mut panoc_cache)
+        .with_nan_screening(true)
+        .solve(&mut u);
+    match result {
+        Err(SolverError::NotFiniteOutput {
+            location: "PANOC",
+            function: "gradient",
+            index: 1,
+            ..
+        }) => {}
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    let problem = Problem::new(&bounds, gradient_nan_far_from_origin, super::mocks::my_cost);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), 0.1, 1e-10).unwrap();
+    let mut u = [0.0; 2];
+    let result = FBSOptimizer::new(problem, &mut fbs_cache)
+        .with_nan_screening(true)
+        .solve(&mut u);
+    match result {
+        Err(SolverError::NotFiniteOutput {
+            location: "FBS",
+            function: "gradient",
+            index: 1,
+            ..
+        }) => {}
+        _ => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn t_nan_screening_alm() {
+    use crate::alm::*;
+
+    let panoc_cache = crate::panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+    let mut alm_cache = AlmCache::new(panoc_cache, 1, 0);
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 1.0).unwrap(),
+        Some(Ball2::new(None, 1.0).unwrap()),
+        Some(Ball2::new(None, 100.0).unwrap()),
+        |u: &[f64], _xi: &[f64], cost: &mut f64| super::mocks::my_cost(u, cost),
+        |u: &[f64], _xi: &[f64], grad: &mut [f64]| super::mocks::my_gradient(u, grad),
+        Some(|_u: &[f64], f1: &mut [f64]| -> crate::FunctionCallResult {
+            f1[0] = f64::INFINITY;
+            Ok(())
+        }),
+        NO_MAPPING,
+        1,
+        0,
+    )
+    .unwrap();
+    let mut u = [0.0; 2];
+    let result = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .with_nan_screening(true)
+        .solve(&mut u);
+    assert_eq!(
+        Err(SolverError::NotFiniteOutput {
+            location: "ALM",
+            function: "F1",
+            index: 0,
+            iteration: 0,
+        }),
+        result.map(|_| ())
+    );
+}
+
+#[test]
+fn t_nan_screening_display() {
+    let error = SolverError::NotFiniteOutput {
+        location: "PANOC",
+        function: "gradient",
+        index: 2,
+        iteration: 7,
+    };
+    assert_eq!(
+        "PANOC: the output of `gradient` is not finite at index 2 (iteration 7)",
+        error.to_string()
+    );
+}
+
+#[test]
+fn t_invalid_parameters() {
+    let error = Ball2::new(None, -1.0).err().unwrap();
+    assert_eq!(
+        SolverError::InvalidParameter {
+            name: "radius",
+            reason: "must be positive"
+        },
+        error
+    );
+    assert_eq!(
+        "invalid parameter `radius`: must be positive",
+        error.to_string()
+    );
+
+    assert!(panoc::PANOCCache::new(2, -1e-6, 5).is_err());
+    assert!(FBSCache::new(NonZeroUsize::new(2).unwrap(), 0.0, 1e-6).is_err());
+
+    let bounds = Ball2::new(None, 1.0).unwrap();
+    let problem = Problem::new(&bounds, super::mocks::my_gradient, super::mocks::my_cost);
+    let mut panoc_cache = panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+    let panoc = panoc::PANOCOptimizer::new(problem, &mut panoc_cache).with_max_iter(0);
+    assert!(matches!(
+        panoc,
+        Err(SolverError::InvalidParameter {
+            name: "max_iter",
+            ..
+        })
+    ));
+}
+
+/// Solves a problem using any optimizer
+fn solve_generic<O: Optimizer>(optimizer: &mut O, u: &mut [f64]) -> SolverStatus {
+    optimizer.solve(u).unwrap()
+}
+
+#[test]
+fn t_optimizer_trait_alm() {
+    use crate::alm::*;
+
+    let mut u = [0.0; 2];
+    let mut u_generic = [0.0; 2];
+    let solve = |u: &mut [f64], generic: bool| -> (SolverStatus, Option<AlmOptimizerStatus>) {
+        let panoc_cache = crate::panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+        let mut alm_cache = AlmCache::new(panoc_cache, 0, 0);
+        let alm_problem = AlmProblem::new(
+            Ball2::new(None, 0.2).unwrap(),
+            NO_SET,
+            NO_SET,
+            |u: &[f64], _xi: &[f64], cost: &mut f64| super::mocks::my_cost(u, cost),
+            |u: &[f64], _xi: &[f64], grad: &mut [f64]| super::mocks::my_gradient(u, grad),
+            NO_MAPPING,
+            NO_MAPPING,
+            0,
+            0,
+        )
+        .unwrap();
+        let mut alm_optimizer = AlmOptimizer::new(&mut alm_cache, alm_problem);
+        if generic {
+            (solve_generic(&mut alm_optimizer, u), None)
+        } else {
+            let alm_status = alm_optimizer.solve(u).unwrap();
+            (SolverStatus::from(&alm_status), Some(alm_status))
+        }
+    };
+    let (status, alm_status) = solve(&mut u, false);
+    let (status_generic, _) = solve(&mut u_generic, true);
+    let alm_status = alm_status.unwrap();
+
+    assert!(status_generic.has_converged());
+    assert_eq!(u, u_generic);
+    assert_eq!(alm_status.num_inner_iterations(), status.iterations());
+    assert_eq!(status.iterations(), status_generic.iterations());
+    assert_eq!(alm_status.cost(), status_generic.cost_value());
+    assert_eq!(
+        alm_status.last_problem_norm_fpr(),
+        status_generic.norm_fpr()
+    );
+}
+
+#[test]
+fn t_oracle_calls_panoc_fbs() {
+    let bounds = Ball2::new(None, 0.2).unwrap();
+
+    let problem = Problem::new(&bounds, super::mocks::my_gradient, super::mocks::my_cost);
+    let mut panoc_cache = crate::panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+    let mut u = [0.0; 2];
+    let status = crate::panoc::PANOCOptimizer::new(problem, &mut panoc_cache)
+        .solve(&mut u)
+        .unwrap();
+    let calls = status.oracle_calls();
+    assert!(status.has_converged());
+    assert!(calls.cost() > status.iterations());
+    assert!(calls.gradient() > status.iterations());
+    assert!(calls.projections() > status.iterations());
+    assert_eq!(0, calls.mapping_f1());
+    assert_eq!(0, calls.mapping_f2());
+
+    // the counters are reset when the cache is reused
+    let problem = Problem::new(&bounds, super::mocks::my_gradient, super::mocks::my_cost);
+    let mut u = [0.0; 2];
+    let status_again = crate::panoc::PANOCOptimizer::new(problem, &mut panoc_cache)
+        .solve(&mut u)
+        .unwrap();
+    assert_eq!(calls, status_again.oracle_calls());
+
+    // FBS: one gradient evaluation and one projection per iteration, and
+    // the cost is evaluated only at the solution
+    let problem = Problem::new(&bounds, super::mocks::my_gradient, super::mocks::my_cost);
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), 0.1, 1e-6).unwrap();
+    let mut u = [0.0; 2];
+    let status = FBSOptimizer::new(problem, &mut fbs_cache)
+        .solve(&mut u)
+        .unwrap();
+    let calls = status.oracle_calls();
+    assert_eq!(status.iterations() + 1, calls.gradient());
+    assert_eq!(calls.gradient(), calls.projections());
+    assert_eq!(1, calls.cost());
+}
+
+#[test]
+fn t_oracle_calls_alm() {
+    use crate::alm::*;
+
+    let panoc_cache = crate::panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+    let mut alm_cache = AlmCache::new(panoc_cache, 1, 1);
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 1.0).unwrap(),
+        Some(Ball2::new(None, 0.1).unwrap()),
+        Some(Ball2::new(None, 100.0).unwrap()),
+        |u: &[f64], _xi: &[f64], cost: &mut f64| super::mocks::my_cost(u, cost),
+        |u: &[f64], _xi: &[f64], grad: &mut [f64]| super::mocks::my_gradient(u, grad),
+        Some(|u: &[f64], f1: &mut [f64]| -> crate::FunctionCallResult {
+            f1[0] = u[0] + u[1];
+            Ok(())
+        }),
+        Some(|u: &[f64], f2: &mut [f64]| -> crate::FunctionCallResult {
+            f2[0] = u[0] - u[1];
+            Ok(())
+        }),
+        1,
+        1,
+    )
+    .unwrap();
+    let mut u = [0.0; 2];
+    let status = AlmOptimizer::new(&mut alm_cache, alm_problem)
+        .solve(&mut u)
+        .unwrap();
+    let calls = status.oracle_calls();
+    assert_eq!(status.num_outer_iterations(), calls.mapping_f1());
+    assert_eq!(status.num_outer_iterations(), calls.mapping_f2());
+    assert!(calls.gradient() > status.num_inner_iterations());
+    assert!(calls.projections() > 2 * status.num_outer_iterations());
+    assert_eq!(calls, SolverStatus::from(&status).oracle_calls());
+}
+
+#[test]
+fn t_status_display() {
+    let status = SolverStatus::new(
+        ExitStatus::NotConvergedIterations,
+        42,
+        std::time::Duration::from_micros(1500),
+        1.5e-7,
+        -2.0,
+    );
+    assert_eq!(
+        "exit status : not converged (maximum number of iterations)\n\
+         iterations  : 42\n\
+         solve time  : 1.500ms\n\
+         FPR         : 1.500000e-7\n\
+         cost        : -2.000000e0",
+        status.to_string()
+    );
+
+    let bounds = Ball2::new(None, 0.2).unwrap();
+    let problem = Problem::new(&bounds, super::mocks::my_gradient, super::mocks::my_cost);
+    let mut panoc_cache = crate::panoc::PANOCCache::new(2, 1e-6, 5).unwrap();
+    let mut u = [0.0; 2];
+    let status = crate::panoc::PANOCOptimizer::new(problem, &mut panoc_cache)
+        .solve(&mut u)
+        .unwrap();
+    let summary = status.to_string();
+    assert_eq!(5, summary.lines().count());
+    assert!(summary.starts_with("exit status : converged\n"));
+}
+
+#[test]
+fn t_alm_status_display() {
+    let status = crate::alm::AlmOptimizerStatus::new(ExitStatus::Converged)
+        .with_outer_iterations(3)
+        .with_inner_iterations(25)
+        .with_solve_time(std::time::Duration::from_millis(2))
+        .with_last_problem_norm_fpr(1e-6)
+        .with_cost(0.5)
+        .with_penalty(10.0)
+        .with_delta_y_norm(1e-5)
+        .with_f2_norm(0.0);
+    assert_eq!(
+        "exit status         : converged\n\
+         outer iterations    : 3\n\
+         inner iterations    : 25\n\
+         solve time          : 2.000ms\n\
+         FPR (last inner)    : 1.000000e-6\n\
+         cost                : 5.000000e-1\n\
+         penalty             : 1.000000e1\n\
+         infeasibility (ALM) : 1.000000e-5\n\
+         infeasibility (PM)  : 0.000000e0",
+        status.to_string()
+    );
+}
+
+#[test]
+fn t_dyn_problem_panoc_fbs() {
+    let bounds = Ball2::new(None, 0.2).unwrap();
+    let problem = Problem::new(&bounds, super::mocks::my_gradient, super::mocks::my_cost);
+    let mut panoc_cache = crate::panoc::PANOCCache::new(2, 1e-8, 5).unwrap();
+    let mut u_generic = [0.0; 2];
+    crate::panoc::PANOCOptimizer::new(problem, &mut panoc_cache)
+        .solve(&mut u_generic)
+        .unwrap();
+
+    // optimizers of different problems have the same type
+    let mut optimizers: Vec<crate::panoc::DynPANOCOptimizer> = vec![
+        crate::panoc::PANOCOptimizer::from_dimensions(
+            DynProblem::new_boxed(
+                Ball2::new(None, 0.2).unwrap(),
+                super::mocks::my_gradient,
+                super::mocks::my_cost,
+            ),
+            2,
+            1e-8,
+            5,
+        )
+        .unwrap(),
+        crate::panoc::PANOCOptimizer::from_dimensions(
+            Problem::new_boxed(
+                BallInf::new(None, 0.2).unwrap(),
+                super::mocks::my_gradient,
+                super::mocks::my_cost,
+            ),
+            2,
+            1e-8,
+            5,
+        )
+        .unwrap(),
+    ];
+    let mut u = [0.0; 2];
+    assert!(optimizers[0].solve(&mut u).unwrap().has_converged());
+    assert_eq!(u_generic, u);
+    let mut u = [0.0; 2];
+    assert!(optimizers[1].solve(&mut u).unwrap().has_converged());
+    assert!(crate::matrix_operations::norm_inf(&u) <= 0.2 + 1e-12);
+
+    let problem: DynProblem = Problem::new_boxed(
+        Ball2::new(None, 0.2).unwrap(),
+        super::mocks::my_gradient,
+        super::mocks::my_cost,
+    );
+    let mut fbs_cache = FBSCache::new(NonZeroUsize::new(2).unwrap(), 0.1, 1e-8).unwrap();
+    let mut fbs: DynFBSOptimizer = FBSOptimizer::new(problem, &mut fbs_cache);
+    let mut u = [0.0; 2];
+    assert!(fbs.solve(&mut u).unwrap().has_converged());
+    assert!((u[0] - u_generic[0]).abs() < 1e-6);
+    assert!((u[1] - u_generic[1]).abs() < 1e-6);
+}
+
+#[test]
+fn t_dyn_alm_problem() {
+    use crate::alm::*;
+
+    let f1 = |u: &[f64], f1u: &mut [f64]| -> crate::FunctionCallResult {
+        f1u[0] = u[0] + u[1];
+        Ok(())
+    };
+    let psi = |u: &[f64], xi: &[f64], cost: &mut f64| -> crate::FunctionCallResult {
+        super::mocks::my_cost(u, cost)?;
+        *cost += xi[1] * (u[0] + u[1]);
+        Ok(())
+    };
+    let d_psi = |u: &[f64], xi: &[f64], grad: &mut [f64]| -> crate::FunctionCallResult {
+        super::mocks::my_gradient(u, grad)?;
+        grad.iter_mut().for_each(|g| *g += xi[1]);
+        Ok(())
+    };
+
+    let alm_problem = AlmProblem::new(
+        Ball2::new(None, 1.0).unwrap(),
+        Some(Ball2::new(None, 0.1).unwrap()),
+        Some(BallInf::new(None, 1e4).unwrap()),
+        psi,
+        d_psi,
+        Some(f1),
+        NO_MAPPING,
+        1,
+        0,
+    )
+    .unwrap();
+    let mut u_generic = [0.0; 2];
+    let status_generic = AlmOptimizer::from_dimensions(alm_problem, 2, 1e-8, 5)
+        .unwrap()
+        .solve(&mut u_generic)
+        .unwrap();
+
+    let alm_problem: DynAlmProblem = AlmProblem::new_boxed(
+        Ball2::new(None, 1.0).unwrap(),
+        Some(Ball2::new(None, 0.1).unwrap()),
+        Some(BallInf::new(None, 1e4).unwrap()),
+        psi,
+        d_psi,
+        Some(f1),
+        NO_MAPPING,
+        1,
+        0,
+    )
+    .unwrap();
+    let mut alm_optimizer: DynAlmOptimizer =
+        AlmOptimizer::from_dimensions(alm_problem, 2, 1e-8, 5).unwrap();
+    let mut u = [0.0; 2];
+    let status = alm_optimizer.solve(&mut u).unwrap();
+    assert_eq!(status_generic.exit_status(), status.exit_status());
+    assert_eq!(
+        status_generic.num_inner_iterations(),
+        status.num_inner_iterations()
+    );
+    assert_eq!(u_generic, u);
+
+    // the dimensions are validated as in `AlmProblem::new`
+    assert!(AlmProblem::new_boxed(
+        Ball2::new(None, 1.0).unwrap(),
+        Some(Ball2::new(None, 0.1).unwrap()),
+        NO_SET,
+        psi,
+        d_psi,
+        Some(f1),
+        NO_MAPPING,
+        1,
+        0,
+    )
+    .is_err());
+}
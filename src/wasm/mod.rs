@@ -0,0 +1,231 @@
+#![deny(missing_docs)]
+//! WebAssembly interface
+//!
+//! This module (which requires the feature `wasm`) is a thin
+//! [wasm-bindgen](https://rustwasm.github.io/docs/wasm-bindgen/) wrapper
+//! around PANOC, so that OpEn can be compiled to `wasm32-unknown-unknown`
+//! (e.g., with `wasm-pack`) and used from JavaScript, in the browser or in
+//! Node.js. With the feature `wasm`, the solve time is measured with
+//! `performance.now()`, since `std::time::Instant` is not available on
+//! `wasm32-unknown-unknown`.
+//!
+//! The cost function and its gradient are JavaScript functions:
+//! `cost(u)` returns the cost at `u` (a `Float64Array`) and
+//! `gradient(u, grad)` writes the gradient at `u` into the `Float64Array`
+//! `grad`. If either function throws, the solver stops and the exception is
+//! reported.
+//!
+//! # Example
+//!
+//! ```js
+//! import { Panoc } from "optimization_engine";
+//!
+//! const panoc = new Panoc(2, 1e-6, 5);
+//! panoc.set_bounds(new Float64Array([-1, -1]), new Float64Array([1, 1]));
+//! panoc.set_max_iterations(200);
+//! const u = new Float64Array([0.5, 0.5]);
+//! const status = panoc.solve(
+//!     (u) => (1 - u[0]) ** 2 + 100 * (u[1] - u[0] ** 2) ** 2,
+//!     (u, grad) => { /* ... */ },
+//!     u,
+//! );
+//! console.log(status.converged, status.iterations, u);
+//! ```
+//!
+use crate::{
+    constraints::{BoxedConstraint, NoConstraints, Rectangle},
+    core::{
+        panoc::{PANOCCache, PANOCOptimizer, PanocConfig},
+        ExitStatus, Optimizer, Problem, SolverStatus,
+    },
+    FunctionCallResult, SolverError,
+};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    /// A JavaScript function
+    pub type JsFunction;
+
+    #[wasm_bindgen(method, catch, js_name = call)]
+    fn call_cost(this: &JsFunction, context: &JsValue, u: &[f64]) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = call)]
+    fn call_gradient(
+        this: &JsFunction,
+        context: &JsValue,
+        u: &[f64],
+        grad: &mut [f64],
+    ) -> Result<JsValue, JsValue>;
+}
+
+/// Converts an exception, which was thrown by a JavaScript function, into
+/// a `SolverError`
+fn js_error(function: &str, exception: JsValue) -> SolverError {
+    SolverError::user(format!("{} threw {:?}", function, exception))
+}
+
+/// Converts a `SolverError` into a JavaScript exception
+fn to_js_error(error: SolverError) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+/// Status of PANOC (see `SolverStatus`)
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PanocStatus {
+    /// whether the solver has converged
+    pub converged: bool,
+    /// whether the solver has stopped because it ran out of time
+    pub out_of_time: bool,
+    /// number of iterations
+    pub iterations: usize,
+    /// solve time in milliseconds
+    pub solve_time_ms: f64,
+    /// norm of the fixed-point residual
+    pub norm_fpr: f64,
+    /// cost at the solution
+    pub cost: f64,
+}
+
+impl From<&SolverStatus> for PanocStatus {
+    fn from(status: &SolverStatus) -> Self {
+        PanocStatus {
+            converged: status.has_converged(),
+            out_of_time: status.exit_status() == ExitStatus::NotConvergedOutOfTime,
+            iterations: status.iterations(),
+            solve_time_ms: status.solve_time().as_secs_f64() * 1e3,
+            norm_fpr: status.norm_fpr(),
+            cost: status.cost_value(),
+        }
+    }
+}
+
+/// PANOC solver for JavaScript
+///
+/// The solver owns its cache, so it is allocated once and can be used to
+/// solve many problems of the same dimension, e.g., in an animation loop.
+#[wasm_bindgen]
+pub struct Panoc {
+    cache: PANOCCache,
+    config: PanocConfig,
+    problem_size: usize,
+    u_min: Option<Vec<f64>>,
+    u_max: Option<Vec<f64>>,
+}
+
+#[wasm_bindgen]
+impl Panoc {
+    /// Constructs a new solver
+    ///
+    /// # Arguments
+    ///
+    /// - `problem_size`: number of decision variables
+    /// - `tolerance`: tolerance on the norm of the fixed-point residual
+    /// - `lbfgs_memory`: memory of the L-BFGS buffer
+    ///
+    /// # Errors
+    ///
+    /// Throws if `tolerance` is not positive or if `problem_size` or
+    /// `lbfgs_memory` is zero
+    #[wasm_bindgen(constructor)]
+    pub fn new(problem_size: usize, tolerance: f64, lbfgs_memory: usize) -> Result<Panoc, JsValue> {
+        let cache = PANOCCache::new(problem_size, tolerance, lbfgs_memory).map_err(to_js_error)?;
+        Ok(Panoc {
+            cache,
+            config: PanocConfig::new(),
+            problem_size,
+            u_min: None,
+            u_max: None,
+        })
+    }
+
+    /// Sets the bounds on the decision variables (either may be `undefined`,
+    /// in which case there is no bound)
+    ///
+    /// # Errors
+    ///
+    /// Throws if the bounds do not have the dimension of the problem
+    pub fn set_bounds(
+        &mut self,
+        u_min: Option<Vec<f64>>,
+        u_max: Option<Vec<f64>>,
+    ) -> Result<(), JsValue> {
+        let check_dimension = |name: &'static str, bound: &Option<Vec<f64>>| match bound {
+            Some(bound) if bound.len() != self.problem_size => {
+                Err(to_js_error(SolverError::DimensionMismatch {
+                    name,
+                    expected: self.problem_size,
+                    actual: bound.len(),
+                }))
+            }
+            _ => Ok(()),
+        };
+        check_dimension("u_min", &u_min)?;
+        check_dimension("u_max", &u_max)?;
+        self.u_min = u_min;
+        self.u_max = u_max;
+        Ok(())
+    }
+
+    /// Sets the maximum number of iterations
+    pub fn set_max_iterations(&mut self, max_iter: usize) {
+        self.config.max_iter = max_iter;
+    }
+
+    /// Sets the maximum solution time in milliseconds
+    pub fn set_max_duration_ms(&mut self, max_duration_ms: f64) {
+        self.config.max_duration = Some(std::time::Duration::from_secs_f64(max_duration_ms / 1e3));
+    }
+
+    /// Solves the problem
+    ///
+    /// # Arguments
+    ///
+    /// - `cost`: cost function, `(u) => number`
+    /// - `gradient`: gradient of the cost function, `(u, grad) => void`
+    /// - `u`: initial guess, which is overwritten with the solution
+    ///
+    /// # Errors
+    ///
+    /// Throws if the solver fails (e.g., if `cost` or `gradient` throws or
+    /// returns a non-finite value)
+    pub fn solve(
+        &mut self,
+        cost: &JsFunction,
+        gradient: &JsFunction,
+        u: &mut [f64],
+    ) -> Result<PanocStatus, JsValue> {
+        let constraints: BoxedConstraint = match (self.u_min.as_deref(), self.u_max.as_deref()) {
+            (None, None) => Box::new(NoConstraints::new()),
+            (u_min, u_max) => Box::new(Rectangle::new(u_min, u_max).map_err(to_js_error)?),
+        };
+        let cost = |u: &[f64], c: &mut f64| -> FunctionCallResult {
+            let value = cost
+                .call_cost(&JsValue::NULL, u)
+                .map_err(|e| js_error("cost", e))?;
+            *c = value
+                .as_f64()
+                .ok_or_else(|| SolverError::user("cost did not return a number"))?;
+            Ok(())
+        };
+        let gradient = |u: &[f64], grad: &mut [f64]| -> FunctionCallResult {
+            gradient
+                .call_gradient(&JsValue::NULL, u, grad)
+                .map_err(|e| js_error("gradient", e))?;
+            Ok(())
+        };
+        let problem = Problem::new(&constraints, gradient, cost);
+        let mut panoc = PANOCOptimizer::new(problem, &mut self.cache)
+            .with_config(&self.config)
+            .map_err(to_js_error)?;
+        let status = panoc.solve(u).map_err(to_js_error)?;
+        Ok(PanocStatus::from(&status))
+    }
+}
+
+/* ---------------------------------------------------------------------------- */
+/*          TESTS                                                               */
+/* ---------------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests;
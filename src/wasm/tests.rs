@@ -0,0 +1,37 @@
+use super::*;
+
+// JavaScript functions can only be called on wasm targets, so these tests
+// only cover the parts of the interface which do not call into JavaScript
+
+#[test]
+fn t_wasm_status() {
+    let status = SolverStatus::new(
+        ExitStatus::NotConvergedOutOfTime,
+        12,
+        std::time::Duration::from_micros(2500),
+        1e-3,
+        4.5,
+    );
+    let wasm_status = PanocStatus::from(&status);
+    assert!(!wasm_status.converged);
+    assert!(wasm_status.out_of_time);
+    assert_eq!(12, wasm_status.iterations);
+    unit_test_utils::assert_nearly_equal(2.5, wasm_status.solve_time_ms, 1e-12, 1e-12, "time");
+    assert_eq!(1e-3, wasm_status.norm_fpr);
+    assert_eq!(4.5, wasm_status.cost);
+}
+
+#[test]
+fn t_wasm_panoc_settings() {
+    let mut panoc = Panoc::new(3, 1e-6, 5).ok().unwrap();
+    assert!(panoc.set_bounds(Some(vec![-1.0; 3]), None).is_ok());
+    assert_eq!(Some(vec![-1.0; 3]), panoc.u_min);
+    assert_eq!(None, panoc.u_max);
+    panoc.set_max_iterations(42);
+    panoc.set_max_duration_ms(1.5);
+    assert_eq!(42, panoc.config.max_iter);
+    assert_eq!(
+        Some(std::time::Duration::from_micros(1500)),
+        panoc.config.max_duration
+    );
+}